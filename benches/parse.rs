@@ -0,0 +1,76 @@
+//! Times parsing a full page of nyaa results and building its rows, which
+//! runs on every search. Run with `cargo bench`.
+
+use std::time::{Duration, Instant};
+
+use nyaa::{
+    source::{
+        nyaa_engine::{nyaa_table, parse_html},
+        nyaa_html::{NyaaConfig, NyaaHtmlSource},
+        SourceResponse,
+    },
+    theme::Theme,
+    widget::sort::SelectedSort,
+};
+use reqwest::Url;
+
+static ROWS: usize = 75;
+static RUNS: u32 = 50;
+
+fn page() -> String {
+    let rows: String = (0..ROWS)
+        .map(|i| {
+            format!(
+                r#"<tr class="{}">
+<td><a href="/?c=1_2" title="Anime - English-translated"></a></td>
+<td colspan="2"><a href="/view/{id}#comments" class="comments"></a><a href="/view/{id}" title="[SubsPlease] Show {i} - {ep:02} (1080p) [ABCD{id}].mkv">[SubsPlease] Show {i} - {ep:02} (1080p) [ABCD{id}].mkv</a></td>
+<td class="text-center"><a href="/download/{id}.torrent"></a><a href="magnet:?xt=urn:btih:{id}"></a></td>
+<td class="text-center">1.4 GiB</td>
+<td class="text-center">2024-06-14 18:01</td>
+<td class="text-center">{seeders}</td>
+<td class="text-center">12</td>
+<td class="text-center">5042</td>
+</tr>"#,
+                ["default", "success", "danger"][i % 3],
+                id = 1800000 + i,
+                ep = i % 24 + 1,
+                seeders = i * 7,
+            )
+        })
+        .collect();
+    format!(
+        r#"<html><body><div class="pagination-page-info">Displaying results 1-75 out of 1000 results.</div>
+<table class="torrent-list"><tbody>{}</tbody></table></body></html>"#,
+        rows
+    )
+}
+
+fn time<T>(name: &str, mut f: impl FnMut() -> T) {
+    // Once to warm up
+    f();
+    let start = Instant::now();
+    for _ in 0..RUNS {
+        std::hint::black_box(f());
+    }
+    let each: Duration = start.elapsed() / RUNS;
+    println!("{:<12} {:>8.2} ms", name, each.as_secs_f64() * 1000.0);
+}
+
+fn main() {
+    let html = page();
+    let url = Url::parse("https://nyaa.si/?q=show").unwrap();
+    let config = NyaaConfig::default();
+    let parse = || parse_html::<NyaaHtmlSource>(&html, &url, &config, "nyaa", false, None);
+
+    let Ok(SourceResponse::Results(res)) = parse() else {
+        panic!("Failed to parse the page");
+    };
+    assert_eq!(res.items.len(), ROWS);
+
+    let theme = Theme::default();
+    let sort = SelectedSort::default();
+    time("parse", parse);
+    time("rows", || {
+        nyaa_table(res.items.clone(), &theme, &sort, &None)
+    });
+}
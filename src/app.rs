@@ -1,69 +1,104 @@
 use std::{
+    collections::{HashMap, VecDeque},
     error::Error,
     fmt::Display,
     sync::Arc,
     time::{Duration, Instant},
 };
 
-use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
 use indexmap::IndexMap;
 use ratatui::{
     backend::Backend,
-    layout::{Constraint, Direction, Layout},
+    layout::{Constraint, Direction, Layout, Margin, Rect},
     Frame, Terminal,
 };
 use reqwest::cookie::Jar;
-use tokio::{sync::mpsc, task::AbortHandle};
+use strum::VariantArray as _;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
 
 #[cfg(feature = "captcha")]
 use crate::widget::captcha::CaptchaPopup;
 use crate::{
-    client::{Client, DownloadResult},
+    client::{progress, Client, DownloadOptions, DownloadResult},
     clip,
-    config::{Config, ConfigManager},
-    results::Results,
+    config::{
+        state::{SavedBatch, State},
+        Config, ConfigManager, CONFIG_FILE,
+    },
+    error::NyaaError,
+    results::{history::History, QuickFilter, Results, ResultsView},
     source::{
-        nyaa_html::NyaaHtmlSource, request_client, Item, Source, SourceInfo, SourceResults, Sources,
+        nyaa_html::NyaaHtmlSource, request_client, source_request_client,
+        source_request_client_for, Item, ItemId, Source, SourceInfo, SourceResults, Sources,
+    },
+    sync::{
+        resolve_and_download,
+        tasks::{TaskId, TaskInfo, TaskKind, TaskRegistry},
+        EventSync, SearchQuery,
     },
-    sync::{EventSync, SearchQuery},
     theme::{self, Theme},
-    util::conv::key_to_string,
+    util::{
+        anime::Related,
+        complete::PrefixIndex,
+        conv::key_to_string,
+        crash, log, stats, template, term, update,
+        watch::WatchItem,
+        webhook::{self, WebhookEvent, WebhookMessage},
+    },
     widget::{
         batch::BatchWidget,
         category::CategoryPopup,
         clients::ClientsPopup,
+        columns::ColumnsPopup,
+        combo::{ChordAction, Chords, ComboPopup},
+        confirm::ConfirmPopup,
+        dashboard::DashboardWidget,
+        details::DetailsWidget,
+        error::{ErrorMsg, ErrorPopup},
         filter::FilterPopup,
+        find::FindPopup,
+        focus::{self, Focus, Pane},
         help::HelpPopup,
+        hints::HintBar,
         notifications::NotificationWidget,
         page::PagePopup,
-        results::ResultsWidget,
+        quit::QuitPopup,
+        results::{ResultsWidget, SPINNER_MILLIS},
         search::SearchWidget,
         sort::{SortDir, SortPopup},
         sources::SourcesPopup,
+        stats::StatsPopup,
+        tasks::TasksPopup,
+        template::TemplatePopup,
         themes::ThemePopup,
-        user::UserPopup,
+        user::{UserCheck, UserPopup},
+        watch_later::WatchLaterPopup,
+        whats_new::WhatsNewPopup,
         Widget,
     },
     widgets,
 };
 
-#[cfg(unix)]
-use core::panic;
-#[cfg(unix)]
-use crossterm::event::KeyModifiers;
-
-#[cfg(unix)]
-use crate::util::term;
-
 pub static APP_NAME: &str = "nyaa";
 
 // To ensure that other events will get a chance to be received
 static ANIMATE_SLEEP_MILLIS: u64 = 5;
+// Oldest batch changes are forgotten past this
+static MAX_UNDO: usize = 50;
+// Largest count accepted before a motion, like 5j
+static MAX_COUNT: usize = 999;
+// Searches remembered for the dashboard
+static MAX_RECENT_SEARCHES: usize = 10;
+static MAX_RECENT_USERS: usize = 20;
 
 #[derive(PartialEq, Clone)]
 pub enum LoadType {
     Sourcing,
     Searching,
+    /// Searching this many pages at once
+    DeepSearching(usize),
     SolvingCaptcha(String),
     Sorting,
     Filtering,
@@ -81,32 +116,56 @@ pub enum Mode {
     Category,
     Sort(SortDir),
     Batch,
+    Details,
+    Find,
     Filter,
+    Columns,
+    WatchLater,
     Theme,
     Sources,
     Clients,
     Page,
     User,
+    Template,
+    Stats,
+    Tasks,
+    WhatsNew,
     Help,
+    Confirm(LoadType),
+    Quit,
     Captcha,
 }
 
 widgets! {
     Widgets;
     batch: [Mode::Batch] => BatchWidget,
+    details: [Mode::Details] => DetailsWidget,
     search: [Mode::Search] => SearchWidget,
     results: [Mode::Normal] => ResultsWidget,
+    dashboard: DashboardWidget,
     notification: NotificationWidget,
+    error: ErrorPopup,
+    hints: HintBar,
+    combo: ComboPopup,
     [popups]: {
         category: [Mode::Category]  => CategoryPopup,
         sort: [Mode::Sort(_)]  => SortPopup,
         filter: [Mode::Filter]  => FilterPopup,
+        columns: [Mode::Columns]  => ColumnsPopup,
+        watch_later: [Mode::WatchLater]  => WatchLaterPopup,
         theme: [Mode::Theme]  => ThemePopup,
         sources: [Mode::Sources]  => SourcesPopup,
         clients: [Mode::Clients]  => ClientsPopup,
         page: [Mode::Page]  => PagePopup,
         user: [Mode::User] => UserPopup,
+        template: [Mode::Template] => TemplatePopup,
+        stats: [Mode::Stats] => StatsPopup,
+        tasks: [Mode::Tasks] => TasksPopup,
+        whats_new: [Mode::WhatsNew] => WhatsNewPopup,
         help: [Mode::Help] => HelpPopup,
+        find: [Mode::Find] => FindPopup,
+        confirm: [Mode::Confirm(_)] => ConfirmPopup,
+        quit: [Mode::Quit] => QuitPopup,
         #[cfg(feature = "captcha")]
         captcha: [Mode::Captcha] => CaptchaPopup,
     }
@@ -117,6 +176,7 @@ impl Display for LoadType {
         let s = match self {
             LoadType::Sourcing => "Sourcing",
             LoadType::Searching => "Searching",
+            LoadType::DeepSearching(_) => "Deep Searching",
             LoadType::SolvingCaptcha(_) => "Solving",
             LoadType::Sorting => "Sorting",
             LoadType::Filtering => "Filtering",
@@ -133,17 +193,27 @@ impl Display for Mode {
         let s = match self {
             Mode::Normal | Mode::KeyCombo(_) => "Normal",
             Mode::Batch => "Batch",
+            Mode::Details => "Details",
+            Mode::Find => "Find",
             Mode::Search => "Search",
             Mode::Category => "Category",
             Mode::Sort(_) => "Sort",
             Mode::Filter => "Filter",
+            Mode::Columns => "Columns",
+            Mode::WatchLater => "Watch Later",
             Mode::Theme => "Theme",
             Mode::Sources => "Sources",
             Mode::Clients => "Clients",
             Mode::Loading(_) => "Loading",
             Mode::Page => "Page",
             Mode::User => "User",
+            Mode::Template => "Template",
+            Mode::Stats => "Stats",
+            Mode::Tasks => "Tasks",
+            Mode::WhatsNew => "What's New",
             Mode::Help => "Help",
+            Mode::Confirm(_) => "Confirm",
+            Mode::Quit => "Quit",
             Mode::Captcha => "Captcha",
         }
         .to_owned();
@@ -160,10 +230,14 @@ pub struct App {
 pub struct Context {
     pub mode: Mode,
     pub load_type: Option<LoadType>,
+    /// When the current load started, for showing elapsed time
+    pub load_start: Option<Instant>,
     pub themes: IndexMap<String, Theme>,
     pub src_info: SourceInfo,
     pub theme: Theme,
     pub config: Config,
+    /// What's kept between sessions apart from the config
+    pub state: State,
     pub page: usize,
     pub user: Option<String>,
     pub src: Sources,
@@ -172,34 +246,641 @@ pub struct Context {
     pub last_key: String,
     pub results: Results,
     pub deltatime: f64,
-    errors: Vec<String>,
+    /// Whether any search has finished, after which results replace the dashboard
+    pub searched: bool,
+    /// Downloads which have been started but not finished
+    pub downloads: usize,
+    /// Tasks running when last drawn, listed in the tasks popup
+    pub tasks: Vec<TaskInfo>,
+    /// Showing the source's front page instead of the results of the search
+    pub browsing: bool,
+    /// Whether the details pane was drawn, which depends on the terminal width
+    pub three_pane: bool,
+    /// Whether the compact layout for narrow terminals is used
+    pub narrow: bool,
+    /// Titles to jump between with n/N, instead of changing page
+    pub find: Option<String>,
+    /// Which pane has focus, and where each pane is
+    pub focus: Focus,
+    /// Value last given to each variable of a query template
+    pub template_values: HashMap<String, String>,
+    /// Words from result titles and searches, completed with Tab when searching
+    pub completions: PrefixIndex,
+    /// Events waiting to be posted to the webhooks listening for them
+    webhook_queue: Vec<WebhookMessage>,
+    /// Filters hiding loaded results which don't match, kept between searches
+    pub quick_filters: Vec<QuickFilter>,
+    /// Register of the macro being recorded
+    pub recording: Option<char>,
+    /// The batch before each change, with a description of the change
+    batch_undo: Vec<(String, Vec<Item>)>,
+    /// Results left behind by other searches, to go back and forward to
+    pub history: History,
+    /// Save path, label and paused flag chosen for the next download
+    pub download_options: Option<DownloadOptions>,
+    /// Last page to add to the batch, and how many items have been added so far
+    batch_pages: Option<(usize, usize)>,
+    /// Lowest id the next batch download can have
+    next_batch: usize,
+    /// Mode a key combo was started from, which gets focus back once it ends
+    combo_mode: Mode,
+    /// When the pending key combo started
+    combo_start: Option<Instant>,
+    /// Key sequences which start with `y`, `b` or the leader key, from the
+    /// config
+    pub chords: Chords,
+    registers: HashMap<char, Vec<Event>>,
+    last_register: Option<char>,
+    /// Events of a macro waiting to be replayed
+    replay: VecDeque<Event>,
+    /// Whether the last load from each source succeeded
+    health: Vec<(Sources, bool)>,
+    /// Result of the last connection test of each client
+    client_status: Vec<(Client, Result<(), String>)>,
+    errors: Vec<ErrorMsg>,
     notifications: Vec<String>,
     failed_config_load: bool,
+    /// Whether anything shown has changed since the last frame was drawn
+    dirty: bool,
     should_quit: bool,
+    should_quit_when_done: bool,
+    should_cancel_downloads: bool,
+    should_cancel_task: Option<TaskId>,
     should_dismiss_notifications: bool,
+    should_cancel_load: bool,
+    should_live_search: bool,
     should_save_config: bool,
+    should_save_state: bool,
+    should_edit_source: Option<Sources>,
+    should_test_clients: bool,
+    should_refresh_watch_later: bool,
+    should_check_user: Option<String>,
 }
 
 impl Context {
     pub fn show_error<S: Display>(&mut self, error: S) {
-        self.errors.push(error.to_string());
+        self.errors.push(ErrorMsg::new(error));
+    }
+
+    /// Show an error along with its sources, which can be viewed with the
+    /// details toggle in the error popup
+    pub fn show_error_chain(&mut self, error: &(dyn Error + 'static)) {
+        self.errors.push(ErrorMsg::from_error(error));
+    }
+
+    /// Translate an English string into the configured locale
+    pub fn tr<'a>(&self, msg: &'a str) -> &'a str {
+        self.config.locale.tr(msg)
     }
 
     pub fn notify<S: Display>(&mut self, msg: S) {
         self.notifications.push(msg.to_string());
     }
 
+    /// Draw the next frame, which is skipped while nothing shown changes
+    pub fn redraw(&mut self) {
+        self.dirty = true;
+    }
+
     pub fn dismiss_notifications(&mut self) {
         self.should_dismiss_notifications = true;
     }
 
+    /// Stop waiting for the current search, keeping the previous results
+    pub fn cancel_load(&mut self) {
+        self.should_cancel_load = true;
+    }
+
+    /// Search once typing has paused, replacing any search already queued
+    pub fn live_search(&mut self) {
+        self.should_live_search = true;
+    }
+
     pub fn save_config(&mut self) -> Result<(), Box<dyn Error>> {
         self.should_save_config = true;
         Ok(())
     }
 
+    /// Save [`Context::state`], even if the config isn't saved on change
+    pub fn save_state(&mut self) {
+        self.should_save_state = true;
+    }
+
+    /// Quit, or ask what to do with the downloads still running first
     pub fn quit(&mut self) {
-        self.should_quit = true;
+        match self.downloads {
+            0 => self.should_quit = true,
+            _ => self.mode = Mode::Quit,
+        }
+    }
+
+    /// Quit once every download has finished, or stop waiting to
+    pub fn quit_when_done(&mut self, wait: bool) {
+        self.should_quit_when_done = wait;
+    }
+
+    pub fn is_waiting_to_quit(&self) -> bool {
+        self.should_quit_when_done
+    }
+
+    /// Stop every download still running, then quit
+    pub fn cancel_downloads_and_quit(&mut self) {
+        self.should_cancel_downloads = true;
+    }
+
+    /// Stop the task `id`, listed in the tasks popup
+    pub fn cancel_task(&mut self, id: TaskId) {
+        self.should_cancel_task = Some(id);
+    }
+
+    /// Open the config file to change the settings of `src`
+    pub fn edit_source(&mut self, src: Sources) {
+        self.should_edit_source = Some(src);
+    }
+
+    /// Whether the last load from `src` succeeded, if it has been used
+    pub fn health(&self, src: Sources) -> Option<bool> {
+        self.health
+            .iter()
+            .find(|(s, _)| *s == src)
+            .map(|(_, ok)| *ok)
+    }
+
+    /// Test whether each client with an API can be reached, replacing the
+    /// results of the last test
+    pub fn test_clients(&mut self) {
+        self.client_status.clear();
+        self.should_test_clients = true;
+    }
+
+    /// Add `item` to the watch later list, or remove it if it's already there
+    pub fn toggle_watch_later(&mut self, item: &Item) {
+        let id = item.id.to_string();
+        let list = &mut self.state.watch_later;
+        match list.iter().position(|w| w.id == id) {
+            Some(idx) => {
+                list.remove(idx);
+                self.notify(format!("Removed \"{}\" from watch later", item.title));
+            }
+            None => {
+                list.push(WatchItem::new(item, self.src));
+                self.notify(format!("Added \"{}\" to watch later", item.title));
+            }
+        }
+        self.save_state();
+    }
+
+    /// Move the watch later item at `idx` into the batch, or every item if
+    /// `idx` is `None`. Items already in the batch are only removed from the
+    /// list.
+    pub fn watch_later_to_batch(&mut self, idx: Option<usize>) {
+        let moved: Vec<WatchItem> = match idx {
+            Some(idx) if idx < self.state.watch_later.len() => {
+                vec![self.state.watch_later.remove(idx)]
+            }
+            Some(_) => return,
+            None => std::mem::take(&mut self.state.watch_later),
+        };
+        if moved.is_empty() {
+            return;
+        }
+        self.save_batch(format!("adding {} watch later items", moved.len()));
+        let added = self.add_to_batch(moved.iter().map(WatchItem::to_item).collect());
+        self.notify(format!("Moved {} torrents to the batch", added));
+        self.save_state();
+    }
+
+    /// Save the items of a batch download which is starting, so it can be
+    /// resumed after a restart if it doesn't finish. Items are taken out of
+    /// the batches saved before, so each is only resumed once. Returns the id
+    /// of the new batch.
+    pub fn save_unfinished_batch(&mut self, items: &[Item]) -> usize {
+        let id = self
+            .state
+            .batches
+            .iter()
+            .map(|b| b.id + 1)
+            .fold(self.next_batch, usize::max);
+        self.next_batch = id + 1;
+        let ids: Vec<String> = items.iter().map(|i| i.id.to_string()).collect();
+        for saved in self.state.batches.iter_mut() {
+            saved.items.retain(|w| !ids.contains(&w.id));
+        }
+        self.state.batches.retain(|b| !b.items.is_empty());
+        self.state.batches.push(SavedBatch {
+            id,
+            items: items.iter().map(|i| WatchItem::new(i, self.src)).collect(),
+        });
+        self.save_state();
+        id
+    }
+
+    /// Forget the items the batch `id` sent, and the batch once nothing is
+    /// left to send
+    pub fn forget_sent(&mut self, id: usize, sent: &[ItemId]) {
+        let sent: Vec<String> = sent.iter().map(ItemId::to_string).collect();
+        if let Some(saved) = self.state.batches.iter_mut().find(|b| b.id == id) {
+            saved.items.retain(|w| !sent.contains(&w.id));
+        }
+        self.state.batches.retain(|b| !b.items.is_empty());
+        self.save_state();
+    }
+
+    /// Put what's left of unfinished batch downloads back in the batch
+    pub fn restore_unfinished_batches(&mut self) {
+        let items: Vec<Item> = self
+            .state
+            .batches
+            .iter()
+            .flat_map(|b| b.items.iter().map(WatchItem::to_item))
+            .collect();
+        match self.add_to_batch(items) {
+            0 => {}
+            n => self.notify(format!(
+                "Put {} torrents of an unfinished batch download back in the batch, press Ctrl-A to resume",
+                n
+            )),
+        }
+    }
+
+    /// Forget saved items which were taken out of the batch, so they aren't
+    /// put back next time
+    pub fn prune_unfinished_batches(&mut self) {
+        let before = self.state.batches.clone();
+        for saved in self.state.batches.iter_mut() {
+            saved
+                .items
+                .retain(|w| self.batch.iter().any(|i| i.id.to_string() == w.id));
+        }
+        self.state.batches.retain(|b| !b.items.is_empty());
+        if self.state.batches != before {
+            self.save_state();
+        }
+    }
+
+    /// Search for each watch later item again to update its seeders
+    pub fn refresh_watch_later(&mut self) {
+        match self.state.watch_later.is_empty() {
+            true => self.notify("Nothing to refresh"),
+            false => self.should_refresh_watch_later = true,
+        }
+    }
+
+    /// Put the seeders, leechers and downloads of refreshed items back in the
+    /// watch later list
+    pub fn update_watch_later(&mut self, refreshed: Vec<WatchItem>) {
+        for new in refreshed.iter() {
+            if let Some(w) = self.state.watch_later.iter_mut().find(|w| w.id == new.id) {
+                w.seeders = new.seeders;
+                w.leechers = new.leechers;
+                w.downloads = new.downloads;
+            }
+        }
+        self.notify(format!("Refreshed {} watch later items", refreshed.len()));
+        self.save_state();
+    }
+
+    /// Result of the last connection test of `client`, if it has finished
+    pub fn client_status(&self, client: Client) -> Option<&Result<(), String>> {
+        self.client_status
+            .iter()
+            .find(|(c, _)| *c == client)
+            .map(|(_, res)| res)
+    }
+
+    fn set_health(&mut self, src: Sources, ok: bool) {
+        match self.health.iter_mut().find(|(s, _)| *s == src) {
+            Some(h) => h.1 = ok,
+            None => self.health.push((src, ok)),
+        }
+    }
+
+    /// Remember the batch as it is before `change`, so it can be undone
+    pub fn save_batch<S: Into<String>>(&mut self, change: S) {
+        if self.batch_undo.len() >= MAX_UNDO {
+            self.batch_undo.remove(0);
+        }
+        self.batch_undo.push((change.into(), self.batch.clone()));
+    }
+
+    /// Post `message` to every webhook listening for `event`
+    pub fn webhook<S: Into<String>>(
+        &mut self,
+        event: WebhookEvent,
+        message: S,
+        titles: Vec<String>,
+    ) {
+        if self.config.webhooks.iter().any(|h| h.wants(event)) {
+            self.webhook_queue.push(WebhookMessage {
+                event,
+                message: message.into(),
+                titles,
+            });
+        }
+    }
+
+    /// Turn the quick filter `filter` on or off, and only show the results
+    /// matching every filter still on
+    pub fn toggle_quick_filter(&mut self, filter: QuickFilter) {
+        let on = match self.quick_filters.iter().position(|f| *f == filter) {
+            Some(idx) => {
+                self.quick_filters.remove(idx);
+                false
+            }
+            None => {
+                self.quick_filters.push(filter);
+                true
+            }
+        };
+        match filter {
+            QuickFilter::NoRemakes => self.config.hide_remakes = on,
+            QuickFilter::TrustedOnly => self.config.trusted_only = on,
+            _ => {}
+        }
+        if filter.sticky() {
+            if let Err(e) = self.save_config() {
+                self.show_error(e);
+            }
+        }
+        self.apply_quick_filters();
+    }
+
+    /// Show or hide a column of the current source's results, saving it to
+    /// the source's `columns` config
+    pub fn toggle_column(&mut self, idx: usize) {
+        let Some((_, shown)) = self
+            .src
+            .columns(&self.config.sources)
+            .and_then(|c| c.get(idx).copied())
+        else {
+            return;
+        };
+        self.src.set_column(&mut self.config.sources, idx, !shown);
+        if let Err(e) = self.save_config() {
+            self.show_error(e);
+        }
+        self.format_results();
+    }
+
+    /// Star `item`, or unstar it if already starred. Stars are kept in the
+    /// state file, separate from the batch.
+    pub fn toggle_star(&mut self, item: &Item) {
+        let id = item.id.to_string();
+        match self.results.starred.remove(&id) {
+            true => self.state.starred.retain(|s| *s != id),
+            false => {
+                self.results.starred.insert(id.clone());
+                self.state.starred.push(id);
+            }
+        }
+        self.save_state();
+        if self.quick_filters.contains(&QuickFilter::Starred) {
+            self.apply_quick_filters();
+        }
+    }
+
+    fn apply_quick_filters(&mut self) {
+        self.results.quick_filter(&self.quick_filters);
+        self.format_results();
+    }
+
+    fn format_results(&mut self) {
+        self.results.table = self.src.format_table(
+            &self.results.response.items,
+            &self.results.search,
+            &self.config.sources,
+            &self.theme,
+        );
+    }
+
+    /// Remember a search for the dashboard, moving it to the front if it was
+    /// already there
+    pub fn remember_search(&mut self, query: &str) {
+        let query = query.trim();
+        if query.is_empty() {
+            return;
+        }
+        self.completions.add(query);
+        let recent = &mut self.state.recent_searches;
+        recent.retain(|q| q != query);
+        recent.insert(0, query.to_owned());
+        recent.truncate(MAX_RECENT_SEARCHES);
+        self.save_state();
+    }
+
+    /// Remember an uploader searched for, moving it to the front if it was
+    /// already there
+    pub fn remember_user(&mut self, user: &str) {
+        let recent = &mut self.state.recent_users;
+        recent.retain(|u| u != user);
+        recent.insert(0, user.to_owned());
+        recent.truncate(MAX_RECENT_USERS);
+        self.save_state();
+    }
+
+    /// Search the source for `user`'s torrents in the background, to say in
+    /// the user popup whether there are any
+    pub fn check_user(&mut self, user: String) {
+        self.should_check_user = Some(user);
+    }
+
+    /// Switch between the front page of the source and the results of the
+    /// search, keeping the search for when it is switched back
+    pub fn toggle_browse(&mut self) {
+        self.browsing = !self.browsing;
+        self.page = 1;
+        self.mode = Mode::Loading(LoadType::Searching);
+        match self.browsing {
+            true => self.notify("Browsing the front page"),
+            false => self.notify("Back to search results"),
+        }
+    }
+
+    /// Switch between the bordered and minimal layouts, keeping the choice in
+    /// the config
+    pub fn toggle_minimal(&mut self) {
+        self.config.layout.minimal = !self.config.layout.minimal;
+        let _ = self.save_config();
+        match self.config.layout.minimal {
+            true => self.notify("Switched to the minimal layout"),
+            false => self.notify("Switched to the bordered layout"),
+        }
+    }
+
+    /// Put the batch back to how it was before the last change
+    pub fn undo_batch(&mut self) {
+        match self.batch_undo.pop() {
+            Some((change, batch)) => {
+                self.batch = batch;
+                self.notify(format!("Undid {}", change));
+            }
+            None => self.notify("Nothing to undo"),
+        }
+    }
+
+    /// Add each item not already in the batch and without a dead link,
+    /// returning how many were added
+    pub fn add_to_batch(&mut self, items: Vec<Item>) -> usize {
+        let len = self.batch.len();
+        for item in items {
+            if !self.batch.iter().any(|b| b.id == item.id) && !self.results.dead.contains(&item.id)
+            {
+                self.batch.push(item);
+            }
+        }
+        self.batch.len() - len
+    }
+
+    /// Add every item on the page that isn't in the batch, and remove the rest
+    pub fn invert_batch(&mut self) {
+        for item in self.results.response.items.iter() {
+            match self.batch.iter().position(|b| b.id == item.id) {
+                Some(p) => {
+                    self.batch.remove(p);
+                }
+                None if self.results.dead.contains(&item.id) => {}
+                None => self.batch.push(item.to_owned()),
+            }
+        }
+    }
+
+    /// Search the first `deep_search_pages` pages at once, then page through
+    /// them without loading again
+    pub fn deep_search(&mut self) {
+        self.page = 1;
+        self.mode = Mode::Loading(LoadType::DeepSearching(self.config.deep_search_pages));
+    }
+
+    /// Go to `page` of the results, loading it unless a deep search found it
+    pub fn goto_page(&mut self, page: usize) {
+        self.page = page;
+        match self.results.show_page(page) {
+            true => {
+                self.history.set_page(page);
+                self.apply_quick_filters();
+            }
+            false => self.mode = Mode::Loading(LoadType::Searching),
+        }
+    }
+
+    /// Search each page from the first, adding every result to the batch
+    pub fn batch_all_pages(&mut self) {
+        // Every page of a deep search is already loaded
+        if let Some(items) = self.results.deep.clone() {
+            let added = self.add_to_batch(items);
+            return self.notify(format!("Added {} torrents to the batch", added));
+        }
+        let last = self
+            .results
+            .response
+            .last_page
+            .min(self.config.batch_page_limit);
+        if last == 0 {
+            return self.show_error("Failed to add pages:\nThere are no results");
+        }
+        self.batch_pages = Some((last, 0));
+        self.page = 1;
+        self.mode = Mode::Loading(LoadType::Searching);
+    }
+
+    /// When to list the keys which can finish the pending combo, if it has
+    /// any
+    pub fn combo_popup_at(&self) -> Option<Instant> {
+        match &self.mode {
+            Mode::KeyCombo(keys)
+                if self.config.combo_popup && self.chords.continuations(keys).is_some() =>
+            {
+                let delay = Duration::from_millis(self.config.combo_popup_delay);
+                self.combo_start.map(|start| start + delay)
+            }
+            _ => None,
+        }
+    }
+
+    /// Title for the terminal window, like `nyaa — 'frieren' p2`
+    pub fn window_title(&self) -> String {
+        if !self.searched {
+            return APP_NAME.to_owned();
+        }
+        let search = match self.results.search.query.as_str() {
+            q if self.browsing || q.is_empty() => "front page".to_owned(),
+            q => format!("'{}'", q),
+        };
+        let mode = match self.focused_mode() {
+            Mode::Normal | Mode::Loading(_) => String::new(),
+            mode => format!(" [{}]", mode),
+        };
+        format!("{} — {} p{}{}", APP_NAME, search, self.page, mode)
+    }
+
+    /// Focus `pane`, handling its keys from now on
+    pub fn focus_pane(&mut self, pane: Pane) {
+        self.focus.set(pane);
+        self.mode = pane.mode();
+        self.redraw();
+    }
+
+    /// Whether `pane` has focus, and no popup is open over it
+    pub fn has_focus(&self, pane: Pane) -> bool {
+        Pane::from_mode(self.focused_mode()) == Some(pane)
+    }
+
+    /// The mode which has focus, treating key combos as the mode they started in
+    pub fn focused_mode(&self) -> &Mode {
+        match &self.mode {
+            Mode::KeyCombo(_) => &self.combo_mode,
+            mode => mode,
+        }
+    }
+
+    /// Start recording key presses into `register`, replacing what it held
+    pub fn start_recording(&mut self, register: char) {
+        self.registers.insert(register, vec![]);
+        self.recording = Some(register);
+        self.notify(format!("Recording @{}", register));
+    }
+
+    /// Stop recording, leaving out the key press which stopped it
+    pub fn stop_recording(&mut self) {
+        let Some(register) = self.recording.take() else {
+            return;
+        };
+        let len = self.registers.get_mut(&register).map(|events| {
+            events.pop();
+            events.len()
+        });
+        self.notify(format!(
+            "Recorded {} keys to @{}",
+            len.unwrap_or(0),
+            register
+        ));
+    }
+
+    fn record(&mut self, evt: &Event) {
+        if let (Some(register), Event::Key(_)) = (self.recording, evt) {
+            if let Some(events) = self.registers.get_mut(&register) {
+                events.push(evt.to_owned());
+            }
+        }
+    }
+
+    /// Queue the key presses in `register`, or the last replayed register if
+    /// it is `@`
+    pub fn replay(&mut self, register: char) {
+        let register = match register {
+            '@' => match self.last_register {
+                Some(r) => r,
+                None => return self.show_error("No macro has been replayed yet"),
+            },
+            r => r,
+        };
+        match self.registers.get(&register) {
+            Some(events) if !events.is_empty() => {
+                self.replay.extend(events.iter().cloned());
+                self.last_register = Some(register);
+            }
+            _ => self.show_error(format!("Register @{} is empty", register)),
+        }
     }
 }
 
@@ -208,10 +889,12 @@ impl Default for Context {
         Context {
             mode: Mode::Loading(LoadType::Searching),
             load_type: None,
+            load_start: None,
             themes: theme::default_themes(),
             src_info: NyaaHtmlSource::info(),
             theme: Theme::default(),
             config: Config::default(),
+            state: State::default(),
             errors: Vec::new(),
             notifications: Vec::new(),
             page: 1,
@@ -222,15 +905,140 @@ impl Default for Context {
             last_key: "".to_owned(),
             results: Results::default(),
             deltatime: 0.0,
+            searched: false,
+            downloads: 0,
+            tasks: vec![],
+            browsing: false,
+            three_pane: false,
+            narrow: false,
+            find: None,
+            focus: Focus::default(),
+            template_values: HashMap::new(),
+            completions: PrefixIndex::default(),
+            webhook_queue: vec![],
+            quick_filters: vec![],
+            recording: None,
+            combo_mode: Mode::Normal,
+            combo_start: None,
+            chords: Chords::default(),
+            batch_undo: vec![],
+            history: History::default(),
+            download_options: None,
+            batch_pages: None,
+            next_batch: 0,
+            registers: HashMap::new(),
+            last_register: None,
+            replay: VecDeque::new(),
+            health: vec![],
+            client_status: vec![],
             failed_config_load: true,
+            dirty: true,
             should_quit: false,
+            should_quit_when_done: false,
+            should_cancel_downloads: false,
+            should_cancel_task: None,
             should_dismiss_notifications: false,
+            should_cancel_load: false,
+            should_live_search: false,
             should_save_config: false,
+            should_save_state: false,
+            should_edit_source: None,
+            should_test_clients: false,
+            should_refresh_watch_later: false,
+            should_check_user: None,
         }
     }
 }
 
+/// Whether `evt` goes back (`Some(true)`) or forward (`Some(false)`) through
+/// the history. Most terminals send Ctrl-i as Tab, so Ctrl-n goes forward too.
+fn history_key(evt: &Event) -> Option<bool> {
+    let Event::Key(KeyEvent {
+        code,
+        modifiers,
+        kind: KeyEventKind::Press,
+        ..
+    }) = evt
+    else {
+        return None;
+    };
+    match (code, *modifiers) {
+        (KeyCode::Backspace, _) | (KeyCode::Char('o'), KeyModifiers::CONTROL) => Some(true),
+        (KeyCode::Char('i' | 'n'), KeyModifiers::CONTROL) => Some(false),
+        _ => None,
+    }
+}
+
+/// Whether handling `evt` can change what is drawn. Moving the mouse sends an
+/// event for each cell crossed, and only clicks do anything.
+fn changes_frame(evt: &Event) -> bool {
+    match evt {
+        Event::Key(KeyEvent { kind, .. }) => *kind != KeyEventKind::Release,
+        Event::Mouse(_) => focus::click(evt).is_some(),
+        Event::FocusGained | Event::FocusLost => false,
+        _ => true,
+    }
+}
+
+/// Whether `evt` acts on what was last drawn, so can't be handled along with
+/// the events before it
+fn needs_frame(evt: &Event) -> bool {
+    matches!(evt, Event::Resize(..))
+        || focus::click(evt).is_some()
+        || focus::cycle_key(evt).is_some()
+}
+
+/// Rows moved by `evt` if it only moves the selection up or down, so a run of
+/// it can be handled as one move
+fn motion(evt: &Event) -> Option<isize> {
+    let Event::Key(KeyEvent {
+        code,
+        modifiers: KeyModifiers::NONE,
+        kind: KeyEventKind::Press,
+        ..
+    }) = evt
+    else {
+        return None;
+    };
+    match code {
+        KeyCode::Char('j') | KeyCode::Down => Some(1),
+        KeyCode::Char('k') | KeyCode::Up => Some(-1),
+        _ => None,
+    }
+}
+
 impl App {
+    /// The keybinds of every mode, as shown in the help popup
+    pub fn keybinds(&self) -> Vec<(String, Vec<(&'static str, &'static str)>)> {
+        [
+            Mode::Normal,
+            Mode::Batch,
+            Mode::Details,
+            Mode::Find,
+            Mode::Search,
+            Mode::Category,
+            Mode::Sort(SortDir::Desc),
+            Mode::Filter,
+            Mode::Columns,
+            Mode::WatchLater,
+            Mode::Theme,
+            Mode::Sources,
+            Mode::Clients,
+            Mode::Page,
+            Mode::User,
+            Mode::Template,
+            Mode::Stats,
+            Mode::Tasks,
+            Mode::WhatsNew,
+            Mode::Confirm(LoadType::Batching),
+            Mode::Quit,
+            Mode::Captcha,
+        ]
+        .iter()
+        .filter_map(|m| self.widgets.get_help(m).map(|h| (m.to_string(), h)))
+        .collect()
+    }
+
     pub async fn run_app<B: Backend, S: EventSync + Clone, C: ConfigManager, const TEST: bool>(
         &mut self,
         terminal: &mut Terminal<B>,
@@ -240,17 +1048,51 @@ impl App {
 
         let timer = tokio::time::sleep(Duration::from_millis(ANIMATE_SLEEP_MILLIS));
         tokio::pin!(timer);
+        let debounce = tokio::time::sleep(Duration::ZERO);
+        tokio::pin!(debounce);
+        let combo_timer = tokio::time::sleep(Duration::ZERO);
+        tokio::pin!(combo_timer);
+        let mut live_search_pending = false;
+        // Mode to go back to once a live search has started
+        let mut live_search_mode: Option<Mode> = None;
 
-        let (tx_res, mut rx_res) =
-            mpsc::channel::<Result<SourceResults, Box<dyn Error + Send + Sync>>>(32);
+        let (tx_res, mut rx_res) = mpsc::channel::<Result<SourceResults, NyaaError>>(32);
         let (tx_evt, mut rx_evt) = mpsc::channel::<Event>(100);
         let (tx_dl, mut rx_dl) = mpsc::channel::<DownloadResult>(100);
+        let (tx_val, mut rx_val) = mpsc::channel::<Vec<ItemId>>(32);
+        let (tx_test, mut rx_test) = mpsc::channel::<(Client, Result<(), String>)>(32);
+        let (tx_update, mut rx_update) = mpsc::channel::<String>(1);
+        let (tx_watch, mut rx_watch) = mpsc::channel::<Result<Vec<WatchItem>, NyaaError>>(32);
+        let (tx_user, mut rx_user) = mpsc::channel::<(Sources, String, UserCheck)>(32);
 
-        tokio::task::spawn(sync.clone().read_event_loop(tx_evt));
+        // Dropped when the app stops, however it stops, which stops every
+        // task still running
+        let mut tasks = TaskRegistry::default();
 
+        let _ = tasks.spawn(
+            TaskKind::Events,
+            "Read terminal events",
+            sync.clone().read_event_loop(tx_evt),
+        );
+
+        match C::migrate() {
+            Ok(changes) => changes.into_iter().for_each(|c| ctx.notify(c)),
+            Err(e) => ctx.show_error(format!("Failed to upgrade config:\n{}", e)),
+        }
+        match C::load_state() {
+            Ok(state) => {
+                ctx.state = state;
+                ctx.restore_unfinished_batches();
+            }
+            Err(e) => ctx.show_error(format!("Failed to load state:\n{}", e)),
+        }
         match C::load() {
             Ok(config) => {
                 ctx.failed_config_load = false;
+                let report = C::check();
+                if !report.is_empty() {
+                    ctx.show_error(report);
+                }
                 if let Err(e) = config.apply::<C>(ctx, &mut self.widgets) {
                     ctx.show_error(e);
                 } else if let Err(e) = ctx.save_config() {
@@ -264,46 +1106,317 @@ impl App {
                 }
             }
         }
+        // A preset may have been searched on startup
+        self.apply_user_search(ctx);
+        if let Ok(dir) = C::path() {
+            crash::set_log_path(dir.join(log::LOG_FILE));
+        }
+        if ctx.config.mouse && !TEST {
+            if let Err(e) = term::set_mouse(true) {
+                ctx.show_error(format!("Failed to turn on mouse support:\n{}", e));
+            }
+        }
 
         let jar = Arc::new(Jar::default());
-        let client = request_client(&jar, ctx)?;
-        let mut last_load_abort: Option<AbortHandle> = None;
+        if ctx.config.check_for_updates && !TEST {
+            if let Ok(client) = request_client(&jar, ctx) {
+                // Failing to check isn't worth an error
+                let _ = tasks.spawn(TaskKind::Update, "Check for updates", async move {
+                    if let Ok(Some(version)) = update::check(&client).await {
+                        let _ = tx_update
+                            .send(format!(
+                                "nyaa v{} is available, update with `nyaa self-update`",
+                                version
+                            ))
+                            .await;
+                    }
+                });
+            }
+        }
         let mut last_time: Option<Instant> = None;
+        let mut spinner_frame = 0;
+        // Search of the results being shown, restored if a load is cancelled
+        let mut loaded = self.current_search(ctx);
+        let mut loaded_src = ctx.src;
+        // Last drawn progress of the batch being downloaded
+        let mut batch_progress = None;
+        let mut window_title: Option<String> = None;
+        // Event which has to wait for the next frame, like a click
+        let mut deferred: Option<Event> = None;
 
         while !ctx.should_quit {
+            // Wait for the first results before showing what's new
+            if ctx.mode == Mode::Normal && self.widgets.whats_new.take_pending() {
+                ctx.mode = Mode::WhatsNew;
+                ctx.redraw();
+            }
             if ctx.should_save_config && ctx.config.save_config_on_change {
                 if let Err(e) = C::store(&ctx.config) {
                     ctx.show_error(e);
                 }
             }
+            ctx.should_save_config = false;
+            if ctx.should_save_state {
+                ctx.should_save_state = false;
+                if let Err(e) = C::store_state(&ctx.state) {
+                    ctx.show_error(e);
+                }
+            }
             if !ctx.notifications.is_empty() {
-                ctx.notifications
-                    .clone()
-                    .into_iter()
-                    .for_each(|n| self.widgets.notification.add_notification(n));
+                ctx.redraw();
+                if self.widgets.notification.is_quiet() {
+                    if let Ok(dir) = C::path() {
+                        let _ = log::append(dir.join(log::LOG_FILE), &ctx.notifications.join("\n"));
+                    }
+                } else {
+                    ctx.notifications
+                        .clone()
+                        .into_iter()
+                        .for_each(|n| self.widgets.notification.add_notification(n));
+                }
                 ctx.notifications.clear();
             }
             if !ctx.errors.is_empty() {
-                ctx.errors
-                    .clone()
-                    .into_iter()
-                    .for_each(|n| self.widgets.notification.add_error(n));
-                ctx.errors.clear();
+                ctx.redraw();
+                for e in ctx.errors.drain(..).collect::<Vec<ErrorMsg>>() {
+                    ctx.webhook(WebhookEvent::Error, e.msg.clone(), vec![]);
+                    self.widgets.error.add(e);
+                }
+            }
+            if !ctx.webhook_queue.is_empty() {
+                let msgs = std::mem::take(&mut ctx.webhook_queue);
+                let log_dir = C::path().ok();
+                match request_client(&jar, ctx) {
+                    Ok(client) if !TEST => {
+                        for msg in msgs {
+                            for hook in ctx.config.webhooks.iter().filter(|h| h.wants(msg.event)) {
+                                let (client, hook, msg, log_dir) =
+                                    (client.clone(), hook.clone(), msg.clone(), log_dir.clone());
+                                let spawned =
+                                    tasks.spawn(TaskKind::Webhook, "Post webhook", async move {
+                                        // Only logged, so a failing webhook can't post its own errors
+                                        if let (Err(e), Some(dir)) =
+                                            (webhook::send(&client, &hook, &msg).await, log_dir)
+                                        {
+                                            let _ = log::append(dir.join(log::LOG_FILE), &e);
+                                        }
+                                    });
+                                if let (Err(e), Ok(dir)) = (spawned, C::path()) {
+                                    let _ = log::append(dir.join(log::LOG_FILE), &e);
+                                }
+                            }
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(e) => {
+                        if let Some(dir) = log_dir {
+                            let _ = log::append(dir.join(log::LOG_FILE), &e.to_string());
+                        }
+                    }
+                }
+            }
+            if let Some(src) = ctx.should_edit_source.take() {
+                match C::path() {
+                    Ok(dir) => {
+                        let path = dir.join(format!("{}.toml", CONFIG_FILE));
+                        let opened = match TEST {
+                            true => Ok(()),
+                            false => open::that_detached(&path),
+                        };
+                        match opened {
+                            Ok(_) => ctx.notify(format!(
+                                "Edit {} in {}",
+                                src.config_table(&ctx.config.sources),
+                                path.display()
+                            )),
+                            Err(e) => {
+                                ctx.show_error(format!("Failed to open {}:\n{}", path.display(), e))
+                            }
+                        }
+                    }
+                    Err(e) => ctx.show_error(e),
+                }
+            }
+            if ctx.should_test_clients {
+                ctx.should_test_clients = false;
+                match request_client(&jar, ctx) {
+                    Ok(rq_client) => {
+                        for c in Client::VARIANTS.iter().filter(|c| c.is_api()) {
+                            let spawned = tasks.spawn(
+                                TaskKind::TestClient,
+                                format!("Test {}", c),
+                                sync.clone().test_client(
+                                    tx_test.clone(),
+                                    *c,
+                                    ctx.config.client.clone(),
+                                    rq_client.clone(),
+                                ),
+                            );
+                            if let Err(e) = spawned {
+                                ctx.show_error(e);
+                            }
+                        }
+                    }
+                    Err(e) => ctx.show_error(e),
+                }
+            }
+            if ctx.should_refresh_watch_later {
+                ctx.should_refresh_watch_later = false;
+                // Each source is searched with its own headers and auth
+                let mut by_source: Vec<(Sources, Vec<WatchItem>)> = vec![];
+                for w in ctx.state.watch_later.iter() {
+                    match by_source.iter_mut().find(|(s, _)| *s == w.source) {
+                        Some((_, items)) => items.push(w.clone()),
+                        None => by_source.push((w.source, vec![w.clone()])),
+                    }
+                }
+                for (src, items) in by_source {
+                    match source_request_client_for(&jar, ctx, src) {
+                        Ok(client) => {
+                            let spawned = tasks.spawn(
+                                TaskKind::WatchLater,
+                                format!("Refresh {}", src.name(&ctx.config.sources)),
+                                sync.clone().refresh_watch_later(
+                                    tx_watch.clone(),
+                                    src,
+                                    items,
+                                    client,
+                                    ctx.config.sources.clone(),
+                                ),
+                            );
+                            if let Err(e) = spawned {
+                                ctx.show_error(e);
+                            }
+                        }
+                        Err(e) => ctx.show_error(e),
+                    }
+                }
+            }
+            if let Some(user) = ctx.should_check_user.take() {
+                match source_request_client(&jar, ctx) {
+                    Ok(client) => {
+                        let spawned = tasks.spawn(
+                            TaskKind::CheckUser,
+                            format!("Check user \"{}\"", user),
+                            sync.clone().check_user(
+                                tx_user.clone(),
+                                ctx.src,
+                                client,
+                                user,
+                                ctx.config.sources.clone(),
+                            ),
+                        );
+                        if let Err(e) = spawned {
+                            ctx.show_error(e);
+                        }
+                    }
+                    Err(e) => ctx.show_error(e),
+                }
+            }
+            if ctx.should_live_search {
+                ctx.should_live_search = false;
+                debounce.as_mut().reset(
+                    tokio::time::Instant::now()
+                        + Duration::from_millis(ctx.config.live_search_delay),
+                );
+                live_search_pending = true;
+            }
+            if let Some(id) = ctx.should_cancel_task.take() {
+                match tasks.kind(id) {
+                    // Cancelled like any other search, to go back to the page shown
+                    Some(TaskKind::Search) => ctx.cancel_load(),
+                    // Counted as done once it reports what it sent
+                    _ => {
+                        tasks.cancel(id);
+                    }
+                }
+            }
+            if ctx.should_cancel_load {
+                ctx.should_cancel_load = false;
+                ctx.batch_pages = None;
+                tasks.cancel_kind(TaskKind::Search);
+                // Still loading until its results are received, even once the
+                // task has sent them
+                if ctx.load_type.is_some() {
+                    ctx.load_type = None;
+                    ctx.load_start = None;
+                    self.restore_search(ctx, &loaded);
+                    ctx.notify("Search cancelled");
+                }
+            }
+            if ctx.should_cancel_downloads {
+                ctx.should_cancel_downloads = false;
+                // Batches stop between torrents, and torrents already being
+                // sent are let finish, so each is either sent or left in the
+                // batch. Nothing is shown once quit, so it's logged instead.
+                tasks.join_kind(TaskKind::Download).await;
+                while let Ok(mut dl) = rx_dl.try_recv() {
+                    // Not watched, but given the chance to log out
+                    let stopped = CancellationToken::new();
+                    stopped.cancel();
+                    for watcher in std::mem::take(&mut dl.watchers) {
+                        watcher.start(stopped.clone()).await;
+                    }
+                    self.on_download::<TEST>(ctx, dl);
+                }
+                if let Ok(dir) = C::path() {
+                    let _ = log::append(dir.join(log::LOG_FILE), &ctx.notifications.join("\n"));
+                }
+                ctx.downloads = 0;
+                ctx.should_quit = true;
+                continue;
+            }
+            // Nothing is left to wait for, so either quit or go back
+            if ctx.mode == Mode::Quit && ctx.downloads == 0 {
+                ctx.should_quit = ctx.should_quit_when_done;
+                ctx.mode = Mode::Normal;
+                ctx.redraw();
             }
             if ctx.should_dismiss_notifications {
                 self.widgets.notification.dismiss_all();
                 ctx.should_dismiss_notifications = false;
+                ctx.redraw();
             }
             if ctx.mode == Mode::Batch && ctx.batch.is_empty() {
-                ctx.mode = Mode::Normal;
+                ctx.focus_pane(Pane::Results);
+            }
+            if ctx.mode == Mode::Details && !ctx.three_pane {
+                ctx.focus_pane(Pane::Results);
             }
 
-            self.get_help(ctx);
-            terminal.draw(|f| self.draw(ctx, f))?;
+            if ctx.dirty {
+                ctx.dirty = false;
+                tasks.reap();
+                ctx.tasks = tasks.running();
+                self.get_help(ctx);
+                crash::set_state(ctx);
+                terminal.draw(|f| self.draw(ctx, f))?;
+                if ctx.config.window_title && !TEST {
+                    let title = ctx.window_title();
+                    if window_title.as_ref() != Some(&title) {
+                        let _ = term::set_title(&title);
+                        window_title = Some(title);
+                    }
+                }
+            }
             if let Mode::Loading(load_type) = ctx.mode.clone() {
-                ctx.mode = Mode::Normal;
+                ctx.mode = live_search_mode.take().unwrap_or(Mode::Normal);
+                // This load replaces any live search that was waiting to start
+                live_search_pending = false;
                 match load_type {
                     LoadType::Downloading => {
+                        // Torrents are fetched from the source, so use its timeout
+                        let dl_client = match source_request_client(&jar, ctx) {
+                            Ok(c) => c,
+                            Err(e) => {
+                                ctx.show_error(e);
+                                continue;
+                            }
+                        };
+                        let mut conf = ctx.config.client.clone();
+                        if let Some(opts) = ctx.download_options.take() {
+                            opts.apply(ctx.client, &mut conf);
+                        }
                         if let Some(i) = self
                             .widgets
                             .results
@@ -311,132 +1424,427 @@ impl App {
                             .selected()
                             .and_then(|i| ctx.results.response.items.get(i))
                         {
-                            tokio::spawn(sync.clone().download(
-                                tx_dl.clone(),
-                                false,
-                                vec![i.to_owned()],
-                                ctx.config.client.clone(),
-                                client.clone(),
-                                ctx.client,
-                            ));
-                            ctx.notify(format!("Downloading torrent with {}", ctx.client));
+                            if ctx.results.dead.contains(&i.id) {
+                                ctx.show_error(format!(
+                                    "Not downloading \"{}\":\nIts torrent link is dead",
+                                    i.title
+                                ));
+                                continue;
+                            }
+                            let (sync, tx_dl, item) = (sync.clone(), tx_dl.clone(), i.to_owned());
+                            let (src, sources, client) =
+                                (ctx.src, ctx.config.sources.clone(), ctx.client);
+                            let spawned = tasks.spawn_stoppable(
+                                TaskKind::Download,
+                                format!("Download \"{}\"", i.title),
+                                move |stop| {
+                                    resolve_and_download(
+                                        sync,
+                                        tx_dl,
+                                        None,
+                                        vec![item],
+                                        src,
+                                        sources,
+                                        conf,
+                                        dl_client,
+                                        client,
+                                        stop,
+                                    )
+                                },
+                            );
+                            match spawned {
+                                Ok(_) => {
+                                    ctx.downloads += 1;
+                                    ctx.notify(format!("Downloading torrent with {}", ctx.client));
+                                }
+                                Err(e) => ctx.show_error(e),
+                            }
                         }
                         continue;
                     }
                     LoadType::Batching => {
-                        tokio::spawn(sync.clone().download(
-                            tx_dl.clone(),
-                            true,
-                            ctx.batch.clone(),
-                            ctx.config.client.clone(),
-                            client.clone(),
-                            ctx.client,
-                        ));
-                        ctx.notify(format!(
-                            "Downloading {} torrents with {}",
-                            ctx.batch.len(),
-                            ctx.client
-                        ));
+                        let dl_client = match source_request_client(&jar, ctx) {
+                            Ok(c) => c,
+                            Err(e) => {
+                                ctx.show_error(e);
+                                continue;
+                            }
+                        };
+                        let mut conf = ctx.config.client.clone();
+                        if let Some(opts) = ctx.download_options.take() {
+                            opts.apply(ctx.client, &mut conf);
+                        }
+                        let (sync, tx_dl, items) = (sync.clone(), tx_dl.clone(), ctx.batch.clone());
+                        let (src, sources, client) =
+                            (ctx.src, ctx.config.sources.clone(), ctx.client);
+                        let id = Some(ctx.save_unfinished_batch(&items));
+                        let spawned = tasks.spawn_stoppable(
+                            TaskKind::Download,
+                            format!("Download batch of {}", ctx.batch.len()),
+                            move |stop| {
+                                resolve_and_download(
+                                    sync, tx_dl, id, items, src, sources, conf, dl_client, client,
+                                    stop,
+                                )
+                            },
+                        );
+                        match spawned {
+                            Ok(_) => {
+                                ctx.downloads += 1;
+                                ctx.notify(format!(
+                                    "Downloading {} torrents with {}",
+                                    ctx.batch.len(),
+                                    ctx.client
+                                ));
+                            }
+                            Err(e) => ctx.show_error(e),
+                        }
                         continue;
                     }
                     LoadType::Sourcing => {
                         // On sourcing, update info, reset things like category, etc.
                         ctx.src.apply(ctx, &mut self.widgets);
+                        // The source's default search is what's run first
+                        if !self.widgets.search.input.input.is_empty() {
+                            ctx.browsing = false;
+                        }
+                        if !ctx.config.search_on_source_change {
+                            ctx.results = Results::default();
+                            ctx.history.clear();
+                            ctx.searched = false;
+                            ctx.mode = Mode::Search;
+                            ctx.redraw();
+                            continue;
+                        }
                     }
                     _ => {}
                 }
 
+                // Built for each search, so the next User-Agent is used
+                let src_client = match source_request_client(&jar, ctx) {
+                    Ok(c) => c,
+                    Err(e) => {
+                        ctx.show_error(e);
+                        continue;
+                    }
+                };
                 ctx.load_type = Some(load_type.clone());
+                ctx.load_start = Some(Instant::now());
 
-                if let Some(handle) = last_load_abort.as_ref() {
-                    handle.abort();
-                }
+                let search = match ctx.browsing {
+                    true => SearchQuery {
+                        page: ctx.page,
+                        category: self.widgets.category.selected,
+                        filter: self.widgets.filter.selected,
+                        sort: self.widgets.sort.selected,
+                        ..Default::default()
+                    },
+                    false => SearchQuery {
+                        query: template::expand(
+                            &self.widgets.search.input.input,
+                            &ctx.template_values,
+                        ),
+                        page: ctx.page,
+                        category: self.widgets.category.selected,
+                        filter: self.widgets.filter.selected,
+                        sort: self.widgets.sort.selected,
+                        user: ctx.user.clone(),
+                    },
+                };
 
-                let search = SearchQuery {
-                    query: self.widgets.search.input.input.clone(),
-                    page: ctx.page,
-                    category: self.widgets.category.selected,
-                    filter: self.widgets.filter.selected,
-                    sort: self.widgets.sort.selected,
-                    user: ctx.user.clone(),
+                let name = match search.query.is_empty() {
+                    true => format!("{} {}", load_type, ctx.src.name(&ctx.config.sources)),
+                    false => format!("{} \"{}\"", load_type, search.query),
                 };
+                // Replaces the search being loaded, if any
+                let _ = tasks.spawn(
+                    TaskKind::Search,
+                    name,
+                    sync.clone().load_results(
+                        tx_res.clone(),
+                        load_type.clone(),
+                        ctx.src,
+                        src_client,
+                        search,
+                        ctx.config.sources.clone(),
+                        ResultsView {
+                            theme: ctx.theme.clone(),
+                            quick_filters: ctx.quick_filters.clone(),
+                            starred: ctx.state.starred.iter().cloned().collect(),
+                        },
+                        ctx.config.date_format.clone(),
+                    ),
+                );
+                ctx.redraw();
+                continue;
+            }
 
-                let task = tokio::spawn(sync.clone().load_results(
-                    tx_res.clone(),
-                    load_type.clone(),
-                    ctx.src,
-                    client.clone(),
-                    search,
-                    ctx.config.sources.clone(),
-                    ctx.theme.clone(),
-                    ctx.config.date_format.clone(),
-                ));
-                last_load_abort = Some(task.abort_handle());
-                continue; // Redraw
+            // Replay macros one key at a time, waiting for each load to finish
+            if ctx.load_type.is_none() {
+                if let Some(evt) = ctx.replay.pop_front() {
+                    self.on::<B, TEST>(&evt, ctx, terminal);
+                    continue;
+                }
+            }
+            if let Some(evt) = deferred.take() {
+                ctx.record(&evt);
+                self.on::<B, TEST>(&evt, ctx, terminal);
+                continue;
+            }
+
+            // Wake up to list the keys which can finish a pending combo
+            let combo_popup_at = ctx.combo_popup_at().filter(|at| *at > Instant::now());
+            if let Some(at) = combo_popup_at {
+                combo_timer
+                    .as_mut()
+                    .reset(tokio::time::Instant::from_std(at));
             }
 
             loop {
                 tokio::select! {
                     biased;
                     Some(evt) = rx_evt.recv() => {
-                        #[cfg(unix)]
+                        ctx.record(&evt);
                         self.on::<B, TEST>(&evt, ctx, terminal);
-                        #[cfg(not(unix))]
-                        self.on::<B, TEST>(&evt, ctx);
-
+                        // Handle the events already queued before drawing, like
+                        // keys repeated or pasted, while they stay in one mode
+                        let mode = ctx.mode.clone();
+                        // Holding j or k repeats it faster than slow terminals
+                        // draw, so the same motion queued again is counted and
+                        // moved through at once
+                        let mut last = evt;
+                        let mut repeats = 0;
+                        while ctx.mode == mode
+                            && !matches!(ctx.mode, Mode::Loading(_))
+                            && ctx.errors.is_empty()
+                            && ctx.replay.is_empty()
+                            && !ctx.should_quit
+                        {
+                            let Ok(evt) = rx_evt.try_recv() else {
+                                break;
+                            };
+                            // Tests quit on FocusLost, once the frame before is drawn
+                            if needs_frame(&evt) || (TEST && evt == Event::FocusLost) {
+                                deferred = Some(evt);
+                                break;
+                            }
+                            ctx.record(&evt);
+                            if evt == last && motion(&evt).is_some() && matches!(ctx.mode, Mode::Normal | Mode::Batch) {
+                                repeats += 1;
+                                continue;
+                            }
+                            self.repeat_motion(ctx, &last, std::mem::take(&mut repeats));
+                            self.on::<B, TEST>(&evt, ctx, terminal);
+                            last = evt;
+                        }
+                        self.repeat_motion(ctx, &last, repeats);
+                        break;
+                    },
+                    () = &mut combo_timer, if combo_popup_at.is_some() => {
+                        ctx.redraw();
+                        break;
+                    },
+                    () = &mut debounce, if live_search_pending => {
+                        live_search_pending = false;
+                        // Only search if still typing
+                        if ctx.mode == Mode::Search {
+                            ctx.redraw();
+                            ctx.browsing = false;
+                            live_search_mode = Some(Mode::Search);
+                            ctx.mode = Mode::Loading(LoadType::Searching);
+                            ctx.page = 1;
+                        }
                         break;
                     },
-                    () = &mut timer, if self.widgets.notification.is_animating() => {
+                    () = &mut timer, if self.widgets.notification.is_animating() || ctx.load_start.is_some() || ctx.downloads > 0 || progress::is_active() || ctx.mode == Mode::Tasks => {
                         timer.as_mut().reset(tokio::time::Instant::now() + Duration::from_millis(ANIMATE_SLEEP_MILLIS));
+                        if ctx.downloads > 0 || progress::is_active() {
+                            self.widgets.notification.set_progress(progress::summary());
+                        }
+                        // The quit popup lists the progress of each download, and the
+                        // tasks popup how long each task has run
+                        if ctx.mode == Mode::Quit || ctx.mode == Mode::Tasks {
+                            ctx.redraw();
+                            break;
+                        }
+                        // Redraw the batch gauge as torrents are fetched
+                        if progress::total_batch_progress() != batch_progress {
+                            batch_progress = progress::total_batch_progress();
+                            ctx.redraw();
+                            break;
+                        }
+                        // Only redraw the loading spinner when it changes
+                        let mut spinner_changed = false;
+                        if let Some(start) = ctx.load_start {
+                            let frame = start.elapsed().as_millis() / SPINNER_MILLIS;
+                            spinner_changed = frame != spinner_frame;
+                            spinner_frame = frame;
+                        }
                         if let Ok(size) = terminal.size() {
                             let now = Instant::now();
                             ctx.deltatime = last_time.map(|l| (now - l).as_secs_f64()).unwrap_or(0.0);
                             last_time = Some(now);
 
-                            if self.widgets.notification.update(ctx.deltatime, size) {
+                            if self.widgets.notification.update(ctx.deltatime, size) || spinner_changed {
+                                ctx.redraw();
                                 break;
                             }
                         } else {
+                            ctx.redraw();
                             break;
                         }
                     },
                     Some(rt) = rx_res.recv() => {
+                        ctx.redraw();
+                        if let Some(start) = ctx.load_start {
+                            stats::record_search(&ctx.src.name(&ctx.config.sources), start.elapsed(), rt.is_ok());
+                        }
                         match rt {
-                            Ok(SourceResults::Results(rt)) => {
-                                self.widgets.results.reset();
+                            Ok(SourceResults::Results(mut rt)) => {
+                                ctx.set_health(ctx.src, true);
+                                for item in rt.all_items().iter() {
+                                    ctx.completions.add(&item.title);
+                                }
+                                // Other searches leave the results shown behind, to go back to
+                                let new_view = !ctx.searched || ctx.src != loaded_src || !rt.search.same_view(&ctx.results.search);
+                                if ctx.src != loaded_src {
+                                    ctx.history.clear();
+                                } else if new_view && ctx.searched {
+                                    let selected = self.widgets.results.table.selected().unwrap_or(0);
+                                    ctx.history.visit(ctx.history.current(ctx.results.clone(), selected));
+                                }
+                                // Stay on the same item when reloading or sorting
+                                match ctx.src == loaded_src && rt.search.same_results(&ctx.results.search) {
+                                    true => self.widgets.results.reselect(&ctx.results, &rt),
+                                    false => self.widgets.results.reset(),
+                                }
+                                if !rt.response.warnings.is_empty() {
+                                    let msg = rt.response.warnings.join("\n");
+                                    // Quiet mode already logs every notification
+                                    if !self.widgets.notification.is_quiet() {
+                                        if let Ok(dir) = C::path() {
+                                            let _ = log::append(dir.join(log::LOG_FILE), &msg);
+                                        }
+                                    }
+                                    ctx.notify(msg);
+                                }
+                                if ctx.src == loaded_src {
+                                    rt.mark_new(&ctx.results);
+                                    if !rt.new.is_empty() {
+                                        let titles: Vec<String> = rt.all_items()
+                                            .iter()
+                                            .filter(|i| rt.new.contains(&i.id))
+                                            .map(|i| i.title.clone())
+                                            .collect();
+                                        let search = match rt.search.query.is_empty() {
+                                            true => "the front page".to_owned(),
+                                            false => format!("\"{}\"", rt.search.query),
+                                        };
+                                        ctx.webhook(WebhookEvent::NewResults, format!("{} new results for {}", titles.len(), search), titles);
+                                    }
+                                }
+                                if ctx.batch_pages.is_none() {
+                                    ctx.remember_search(&rt.search.query);
+                                }
+                                // Already split into pages, filtered and formatted
                                 ctx.results = rt;
+                                ctx.history.loaded(ctx.page, ctx.browsing, self.widgets.search.input.input.clone(), new_view);
+                                loaded = self.current_search(ctx);
+                                loaded_src = ctx.src;
+                                if let Some((last, added)) = ctx.batch_pages {
+                                    let added = added + ctx.add_to_batch(ctx.results.response.items.clone());
+                                    if ctx.page < last.min(ctx.results.response.last_page) {
+                                        ctx.batch_pages = Some((last, added));
+                                        ctx.page += 1;
+                                        ctx.mode = Mode::Loading(LoadType::Searching);
+                                    } else {
+                                        ctx.batch_pages = None;
+                                        ctx.notify(format!("Added {} torrents from {} pages to the batch", added, ctx.page));
+                                    }
+                                } else if ctx.config.validate_links {
+                                    match source_request_client(&jar, ctx) {
+                                        Ok(client) => {
+                                            let _ = tasks.spawn(
+                                                TaskKind::Validate,
+                                                "Check torrent links",
+                                                sync.clone().validate_links(
+                                                    tx_val.clone(),
+                                                    ctx.results.response.items.clone(),
+                                                    client,
+                                                ),
+                                            );
+                                        }
+                                        Err(e) => ctx.show_error(e),
+                                    }
+                                }
                             }
                             #[cfg(feature = "captcha")]
                             Ok(SourceResults::Captcha(c)) => {
                                 ctx.results = Results::default();
                                 ctx.mode = Mode::Captcha;
-                                self.widgets.captcha.image = Some(c);
+                                self.widgets.captcha.image = Some(c.image);
+                                self.widgets.captcha.bytes = c.bytes;
                                 self.widgets.captcha.input.clear();
                             }
                             Err(e) => {
-                                // Clear results on error
+                                ctx.set_health(ctx.src, false);
+                                // Clear results on error, keeping them to go back to
+                                if !ctx.results.response.items.is_empty() {
+                                    let selected = self.widgets.results.table.selected().unwrap_or(0);
+                                    ctx.history.visit(ctx.history.current(std::mem::take(&mut ctx.results), selected));
+                                }
+                                ctx.history.loaded(ctx.page, ctx.browsing, self.widgets.search.input.input.clone(), true);
                                 ctx.results = Results::default();
-                                ctx.show_error(e);
+                                ctx.batch_pages = None;
+                                let retryable = e.is_retryable();
+                                ctx.show_error_chain(&e);
+                                if retryable {
+                                    ctx.notify(ctx.tr("Press r to retry"));
+                                }
                             },
                         }
                         ctx.load_type = None;
-                        last_load_abort = None;
+                        ctx.load_start = None;
+                        ctx.searched = true;
+                        ctx.history.evict(ctx.config.max_kept_results, ctx.results.all_items().len());
                         break;
                     },
-                    Some(dl) = rx_dl.recv() => {
-                        if dl.batch {
-                            for id in dl.success_ids.iter() {
-                                ctx.batch.retain(|i| i.id.ne(id));
-                            }
-                        }
-                        if !dl.success_ids.is_empty() {
-                            if let Some(notif) = dl.success_msg {
-                                ctx.notify(notif);
-                            }
+                    Some(dead) = rx_val.recv() => {
+                        ctx.results.mark_dead(dead);
+                        ctx.redraw();
+                        break;
+                    },
+                    Some((c, res)) = rx_test.recv() => {
+                        ctx.client_status.push((c, res));
+                        ctx.redraw();
+                        break;
+                    },
+                    Some(res) = rx_watch.recv() => {
+                        ctx.redraw();
+                        match res {
+                            Ok(refreshed) => ctx.update_watch_later(refreshed),
+                            Err(e) => ctx.show_error_chain(&e),
                         }
-                        for e in dl.errors.iter() {
-                            ctx.show_error(e)
+                        break;
+                    },
+                    Some((src, user, check)) = rx_user.recv() => {
+                        self.widgets.user.set_check(src, user, check);
+                        ctx.redraw();
+                        break;
+                    },
+                    Some(msg) = rx_update.recv() => {
+                        ctx.notify(msg);
+                        break;
+                    },
+                    Some(mut dl) = rx_dl.recv() => {
+                        for watcher in std::mem::take(&mut dl.watchers) {
+                            let name = watcher.name.clone();
+                            // Progress just isn't shown if too many downloads are running
+                            let _ = tasks.spawn_stoppable(TaskKind::Download, name, |stop| {
+                                watcher.start(stop)
+                            });
                         }
+                        self.on_download::<TEST>(ctx, dl);
                         break;
                     }
                     // _ = async{}, if matches!(terminal.size().map(|s| self.widgets.notification.update(last_time.map(|l| (Instant::now() - l).as_secs_f64()).unwrap_or(0.), s)), Ok(true)) => {
@@ -449,44 +1857,314 @@ impl App {
                 last_time = None;
             }
         }
+        // Downloads still running keep their items in the batch, so they're
+        // resumed next time
+        ctx.prune_unfinished_batches();
+        if ctx.should_save_state {
+            let _ = C::store_state(&ctx.state);
+        }
         Ok(())
     }
 
+    /// Show what a download sent, and forget what it sent from the batch
+    fn on_download<const TEST: bool>(&mut self, ctx: &mut Context, dl: DownloadResult) {
+        ctx.redraw();
+        ctx.downloads = ctx.downloads.saturating_sub(1);
+        self.widgets.notification.set_progress(progress::summary());
+        // Before the batch forgets the items that were downloaded
+        let titles: Vec<String> = dl
+            .success_ids
+            .iter()
+            .filter_map(|id| {
+                ctx.batch
+                    .iter()
+                    .chain(ctx.results.all_items())
+                    .find(|i| i.id == *id)
+            })
+            .map(|i| i.title.clone())
+            .collect();
+        if let Some(id) = dl.batch_id {
+            ctx.forget_sent(id, &dl.success_ids);
+        }
+        if dl.batch {
+            for id in dl.success_ids.iter() {
+                ctx.batch.retain(|i| i.id.ne(id));
+            }
+            if ctx.config.batch_bell && !TEST {
+                let _ = term::bell();
+            }
+        }
+        if !dl.success_ids.is_empty() {
+            if let Some(notif) = dl.success_msg {
+                ctx.webhook(WebhookEvent::Download, notif.clone(), titles);
+                ctx.notify(notif);
+            }
+        }
+        match (dl.skipped.len(), dl.batch) {
+            (0, _) => {}
+            (n, true) => ctx.notify(format!(
+                "Stopped before sending {} torrents, which are left in the batch",
+                n
+            )),
+            (_, false) => ctx.notify("Stopped before sending the torrent"),
+        }
+        for w in dl.warnings {
+            ctx.notify(w);
+        }
+        for e in dl.errors.iter() {
+            ctx.show_error(e)
+        }
+    }
+
     pub fn draw(&mut self, ctx: &mut Context, f: &mut Frame) {
+        let hint_height = ctx.config.hint_bar as u16;
+        let layout = ctx.config.layout;
+        ctx.focus.clear();
+        ctx.three_pane = layout.three_pane && f.size().width >= layout.min_width;
+        ctx.narrow = f.size().width < layout.narrow_width;
         let layout_vertical = Layout::new(
             Direction::Vertical,
-            [Constraint::Length(3), Constraint::Min(1)],
+            [
+                Constraint::Length(match ctx.narrow || layout.minimal {
+                    true => 1,
+                    false => 3,
+                }),
+                Constraint::Min(1),
+                Constraint::Length(hint_height),
+            ],
         )
         .split(f.size());
 
         self.widgets.search.draw(f, ctx, layout_vertical[0]);
-        // Dont draw batch pane if empty
-        if ctx.batch.is_empty() {
-            self.widgets.results.draw(f, ctx, layout_vertical[1]);
+        if ctx.three_pane {
+            let layout_horizontal = Layout::new(
+                Direction::Horizontal,
+                [
+                    Constraint::Ratio(2, 4),
+                    Constraint::Ratio(1, 4),
+                    Constraint::Ratio(1, 4),
+                ],
+            )
+            .split(layout_vertical[1]);
+            let item = self
+                .widgets
+                .results
+                .table
+                .selected()
+                .and_then(|i| ctx.results.response.items.get(i))
+                .cloned();
+            self.widgets.details.with_item(item);
+            self.draw_results(ctx, f, layout_horizontal[0]);
+            self.widgets.details.draw(f, ctx, layout_horizontal[1]);
+            self.widgets.batch.draw(f, ctx, layout_horizontal[2]);
+            ctx.focus.drawn(Pane::Details, layout_horizontal[1]);
+            ctx.focus.drawn(Pane::Batch, layout_horizontal[2]);
+        } else if ctx.batch.is_empty() {
+            // Dont draw batch pane if empty
+            self.draw_results(ctx, f, layout_vertical[1]);
+        } else if ctx.narrow {
+            // There is no room for both, so the batch covers the results while focused
+            self.draw_results(ctx, f, layout_vertical[1]);
+            if matches!(ctx.focused_mode(), Mode::Batch | Mode::Help) {
+                let overlay = layout_vertical[1].inner(&Margin {
+                    vertical: 1,
+                    horizontal: 2,
+                });
+                self.widgets.batch.draw(f, ctx, overlay);
+                ctx.focus.drawn(Pane::Batch, overlay);
+            } else {
+                ctx.focus.hidden(Pane::Batch);
+            }
         } else {
             let layout_horizontal = Layout::new(
                 Direction::Horizontal,
-                match ctx.mode {
+                match ctx.focused_mode() {
                     Mode::Batch | Mode::Help => [Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)],
                     _ => [Constraint::Ratio(3, 4), Constraint::Ratio(1, 4)],
                 },
             )
             .split(layout_vertical[1]);
-            self.widgets.results.draw(f, ctx, layout_horizontal[0]);
+            self.draw_results(ctx, f, layout_horizontal[0]);
             self.widgets.batch.draw(f, ctx, layout_horizontal[1]);
+            ctx.focus.drawn(Pane::Batch, layout_horizontal[1]);
+        }
+        if ctx.config.hint_bar {
+            let (hints, help) = match &ctx.mode {
+                Mode::Help => (
+                    vec![("Esc", "Close"), ("/", "Search"), ("j, k", "Down/Up")],
+                    false,
+                ),
+                Mode::KeyCombo(_) | Mode::Loading(_) => (
+                    self.widgets.get_help(&Mode::Normal).unwrap_or_default(),
+                    true,
+                ),
+                mode => (self.widgets.get_help(mode).unwrap_or_default(), true),
+            };
+            self.widgets.hints.with_items(hints, help);
+            self.widgets.hints.draw(f, ctx, layout_vertical[2]);
         }
         self.widgets.draw_popups(ctx, f);
+        if ctx.combo_popup_at().is_some_and(|at| at <= Instant::now()) {
+            self.widgets.combo.draw(f, ctx, f.size());
+        }
+        self.widgets.error.draw(f, ctx, f.size());
         self.widgets.notification.draw(f, ctx, f.size());
+        if ctx.theme.monochrome {
+            ctx.theme.to_monochrome(f.buffer_mut());
+        }
+    }
+
+    /// Draw the results, or the dashboard if nothing has been searched yet
+    fn draw_results(&mut self, ctx: &mut Context, f: &mut Frame, area: Rect) {
+        ctx.focus.drawn(Pane::Results, area);
+        match ctx.searched || ctx.load_type.is_some() || matches!(ctx.mode, Mode::Loading(_)) {
+            true => self.widgets.results.draw(f, ctx, area),
+            false => self.widgets.dashboard.draw(f, ctx, area),
+        }
+    }
+
+    /// Select the category and filter confirmed in the user popup
+    fn apply_user_search(&mut self, ctx: &mut Context) {
+        if let Some((category, filter, query)) = self.widgets.user.take_confirmed() {
+            self.select_category(ctx, category);
+            self.widgets
+                .filter
+                .select(ctx, FilterPopup::from_row(ctx, filter));
+            if query != self.widgets.search.input.input {
+                self.widgets.search.input.input = query;
+                self.widgets.search.input.cursor = self.widgets.search.input.input.len();
+            }
+            // A preset's query may be a template to fill in first
+            if !template::variables(&self.widgets.search.input.input).is_empty() {
+                ctx.mode = Mode::Template;
+                self.open_template(ctx);
+            }
+        }
+    }
+
+    /// Search for items related to the highlighted one. The current results
+    /// can be gone back to like any other search.
+    pub fn related_search(&mut self, ctx: &mut Context, related: Related) {
+        let selected = self.widgets.results.table.selected().unwrap_or(0);
+        let Some(item) = ctx.results.response.items.get(selected) else {
+            return ctx.show_error("No item to find related results for");
+        };
+        let Some(query) = related.query(&item.parsed()) else {
+            return ctx.show_error(format!(
+                "Couldn't read the {} from \"{}\"",
+                match related {
+                    Related::Show => "show name",
+                    Related::Group => "group",
+                },
+                item.title
+            ));
+        };
+        ctx.history.label_next(related.label());
+        ctx.user = None;
+        self.widgets.search.input.input = query;
+        self.widgets.search.input.cursor = self.widgets.search.input.input.len();
+        ctx.browsing = false;
+        ctx.page = 1;
+        ctx.mode = Mode::Loading(LoadType::Searching);
+    }
+
+    /// Go back to the results of the search before, or forward again if not
+    /// `back`, without loading them unless they were dropped to save memory
+    pub fn navigate(&mut self, ctx: &mut Context, back: bool) {
+        let selected = self.widgets.results.table.selected().unwrap_or(0);
+        let current = ctx.history.current(ctx.results.clone(), selected);
+        let view = match back {
+            true => ctx.history.back(current),
+            false => ctx.history.forward(current),
+        };
+        let Some(view) = view else {
+            return ctx.notify(match back {
+                true => "Nothing to go back to",
+                false => "Nothing to go forward to",
+            });
+        };
+        let search = &view.results.search;
+        self.select_category(ctx, search.category);
+        self.widgets.filter.select(ctx, search.filter);
+        self.widgets.sort.selected = search.sort;
+        self.widgets.sort.table.select(search.sort.sort);
+        ctx.user.clone_from(&search.user);
+        self.widgets.search.input.input = view.input;
+        self.widgets.search.input.cursor = self.widgets.search.input.input.len();
+        self.widgets.results.table.select(view.selected);
+        ctx.page = view.page;
+        ctx.browsing = view.browsing;
+        ctx.results = view.results;
+        if view.evicted {
+            ctx.mode = Mode::Loading(LoadType::Searching);
+        } else {
+            stats::record_cache_hit();
+            ctx.history
+                .evict(ctx.config.max_kept_results, ctx.results.all_items().len());
+        }
+    }
+
+    /// The search the widgets are set to, with the query as it was typed
+    fn current_search(&self, ctx: &Context) -> SearchQuery {
+        SearchQuery {
+            query: self.widgets.search.input.input.clone(),
+            page: ctx.page,
+            category: self.widgets.category.selected,
+            filter: self.widgets.filter.selected,
+            sort: self.widgets.sort.selected,
+            user: ctx.user.clone(),
+        }
+    }
+
+    /// Set the widgets back to `search`, from [`App::current_search`]
+    fn restore_search(&mut self, ctx: &mut Context, search: &SearchQuery) {
+        self.select_category(ctx, search.category);
+        self.widgets.filter.select(ctx, search.filter);
+        self.widgets.sort.selected = search.sort;
+        self.widgets.sort.table.select(search.sort.sort);
+        ctx.user.clone_from(&search.user);
+        if search.query != self.widgets.search.input.input {
+            self.widgets.search.input.input.clone_from(&search.query);
+            self.widgets.search.input.cursor = search.query.len();
+        }
+        ctx.page = search.page;
+    }
+
+    fn select_category(&mut self, ctx: &Context, category: usize) {
+        let (major, minor) = ctx.src_info.get_major_minor(category);
+        self.widgets.category.selected = category;
+        self.widgets.category.major = major;
+        self.widgets.category.minor = minor;
+        self.widgets.category.table.select(major + minor + 1);
+    }
+
+    /// Ask for the variables of the query template being searched
+    fn open_template(&mut self, ctx: &Context) {
+        let selected = self
+            .widgets
+            .results
+            .table
+            .selected()
+            .and_then(|i| ctx.results.response.items.get(i));
+        self.widgets.template.open(
+            &self.widgets.search.input.input,
+            &ctx.template_values,
+            selected,
+        );
     }
 
     fn on<B: Backend, const TEST: bool>(
         &mut self,
         evt: &Event,
         ctx: &mut Context,
-        #[cfg(unix)] terminal: &mut Terminal<B>,
+        terminal: &mut Terminal<B>,
     ) {
+        // Test downloads never finish, so don't wait for them
         if TEST && Event::FocusLost == *evt {
-            ctx.quit();
+            ctx.should_quit = true;
+        }
+        if changes_frame(evt) {
+            ctx.redraw();
         }
 
         if let Event::Key(KeyEvent {
@@ -496,7 +2174,6 @@ impl App {
             ..
         }) = evt
         {
-            #[cfg(unix)]
             if let (KeyCode::Char('z'), &KeyModifiers::CONTROL) = (code, modifiers) {
                 if let Err(e) = term::suspend_self(terminal) {
                     ctx.show_error(format!("Failed to suspend:\n{}", e));
@@ -507,6 +2184,28 @@ impl App {
                 }
                 return;
             }
+        }
+        // Errors must be dismissed before anything else
+        if self.widgets.error.is_open() {
+            self.widgets.error.handle_event(ctx, evt);
+            return;
+        }
+        // Clicking a pane focuses it, unless a popup is open over it
+        if let Some((column, row)) = focus::click(evt) {
+            if let (Some(_), Some(pane)) =
+                (Pane::from_mode(&ctx.mode), ctx.focus.pane_at(column, row))
+            {
+                ctx.focus_pane(pane);
+            }
+            return;
+        }
+        if let Event::Key(KeyEvent {
+            code,
+            kind: KeyEventKind::Press,
+            modifiers,
+            ..
+        }) = evt
+        {
             match ctx.mode.to_owned() {
                 Mode::KeyCombo(keys) => {
                     ctx.last_key = keys;
@@ -514,11 +2213,69 @@ impl App {
                 _ => ctx.last_key = key_to_string(*code, *modifiers),
             };
         }
+        let prev_mode = ctx.mode.to_owned();
         match ctx.mode.to_owned() {
             Mode::KeyCombo(keys) => self.on_combo(ctx, keys, evt),
             Mode::Loading(_) => {}
+            Mode::Normal if history_key(evt).is_some() => {
+                self.navigate(ctx, history_key(evt) == Some(true))
+            }
+            Mode::Normal | Mode::Batch | Mode::Details if focus::cycle_key(evt).is_some() => {
+                let pane = ctx.focus.cycle(focus::cycle_key(evt) == Some(true));
+                ctx.focus_pane(pane);
+            }
             _ => self.widgets.handle_event(ctx, evt),
         }
+        // Widgets which focus a pane by setting the mode
+        if let Some(pane) = Pane::from_mode(&ctx.mode) {
+            ctx.focus.set(pane);
+        }
+        if matches!(ctx.mode, Mode::KeyCombo(_)) && !matches!(prev_mode, Mode::KeyCombo(_)) {
+            ctx.combo_mode = prev_mode.to_owned();
+            ctx.combo_start = Some(Instant::now());
+        }
+        // Client connections are tested once, then retested on request
+        if ctx.mode == Mode::Clients && prev_mode != Mode::Clients && !self.widgets.clients.tested {
+            self.widgets.clients.tested = true;
+            ctx.test_clients();
+        }
+        if ctx.mode == Mode::Find && prev_mode != Mode::Find {
+            self.widgets
+                .find
+                .open(self.widgets.results.table.selected().unwrap_or(0));
+        }
+        if let Some(sel) = self.widgets.find.take_selection() {
+            self.widgets.results.table.select(sel);
+        }
+        if ctx.mode == Mode::Filter && prev_mode != Mode::Filter {
+            self.widgets.filter.open();
+        }
+        if ctx.mode == Mode::User && prev_mode != Mode::User {
+            self.widgets.user.open(
+                self.widgets.category.selected,
+                self.widgets.filter.row(ctx),
+                &self.widgets.search.input.input,
+            );
+        }
+        if ctx.mode == Mode::Template && prev_mode != Mode::Template {
+            self.open_template(ctx);
+        }
+        if let (Mode::Confirm(load), false) = (&ctx.mode, matches!(prev_mode, Mode::Confirm(_))) {
+            let items = match load {
+                LoadType::Batching => ctx.batch.clone(),
+                _ => self
+                    .widgets
+                    .results
+                    .table
+                    .selected()
+                    .and_then(|i| ctx.results.response.items.get(i))
+                    .cloned()
+                    .into_iter()
+                    .collect(),
+            };
+            self.widgets.confirm.open(ctx, items);
+        }
+        self.apply_user_search(ctx);
         if ctx.mode != Mode::Help {
             self.on_help(evt, ctx);
         }
@@ -532,7 +2289,7 @@ impl App {
         }) = e
         {
             match code {
-                KeyCode::Char('?') if ctx.mode != Mode::Search => {
+                KeyCode::Char('?') if !matches!(ctx.mode, Mode::Search | Mode::Confirm(_)) => {
                     ctx.mode = Mode::Help;
                 }
                 KeyCode::F(1) => {
@@ -546,12 +2303,71 @@ impl App {
     fn get_help(&mut self, ctx: &Context) {
         let help = self.widgets.get_help(&ctx.mode);
         if let Some(msg) = help {
+            // Translated here so searching matches what is shown
+            let msg = msg.into_iter().map(|(k, m)| (k, ctx.tr(m))).collect();
             self.widgets.help.with_items(msg, ctx.mode.clone());
             self.widgets.help.table.select(0);
         }
     }
 
+    /// Move the selection of the focused pane `count` times by the motion `e`
+    fn repeat_motion(&mut self, ctx: &mut Context, e: &Event, count: usize) {
+        let Some(amt) = motion(e).filter(|_| count > 0) else {
+            return;
+        };
+        let amt = amt * count as isize;
+        match ctx.mode {
+            Mode::Batch => {
+                self.widgets.batch.table.next(ctx.batch.len(), amt);
+            }
+            Mode::Normal => self.widgets.results.move_by(ctx, amt),
+            _ => {}
+        }
+    }
+
+    /// Repeat a motion key `count` times, where `keys` is the count typed so far
+    fn on_count(&mut self, ctx: &mut Context, mut keys: String, e: &Event) {
+        let Event::Key(KeyEvent {
+            code,
+            kind: KeyEventKind::Press,
+            ..
+        }) = e
+        else {
+            return;
+        };
+        let count = match code {
+            KeyCode::Char(c) if c.is_ascii_digit() => {
+                keys.push(*c);
+                ctx.last_key.clone_from(&keys);
+                ctx.mode = Mode::KeyCombo(keys);
+                return;
+            }
+            KeyCode::Esc => {
+                ctx.mode = ctx.combo_mode.to_owned();
+                return;
+            }
+            KeyCode::Char('j' | 'k' | 'J' | 'K' | 'n' | 'l' | 'p' | 'h')
+            | KeyCode::Up
+            | KeyCode::Down
+            | KeyCode::Left
+            | KeyCode::Right => keys.parse::<usize>().unwrap_or(MAX_COUNT).min(MAX_COUNT),
+            // Anything else ignores the count
+            _ => 1,
+        };
+        ctx.mode = ctx.combo_mode.to_owned();
+        // Page motions only load once, since each one just changes the page
+        for _ in 0..count {
+            match ctx.combo_mode {
+                Mode::Batch => self.widgets.batch.handle_event(ctx, e),
+                _ => self.widgets.results.handle_event(ctx, e),
+            }
+        }
+    }
+
     fn on_combo(&mut self, ctx: &mut Context, mut keys: String, e: &Event) {
+        if keys.starts_with(|c: char| c.is_ascii_digit()) {
+            return self.on_count(ctx, keys, e);
+        }
         if let Event::Key(KeyEvent {
             code,
             kind: KeyEventKind::Press,
@@ -569,36 +2385,117 @@ impl App {
                 _ => {}
             }
         }
-        ctx.last_key.clone_from(&keys);
+        ctx.last_key = ctx.chords.display(&keys);
         match keys.chars().collect::<Vec<char>>()[..] {
-            ['y', c] => {
-                let s = self.widgets.results.table.state.selected().unwrap_or(0);
+            ['Q', c] => {
                 ctx.mode = Mode::Normal;
-                match ctx.results.response.items.get(s).cloned() {
-                    Some(item) => {
-                        let link = match c {
-                            't' => item.torrent_link,
-                            'm' => item.magnet_link,
-                            'p' => item.post_link,
-                            'i' => match item.extra.get("imdb").cloned() {
-                                Some(imdb) => imdb,
-                                None => return ctx.show_error("No imdb ID found for this item."),
-                            },
-                            _ => return,
-                        };
-                        match clip::copy_to_clipboard(link.to_owned(), ctx.config.clipboard.clone())
-                        {
-                            Ok(_) => ctx.notify(format!("Copied \"{}\" to clipboard", link)),
-                            Err(e) => ctx.show_error(e),
-                        }
-                    }
-                    None if ['t', 'm', 'p', 'i'].contains(&c) => {
-                        ctx.show_error("Failed to copy:\nFailed to get item")
+                if c.is_ascii_lowercase() {
+                    ctx.start_recording(c);
+                }
+            }
+            ['@', c] => {
+                ctx.mode = Mode::Normal;
+                if ctx.recording.is_some() {
+                    // Keep macros from replaying themselves
+                    if let Some(events) = ctx.recording.and_then(|r| ctx.registers.get_mut(&r)) {
+                        events.truncate(events.len().saturating_sub(2));
                     }
-                    None => {}
+                    return ctx.show_error("Can't replay a macro while recording");
+                }
+                if c.is_ascii_lowercase() || c == '@' {
+                    ctx.replay(c);
+                }
+            }
+            _ => match ctx.chords.get(&keys) {
+                Some(action) => {
+                    ctx.mode = Mode::Normal;
+                    self.on_chord(ctx, action);
+                }
+                // Wait for the next key of a longer chord
+                None if ctx.chords.continuations(&keys).is_some() => {
+                    ctx.mode = Mode::KeyCombo(keys)
+                }
+                None => ctx.mode = Mode::Normal,
+            },
+        }
+    }
+
+    /// Do what the chord just finished is mapped to
+    fn on_chord(&mut self, ctx: &mut Context, action: ChordAction) {
+        match action {
+            ChordAction::AddPage => {
+                ctx.save_batch("adding the page to the batch");
+                let added = ctx.add_to_batch(ctx.results.response.items.clone());
+                ctx.notify(format!("Added {} torrents to the batch", added));
+            }
+            ChordAction::AddAllPages => {
+                ctx.save_batch("adding every page to the batch");
+                ctx.batch_all_pages();
+            }
+            ChordAction::InvertBatch => {
+                ctx.save_batch("inverting the batch");
+                ctx.invert_batch();
+            }
+            ChordAction::ClearBatch => {
+                ctx.save_batch("clearing the batch");
+                ctx.batch.clear();
+            }
+            ChordAction::UndoBatch => ctx.undo_batch(),
+            ChordAction::SameShow => self.related_search(ctx, Related::Show),
+            ChordAction::SameGroup => self.related_search(ctx, Related::Group),
+            ChordAction::Reload => ctx.mode = Mode::Loading(LoadType::Searching),
+            ChordAction::DeepSearch => ctx.deep_search(),
+            ChordAction::Stats => ctx.mode = Mode::Stats,
+            ChordAction::Tasks => ctx.mode = Mode::Tasks,
+            ChordAction::CopyBatchMagnets | ChordAction::CopyBatchTorrents => {
+                if ctx.batch.is_empty() {
+                    return ctx.show_error("Failed to copy:\nBatch is empty");
+                }
+                let magnets = action == ChordAction::CopyBatchMagnets;
+                let links = ctx
+                    .batch
+                    .iter()
+                    .map(|i| match magnets {
+                        true => i.magnet_link.to_owned(),
+                        false => i.torrent_link.to_owned(),
+                    })
+                    .collect::<Vec<String>>();
+                let kind = match magnets {
+                    true => "magnet",
+                    false => "torrent",
+                };
+                match clip::copy_to_clipboard(links.join("\n"), ctx.config.clipboard.clone()) {
+                    Ok(_) => ctx.notify(format!(
+                        "Copied {} {} link{} to clipboard",
+                        links.len(),
+                        kind,
+                        if links.len() == 1 { "" } else { "s" }
+                    )),
+                    Err(e) => ctx.show_error(e),
+                }
+            }
+            ChordAction::CopyTorrent
+            | ChordAction::CopyMagnet
+            | ChordAction::CopyPost
+            | ChordAction::CopyImdb => {
+                let s = self.widgets.results.table.state.selected().unwrap_or(0);
+                let Some(item) = ctx.results.response.items.get(s).cloned() else {
+                    return ctx.show_error("Failed to copy:\nFailed to get item");
+                };
+                let link = match action {
+                    ChordAction::CopyTorrent => item.torrent_link,
+                    ChordAction::CopyMagnet => item.magnet_link,
+                    ChordAction::CopyPost => item.post_link,
+                    _ => match item.extra.get("imdb").cloned() {
+                        Some(imdb) => imdb,
+                        None => return ctx.show_error("No imdb ID found for this item."),
+                    },
+                };
+                match clip::copy_to_clipboard(link.to_owned(), ctx.config.clipboard.clone()) {
+                    Ok(_) => ctx.notify(format!("Copied \"{}\" to clipboard", link)),
+                    Err(e) => ctx.show_error(e),
                 }
             }
-            _ => ctx.mode = Mode::KeyCombo(keys),
         }
     }
 }
@@ -1,15 +1,21 @@
-use std::fmt::Display;
+use std::{fmt::Display, future::Future, pin::Pin, sync::Arc};
 
 use serde::{Deserialize, Serialize};
 use strum::{Display, VariantArray};
-use tokio::task::JoinSet;
+use tokio::{sync::Semaphore, task::JoinSet};
+use tokio_util::sync::CancellationToken;
 
-use crate::{app::Context, client::cmd::CmdClient, source::Item};
+use crate::{
+    app::Context,
+    client::cmd::CmdClient,
+    source::{Item, ItemId},
+};
 
 use self::{
     cmd::CmdConfig,
     default_app::{DefaultAppClient, DefaultAppConfig},
     download::{DownloadConfig, DownloadFileClient},
+    progress::{Batch, BatchGuard},
     qbit::{QbitClient, QbitConfig},
     rqbit::{RqbitClient, RqbitConfig},
     transmission::{TransmissionClient, TransmissionConfig},
@@ -18,11 +24,15 @@ use self::{
 pub mod cmd;
 pub mod default_app;
 pub mod download;
+pub mod progress;
 pub mod qbit;
 pub mod rqbit;
 pub mod transmission;
 
-pub struct DownloadError(String);
+// Most torrents of a batch downloaded at once
+static MAX_CONCURRENT_DOWNLOADS: usize = 4;
+
+pub struct DownloadError(pub String);
 
 pub trait DownloadClient {
     fn download(
@@ -30,10 +40,13 @@ pub trait DownloadClient {
         conf: ClientConfig,
         client: reqwest::Client,
     ) -> impl std::future::Future<Output = DownloadResult> + std::marker::Send + 'static;
+    /// Send every item, stopping before the next one once the batch's `stop`
+    /// is cancelled
     fn batch_download(
         items: Vec<Item>,
         conf: ClientConfig,
         client: reqwest::Client,
+        batch: Batch,
     ) -> impl std::future::Future<Output = DownloadResult> + std::marker::Send + 'static;
 }
 
@@ -43,17 +56,59 @@ impl Display for DownloadError {
     }
 }
 
+type WatchFn =
+    Box<dyn FnOnce(CancellationToken) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send>;
+
+/// Polls a client for the progress of torrents sent to it. It's left to the
+/// app to start as a download task, so it's stopped along with the downloads.
+pub struct Watcher {
+    /// Shown in the tasks popup
+    pub name: String,
+    run: WatchFn,
+}
+
+impl Watcher {
+    /// Watch with the future `run` makes, which should stop once the token
+    /// it's given is cancelled
+    pub fn new<F>(
+        name: impl Into<String>,
+        run: impl FnOnce(CancellationToken) -> F + Send + 'static,
+    ) -> Self
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        Watcher {
+            name: name.into(),
+            run: Box::new(move |stop| Box::pin(run(stop))),
+        }
+    }
+
+    pub fn start(self, stop: CancellationToken) -> impl Future<Output = ()> + Send + 'static {
+        (self.run)(stop)
+    }
+}
+
 pub struct DownloadResult {
     pub success_msg: Option<String>,
-    pub success_ids: Vec<String>,
+    pub success_ids: Vec<ItemId>,
     pub batch: bool,
+    /// Id of the batch the items were sent from, kept in the state file
+    /// until it's done
+    pub batch_id: Option<usize>,
     pub errors: Vec<DownloadError>,
+    /// Problems which didn't stop the download, shown as notifications
+    pub warnings: Vec<String>,
+    /// Items which weren't sent since the download was stopped, left in the
+    /// batch
+    pub skipped: Vec<ItemId>,
+    /// Progress of the torrents sent, to be watched once the app gets this
+    pub watchers: Vec<Watcher>,
 }
 
 impl DownloadResult {
     pub fn new<S: Into<Option<String>>>(
         success_msg: S,
-        success_ids: Vec<String>,
+        success_ids: Vec<ItemId>,
         errors: Vec<DownloadError>,
         batch: bool,
     ) -> Self {
@@ -61,16 +116,31 @@ impl DownloadResult {
             success_msg: success_msg.into(),
             success_ids,
             batch,
+            batch_id: None,
             errors,
+            warnings: vec![],
+            skipped: vec![],
+            watchers: vec![],
         }
     }
 
+    /// Nothing was sent, since the download was stopped before it started
+    pub fn stopped(items: &[Item], batch: bool) -> Self {
+        let mut res = DownloadResult::new(None, vec![], vec![], batch);
+        res.skipped = items.iter().map(|i| i.id.clone()).collect();
+        res
+    }
+
     pub fn error(error: DownloadError) -> Self {
         DownloadResult {
             success_msg: None,
             success_ids: vec![],
             batch: false,
+            batch_id: None,
             errors: vec![error],
+            warnings: vec![],
+            skipped: vec![],
+            watchers: vec![],
         }
     }
 }
@@ -118,32 +188,123 @@ pub struct ClientConfig {
     pub rqbit: Option<RqbitConfig>,
 }
 
+/// Where a download is saved, how it's labelled and whether it starts
+/// paused, overriding the client's config for one download
+#[derive(Default, Clone, PartialEq, Debug)]
+pub struct DownloadOptions {
+    pub save_path: Option<String>,
+    pub label: Option<String>,
+    pub paused: Option<bool>,
+}
+
+impl DownloadOptions {
+    /// The options `client` would use from `conf`
+    pub fn from_config(client: Client, conf: &ClientConfig) -> Self {
+        match client {
+            Client::Qbit => conf.qbit.as_ref().map(|q| DownloadOptions {
+                save_path: q.savepath.to_owned(),
+                label: q.category.to_owned(),
+                paused: q.paused,
+            }),
+            Client::Transmission => conf.transmission.as_ref().map(|t| DownloadOptions {
+                save_path: t.download_dir.to_owned(),
+                label: t.labels.as_ref().map(|l| l.join(",")),
+                paused: t.paused,
+            }),
+            Client::Rqbit => conf.rqbit.as_ref().map(|r| DownloadOptions {
+                save_path: r.output_folder.to_owned(),
+                ..Default::default()
+            }),
+            Client::Download => conf.download.as_ref().map(|d| DownloadOptions {
+                save_path: Some(d.save_dir.to_owned()),
+                ..Default::default()
+            }),
+            Client::DefaultApp | Client::Cmd => None,
+        }
+        .unwrap_or_default()
+    }
+
+    /// Replace the save path and label `client` uses in `conf`
+    pub fn apply(&self, client: Client, conf: &mut ClientConfig) {
+        match client {
+            Client::Qbit => {
+                let qbit = conf.qbit.get_or_insert_with(Default::default);
+                qbit.savepath.clone_from(&self.save_path);
+                qbit.category.clone_from(&self.label);
+                qbit.paused = self.paused;
+            }
+            Client::Transmission => {
+                let tr = conf.transmission.get_or_insert_with(Default::default);
+                tr.download_dir.clone_from(&self.save_path);
+                tr.labels = self
+                    .label
+                    .as_ref()
+                    .map(|l| l.split(',').map(|s| s.trim().to_owned()).collect());
+                tr.paused = self.paused;
+            }
+            Client::Rqbit => {
+                let rqbit = conf.rqbit.get_or_insert_with(Default::default);
+                rqbit.output_folder.clone_from(&self.save_path);
+            }
+            Client::Download => {
+                if let Some(path) = self.save_path.to_owned() {
+                    conf.download.get_or_insert_with(Default::default).save_dir = path;
+                }
+            }
+            Client::DefaultApp | Client::Cmd => {}
+        }
+    }
+}
+
+/// Download each item on its own, a few at a time, until the batch's `stop` is
+/// cancelled. Items not started by then are returned as skipped.
 pub async fn multidownload<C: DownloadClient, F>(
     success_msg: F,
     items: &[Item],
     conf: &ClientConfig,
     client: &reqwest::Client,
+    batch: Batch,
 ) -> DownloadResult
 where
     F: Fn(usize) -> String,
 {
+    let guard = Arc::new(BatchGuard::new(&batch, items.len()));
+    let permits = Arc::new(Semaphore::new(MAX_CONCURRENT_DOWNLOADS));
     let mut set = JoinSet::new();
     for item in items.iter() {
-        let item = item.to_owned();
-        set.spawn(C::download(item.clone(), conf.clone(), client.clone()));
+        let (item, conf, client) = (item.to_owned(), conf.clone(), client.clone());
+        let (guard, permits) = (guard.clone(), permits.clone());
+        set.spawn(async move {
+            let Ok(_permit) = permits.acquire().await else {
+                return Err(item.id);
+            };
+            // Left in the batch to resume later
+            if guard.is_cancelled() {
+                return Err(item.id);
+            }
+            let res = C::download(item, conf, client).await;
+            guard.finish(res.errors.is_empty());
+            Ok(res)
+        });
     }
     let mut results: Vec<DownloadResult> = vec![];
+    let mut skipped: Vec<ItemId> = vec![];
     while let Some(res) = set.join_next().await {
-        let res = match res {
-            Ok(res) => res,
-            Err(e) => {
-                results.push(DownloadResult::error(DownloadError(e.to_string())));
-                continue;
-            }
-        };
-        results.push(res);
+        match res {
+            Ok(Ok(res)) => results.push(res),
+            Ok(Err(id)) => skipped.push(id),
+            Err(e) => results.push(DownloadResult::error(DownloadError(e.to_string()))),
+        }
     }
 
+    let warnings: Vec<String> = results
+        .iter_mut()
+        .flat_map(|r| std::mem::take(&mut r.warnings))
+        .collect();
+    let watchers: Vec<Watcher> = results
+        .iter_mut()
+        .flat_map(|r| std::mem::take(&mut r.watchers))
+        .collect();
     let (success, failure): (Vec<DownloadResult>, Vec<DownloadResult>) =
         results.into_iter().partition(|d| d.errors.is_empty());
     let success_ids = success.into_iter().fold(vec![], |acc, s| {
@@ -153,10 +314,37 @@ where
         .into_iter()
         .fold(vec![], |acc, s| acc.into_iter().chain(s.errors).collect());
 
-    DownloadResult::new(success_msg(success_ids.len()), success_ids, errors, true)
+    let mut res = DownloadResult::new(success_msg(success_ids.len()), success_ids, errors, true);
+    res.warnings = warnings;
+    res.skipped = skipped;
+    res.watchers = watchers;
+    res
 }
 
 impl Client {
+    /// Whether the client can save to a chosen path
+    pub fn has_save_path(self) -> bool {
+        matches!(
+            self,
+            Client::Qbit | Client::Transmission | Client::Rqbit | Client::Download
+        )
+    }
+
+    /// Whether the client can label or categorize downloads
+    pub fn has_label(self) -> bool {
+        matches!(self, Client::Qbit | Client::Transmission)
+    }
+
+    /// Whether the client can add downloads paused
+    pub fn has_paused(self) -> bool {
+        matches!(self, Client::Qbit | Client::Transmission)
+    }
+
+    /// Whether the client is a torrent client with an API
+    pub fn is_api(self) -> bool {
+        matches!(self, Client::Qbit | Client::Transmission | Client::Rqbit)
+    }
+
     // pub async fn download(&self, item: Item, ctx: &mut Context) {
     //     let conf = ctx.config.client.to_owned();
     //     let timeout = ctx.config.timeout;
@@ -196,14 +384,21 @@ impl Client {
         items: Vec<Item>,
         conf: ClientConfig,
         client: reqwest::Client,
+        batch: Batch,
     ) -> DownloadResult {
         match self {
-            Client::Cmd => CmdClient::batch_download(items, conf, client).await,
-            Client::DefaultApp => DefaultAppClient::batch_download(items, conf, client).await,
-            Client::Download => DownloadFileClient::batch_download(items, conf, client).await,
-            Client::Rqbit => RqbitClient::batch_download(items, conf, client).await,
-            Client::Qbit => QbitClient::batch_download(items, conf, client).await,
-            Client::Transmission => TransmissionClient::batch_download(items, conf, client).await,
+            Client::Cmd => CmdClient::batch_download(items, conf, client, batch).await,
+            Client::DefaultApp => {
+                DefaultAppClient::batch_download(items, conf, client, batch).await
+            }
+            Client::Download => {
+                DownloadFileClient::batch_download(items, conf, client, batch).await
+            }
+            Client::Rqbit => RqbitClient::batch_download(items, conf, client, batch).await,
+            Client::Qbit => QbitClient::batch_download(items, conf, client, batch).await,
+            Client::Transmission => {
+                TransmissionClient::batch_download(items, conf, client, batch).await
+            }
         }
         // let conf = ctx.config.client.to_owned();
         // let timeout = ctx.config.timeout;
@@ -256,6 +451,24 @@ impl Client {
         // ctx.batch.retain(|i| !success_ids.contains(&i.id)); // Remove successes from batch
     }
 
+    /// Check the client can be reached with its config. Clients without an
+    /// API always pass.
+    pub async fn test_connection(
+        self,
+        conf: ClientConfig,
+        client: reqwest::Client,
+    ) -> Result<(), String> {
+        let missing = || format!("No config for {}", self);
+        match self {
+            Self::Qbit => qbit::test_connection(&conf.qbit.ok_or_else(missing)?, &client).await,
+            Self::Transmission => {
+                transmission::test_connection(&conf.transmission.ok_or_else(missing)?, client).await
+            }
+            Self::Rqbit => rqbit::test_connection(&conf.rqbit.ok_or_else(missing)?, &client).await,
+            Self::Cmd | Self::DefaultApp | Self::Download => Ok(()),
+        }
+    }
+
     pub fn load_config(self, ctx: &mut Context) {
         match self {
             Self::Cmd => cmd::load_config(ctx),
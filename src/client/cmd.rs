@@ -2,7 +2,9 @@ use serde::{Deserialize, Serialize};
 
 use crate::{app::Context, source::Item, util::cmd::CommandBuilder};
 
-use super::{multidownload, ClientConfig, DownloadClient, DownloadError, DownloadResult};
+use super::{
+    multidownload, progress::Batch, ClientConfig, DownloadClient, DownloadError, DownloadResult,
+};
 
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(default)]
@@ -64,12 +66,14 @@ impl DownloadClient for CmdClient {
         items: Vec<Item>,
         conf: ClientConfig,
         client: reqwest::Client,
+        batch: Batch,
     ) -> DownloadResult {
         multidownload::<CmdClient, _>(
             |s| format!("Successfully ran command on {} torrents", s),
             &items,
             &conf,
             &client,
+            batch,
         )
         .await
     }
@@ -2,7 +2,9 @@ use serde::{Deserialize, Serialize};
 
 use crate::{app::Context, source::Item};
 
-use super::{multidownload, ClientConfig, DownloadClient, DownloadError, DownloadResult};
+use super::{
+    multidownload, progress::Batch, ClientConfig, DownloadClient, DownloadError, DownloadResult,
+};
 
 #[derive(Serialize, Deserialize, Clone, Default)]
 #[serde(default)]
@@ -50,12 +52,14 @@ impl DownloadClient for DefaultAppClient {
         items: Vec<Item>,
         conf: ClientConfig,
         client: reqwest::Client,
+        batch: Batch,
     ) -> DownloadResult {
         multidownload::<DefaultAppClient, _>(
             |s| format!("Successfully opened {} links in default app", s),
             &items,
             &conf,
             &client,
+            batch,
         )
         .await
     }
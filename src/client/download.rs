@@ -1,17 +1,67 @@
-use std::{error::Error, fs, path::PathBuf};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
 
 use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
 
-use crate::{app::Context, source::Item};
+use crate::{
+    app::Context,
+    error::NyaaError,
+    source::Item,
+    util::{stats, torrent},
+};
 
-use super::{multidownload, ClientConfig, DownloadClient, DownloadError, DownloadResult};
+use super::{
+    multidownload,
+    progress::{Batch, ProgressGuard},
+    ClientConfig, DownloadClient, DownloadError, DownloadResult,
+};
+
+// Longest file name kept before `.torrent`, leaving room for the id added on
+// collisions within the 255 bytes most filesystems allow
+static MAX_STEM_BYTES: usize = 200;
+
+// Names Windows won't create a file with, whatever the extension
+static WINDOWS_RESERVED: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// What `.torrent` files are named after
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum FileNaming {
+    /// The id from the source, like `1234567.torrent`
+    #[default]
+    Id,
+    /// The title of the torrent, with the id added if the name is taken by
+    /// another torrent
+    Title,
+}
+
+/// What to do with a torrent whose info hash matches a file already in the
+/// folder, so watch folders don't add it twice
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Duplicates {
+    /// Don't save it, and say which file it matched
+    #[default]
+    Skip,
+    /// Save it anyway, and say which file it matched
+    Warn,
+    /// Save it without checking
+    Allow,
+}
 
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(default)]
 pub struct DownloadConfig {
-    save_dir: String,
+    pub save_dir: String,
     filename: Option<String>,
+    pub naming: FileNaming,
+    pub duplicates: Duplicates,
 }
 
 pub struct DownloadFileClient;
@@ -28,6 +78,87 @@ impl Default for DownloadConfig {
         DownloadConfig {
             save_dir: download_dir.to_string_lossy().to_string(),
             filename: None,
+            naming: FileNaming::default(),
+            duplicates: Duplicates::default(),
+        }
+    }
+}
+
+/// Replace the characters `name` can't contain as a file name and shorten it.
+/// Windows forbids more characters than other systems, along with device
+/// names like `CON` and trailing dots or spaces.
+pub fn sanitize_filename(name: &str, windows: bool) -> String {
+    let forbidden: &[char] = match windows {
+        true => &['<', '>', ':', '"', '/', '\\', '|', '?', '*'],
+        false => &['/'],
+    };
+    let mut clean: String = name
+        .chars()
+        .map(|c| match c.is_control() || forbidden.contains(&c) {
+            true => '_',
+            false => c,
+        })
+        .collect();
+    truncate_bytes(&mut clean, MAX_STEM_BYTES);
+    if windows {
+        clean.truncate(clean.trim_end_matches(['.', ' ']).len());
+        let base = clean.split('.').next().unwrap_or_default().trim_end();
+        if WINDOWS_RESERVED
+            .iter()
+            .any(|r| r.eq_ignore_ascii_case(base))
+        {
+            clean.insert(0, '_');
+        }
+    }
+    match clean.trim() {
+        "" | "." | ".." => "torrent".to_owned(),
+        _ => clean,
+    }
+}
+
+/// Cut `s` to at most `max` bytes, on a character boundary
+fn truncate_bytes(s: &mut String, max: usize) {
+    if s.len() > max {
+        let mut end = max;
+        while !s.is_char_boundary(end) {
+            end -= 1;
+        }
+        s.truncate(end);
+    }
+}
+
+/// Where `item` with the torrent file `content` is saved in `dir`. Names from
+/// titles which are already taken by another torrent get the id of the item
+/// added, so the same item always gets the same name.
+pub fn torrent_path(item: &Item, conf: &DownloadConfig, dir: &Path, content: &[u8]) -> PathBuf {
+    let windows = cfg!(windows);
+    if let Some(name) = conf.filename.as_ref() {
+        return dir.join(name);
+    }
+    match conf.naming {
+        FileNaming::Id => dir.join(sanitize_filename(&item.file_name, windows)),
+        FileNaming::Title => {
+            let path = dir.join(format!(
+                "{}.torrent",
+                sanitize_filename(&item.title, windows)
+            ));
+            let same = torrent::info_hash(content).is_some_and(|hash| {
+                fs::read(&path).ok().and_then(|d| torrent::info_hash(&d)) == Some(hash)
+            });
+            if !path.exists() || same {
+                return path;
+            }
+            // Cut the title rather than the id, so long titles still get a
+            // name of their own
+            let suffix = format!(" [{}]", item.id);
+            let mut title = item.title.clone();
+            truncate_bytes(&mut title, MAX_STEM_BYTES.saturating_sub(suffix.len()));
+            let unique = dir.join(format!(
+                "{}.torrent",
+                sanitize_filename(&format!("{}{}", title, suffix), windows)
+            ));
+            debug_assert_ne!(unique, path);
+            unique
         }
     }
 }
@@ -39,23 +170,69 @@ pub fn load_config(app: &mut Context) {
     }
 }
 
+/// What happened to a downloaded torrent file
+#[derive(PartialEq, Eq, Debug)]
+pub enum Saved {
+    /// Written to the path
+    New(PathBuf),
+    /// Written to the first path, though the second has the same torrent
+    Duplicate(PathBuf, PathBuf),
+    /// Not written, since the path has the same torrent
+    Skipped(PathBuf),
+}
+
+/// Write the torrent file `content` of `item` into `dir`, unless a file with
+/// the same info hash is already there and `duplicates` says to skip it
+pub fn save_torrent(
+    item: &Item,
+    conf: &DownloadConfig,
+    dir: &Path,
+    content: &[u8],
+) -> std::io::Result<Saved> {
+    let existing = match conf.duplicates {
+        Duplicates::Allow => None,
+        _ => torrent::info_hash(content).and_then(|hash| torrent::find_duplicate(dir, &hash)),
+    };
+    if let (Some(existing), Duplicates::Skip) = (&existing, conf.duplicates) {
+        return Ok(Saved::Skipped(existing.to_owned()));
+    }
+    let path = torrent_path(item, conf, dir, content);
+    fs::write(&path, content)?;
+    Ok(match existing {
+        // Saving the same item again only writes over its own file
+        Some(existing) if existing != path => Saved::Duplicate(path, existing),
+        _ => Saved::New(path),
+    })
+}
+
 async fn download_torrent(
-    torrent_link: String,
-    filename: String,
-    save_dir: String,
+    item: &Item,
+    conf: &DownloadConfig,
     client: reqwest::Client,
-) -> Result<String, Box<dyn Error>> {
-    let response = client.get(torrent_link.to_owned()).send().await?;
+) -> Result<Saved, NyaaError> {
+    let torrent_link = item.torrent_link.to_owned();
+    let mut response = stats::send(client.get(torrent_link.to_owned())).await?;
     if response.status() != StatusCode::OK {
         // Throw error if response code is not OK
         let code = response.status().as_u16();
-        return Err(format!("{}\nInvalid response code: {}", torrent_link, code).into());
+        return Err(NyaaError::Status {
+            url: torrent_link,
+            code,
+        });
+    }
+    let progress = ProgressGuard::new(
+        item.id.to_string(),
+        item.title.to_owned(),
+        response.content_length(),
+    );
+    let mut content = vec![];
+    while let Some(chunk) = response.chunk().await? {
+        progress.add(chunk.len() as u64);
+        stats::record_download(chunk.len() as u64);
+        content.extend_from_slice(&chunk);
     }
-    let content = response.bytes().await?;
-    let mut buf = PathBuf::from(shellexpand::tilde(&save_dir).to_string());
-    buf.push(filename);
-    fs::write(buf.clone(), content)?;
-    Ok(buf.to_string_lossy().to_string())
+    let dir = PathBuf::from(shellexpand::tilde(&conf.save_dir).to_string());
+    Ok(save_torrent(item, conf, &dir, &content)?)
 }
 
 impl DownloadClient for DownloadFileClient {
@@ -69,20 +246,34 @@ impl DownloadClient for DownloadFileClient {
             }
         };
 
-        let filename = conf.filename.unwrap_or(item.file_name.to_owned());
-        let (success_msg, success_ids, errors) = match download_torrent(
-            item.torrent_link.to_owned(),
-            filename,
-            conf.save_dir.clone(),
-            client,
-        )
-        .await
+        let mut warnings = vec![];
+        let (success_msg, success_ids, errors) = match download_torrent(&item, &conf, client).await
         {
-            Ok(path) => (
-                Some(format!("Saved to \"{}\"", path)),
+            Ok(Saved::New(path)) => (
+                Some(format!("Saved to \"{}\"", path.display())),
                 vec![item.id],
                 vec![],
             ),
+            Ok(Saved::Duplicate(path, existing)) => {
+                warnings.push(format!(
+                    "\"{}\" was already saved as \"{}\"",
+                    item.title,
+                    existing.display()
+                ));
+                (
+                    Some(format!("Saved to \"{}\"", path.display())),
+                    vec![item.id],
+                    vec![],
+                )
+            }
+            Ok(Saved::Skipped(existing)) => {
+                warnings.push(format!(
+                    "Skipped \"{}\", it is already saved as \"{}\"",
+                    item.title,
+                    existing.display()
+                ));
+                (None, vec![item.id], vec![])
+            }
             Err(e) => (
                 None,
                 vec![],
@@ -96,13 +287,16 @@ impl DownloadClient for DownloadFileClient {
                 )],
             ),
         };
-        DownloadResult::new(success_msg, success_ids, errors, false)
+        let mut res = DownloadResult::new(success_msg, success_ids, errors, false);
+        res.warnings = warnings;
+        res
     }
 
     async fn batch_download(
         items: Vec<Item>,
         conf: ClientConfig,
         client: reqwest::Client,
+        batch: Batch,
     ) -> DownloadResult {
         let save_dir = conf.download.clone().unwrap_or_default().save_dir.clone();
         multidownload::<DownloadFileClient, _>(
@@ -110,6 +304,7 @@ impl DownloadClient for DownloadFileClient {
             &items,
             &conf,
             &client,
+            batch,
         )
         .await
     }
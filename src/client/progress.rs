@@ -0,0 +1,255 @@
+use std::{collections::BTreeMap, sync::Mutex, time::Duration};
+
+use tokio_util::sync::CancellationToken;
+
+use crate::util::conv::to_size;
+
+/// How often, and how many times, to check a torrent once added to a client
+pub static POLL_INTERVAL: Duration = Duration::from_secs(2);
+pub static POLL_TIMES: usize = 5;
+
+/// Bytes received so far for a torrent file being downloaded, or for a
+/// torrent which was just added to a client
+#[derive(Clone)]
+pub struct Progress {
+    pub name: String,
+    pub received: u64,
+    pub total: Option<u64>,
+    /// Bytes per second, as reported by the client
+    pub speed: Option<u64>,
+}
+
+// Keyed by item id
+static PROGRESS: Mutex<BTreeMap<String, Progress>> = Mutex::new(BTreeMap::new());
+
+/// Tracks a download until dropped
+pub struct ProgressGuard {
+    id: String,
+}
+
+impl ProgressGuard {
+    pub fn new(id: String, name: String, total: Option<u64>) -> Self {
+        if let Ok(mut map) = PROGRESS.lock() {
+            map.insert(
+                id.clone(),
+                Progress {
+                    name,
+                    received: 0,
+                    total,
+                    speed: None,
+                },
+            );
+        }
+        ProgressGuard { id }
+    }
+
+    pub fn add(&self, bytes: u64) {
+        if let Some(p) = PROGRESS
+            .lock()
+            .ok()
+            .as_mut()
+            .and_then(|m| m.get_mut(&self.id))
+        {
+            p.received += bytes;
+        }
+    }
+
+    /// Replace the progress with what a client reported
+    pub fn set(&self, received: u64, total: u64, speed: u64) {
+        if let Some(p) = PROGRESS
+            .lock()
+            .ok()
+            .as_mut()
+            .and_then(|m| m.get_mut(&self.id))
+        {
+            p.received = received;
+            p.total = Some(total);
+            p.speed = Some(speed);
+        }
+    }
+}
+
+impl Drop for ProgressGuard {
+    fn drop(&mut self) {
+        if let Ok(mut map) = PROGRESS.lock() {
+            map.remove(&self.id);
+        }
+    }
+}
+
+/// How many torrents of the batch being downloaded are done
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct BatchProgress {
+    pub total: usize,
+    pub fetched: usize,
+    pub failed: usize,
+}
+
+impl BatchProgress {
+    pub fn ratio(&self) -> f64 {
+        match self.total {
+            0 => 1.0,
+            total => ((self.fetched + self.failed) as f64 / total as f64).min(1.0),
+        }
+    }
+}
+
+/// A batch being downloaded, told apart from other batches by its id, which
+/// is also the id it's saved under in the state file
+#[derive(Clone)]
+pub struct Batch {
+    pub id: usize,
+    /// Cancelled to stop the batch before its next torrent
+    pub stop: CancellationToken,
+}
+
+// Keyed by batch id
+static BATCHES: Mutex<BTreeMap<usize, (BatchProgress, CancellationToken)>> =
+    Mutex::new(BTreeMap::new());
+
+/// How many of the items being resolved are done, out of how many
+static RESOLVING: Mutex<Option<(usize, usize)>> = Mutex::new(None);
+
+/// Tracks a batch download until dropped
+pub struct BatchGuard {
+    id: usize,
+    cancel: CancellationToken,
+}
+
+impl BatchGuard {
+    /// Track `batch`, which has `total` torrents
+    pub fn new(batch: &Batch, total: usize) -> Self {
+        if let Ok(mut batches) = BATCHES.lock() {
+            let progress = BatchProgress {
+                total,
+                ..Default::default()
+            };
+            batches.insert(batch.id, (progress, batch.stop.clone()));
+        }
+        BatchGuard {
+            id: batch.id,
+            cancel: batch.stop.clone(),
+        }
+    }
+
+    /// Count one torrent as done, whether or not it failed
+    pub fn finish(&self, ok: bool) {
+        if let Some((p, _)) = BATCHES
+            .lock()
+            .ok()
+            .as_mut()
+            .and_then(|b| b.get_mut(&self.id))
+        {
+            match ok {
+                true => p.fetched += 1,
+                false => p.failed += 1,
+            }
+        }
+    }
+
+    /// Whether [`cancel_batches`] has been called or the batch's token was
+    /// cancelled since it started
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+}
+
+impl Drop for BatchGuard {
+    fn drop(&mut self) {
+        if let Ok(mut batches) = BATCHES.lock() {
+            batches.remove(&self.id);
+        }
+    }
+}
+
+/// Tracks items having their links resolved before a download until dropped
+pub struct ResolveGuard;
+
+impl ResolveGuard {
+    pub fn new(total: usize) -> Self {
+        if let Ok(mut resolving) = RESOLVING.lock() {
+            *resolving = Some((0, total));
+        }
+        ResolveGuard
+    }
+
+    pub fn finish(&self) {
+        if let Some((done, _)) = RESOLVING.lock().ok().as_mut().and_then(|r| r.as_mut()) {
+            *done += 1;
+        }
+    }
+}
+
+impl Drop for ResolveGuard {
+    fn drop(&mut self) {
+        if let Ok(mut resolving) = RESOLVING.lock() {
+            *resolving = None;
+        }
+    }
+}
+
+/// How many of the items being resolved are done, and out of how many
+pub fn resolve_progress() -> Option<(usize, usize)> {
+    *RESOLVING.lock().ok()?
+}
+
+/// Progress of the batch `id`, if it's being downloaded
+pub fn batch_progress(id: usize) -> Option<BatchProgress> {
+    BATCHES.lock().ok()?.get(&id).map(|(p, _)| *p)
+}
+
+/// Progress of every batch being downloaded added together, if there are any
+pub fn total_batch_progress() -> Option<BatchProgress> {
+    let batches = BATCHES.lock().ok()?;
+    batches
+        .values()
+        .map(|(p, _)| *p)
+        .reduce(|a, b| BatchProgress {
+            total: a.total + b.total,
+            fetched: a.fetched + b.fetched,
+            failed: a.failed + b.failed,
+        })
+}
+
+/// Stop every batch download once the torrents being fetched are done,
+/// leaving the rest in the batch
+pub fn cancel_batches() {
+    if let Ok(batches) = BATCHES.lock() {
+        batches.values().for_each(|(_, cancel)| cancel.cancel());
+    }
+}
+
+/// Whether anything is being downloaded or watched
+pub fn is_active() -> bool {
+    PROGRESS.lock().is_ok_and(|m| !m.is_empty()) || resolve_progress().is_some()
+}
+
+/// One line per download in progress, or `None` if nothing is downloading
+pub fn summary() -> Option<String> {
+    let map = PROGRESS.lock().ok()?;
+    let resolving =
+        resolve_progress().map(|(done, total)| format!("Resolving links {}/{}", done, total));
+    if map.is_empty() && resolving.is_none() {
+        return None;
+    }
+    let lines = resolving
+        .into_iter()
+        .chain(map.values().map(|p| match (p.total, p.speed) {
+            (Some(total), Some(speed)) if total > 0 => format!(
+                "{}% @ {}/s {}",
+                p.received * 100 / total,
+                to_size(speed),
+                p.name
+            ),
+            (Some(total), None) if total > 0 => format!(
+                "{}% ({}/{}) {}",
+                p.received * 100 / total,
+                to_size(p.received),
+                to_size(total),
+                p.name
+            ),
+            _ => format!("{} {}", to_size(p.received), p.name),
+        }))
+        .collect::<Vec<String>>();
+    Some(lines.join("\n"))
+}
@@ -5,17 +5,27 @@ use reqwest::{
     Response, StatusCode,
 };
 use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
 
-use crate::{app::Context, source::Item, util::conv::add_protocol};
+use crate::{
+    app::Context,
+    config::secret,
+    error::NyaaError,
+    source::Item,
+    util::conv::{add_protocol, info_hash},
+};
 
-use super::{ClientConfig, DownloadClient, DownloadError, DownloadResult};
+use super::{
+    progress::{Batch, ProgressGuard, POLL_INTERVAL, POLL_TIMES},
+    ClientConfig, DownloadClient, DownloadError, DownloadResult, Watcher,
+};
 
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(default)]
 pub struct QbitConfig {
     pub base_url: String,
     pub username: String,
-    pub password: String, // TODO: introduce password_env and password_cmd for retreiving
+    pub password: String,
     pub use_magnet: Option<bool>,
     pub savepath: Option<String>,
     pub category: Option<String>,  // Single category
@@ -114,27 +124,35 @@ struct QbitForm {
     // rename: String // Disabled
 }
 
-async fn login(qbit: &QbitConfig, client: &reqwest::Client) -> Result<String, String> {
+async fn login(qbit: &QbitConfig, client: &reqwest::Client) -> Result<String, NyaaError> {
     let base_url = add_protocol(qbit.base_url.clone(), false);
     let url = format!("{}/api/v2/auth/login", base_url);
     let mut params = HashMap::new();
-    params.insert("username", qbit.username.to_owned());
-    params.insert("password", qbit.password.to_owned());
+    for (key, value) in [("username", &qbit.username), ("password", &qbit.password)] {
+        let value =
+            secret::resolve_named(&format!("qBittorrent.{}", key), Some(value)).map_err(|e| {
+                NyaaError::Client(format!("Failed to read the qBittorrent {}:\n{}", key, e))
+            })?;
+        params.insert(key, value.unwrap_or_default());
+    }
     let res = client.post(url).form(&params).send().await;
-    let res = res.map_err(|e| format!("Failed to send data to qBittorrent\n{}", e))?;
+    let res =
+        res.map_err(|e| NyaaError::Client(format!("Failed to send data to qBittorrent\n{}", e)))?;
     let headers = res.headers().clone();
-    let cookie = headers.get(SET_COOKIE).ok_or(format!(
+    let cookie = headers.get(SET_COOKIE).ok_or(NyaaError::Client(format!(
         "Failed to get cookie from qBittorrent:\n{}",
         res.text().await.unwrap_or_default()
-    ))?;
+    )))?;
 
     let cookie = cookie
         .to_str()
-        .map_err(|e| format!("Failed to parse cookie:\n{}", e))?;
+        .map_err(|e| NyaaError::Client(format!("Failed to parse cookie:\n{}", e)))?;
     cookie
         .split(';')
         .find(|c| c.split_once('=').is_some_and(|s| s.0 == "SID"))
-        .ok_or("No cookie returned by qBittorrent".to_owned())
+        .ok_or(NyaaError::Client(
+            "No cookie returned by qBittorrent".to_owned(),
+        ))
         .map(|s| s.to_owned())
 }
 
@@ -148,6 +166,13 @@ async fn logout(qbit: &QbitConfig, sid: String, client: &reqwest::Client) {
         .await;
 }
 
+/// Log in and out again, to check qBittorrent can be reached with the config
+pub async fn test_connection(qbit: &QbitConfig, client: &reqwest::Client) -> Result<(), String> {
+    let sid = login(qbit, client).await.map_err(|e| e.to_string())?;
+    logout(qbit, sid, client).await;
+    Ok(())
+}
+
 async fn add_torrent(
     qbit: &QbitConfig,
     sid: String,
@@ -166,6 +191,63 @@ async fn add_torrent(
         .await
 }
 
+#[derive(Deserialize)]
+struct QbitTorrentInfo {
+    hash: String,
+    progress: f64,
+    dlspeed: u64,
+    size: u64,
+}
+
+/// Show the progress of newly added torrents for a short while, then logout
+async fn watch_progress(
+    qbit: QbitConfig,
+    sid: String,
+    guards: Vec<(String, ProgressGuard)>,
+    client: reqwest::Client,
+    stop: CancellationToken,
+) {
+    let base_url = add_protocol(qbit.base_url.clone(), false);
+    let hashes = guards
+        .iter()
+        .map(|(h, _)| h.as_str())
+        .collect::<Vec<&str>>()
+        .join("|");
+    let url = format!("{}/api/v2/torrents/info?hashes={}", base_url, hashes);
+    for _ in 0..POLL_TIMES {
+        // Stopped early, but still logged out below
+        tokio::select! {
+            () = tokio::time::sleep(POLL_INTERVAL) => {}
+            () = stop.cancelled() => break,
+        }
+        let res = client
+            .get(&url)
+            .header(REFERER, &base_url)
+            .header(COOKIE, &sid)
+            .send()
+            .await;
+        let Ok(infos) = (match res {
+            Ok(res) => res.json::<Vec<QbitTorrentInfo>>().await,
+            Err(e) => Err(e),
+        }) else {
+            break;
+        };
+        for info in infos.iter() {
+            if let Some((_, guard)) = guards.iter().find(|(h, _)| *h == info.hash) {
+                guard.set(
+                    (info.size as f64 * info.progress) as u64,
+                    info.size,
+                    info.dlspeed,
+                );
+            }
+        }
+        if infos.len() == guards.len() && infos.iter().all(|i| i.progress >= 1.0) {
+            break;
+        }
+    }
+    logout(&qbit, sid, &client).await;
+}
+
 pub fn load_config(app: &mut Context) {
     if app.config.client.qbit.is_none() {
         app.config.client.qbit = Some(QbitConfig::default());
@@ -174,7 +256,7 @@ pub fn load_config(app: &mut Context) {
 
 impl DownloadClient for QbitClient {
     async fn download(item: Item, conf: ClientConfig, client: reqwest::Client) -> DownloadResult {
-        let mut res = Self::batch_download(vec![item], conf, client).await;
+        let mut res = send(vec![item], conf, client, CancellationToken::new()).await;
         res.success_msg = Some("Successfully sent torrent to qBittorrent".to_string());
         res.batch = false;
         res
@@ -184,62 +266,89 @@ impl DownloadClient for QbitClient {
         items: Vec<Item>,
         conf: ClientConfig,
         client: reqwest::Client,
+        batch: Batch,
     ) -> DownloadResult {
-        // return DownloadResult::error(DownloadError("Failed to login :\\"));
-        let Some(qbit) = conf.qbit.to_owned() else {
+        send(items, conf, client, batch.stop).await
+    }
+}
+
+/// Add every item with one request, unless `stop` is cancelled first
+async fn send(
+    items: Vec<Item>,
+    conf: ClientConfig,
+    client: reqwest::Client,
+    stop: CancellationToken,
+) -> DownloadResult {
+    // return DownloadResult::error(DownloadError("Failed to login :\\"));
+    let Some(qbit) = conf.qbit.to_owned() else {
+        return DownloadResult::error(DownloadError("Failed to get qBittorrent config".to_owned()));
+    };
+    if let Some(labels) = qbit.tags.clone() {
+        if let Some(bad) = labels.iter().find(|l| l.contains(',')) {
+            let bad = format!("\"{}\"", bad);
             return DownloadResult::error(DownloadError(
-                "Failed to get qBittorrent config".to_owned(),
+                format!("qBittorrent tags must not contain commas:\n{}", bad).to_owned(),
             ));
-        };
-        if let Some(labels) = qbit.tags.clone() {
-            if let Some(bad) = labels.iter().find(|l| l.contains(',')) {
-                let bad = format!("\"{}\"", bad);
-                return DownloadResult::error(DownloadError(
-                    format!("qBittorrent tags must not contain commas:\n{}", bad).to_owned(),
-                ));
-            }
         }
-        let sid = match login(&qbit, &client).await {
-            Ok(s) => s,
-            Err(e) => {
-                return DownloadResult::error(DownloadError(format!("Failed to get SID:\n{}", e)))
-            }
-        };
-        let links = match qbit.use_magnet.unwrap_or(true) {
-            true => items
-                .iter()
-                .map(|i| i.magnet_link.to_owned())
-                .collect::<Vec<String>>()
-                .join("\n"),
-            false => items
-                .iter()
-                .map(|i| i.torrent_link.to_owned())
-                .collect::<Vec<String>>()
-                .join("\n"),
-        };
-        let res = match add_torrent(&qbit, sid.to_owned(), links, &client).await {
-            Ok(res) => res,
-            Err(e) => {
-                return DownloadResult::error(DownloadError(format!(
-                    "Failed to get response:\n{}",
-                    e
-                )))
-            }
-        };
-        if res.status() != StatusCode::OK {
-            return DownloadResult::error(DownloadError(format!(
-                "qBittorrent returned status code {}",
-                res.status().as_u16()
-            )));
+    }
+    let sid = match login(&qbit, &client).await {
+        Ok(s) => s,
+        Err(e) => {
+            return DownloadResult::error(DownloadError(format!("Failed to get SID:\n{}", e)))
         }
+    };
+    let links = match qbit.use_magnet.unwrap_or(true) {
+        true => items
+            .iter()
+            .map(|i| i.magnet_link.to_owned())
+            .collect::<Vec<String>>()
+            .join("\n"),
+        false => items
+            .iter()
+            .map(|i| i.torrent_link.to_owned())
+            .collect::<Vec<String>>()
+            .join("\n"),
+    };
+    // Every torrent is added with one request, so the batch is either sent
+    // whole or not at all
+    if stop.is_cancelled() {
+        logout(&qbit, sid, &client).await;
+        return DownloadResult::stopped(&items, true);
+    }
+    let res = match add_torrent(&qbit, sid.to_owned(), links, &client).await {
+        Ok(res) => res,
+        Err(e) => {
+            return DownloadResult::error(DownloadError(format!("Failed to get response:\n{}", e)))
+        }
+    };
+    if res.status() != StatusCode::OK {
+        return DownloadResult::error(DownloadError(format!(
+            "qBittorrent returned status code {}",
+            res.status().as_u16()
+        )));
+    }
 
-        logout(&qbit, sid.clone(), &client).await;
-
-        DownloadResult::new(
-            format!("Successfully sent {} torrents to qBittorrent", items.len()),
-            items.into_iter().map(|i| i.id).collect(),
-            vec![],
-            true,
-        )
+    let guards: Vec<(String, ProgressGuard)> = items
+        .iter()
+        .filter_map(|i| {
+            let hash = info_hash(&i.magnet_link)?;
+            let guard = ProgressGuard::new(format!("qbit-{}", hash), i.title.to_owned(), None);
+            Some((hash, guard))
+        })
+        .collect();
+    let mut res = DownloadResult::new(
+        format!("Successfully sent {} torrents to qBittorrent", items.len()),
+        items.into_iter().map(|i| i.id).collect(),
+        vec![],
+        true,
+    );
+    match guards.is_empty() {
+        true => logout(&qbit, sid, &client).await,
+        false => res
+            .watchers
+            .push(Watcher::new("Watch qBittorrent progress", move |stop| {
+                watch_progress(qbit, sid, guards, client, stop)
+            })),
     }
+    res
 }
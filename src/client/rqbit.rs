@@ -1,12 +1,12 @@
-use std::error::Error;
-
 use reqwest::{Response, StatusCode, Url};
 use serde::{Deserialize, Serialize};
 use urlencoding::encode;
 
-use crate::{app::Context, source::Item, util::conv::add_protocol};
+use crate::{app::Context, error::NyaaError, source::Item, util::conv::add_protocol};
 
-use super::{multidownload, ClientConfig, DownloadClient, DownloadError, DownloadResult};
+use super::{
+    multidownload, progress::Batch, ClientConfig, DownloadClient, DownloadError, DownloadResult,
+};
 
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(default)]
@@ -40,7 +40,7 @@ async fn add_torrent(
     conf: &RqbitConfig,
     link: String,
     client: &reqwest::Client,
-) -> Result<Response, Box<dyn Error>> {
+) -> Result<Response, NyaaError> {
     let base_url = add_protocol(conf.base_url.clone(), false);
     let mut url = Url::parse(&base_url)?.join("/torrents")?;
     let mut query: Vec<String> = vec![];
@@ -58,6 +58,24 @@ async fn add_torrent(
     }
 }
 
+/// Check rqbit's API can be reached with the config
+pub async fn test_connection(conf: &RqbitConfig, client: &reqwest::Client) -> Result<(), String> {
+    let base_url = add_protocol(conf.base_url.clone(), false);
+    let res = client
+        .get(&base_url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to connect to rqbit:\n{}", e))?;
+    match res.status().is_success() {
+        true => Ok(()),
+        false => Err(format!(
+            "{}\nInvalid response code: {}",
+            base_url,
+            res.status()
+        )),
+    }
+}
+
 pub fn load_config(app: &mut Context) {
     if app.config.client.rqbit.is_none() {
         app.config.client.rqbit = Some(RqbitConfig::default());
@@ -104,12 +122,14 @@ impl DownloadClient for RqbitClient {
         items: Vec<Item>,
         conf: ClientConfig,
         client: reqwest::Client,
+        batch: Batch,
     ) -> DownloadResult {
         multidownload::<RqbitClient, _>(
             |s| format!("Successfully sent {} torrents to rqbit", s),
             &items,
             &conf,
             &client,
+            batch,
         )
         .await
     }
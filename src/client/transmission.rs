@@ -1,13 +1,23 @@
 use reqwest::Url;
 use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
 use transmission_rpc::{
-    types::{BasicAuth, TorrentAddArgs},
+    types::{BasicAuth, Id, TorrentAddArgs, TorrentGetField},
     TransClient,
 };
 
-use crate::{app::Context, source::Item, util::conv::add_protocol};
+use crate::{
+    app::Context,
+    config::secret,
+    source::Item,
+    util::conv::{add_protocol, info_hash},
+};
 
-use super::{multidownload, ClientConfig, DownloadClient, DownloadError, DownloadResult};
+use super::{
+    multidownload,
+    progress::{Batch, ProgressGuard, POLL_INTERVAL, POLL_TIMES},
+    ClientConfig, DownloadClient, DownloadError, DownloadResult, Watcher,
+};
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(i8)]
@@ -63,20 +73,41 @@ impl TransmissionConfig {
     }
 }
 
-async fn add_torrent(
-    conf: &TransmissionConfig,
-    link: String,
-    client: reqwest::Client,
-) -> Result<(), String> {
+fn rpc_client(conf: &TransmissionConfig, client: reqwest::Client) -> Result<TransClient, String> {
     let base_url = add_protocol(conf.base_url.clone(), false);
     let url = match base_url.parse::<Url>() {
         Ok(url) => url,
         Err(e) => return Err(format!("Failed to parse base_url \"{}\":\n{}", base_url, e)),
     };
     let mut client = TransClient::new_with_client(url.to_owned(), client);
-    if let (Some(user), Some(password)) = (conf.username.clone(), conf.password.clone()) {
+    let user = secret::resolve_named("transmission.username", conf.username.as_deref())
+        .map_err(|e| format!("Failed to read the Transmission username:\n{}", e))?;
+    let password = secret::resolve_named("transmission.password", conf.password.as_deref())
+        .map_err(|e| format!("Failed to read the Transmission password:\n{}", e))?;
+    if let (Some(user), Some(password)) = (user, password) {
         client.set_auth(BasicAuth { user, password });
     }
+    Ok(client)
+}
+
+/// Ask for the session, to check Transmission can be reached with the config
+pub async fn test_connection(
+    conf: &TransmissionConfig,
+    client: reqwest::Client,
+) -> Result<(), String> {
+    let mut client = rpc_client(conf, client)?;
+    match client.session_get().await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(format!("Failed to connect to Transmission:\n{}", e)),
+    }
+}
+
+async fn add_torrent(
+    conf: &TransmissionConfig,
+    link: String,
+    client: reqwest::Client,
+) -> Result<(), String> {
+    let mut client = rpc_client(conf, client)?;
     let add = conf.clone().to_form(link);
     match client.torrent_add(add).await {
         Ok(_) => Ok(()),
@@ -84,6 +115,46 @@ async fn add_torrent(
     }
 }
 
+/// Show the progress of a newly added torrent for a short while
+async fn watch_progress(
+    conf: TransmissionConfig,
+    hash: String,
+    guard: ProgressGuard,
+    client: reqwest::Client,
+    stop: CancellationToken,
+) {
+    let Ok(mut client) = rpc_client(&conf, client) else {
+        return;
+    };
+    let fields = vec![
+        TorrentGetField::PercentDone,
+        TorrentGetField::RateDownload,
+        TorrentGetField::TotalSize,
+    ];
+    for _ in 0..POLL_TIMES {
+        tokio::select! {
+            () = tokio::time::sleep(POLL_INTERVAL) => {}
+            () = stop.cancelled() => break,
+        }
+        let Ok(res) = client
+            .torrent_get(Some(fields.clone()), Some(vec![Id::Hash(hash.clone())]))
+            .await
+        else {
+            break;
+        };
+        let Some(torrent) = res.arguments.torrents.first() else {
+            continue;
+        };
+        let total = torrent.total_size.unwrap_or(0).max(0) as u64;
+        let done = torrent.percent_done.unwrap_or(0.0);
+        let speed = torrent.rate_download.unwrap_or(0).max(0) as u64;
+        guard.set((total as f64 * done as f64) as u64, total, speed);
+        if done >= 1.0 {
+            break;
+        }
+    }
+}
+
 pub fn load_config(app: &mut Context) {
     if app.config.client.transmission.is_none() {
         app.config.client.transmission = Some(TransmissionConfig::default());
@@ -112,27 +183,41 @@ impl DownloadClient for TransmissionClient {
             None | Some(true) => item.magnet_link.to_owned(),
             Some(false) => item.torrent_link.to_owned(),
         };
-        if let Err(e) = add_torrent(&conf, link, client).await {
+        if let Err(e) = add_torrent(&conf, link, client.clone()).await {
             return DownloadResult::error(DownloadError(e.to_string()));
         }
-        DownloadResult::new(
+        let mut res = DownloadResult::new(
             "Successfully sent torrent to Transmission".to_owned(),
             vec![item.id],
             vec![],
             false,
-        )
+        );
+        if let Some(hash) = info_hash(&item.magnet_link) {
+            let guard = ProgressGuard::new(
+                format!("transmission-{}", hash),
+                item.title.to_owned(),
+                None,
+            );
+            res.watchers.push(Watcher::new(
+                format!("Watch \"{}\" in Transmission", item.title),
+                move |stop| watch_progress(conf, hash, guard, client, stop),
+            ));
+        }
+        res
     }
 
     async fn batch_download(
         items: Vec<Item>,
         conf: ClientConfig,
         client: reqwest::Client,
+        batch: Batch,
     ) -> DownloadResult {
         multidownload::<TransmissionClient, _>(
             |s| format!("Successfully sent {} torrents to rqbit", s),
             &items,
             &conf,
             &client,
+            batch,
         )
         .await
     }
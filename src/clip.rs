@@ -1,5 +1,3 @@
-use std::error::Error;
-
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -26,17 +24,15 @@ use cli_clipboard::{
 #[cfg(not(target_os = "linux"))]
 use cli_clipboard::ClipboardContext;
 
-use crate::util::cmd::CommandBuilder;
+use crate::{error::NyaaError, util::cmd::CommandBuilder};
 
-pub fn copy_to_clipboard(
-    link: String,
-    conf: Option<ClipboardConfig>,
-) -> Result<(), Box<dyn Error>> {
+pub fn copy_to_clipboard(link: String, conf: Option<ClipboardConfig>) -> Result<(), NyaaError> {
     if let Some(conf) = conf.to_owned() {
         if let Some(cmd) = conf.cmd {
             return CommandBuilder::new(cmd)
                 .sub("{content}", &link)
-                .run(conf.shell_cmd);
+                .run(conf.shell_cmd)
+                .map_err(|e| NyaaError::Clipboard(e.to_string()));
         }
     }
 
@@ -45,19 +41,23 @@ pub fn copy_to_clipboard(
         match conf.and_then(|sel| sel.x11_selection) {
             Some(X11Selection::Primary) => X11ClipboardContext::<Primary>::new()
                 .and_then(|mut s| s.set_contents(link))
-                .map_err(|e| format!("Failed to copy to x11 \"primary\":\n{}", e).into()),
+                .map_err(|e| {
+                    NyaaError::Clipboard(format!("Failed to copy to x11 \"primary\":\n{}", e))
+                }),
             Some(X11Selection::Clipboard) => X11ClipboardContext::<Clipboard>::new()
                 .and_then(|mut s| s.set_contents(link))
-                .map_err(|e| format!("Failed to copy to x11 \"clipboard\":\n{}", e).into()),
+                .map_err(|e| {
+                    NyaaError::Clipboard(format!("Failed to copy to x11 \"clipboard\":\n{}", e))
+                }),
             None => LinuxClipboardContext::new()
                 .and_then(|mut s| s.set_contents(link))
-                .map_err(|e| format!("Failed to copy to clipboard:\n{}", e).into()),
+                .map_err(|e| NyaaError::Clipboard(format!("Failed to copy to clipboard:\n{}", e))),
         }
     }
     #[cfg(not(target_os = "linux"))]
     {
         ClipboardContext::new()
             .and_then(|mut s| s.set_contents(link))
-            .map_err(|e| format!("Failed to copy to clipboard:\n{}", e).into())
+            .map_err(|e| NyaaError::Clipboard(format!("Failed to copy to clipboard:\n{}", e)))
     }
 }
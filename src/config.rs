@@ -1,4 +1,5 @@
 use std::{
+    collections::BTreeMap,
     error::Error,
     fs::{self, File, OpenOptions},
     io::{ErrorKind, Read, Write as _},
@@ -6,29 +7,81 @@ use std::{
 };
 
 use crate::{
-    app::{Context, Widgets, APP_NAME},
+    app::{Context, Mode, Widgets, APP_NAME},
     client::{Client, ClientConfig},
     clip::ClipboardConfig,
+    locale::Locale,
+    results::QuickFilter,
     source::{SourceConfig, Sources},
     theme::{self, Theme},
-    widget::notifications::NotificationConfig,
+    util::{
+        conv::{self, SizeFormat},
+        crash,
+        webhook::WebhookConfig,
+    },
+    widget::{
+        combo::{ChordAction, Chords},
+        details::LayoutConfig,
+        notifications::NotificationConfig,
+        user::SearchPreset,
+    },
 };
 use directories::ProjectDirs;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
+use self::{
+    state::{State, STATE_FILE},
+    validate::ConfigReport,
+};
+
+pub mod migrate;
+pub mod secret;
+pub mod state;
+pub mod validate;
+
 pub trait ConfigManager {
     fn load() -> Result<Config, Box<dyn Error>>;
     fn store(cfg: &Config) -> Result<(), Box<dyn Error>>;
     fn path() -> Result<PathBuf, Box<dyn Error>>;
+    /// Problems in the config which don't stop it from loading
+    fn check() -> ConfigReport {
+        ConfigReport::default()
+    }
+    /// What's kept between sessions apart from the config
+    fn load_state() -> Result<State, Box<dyn Error>> {
+        Ok(State::default())
+    }
+    fn store_state(_state: &State) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+    /// Upgrade a config written by an older version, returning what changed
+    fn migrate() -> Result<Vec<String>, Box<dyn Error>> {
+        Ok(vec![])
+    }
 }
 
 pub struct AppConfig;
 
 pub static CONFIG_FILE: &str = "config";
 
+/// What to do once the app has opened
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum OnStartup {
+    /// Search with the default search of the source
+    #[default]
+    Search,
+    /// Search with the saved preset of this name
+    Preset(String),
+    /// Wait for a search to be entered
+    Idle,
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(default)]
 pub struct Config {
+    /// Format the config was written in, used to upgrade older configs
+    pub version: u32,
     #[serde(alias = "default_theme")]
     pub theme: String,
     #[serde(rename = "default_source")]
@@ -39,7 +92,69 @@ pub struct Config {
     pub timeout: u64,
     pub scroll_padding: usize,
     pub save_config_on_change: bool,
+    /// Search while typing, once no key has been pressed for `live_search_delay` ms
+    pub live_search: bool,
+    pub live_search_delay: u64,
+    pub on_startup: OnStartup,
+    /// Show the most useful keybinds for the current mode at the bottom
+    pub hint_bar: bool,
+    /// List the keys which can finish a pending combo like `y`, once no key
+    /// has been pressed for `combo_popup_delay` ms
+    pub combo_popup: bool,
+    pub combo_popup_delay: u64,
+    /// Key which starts the chords written with `<leader>`
+    pub leader: Option<char>,
+    /// Key sequences pressed in the results, each mapped to an action
+    pub chords: BTreeMap<String, ChordAction>,
+    /// Show a summary of a batch download before it starts
+    pub confirm_batch_download: bool,
+    /// Ask for a save path, label and paused flag before each download with
+    /// qBittorrent, Transmission or rqbit
+    pub prompt_download_options: bool,
+    /// Check torrent links once results load, dimming any which are dead
+    pub validate_links: bool,
+    /// Most pages `bA` will search when adding every result to the batch
+    pub batch_page_limit: usize,
+    /// Show the search and page in the terminal's window title
+    pub window_title: bool,
+    /// Ring the terminal bell once a batch download finishes
+    pub batch_bell: bool,
+    /// Focus the pane which is clicked
+    pub mouse: bool,
+    /// Check GitHub on startup for a newer release, and notify if there is one
+    pub check_for_updates: bool,
+    /// Mark trusted and remake titles with `[T]` and `[R]` in every theme,
+    /// not only those which can't show them by color
+    pub text_markers: bool,
+    /// Hide remakes from the results of every source
+    pub hide_remakes: bool,
+    /// Only show trusted results from every source
+    pub trusted_only: bool,
+    /// Search as soon as another source is picked, with its `default_search`
+    /// if it has one. Otherwise it's typed into the search box to be run.
+    pub search_on_source_change: bool,
+    /// Pages `D` searches at once
+    pub deep_search_pages: usize,
+    /// Most results kept in memory for going back and forward, counting
+    /// those shown
+    pub max_kept_results: usize,
+    /// Opens the captcha image with `{file}` replaced by its path, instead of
+    /// the default app
+    pub captcha_command: Option<String>,
+    /// Where `e` and `E` write pages with the links in the batch, the
+    /// downloads folder if unset
+    pub batch_export_dir: Option<String>,
+    /// Language for titles, help and notifications
+    pub locale: Locale,
+    /// Read client credentials from the OS keyring first, where they're
+    /// stored with `nyaa secret set <name>`
+    pub use_keyring: bool,
 
+    #[serde(rename = "layout")]
+    pub layout: LayoutConfig,
+    /// Units and decimals sizes are shown with
+    #[serde(rename = "size_format")]
+    pub size_format: SizeFormat,
     #[serde(rename = "notifications")]
     pub notifications: Option<NotificationConfig>,
     #[serde(rename = "clipboard")]
@@ -48,11 +163,17 @@ pub struct Config {
     pub client: ClientConfig,
     #[serde(rename = "source")]
     pub sources: SourceConfig,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub presets: Vec<SearchPreset>,
+    /// Posted to when downloads finish, new results show up or errors happen
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub webhooks: Vec<WebhookConfig>,
 }
 
 impl Default for Config {
     fn default() -> Config {
         Config {
+            version: migrate::CONFIG_VERSION,
             source: Sources::Nyaa,
             download_client: Client::Cmd,
             theme: Theme::default().name,
@@ -61,24 +182,77 @@ impl Default for Config {
             timeout: 30,
             scroll_padding: 3,
             save_config_on_change: true,
+            live_search: false,
+            live_search_delay: 500,
+            on_startup: OnStartup::default(),
+            hint_bar: false,
+            combo_popup: true,
+            combo_popup_delay: 500,
+            leader: None,
+            chords: BTreeMap::new(),
+            batch_page_limit: 10,
+            window_title: true,
+            batch_bell: false,
+            mouse: true,
+            check_for_updates: false,
+            text_markers: false,
+            hide_remakes: false,
+            trusted_only: false,
+            search_on_source_change: true,
+            deep_search_pages: 5,
+            max_kept_results: 5000,
+            captcha_command: None,
+            batch_export_dir: None,
+            validate_links: false,
+            confirm_batch_download: false,
+            prompt_download_options: false,
+            locale: Locale::default(),
+            use_keyring: false,
+            layout: LayoutConfig::default(),
+            size_format: SizeFormat::default(),
             notifications: None,
             clipboard: None,
             client: ClientConfig::default(),
             sources: SourceConfig::default(),
+            presets: vec![],
+            webhooks: vec![],
         }
     }
 }
 
 impl ConfigManager for AppConfig {
     fn load() -> Result<Config, Box<dyn Error>> {
-        get_configuration_file_path(APP_NAME, CONFIG_FILE).and_then(load_path)
+        let path = get_configuration_file_path(APP_NAME, CONFIG_FILE)?;
+        if let Ok(text) = fs::read_to_string(&path) {
+            let report = validate::check(&text);
+            if !report.errors.is_empty() {
+                return Err(report.into());
+            }
+        }
+        load_path(path)
     }
     fn store(cfg: &Config) -> Result<(), Box<dyn Error>> {
         get_configuration_file_path(APP_NAME, CONFIG_FILE).and_then(|p| store_path(p, cfg))
     }
+    fn load_state() -> Result<State, Box<dyn Error>> {
+        load_path(get_configuration_file_path(APP_NAME, STATE_FILE)?)
+    }
+    fn store_state(state: &State) -> Result<(), Box<dyn Error>> {
+        get_configuration_file_path(APP_NAME, STATE_FILE).and_then(|p| store_path(p, state))
+    }
     fn path() -> Result<PathBuf, Box<dyn Error>> {
         get_configuration_folder(APP_NAME)
     }
+    fn check() -> ConfigReport {
+        get_configuration_file_path(APP_NAME, CONFIG_FILE)
+            .ok()
+            .and_then(|p| fs::read_to_string(p).ok())
+            .map(|text| validate::check(&text))
+            .unwrap_or_default()
+    }
+    fn migrate() -> Result<Vec<String>, Box<dyn Error>> {
+        migrate::upgrade(&get_configuration_file_path(APP_NAME, CONFIG_FILE)?)
+    }
 }
 
 impl Config {
@@ -92,14 +266,66 @@ impl Config {
         w.sort.selected.sort = 0;
         w.filter.selected = 0;
         ctx.client = ctx.config.download_client;
-        ctx.src = ctx.config.source;
-        ctx.src_info = ctx.src.info();
+        for query in ctx.state.recent_searches.iter() {
+            ctx.completions.add(query);
+        }
+        secret::set_use_keyring(self.use_keyring);
+        crash::set_config(self);
+        conv::set_size_format(self.size_format);
+        // Already warned about by the config check
+        ctx.chords = Chords::new(self.leader, &self.chords).unwrap_or_default();
+        let preset = match &self.on_startup {
+            OnStartup::Preset(name) => {
+                let preset = self.presets.iter().find(|p| p.name == *name).cloned();
+                if preset.is_none() {
+                    ctx.show_error(format!(
+                        "Failed to search on startup:\nThere is no preset named \"{}\"",
+                        name
+                    ));
+                }
+                preset
+            }
+            _ => None,
+        };
+        ctx.src = match preset
+            .as_ref()
+            .map(|p| p.source)
+            .unwrap_or(ctx.config.source)
+        {
+            // Custom instance was removed from the config
+            Sources::Custom(name)
+                if !ctx
+                    .config
+                    .sources
+                    .custom_nyaa
+                    .iter()
+                    .any(|c| c.name == name) =>
+            {
+                Sources::Nyaa
+            }
+            src => src,
+        };
+        ctx.src_info = ctx.src.info(&ctx.config.sources);
 
         ctx.src.load_config(&mut ctx.config.sources);
         ctx.src.apply(ctx, w);
+        match (&self.on_startup, preset) {
+            (_, Some(preset)) => w.user.search_preset(ctx, &preset),
+            (OnStartup::Idle, _) => ctx.mode = Mode::Normal,
+            _ => {}
+        }
         if let Some(conf) = ctx.config.notifications {
             w.notification.load_config(&conf);
         }
+        ctx.quick_filters.retain(|f| !f.sticky());
+        for (on, filter) in [
+            (self.hide_remakes, QuickFilter::NoRemakes),
+            (self.trusted_only, QuickFilter::TrustedOnly),
+        ] {
+            if on {
+                ctx.quick_filters.push(filter);
+            }
+        }
 
         ctx.client.load_config(ctx);
         let path = C::path()?;
@@ -110,6 +336,16 @@ impl Config {
             w.theme.selected = i;
             w.theme.table.select(i);
             ctx.theme = theme.clone();
+            ctx.theme.markers |= self.text_markers;
+        }
+
+        let version = env!("CARGO_PKG_VERSION");
+        if ctx.state.last_version.as_deref() != Some(version) {
+            if let Some(last) = ctx.state.last_version.as_ref() {
+                w.whats_new.open(last, version);
+            }
+            ctx.state.last_version = Some(version.to_owned());
+            ctx.save_state();
         }
 
         // Load defaults for default source
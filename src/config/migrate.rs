@@ -0,0 +1,173 @@
+use std::{error::Error, fs, io::ErrorKind, path::Path};
+
+use toml::{Table, Value};
+
+use super::state::{self, STATE_FILE};
+
+/// Version of the config format written by this version of nyaa
+pub static CONFIG_VERSION: u32 = 3;
+
+/// Upgrades a config from the version before, returning whether anything was
+/// changed
+type Migration = fn(&mut Table) -> bool;
+
+/// Changes to the config format, with the version they upgrade to
+static MIGRATIONS: &[(u32, &str, Migration)] = &[
+    (1, "Moved base_url into [source.nyaa]", move_base_url),
+    (2, "Renamed default_theme to theme", rename_default_theme),
+    (
+        3,
+        "Custom sources are now saved by name instead of their place in the list",
+        name_custom_sources,
+    ),
+];
+
+/// Version of the config format `table` was written in. Configs from before
+/// versions were added have none.
+pub fn version(table: &Table) -> u32 {
+    table
+        .get("version")
+        .and_then(Value::as_integer)
+        .and_then(|v| u32::try_from(v).ok())
+        .unwrap_or(0)
+}
+
+/// Upgrade `table` to the latest config format, returning a description of
+/// each change made
+pub fn migrate(table: &mut Table) -> Vec<&'static str> {
+    let from = version(table);
+    if from >= CONFIG_VERSION {
+        return vec![];
+    }
+    let changes: Vec<&'static str> = MIGRATIONS
+        .iter()
+        .filter(|(v, _, _)| *v > from)
+        .filter_map(|(_, desc, migration)| migration(table).then_some(*desc))
+        .collect();
+    table.insert("version".to_owned(), Value::Integer(CONFIG_VERSION as i64));
+    changes
+}
+
+/// Upgrade the config file at `path` if it is in an older format, keeping a
+/// copy of the old file next to it. Returns a description of each change made.
+pub fn upgrade(path: &Path) -> Result<Vec<String>, Box<dyn Error>> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(e.into()),
+    };
+    // Syntax errors are reported once the config is validated
+    let Ok(mut table) = text.parse::<Table>() else {
+        return Ok(vec![]);
+    };
+    let from = version(&table);
+    let mut msgs: Vec<String> = migrate(&mut table)
+        .into_iter()
+        .map(ToOwned::to_owned)
+        .collect();
+    let state_path = path.with_file_name(format!("{}.toml", STATE_FILE));
+    if state::move_to_state(&mut table, &state_path)? {
+        msgs.push(format!(
+            "Moved what's kept between sessions to {}",
+            state_path.display()
+        ));
+    }
+    if msgs.is_empty() {
+        return Ok(vec![]);
+    }
+    let backup = path.with_extension(format!("v{}.toml.bak", from));
+    fs::copy(path, &backup)?;
+    fs::write(path, toml::to_string_pretty(&table)?)?;
+
+    msgs.push(format!("The old config was saved to {}", backup.display()));
+    Ok(msgs)
+}
+
+fn move_base_url(table: &mut Table) -> bool {
+    let has_nyaa_url = table
+        .get("source")
+        .and_then(|s| s.get("nyaa"))
+        .is_some_and(|n| n.get("base_url").is_some());
+    // Leave it where it is if [source.nyaa] has its own, so neither is lost
+    if has_nyaa_url {
+        return false;
+    }
+    let Some(url) = table.remove("base_url") else {
+        return false;
+    };
+    let source = table
+        .entry("source")
+        .or_insert_with(|| Value::Table(Table::new()));
+    match source
+        .as_table_mut()
+        .map(|s| {
+            s.entry("nyaa")
+                .or_insert_with(|| Value::Table(Table::new()))
+        })
+        .and_then(Value::as_table_mut)
+    {
+        Some(nyaa) => {
+            nyaa.insert("base_url".to_owned(), url);
+            true
+        }
+        None => {
+            table.insert("base_url".to_owned(), url);
+            false
+        }
+    }
+}
+
+fn rename_default_theme(table: &mut Table) -> bool {
+    if table.contains_key("theme") {
+        return false;
+    }
+    match table.remove("default_theme") {
+        Some(theme) => {
+            table.insert("theme".to_owned(), theme);
+            true
+        }
+        None => false,
+    }
+}
+
+fn name_custom_sources(table: &mut Table) -> bool {
+    let names: Vec<String> = table
+        .get("source")
+        .and_then(|s| s.get("custom_nyaa"))
+        .and_then(Value::as_array)
+        .map(|list| {
+            list.iter()
+                .map(|c| {
+                    c.get("name")
+                        .and_then(Value::as_str)
+                        .unwrap_or_default()
+                        .to_owned()
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    // Saved as `{ Custom = <index> }`
+    let mut rename = |src: &mut Value| {
+        let Some(custom) = src.get_mut("Custom") else {
+            return false;
+        };
+        let name = custom
+            .as_integer()
+            .and_then(|i| names.get(usize::try_from(i).ok()?));
+        match name {
+            Some(name) => {
+                *custom = Value::String(name.to_owned());
+                true
+            }
+            None => false,
+        }
+    };
+    let mut changed = table.get_mut("default_source").is_some_and(&mut rename);
+    for list in ["presets", "watch_later"] {
+        let items = table.get_mut(list).and_then(Value::as_array_mut);
+        for item in items.into_iter().flatten() {
+            changed |= item.get_mut("source").is_some_and(&mut rename);
+        }
+    }
+    changed
+}
@@ -0,0 +1,190 @@
+use std::{
+    env,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+use crate::util::cmd::CommandBuilder;
+
+/// Prefix of a value read from an environment variable, e.g. `env:QBIT_PASS`
+pub static ENV_PREFIX: &str = "env:";
+/// Prefix of a value printed by a command, e.g. `cmd:pass show nyaa`
+pub static CMD_PREFIX: &str = "cmd:";
+
+/// Secrets which can be stored in the OS keyring, with `nyaa secret set <name>`.
+/// `<source>` is the name of a source with `[source.auth.<source>]`.
+pub static KEYRING_SECRETS: &[&str] = &[
+    "qBittorrent.username",
+    "qBittorrent.password",
+    "transmission.username",
+    "transmission.password",
+    "source.<source>.username",
+    "source.<source>.password",
+    "source.<source>.cookie",
+    "source.<source>.token",
+];
+/// Stands for the source's name in [`KEYRING_SECRETS`]
+static SOURCE_PLACEHOLDER: &str = "<source>";
+#[cfg(feature = "keyring")]
+static KEYRING_SERVICE: &str = "nyaa";
+/// Whether to look for secrets in the OS keyring first, from `use_keyring`
+static USE_KEYRING: AtomicBool = AtomicBool::new(false);
+
+/// Output of commands which have already been run, so password managers are
+/// only asked once
+static CMD_CACHE: Mutex<Vec<(String, String)>> = Mutex::new(vec![]);
+
+/// A reference to a secret stored outside the config
+#[derive(Debug, PartialEq)]
+pub enum SecretRef<'a> {
+    Env(&'a str),
+    Cmd(&'a str),
+    Plain(&'a str),
+}
+
+impl<'a> SecretRef<'a> {
+    pub fn parse(value: &'a str) -> Self {
+        if let Some(var) = value.strip_prefix(ENV_PREFIX) {
+            SecretRef::Env(var.trim())
+        } else if let Some(cmd) = value.strip_prefix(CMD_PREFIX) {
+            SecretRef::Cmd(cmd.trim())
+        } else {
+            SecretRef::Plain(value)
+        }
+    }
+}
+
+/// Read the value of a config option which may be given as `env:VAR_NAME` or
+/// `cmd:<command>`. Anything else is used as it is.
+pub fn resolve(value: &str) -> Result<String, String> {
+    match SecretRef::parse(value) {
+        SecretRef::Plain(value) => Ok(value.to_owned()),
+        SecretRef::Env(var) => env::var(var)
+            .map_err(|e| format!("Failed to read environment variable \"{}\":\n{}", var, e)),
+        SecretRef::Cmd(cmd) => {
+            if let Some((_, out)) = CMD_CACHE
+                .lock()
+                .ok()
+                .and_then(|c| c.iter().find(|(c, _)| c == cmd).cloned())
+            {
+                return Ok(out);
+            }
+            let out = CommandBuilder::new(cmd.to_owned())
+                .output(None)
+                .map_err(|e| format!("Failed to read secret from command {}", e))?;
+            // Only the first line, as `pass` and others print extra fields below
+            let out = out.lines().next().unwrap_or_default().to_owned();
+            if let Ok(mut cache) = CMD_CACHE.lock() {
+                cache.push((cmd.to_owned(), out.clone()));
+            }
+            Ok(out)
+        }
+    }
+}
+
+pub fn set_use_keyring(on: bool) {
+    USE_KEYRING.store(on, Ordering::Relaxed);
+}
+
+/// Read the secret `name`, from the OS keyring if `use_keyring` is on and it
+/// has one stored, otherwise from its value in the config
+pub fn resolve_named(name: &str, value: Option<&str>) -> Result<Option<String>, String> {
+    if USE_KEYRING.load(Ordering::Relaxed) {
+        if let Some(secret) = keyring_get(name)? {
+            return Ok(Some(secret));
+        }
+    }
+    value.map(resolve).transpose()
+}
+
+/// Name of the secret `key` of the source `source` in the keyring, like
+/// `source.Nyaa.password`
+pub fn source_secret(source: &str, key: &str) -> String {
+    format!("source.{}.{}", source, key)
+}
+
+/// Whether `name` is one of [`KEYRING_SECRETS`], with any source name in
+/// place of `<source>`
+fn is_secret(name: &str) -> bool {
+    KEYRING_SECRETS
+        .iter()
+        .any(|s| match s.split_once(SOURCE_PLACEHOLDER) {
+            Some((prefix, suffix)) => name
+                .strip_prefix(prefix)
+                .and_then(|n| n.strip_suffix(suffix))
+                .is_some_and(|source| !source.is_empty()),
+            None => *s == name,
+        })
+}
+
+/// Make sure `name` can be stored in the keyring
+pub fn check_name(name: &str) -> Result<(), String> {
+    match is_secret(name) {
+        true => Ok(()),
+        false => Err(format!(
+            "There is no secret named \"{}\", expected one of:\n{}",
+            name,
+            KEYRING_SECRETS.join("\n")
+        )),
+    }
+}
+
+#[cfg(feature = "keyring")]
+fn entry(name: &str) -> Result<keyring::Entry, String> {
+    check_name(name)?;
+    keyring::Entry::new(KEYRING_SERVICE, name)
+        .map_err(|e| format!("Failed to open the keyring entry for {}:\n{}", name, e))
+}
+
+/// The secret `name` stored in the OS keyring, if there is one
+#[cfg(feature = "keyring")]
+pub fn keyring_get(name: &str) -> Result<Option<String>, String> {
+    match entry(name)?.get_password() {
+        Ok(secret) => Ok(Some(secret)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(format!("Failed to read {} from the keyring:\n{}", name, e)),
+    }
+}
+
+#[cfg(feature = "keyring")]
+pub fn keyring_set(name: &str, secret: &str) -> Result<(), String> {
+    entry(name)?
+        .set_password(secret)
+        .map_err(|e| format!("Failed to store {} in the keyring:\n{}", name, e))
+}
+
+/// Remove the secret `name` from the OS keyring, returning whether it was there
+#[cfg(feature = "keyring")]
+pub fn keyring_delete(name: &str) -> Result<bool, String> {
+    match entry(name)?.delete_password() {
+        Ok(()) => Ok(true),
+        Err(keyring::Error::NoEntry) => Ok(false),
+        Err(e) => Err(format!(
+            "Failed to delete {} from the keyring:\n{}",
+            name, e
+        )),
+    }
+}
+
+#[cfg(not(feature = "keyring"))]
+static NO_KEYRING: &str = "nyaa was built without the keyring feature";
+
+#[cfg(not(feature = "keyring"))]
+pub fn keyring_get(name: &str) -> Result<Option<String>, String> {
+    check_name(name)?;
+    Err(NO_KEYRING.to_owned())
+}
+
+#[cfg(not(feature = "keyring"))]
+pub fn keyring_set(name: &str, _secret: &str) -> Result<(), String> {
+    check_name(name)?;
+    Err(NO_KEYRING.to_owned())
+}
+
+#[cfg(not(feature = "keyring"))]
+pub fn keyring_delete(name: &str) -> Result<bool, String> {
+    check_name(name)?;
+    Err(NO_KEYRING.to_owned())
+}
@@ -0,0 +1,77 @@
+use std::{error::Error, fs, io::ErrorKind, path::Path};
+
+use serde::{Deserialize, Serialize};
+use toml::Table;
+
+use crate::util::watch::WatchItem;
+
+/// Name of the file next to the config which [`State`] is kept in
+pub static STATE_FILE: &str = "state";
+
+/// What nyaa keeps between sessions by itself. It's kept apart from the
+/// config, so it's saved whether or not `save_config_on_change` is on, and
+/// saving it never rewrites the config.
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct State {
+    /// Version of nyaa which last opened, to show what's new after upgrading
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_version: Option<String>,
+    /// Ids of starred items, like `nyaa-123`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub starred: Vec<String>,
+    /// Items kept to download later, separate from the batch
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub watch_later: Vec<WatchItem>,
+    /// Latest searches first, shown before the first search
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub recent_searches: Vec<String>,
+    /// Latest uploaders searched for first, listed in the user popup
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub recent_users: Vec<String>,
+    /// Batch downloads which haven't sent every torrent yet, put back in the
+    /// batch on the next start to resume
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub batches: Vec<SavedBatch>,
+}
+
+/// What's left to send of a batch download, saved from when it starts until
+/// every torrent has been sent
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct SavedBatch {
+    /// Id the batch is tracked by while downloading
+    pub id: usize,
+    pub items: Vec<WatchItem>,
+}
+
+/// Keys which older versions kept in the config
+static STATE_KEYS: &[&str] = &[
+    "last_version",
+    "starred",
+    "watch_later",
+    "recent_searches",
+    "recent_users",
+];
+
+/// Move what older versions kept in `config` into the state file at
+/// `state_path`. Anything the state file already has is kept, not replaced.
+/// Returns whether anything was moved.
+pub fn move_to_state(config: &mut Table, state_path: &Path) -> Result<bool, Box<dyn Error>> {
+    let moved: Table = STATE_KEYS
+        .iter()
+        .filter_map(|k| config.remove(*k).map(|v| (k.to_string(), v)))
+        .collect();
+    if moved.is_empty() {
+        return Ok(false);
+    }
+    let mut state = match fs::read_to_string(state_path) {
+        Ok(text) => text.parse::<Table>()?,
+        Err(e) if e.kind() == ErrorKind::NotFound => Table::new(),
+        Err(e) => return Err(e.into()),
+    };
+    for (key, value) in moved {
+        state.entry(key).or_insert(value);
+    }
+    fs::write(state_path, toml::to_string_pretty(&state)?)?;
+    Ok(true)
+}
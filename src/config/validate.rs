@@ -0,0 +1,480 @@
+use std::{error::Error, fmt::Display, ops::Range};
+
+use reqwest::header::HeaderValue;
+use serde::Deserialize as _;
+use toml::{Table, Value};
+use url::Url;
+
+use crate::{
+    source::{auth::SourceAuth, RequestHeaders, SourceConfig, Sources},
+    util::conv::{add_protocol, MAX_DECIMALS},
+    widget::combo::Chords,
+};
+
+use super::{
+    secret::{self, SecretRef},
+    Config,
+};
+
+/// Keys which are still read under an old name
+static ALIASES: &[(&str, &str)] = &[("default_theme", "theme")];
+
+/// Something wrong with the config, at the TOML path of the key
+#[derive(Debug, Clone, PartialEq)]
+pub struct Problem {
+    pub path: String,
+    pub msg: String,
+}
+
+impl Problem {
+    fn new<S: Display>(path: &str, msg: S) -> Self {
+        Problem {
+            path: path.to_owned(),
+            msg: msg.to_string(),
+        }
+    }
+}
+
+impl Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path, self.msg)
+    }
+}
+
+/// Everything wrong with a config file. Errors stop it from loading, while
+/// warnings are only shown.
+#[derive(Debug, Default)]
+pub struct ConfigReport {
+    pub errors: Vec<Problem>,
+    pub warnings: Vec<Problem>,
+}
+
+impl ConfigReport {
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty() && self.warnings.is_empty()
+    }
+}
+
+impl Display for ConfigReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let problems: Vec<String> = self
+            .errors
+            .iter()
+            .chain(self.warnings.iter())
+            .map(|p| format!("- {}", p))
+            .collect();
+        write!(
+            f,
+            "{} problem{} in the config:\n{}",
+            problems.len(),
+            match problems.len() {
+                1 => "",
+                _ => "s",
+            },
+            problems.join("\n")
+        )
+    }
+}
+
+impl Error for ConfigReport {}
+
+/// Check the text of a config file for syntax errors, invalid values,
+/// unknown keys, malformed URLs and options which conflict
+pub fn check(text: &str) -> ConfigReport {
+    let mut report = ConfigReport::default();
+    let table = match text.parse::<Table>() {
+        Ok(table) => table,
+        Err(e) => {
+            report
+                .errors
+                .push(Problem::new(&path_at(text, e.span()), e.message().trim()));
+            return report;
+        }
+    };
+    invalid_values(&table, &[], &mut report.errors);
+    let config = match toml::from_str::<Config>(text) {
+        Ok(config) => config,
+        Err(e) => {
+            // Only shown if no single value was found to be the cause
+            if report.errors.is_empty() {
+                report
+                    .errors
+                    .push(Problem::new(&path_at(text, e.span()), e.message().trim()));
+            }
+            return report;
+        }
+    };
+    if let Ok(known) = Value::try_from(&config) {
+        unknown_keys(&table, &known, "", &mut report.warnings);
+    }
+    bad_urls(&config, &mut report.warnings);
+    missing_secrets(&config, &mut report.warnings);
+    bad_headers(&config, &mut report.warnings);
+    bad_templates(&config, &mut report.warnings);
+    conflicts(&config, &mut report.warnings);
+    report
+}
+
+fn join(path: &str, key: &str) -> String {
+    let bare = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    let key = match bare {
+        true => key.to_owned(),
+        false => format!("\"{}\"", key),
+    };
+    match path.is_empty() {
+        true => key,
+        false => format!("{}.{}", path, key),
+    }
+}
+
+/// Deserialize each value on its own, so every invalid one is found instead
+/// of just the first
+fn invalid_values(table: &Table, keys: &[&str], errors: &mut Vec<Problem>) {
+    for (key, value) in table.iter() {
+        let mut keys = keys.to_vec();
+        keys.push(key);
+        let path = keys.iter().fold(String::new(), |p, k| join(&p, k));
+        match value {
+            Value::Table(t) if !t.is_empty() => invalid_values(t, &keys, errors),
+            Value::Array(a) if !a.is_empty() && a.iter().all(Value::is_table) => {
+                for (i, v) in a.iter().enumerate() {
+                    let path = format!("{}[{}]", path, i);
+                    test_value(&keys, Value::Array(vec![v.clone()]), &path, errors);
+                }
+            }
+            _ => test_value(&keys, value.clone(), &path, errors),
+        }
+    }
+}
+
+fn test_value(keys: &[&str], value: Value, path: &str, errors: &mut Vec<Problem>) {
+    let value = keys.iter().rev().fold(value, |v, k| {
+        let mut t = Table::new();
+        t.insert(k.to_string(), v);
+        Value::Table(t)
+    });
+    if let Err(e) = Config::deserialize(value) {
+        let msg = e.message().trim().to_owned();
+        // Other required fields were left out on purpose
+        if !msg.starts_with("missing field") {
+            errors.push(Problem::new(path, msg));
+        }
+    }
+}
+
+/// Keys in the file which are missing once it has been read and written
+/// again were never read
+fn unknown_keys(table: &Table, known: &Value, path: &str, warnings: &mut Vec<Problem>) {
+    for (key, value) in table.iter() {
+        let path = join(path, key);
+        let Some(known) = known.get(key) else {
+            let empty = match value {
+                Value::Table(t) => t.is_empty(),
+                Value::Array(a) => a.is_empty(),
+                _ => false,
+            };
+            if !empty && !ALIASES.iter().any(|(old, _)| *old == path) {
+                warnings.push(Problem::new(&path, "Unknown key, which is ignored"));
+            }
+            continue;
+        };
+        match (value, known) {
+            (Value::Table(t), Value::Table(_)) => unknown_keys(t, known, &path, warnings),
+            (Value::Array(a), Value::Array(k)) => {
+                for (i, (v, k)) in a.iter().zip(k.iter()).enumerate() {
+                    if let Value::Table(t) = v {
+                        unknown_keys(t, k, &format!("{}[{}]", path, i), warnings);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn bad_urls(config: &Config, warnings: &mut Vec<Problem>) {
+    let sources = &config.sources;
+    let client = &config.client;
+    let mut urls: Vec<(String, String, bool)> = vec![];
+    if let Some(c) = &sources.nyaa {
+        urls.push(("source.nyaa.base_url".to_owned(), c.base_url.clone(), true));
+    }
+    if let Some(c) = &sources.sukebei {
+        urls.push((
+            "source.sukebei.base_url".to_owned(),
+            c.0.base_url.clone(),
+            true,
+        ));
+    }
+    if let Some(c) = &sources.tgx {
+        let path = "source.torrentgalaxy.base_url".to_owned();
+        urls.push((path, c.base_url.clone(), true));
+    }
+    for (i, c) in sources.custom_nyaa.iter().enumerate() {
+        let path = format!("source.custom_nyaa[{}].base_url", i);
+        urls.push((path, c.nyaa.base_url.clone(), true));
+    }
+    if let Some(c) = &client.qbit {
+        let path = "client.qBittorrent.base_url".to_owned();
+        urls.push((path, c.base_url.clone(), false));
+    }
+    if let Some(c) = &client.transmission {
+        let path = "client.transmission.base_url".to_owned();
+        urls.push((path, c.base_url.clone(), false));
+    }
+    if let Some(c) = &client.rqbit {
+        urls.push((
+            "client.rqbit.base_url".to_owned(),
+            c.base_url.clone(),
+            false,
+        ));
+    }
+    if let Some(proxy) = &config.request_proxy {
+        urls.push(("request_proxy".to_owned(), proxy.clone(), false));
+    }
+    for (path, url, https) in urls {
+        if let Err(e) = Url::parse(&add_protocol(url.clone(), https)) {
+            warnings.push(Problem::new(
+                &path,
+                format!("\"{}\" is not a valid URL ({})", url, e),
+            ));
+        }
+    }
+    // Webhooks are posted to as they are, so need their protocol. Their URL
+    // is left out since it usually holds a token.
+    for (i, hook) in config.webhooks.iter().enumerate() {
+        if let Err(e) = Url::parse(&hook.url.replace("{event}", "download")) {
+            warnings.push(Problem::new(
+                &format!("webhooks[{}].url", i),
+                format!("Not a valid URL ({})", e),
+            ));
+        }
+    }
+}
+
+fn bad_headers(config: &Config, warnings: &mut Vec<Problem>) {
+    let sources = &config.sources;
+    let mut headers: Vec<(String, &RequestHeaders)> = vec![];
+    if let Some(h) = sources.nyaa.as_ref().and_then(|c| c.headers.as_ref()) {
+        headers.push(("source.nyaa.headers".to_owned(), h));
+    }
+    if let Some(h) = sources.sukebei.as_ref().and_then(|c| c.0.headers.as_ref()) {
+        headers.push(("source.sukebei.headers".to_owned(), h));
+    }
+    if let Some(h) = sources.tgx.as_ref().and_then(|c| c.headers.as_ref()) {
+        headers.push(("source.torrentgalaxy.headers".to_owned(), h));
+    }
+    for (i, c) in sources.custom_nyaa.iter().enumerate() {
+        if let Some(h) = &c.nyaa.headers {
+            headers.push((format!("source.custom_nyaa[{}].headers", i), h));
+        }
+    }
+    for (path, h) in headers {
+        for (i, ua) in h.user_agents.iter().enumerate() {
+            if HeaderValue::from_str(ua).is_err() {
+                warnings.push(Problem::new(
+                    &format!("{}.user_agents[{}]", path, i),
+                    "Is not a valid header value, so it isn't sent",
+                ));
+            }
+        }
+        if let Some(lang) = &h.accept_language {
+            if HeaderValue::from_str(lang).is_err() {
+                warnings.push(Problem::new(
+                    &format!("{}.accept_language", path),
+                    "Is not a valid header value, so it isn't sent",
+                ));
+            }
+        }
+    }
+}
+
+fn bad_templates(config: &Config, warnings: &mut Vec<Problem>) {
+    let sources = &config.sources;
+    let mut templates: Vec<(String, &String)> = vec![];
+    if let Some(t) = sources
+        .nyaa
+        .as_ref()
+        .and_then(|c| c.download_url_template.as_ref())
+    {
+        templates.push(("source.nyaa.download_url_template".to_owned(), t));
+    }
+    if let Some(t) = sources
+        .sukebei
+        .as_ref()
+        .and_then(|c| c.0.download_url_template.as_ref())
+    {
+        templates.push(("source.sukebei.download_url_template".to_owned(), t));
+    }
+    if let Some(t) = sources
+        .tgx
+        .as_ref()
+        .and_then(|c| c.download_url_template.as_ref())
+    {
+        templates.push(("source.torrentgalaxy.download_url_template".to_owned(), t));
+    }
+    for (i, c) in sources.custom_nyaa.iter().enumerate() {
+        if let Some(t) = &c.nyaa.download_url_template {
+            templates.push((
+                format!("source.custom_nyaa[{}].download_url_template", i),
+                t,
+            ));
+        }
+    }
+    for (path, template) in templates {
+        if !template.contains("{id}") {
+            warnings.push(Problem::new(
+                &path,
+                "Has no {id}, so every torrent is downloaded from the same URL",
+            ));
+        }
+    }
+}
+
+/// Environment variables referenced by `env:` which aren't set. Commands
+/// aren't run, as they may ask for a password.
+fn missing_secrets(config: &Config, warnings: &mut Vec<Problem>) {
+    let mut secrets: Vec<(String, &str)> = vec![];
+    if let Some(c) = &config.client.qbit {
+        secrets.push(("client.qBittorrent.username".to_owned(), &c.username));
+        secrets.push(("client.qBittorrent.password".to_owned(), &c.password));
+    }
+    if let Some(c) = &config.client.transmission {
+        if let Some(user) = &c.username {
+            secrets.push(("client.transmission.username".to_owned(), user));
+        }
+        if let Some(password) = &c.password {
+            secrets.push(("client.transmission.password".to_owned(), password));
+        }
+    }
+    for (name, auth) in config.sources.auth.iter() {
+        let path = |key: &str| format!("source.auth.{}.{}", name, key);
+        match auth {
+            SourceAuth::Login {
+                username, password, ..
+            } => {
+                secrets.push((path("username"), username));
+                secrets.push((path("password"), password));
+            }
+            SourceAuth::Cookie { cookie } => secrets.push((path("cookie"), cookie)),
+            SourceAuth::Token { token } => secrets.push((path("token"), token)),
+        }
+    }
+    for (path, value) in secrets {
+        if let SecretRef::Env(_) = SecretRef::parse(value) {
+            if let Err(e) = secret::resolve(value) {
+                warnings.push(Problem::new(&path, e.replace('\n', " ")));
+            }
+        }
+    }
+}
+
+fn conflicts(config: &Config, warnings: &mut Vec<Problem>) {
+    #[cfg(not(feature = "keyring"))]
+    if config.use_keyring {
+        warnings.push(Problem::new(
+            "use_keyring",
+            "nyaa was built without the keyring feature, so the keyring can't be used",
+        ));
+    }
+    if config.size_format.decimals > MAX_DECIMALS {
+        warnings.push(Problem::new(
+            "size_format.decimals",
+            format!("Sizes are shown with at most {} decimals", MAX_DECIMALS),
+        ));
+    }
+    let layout = config.layout;
+    if layout.three_pane && layout.narrow_width > layout.min_width {
+        warnings.push(Problem::new(
+            "layout.min_width",
+            format!(
+                "Is below layout.narrow_width ({}), so some terminals get both the details pane and the narrow layout",
+                layout.narrow_width
+            ),
+        ));
+    }
+    if let Sources::Custom(name) = config.source {
+        if !config.sources.custom_nyaa.iter().any(|c| c.name == name) {
+            warnings.push(Problem::new(
+                "default_source",
+                format!(
+                    "There is no custom source named \"{}\", so Nyaa is used",
+                    name
+                ),
+            ));
+        }
+    }
+    if let Err(e) = Chords::new(config.leader, &config.chords) {
+        warnings.push(Problem::new("chords", e));
+    }
+    for (i, preset) in config.presets.iter().enumerate() {
+        if config.presets[..i].iter().any(|p| p.name == preset.name) {
+            warnings.push(Problem::new(
+                &format!("presets[{}].name", i),
+                format!("Another preset is already named \"{}\"", preset.name),
+            ));
+        }
+    }
+    let builtin: Vec<String> = Sources::all(&SourceConfig::default())
+        .into_iter()
+        .map(|s| s.to_string())
+        .collect();
+    let custom = &config.sources.custom_nyaa;
+    for (i, c) in custom.iter().enumerate() {
+        if builtin.contains(&c.name) || custom[..i].iter().any(|o| o.name == c.name) {
+            warnings.push(Problem::new(
+                &format!("source.custom_nyaa[{}].name", i),
+                format!("Another source is already named \"{}\"", c.name),
+            ));
+        }
+    }
+    for name in config.sources.auth.keys() {
+        if !builtin.contains(name) && !custom.iter().any(|c| &c.name == name) {
+            warnings.push(Problem::new(
+                &format!("source.auth.{}", name),
+                format!(
+                    "There is no source named \"{}\", so this is never used",
+                    name
+                ),
+            ));
+        }
+    }
+}
+
+/// The TOML path of the key on the line `span` starts on
+fn path_at(text: &str, span: Option<Range<usize>>) -> String {
+    let Some(span) = span else {
+        return "config".to_owned();
+    };
+    let start = span.start.min(text.len());
+    let line_num = text[..start].matches('\n').count();
+    let mut table = String::new();
+    for line in text.lines().take(line_num) {
+        let line = line.trim();
+        if let Some(header) = line
+            .strip_prefix("[[")
+            .and_then(|l| l.split_once("]]"))
+            .or_else(|| line.strip_prefix('[').and_then(|l| l.split_once(']')))
+        {
+            table = header.0.trim().to_owned();
+        }
+    }
+    let key = text
+        .lines()
+        .nth(line_num)
+        .and_then(|l| l.split_once('='))
+        .map(|(k, _)| k.trim().to_owned())
+        .unwrap_or_default();
+    let path = match (table.is_empty(), key.is_empty()) {
+        (_, true) => table,
+        (true, false) => key,
+        (false, false) => format!("{}.{}", table, key),
+    };
+    match path.is_empty() {
+        true => format!("line {}", line_num + 1),
+        false => format!("{} (line {})", path, line_num + 1),
+    }
+}
@@ -0,0 +1,87 @@
+use std::{io, str::Utf8Error, time::SystemTimeError};
+
+use thiserror::Error;
+
+/// Errors from loading results, sending torrents to clients and copying to
+/// the clipboard
+#[derive(Error, Debug)]
+pub enum NyaaError {
+    /// Failed to send a request, or to read its response
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+    /// The server responded, but not with 200 OK
+    #[error("{url}\nInvalid response code: {code}")]
+    Status { url: String, code: u16 },
+    /// The response could not be understood, e.g. a missing results table
+    #[error("{0}")]
+    Parse(String),
+    /// A value in the config is missing or invalid
+    #[error("{0}")]
+    Config(String),
+    /// Signing in to a source failed, like with a wrong password
+    #[error("{0}")]
+    Auth(String),
+    /// A download client failed to add a torrent
+    #[error("{0}")]
+    Client(String),
+    #[error("{0}")]
+    Clipboard(String),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error("{0}")]
+    Other(String),
+}
+
+impl NyaaError {
+    /// Whether trying the same request again could succeed, e.g. after a
+    /// timeout or while the server is overloaded
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            NyaaError::Http(e) => e.is_timeout() || e.is_connect(),
+            NyaaError::Status { code, .. } => *code == 429 || *code >= 500,
+            _ => false,
+        }
+    }
+}
+
+impl From<url::ParseError> for NyaaError {
+    fn from(e: url::ParseError) -> Self {
+        NyaaError::Parse(format!("Invalid url: {}", e))
+    }
+}
+
+impl From<Utf8Error> for NyaaError {
+    fn from(e: Utf8Error) -> Self {
+        NyaaError::Parse(format!("Response was not valid UTF-8: {}", e))
+    }
+}
+
+impl From<rss::Error> for NyaaError {
+    fn from(e: rss::Error) -> Self {
+        NyaaError::Parse(format!("Failed to parse RSS feed: {}", e))
+    }
+}
+
+impl From<tokio::task::JoinError> for NyaaError {
+    fn from(e: tokio::task::JoinError) -> Self {
+        NyaaError::Other(format!("Failed to parse results: {}", e))
+    }
+}
+
+impl From<SystemTimeError> for NyaaError {
+    fn from(e: SystemTimeError) -> Self {
+        NyaaError::Other(e.to_string())
+    }
+}
+
+impl From<String> for NyaaError {
+    fn from(msg: String) -> Self {
+        NyaaError::Other(msg)
+    }
+}
+
+impl From<&str> for NyaaError {
+    fn from(msg: &str) -> Self {
+        NyaaError::Other(msg.to_owned())
+    }
+}
@@ -2,6 +2,8 @@ pub mod app;
 pub mod client;
 pub mod clip;
 pub mod config;
+pub mod error;
+pub mod locale;
 pub mod macros;
 pub mod results;
 pub mod source;
@@ -0,0 +1,339 @@
+use serde::{Deserialize, Serialize};
+use strum::{Display, VariantArray};
+
+/// The language used for titles, help and notifications. Anything missing
+/// from a catalog is shown in English.
+#[derive(
+    Serialize, Deserialize, Display, Clone, Copy, Default, VariantArray, PartialEq, Eq, Debug,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    #[strum(serialize = "en")]
+    En,
+    #[strum(serialize = "ja")]
+    Ja,
+}
+
+impl Locale {
+    /// Translate an English string, falling back to it if there is no entry
+    pub fn tr(self, msg: &str) -> &str {
+        let catalog = match self {
+            Locale::En => return msg,
+            Locale::Ja => JA,
+        };
+        catalog
+            .iter()
+            .find(|(en, _)| *en == msg)
+            .map(|(_, tr)| *tr)
+            .unwrap_or(msg)
+    }
+}
+
+static JA: &[(&str, &str)] = &[
+    // Titles
+    ("Search", "検索"),
+    ("Results", "検索結果"),
+    ("Front Page", "トップページ"),
+    ("Batch", "一括"),
+    ("Category", "カテゴリ"),
+    ("Filter", "フィルター"),
+    ("1080p only", "1080p のみ"),
+    ("HEVC only", "HEVC のみ"),
+    ("Batches only", "バッチのみ"),
+    ("Hide remakes", "リメイクを隠す"),
+    ("Hide untrusted", "未信頼を隠す"),
+    ("Starred only", "スターのみ"),
+    ("Sort", "並べ替え"),
+    ("Sort Ascending", "昇順に並べ替え"),
+    ("Sort Descending", "降順に並べ替え"),
+    ("total", "件"),
+    ("new", "件の新着"),
+    ("Theme", "テーマ"),
+    ("Source", "ソース"),
+    ("Download Client", "ダウンロードクライアント"),
+    ("Goto Page", "ページ移動"),
+    ("Search Constraints", "検索条件"),
+    ("Query Template", "検索テンプレート"),
+    ("Download Batch", "一括ダウンロード"),
+    ("Download", "ダウンロード"),
+    ("Error", "エラー"),
+    ("Help", "ヘルプ"),
+    ("Key", "キー"),
+    ("Action", "操作"),
+    ("Find", "ページ内検索"),
+    ("matches", "件一致"),
+    ("Details", "詳細"),
+    ("Size", "サイズ"),
+    ("Date", "日付"),
+    ("Seeders", "シーダー"),
+    ("Leechers", "リーチャー"),
+    ("Downloads", "ダウンロード数"),
+    ("Post", "投稿"),
+    ("Recent searches", "最近の検索"),
+    ("Presets", "プリセット"),
+    ("Keys", "キー操作"),
+    ("None yet", "まだありません"),
+    ("Host", "ホスト"),
+    ("Mode", "形式"),
+    ("Status", "状態"),
+    ("ok", "正常"),
+    ("failing", "失敗"),
+    ("Auth", "認証"),
+    ("Columns", "列"),
+    ("Title", "タイトル"),
+    ("Resolution", "解像度"),
+    ("Episode", "話数"),
+    ("Group", "グループ"),
+    ("Language", "言語"),
+    ("Uploader", "投稿者"),
+    ("Views", "閲覧数"),
+    ("Season", "シーズン"),
+    ("signed in", "ログイン中"),
+    ("signed out", "未ログイン"),
+    ("cookie", "クッキー"),
+    ("token", "トークン"),
+    ("testing", "確認中"),
+    ("fetched", "件取得"),
+    ("failed", "件失敗"),
+    ("What's New", "新機能"),
+    ("Stats", "統計"),
+    ("Tasks", "タスク"),
+    ("Cancel task", "タスクをキャンセル"),
+    (" Task", " タスク"),
+    ("Doing", "内容"),
+    ("Running", "実行時間"),
+    ("Events", "イベント"),
+    ("Validate", "リンク確認"),
+    ("Watch later", "後で見る"),
+    ("Test client", "クライアント確認"),
+    ("Update", "アップデート"),
+    ("Searches", "検索数"),
+    ("Requests", "リクエスト数"),
+    ("Cache hits", "キャッシュヒット数"),
+    ("Downloaded", "ダウンロード量"),
+    ("Failed", "失敗数"),
+    ("Latency", "平均応答時間"),
+    // Modes
+    ("Normal", "ノーマル"),
+    ("Clients", "クライアント"),
+    ("Sources", "ソース"),
+    ("Page", "ページ"),
+    ("User", "検索条件"),
+    ("Template", "テンプレート"),
+    ("Loading", "読み込み中"),
+    ("Captcha", "キャプチャ"),
+    ("Confirm", "確認"),
+    // Help
+    ("Confirm", "決定"),
+    ("Confirm/Use preset", "決定/プリセットを使用"),
+    (
+        "Cancel search/Dismiss notification",
+        "検索を中止/通知を閉じる",
+    ),
+    ("Close", "閉じる"),
+    (
+        "Cycle ascending, descending, default",
+        "昇順/降順/既定を切り替え",
+    ),
+    ("Stop", "中止"),
+    ("Start download", "ダウンロード開始"),
+    ("Cancel", "キャンセル"),
+    ("Toggle paused", "一時停止を切り替え"),
+    (
+        "Stop batch download, while downloading",
+        "ダウンロード中は一括ダウンロードを中止",
+    ),
+    ("Toggle quick filter", "クイックフィルターを切り替え"),
+    (
+        "Check filter, where several can be used",
+        "フィルターを選択（複数可の場合）",
+    ),
+    (
+        "Export batch as HTML/Markdown",
+        "バッチを HTML/Markdown で書き出す",
+    ),
+    ("Exit App", "終了"),
+    ("Exit app", "終了"),
+    ("Top", "先頭"),
+    ("Bottom", "末尾"),
+    ("Goto Top/Bottom", "先頭/末尾へ移動"),
+    ("Down", "下へ"),
+    ("Up", "上へ"),
+    ("Down/Up", "下/上へ"),
+    ("Up/Down 4 items", "4件上/下へ"),
+    ("Repeat a motion, e.g. 5j", "移動を繰り返す（例: 5j）"),
+    ("Next Page", "次のページ"),
+    ("Prev Page", "前のページ"),
+    ("Last Page", "最後のページ"),
+    ("First Page", "最初のページ"),
+    ("Next Tab", "次のタブ"),
+    ("Prev Tab", "前のタブ"),
+    ("Reload", "再読み込み"),
+    ("Open in browser", "ブラウザで開く"),
+    ("Categories", "カテゴリ"),
+    ("Filters", "フィルター"),
+    ("Sort reversed", "逆順に並べ替え"),
+    ("Themes", "テーマ"),
+    ("Find on page", "ページ内を検索"),
+    ("Filter categories", "カテゴリを絞り込み"),
+    ("Edit source settings", "ソースの設定を編集"),
+    ("Test connections again", "接続を再確認"),
+    ("New captcha", "新しいキャプチャ"),
+    (
+        "Open captcha outside the terminal",
+        "キャプチャをターミナルの外で開く",
+    ),
+    ("Next/Prev match, once found", "次/前の一致へ（検索後）"),
+    (
+        "Toggle front page/search results",
+        "トップページ/検索結果を切り替え",
+    ),
+    ("Filter by User", "ユーザーで絞り込み"),
+    ("Select source", "ソースを選択"),
+    ("Select download client", "ダウンロードクライアントを選択"),
+    ("Deep search", "複数ページを検索"),
+    ("Toggle minimal layout", "ミニマル表示の切り替え"),
+    ("Goto page", "ページ移動"),
+    ("Focus next/previous pane", "次/前のペインにフォーカス"),
+    (
+        "Toggle item for batch download",
+        "一括ダウンロードに追加/削除",
+    ),
+    ("Multi-line select torrents", "複数行を選択"),
+    ("Star/unstar item", "スターを付ける/外す"),
+    ("Add/remove item from watch later", "後で見るに追加/削除"),
+    ("Watch later", "後で見る"),
+    ("Watch Later", "後で見る"),
+    ("Add items from the results with w", "結果から w で追加"),
+    ("Move item to batch", "一括に移動"),
+    ("Move every item to batch", "すべて一括に移動"),
+    ("Refresh seeders", "シーダー数を更新"),
+    ("Remove item", "項目を削除"),
+    ("Same show", "同じ作品"),
+    ("Same group", "同じグループ"),
+    ("Same show, other groups", "同じ作品、他のグループ"),
+    ("Same group, other episodes", "同じグループ、他の話"),
+    (
+        "Related: same show/same group",
+        "関連: 同じ作品/同じグループ",
+    ),
+    ("Back to the search before", "前の検索に戻る"),
+    ("Forward to the next search", "次の検索に進む"),
+    ("Quit", "終了"),
+    (
+        "Wait for downloads, then quit",
+        "ダウンロードを待ってから終了",
+    ),
+    ("Cancel downloads and quit", "ダウンロードを中止して終了"),
+    ("Back, without quitting", "終了せずに戻る"),
+    (
+        "Enter: wait for them, c: cancel them, Esc: back",
+        "Enter: 待つ、c: 中止、Esc: 戻る",
+    ),
+    (
+        "c: cancel them and quit, Esc: back",
+        "c: 中止して終了、Esc: 戻る",
+    ),
+    ("Add page/all pages to batch", "ページ/全ページを一括に追加"),
+    ("Invert/Clear batch selection", "一括の選択を反転/クリア"),
+    (
+        "Copy torrent/magnet/post/imdb id",
+        "トレント/マグネット/投稿/IMDb IDをコピー",
+    ),
+    (
+        "Copy batch magnets/torrents",
+        "一括のマグネット/トレントをコピー",
+    ),
+    ("Download single torrent", "トレントをダウンロード"),
+    ("Download all torrents", "すべてのトレントをダウンロード"),
+    ("Clear batch", "一括をクリア"),
+    ("Undo batch change", "一括の変更を元に戻す"),
+    ("Back to results", "検索結果に戻る"),
+    ("Next field", "次の項目"),
+    ("Previous field", "前の項目"),
+    ("Back to search", "検索に戻る"),
+    ("Change category/filter", "カテゴリ/フィルターを変更"),
+    ("Save as preset", "プリセットとして保存"),
+    ("Delete preset", "プリセットを削除"),
+    ("Move left", "左へ移動"),
+    ("Move right", "右へ移動"),
+    ("Clear search", "検索をクリア"),
+    ("Complete word", "単語を補完"),
+    ("Remove user, at the start", "先頭でユーザーを解除"),
+    (
+        "Recent users, from the user field",
+        "ユーザー欄から最近のユーザー",
+    ),
+    ("Recent", "最近"),
+    ("Checking user…", "ユーザーを確認中…"),
+    ("User found", "ユーザーが見つかりました"),
+    ("User has no results", "ユーザーの結果はありません"),
+    ("No such user", "ユーザーが存在しません"),
+    ("Couldn't check user", "ユーザーを確認できません"),
+    ("End of line", "行末へ"),
+    ("Beginning of line", "行頭へ"),
+    ("Back word", "前の単語へ"),
+    ("Forward word", "次の単語へ"),
+    ("Delete word forward", "後ろの単語を削除"),
+    ("Delete word backwards", "前の単語を削除"),
+    ("Delete letter forwards", "後ろの文字を削除"),
+    ("Delete letter backwards", "前の文字を削除"),
+    ("Record macro/Stop recording", "マクロを記録/記録を停止"),
+    (
+        "Replay macro/Replay last macro",
+        "マクロを再生/直前のマクロを再生",
+    ),
+    ("Copy torrent link", "トレントのリンクをコピー"),
+    ("Copy magnet link", "マグネットリンクをコピー"),
+    ("Copy post link", "投稿のリンクをコピー"),
+    ("Copy imdb id", "IMDb IDをコピー"),
+    ("Copy batch magnets", "一括のマグネットをコピー"),
+    ("Copy batch torrents", "一括のトレントをコピー"),
+    ("Add page to batch", "ページを一括に追加"),
+    ("Add all pages to batch", "全ページを一括に追加"),
+    ("Invert batch selection", "一括の選択を反転"),
+    ("Clear batch selection", "一括の選択をクリア"),
+    ("Record macro", "マクロを記録"),
+    ("Replay macro", "マクロを再生"),
+    ("Replay last macro", "直前のマクロを再生"),
+    // Notifications
+    ("No results", "結果なし"),
+    (
+        "Try another category (c) or filter (f)",
+        "別のカテゴリ (c) かフィルター (f) を試してください",
+    ),
+    (
+        "Try searching all users (u)",
+        "すべてのユーザーで検索してください (u)",
+    ),
+    (
+        "Nothing could be read from the page",
+        "ページから何も読み取れませんでした",
+    ),
+    (
+        "The mirror may be down, or its layout may have changed",
+        "ミラーが停止しているか、レイアウトが変わった可能性があります",
+    ),
+    (
+        "Try setting rss = true for the source (s, then e)",
+        "ソースに rss = true を設定してください (s の後に e)",
+    ),
+    (
+        "Try another source (s) or base_url",
+        "別のソース (s) か base_url を試してください",
+    ),
+    (
+        "Every result is hidden by quick filters",
+        "すべての結果がクイックフィルターで非表示になっています",
+    ),
+    (
+        "Turn them off in the filter popup (f)",
+        "フィルター (f) でオフにしてください",
+    ),
+    ("Press r to retry", "rキーで再試行"),
+    (
+        "Enter a name to save the preset as",
+        "保存するプリセットの名前を入力してください",
+    ),
+];
@@ -34,6 +34,17 @@ macro_rules! widgets {
         }
 
         impl $name {
+            /// Whether `mode` shows a popup over the panes
+            pub fn is_popup(mode: &$crate::app::Mode) -> bool {
+                match mode {
+                    $(
+                        $(#[$docs])*
+                        $($pmode => true,)?
+                    )+
+                    _ => false,
+                }
+            }
+
             fn draw_popups(&mut self, ctx: &$crate::app::Context, f: &mut ratatui::Frame) {
                 match ctx.mode {
                     $(
@@ -181,7 +192,8 @@ macro_rules! sel {
     (
         $text:expr
     ) => {{
-        let raw = Selector::parse($text).map_err(|e| e.to_string());
+        let raw = Selector::parse($text)
+            .map_err(|e| $crate::error::NyaaError::Parse(format!("Invalid selector: {}", e)));
         raw
     }};
 }
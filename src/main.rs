@@ -1,14 +1,24 @@
-use std::{env, io::stdout};
+use std::{env, error::Error, io::stdout, path::Path, sync::Arc};
 
 use app::App;
-use config::AppConfig;
-use ratatui::{backend::CrosstermBackend, Terminal};
-use sync::AppSync;
+use config::{AppConfig, ConfigManager as _};
+use ratatui::{
+    backend::{CrosstermBackend, TestBackend},
+    Terminal,
+};
+use reqwest::cookie::Jar;
+use source::SourceResponse;
+use sync::{
+    replay::{Recorder, ReplaySync, Session},
+    AppSync, SearchQuery,
+};
 
 pub mod app;
 pub mod client;
 pub mod clip;
 pub mod config;
+pub mod error;
+pub mod locale;
 pub mod macros;
 pub mod results;
 pub mod source;
@@ -18,35 +28,206 @@ pub mod util;
 pub mod widget;
 
 #[tokio::main()]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() -> Result<(), Box<dyn Error>> {
     let default_panic = std::panic::take_hook();
     std::panic::set_hook(Box::new(move |info| {
         // Try to reset terminal on panic
         let _ = util::term::reset_terminal();
         default_panic(info);
+        let backtrace = std::backtrace::Backtrace::force_capture().to_string();
+        let report = util::crash::report(&info.to_string(), &backtrace);
+        match util::crash::dir().map(|dir| util::crash::write(dir, &report)) {
+            Some(Ok(path)) => eprintln!(
+                "\nA crash report was saved to {}\nPlease attach it when reporting the bug",
+                path.display()
+            ),
+            Some(Err(e)) => eprintln!("\nFailed to save a crash report:\n{}", e),
+            None => eprintln!("\nFailed to save a crash report:\nNo home directory"),
+        }
         std::process::exit(1);
     }));
 
     // TODO: Use real command line package
-    let args: Vec<String> = env::args().collect();
-    for arg in args {
-        if arg == "--version" || arg == "-V" || arg == "-v" {
-            println!("nyaa v{}", env!("CARGO_PKG_VERSION"));
-            return Ok(());
+    let mut args = env::args().skip(1);
+    let mut sync = AppSync::default();
+    let mut replay_path = None;
+    let mut stats_path = None;
+    let mut plain_query = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--version" | "-V" | "-v" => {
+                println!("nyaa v{}", env!("CARGO_PKG_VERSION"));
+                return Ok(());
+            }
+            "--help" | "-h" => {
+                print!("{}", widget::help::to_usage(&App::default().keybinds()));
+                return Ok(());
+            }
+            "--record" => {
+                let path = args.next().ok_or("--record requires a file")?;
+                sync = sync.record(Recorder::new(path)?);
+            }
+            "--offline" => {
+                sync = sync.offline();
+            }
+            "--export-keybinds" => {
+                let path = args.next().ok_or("--export-keybinds requires a file")?;
+                let md = widget::help::to_markdown(&App::default().keybinds());
+                std::fs::write(&path, md)
+                    .map_err(|e| format!("{}\nFailed to write keybinds:\n{}", path, e))?;
+                return Ok(());
+            }
+            "--export-man" => {
+                let path = args.next().ok_or("--export-man requires a file")?;
+                let man = widget::help::to_man(&App::default().keybinds());
+                std::fs::write(&path, man)
+                    .map_err(|e| format!("{}\nFailed to write man page:\n{}", path, e))?;
+                return Ok(());
+            }
+            "secret" => {
+                return secret_cmd(args.next(), args.next());
+            }
+            "self-update" => {
+                return self_update().await;
+            }
+            "--stats-json" => {
+                stats_path = Some(args.next().ok_or("--stats-json requires a file")?);
+            }
+            "--plain" => {
+                plain_query = Some(args.next().ok_or("--plain requires a search")?);
+            }
+            "--replay" => {
+                replay_path = Some(args.next().ok_or("--replay requires a file")?);
+            }
+            _ => {}
         }
     }
+    if let Some(path) = replay_path {
+        return replay(path, sync).await;
+    }
+    if let Some(query) = plain_query {
+        return plain(query).await;
+    }
     util::term::setup_terminal()?;
+    tokio::task::spawn(util::term::reset_on_terminate());
     let backend = CrosstermBackend::new(stdout());
     let mut terminal = Terminal::new(backend)?;
 
     let mut app = App::default();
-    let sync = AppSync {};
 
-    app.run_app::<_, _, AppConfig, false>(&mut terminal, sync)
-        .await?;
+    // Always restore the terminal, even if the app exited with an error
+    let res = app
+        .run_app::<_, _, AppConfig, false>(&mut terminal, sync)
+        .await;
+
+    // Written before any error is returned, since runs which fail are the ones
+    // worth looking into
+    let stats = match stats_path {
+        Some(path) => {
+            let json = util::stats::to_json(&util::stats::snapshot());
+            std::fs::write(&path, json)
+                .map_err(|e| format!("{}\nFailed to write stats:\n{}", path, e))
+        }
+        None => Ok(()),
+    };
 
     util::term::reset_terminal()?;
     terminal.show_cursor()?;
+    res?;
+    stats?;
+    Ok(())
+}
+
+/// Replay a recorded session without a terminal, then print the final frame
+async fn replay(path: impl AsRef<Path>, sync: AppSync) -> Result<(), Box<dyn Error>> {
+    let session = Session::load(path)?;
+    let (w, h) = session.size().unwrap_or((80, 24));
+    let mut terminal = Terminal::new(TestBackend::new(w, h))?;
+
+    let mut app = App::default();
+    app.run_app::<_, _, AppConfig, true>(&mut terminal, ReplaySync::new(session, sync))
+        .await?;
 
-    std::process::exit(0);
+    let buf = terminal.backend().buffer();
+    for y in 0..buf.area.height {
+        let line: String = (0..buf.area.width)
+            .map(|x| buf.get(x, y).symbol())
+            .collect();
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+/// Search the default source without a terminal, then print each result on a
+/// line of its own, for screen readers and scripts
+async fn plain(query: String) -> Result<(), Box<dyn Error>> {
+    let mut ctx = app::Context::default();
+    ctx.config = AppConfig::load()?;
+    ctx.src = ctx.config.source;
+    ctx.src.load_config(&mut ctx.config.sources);
+    let client = source::source_request_client(&Arc::new(Jar::default()), &ctx)?;
+    let search = SearchQuery {
+        query,
+        ..Default::default()
+    };
+    let res = ctx
+        .src
+        .load(
+            app::LoadType::Searching,
+            &client,
+            &search,
+            &ctx.config.sources,
+            ctx.config.date_format.clone(),
+        )
+        .await?;
+    // Only one variant without the captcha feature
+    #[allow(clippy::infallible_destructuring_match)]
+    let res = match res {
+        SourceResponse::Results(res) => res,
+        #[cfg(feature = "captcha")]
+        SourceResponse::Captcha(_) => {
+            return Err("The source asked for a captcha, search in nyaa to solve it".into())
+        }
+    };
+    for item in res.items {
+        println!("{}", util::export::plain_line(&item));
+    }
+    Ok(())
+}
+
+/// Replace this binary with the latest release from GitHub, with
+/// `nyaa self-update`
+async fn self_update() -> Result<(), Box<dyn Error>> {
+    let mut ctx = app::Context::default();
+    ctx.config = AppConfig::load()?;
+    let client = source::request_client(&Arc::new(Jar::default()), &ctx)?;
+    println!("{}", util::update::self_update(&client).await?);
+    Ok(())
+}
+
+/// Store or delete a secret in the OS keyring, with `nyaa secret set <name>`
+/// or `nyaa secret delete <name>`
+fn secret_cmd(action: Option<String>, name: Option<String>) -> Result<(), Box<dyn Error>> {
+    let usage = format!(
+        "Usage: nyaa secret <set|delete> <name>\nWhere name is one of:\n{}",
+        config::secret::KEYRING_SECRETS.join("\n")
+    );
+    let (Some(action), Some(name)) = (action, name) else {
+        return Err(usage.into());
+    };
+    match action.as_str() {
+        "set" => {
+            config::secret::check_name(&name)?;
+            eprint!("Enter the value for {} (hidden): ", name);
+            let secret = util::term::read_hidden()?;
+            config::secret::keyring_set(&name, &secret)?;
+            println!("Stored {} in the keyring", name);
+        }
+        "delete" => match config::secret::keyring_delete(&name)? {
+            true => println!("Deleted {} from the keyring", name),
+            false => println!("{} was not in the keyring", name),
+        },
+        _ => return Err(usage.into()),
+    }
+    Ok(())
 }
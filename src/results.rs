@@ -1,3 +1,5 @@
+use std::{collections::HashSet, ops::Range};
+
 use ratatui::{
     layout::{Alignment, Constraint},
     style::{Style, Stylize},
@@ -5,13 +7,92 @@ use ratatui::{
     widgets::Row,
 };
 
-use crate::{source::Item, sync::SearchQuery, widget::sort::SortDir};
+use crate::{
+    cond_vec,
+    source::{Item, ItemId, ItemType},
+    sync::SearchQuery,
+    theme::Theme,
+    util::anime::ParsedTitle,
+    widget::sort::SortDir,
+};
+
+pub mod history;
 
 #[derive(Clone, Default)]
 pub struct Results {
     pub search: SearchQuery,
     pub response: ResultResponse,
     pub table: ResultTable,
+    /// Items which weren't in the last results for the same search
+    pub new: HashSet<ItemId>,
+    /// Items whose torrent link no longer exists
+    pub dead: HashSet<ItemId>,
+    /// Ids of the items starred with `*`, like `nyaa-123`
+    pub starred: HashSet<String>,
+    /// Every item loaded, before any quick filters hid some of them
+    pub unfiltered: Option<Vec<Item>>,
+    /// Every item found by a deep search, shown a page at a time without
+    /// loading again
+    pub deep: Option<Vec<Item>>,
+}
+
+/// How results are shown, sent along with each search so the task loading it
+/// builds the rows to draw, and nothing is left to do once it's done
+#[derive(Clone, Default)]
+pub struct ResultsView {
+    pub theme: Theme,
+    pub quick_filters: Vec<QuickFilter>,
+    pub starred: HashSet<String>,
+}
+
+/// Filters applied to the loaded results without searching again, toggled in
+/// the filter popup
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum QuickFilter {
+    FullHd,
+    Hevc,
+    Batch,
+    NoRemakes,
+    TrustedOnly,
+    Starred,
+}
+
+impl QuickFilter {
+    pub const ALL: [QuickFilter; 6] = [
+        QuickFilter::FullHd,
+        QuickFilter::Hevc,
+        QuickFilter::Batch,
+        QuickFilter::NoRemakes,
+        QuickFilter::TrustedOnly,
+        QuickFilter::Starred,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            QuickFilter::FullHd => "1080p only",
+            QuickFilter::Hevc => "HEVC only",
+            QuickFilter::Batch => "Batches only",
+            QuickFilter::NoRemakes => "Hide remakes",
+            QuickFilter::TrustedOnly => "Hide untrusted",
+            QuickFilter::Starred => "Starred only",
+        }
+    }
+
+    /// Whether the filter is kept in the config, staying on between sessions
+    pub fn sticky(self) -> bool {
+        matches!(self, QuickFilter::NoRemakes | QuickFilter::TrustedOnly)
+    }
+
+    pub fn matches(self, item: &Item, parsed: &ParsedTitle, starred: &HashSet<String>) -> bool {
+        match self {
+            QuickFilter::FullHd => parsed.resolution.as_deref() == Some("1080p"),
+            QuickFilter::Hevc => parsed.codec.as_deref() == Some("HEVC"),
+            QuickFilter::Batch => parsed.batch,
+            QuickFilter::NoRemakes => !matches!(item.item_type, ItemType::Remake),
+            QuickFilter::TrustedOnly => matches!(item.item_type, ItemType::Trusted),
+            QuickFilter::Starred => starred.contains(&item.id.to_string()),
+        }
+    }
 }
 
 impl Results {
@@ -20,15 +101,205 @@ impl Results {
             search,
             response,
             table,
+            new: HashSet::new(),
+            dead: HashSet::new(),
+            starred: HashSet::new(),
+            unfiltered: None,
+            deep: None,
+        }
+    }
+
+    /// Results ready to be drawn, with only the items `view` shows formatted
+    /// by `format`. The items of a deep search are split into pages first.
+    pub fn prepare<F>(
+        search: SearchQuery,
+        response: ResultResponse,
+        view: ResultsView,
+        deep: bool,
+        format: F,
+    ) -> Self
+    where
+        F: FnOnce(&[Item], &SearchQuery, &Theme) -> ResultTable,
+    {
+        let mut results = Results::new(search, response, ResultTable::default());
+        results.starred = view.starred;
+        if deep {
+            results.paginate();
+        }
+        if !view.quick_filters.is_empty() {
+            results.quick_filter(&view.quick_filters);
+        }
+        results.table = format(&results.response.items, &results.search, &view.theme);
+        results
+    }
+
+    /// Every item loaded, including those hidden by quick filters or on other
+    /// pages of a deep search
+    pub fn all_items(&self) -> &Vec<Item> {
+        self.deep
+            .as_ref()
+            .or(self.unfiltered.as_ref())
+            .unwrap_or(&self.response.items)
+    }
+
+    /// Keep the items of a deep search, split into `last_page` pages to be
+    /// shown one at a time, starting from the first
+    pub fn paginate(&mut self) {
+        self.deep = Some(std::mem::take(&mut self.response.items));
+        self.show_page(1);
+    }
+
+    /// Show `page` of a deep search, returning false if these aren't the
+    /// results of one
+    pub fn show_page(&mut self, page: usize) -> bool {
+        let Some(deep) = self.deep.as_ref() else {
+            return false;
+        };
+        let per_page = deep.len().div_ceil(self.response.last_page.max(1)).max(1);
+        self.response.items = deep
+            .iter()
+            .skip(page.saturating_sub(1) * per_page)
+            .take(per_page)
+            .cloned()
+            .collect();
+        self.unfiltered = None;
+        true
+    }
+
+    /// Only show the items matching every filter in `filters`
+    pub fn quick_filter(&mut self, filters: &[QuickFilter]) {
+        let all = self
+            .unfiltered
+            .take()
+            .unwrap_or_else(|| self.response.items.clone());
+        self.response.items = all
+            .iter()
+            .filter(|i| {
+                let parsed = i.parsed();
+                filters.iter().all(|f| f.matches(i, &parsed, &self.starred))
+            })
+            .cloned()
+            .collect();
+        if !filters.is_empty() {
+            self.unfiltered = Some(all);
+        }
+    }
+
+    /// Why no items are shown, if there are none
+    pub fn empty_reason(&self) -> Option<EmptyReason> {
+        match self.unfiltered.as_ref().is_some_and(|u| !u.is_empty()) {
+            true if self.response.items.is_empty() => Some(EmptyReason::Filtered),
+            _ => self.response.empty_reason(),
+        }
+    }
+
+    /// Mark each item not in `prev` as new, if it was the same search
+    pub fn mark_new(&mut self, prev: &Results) {
+        if self.search != prev.search || prev.all_items().is_empty() {
+            return;
+        }
+        let prev_ids: HashSet<&ItemId> = prev.all_items().iter().map(|i| &i.id).collect();
+        self.new = self
+            .all_items()
+            .iter()
+            .filter(|i| !prev_ids.contains(&i.id))
+            .map(|i| i.id.to_owned())
+            .collect();
+    }
+
+    /// Whether `item` has been starred
+    pub fn is_starred(&self, item: &Item) -> bool {
+        self.starred.contains(&item.id.to_string())
+    }
+
+    /// The first row from `from` whose title fuzzily matches `pattern`,
+    /// searching backwards if not `forward` and wrapping around the page
+    pub fn find(&self, pattern: &str, from: usize, forward: bool) -> Option<usize> {
+        let len = self.response.items.len();
+        if len == 0 || pattern.trim().is_empty() {
+            return None;
+        }
+        (0..len)
+            .map(|i| match forward {
+                true => (from + i) % len,
+                false => (from + len * 2 - i) % len,
+            })
+            .find(|i| fuzzy_match(pattern, &self.response.items[*i].title))
+    }
+
+    /// How many titles on the page fuzzily match `pattern`
+    pub fn num_matches(&self, pattern: &str) -> usize {
+        self.response
+            .items
+            .iter()
+            .filter(|i| fuzzy_match(pattern, &i.title))
+            .count()
+    }
+
+    /// Mark the items in `ids` which are still shown as having a dead link
+    pub fn mark_dead(&mut self, ids: Vec<ItemId>) {
+        for id in ids {
+            if self.response.items.iter().any(|i| i.id == id) {
+                self.dead.insert(id);
+            }
         }
     }
 }
 
-#[derive(Default, Clone)]
+/// Whether every character of `pattern` appears in `text` in order, ignoring
+/// case and spaces
+fn fuzzy_match(pattern: &str, text: &str) -> bool {
+    let mut chars = text.chars().flat_map(char::to_lowercase);
+    pattern
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .all(|p| chars.any(|c| c == p))
+}
+
+#[derive(Clone)]
 pub struct ResultResponse {
     pub items: Vec<Item>,
     pub last_page: usize,
-    pub total_results: usize,
+    /// Number of results the source said it found. `None` if the page didn't
+    /// say, like the front page.
+    pub total_results: Option<usize>,
+    /// Problems that did not stop the results from loading, shown as a
+    /// notification
+    pub warnings: Vec<String>,
+}
+
+impl Default for ResultResponse {
+    fn default() -> Self {
+        ResultResponse {
+            items: vec![],
+            last_page: 0,
+            total_results: Some(0),
+            warnings: vec![],
+        }
+    }
+}
+
+/// Why a response has no items
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EmptyReason {
+    /// The source said nothing matched the search
+    NoResults,
+    /// The page didn't say there were no results, but no items could be read
+    /// from it, so the mirror or its layout has probably changed
+    Unreadable,
+    /// Every item was hidden by quick filters
+    Filtered,
+}
+
+impl ResultResponse {
+    pub fn empty_reason(&self) -> Option<EmptyReason> {
+        match (self.items.is_empty(), self.total_results) {
+            (false, _) => None,
+            (true, Some(0)) => Some(EmptyReason::NoResults),
+            (true, _) => Some(EmptyReason::Unreadable),
+        }
+    }
 }
 
 pub struct ResultHeader<S: PartialEq + Copy> {
@@ -84,7 +355,7 @@ impl<S: PartialEq + Copy> ResultColumn<S> {
                             name.replace_range(
                                 name.char_indices()
                                     .nth(idx + 2)
-                                    .map(|(pos, ch)| (pos..pos + ch.len_utf8()))
+                                    .map(|(pos, ch)| pos..pos + ch.len_utf8())
                                     .unwrap(),
                                 match dir {
                                     SortDir::Asc => "▲",
@@ -106,6 +377,41 @@ pub struct ResultTable {
     pub headers: ResultRow,
     pub rows: Vec<ResultRow>,
     pub binding: Vec<Constraint>,
+    /// Whether each column is still shown on narrow terminals. Every column
+    /// is kept if this is empty.
+    pub narrow: Vec<bool>,
+}
+
+impl ResultTable {
+    /// Only the `rows` in view, so drawing costs the same however many
+    /// results there are. The columns hidden on narrow terminals are dropped
+    /// if `narrow`.
+    pub fn window(&self, rows: Range<usize>, narrow: bool) -> ResultTable {
+        let rows = self.rows.get(rows).unwrap_or_default().to_vec();
+        if !narrow || self.narrow.len() != self.binding.len() {
+            return ResultTable {
+                headers: self.headers.clone(),
+                rows,
+                binding: self.binding.clone(),
+                narrow: self.narrow.clone(),
+            };
+        }
+        let cols = &self.narrow;
+        let mut headers = self.headers.clone();
+        headers.cells = cond_vec!(cols ; headers.cells);
+        ResultTable {
+            headers,
+            rows: rows
+                .into_iter()
+                .map(|mut r| {
+                    r.cells = cond_vec!(cols ; r.cells);
+                    r
+                })
+                .collect(),
+            binding: cond_vec!(cols ; self.binding.to_owned()),
+            narrow: vec![],
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -0,0 +1,190 @@
+use super::Results;
+
+/// Most views kept to go back to
+static MAX_HISTORY: usize = 50;
+
+/// Results shown earlier, to go back to without loading them again
+#[derive(Clone, Default)]
+pub struct View {
+    pub results: Results,
+    pub page: usize,
+    /// Whether it was the front page instead of the results of the search
+    pub browsing: bool,
+    /// What was in the search box
+    pub input: String,
+    /// Row which was highlighted
+    pub selected: usize,
+    /// Shown in the title in place of `Results`, e.g. `Same show`
+    pub label: Option<&'static str>,
+    /// Whether its items were dropped to keep memory down, so it has to be
+    /// loaded again when gone back to
+    pub evicted: bool,
+    /// When it was last left, to drop the items of the views seen longest ago
+    /// first
+    left: u64,
+}
+
+impl View {
+    /// How many items it keeps in memory
+    pub fn kept_items(&self) -> usize {
+        self.results.all_items().len()
+    }
+
+    /// Drop every item, keeping the search to load them again
+    fn evict(&mut self) {
+        self.results = Results {
+            search: std::mem::take(&mut self.results.search),
+            ..Results::default()
+        };
+        self.evicted = true;
+    }
+}
+
+/// Views to go back and forward through, like a browser. A view is left
+/// behind whenever results of a different search are loaded, but not when
+/// only the page or sort changes.
+#[derive(Clone, Default)]
+pub struct History {
+    back: Vec<View>,
+    forward: Vec<View>,
+    /// Page, search and label of the results shown now
+    page: usize,
+    browsing: bool,
+    input: String,
+    label: Option<&'static str>,
+    /// Label of the results being loaded
+    pending_label: Option<&'static str>,
+    /// Counts up each time a view is left
+    clock: u64,
+}
+
+impl History {
+    pub fn can_go_back(&self) -> bool {
+        !self.back.is_empty()
+    }
+
+    pub fn can_go_forward(&self) -> bool {
+        !self.forward.is_empty()
+    }
+
+    /// Label of the results shown now, if they aren't a plain search
+    pub fn label(&self) -> Option<&'static str> {
+        self.label
+    }
+
+    /// Label the results of the next load, e.g. `Same show`
+    pub fn label_next(&mut self, label: &'static str) {
+        self.pending_label = Some(label);
+    }
+
+    /// Note how the results just shown were searched for. The label given to
+    /// the load is only used if they're from a different search.
+    pub fn loaded(&mut self, page: usize, browsing: bool, input: String, new_view: bool) {
+        self.page = page;
+        self.browsing = browsing;
+        self.input = input;
+        let label = self.pending_label.take();
+        if new_view {
+            self.label = label;
+        }
+    }
+
+    /// Note a page of a deep search shown without loading it
+    pub fn set_page(&mut self, page: usize) {
+        self.page = page;
+    }
+
+    /// The view shown now, made of `results` with `selected` highlighted
+    pub fn current(&self, results: Results, selected: usize) -> View {
+        View {
+            results,
+            page: self.page,
+            browsing: self.browsing,
+            input: self.input.to_owned(),
+            selected,
+            label: self.label,
+            evicted: false,
+            left: 0,
+        }
+    }
+
+    /// Leave `view` behind to go back to, forgetting what was ahead of it
+    pub fn visit(&mut self, view: View) {
+        let view = self.leave(view);
+        self.back.push(view);
+        if self.back.len() > MAX_HISTORY {
+            self.back.remove(0);
+        }
+        self.forward.clear();
+    }
+
+    /// The view before `current`, which is kept to go forward to
+    pub fn back(&mut self, current: View) -> Option<View> {
+        let view = self.back.pop()?;
+        let current = self.leave(current);
+        self.forward.push(current);
+        self.show(&view);
+        Some(view)
+    }
+
+    /// The view after `current`, which is kept to go back to
+    pub fn forward(&mut self, current: View) -> Option<View> {
+        let view = self.forward.pop()?;
+        let current = self.leave(current);
+        self.back.push(current);
+        self.show(&view);
+        Some(view)
+    }
+
+    /// Drop the items of the views left longest ago until no more than `max`
+    /// are kept, counting the `shown` items of the results shown now, which
+    /// are never dropped. Returns how many views were dropped.
+    pub fn evict(&mut self, max: usize, shown: usize) -> usize {
+        let mut kept = shown + self.kept_items();
+        let mut evicted = 0;
+        while kept > max {
+            let Some(oldest) = self
+                .back
+                .iter_mut()
+                .chain(self.forward.iter_mut())
+                .filter(|v| v.kept_items() > 0)
+                .min_by_key(|v| v.left)
+            else {
+                break;
+            };
+            kept -= oldest.kept_items();
+            oldest.evict();
+            evicted += 1;
+        }
+        evicted
+    }
+
+    /// How many items the views to go back and forward to keep
+    pub fn kept_items(&self) -> usize {
+        self.views().map(View::kept_items).sum()
+    }
+
+    fn views(&self) -> impl Iterator<Item = &View> {
+        self.back.iter().chain(self.forward.iter())
+    }
+
+    fn leave(&mut self, mut view: View) -> View {
+        self.clock += 1;
+        view.left = self.clock;
+        view
+    }
+
+    /// Forget every view, as when the source changes
+    pub fn clear(&mut self) {
+        self.back.clear();
+        self.forward.clear();
+        self.label = None;
+    }
+
+    fn show(&mut self, view: &View) {
+        self.page = view.page;
+        self.browsing = view.browsing;
+        view.input.clone_into(&mut self.input);
+        self.label = view.label;
+    }
+}
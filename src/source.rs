@@ -1,18 +1,35 @@
-use std::{collections::HashMap, error::Error, sync::Arc, time::Duration};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use chrono::{DateTime, FixedOffset, NaiveDateTime, TimeZone};
 
 use nyaa_html::NyaaTheme;
-use reqwest::{cookie::Jar, Proxy};
-use serde::{Deserialize, Serialize};
-use strum::{Display, VariantArray};
+use reqwest::{
+    cookie::Jar,
+    header::{HeaderMap, HeaderValue, ACCEPT_LANGUAGE, USER_AGENT},
+    Proxy, Url,
+};
+use serde::{Deserialize, Deserializer, Serialize};
+use strum::Display;
 use sukebei_nyaa::SukebeiTheme;
 use torrent_galaxy::TgxTheme;
 
 use crate::{
     app::{Context, LoadType, Widgets},
+    error::NyaaError,
     results::{ResultResponse, ResultTable, Results},
     sync::SearchQuery,
     theme::Theme,
-    util::conv::add_protocol,
+    util::{
+        anime::{self, ParsedTitle},
+        conv::{add_protocol, to_size},
+    },
     widget::{
         category::{CatEntry, CatIcon, CatStruct},
         sort::SelectedSort,
@@ -20,7 +37,13 @@ use crate::{
 };
 
 use self::{
-    nyaa_html::{NyaaConfig, NyaaHtmlSource},
+    airing::{AiringConfig, AiringSource},
+    archive::{ArchiveConfig, ArchiveSource},
+    auth::{AuthMap, SourceAuth},
+    category_map::CategoryMap,
+    custom_nyaa::CustomNyaaConfig,
+    eztv::{EztvConfig, EztvSource},
+    nyaa_html::{NyaaConfig, NyaaHtmlSource, NyaaTransport},
     sukebei_nyaa::{SukebeiHtmlSource, SukebeiNyaaConfig},
     torrent_galaxy::{TgxConfig, TorrentGalaxyHtmlSource},
 };
@@ -28,23 +51,43 @@ use self::{
 #[cfg(feature = "captcha")]
 use ratatui_image::protocol::StatefulProtocol;
 
+pub mod airing;
+pub mod archive;
+pub mod auth;
+pub mod category_map;
+pub mod custom_nyaa;
+pub mod deep;
+pub mod eztv;
+pub mod mock;
+pub mod nyaa_api;
+pub mod nyaa_engine;
 pub mod nyaa_html;
 pub mod nyaa_rss;
+pub mod resolve;
 pub mod sukebei_nyaa;
 pub mod torrent_galaxy;
 
+/// A captcha to solve before the source shows results
+#[cfg(feature = "captcha")]
+#[derive(Clone)]
+pub struct Captcha {
+    pub image: Box<dyn StatefulProtocol>,
+    /// The image as it was downloaded, to open outside the terminal
+    pub bytes: Vec<u8>,
+}
+
 #[derive(Clone)]
 pub enum SourceResults {
     Results(Results),
     #[cfg(feature = "captcha")]
-    Captcha(Box<dyn StatefulProtocol>),
+    Captcha(Captcha),
 }
 
 #[derive(Clone)]
 pub enum SourceResponse {
     Results(ResultResponse),
     #[cfg(feature = "captcha")]
-    Captcha(Box<dyn StatefulProtocol>),
+    Captcha(Captcha),
 }
 
 #[derive(Serialize, Deserialize, Clone, Copy, Default)]
@@ -60,11 +103,22 @@ pub struct SourceTheme {
 #[derive(Serialize, Deserialize, Clone, Default)]
 #[serde(default)]
 pub struct SourceConfig {
+    /// Warn when scraping selectors match nothing
+    pub strict: bool,
     pub nyaa: Option<NyaaConfig>,
     #[serde(rename = "sukebei")]
     pub sukebei: Option<SukebeiNyaaConfig>,
     #[serde(rename = "torrentgalaxy")]
     pub tgx: Option<TgxConfig>,
+    pub eztv: Option<EztvConfig>,
+    pub archive: Option<ArchiveConfig>,
+    pub airing: Option<AiringConfig>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub custom_nyaa: Vec<CustomNyaaConfig>,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub category_map: CategoryMap,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub auth: AuthMap,
 }
 
 #[derive(Clone)]
@@ -72,6 +126,42 @@ pub struct SourceInfo {
     pub cats: Vec<CatStruct>,
     pub filters: Vec<String>,
     pub sorts: Vec<String>,
+    pub capabilities: SourceCapabilities,
+}
+
+/// What a source can do, so keys and popups for anything else can say so
+/// instead of doing nothing
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct SourceCapabilities {
+    /// Searching within one uploader's torrents
+    pub user: bool,
+    /// Sorting by anything in `sorts`
+    pub sort: bool,
+    /// Choosing a category
+    pub category: bool,
+    /// Loading results from an RSS feed
+    pub rss: bool,
+    /// Needs an account, set up in `[source.auth]`
+    pub auth: bool,
+    /// Posts have comments
+    pub comments: bool,
+    /// Searching with several filters at once, each one a bit of the filter
+    pub multi_filter: bool,
+}
+
+impl SourceCapabilities {
+    /// Everything the nyaa codebase supports without an account
+    pub fn nyaa() -> Self {
+        SourceCapabilities {
+            user: true,
+            sort: true,
+            category: true,
+            rss: true,
+            auth: false,
+            comments: true,
+            multi_filter: false,
+        }
+    }
 }
 
 impl SourceInfo {
@@ -111,18 +201,130 @@ impl SourceInfo {
     }
 }
 
+/// A source's `columns` config, which can be changed in the columns popup
+pub trait ColumnConfig: Copy + Default {
+    /// Names of the columns, in the order they're shown
+    const NAMES: &'static [&'static str];
+    /// Whether each column in [`Self::NAMES`] is shown
+    fn shown(self) -> Vec<bool>;
+    /// Show or hide the column at `idx` in [`Self::NAMES`]
+    fn set(&mut self, idx: usize, shown: bool);
+}
+
+fn column_list<C: ColumnConfig>(columns: Option<C>) -> Vec<(&'static str, bool)> {
+    let shown = columns.unwrap_or_default().shown();
+    C::NAMES.iter().copied().zip(shown).collect()
+}
+
+fn set_column<C: ColumnConfig>(columns: &mut Option<C>, idx: usize, shown: bool) {
+    columns.get_or_insert_with(C::default).set(idx, shown);
+}
+
+/// Headers sent with each request to a source, for mirrors which block
+/// requests that don't look like they came from a browser
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct RequestHeaders {
+    /// Sent as the User-Agent. With more than one, each search uses the next.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub user_agents: Vec<String>,
+    pub accept_language: Option<String>,
+}
+
+/// Index into `user_agents` of the next one to use
+static NEXT_USER_AGENT: AtomicUsize = AtomicUsize::new(0);
+
+impl RequestHeaders {
+    /// The headers to send, taking the next User-Agent in turn. Values which
+    /// aren't valid headers are left out, and warned about in the config check.
+    pub fn to_header_map(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        if !self.user_agents.is_empty() {
+            let idx = NEXT_USER_AGENT.fetch_add(1, Ordering::Relaxed) % self.user_agents.len();
+            if let Ok(ua) = HeaderValue::from_str(&self.user_agents[idx]) {
+                headers.insert(USER_AGENT, ua);
+            }
+        }
+        if let Some(Ok(lang)) = self.accept_language.as_deref().map(HeaderValue::from_str) {
+            headers.insert(ACCEPT_LANGUAGE, lang);
+        }
+        headers
+    }
+}
+
 pub fn request_client(jar: &Arc<Jar>, ctx: &Context) -> Result<reqwest::Client, reqwest::Error> {
+    build_client(jar, ctx, ctx.config.timeout, None)
+}
+
+/// Same as [`request_client`], but uses the current source's `timeout` if it
+/// overrides the global one, and sends its `headers`. Used for searching and
+/// fetching torrents from that source. Its cookie or token from
+/// `[source.auth]` is sent too; ones which can't be read are left out, and
+/// warned about in the config check.
+pub fn source_request_client(
+    jar: &Arc<Jar>,
+    ctx: &Context,
+) -> Result<reqwest::Client, reqwest::Error> {
+    source_request_client_for(jar, ctx, ctx.src)
+}
+
+/// Same as [`source_request_client`], but for `src` instead of the current
+/// source
+pub fn source_request_client_for(
+    jar: &Arc<Jar>,
+    ctx: &Context,
+    src: Sources,
+) -> Result<reqwest::Client, reqwest::Error> {
+    let config = &ctx.config.sources;
+    let timeout = src.timeout(config).unwrap_or(ctx.config.timeout);
+    let mut headers = src
+        .headers(config)
+        .map(|h| h.to_header_map())
+        .unwrap_or_default();
+    if let Some(auth) = src.auth(config) {
+        let name = src.auth_name(config);
+        if let Ok(base_url) = Url::parse(&add_protocol(src.base_url(config), true)) {
+            let _ = auth.add_cookies(&name, jar, &base_url);
+        }
+        headers.extend(auth.headers(&name).unwrap_or_default());
+    }
+    build_client(jar, ctx, timeout, Some(headers))
+}
+
+fn build_client(
+    jar: &Arc<Jar>,
+    ctx: &Context,
+    timeout: u64,
+    headers: Option<HeaderMap>,
+) -> Result<reqwest::Client, reqwest::Error> {
     let mut client = reqwest::Client::builder()
         .gzip(true)
         .cookie_provider(jar.clone())
         // .cookie_store(true)
-        .timeout(Duration::from_secs(ctx.config.timeout));
+        .timeout(Duration::from_secs(timeout));
+    if let Some(headers) = headers {
+        client = client.default_headers(headers);
+    }
     if let Some(proxy_url) = ctx.config.request_proxy.to_owned() {
         client = client.proxy(Proxy::all(add_protocol(proxy_url, false))?);
     }
     client.build()
 }
 
+/// The torrent link of the item `id` made from a `download_url_template`.
+/// Templates without a host, like `/download/{id}.torrent`, are relative to
+/// `base_url`.
+pub fn download_url(template: &str, base_url: &str, id: &ItemId) -> Option<String> {
+    let link = template.replace("{id}", &id.num.to_string());
+    match Url::parse(&link) {
+        Ok(url) => Some(url.into()),
+        Err(_) => Url::parse(&add_protocol(base_url.to_owned(), true))
+            .and_then(|base| base.join(&link))
+            .map(Into::into)
+            .ok(),
+    }
+}
+
 #[derive(Default, Clone, Copy)]
 pub enum ItemType {
     #[default]
@@ -131,15 +333,124 @@ pub enum ItemType {
     Remake,
 }
 
+impl ItemType {
+    /// Shown before titles when trusted and remake can't be told apart by
+    /// color
+    pub fn marker(self) -> Option<&'static str> {
+        match self {
+            ItemType::Trusted => Some("[T]"),
+            ItemType::Remake => Some("[R]"),
+            ItemType::None => None,
+        }
+    }
+
+    /// A single character for the status column
+    pub fn symbol(self) -> &'static str {
+        match self {
+            ItemType::Trusted => "✓",
+            ItemType::Remake => "✗",
+            ItemType::None => "",
+        }
+    }
+}
+
+/// Identifies an item across sources, shown as e.g. `nyaa-123`
+#[derive(Clone, Default, PartialEq, Eq, Hash, Debug)]
+pub struct ItemId {
+    pub source: &'static str,
+    pub num: usize,
+}
+
+/// Source names of ids read back from text, each kept once for as long as
+/// nyaa runs
+static ID_SOURCES: Mutex<Vec<&'static str>> = Mutex::new(vec![]);
+
+impl ItemId {
+    pub fn new(source: &'static str, num: usize) -> Self {
+        ItemId { source, num }
+    }
+
+    /// Read an id shown as e.g. `nyaa-123`
+    pub fn parse(id: &str) -> Option<Self> {
+        let (source, num) = id.rsplit_once('-')?;
+        let num = num.parse().ok()?;
+        Some(ItemId {
+            source: Self::source_name(source),
+            num,
+        })
+    }
+
+    /// The name of a source only known while running, like a custom
+    /// instance, kept once so ids can point to it
+    pub fn source_name(source: &str) -> &'static str {
+        let mut sources = ID_SOURCES.lock().unwrap_or_else(|e| e.into_inner());
+        match sources.iter().find(|s| **s == source) {
+            Some(s) => s,
+            None => {
+                let s: &'static str = Box::leak(source.to_owned().into_boxed_str());
+                sources.push(s);
+                s
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for ItemId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{}", self.source, self.num)
+    }
+}
+
+/// When an item was uploaded, along with the format to show it in. The time
+/// keeps the offset it should be shown in, so sites listing times in UTC
+/// still show UTC unless a `date_format` is set.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ItemDate {
+    pub time: DateTime<FixedOffset>,
+    pub format: String,
+}
+
+impl ItemDate {
+    pub fn new<Tz: TimeZone>(time: DateTime<Tz>, format: impl Into<String>) -> Self {
+        ItemDate {
+            time: time.fixed_offset(),
+            format: format.into(),
+        }
+    }
+
+    /// Parse a date listed in UTC. It is converted to local time if a
+    /// `date_format` is given, otherwise it is shown in the format it was
+    /// listed in.
+    pub fn parse_utc(date: &str, listed_format: &str, date_format: Option<String>) -> Self {
+        let naive = NaiveDateTime::parse_from_str(date.trim(), listed_format).unwrap_or_default();
+        let utc = naive.and_utc();
+        match date_format {
+            Some(format) => ItemDate::new(utc.with_timezone(&chrono::Local), format),
+            None => ItemDate::new(utc, listed_format),
+        }
+    }
+}
+
+impl Default for ItemDate {
+    fn default() -> Self {
+        ItemDate::new(DateTime::UNIX_EPOCH, "%Y-%m-%d %H:%M")
+    }
+}
+
+impl std::fmt::Display for ItemDate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.time.format(&self.format))
+    }
+}
+
 #[derive(Clone, Default)]
 pub struct Item {
-    pub id: String,
-    pub date: String,
+    pub id: ItemId,
+    pub date: ItemDate,
     pub seeders: u32,
     pub leechers: u32,
     pub downloads: u32,
-    pub size: String,
-    pub bytes: usize,
+    pub bytes: u64,
     pub title: String,
     pub torrent_link: String,
     pub magnet_link: String,
@@ -151,14 +462,83 @@ pub struct Item {
     pub extra: HashMap<String, String>,
 }
 
-#[derive(Serialize, Deserialize, Display, Clone, Copy, VariantArray, PartialEq, Eq)]
+impl Item {
+    /// Size in the same units as progress, e.g. `1.4 GiB`
+    pub fn size(&self) -> String {
+        to_size(self.bytes)
+    }
+
+    /// The show name, episode, resolution and group read from the title
+    pub fn parsed(&self) -> ParsedTitle {
+        anime::parse(&self.title)
+    }
+}
+
+#[derive(Serialize, Display, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Sources {
     #[strum(serialize = "Nyaa")]
-    Nyaa = 0,
+    Nyaa,
     #[strum(serialize = "Sukebei")]
-    SukebeiNyaa = 1,
+    SukebeiNyaa,
     #[strum(serialize = "TorrentGalaxy")]
-    TorrentGalaxy = 2,
+    TorrentGalaxy,
+    #[strum(serialize = "EZTV")]
+    Eztv,
+    /// Public domain and freely licensed items on archive.org
+    #[strum(serialize = "InternetArchive")]
+    InternetArchive,
+    /// Today's episodes of airing anime, found on nyaa
+    #[strum(serialize = "Airing")]
+    Airing,
+    /// Name of a `[[source.custom_nyaa]]` entry. Indexes into the list, which
+    /// older versions saved, are read as `#<index>` until the config is
+    /// upgraded.
+    #[strum(serialize = "Custom")]
+    Custom(&'static str),
+}
+
+/// [`Sources`] as saved, read with owned names which are then kept once
+#[derive(Deserialize)]
+enum SavedSource {
+    Nyaa,
+    SukebeiNyaa,
+    TorrentGalaxy,
+    Eztv,
+    InternetArchive,
+    Airing,
+    Custom(CustomName),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum CustomName {
+    Name(String),
+    Index(usize),
+}
+
+impl<'de> Deserialize<'de> for Sources {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        SavedSource::deserialize(d).map(Into::into)
+    }
+}
+
+impl From<SavedSource> for Sources {
+    fn from(src: SavedSource) -> Self {
+        match src {
+            SavedSource::Nyaa => Sources::Nyaa,
+            SavedSource::SukebeiNyaa => Sources::SukebeiNyaa,
+            SavedSource::TorrentGalaxy => Sources::TorrentGalaxy,
+            SavedSource::Eztv => Sources::Eztv,
+            SavedSource::InternetArchive => Sources::InternetArchive,
+            SavedSource::Airing => Sources::Airing,
+            SavedSource::Custom(CustomName::Name(name)) => {
+                Sources::Custom(ItemId::source_name(&name))
+            }
+            SavedSource::Custom(CustomName::Index(idx)) => {
+                Sources::Custom(ItemId::source_name(&format!("#{}", idx)))
+            }
+        }
+    }
 }
 
 pub trait Source {
@@ -167,32 +547,42 @@ pub trait Source {
         search: &SearchQuery,
         config: &SourceConfig,
         date_format: Option<String>,
-    ) -> impl std::future::Future<Output = Result<SourceResponse, Box<dyn Error + Send + Sync>>> + Send;
+    ) -> impl std::future::Future<Output = Result<SourceResponse, NyaaError>> + Send;
     fn sort(
         client: &reqwest::Client,
         search: &SearchQuery,
         config: &SourceConfig,
         date_format: Option<String>,
-    ) -> impl std::future::Future<Output = Result<SourceResponse, Box<dyn Error + Send + Sync>>> + Send;
+    ) -> impl std::future::Future<Output = Result<SourceResponse, NyaaError>> + Send;
     fn filter(
         client: &reqwest::Client,
         search: &SearchQuery,
         config: &SourceConfig,
         date_format: Option<String>,
-    ) -> impl std::future::Future<Output = Result<SourceResponse, Box<dyn Error + Send + Sync>>> + Send;
+    ) -> impl std::future::Future<Output = Result<SourceResponse, NyaaError>> + Send;
     fn categorize(
         client: &reqwest::Client,
         search: &SearchQuery,
         config: &SourceConfig,
         date_format: Option<String>,
-    ) -> impl std::future::Future<Output = Result<SourceResponse, Box<dyn Error + Send + Sync>>> + Send;
+    ) -> impl std::future::Future<Output = Result<SourceResponse, NyaaError>> + Send;
     fn solve(
         solution: String,
         client: &reqwest::Client,
         search: &SearchQuery,
         config: &SourceConfig,
         date_format: Option<String>,
-    ) -> impl std::future::Future<Output = Result<SourceResponse, Box<dyn Error + Send + Sync>>> + Send;
+    ) -> impl std::future::Future<Output = Result<SourceResponse, NyaaError>> + Send;
+    /// Fill in the torrent and magnet links of an item just before it's
+    /// downloaded, for sources whose list pages don't have them. By default
+    /// they're read from the item's post page.
+    fn resolve(
+        client: &reqwest::Client,
+        item: Item,
+        _config: &SourceConfig,
+    ) -> impl std::future::Future<Output = Result<Item, NyaaError>> + Send {
+        resolve::from_post(client, item)
+    }
     fn info() -> SourceInfo;
     fn load_config(config: &mut SourceConfig);
 
@@ -217,8 +607,61 @@ impl Sources {
         search: &SearchQuery,
         config: &SourceConfig,
         date_format: Option<String>,
-    ) -> Result<SourceResponse, Box<dyn Error + Send + Sync>> {
-        match self {
+    ) -> Result<SourceResponse, NyaaError> {
+        // Sources with a login are signed in to before their first search, and
+        // again if the session seems to have run out
+        let login = self.auth(config).filter(SourceAuth::is_login);
+        let name = self.auth_name(config);
+        if let Some(auth) = login.as_ref().filter(|_| !auth::logged_in(&name)) {
+            auth.login(client, &name).await?;
+        }
+        let res = self
+            .load_signed_in(
+                load_type.clone(),
+                client,
+                search,
+                config,
+                date_format.clone(),
+            )
+            .await;
+        match (res, login) {
+            (Err(e), Some(auth)) if auth::is_expired(&e) => {
+                auth.login(client, &name).await?;
+                self.load_signed_in(load_type, client, search, config, date_format)
+                    .await
+            }
+            (res, _) => res,
+        }
+    }
+
+    async fn load_signed_in(
+        &self,
+        load_type: LoadType,
+        client: &reqwest::Client,
+        search: &SearchQuery,
+        config: &SourceConfig,
+        date_format: Option<String>,
+    ) -> Result<SourceResponse, NyaaError> {
+        match load_type {
+            LoadType::DeepSearching(pages) => {
+                deep::search(*self, pages, client, search, config, date_format).await
+            }
+            load_type => {
+                self.load_page(load_type, client, search, config, date_format)
+                    .await
+            }
+        }
+    }
+
+    async fn load_page(
+        &self,
+        load_type: LoadType,
+        client: &reqwest::Client,
+        search: &SearchQuery,
+        config: &SourceConfig,
+        date_format: Option<String>,
+    ) -> Result<SourceResponse, NyaaError> {
+        let res = match self {
             Sources::Nyaa => match load_type {
                 LoadType::Searching | LoadType::Sourcing => {
                     NyaaHtmlSource::search(client, search, config, date_format).await
@@ -235,7 +678,9 @@ impl Sources {
                 LoadType::SolvingCaptcha(solution) => {
                     NyaaHtmlSource::solve(solution, client, search, config, date_format).await
                 }
-                LoadType::Downloading | LoadType::Batching => unreachable!(),
+                LoadType::Downloading | LoadType::Batching | LoadType::DeepSearching(_) => {
+                    unreachable!()
+                }
             },
             Sources::SukebeiNyaa => match load_type {
                 LoadType::Searching | LoadType::Sourcing => {
@@ -253,7 +698,9 @@ impl Sources {
                 LoadType::SolvingCaptcha(solution) => {
                     SukebeiHtmlSource::solve(solution, client, search, config, date_format).await
                 }
-                LoadType::Downloading | LoadType::Batching => unreachable!(),
+                LoadType::Downloading | LoadType::Batching | LoadType::DeepSearching(_) => {
+                    unreachable!()
+                }
             },
             Sources::TorrentGalaxy => match load_type {
                 LoadType::Searching | LoadType::Sourcing => {
@@ -272,24 +719,143 @@ impl Sources {
                     TorrentGalaxyHtmlSource::solve(solution, client, search, config, date_format)
                         .await
                 }
-                LoadType::Downloading | LoadType::Batching => unreachable!(),
+                LoadType::Downloading | LoadType::Batching | LoadType::DeepSearching(_) => {
+                    unreachable!()
+                }
             },
+            Sources::Eztv => match load_type {
+                LoadType::Searching | LoadType::Sourcing => {
+                    EztvSource::search(client, search, config, date_format).await
+                }
+                LoadType::Sorting => EztvSource::sort(client, search, config, date_format).await,
+                LoadType::Filtering => {
+                    EztvSource::filter(client, search, config, date_format).await
+                }
+                LoadType::Categorizing => {
+                    EztvSource::categorize(client, search, config, date_format).await
+                }
+                LoadType::SolvingCaptcha(solution) => {
+                    EztvSource::solve(solution, client, search, config, date_format).await
+                }
+                LoadType::Downloading | LoadType::Batching | LoadType::DeepSearching(_) => {
+                    unreachable!()
+                }
+            },
+            Sources::InternetArchive => match load_type {
+                LoadType::Searching | LoadType::Sourcing => {
+                    ArchiveSource::search(client, search, config, date_format).await
+                }
+                LoadType::Sorting => ArchiveSource::sort(client, search, config, date_format).await,
+                LoadType::Filtering => {
+                    ArchiveSource::filter(client, search, config, date_format).await
+                }
+                LoadType::Categorizing => {
+                    ArchiveSource::categorize(client, search, config, date_format).await
+                }
+                LoadType::SolvingCaptcha(solution) => {
+                    ArchiveSource::solve(solution, client, search, config, date_format).await
+                }
+                LoadType::Downloading | LoadType::Batching | LoadType::DeepSearching(_) => {
+                    unreachable!()
+                }
+            },
+            Sources::Airing => match load_type {
+                LoadType::Searching | LoadType::Sourcing => {
+                    AiringSource::search(client, search, config, date_format).await
+                }
+                LoadType::Sorting => AiringSource::sort(client, search, config, date_format).await,
+                LoadType::Filtering => {
+                    AiringSource::filter(client, search, config, date_format).await
+                }
+                LoadType::Categorizing => {
+                    AiringSource::categorize(client, search, config, date_format).await
+                }
+                LoadType::SolvingCaptcha(solution) => {
+                    AiringSource::solve(solution, client, search, config, date_format).await
+                }
+                LoadType::Downloading | LoadType::Batching | LoadType::DeepSearching(_) => {
+                    unreachable!()
+                }
+            },
+            // Custom instances only support searching, so every load does a new search
+            Sources::Custom(name) => {
+                custom_nyaa::search(name, client, search, config, date_format).await
+            }
+        };
+        match (res, self.download_url_template(config)) {
+            (Ok(SourceResponse::Results(mut res)), Some(template)) => {
+                let base_url = self.base_url(config);
+                for item in res.items.iter_mut() {
+                    if let Some(link) = download_url(&template, &base_url, &item.id) {
+                        item.torrent_link = link;
+                    }
+                }
+                Ok(SourceResponse::Results(res))
+            }
+            (res, _) => res,
+        }
+    }
+
+    /// Fill in the links of an item before downloading it. See [`Source::resolve`].
+    pub async fn resolve(
+        self,
+        client: &reqwest::Client,
+        item: Item,
+        config: &SourceConfig,
+    ) -> Result<Item, NyaaError> {
+        match self {
+            Sources::Nyaa => NyaaHtmlSource::resolve(client, item, config).await,
+            Sources::SukebeiNyaa => SukebeiHtmlSource::resolve(client, item, config).await,
+            Sources::TorrentGalaxy => TorrentGalaxyHtmlSource::resolve(client, item, config).await,
+            Sources::Eztv => EztvSource::resolve(client, item, config).await,
+            Sources::InternetArchive => ArchiveSource::resolve(client, item, config).await,
+            Sources::Airing => AiringSource::resolve(client, item, config).await,
+            Sources::Custom(_) => resolve::from_post(client, item).await,
+        }
+    }
+
+    /// Built-in sources, followed by every custom instance in the config
+    pub fn all(config: &SourceConfig) -> Vec<Sources> {
+        let mut all = vec![
+            Sources::Nyaa,
+            Sources::SukebeiNyaa,
+            Sources::TorrentGalaxy,
+            Sources::Eztv,
+            Sources::InternetArchive,
+            Sources::Airing,
+        ];
+        all.extend(
+            config
+                .custom_nyaa
+                .iter()
+                .map(|c| Sources::Custom(ItemId::source_name(&c.name))),
+        );
+        all
+    }
+
+    pub fn name(self, _config: &SourceConfig) -> String {
+        match self {
+            Sources::Custom(name) => name.to_owned(),
+            src => src.to_string(),
         }
     }
 
     pub fn apply(self, ctx: &mut Context, w: &mut Widgets) {
-        ctx.src_info = self.info();
+        ctx.src_info = self.info(&ctx.config.sources);
         w.category.selected = self.default_category(&ctx.config.sources);
 
-        let (major, minor) = ctx.src_info.get_major_minor(w.category.selected);
+        let (major, minor) = w
+            .category
+            .expanded(self)
+            .unwrap_or_else(|| ctx.src_info.get_major_minor(w.category.selected));
         w.category.table.select(major + minor + 1);
         w.category.major = major;
         w.category.minor = minor;
 
         w.sort.selected = self.default_sort(&ctx.config.sources);
         w.sort.table.select(w.sort.selected.sort);
-        w.filter.selected = self.default_filter(&ctx.config.sources);
-        w.filter.table.select(w.filter.selected);
+        w.filter
+            .select(ctx, self.default_filter(&ctx.config.sources));
 
         w.search.input.input = self.default_search(&ctx.config.sources);
         w.search.input.cursor = w.search.input.input.len();
@@ -298,11 +864,15 @@ impl Sources {
         ctx.page = 1;
     }
 
-    pub fn info(self) -> SourceInfo {
+    pub fn info(self, config: &SourceConfig) -> SourceInfo {
         match self {
             Sources::Nyaa => NyaaHtmlSource::info(),
             Sources::SukebeiNyaa => SukebeiHtmlSource::info(),
             Sources::TorrentGalaxy => TorrentGalaxyHtmlSource::info(),
+            Sources::Eztv => EztvSource::info(),
+            Sources::InternetArchive => ArchiveSource::info(),
+            Sources::Airing => AiringSource::info(),
+            Sources::Custom(name) => custom_nyaa::info(name, config),
         }
     }
 
@@ -311,14 +881,190 @@ impl Sources {
             Sources::Nyaa => NyaaHtmlSource::load_config(config),
             Sources::SukebeiNyaa => SukebeiHtmlSource::load_config(config),
             Sources::TorrentGalaxy => TorrentGalaxyHtmlSource::load_config(config),
+            Sources::Eztv => EztvSource::load_config(config),
+            Sources::InternetArchive => ArchiveSource::load_config(config),
+            Sources::Airing => AiringSource::load_config(config),
+            // Custom instances are only ever created by the user
+            Sources::Custom(_) => {}
         };
     }
 
+    pub fn base_url(self, config: &SourceConfig) -> String {
+        match self {
+            // Airing episodes are searched for on nyaa
+            Sources::Nyaa | Sources::Airing => config.nyaa.clone().unwrap_or_default().base_url,
+            Sources::SukebeiNyaa => config.sukebei.clone().unwrap_or_default().0.base_url,
+            Sources::TorrentGalaxy => config.tgx.clone().unwrap_or_default().base_url,
+            Sources::Eztv => config.eztv.clone().unwrap_or_default().base_url,
+            Sources::InternetArchive => config.archive.clone().unwrap_or_default().base_url,
+            Sources::Custom(name) => custom_nyaa::base_url(name, config),
+        }
+    }
+
+    /// Where torrents are downloaded from instead of the scraped links, with
+    /// `{id}` replaced by the number of the post
+    pub fn download_url_template(self, config: &SourceConfig) -> Option<String> {
+        match self {
+            Sources::Nyaa | Sources::Airing => config
+                .nyaa
+                .as_ref()
+                .and_then(|c| c.download_url_template.clone()),
+            Sources::SukebeiNyaa => config
+                .sukebei
+                .as_ref()
+                .and_then(|c| c.0.download_url_template.clone()),
+            Sources::TorrentGalaxy => config
+                .tgx
+                .as_ref()
+                .and_then(|c| c.download_url_template.clone()),
+            // EZTV and archive.org link straight to their torrents
+            Sources::Eztv | Sources::InternetArchive => None,
+            Sources::Custom(name) => custom_nyaa::download_url_template(name, config),
+        }
+    }
+
+    /// Host searches are sent to, e.g. `nyaa.si`
+    pub fn host(self, config: &SourceConfig) -> String {
+        let base_url = self.base_url(config);
+        Url::parse(&add_protocol(base_url.to_owned(), true))
+            .ok()
+            .and_then(|u| u.host_str().map(ToOwned::to_owned))
+            .unwrap_or(base_url)
+    }
+
+    /// Whether results are loaded from the HTML pages, the RSS feed or an API
+    pub fn transport(self, config: &SourceConfig) -> NyaaTransport {
+        match self {
+            Sources::Nyaa | Sources::Airing => config
+                .nyaa
+                .as_ref()
+                .map(NyaaConfig::transport)
+                .unwrap_or_default(),
+            Sources::SukebeiNyaa => config
+                .sukebei
+                .as_ref()
+                .map(|c| c.0.transport())
+                .unwrap_or_default(),
+            // TorrentGalaxy is only ever scraped
+            Sources::TorrentGalaxy => NyaaTransport::Html,
+            Sources::Eztv | Sources::InternetArchive => NyaaTransport::Api,
+            Sources::Custom(name) => custom_nyaa::transport(name, config),
+        }
+    }
+
+    /// Table in the config file holding the settings of the source
+    pub fn config_table(self, _config: &SourceConfig) -> String {
+        match self {
+            Sources::Nyaa => "[source.nyaa]".to_owned(),
+            Sources::SukebeiNyaa => "[source.sukebei]".to_owned(),
+            Sources::TorrentGalaxy => "[source.torrentgalaxy]".to_owned(),
+            Sources::Eztv => "[source.eztv]".to_owned(),
+            Sources::InternetArchive => "[source.archive]".to_owned(),
+            Sources::Airing => "[source.airing]".to_owned(),
+            Sources::Custom(name) => format!("[[source.custom_nyaa]] named \"{}\"", name),
+        }
+    }
+
+    /// Per-source timeout in seconds, overriding the global `timeout`
+    pub fn timeout(self, config: &SourceConfig) -> Option<u64> {
+        match self {
+            Sources::Nyaa => config.nyaa.as_ref().and_then(|c| c.timeout),
+            Sources::SukebeiNyaa => config.sukebei.as_ref().and_then(|c| c.0.timeout),
+            Sources::TorrentGalaxy => config.tgx.as_ref().and_then(|c| c.timeout),
+            Sources::Eztv => config.eztv.as_ref().and_then(|c| c.timeout),
+            Sources::InternetArchive => config.archive.as_ref().and_then(|c| c.timeout),
+            Sources::Airing => config.airing.as_ref().and_then(|c| c.timeout),
+            Sources::Custom(name) => custom_nyaa::timeout(name, config),
+        }
+    }
+
+    /// Extra headers to send to the source, from its `[source.<name>.headers]`
+    pub fn headers(self, config: &SourceConfig) -> Option<RequestHeaders> {
+        match self {
+            Sources::Nyaa | Sources::Airing => config.nyaa.as_ref().and_then(|c| c.headers.clone()),
+            Sources::SukebeiNyaa => config.sukebei.as_ref().and_then(|c| c.0.headers.clone()),
+            Sources::TorrentGalaxy => config.tgx.as_ref().and_then(|c| c.headers.clone()),
+            Sources::Eztv => config.eztv.as_ref().and_then(|c| c.headers.clone()),
+            Sources::InternetArchive => config.archive.as_ref().and_then(|c| c.headers.clone()),
+            Sources::Custom(name) => custom_nyaa::headers(name, config),
+        }
+    }
+
+    /// Each column of the results table and whether it's shown, or `None` if
+    /// the source's columns can't be changed
+    pub fn columns(self, config: &SourceConfig) -> Option<Vec<(&'static str, bool)>> {
+        match self {
+            Sources::Nyaa | Sources::Airing => {
+                Some(column_list(config.nyaa.as_ref().and_then(|c| c.columns)))
+            }
+            Sources::SukebeiNyaa => Some(column_list(
+                config.sukebei.as_ref().and_then(|c| c.0.columns),
+            )),
+            Sources::TorrentGalaxy => {
+                Some(column_list(config.tgx.as_ref().and_then(|c| c.columns)))
+            }
+            Sources::Eztv => Some(column_list(config.eztv.as_ref().and_then(|c| c.columns))),
+            Sources::InternetArchive => None,
+            Sources::Custom(name) => custom_nyaa::columns(name, config).map(column_list),
+        }
+    }
+
+    /// Show or hide the column at `idx` in [`Sources::columns`]
+    pub fn set_column(self, config: &mut SourceConfig, idx: usize, shown: bool) {
+        match self {
+            Sources::Nyaa | Sources::Airing => {
+                let nyaa = config.nyaa.get_or_insert_with(Default::default);
+                set_column(&mut nyaa.columns, idx, shown);
+            }
+            Sources::SukebeiNyaa => {
+                let sukebei = config.sukebei.get_or_insert_with(Default::default);
+                set_column(&mut sukebei.0.columns, idx, shown);
+            }
+            Sources::TorrentGalaxy => {
+                let tgx = config.tgx.get_or_insert_with(Default::default);
+                set_column(&mut tgx.columns, idx, shown);
+            }
+            Sources::Eztv => {
+                let eztv = config.eztv.get_or_insert_with(Default::default);
+                set_column(&mut eztv.columns, idx, shown);
+            }
+            Sources::InternetArchive => {}
+            Sources::Custom(name) => {
+                if let Some(custom) = config.custom_nyaa.iter_mut().find(|c| c.name == name) {
+                    set_column(&mut custom.nyaa.columns, idx, shown);
+                }
+            }
+        }
+    }
+
+    /// The name the source's `[source.auth]` entry is under. Airing searches
+    /// nyaa, so it signs in as nyaa.
+    pub fn auth_name(self, config: &SourceConfig) -> String {
+        match self {
+            Sources::Airing => Sources::Nyaa.name(config),
+            src => src.name(config),
+        }
+    }
+
+    pub fn auth(self, config: &SourceConfig) -> Option<SourceAuth> {
+        config.auth.get(&self.auth_name(config)).cloned()
+    }
+
+    /// Shown in the sources popup, or `None` without a `[source.auth]` entry
+    pub fn auth_state(self, config: &SourceConfig) -> Option<&'static str> {
+        self.auth(config)
+            .map(|auth| auth.state(&self.auth_name(config)))
+    }
+
     pub fn default_category(self, config: &SourceConfig) -> usize {
         match self {
             Sources::Nyaa => NyaaHtmlSource::default_category(config),
             Sources::SukebeiNyaa => SukebeiHtmlSource::default_category(config),
             Sources::TorrentGalaxy => TorrentGalaxyHtmlSource::default_category(config),
+            Sources::Eztv => EztvSource::default_category(config),
+            Sources::InternetArchive => ArchiveSource::default_category(config),
+            Sources::Airing => AiringSource::default_category(config),
+            Sources::Custom(name) => custom_nyaa::default_category(name, config),
         }
     }
 
@@ -327,6 +1073,10 @@ impl Sources {
             Sources::Nyaa => NyaaHtmlSource::default_sort(config),
             Sources::SukebeiNyaa => SukebeiHtmlSource::default_sort(config),
             Sources::TorrentGalaxy => TorrentGalaxyHtmlSource::default_sort(config),
+            Sources::Eztv => EztvSource::default_sort(config),
+            Sources::InternetArchive => ArchiveSource::default_sort(config),
+            Sources::Airing => AiringSource::default_sort(config),
+            Sources::Custom(name) => custom_nyaa::default_sort(name, config),
         }
     }
 
@@ -335,6 +1085,10 @@ impl Sources {
             Sources::Nyaa => NyaaHtmlSource::default_filter(config),
             Sources::SukebeiNyaa => SukebeiHtmlSource::default_filter(config),
             Sources::TorrentGalaxy => TorrentGalaxyHtmlSource::default_filter(config),
+            Sources::Eztv => EztvSource::default_filter(config),
+            Sources::InternetArchive => ArchiveSource::default_filter(config),
+            Sources::Airing => AiringSource::default_filter(config),
+            Sources::Custom(name) => custom_nyaa::default_filter(name, config),
         }
     }
 
@@ -343,6 +1097,10 @@ impl Sources {
             Sources::Nyaa => NyaaHtmlSource::default_search(config),
             Sources::SukebeiNyaa => SukebeiHtmlSource::default_search(config),
             Sources::TorrentGalaxy => TorrentGalaxyHtmlSource::default_search(config),
+            Sources::Eztv => EztvSource::default_search(config),
+            Sources::InternetArchive => ArchiveSource::default_search(config),
+            Sources::Airing => AiringSource::default_search(config),
+            Sources::Custom(name) => custom_nyaa::default_search(name, config),
         }
     }
 
@@ -359,6 +1117,10 @@ impl Sources {
             Sources::TorrentGalaxy => {
                 TorrentGalaxyHtmlSource::format_table(items, search, config, theme)
             }
+            Sources::Eztv => EztvSource::format_table(items, search, config, theme),
+            Sources::InternetArchive => ArchiveSource::format_table(items, search, config, theme),
+            Sources::Airing => AiringSource::format_table(items, search, config, theme),
+            Sources::Custom(name) => custom_nyaa::format_table(name, items, search, config, theme),
         }
     }
 }
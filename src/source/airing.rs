@@ -0,0 +1,232 @@
+use std::time::Duration;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::NyaaError,
+    results::{ResultResponse, ResultTable},
+    sync::SearchQuery,
+    theme::Theme,
+    util::stats,
+    widget::sort::SelectedSort,
+};
+
+use super::{
+    nyaa_engine::{nyaa_table, search_nyaa},
+    nyaa_html::NyaaHtmlSource,
+    Item, Source, SourceConfig, SourceInfo, SourceResponse,
+};
+
+/// Today's episodes, found by searching nyaa for the releases of the shows on
+/// a schedule. Searches use the options in `[source.nyaa]`.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct AiringConfig {
+    /// Today's schedule, in the same shape as SubsPlease's API
+    pub schedule_url: String,
+    /// Group whose releases are searched for
+    pub group: String,
+    pub resolution: String,
+    pub default_search: String,
+    pub timeout: Option<u64>,
+}
+
+impl Default for AiringConfig {
+    fn default() -> Self {
+        Self {
+            schedule_url: "https://subsplease.org/api/?f=schedule&h=true&tz=UTC".to_owned(),
+            group: "SubsPlease".to_owned(),
+            resolution: "1080p".to_owned(),
+            default_search: Default::default(),
+            timeout: None,
+        }
+    }
+}
+
+/// A show airing today
+#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct AiringShow {
+    pub title: String,
+    /// When the episode airs, like `16:30`
+    #[serde(default)]
+    pub time: String,
+    #[serde(default)]
+    pub aired: bool,
+}
+
+#[derive(Deserialize)]
+struct Schedule {
+    #[serde(default)]
+    schedule: Vec<AiringShow>,
+}
+
+pub struct AiringSource;
+
+pub fn parse_schedule(json: &str) -> Result<Vec<AiringShow>, NyaaError> {
+    serde_json::from_str::<Schedule>(json)
+        .map(|s| s.schedule)
+        .map_err(|e| NyaaError::Parse(format!("Failed to read the airing schedule:\n{}", e)))
+}
+
+/// Titles compare equal regardless of case and punctuation
+fn normalize(title: &str) -> String {
+    title
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Releases of the shows on the schedule which were uploaded in the last day,
+/// along with the time each one aired
+pub fn airing_items(items: Vec<Item>, schedule: &[AiringShow], now: DateTime<Utc>) -> Vec<Item> {
+    let since = now - TimeDelta::days(1);
+    items
+        .into_iter()
+        .filter(|item| item.date.time >= since)
+        .filter_map(|mut item| {
+            let name = normalize(&item.parsed().name);
+            let show = schedule.iter().find(|s| normalize(&s.title) == name)?;
+            if !show.time.is_empty() {
+                item.extra.insert("airs".to_owned(), show.time.to_owned());
+            }
+            Some(item)
+        })
+        .collect()
+}
+
+async fn get_schedule(
+    client: &reqwest::Client,
+    airing: &AiringConfig,
+) -> Result<Vec<AiringShow>, NyaaError> {
+    let mut request = client.get(&airing.schedule_url);
+    if let Some(timeout) = airing.timeout {
+        request = request.timeout(Duration::from_secs(timeout));
+    }
+    let response = stats::send(request).await?;
+    if response.status() != StatusCode::OK {
+        return Err(NyaaError::Status {
+            url: airing.schedule_url.to_owned(),
+            code: response.status().as_u16(),
+        });
+    }
+    parse_schedule(&response.text().await?)
+}
+
+impl Source for AiringSource {
+    async fn search(
+        client: &reqwest::Client,
+        search: &SearchQuery,
+        config: &SourceConfig,
+        date_format: Option<String>,
+    ) -> Result<SourceResponse, NyaaError> {
+        let airing = config.airing.to_owned().unwrap_or_default();
+        let schedule = get_schedule(client, &airing).await?;
+
+        // A day of releases from one group fits on the first page
+        let query = format!("{} {} {}", airing.group, airing.resolution, search.query);
+        let search = SearchQuery {
+            query: query.trim().to_owned(),
+            page: 1,
+            ..search.clone()
+        };
+        let nyaa = config.nyaa.to_owned().unwrap_or_default();
+        let res = search_nyaa::<NyaaHtmlSource>(
+            client,
+            &search,
+            nyaa,
+            "nyaa",
+            config.strict,
+            date_format,
+        )
+        .await?;
+        Ok(match res {
+            SourceResponse::Results(res) => {
+                let items = airing_items(res.items, &schedule, Utc::now());
+                SourceResponse::Results(ResultResponse {
+                    total_results: Some(items.len()),
+                    items,
+                    last_page: 1,
+                    warnings: res.warnings,
+                })
+            }
+            #[cfg(feature = "captcha")]
+            res => res,
+        })
+    }
+    async fn sort(
+        client: &reqwest::Client,
+        search: &SearchQuery,
+        config: &SourceConfig,
+        date_format: Option<String>,
+    ) -> Result<SourceResponse, NyaaError> {
+        AiringSource::search(client, search, config, date_format).await
+    }
+    async fn filter(
+        client: &reqwest::Client,
+        search: &SearchQuery,
+        config: &SourceConfig,
+        date_format: Option<String>,
+    ) -> Result<SourceResponse, NyaaError> {
+        AiringSource::search(client, search, config, date_format).await
+    }
+    async fn categorize(
+        client: &reqwest::Client,
+        search: &SearchQuery,
+        config: &SourceConfig,
+        date_format: Option<String>,
+    ) -> Result<SourceResponse, NyaaError> {
+        AiringSource::search(client, search, config, date_format).await
+    }
+    async fn solve(
+        _solution: String,
+        client: &reqwest::Client,
+        search: &SearchQuery,
+        config: &SourceConfig,
+        date_format: Option<String>,
+    ) -> Result<SourceResponse, NyaaError> {
+        AiringSource::search(client, search, config, date_format).await
+    }
+
+    fn info() -> SourceInfo {
+        NyaaHtmlSource::info()
+    }
+
+    fn load_config(config: &mut SourceConfig) {
+        if config.airing.is_none() {
+            config.airing = Some(AiringConfig::default());
+        }
+        NyaaHtmlSource::load_config(config);
+    }
+
+    fn default_category(_cfg: &SourceConfig) -> usize {
+        Self::info().entry_from_cfg("AnimeEnglishTranslated").id
+    }
+
+    fn default_sort(cfg: &SourceConfig) -> SelectedSort {
+        NyaaHtmlSource::default_sort(cfg)
+    }
+
+    fn default_filter(cfg: &SourceConfig) -> usize {
+        NyaaHtmlSource::default_filter(cfg)
+    }
+
+    fn default_search(cfg: &SourceConfig) -> String {
+        cfg.airing
+            .as_ref()
+            .map(|c| c.default_search.to_owned())
+            .unwrap_or_default()
+    }
+
+    fn format_table(
+        items: &[Item],
+        search: &SearchQuery,
+        config: &SourceConfig,
+        theme: &Theme,
+    ) -> ResultTable {
+        let columns = config.nyaa.as_ref().and_then(|c| c.columns);
+        nyaa_table(items.into(), theme, &search.sort, &columns)
+    }
+}
@@ -0,0 +1,409 @@
+use std::{collections::HashMap, time::Duration};
+
+use chrono::DateTime;
+use ratatui::{
+    layout::{Alignment, Constraint},
+    style::Stylize,
+};
+use reqwest::{StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use strum::{FromRepr, VariantArray};
+use urlencoding::encode;
+
+use crate::{
+    cats,
+    error::NyaaError,
+    results::{ResultColumn, ResultHeader, ResultResponse, ResultRow, ResultTable},
+    sync::SearchQuery,
+    theme::Theme,
+    util::{conv::shorten_number, stats},
+    widget::sort::{SelectedSort, SortDir},
+};
+
+use super::{
+    add_protocol, nyaa_engine::size_width, Item, ItemDate, ItemId, RequestHeaders, Source,
+    SourceCapabilities, SourceConfig, SourceInfo, SourceResponse,
+};
+
+static ARCHIVE_PAGE_SIZE: usize = 50;
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct ArchiveConfig {
+    pub base_url: String,
+    pub default_sort: ArchiveSort,
+    pub default_sort_dir: SortDir,
+    pub default_category: String,
+    pub default_search: String,
+    pub timeout: Option<u64>,
+    pub headers: Option<RequestHeaders>,
+}
+
+impl Default for ArchiveConfig {
+    fn default() -> Self {
+        Self {
+            base_url: "https://archive.org/".to_owned(),
+            default_sort: ArchiveSort::Date,
+            default_sort_dir: SortDir::Desc,
+            default_category: "AllCategories".to_owned(),
+            default_search: Default::default(),
+            timeout: None,
+            headers: None,
+        }
+    }
+}
+
+#[derive(
+    Serialize, Deserialize, strum::Display, Clone, Copy, VariantArray, PartialEq, Eq, FromRepr,
+)]
+#[repr(usize)]
+pub enum ArchiveSort {
+    Date = 0,
+    Downloads = 1,
+    Size = 2,
+    Title = 3,
+}
+
+impl ArchiveSort {
+    fn to_url(self) -> &'static str {
+        match self {
+            ArchiveSort::Date => "publicdate",
+            ArchiveSort::Downloads => "downloads",
+            ArchiveSort::Size => "item_size",
+            ArchiveSort::Title => "titleSorter",
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ArchiveDoc {
+    identifier: String,
+    /// Usually a string, but a list for items with more than one
+    #[serde(default)]
+    title: serde_json::Value,
+    #[serde(default)]
+    item_size: u64,
+    #[serde(default)]
+    downloads: u32,
+    #[serde(default)]
+    publicdate: String,
+    #[serde(default)]
+    mediatype: String,
+}
+
+#[derive(Deserialize)]
+struct ArchiveResults {
+    #[serde(rename = "numFound", default)]
+    num_found: usize,
+    #[serde(default)]
+    docs: Vec<ArchiveDoc>,
+}
+
+#[derive(Deserialize)]
+struct ArchiveResponse {
+    response: ArchiveResults,
+}
+
+pub struct ArchiveSource;
+
+/// A number for an item's identifier, which is a string. The same identifier
+/// always gives the same number, so history and batches still recognise it.
+pub fn identifier_num(identifier: &str) -> usize {
+    // 64-bit FNV-1a
+    identifier.bytes().fold(0xcbf29ce484222325u64, |hash, b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    }) as usize
+}
+
+/// The advanced search query for `query`, limited to items with a torrent
+pub fn search_query(query: &str, category: usize) -> String {
+    let mut q = vec![];
+    if !query.trim().is_empty() {
+        q.push(format!("({})", query.trim()));
+    }
+    if let Some(mediatype) = mediatype(category) {
+        q.push(format!("mediatype:{}", mediatype));
+    }
+    q.push("format:\"Archive BitTorrent\"".to_owned());
+    q.join(" AND ")
+}
+
+fn mediatype(category: usize) -> Option<&'static str> {
+    Some(match category {
+        1 => "movies",
+        2 => "audio",
+        3 => "texts",
+        4 => "software",
+        5 => "image",
+        6 => "etree",
+        _ => return None,
+    })
+}
+
+/// Read a page of results from the JSON of archive.org's advanced search.
+/// Each item's torrent is the one archive.org makes for every file in it.
+pub fn parse_archive(
+    json: &str,
+    base_url: &Url,
+    date_format: Option<String>,
+) -> Result<ResultResponse, NyaaError> {
+    let res: ArchiveResponse = serde_json::from_str(json).map_err(|e| {
+        NyaaError::Parse(format!("Failed to read the archive.org response:\n{}", e))
+    })?;
+    let info = ArchiveSource::info();
+    let link = |path: String| {
+        base_url
+            .join(&path)
+            .map(Into::into)
+            .unwrap_or("null".to_owned())
+    };
+    let items = res
+        .response
+        .docs
+        .into_iter()
+        .map(|d| {
+            let title = match &d.title {
+                serde_json::Value::String(s) => s.to_owned(),
+                serde_json::Value::Array(a) => a
+                    .first()
+                    .and_then(|t| t.as_str())
+                    .unwrap_or(&d.identifier)
+                    .to_owned(),
+                _ => d.identifier.to_owned(),
+            };
+            let time = DateTime::parse_from_rfc3339(&d.publicdate).unwrap_or_default();
+            let date = match date_format.to_owned() {
+                Some(format) => ItemDate::new(time.with_timezone(&chrono::Local), format),
+                None => ItemDate::new(time, "%Y-%m-%d %H:%M"),
+            };
+            let category = (1..=6)
+                .find(|c| mediatype(*c) == Some(d.mediatype.as_str()))
+                .unwrap_or(0);
+            let id = encode(&d.identifier).into_owned();
+            let mut extra = HashMap::new();
+            extra.insert("identifier".to_owned(), d.identifier.to_owned());
+            Item {
+                id: ItemId::new("archive", identifier_num(&d.identifier)),
+                date,
+                downloads: d.downloads,
+                bytes: d.item_size,
+                title,
+                torrent_link: link(format!("/download/{}/{}_archive.torrent", id, id)),
+                post_link: link(format!("/details/{}", id)),
+                file_name: format!("{}_archive.torrent", d.identifier),
+                category,
+                icon: info.clone().entry_from_id(category).icon,
+                extra,
+                ..Default::default()
+            }
+        })
+        .collect();
+
+    Ok(ResultResponse {
+        items,
+        total_results: Some(res.response.num_found),
+        last_page: res.response.num_found.div_ceil(ARCHIVE_PAGE_SIZE).max(1),
+        ..Default::default()
+    })
+}
+
+impl Source for ArchiveSource {
+    async fn search(
+        client: &reqwest::Client,
+        search: &SearchQuery,
+        config: &SourceConfig,
+        date_format: Option<String>,
+    ) -> Result<SourceResponse, NyaaError> {
+        let archive = config.archive.to_owned().unwrap_or_default();
+        let base_url = Url::parse(&add_protocol(archive.base_url, true))?;
+        let sort = ArchiveSort::from_repr(search.sort.sort).unwrap_or(ArchiveSort::Date);
+        let dir = match search.sort.dir {
+            SortDir::Desc => "desc",
+            SortDir::Asc => "asc",
+        };
+        let mut url = base_url.join("advancedsearch.php")?;
+        let fields = [
+            "identifier",
+            "title",
+            "item_size",
+            "downloads",
+            "publicdate",
+            "mediatype",
+        ]
+        .map(|f| format!("fl[]={}", f))
+        .join("&");
+        url.set_query(Some(&format!(
+            "q={}&{}&sort[]={}&rows={}&page={}&output=json",
+            encode(&search_query(&search.query, search.category)),
+            fields,
+            encode(&format!("{} {}", sort.to_url(), dir)),
+            ARCHIVE_PAGE_SIZE,
+            search.page,
+        )));
+
+        let mut request = client.get(url.to_owned());
+        if let Some(timeout) = archive.timeout {
+            request = request.timeout(Duration::from_secs(timeout));
+        }
+        let response = stats::send(request).await?;
+        if response.status() != StatusCode::OK {
+            return Err(NyaaError::Status {
+                url: url.to_string(),
+                code: response.status().as_u16(),
+            });
+        }
+        let json = response.text().await?;
+        let res = parse_archive(&json, &base_url, date_format)?;
+        Ok(SourceResponse::Results(res))
+    }
+    async fn sort(
+        client: &reqwest::Client,
+        search: &SearchQuery,
+        config: &SourceConfig,
+        date_format: Option<String>,
+    ) -> Result<SourceResponse, NyaaError> {
+        ArchiveSource::search(client, search, config, date_format).await
+    }
+    async fn filter(
+        client: &reqwest::Client,
+        search: &SearchQuery,
+        config: &SourceConfig,
+        date_format: Option<String>,
+    ) -> Result<SourceResponse, NyaaError> {
+        ArchiveSource::search(client, search, config, date_format).await
+    }
+    async fn categorize(
+        client: &reqwest::Client,
+        search: &SearchQuery,
+        config: &SourceConfig,
+        date_format: Option<String>,
+    ) -> Result<SourceResponse, NyaaError> {
+        ArchiveSource::search(client, search, config, date_format).await
+    }
+    async fn solve(
+        _solution: String,
+        client: &reqwest::Client,
+        search: &SearchQuery,
+        config: &SourceConfig,
+        date_format: Option<String>,
+    ) -> Result<SourceResponse, NyaaError> {
+        ArchiveSource::search(client, search, config, date_format).await
+    }
+
+    fn info() -> SourceInfo {
+        let cats = cats! {
+            "All Categories" => {
+                0 => ("---", "All Categories", "AllCategories", fg);
+            }
+            "Media" => {
+                1 => ("Mov", "Movies", "Movies", fg);
+                2 => ("Aud", "Audio", "Audio", fg);
+                6 => ("Liv", "Live Music", "LiveMusic", fg);
+                3 => ("Txt", "Texts", "Texts", fg);
+                4 => ("Sof", "Software", "Software", fg);
+                5 => ("Img", "Images", "Images", fg);
+            }
+        };
+        SourceInfo {
+            cats,
+            filters: vec!["NoFilter".to_owned()],
+            sorts: ArchiveSort::VARIANTS
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            capabilities: SourceCapabilities {
+                sort: true,
+                category: true,
+                comments: true,
+                ..Default::default()
+            },
+        }
+    }
+
+    fn load_config(config: &mut SourceConfig) {
+        if config.archive.is_none() {
+            config.archive = Some(ArchiveConfig::default());
+        }
+    }
+
+    fn default_category(cfg: &SourceConfig) -> usize {
+        let default = cfg
+            .archive
+            .as_ref()
+            .map(|c| c.default_category.to_owned())
+            .unwrap_or_default();
+        Self::info().entry_from_cfg(&default).id
+    }
+
+    fn default_sort(cfg: &SourceConfig) -> SelectedSort {
+        cfg.archive
+            .as_ref()
+            .map(|c| SelectedSort {
+                sort: c.default_sort as usize,
+                dir: c.default_sort_dir,
+            })
+            .unwrap_or_default()
+    }
+
+    fn default_filter(_cfg: &SourceConfig) -> usize {
+        0
+    }
+
+    fn default_search(cfg: &SourceConfig) -> String {
+        cfg.archive
+            .as_ref()
+            .map(|c| c.default_search.to_owned())
+            .unwrap_or_default()
+    }
+
+    fn format_table(
+        items: &[Item],
+        search: &SearchQuery,
+        _config: &SourceConfig,
+        theme: &Theme,
+    ) -> ResultTable {
+        let date_width = items
+            .iter()
+            .map(|i| i.date.to_string().len())
+            .max()
+            .unwrap_or_default()
+            .max(6) as u16;
+        let size_width = size_width(items);
+
+        let header = ResultHeader::new([
+            ResultColumn::Normal("Cat".to_owned(), Constraint::Length(3)),
+            ResultColumn::Normal("Name".to_owned(), Constraint::Min(3)),
+            ResultColumn::Sorted("Size".to_owned(), size_width, ArchiveSort::Size as u32),
+            ResultColumn::Sorted("Date".to_owned(), date_width, ArchiveSort::Date as u32),
+            ResultColumn::Sorted("".to_owned(), 5, ArchiveSort::Downloads as u32),
+        ]);
+        let align = [
+            Alignment::Left,
+            Alignment::Left,
+            Alignment::Right,
+            Alignment::Left,
+            Alignment::Left,
+        ];
+        let rows: Vec<ResultRow> = items
+            .iter()
+            .map(|item| {
+                ResultRow::new([
+                    item.icon.label.fg((item.icon.color)(theme)),
+                    theme.item_title(item),
+                    item.size().fg(theme.fg),
+                    item.date.to_string().fg(theme.fg),
+                    shorten_number(item.downloads).fg(theme.fg),
+                ])
+                .aligned(align)
+                .fg(theme.fg)
+            })
+            .collect();
+
+        ResultTable {
+            headers: header.get_row(search.sort.dir, search.sort.sort as u32),
+            rows,
+            binding: header.get_binding(),
+            narrow: vec![true, true, true, true, false],
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use reqwest::{
+    cookie::Jar,
+    header::{HeaderMap, HeaderValue, AUTHORIZATION, SET_COOKIE},
+    Url,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{config::secret, error::NyaaError, util::stats};
+
+/// How to sign in to a source, from `[source.auth.<name>]` where `<name>` is
+/// the name shown in the sources popup. Every value can be given as
+/// `env:VAR_NAME` or `cmd:<command>` instead, or left out and kept in the OS
+/// keyring as `source.<name>.<key>`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum SourceAuth {
+    /// Log in with a form, keeping the session cookie it sets
+    Login {
+        url: String,
+        #[serde(default)]
+        username: String,
+        #[serde(default)]
+        password: String,
+        #[serde(default = "username_field")]
+        username_field: String,
+        #[serde(default = "password_field")]
+        password_field: String,
+    },
+    /// Cookies copied from the browser, like `uid=1234; pass=abcd`
+    Cookie {
+        #[serde(default)]
+        cookie: String,
+    },
+    /// Sent as `Authorization: Bearer <token>`
+    Token {
+        #[serde(default)]
+        token: String,
+    },
+}
+
+fn username_field() -> String {
+    "username".to_owned()
+}
+
+fn password_field() -> String {
+    "password".to_owned()
+}
+
+/// Keyed by source name, e.g. `[source.auth.Nyaa]`
+pub type AuthMap = HashMap<String, SourceAuth>;
+
+/// Sources which have been logged in to since nyaa started
+static LOGGED_IN: Mutex<Vec<String>> = Mutex::new(vec![]);
+
+pub fn logged_in(name: &str) -> bool {
+    LOGGED_IN.lock().is_ok_and(|l| l.iter().any(|n| n == name))
+}
+
+fn set_logged_in(name: &str, on: bool) {
+    if let Ok(mut l) = LOGGED_IN.lock() {
+        l.retain(|n| n != name);
+        if on {
+            l.push(name.to_owned());
+        }
+    }
+}
+
+/// Whether `err` may be from a session which has run out, so logging in again
+/// could fix it. Expired sessions are often sent to the login page, which
+/// can't be read as results.
+pub fn is_expired(err: &NyaaError) -> bool {
+    match err {
+        NyaaError::Status { code, .. } => *code == 401 || *code == 403,
+        NyaaError::Parse(_) => true,
+        _ => false,
+    }
+}
+
+/// Read the `what` of the source `name`, from the keyring if it's used and has
+/// it, otherwise from `value`
+fn resolve(name: &str, what: &str, value: &str) -> Result<String, NyaaError> {
+    secret::resolve_named(&secret::source_secret(name, what), Some(value))
+        .map(Option::unwrap_or_default)
+        .map_err(|e| NyaaError::Config(format!("Failed to read the source {}:\n{}", what, e)))
+}
+
+impl SourceAuth {
+    pub fn is_login(&self) -> bool {
+        matches!(self, SourceAuth::Login { .. })
+    }
+
+    /// Shown in the sources popup
+    pub fn state(&self, name: &str) -> &'static str {
+        match self {
+            SourceAuth::Login { .. } if logged_in(name) => "signed in",
+            SourceAuth::Login { .. } => "signed out",
+            SourceAuth::Cookie { .. } => "cookie",
+            SourceAuth::Token { .. } => "token",
+        }
+    }
+
+    /// Headers sent with every request to the source `name`
+    pub fn headers(&self, name: &str) -> Result<HeaderMap, NyaaError> {
+        let mut headers = HeaderMap::new();
+        if let SourceAuth::Token { token } = self {
+            let value = format!("Bearer {}", resolve(name, "token", token)?);
+            let mut value = HeaderValue::from_str(&value)
+                .map_err(|e| NyaaError::Config(format!("Invalid source token:\n{}", e)))?;
+            value.set_sensitive(true);
+            headers.insert(AUTHORIZATION, value);
+        }
+        Ok(headers)
+    }
+
+    /// Put the cookies of the source `name` in `jar`, to be sent to `base_url`
+    pub fn add_cookies(&self, name: &str, jar: &Jar, base_url: &Url) -> Result<(), NyaaError> {
+        if let SourceAuth::Cookie { cookie } = self {
+            for c in resolve(name, "cookie", cookie)?.split(';') {
+                if !c.trim().is_empty() {
+                    jar.add_cookie_str(c.trim(), base_url);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Log in to the source `name`. The session cookie is kept by the client's
+    /// cookie jar. Login forms often answer a wrong password with the form
+    /// again, so it only counts as signed in once the source sends a cookie
+    /// or away from the login page, without showing the form again.
+    pub async fn login(&self, client: &reqwest::Client, name: &str) -> Result<(), NyaaError> {
+        let SourceAuth::Login {
+            url,
+            username,
+            password,
+            username_field,
+            password_field,
+        } = self
+        else {
+            return Ok(());
+        };
+        set_logged_in(name, false);
+        let form = [
+            (
+                username_field.as_str(),
+                resolve(name, "username", username)?,
+            ),
+            (
+                password_field.as_str(),
+                resolve(name, "password", password)?,
+            ),
+        ];
+        let login_url = Url::parse(url)?;
+        let res = stats::send(client.post(login_url.clone()).form(&form)).await?;
+        if !res.status().is_success() {
+            return Err(NyaaError::Status {
+                url: url.to_owned(),
+                code: res.status().as_u16(),
+            });
+        }
+        let redirected = res.url().path() != login_url.path();
+        let sets_cookie = res.headers().contains_key(SET_COOKIE);
+        let form_again = res
+            .text()
+            .await?
+            .contains(&format!("name=\"{}\"", password_field));
+        if form_again || !(redirected || sets_cookie) {
+            return Err(NyaaError::Auth(format!(
+                "Failed to sign in to {}, check the username and password",
+                name
+            )));
+        }
+        set_logged_in(name, true);
+        Ok(())
+    }
+}
@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use strum::{Display, VariantArray};
+
+use super::{custom_nyaa::CustomCategories, SourceConfig, Sources};
+
+/// A category shared by every source, for searches spanning more than one.
+/// Each source maps it onto one of its own categories.
+#[derive(
+    Serialize, Deserialize, Display, Clone, Copy, VariantArray, PartialEq, Eq, Hash, Debug,
+)]
+pub enum UnifiedCategory {
+    #[strum(serialize = "All Categories")]
+    All,
+    #[strum(serialize = "Anime")]
+    Anime,
+    #[strum(serialize = "Anime – English Translated")]
+    AnimeEnglish,
+    #[strum(serialize = "Anime – Non-English Translated")]
+    AnimeNonEnglish,
+    #[strum(serialize = "Anime – Raw")]
+    AnimeRaw,
+    #[strum(serialize = "Music")]
+    Music,
+    #[strum(serialize = "Books")]
+    Books,
+    #[strum(serialize = "Live Action")]
+    LiveAction,
+    #[strum(serialize = "Pictures")]
+    Pictures,
+    #[strum(serialize = "Software")]
+    Software,
+    #[strum(serialize = "Games")]
+    Games,
+    #[strum(serialize = "Movies")]
+    Movies,
+    #[strum(serialize = "TV")]
+    Tv,
+    #[strum(serialize = "Adult")]
+    Adult,
+}
+
+/// Overrides for each source, keyed by source name, e.g.
+/// `[source.category_map.TorrentGalaxy]`. Values are the same names used for
+/// `default_category`.
+pub type CategoryMap = HashMap<String, HashMap<UnifiedCategory, String>>;
+
+static NYAA: &[(UnifiedCategory, &str)] = &[
+    (UnifiedCategory::All, "AllCategories"),
+    (UnifiedCategory::Anime, "AllAnime"),
+    (UnifiedCategory::AnimeEnglish, "AnimeEnglishTranslated"),
+    (
+        UnifiedCategory::AnimeNonEnglish,
+        "AnimeNonEnglishTranslated",
+    ),
+    (UnifiedCategory::AnimeRaw, "AnimeRaw"),
+    (UnifiedCategory::Music, "AllAudio"),
+    (UnifiedCategory::Books, "AllLiterature"),
+    (UnifiedCategory::LiveAction, "AllLiveAction"),
+    (UnifiedCategory::Pictures, "AllPictures"),
+    (UnifiedCategory::Software, "AllSoftware"),
+    (UnifiedCategory::Games, "SoftGames"),
+];
+
+static SUKEBEI: &[(UnifiedCategory, &str)] = &[
+    (UnifiedCategory::All, "AllCategories"),
+    (UnifiedCategory::Anime, "ArtAnime"),
+    (UnifiedCategory::AnimeEnglish, "ArtAnime"),
+    (UnifiedCategory::AnimeNonEnglish, "ArtAnime"),
+    (UnifiedCategory::AnimeRaw, "ArtAnime"),
+    (UnifiedCategory::Books, "ArtManga"),
+    (UnifiedCategory::LiveAction, "RealVideos"),
+    (UnifiedCategory::Pictures, "ArtPictures"),
+    (UnifiedCategory::Games, "ArtGames"),
+    (UnifiedCategory::Adult, "AllCategories"),
+];
+
+static TGX: &[(UnifiedCategory, &str)] = &[
+    (UnifiedCategory::All, "AllCategories"),
+    (UnifiedCategory::Anime, "Anime"),
+    (UnifiedCategory::AnimeEnglish, "Anime"),
+    (UnifiedCategory::AnimeNonEnglish, "Anime"),
+    (UnifiedCategory::AnimeRaw, "Anime"),
+    (UnifiedCategory::Music, "AlbumsMusic"),
+    (UnifiedCategory::Books, "Ebooks"),
+    (UnifiedCategory::Pictures, "PicturesOther"),
+    (UnifiedCategory::Software, "AppsWindows"),
+    (UnifiedCategory::Games, "WindowsGames"),
+    (UnifiedCategory::Movies, "HdMovies"),
+    (UnifiedCategory::Tv, "HdTV"),
+    (UnifiedCategory::Adult, "HdXXX"),
+];
+
+static EZTV: &[(UnifiedCategory, &str)] = &[
+    (UnifiedCategory::All, "AllCategories"),
+    (UnifiedCategory::Tv, "AllCategories"),
+];
+
+static ARCHIVE: &[(UnifiedCategory, &str)] = &[
+    (UnifiedCategory::All, "AllCategories"),
+    (UnifiedCategory::Music, "Audio"),
+    (UnifiedCategory::Books, "Texts"),
+    (UnifiedCategory::Pictures, "Images"),
+    (UnifiedCategory::Software, "Software"),
+    (UnifiedCategory::Movies, "Movies"),
+];
+
+fn builtin(src: Sources, config: &SourceConfig) -> &'static [(UnifiedCategory, &'static str)] {
+    match src {
+        Sources::Nyaa | Sources::Airing => NYAA,
+        Sources::SukebeiNyaa => SUKEBEI,
+        Sources::TorrentGalaxy => TGX,
+        Sources::Eztv => EZTV,
+        Sources::InternetArchive => ARCHIVE,
+        Sources::Custom(name) => match config
+            .custom_nyaa
+            .iter()
+            .find(|c| c.name == name)
+            .map(|c| c.categories)
+        {
+            Some(CustomCategories::Sukebei) => SUKEBEI,
+            _ => NYAA,
+        },
+    }
+}
+
+impl UnifiedCategory {
+    /// The config name of the category `src` uses for this one, if it has one
+    pub fn native_cfg(self, src: Sources, config: &SourceConfig) -> Option<String> {
+        let name = src.name(config);
+        if let Some(cfg) = config.category_map.get(&name).and_then(|m| m.get(&self)) {
+            return Some(cfg.to_owned());
+        }
+        builtin(src, config)
+            .iter()
+            .find(|(cat, _)| *cat == self)
+            .map(|(_, cfg)| cfg.to_string())
+    }
+
+    /// The id of the category `src` uses for this one. `None` means the
+    /// source has nothing matching, and should be left out of the search.
+    pub fn to_native(self, src: Sources, config: &SourceConfig) -> Option<usize> {
+        let cfg = self.native_cfg(src, config)?;
+        src.info(config)
+            .cats
+            .iter()
+            .flat_map(|c| c.entries.iter())
+            .find(|e| e.cfg == cfg)
+            .map(|e| e.id)
+    }
+
+    /// The first unified category that maps onto category `id` of `src`
+    pub fn from_native(src: Sources, id: usize, config: &SourceConfig) -> Option<Self> {
+        UnifiedCategory::VARIANTS
+            .iter()
+            .find(|cat| cat.to_native(src, config) == Some(id))
+            .copied()
+    }
+}
@@ -0,0 +1,160 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::NyaaError, results::ResultTable, sync::SearchQuery, theme::Theme,
+    widget::sort::SelectedSort,
+};
+
+use super::{
+    nyaa_engine::{nyaa_table, search_nyaa, NyaaColumns},
+    nyaa_html::{NyaaConfig, NyaaHtmlSource, NyaaTransport},
+    sukebei_nyaa::SukebeiHtmlSource,
+    Item, ItemId, RequestHeaders, Source, SourceConfig, SourceInfo, SourceResponse,
+};
+
+/// Which set of categories an instance uses
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CustomCategories {
+    #[default]
+    Nyaa,
+    Sukebei,
+}
+
+/// A self-hosted site running the nyaa codebase, defined with
+/// `[[source.custom_nyaa]]`. Takes the same options as `[source.nyaa]`.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct CustomNyaaConfig {
+    pub name: String,
+    pub categories: CustomCategories,
+    /// For private instances, which can't be searched without signing in
+    pub needs_auth: bool,
+    #[serde(flatten)]
+    pub nyaa: NyaaConfig,
+}
+
+impl CustomNyaaConfig {
+    fn info(&self) -> SourceInfo {
+        let mut info = match self.categories {
+            CustomCategories::Nyaa => NyaaHtmlSource::info(),
+            CustomCategories::Sukebei => SukebeiHtmlSource::info(),
+        };
+        info.capabilities.auth = self.needs_auth;
+        info
+    }
+}
+
+fn get<'a>(name: &str, config: &'a SourceConfig) -> Option<&'a CustomNyaaConfig> {
+    config.custom_nyaa.iter().find(|c| c.name == name)
+}
+
+pub async fn search(
+    name: &str,
+    client: &reqwest::Client,
+    search: &SearchQuery,
+    config: &SourceConfig,
+    date_format: Option<String>,
+) -> Result<SourceResponse, NyaaError> {
+    let custom = get(name, config).ok_or(NyaaError::Config(format!(
+        "There is no custom source named \"{}\" in the config",
+        name
+    )))?;
+    let nyaa = custom.nyaa.to_owned();
+    let prefix = id_prefix(name);
+    match custom.categories {
+        CustomCategories::Nyaa => {
+            search_nyaa::<NyaaHtmlSource>(client, search, nyaa, prefix, config.strict, date_format)
+                .await
+        }
+        CustomCategories::Sukebei => {
+            search_nyaa::<SukebeiHtmlSource>(
+                client,
+                search,
+                nyaa,
+                prefix,
+                config.strict,
+                date_format,
+            )
+            .await
+        }
+    }
+}
+
+/// What ids of items from the instance start with, its name as in
+/// `My Nyaa-123`, so they can't be mixed up with items from nyaa, sukebei or
+/// another instance, however the instances are ordered
+pub fn id_prefix(name: &str) -> &'static str {
+    ItemId::source_name(name)
+}
+
+pub fn info(name: &str, config: &SourceConfig) -> SourceInfo {
+    get(name, config)
+        .map(CustomNyaaConfig::info)
+        .unwrap_or_else(NyaaHtmlSource::info)
+}
+
+pub fn columns(name: &str, config: &SourceConfig) -> Option<Option<NyaaColumns>> {
+    get(name, config).map(|c| c.nyaa.columns)
+}
+
+pub fn base_url(name: &str, config: &SourceConfig) -> String {
+    get(name, config)
+        .map(|c| c.nyaa.base_url.to_owned())
+        .unwrap_or_default()
+}
+
+pub fn transport(name: &str, config: &SourceConfig) -> NyaaTransport {
+    get(name, config)
+        .map(|c| c.nyaa.transport())
+        .unwrap_or_default()
+}
+
+pub fn timeout(name: &str, config: &SourceConfig) -> Option<u64> {
+    get(name, config).and_then(|c| c.nyaa.timeout)
+}
+
+pub fn download_url_template(name: &str, config: &SourceConfig) -> Option<String> {
+    get(name, config).and_then(|c| c.nyaa.download_url_template.clone())
+}
+
+pub fn headers(name: &str, config: &SourceConfig) -> Option<RequestHeaders> {
+    get(name, config).and_then(|c| c.nyaa.headers.clone())
+}
+
+pub fn default_category(name: &str, config: &SourceConfig) -> usize {
+    get(name, config)
+        .map(|c| c.info().entry_from_cfg(&c.nyaa.default_category).id)
+        .unwrap_or_default()
+}
+
+pub fn default_sort(name: &str, config: &SourceConfig) -> SelectedSort {
+    get(name, config)
+        .map(|c| SelectedSort {
+            sort: c.nyaa.default_sort as usize,
+            dir: c.nyaa.default_sort_dir,
+        })
+        .unwrap_or_default()
+}
+
+pub fn default_filter(name: &str, config: &SourceConfig) -> usize {
+    get(name, config)
+        .map(|c| c.nyaa.default_filter as usize)
+        .unwrap_or_default()
+}
+
+pub fn default_search(name: &str, config: &SourceConfig) -> String {
+    get(name, config)
+        .map(|c| c.nyaa.default_search.to_owned())
+        .unwrap_or_default()
+}
+
+pub fn format_table(
+    name: &str,
+    items: &[Item],
+    search: &SearchQuery,
+    config: &SourceConfig,
+    theme: &Theme,
+) -> ResultTable {
+    let columns = get(name, config).and_then(|c| c.nyaa.columns);
+    nyaa_table(items.into(), theme, &search.sort, &columns)
+}
@@ -0,0 +1,96 @@
+use std::{collections::HashSet, sync::Arc};
+
+use tokio::{sync::Semaphore, task::JoinSet};
+
+use crate::{app::LoadType, error::NyaaError, results::ResultResponse, sync::SearchQuery};
+
+use super::{ItemId, SourceConfig, SourceResponse, Sources};
+
+// Most pages requested from a source at once
+static MAX_CONCURRENT_PAGES: usize = 3;
+
+/// Search the first `pages` pages of `src` and merge their results, dropping
+/// items listed on more than one page. The first page is loaded on its own to
+/// find out how many pages there are, then the rest a few at a time. Pages
+/// after the first which fail are left out and reported as warnings.
+pub async fn search(
+    src: Sources,
+    pages: usize,
+    client: &reqwest::Client,
+    search: &SearchQuery,
+    config: &SourceConfig,
+    date_format: Option<String>,
+) -> Result<SourceResponse, NyaaError> {
+    let first = SearchQuery {
+        page: 1,
+        ..search.clone()
+    };
+    // Only one variant without the captcha feature
+    #[allow(clippy::infallible_destructuring_match)]
+    let mut res = match src
+        .load_page(
+            LoadType::Searching,
+            client,
+            &first,
+            config,
+            date_format.clone(),
+        )
+        .await?
+    {
+        SourceResponse::Results(res) => res,
+        #[cfg(feature = "captcha")]
+        captcha => return Ok(captcha),
+    };
+
+    let permits = Arc::new(Semaphore::new(MAX_CONCURRENT_PAGES));
+    let mut set = JoinSet::new();
+    for page in 2..=pages.min(res.last_page) {
+        let (client, permits, config, date_format) = (
+            client.clone(),
+            permits.clone(),
+            config.clone(),
+            date_format.clone(),
+        );
+        let search = SearchQuery {
+            page,
+            ..search.clone()
+        };
+        set.spawn(async move {
+            let _permit = permits.acquire().await;
+            let res = src
+                .load_page(LoadType::Searching, &client, &search, &config, date_format)
+                .await;
+            (page, res)
+        });
+    }
+    let mut loaded: Vec<(usize, ResultResponse)> = vec![];
+    let mut failed: Vec<(usize, String)> = vec![];
+    while let Some(joined) = set.join_next().await {
+        match joined {
+            Ok((page, Ok(SourceResponse::Results(r)))) => loaded.push((page, r)),
+            Ok((page, Err(e))) => failed.push((page, e.to_string())),
+            #[cfg(feature = "captcha")]
+            Ok((page, Ok(_))) => failed.push((page, "The source asked for a captcha".to_owned())),
+            // Only if the task panicked, which is already reported
+            Err(_) => {}
+        }
+    }
+    loaded.sort_by_key(|(page, _)| *page);
+    failed.sort_by_key(|(page, _)| *page);
+
+    let mut seen: HashSet<ItemId> = res.items.iter().map(|i| i.id.clone()).collect();
+    for (_, page) in loaded.iter_mut() {
+        res.warnings.append(&mut page.warnings);
+        res.items
+            .extend(page.items.drain(..).filter(|i| seen.insert(i.id.clone())));
+    }
+    for (page, e) in failed {
+        res.warnings.push(format!(
+            "Failed to load page {} of the deep search:\n{}",
+            page, e
+        ));
+    }
+    // Shown as the pages which loaded, split locally
+    res.last_page = loaded.len() + 1;
+    Ok(SourceResponse::Results(res))
+}
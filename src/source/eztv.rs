@@ -0,0 +1,439 @@
+use std::{cmp::Ordering, collections::HashMap, time::Duration};
+
+use chrono::DateTime;
+use ratatui::{
+    layout::{Alignment, Constraint},
+    style::Stylize,
+};
+use reqwest::{StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use strum::{FromRepr, VariantArray};
+
+use crate::{
+    cats, cond_vec,
+    error::NyaaError,
+    results::{ResultColumn, ResultHeader, ResultResponse, ResultRow, ResultTable},
+    sync::SearchQuery,
+    theme::Theme,
+    util::stats,
+    widget::sort::{SelectedSort, SortDir},
+};
+
+use super::{
+    add_protocol, nyaa_engine::size_width, ColumnConfig, Item, ItemDate, ItemId, RequestHeaders,
+    Source, SourceCapabilities, SourceConfig, SourceInfo, SourceResponse,
+};
+
+/// Torrents per page of the API, which allows at most 100
+static EZTV_PAGE_SIZE: usize = 100;
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct EztvConfig {
+    pub base_url: String,
+    pub default_sort: EztvSort,
+    pub default_sort_dir: SortDir,
+    pub default_search: String,
+    pub timeout: Option<u64>,
+    pub columns: Option<EztvColumns>,
+    pub headers: Option<RequestHeaders>,
+}
+
+impl Default for EztvConfig {
+    fn default() -> Self {
+        Self {
+            base_url: "https://eztvx.to/".to_owned(),
+            default_sort: EztvSort::Date,
+            default_sort_dir: SortDir::Desc,
+            default_search: Default::default(),
+            timeout: None,
+            columns: None,
+            headers: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, Default)]
+pub struct EztvColumns {
+    title: Option<bool>,
+    season: Option<bool>,
+    episode: Option<bool>,
+    imdb: Option<bool>,
+    size: Option<bool>,
+    date: Option<bool>,
+    seeders: Option<bool>,
+    leechers: Option<bool>,
+}
+
+impl ColumnConfig for EztvColumns {
+    const NAMES: &'static [&'static str] = &[
+        "Title", "Season", "Episode", "IMDB", "Size", "Date", "Seeders", "Leechers",
+    ];
+
+    fn shown(self) -> Vec<bool> {
+        self.array().to_vec()
+    }
+
+    fn set(&mut self, idx: usize, shown: bool) {
+        let fields = [
+            &mut self.title,
+            &mut self.season,
+            &mut self.episode,
+            &mut self.imdb,
+            &mut self.size,
+            &mut self.date,
+            &mut self.seeders,
+            &mut self.leechers,
+        ];
+        if let Some(field) = fields.into_iter().nth(idx) {
+            *field = Some(shown);
+        }
+    }
+}
+
+impl EztvColumns {
+    fn array(self) -> [bool; 8] {
+        [
+            self.title.unwrap_or(true),
+            self.season.unwrap_or(true),
+            self.episode.unwrap_or(true),
+            self.imdb.unwrap_or(true),
+            self.size.unwrap_or(true),
+            self.date.unwrap_or(true),
+            self.seeders.unwrap_or(true),
+            self.leechers.unwrap_or(true),
+        ]
+    }
+}
+
+/// The API doesn't sort, so each page is sorted after it loads
+#[derive(
+    Serialize, Deserialize, strum::Display, Clone, Copy, VariantArray, PartialEq, Eq, FromRepr,
+)]
+#[repr(usize)]
+pub enum EztvSort {
+    Date = 0,
+    Seeders = 1,
+    Leechers = 2,
+    Size = 3,
+}
+
+#[derive(Deserialize)]
+struct EztvTorrent {
+    id: usize,
+    #[serde(default)]
+    hash: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    episode_url: String,
+    #[serde(default)]
+    torrent_url: String,
+    #[serde(default)]
+    magnet_url: String,
+    #[serde(default)]
+    imdb_id: String,
+    #[serde(default)]
+    season: String,
+    #[serde(default)]
+    episode: String,
+    #[serde(default)]
+    seeds: u32,
+    #[serde(default)]
+    peers: u32,
+    #[serde(default)]
+    date_released_unix: i64,
+    /// Given as a string of digits
+    #[serde(default)]
+    size_bytes: serde_json::Value,
+}
+
+#[derive(Deserialize)]
+struct EztvResponse {
+    #[serde(default)]
+    torrents_count: usize,
+    #[serde(default)]
+    limit: Option<usize>,
+    #[serde(default)]
+    torrents: Vec<EztvTorrent>,
+}
+
+pub struct EztvSource;
+
+/// The number of an IMDB id, if `query` is one, like `tt0944947`
+pub fn imdb_id(query: &str) -> Option<String> {
+    let digits = query.trim().strip_prefix("tt")?;
+    (!digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit()))
+        .then(|| digits.trim_start_matches('0').to_owned())
+}
+
+/// Sort items in descending order, or ascending if `sort.dir` is `Asc`
+pub fn sort_items(items: &mut [Item], sort: SelectedSort) {
+    let f: fn(&Item, &Item) -> Ordering = match EztvSort::from_repr(sort.sort) {
+        Some(EztvSort::Seeders) => |a, b| b.seeders.cmp(&a.seeders),
+        Some(EztvSort::Leechers) => |a, b| b.leechers.cmp(&a.leechers),
+        Some(EztvSort::Size) => |a, b| b.bytes.cmp(&a.bytes),
+        _ => |a, b| b.date.time.cmp(&a.date.time).then(b.id.num.cmp(&a.id.num)),
+    };
+    items.sort_by(f);
+    if sort.dir == SortDir::Asc {
+        items.reverse();
+    }
+}
+
+/// Read a page of torrents from the JSON of EZTV's API. Searches which aren't
+/// for an IMDB id only keep the titles containing every word of `query`.
+pub fn parse_eztv(
+    json: &str,
+    query: &str,
+    date_format: Option<String>,
+) -> Result<ResultResponse, NyaaError> {
+    let res: EztvResponse = serde_json::from_str(json)
+        .map_err(|e| NyaaError::Parse(format!("Failed to read the EZTV response:\n{}", e)))?;
+    let words: Vec<String> = match imdb_id(query) {
+        Some(_) => vec![],
+        None => query.split_whitespace().map(str::to_lowercase).collect(),
+    };
+    let icon = EztvSource::info().entry_from_id(0).icon;
+    let items: Vec<Item> = res
+        .torrents
+        .into_iter()
+        .filter(|t| {
+            let title = t.title.to_lowercase();
+            words.iter().all(|w| title.contains(w))
+        })
+        .map(|t| {
+            let time = DateTime::from_timestamp(t.date_released_unix, 0).unwrap_or_default();
+            let date = match date_format.to_owned() {
+                Some(format) => ItemDate::new(time.with_timezone(&chrono::Local), format),
+                None => ItemDate::new(time, "%Y-%m-%d %H:%M"),
+            };
+            let bytes = match &t.size_bytes {
+                serde_json::Value::String(s) => s.parse().unwrap_or_default(),
+                v => v.as_u64().unwrap_or_default(),
+            };
+            let mut extra = HashMap::new();
+            extra.insert("season".to_owned(), t.season);
+            extra.insert("episode".to_owned(), t.episode);
+            if !t.imdb_id.is_empty() && t.imdb_id != "0" {
+                extra.insert("imdb".to_owned(), format!("tt{:0>7}", t.imdb_id));
+            }
+            Item {
+                id: ItemId::new("eztv", t.id),
+                date,
+                seeders: t.seeds,
+                leechers: t.peers,
+                bytes,
+                title: t.title,
+                torrent_link: t.torrent_url,
+                magnet_link: t.magnet_url,
+                post_link: t.episode_url,
+                file_name: format!("{}.torrent", t.hash),
+                icon: icon.clone(),
+                extra,
+                ..Default::default()
+            }
+        })
+        .collect();
+
+    let limit = res.limit.unwrap_or(EZTV_PAGE_SIZE).max(1);
+    Ok(ResultResponse {
+        items,
+        // The count is of every torrent, not only those matching the words
+        total_results: words.is_empty().then_some(res.torrents_count),
+        last_page: res.torrents_count.div_ceil(limit).max(1),
+        ..Default::default()
+    })
+}
+
+impl Source for EztvSource {
+    async fn search(
+        client: &reqwest::Client,
+        search: &SearchQuery,
+        config: &SourceConfig,
+        date_format: Option<String>,
+    ) -> Result<SourceResponse, NyaaError> {
+        let eztv = config.eztv.to_owned().unwrap_or_default();
+        let base_url = Url::parse(&add_protocol(eztv.base_url, true))?;
+        let mut url = base_url.join("api/get-torrents")?;
+        let mut query = format!("limit={}&page={}", EZTV_PAGE_SIZE, search.page);
+        if let Some(id) = imdb_id(&search.query) {
+            query = format!("imdb_id={}&{}", id, query);
+        }
+        url.set_query(Some(&query));
+
+        let mut request = client.get(url.to_owned());
+        if let Some(timeout) = eztv.timeout {
+            request = request.timeout(Duration::from_secs(timeout));
+        }
+        let response = stats::send(request).await?;
+        if response.status() != StatusCode::OK {
+            return Err(NyaaError::Status {
+                url: url.to_string(),
+                code: response.status().as_u16(),
+            });
+        }
+        let json = response.text().await?;
+        let mut res = parse_eztv(&json, &search.query, date_format)?;
+        sort_items(&mut res.items, search.sort);
+        Ok(SourceResponse::Results(res))
+    }
+    async fn sort(
+        client: &reqwest::Client,
+        search: &SearchQuery,
+        config: &SourceConfig,
+        date_format: Option<String>,
+    ) -> Result<SourceResponse, NyaaError> {
+        EztvSource::search(client, search, config, date_format).await
+    }
+    async fn filter(
+        client: &reqwest::Client,
+        search: &SearchQuery,
+        config: &SourceConfig,
+        date_format: Option<String>,
+    ) -> Result<SourceResponse, NyaaError> {
+        EztvSource::search(client, search, config, date_format).await
+    }
+    async fn categorize(
+        client: &reqwest::Client,
+        search: &SearchQuery,
+        config: &SourceConfig,
+        date_format: Option<String>,
+    ) -> Result<SourceResponse, NyaaError> {
+        EztvSource::search(client, search, config, date_format).await
+    }
+    async fn solve(
+        _solution: String,
+        client: &reqwest::Client,
+        search: &SearchQuery,
+        config: &SourceConfig,
+        date_format: Option<String>,
+    ) -> Result<SourceResponse, NyaaError> {
+        EztvSource::search(client, search, config, date_format).await
+    }
+
+    fn info() -> SourceInfo {
+        let cats = cats! {
+            "All Categories" => {
+                0 => ("TV ", "All TV", "AllCategories", fg);
+            }
+        };
+        SourceInfo {
+            cats,
+            filters: vec!["NoFilter".to_owned()],
+            sorts: EztvSort::VARIANTS.iter().map(ToString::to_string).collect(),
+            capabilities: SourceCapabilities {
+                sort: true,
+                ..Default::default()
+            },
+        }
+    }
+
+    fn load_config(config: &mut SourceConfig) {
+        if config.eztv.is_none() {
+            config.eztv = Some(EztvConfig::default());
+        }
+    }
+
+    fn default_category(_cfg: &SourceConfig) -> usize {
+        0
+    }
+
+    fn default_sort(cfg: &SourceConfig) -> SelectedSort {
+        cfg.eztv
+            .as_ref()
+            .map(|c| SelectedSort {
+                sort: c.default_sort as usize,
+                dir: c.default_sort_dir,
+            })
+            .unwrap_or_default()
+    }
+
+    fn default_filter(_cfg: &SourceConfig) -> usize {
+        0
+    }
+
+    fn default_search(cfg: &SourceConfig) -> String {
+        cfg.eztv
+            .as_ref()
+            .map(|c| c.default_search.to_owned())
+            .unwrap_or_default()
+    }
+
+    fn format_table(
+        items: &[Item],
+        search: &SearchQuery,
+        config: &SourceConfig,
+        theme: &Theme,
+    ) -> ResultTable {
+        let eztv = config.eztv.to_owned().unwrap_or_default();
+        let date_width = items
+            .iter()
+            .map(|i| i.date.to_string().len())
+            .max()
+            .unwrap_or_default()
+            .max(6) as u16;
+        let size_width = size_width(items);
+        let extra = |item: &Item, key: &str| item.extra.get(key).cloned().unwrap_or_default();
+
+        let header = ResultHeader::new([
+            ResultColumn::Normal("Name".to_owned(), Constraint::Min(3)),
+            ResultColumn::Normal("S".to_owned(), Constraint::Length(2)),
+            ResultColumn::Normal("Ep".to_owned(), Constraint::Length(3)),
+            ResultColumn::Normal("imdb".to_owned(), Constraint::Length(10)),
+            ResultColumn::Sorted("Size".to_owned(), size_width, EztvSort::Size as u32),
+            ResultColumn::Sorted("Date".to_owned(), date_width, EztvSort::Date as u32),
+            ResultColumn::Sorted("".to_owned(), 4, EztvSort::Seeders as u32),
+            ResultColumn::Sorted("".to_owned(), 4, EztvSort::Leechers as u32),
+        ]);
+        let mut binding = header.get_binding();
+        let align = [
+            Alignment::Left,
+            Alignment::Right,
+            Alignment::Right,
+            Alignment::Left,
+            Alignment::Right,
+            Alignment::Left,
+            Alignment::Right,
+            Alignment::Right,
+        ];
+        let mut rows: Vec<ResultRow> = items
+            .iter()
+            .map(|item| {
+                ResultRow::new([
+                    theme.item_title(item),
+                    extra(item, "season").fg(theme.fg),
+                    extra(item, "episode").fg(theme.fg),
+                    extra(item, "imdb").fg(theme.fg),
+                    item.size().fg(theme.fg),
+                    item.date.to_string().fg(theme.fg),
+                    item.seeders.to_string().fg(theme.success),
+                    item.leechers.to_string().fg(theme.error),
+                ])
+                .aligned(align)
+                .fg(theme.fg)
+            })
+            .collect();
+        let mut narrow = vec![true, true, true, false, true, true, true, false];
+        let mut headers = header.get_row(search.sort.dir, search.sort.sort as u32);
+        let cols = eztv.columns.unwrap_or_default().array();
+
+        headers.cells = cond_vec!(cols ; headers.cells);
+        rows = rows
+            .into_iter()
+            .map(|mut r| {
+                r.cells = cond_vec!(cols ; r.cells.to_owned());
+                r
+            })
+            .collect();
+        binding = cond_vec!(cols ; binding);
+        narrow = cond_vec!(cols ; narrow);
+
+        ResultTable {
+            headers,
+            rows,
+            binding,
+            narrow,
+        }
+    }
+}
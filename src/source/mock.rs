@@ -0,0 +1,174 @@
+use crate::{
+    error::NyaaError,
+    results::{ResultResponse, ResultTable},
+    sync::SearchQuery,
+    theme::Theme,
+    util::conv::to_bytes,
+    widget::sort::SelectedSort,
+};
+
+use super::{
+    nyaa_html::NyaaHtmlSource, nyaa_rss, Item, ItemDate, ItemId, ItemType, Source, SourceConfig,
+    SourceInfo, SourceResponse,
+};
+
+// (title, category, size, date, seeders, leechers, downloads, type)
+type Fixture = (
+    &'static str,
+    usize,
+    &'static str,
+    &'static str,
+    u32,
+    u32,
+    u32,
+    ItemType,
+);
+
+#[rustfmt::skip]
+static FIXTURES: &[Fixture] = &[
+    ("[SubsPlease] Sousou no Frieren - 28 (1080p) [A1B2C3D4].mkv", 12, "1.4 GiB", "2024-06-14 18:02", 2311, 104, 15230, ItemType::Trusted),
+    ("[SubsPlease] Sousou no Frieren - 28 (720p) [E5F6A7B8].mkv", 12, "724.1 MiB", "2024-06-14 18:01", 874, 31, 6120, ItemType::Trusted),
+    ("[Erai-raws] One Punch Man - 01 ~ 12 [1080p][Multiple Subtitle]", 12, "16.8 GiB", "2024-06-12 09:41", 412, 56, 3301, ItemType::None),
+    ("[Judas] Dungeon Meshi - S01E22 [1080p][HEVC x265 10bit][Eng-Subs]", 12, "402.6 MiB", "2024-06-11 21:15", 198, 12, 2450, ItemType::None),
+    ("[ASW] Kaijuu 8-gou - 10 [1080p HEVC][C0FFEE00].mkv", 12, "311.9 MiB", "2024-06-11 15:30", 356, 9, 4012, ItemType::None),
+    ("[Ohys-Raws] Hibike! Euphonium 3 - 11 (BS11 1280x720 x264 AAC).mp4", 14, "512.3 MiB", "2024-06-10 23:59", 88, 4, 901, ItemType::None),
+    ("[Anime Time] Mushishi (Complete Series) [BD][1080p][HEVC 10bit x265]", 12, "22.1 GiB", "2024-06-09 04:12", 61, 27, 1278, ItemType::None),
+    ("[Fan-Sub] Frieren AMV - Anytime Anywhere (1080p).mp4", 11, "98.4 MiB", "2024-06-08 12:00", 5, 0, 77, ItemType::Remake),
+    ("Yoasobi - THE BOOK 3 [FLAC 24bit/96kHz]", 21, "1.1 GiB", "2024-06-07 08:20", 143, 3, 2133, ItemType::Trusted),
+    ("Kenshi Yonezu - LOST CORNER [MP3 320K]", 22, "184.2 MiB", "2024-06-06 17:45", 77, 1, 980, ItemType::None),
+    ("[LuCaZ] Chainsaw Man v01-16 (Digital) [English]", 31, "2.3 GiB", "2024-06-05 10:10", 120, 14, 1665, ItemType::None),
+    ("Shin Godzilla (2016) [BD 1080p x264 DTS-HD]", 41, "8.7 GiB", "2024-06-03 02:33", 35, 6, 512, ItemType::None),
+];
+
+/// Serves a fixed set of results without touching the network, used by
+/// `--offline` and in tests
+pub struct MockSource;
+
+impl MockSource {
+    pub fn items(search: &SearchQuery) -> Vec<Item> {
+        let info = Self::info();
+        let query = search.query.to_lowercase();
+        let mut items: Vec<Item> = FIXTURES
+            .iter()
+            .enumerate()
+            .map(|(i, f)| {
+                let (title, cat, size, date, seeders, leechers, downloads, item_type) = *f;
+                let id = ItemId::new("mock", 1000 + FIXTURES.len() - i);
+                Item {
+                    id: id.clone(),
+                    date: ItemDate::parse_utc(date, "%Y-%m-%d %H:%M", None),
+                    seeders,
+                    leechers,
+                    downloads,
+                    bytes: to_bytes(size),
+                    title: title.to_owned(),
+                    torrent_link: format!("https://example.com/download/{}.torrent", id),
+                    magnet_link: format!("magnet:?xt=urn:btih:{}", id),
+                    post_link: format!("https://example.com/view/{}", id),
+                    file_name: format!("{}.torrent", id),
+                    category: cat,
+                    icon: info.clone().entry_from_id(cat).icon,
+                    item_type,
+                    ..Default::default()
+                }
+            })
+            .filter(|i| query.is_empty() || i.title.to_lowercase().contains(&query))
+            .filter(|i| match search.category {
+                0 => true,
+                c if c % 10 == 0 => i.category / 10 == c / 10,
+                c => i.category == c,
+            })
+            .filter(|i| match (search.filter, i.item_type) {
+                (1, ItemType::Remake) => false,
+                (2, t) => matches!(t, ItemType::Trusted),
+                _ => true,
+            })
+            .collect();
+        nyaa_rss::sort_items(&mut items, search.sort);
+        items
+    }
+}
+
+impl Source for MockSource {
+    async fn search(
+        _client: &reqwest::Client,
+        search: &SearchQuery,
+        _config: &SourceConfig,
+        _date_format: Option<String>,
+    ) -> Result<SourceResponse, NyaaError> {
+        let items = Self::items(search);
+        Ok(SourceResponse::Results(ResultResponse {
+            total_results: Some(items.len()),
+            last_page: 1,
+            items,
+            ..Default::default()
+        }))
+    }
+    async fn sort(
+        client: &reqwest::Client,
+        search: &SearchQuery,
+        config: &SourceConfig,
+        date_format: Option<String>,
+    ) -> Result<SourceResponse, NyaaError> {
+        MockSource::search(client, search, config, date_format).await
+    }
+    async fn filter(
+        client: &reqwest::Client,
+        search: &SearchQuery,
+        config: &SourceConfig,
+        date_format: Option<String>,
+    ) -> Result<SourceResponse, NyaaError> {
+        MockSource::search(client, search, config, date_format).await
+    }
+    async fn categorize(
+        client: &reqwest::Client,
+        search: &SearchQuery,
+        config: &SourceConfig,
+        date_format: Option<String>,
+    ) -> Result<SourceResponse, NyaaError> {
+        MockSource::search(client, search, config, date_format).await
+    }
+    async fn solve(
+        _solution: String,
+        client: &reqwest::Client,
+        search: &SearchQuery,
+        config: &SourceConfig,
+        date_format: Option<String>,
+    ) -> Result<SourceResponse, NyaaError> {
+        MockSource::search(client, search, config, date_format).await
+    }
+
+    // The fixtures mimic nyaa, so reuse its categories, sorts and table layout
+    fn info() -> SourceInfo {
+        NyaaHtmlSource::info()
+    }
+
+    fn load_config(config: &mut SourceConfig) {
+        NyaaHtmlSource::load_config(config)
+    }
+
+    fn default_category(config: &SourceConfig) -> usize {
+        NyaaHtmlSource::default_category(config)
+    }
+
+    fn default_sort(config: &SourceConfig) -> SelectedSort {
+        NyaaHtmlSource::default_sort(config)
+    }
+
+    fn default_filter(config: &SourceConfig) -> usize {
+        NyaaHtmlSource::default_filter(config)
+    }
+
+    fn default_search(config: &SourceConfig) -> String {
+        NyaaHtmlSource::default_search(config)
+    }
+
+    fn format_table(
+        items: &[Item],
+        search: &SearchQuery,
+        config: &SourceConfig,
+        theme: &Theme,
+    ) -> ResultTable {
+        NyaaHtmlSource::format_table(items, search, config, theme)
+    }
+}
@@ -0,0 +1,184 @@
+use std::time::Duration;
+
+use reqwest::{StatusCode, Url};
+use serde::Deserialize;
+use urlencoding::encode;
+
+use crate::{error::NyaaError, results::ResultResponse, sync::SearchQuery, util::stats};
+
+use super::{
+    add_protocol, nyaa_html::NyaaSort, Item, ItemDate, ItemId, ItemType, Source, SourceResponse,
+};
+
+/// Searched relative to `base_url`, with the same parameters as the search page
+pub static API_PATH: &str = "api/search";
+
+// Pages of the site's search, assumed when the API doesn't say how many
+// there are
+static API_PAGE_SIZE: usize = 75;
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct ApiStats {
+    seeders: u32,
+    leechers: u32,
+    downloads: u32,
+}
+
+/// A torrent in the same shape as nyaa's `/api/info/<id>`
+#[derive(Deserialize)]
+struct ApiTorrent {
+    id: usize,
+    name: String,
+    #[serde(default)]
+    url: Option<String>,
+    #[serde(default)]
+    magnet: String,
+    #[serde(default)]
+    main_category_id: usize,
+    #[serde(default)]
+    sub_category_id: usize,
+    #[serde(default)]
+    filesize: u64,
+    /// Like `2024-06-12 09:41 UTC`
+    #[serde(default)]
+    creation_date: String,
+    #[serde(default)]
+    stats: ApiStats,
+    #[serde(default)]
+    is_trusted: bool,
+    #[serde(default)]
+    is_remake: bool,
+}
+
+/// Either a bare list of torrents, or a page of them with the number of
+/// results and pages
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ApiResponse {
+    Page {
+        torrents: Vec<ApiTorrent>,
+        #[serde(default)]
+        total: Option<usize>,
+        #[serde(default)]
+        last_page: Option<usize>,
+    },
+    List(Vec<ApiTorrent>),
+}
+
+/// Read a page of results from the JSON of a nyaa-compatible API. Links which
+/// aren't given are made relative to `base_url`, like the search page's.
+pub fn parse_api<S: Source>(
+    json: &str,
+    base_url: &Url,
+    page: usize,
+    id_prefix: &'static str,
+    date_format: Option<String>,
+) -> Result<ResultResponse, NyaaError> {
+    let res: ApiResponse = serde_json::from_str(json)
+        .map_err(|e| NyaaError::Parse(format!("Failed to read the API response:\n{}", e)))?;
+    let (torrents, total, last_page) = match res {
+        ApiResponse::Page {
+            torrents,
+            total,
+            last_page,
+        } => (torrents, total, last_page),
+        ApiResponse::List(torrents) => (torrents, None, None),
+    };
+    let last_page = last_page
+        .or(total.map(|t| t.div_ceil(API_PAGE_SIZE)))
+        // Without a count, there may be another page if this one is full
+        .unwrap_or(match torrents.len() < API_PAGE_SIZE {
+            true => page,
+            false => page + 1,
+        })
+        .max(1);
+
+    let items = torrents
+        .into_iter()
+        .map(|t| {
+            let cat =
+                S::info().entry_from_str(&format!("{}_{}", t.main_category_id, t.sub_category_id));
+            let link = |path: String| {
+                base_url
+                    .join(&path)
+                    .map(Into::into)
+                    .unwrap_or("null".to_owned())
+            };
+            let item_type = match (t.is_trusted, t.is_remake) {
+                (true, _) => ItemType::Trusted,
+                (_, true) => ItemType::Remake,
+                _ => ItemType::None,
+            };
+            let date = t.creation_date.trim_end_matches("UTC");
+            Item {
+                id: ItemId::new(id_prefix, t.id),
+                date: ItemDate::parse_utc(date, "%Y-%m-%d %H:%M", date_format.to_owned()),
+                seeders: t.stats.seeders,
+                leechers: t.stats.leechers,
+                downloads: t.stats.downloads,
+                bytes: t.filesize,
+                title: t.name,
+                torrent_link: link(format!("/download/{}.torrent", t.id)),
+                magnet_link: t.magnet,
+                post_link: t.url.unwrap_or_else(|| link(format!("/view/{}", t.id))),
+                file_name: format!("{}.torrent", t.id),
+                item_type,
+                category: cat.id,
+                icon: cat.icon.clone(),
+                ..Default::default()
+            }
+        })
+        .collect();
+
+    Ok(ResultResponse {
+        items,
+        total_results: total,
+        last_page,
+        ..Default::default()
+    })
+}
+
+pub async fn search_api<S: Source>(
+    base_url: String,
+    timeout: Option<u64>,
+    client: &reqwest::Client,
+    search: &SearchQuery,
+    id_prefix: &'static str,
+    date_format: Option<String>,
+) -> Result<SourceResponse, NyaaError> {
+    let (high, low) = (search.category / 10, search.category % 10);
+    let sort = NyaaSort::from_repr(search.sort.sort)
+        .unwrap_or(NyaaSort::Date)
+        .to_url();
+    let base_url = Url::parse(&add_protocol(base_url, true))?;
+    let mut url = base_url
+        .join(API_PATH)
+        .map_err(|e| NyaaError::Config(format!("{}\nInvalid API url:\n{}", base_url, e)))?;
+    url.set_query(Some(&format!(
+        "q={}&c={}_{}&f={}&p={}&s={}&o={}&u={}",
+        encode(&search.query),
+        high,
+        low,
+        search.filter,
+        search.page,
+        sort,
+        search.sort.dir.to_url(),
+        search.user.to_owned().unwrap_or_default()
+    )));
+
+    let mut request = client.get(url.to_owned());
+    if let Some(timeout) = timeout {
+        request = request.timeout(Duration::from_secs(timeout));
+    }
+    let response = stats::send(request).await?;
+    if response.status() != StatusCode::OK {
+        return Err(NyaaError::Status {
+            url: url.to_string(),
+            code: response.status().as_u16(),
+        });
+    }
+    let json = response.text().await?;
+    let res = parse_api::<S>(&json, &base_url, search.page, id_prefix, date_format)?;
+    Ok(SourceResponse::Results(res))
+}
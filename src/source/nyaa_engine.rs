@@ -0,0 +1,535 @@
+//! Scraping shared by every site running the nyaa codebase. Sites only differ
+//! in their base URL, category table, columns and the prefix given to ids, so
+//! adding another one only needs a config and a set of categories.
+
+use std::{cmp::max, time::Duration};
+
+use ratatui::{
+    layout::{Alignment, Constraint},
+    style::Stylize as _,
+};
+use rayon::iter::{
+    IndexedParallelIterator as _, IntoParallelIterator as _, IntoParallelRefIterator as _,
+    ParallelIterator as _,
+};
+use reqwest::{StatusCode, Url};
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use urlencoding::encode;
+
+use crate::{
+    cond_vec,
+    error::NyaaError,
+    results::{ResultColumn, ResultHeader, ResultResponse, ResultRow, ResultTable},
+    sel,
+    sync::SearchQuery,
+    theme::Theme,
+    util::{
+        anime::ParsedTitle,
+        conv::{shorten_number, to_bytes},
+        html::{as_type, attr, check_selectors, inner},
+        stats,
+    },
+    widget::sort::SelectedSort,
+};
+
+use super::{
+    add_protocol, nyaa_api,
+    nyaa_html::{NyaaConfig, NyaaSort, NyaaTransport},
+    nyaa_rss, ColumnConfig, Item, ItemDate, ItemId, ItemType, Source, SourceResponse,
+};
+
+/// CSS selectors used to scrape nyaa-like sites. Any that are unset use the
+/// selectors for nyaa.si.
+#[derive(Clone, Serialize, Deserialize, Default)]
+pub struct NyaaSelectors {
+    pub table: Option<String>,
+    pub item: Option<String>,
+    pub icon: Option<String>,
+    pub title: Option<String>,
+    pub torrent: Option<String>,
+    pub magnet: Option<String>,
+    pub size: Option<String>,
+    pub date: Option<String>,
+    pub seeders: Option<String>,
+    pub leechers: Option<String>,
+    pub downloads: Option<String>,
+    pub pagination: Option<String>,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, Default)]
+pub struct NyaaColumns {
+    category: Option<bool>,
+    title: Option<bool>,
+    size: Option<bool>,
+    date: Option<bool>,
+    seeders: Option<bool>,
+    leechers: Option<bool>,
+    downloads: Option<bool>,
+    /// Resolution read from the title, hidden unless enabled
+    resolution: Option<bool>,
+    /// Episode read from the title, hidden unless enabled
+    episode: Option<bool>,
+    /// Release group read from the title, hidden unless enabled
+    group: Option<bool>,
+    /// ✓ for trusted and ✗ for remakes, hidden unless enabled
+    status: Option<bool>,
+}
+
+impl ColumnConfig for NyaaColumns {
+    const NAMES: &'static [&'static str] = &[
+        "Category",
+        "Status",
+        "Title",
+        "Resolution",
+        "Episode",
+        "Group",
+        "Size",
+        "Date",
+        "Seeders",
+        "Leechers",
+        "Downloads",
+    ];
+
+    fn shown(self) -> Vec<bool> {
+        self.array().to_vec()
+    }
+
+    fn set(&mut self, idx: usize, shown: bool) {
+        let fields = [
+            &mut self.category,
+            &mut self.status,
+            &mut self.title,
+            &mut self.resolution,
+            &mut self.episode,
+            &mut self.group,
+            &mut self.size,
+            &mut self.date,
+            &mut self.seeders,
+            &mut self.leechers,
+            &mut self.downloads,
+        ];
+        if let Some(field) = fields.into_iter().nth(idx) {
+            *field = Some(shown);
+        }
+    }
+}
+
+impl NyaaColumns {
+    fn array(self) -> [bool; 11] {
+        [
+            self.category.unwrap_or(true),
+            self.status.unwrap_or(false),
+            self.title.unwrap_or(true),
+            self.resolution.unwrap_or(false),
+            self.episode.unwrap_or(false),
+            self.group.unwrap_or(false),
+            self.size.unwrap_or(true),
+            self.date.unwrap_or(true),
+            self.seeders.unwrap_or(true),
+            self.leechers.unwrap_or(true),
+            self.downloads.unwrap_or(true),
+        ]
+    }
+}
+
+pub fn nyaa_table(
+    items: Vec<Item>,
+    theme: &Theme,
+    sel_sort: &SelectedSort,
+    columns: &Option<NyaaColumns>,
+) -> ResultTable {
+    let raw_date_width = items
+        .iter()
+        .map(|i| i.date.to_string().len())
+        .max()
+        .unwrap_or_default() as u16;
+    let date_width = max(raw_date_width, 6);
+    let size_width = size_width(&items);
+    let cols = columns.unwrap_or_default().array();
+    // Resolution, episode and group
+    let parsed = parse_titles(&items, cols[3..=5].contains(&true));
+    let (ep_width, group_width) = parsed_widths(&parsed);
+
+    let header = ResultHeader::new([
+        ResultColumn::Normal("Cat".to_owned(), Constraint::Length(3)),
+        ResultColumn::Normal("".to_owned(), Constraint::Length(1)),
+        ResultColumn::Normal("Name".to_owned(), Constraint::Min(3)),
+        ResultColumn::Normal("Res".to_owned(), Constraint::Length(5)),
+        ResultColumn::Normal("Ep".to_owned(), Constraint::Length(ep_width)),
+        ResultColumn::Normal("Group".to_owned(), Constraint::Length(group_width)),
+        ResultColumn::Sorted("Size".to_owned(), size_width, NyaaSort::Size as u32),
+        ResultColumn::Sorted("Date".to_owned(), date_width, NyaaSort::Date as u32),
+        ResultColumn::Sorted("".to_owned(), 4, NyaaSort::Seeders as u32),
+        ResultColumn::Sorted("".to_owned(), 4, NyaaSort::Leechers as u32),
+        ResultColumn::Sorted("".to_owned(), 5, NyaaSort::Downloads as u32),
+    ]);
+    let mut binding = header.get_binding();
+    let align = [
+        Alignment::Left,
+        Alignment::Left,
+        Alignment::Left,
+        Alignment::Left,
+        Alignment::Left,
+        Alignment::Left,
+        Alignment::Right,
+        Alignment::Left,
+        Alignment::Right,
+        Alignment::Right,
+        Alignment::Left,
+    ];
+    let mut rows: Vec<ResultRow> = items
+        .into_par_iter()
+        .zip(parsed)
+        .map(|(item, parsed)| {
+            let size = item.size();
+            ResultRow::new([
+                item.icon.label.fg((item.icon.color)(theme)),
+                theme.item_status(&item),
+                theme.item_title(&item),
+                parsed.resolution.unwrap_or_default().fg(theme.fg),
+                parsed.episode.unwrap_or_default().fg(theme.fg),
+                parsed.group.unwrap_or_default().fg(theme.fg),
+                size.fg(theme.fg),
+                item.date.to_string().fg(theme.fg),
+                item.seeders.to_string().fg(theme.success),
+                item.leechers.to_string().fg(theme.error),
+                shorten_number(item.downloads).fg(theme.fg),
+            ])
+            .aligned(align)
+            .fg(theme.fg)
+        })
+        .collect();
+
+    // Leechers and downloads are hidden first on narrow terminals
+    let mut narrow = vec![
+        true, true, true, false, true, false, true, true, true, false, false,
+    ];
+    let mut headers = header.get_row(sel_sort.dir, sel_sort.sort as u32);
+
+    headers.cells = cond_vec!(cols ; headers.cells);
+    rows = rows
+        .clone()
+        .into_iter()
+        .map(|mut r| {
+            r.cells = cond_vec!(cols ; r.cells.to_owned());
+            r
+        })
+        .collect::<Vec<ResultRow>>();
+    binding = cond_vec!(cols ; binding);
+    narrow = cond_vec!(cols ; narrow);
+    ResultTable {
+        headers,
+        rows,
+        binding,
+        narrow,
+    }
+}
+
+/// What can be read from the title of each item, if any column made from it is
+/// `shown`. Parsing titles takes most of the time, so it's skipped while those
+/// columns are hidden, and spread over threads while they aren't.
+pub fn parse_titles(items: &[Item], shown: bool) -> Vec<ParsedTitle> {
+    match shown {
+        true => items.par_iter().map(Item::parsed).collect(),
+        false => vec![ParsedTitle::default(); items.len()],
+    }
+}
+
+/// Width of the size column, fitting sizes shown with more decimals
+pub fn size_width(items: &[Item]) -> u16 {
+    let raw = items
+        .iter()
+        .map(|i| i.size().chars().count())
+        .max()
+        .unwrap_or_default() as u16;
+    max(raw, 9)
+}
+
+/// Widths of the episode and group columns, fitting the longest of each. Long
+/// group names are cut off rather than squeezing the name.
+pub fn parsed_widths(parsed: &[ParsedTitle]) -> (u16, u16) {
+    let width = |f: fn(&ParsedTitle) -> &Option<String>, min: usize| {
+        parsed
+            .iter()
+            .filter_map(|p| f(p).as_ref().map(|s| s.chars().count()))
+            .max()
+            .unwrap_or_default()
+            .max(min) as u16
+    };
+    (width(|p| &p.episode, 2), width(|p| &p.group, 5).min(16))
+}
+
+/// Search any site running the nyaa codebase, using the categories of `S`.
+/// Item ids are the post id with `id_prefix` in front, e.g. `nyaa-123`.
+pub async fn search_nyaa<S: Source>(
+    client: &reqwest::Client,
+    search: &SearchQuery,
+    nyaa: NyaaConfig,
+    id_prefix: &'static str,
+    strict: bool,
+    date_format: Option<String>,
+) -> Result<SourceResponse, NyaaError> {
+    let res = match nyaa.transport() {
+        NyaaTransport::Rss => {
+            return nyaa_rss::search_rss::<S>(
+                nyaa.base_url,
+                nyaa.timeout,
+                client,
+                search,
+                id_prefix,
+                date_format,
+            )
+            .await;
+        }
+        NyaaTransport::Api => {
+            nyaa_api::search_api::<S>(
+                nyaa.base_url.clone(),
+                nyaa.timeout,
+                client,
+                search,
+                id_prefix,
+                date_format.clone(),
+            )
+            .await
+        }
+        NyaaTransport::Html => {
+            search_html::<S>(
+                client,
+                search,
+                &nyaa,
+                id_prefix,
+                strict,
+                date_format.clone(),
+            )
+            .await
+        }
+    };
+    // Only when the page couldn't be read, since status and network errors
+    // are needed to tell a missing user or an expired login
+    match res {
+        Err(e @ NyaaError::Parse(_)) if nyaa.rss_fallback => {
+            nyaa_rss::fallback_rss::<S>(
+                e,
+                nyaa.base_url,
+                nyaa.timeout,
+                client,
+                search,
+                id_prefix,
+                date_format,
+            )
+            .await
+        }
+        res => res,
+    }
+}
+
+async fn search_html<S: Source>(
+    client: &reqwest::Client,
+    search: &SearchQuery,
+    nyaa: &NyaaConfig,
+    id_prefix: &'static str,
+    strict: bool,
+    date_format: Option<String>,
+) -> Result<SourceResponse, NyaaError> {
+    let cat = search.category;
+    let filter = search.filter;
+    let page = search.page;
+    let user = search.user.to_owned().unwrap_or_default();
+    let sort = NyaaSort::from_repr(search.sort.sort)
+        .unwrap_or(NyaaSort::Date)
+        .to_url();
+
+    let base_url = add_protocol(nyaa.base_url.clone(), true);
+    let (high, low) = (cat / 10, cat % 10);
+    let query = encode(&search.query);
+    let dir = search.sort.dir.to_url();
+    let url = Url::parse(&base_url)?;
+    let mut url_query = url.clone();
+    url_query.set_query(Some(&format!(
+        "q={}&c={}_{}&f={}&p={}&s={}&o={}&u={}",
+        query, high, low, filter, page, sort, dir, user
+    )));
+
+    let mut request = client.get(url_query.to_owned());
+    if let Some(timeout) = nyaa.timeout {
+        request = request.timeout(Duration::from_secs(timeout));
+    }
+    let response = stats::send(request).await?;
+    if response.status() != StatusCode::OK {
+        // Throw error if response code is not OK
+        let code = response.status().as_u16();
+        return Err(NyaaError::Status {
+            url: url_query.to_string(),
+            code,
+        });
+    }
+    let content = response.bytes().await?;
+    let nyaa = nyaa.clone();
+    // Parsed on a thread of its own, so loading a page never holds up drawing
+    tokio::task::spawn_blocking(move || {
+        parse_html::<S>(
+            std::str::from_utf8(&content[..])?,
+            &url_query,
+            &nyaa,
+            id_prefix,
+            strict,
+            date_format,
+        )
+    })
+    .await?
+}
+
+/// Read the items of a results page of a site running the nyaa codebase,
+/// fetched from `url_query`
+pub fn parse_html<S: Source>(
+    content: &str,
+    url_query: &Url,
+    nyaa: &NyaaConfig,
+    id_prefix: &'static str,
+    strict: bool,
+    date_format: Option<String>,
+) -> Result<SourceResponse, NyaaError> {
+    let doc = Html::parse_document(content);
+
+    // Empty searches have no table either, but say so. Anything else is
+    // probably a DDoS protection page or a layout change.
+    let sels = nyaa.selectors.to_owned().unwrap_or_default();
+    let table_sel = &sel!(sels.table.as_deref().unwrap_or("table.torrent-list"))?;
+    let has_table = doc.select(table_sel).next().is_some();
+    if !has_table && !doc.root_element().inner_html().contains("No results found") {
+        return Err(NyaaError::Parse(format!(
+            "{}\nPage did not contain a results table",
+            url_query
+        )));
+    }
+
+    let item_sel = &sel!(sels
+        .item
+        .as_deref()
+        .unwrap_or("table.torrent-list > tbody > tr"))?;
+    let icon_sel = &sel!(sels.icon.as_deref().unwrap_or("td:first-of-type > a"))?;
+    let title_sel = &sel!(sels
+        .title
+        .as_deref()
+        .unwrap_or("td:nth-of-type(2) > a:last-of-type"))?;
+    let torrent_sel = &sel!(sels
+        .torrent
+        .as_deref()
+        .unwrap_or("td:nth-of-type(3) > a:nth-of-type(1)"))?;
+    let magnet_sel = &sel!(sels
+        .magnet
+        .as_deref()
+        .unwrap_or("td:nth-of-type(3) > a:nth-of-type(2)"))?;
+    let size_sel = &sel!(sels.size.as_deref().unwrap_or("td:nth-of-type(4)"))?;
+    let date_sel = &sel!(sels.date.as_deref().unwrap_or("td:nth-of-type(5)"))?;
+    let seed_sel = &sel!(sels.seeders.as_deref().unwrap_or("td:nth-of-type(6)"))?;
+    let leech_sel = &sel!(sels.leechers.as_deref().unwrap_or("td:nth-of-type(7)"))?;
+    let dl_sel = &sel!(sels.downloads.as_deref().unwrap_or("td:nth-of-type(8)"))?;
+    let pagination_sel = &sel!(sels
+        .pagination
+        .as_deref()
+        .unwrap_or(".pagination-page-info"))?;
+
+    let mut last_page = 100;
+    let mut total_results = match has_table {
+        true => None,
+        // Checked above to say "No results found"
+        false => Some(0),
+    };
+    // For searches, pagination has a description of total results found
+    if let Some(pagination) = doc.select(pagination_sel).next() {
+        // 6th word in pagination description contains total number of results
+        if let Some(num_results_str) = pagination.inner_html().split(' ').nth(5) {
+            if let Ok(num_results) = num_results_str.parse::<usize>() {
+                last_page = num_results.div_ceil(75);
+                total_results = Some(num_results);
+            }
+        }
+    }
+
+    let items: Vec<Item> = doc
+        .select(item_sel)
+        .filter_map(|e| {
+            let cat_str = attr(e, icon_sel, "href");
+            let cat_str = cat_str.split('=').next_back().unwrap_or("");
+            let cat = S::info().entry_from_str(cat_str);
+            let category = cat.id;
+            let icon = cat.icon.clone();
+
+            let torrent = attr(e, torrent_sel, "href");
+            let post_link = url_query
+                .join(&attr(e, title_sel, "href"))
+                .map(Into::into)
+                .unwrap_or("null".to_owned());
+            let id = post_link.split('/').next_back()?.parse::<usize>().ok()?;
+            let id = ItemId::new(id_prefix, id);
+            let file_name = format!("{}.torrent", id);
+
+            let size = inner(e, size_sel, "0 bytes")
+                .replace('i', "")
+                .replace("Bytes", "B");
+            let bytes = to_bytes(&size);
+
+            let date = inner(e, date_sel, "");
+            let date = ItemDate::parse_utc(&date, "%Y-%m-%d %H:%M", date_format.to_owned());
+
+            let seeders = as_type(inner(e, seed_sel, "0")).unwrap_or_default();
+            let leechers = as_type(inner(e, leech_sel, "0")).unwrap_or_default();
+            let downloads = as_type(inner(e, dl_sel, "0")).unwrap_or_default();
+            let torrent_link = url_query
+                .join(&torrent)
+                .map(Into::into)
+                .unwrap_or("null".to_owned());
+
+            let trusted = e.value().classes().any(|e| e == "success");
+            let remake = e.value().classes().any(|e| e == "danger");
+            let item_type = match (trusted, remake) {
+                (true, _) => ItemType::Trusted,
+                (_, true) => ItemType::Remake,
+                _ => ItemType::None,
+            };
+
+            Some(Item {
+                id,
+                date,
+                seeders,
+                leechers,
+                downloads,
+                bytes,
+                title: attr(e, title_sel, "title"),
+                torrent_link,
+                magnet_link: attr(e, magnet_sel, "href"),
+                post_link,
+                file_name: file_name.to_owned(),
+                category,
+                icon,
+                item_type,
+                ..Default::default()
+            })
+        })
+        .collect();
+
+    let mut warnings = vec![];
+    if strict && has_table {
+        let cols = [
+            icon_sel,
+            title_sel,
+            torrent_sel,
+            magnet_sel,
+            size_sel,
+            date_sel,
+            seed_sel,
+            leech_sel,
+            dl_sel,
+        ];
+        warnings.extend(check_selectors(&doc, item_sel, &cols));
+    }
+
+    Ok(SourceResponse::Results(ResultResponse {
+        items,
+        total_results,
+        last_page,
+        warnings,
+    }))
+}
@@ -1,31 +1,19 @@
-use std::{cmp::max, error::Error, time::Duration};
-
-use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
-use ratatui::{
-    layout::{Alignment, Constraint},
-    style::{Color, Stylize as _},
-};
-use reqwest::{StatusCode, Url};
-use scraper::{Html, Selector};
+use ratatui::style::Color;
 use serde::{Deserialize, Serialize};
 use strum::{Display, FromRepr, VariantArray};
-use urlencoding::encode;
 
 use crate::{
-    cats, cond_vec,
-    results::{ResultColumn, ResultHeader, ResultResponse, ResultRow, ResultTable},
-    sel,
+    cats,
+    error::NyaaError,
+    results::ResultTable,
     sync::SearchQuery,
     theme::Theme,
-    util::{
-        conv::{shorten_number, to_bytes},
-        html::{as_type, attr, inner},
-    },
     widget::sort::{SelectedSort, SortDir},
 };
 
 use super::{
-    add_protocol, nyaa_rss, Item, ItemType, Source, SourceConfig, SourceInfo, SourceResponse,
+    nyaa_engine::{nyaa_table, search_nyaa, NyaaColumns, NyaaSelectors},
+    Item, RequestHeaders, Source, SourceCapabilities, SourceConfig, SourceInfo, SourceResponse,
 };
 
 #[derive(Serialize, Deserialize, Clone, Copy, Default)]
@@ -108,34 +96,17 @@ pub struct NyaaConfig {
     pub default_filter: NyaaFilter,
     pub default_category: String,
     pub default_search: String,
+    pub transport: NyaaTransport,
+    /// Same as `transport = "rss"`
     pub rss: bool,
+    pub rss_fallback: bool,
     pub timeout: Option<u64>,
+    /// Where to download torrents from, with `{id}` replaced by the post
+    /// number. Used instead of the links on the page.
+    pub download_url_template: Option<String>,
     pub columns: Option<NyaaColumns>,
-}
-
-#[derive(Clone, Copy, Serialize, Deserialize, Default)]
-pub struct NyaaColumns {
-    category: Option<bool>,
-    title: Option<bool>,
-    size: Option<bool>,
-    date: Option<bool>,
-    seeders: Option<bool>,
-    leechers: Option<bool>,
-    downloads: Option<bool>,
-}
-
-impl NyaaColumns {
-    fn array(self) -> [bool; 7] {
-        [
-            self.category.unwrap_or(true),
-            self.title.unwrap_or(true),
-            self.size.unwrap_or(true),
-            self.date.unwrap_or(true),
-            self.seeders.unwrap_or(true),
-            self.leechers.unwrap_or(true),
-            self.downloads.unwrap_or(true),
-        ]
-    }
+    pub selectors: Option<NyaaSelectors>,
+    pub headers: Option<RequestHeaders>,
 }
 
 impl Default for NyaaConfig {
@@ -147,13 +118,44 @@ impl Default for NyaaConfig {
             default_filter: NyaaFilter::NoFilter,
             default_category: "AllCategories".to_owned(),
             default_search: Default::default(),
+            transport: NyaaTransport::Html,
             rss: false,
+            rss_fallback: true,
             timeout: None,
+            download_url_template: None,
             columns: None,
+            selectors: None,
+            headers: None,
+        }
+    }
+}
+
+impl NyaaConfig {
+    pub fn transport(&self) -> NyaaTransport {
+        match self.rss {
+            true => NyaaTransport::Rss,
+            false => self.transport,
         }
     }
 }
 
+/// How results are loaded from a site running the nyaa codebase
+#[derive(Serialize, Deserialize, Display, Clone, Copy, Default, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum NyaaTransport {
+    /// Scraped from the search page
+    #[default]
+    #[strum(serialize = "HTML")]
+    Html,
+    /// Read from the RSS feed, which has no pages of its own
+    #[strum(serialize = "RSS")]
+    Rss,
+    /// Read from a JSON API at `/api/search`, which some self-hosted instances
+    /// have
+    #[strum(serialize = "API")]
+    Api,
+}
+
 #[derive(Serialize, Deserialize, Display, Clone, Copy, VariantArray, PartialEq, Eq, FromRepr)]
 #[repr(usize)]
 pub enum NyaaSort {
@@ -196,257 +198,31 @@ pub enum NyaaFilter {
 
 pub struct NyaaHtmlSource;
 
-pub fn nyaa_table(
-    items: Vec<Item>,
-    theme: &Theme,
-    sel_sort: &SelectedSort,
-    columns: &Option<NyaaColumns>,
-) -> ResultTable {
-    let raw_date_width = items.iter().map(|i| i.date.len()).max().unwrap_or_default() as u16;
-    let date_width = max(raw_date_width, 6);
-
-    let header = ResultHeader::new([
-        ResultColumn::Normal("Cat".to_owned(), Constraint::Length(3)),
-        ResultColumn::Normal("Name".to_owned(), Constraint::Min(3)),
-        ResultColumn::Sorted("Size".to_owned(), 9, NyaaSort::Size as u32),
-        ResultColumn::Sorted("Date".to_owned(), date_width, NyaaSort::Date as u32),
-        ResultColumn::Sorted("".to_owned(), 4, NyaaSort::Seeders as u32),
-        ResultColumn::Sorted("".to_owned(), 4, NyaaSort::Leechers as u32),
-        ResultColumn::Sorted("".to_owned(), 5, NyaaSort::Downloads as u32),
-    ]);
-    let mut binding = header.get_binding();
-    let align = [
-        Alignment::Left,
-        Alignment::Left,
-        Alignment::Right,
-        Alignment::Left,
-        Alignment::Right,
-        Alignment::Right,
-        Alignment::Left,
-    ];
-    let mut rows: Vec<ResultRow> = items
-        .into_iter()
-        .map(|item| {
-            ResultRow::new([
-                item.icon.label.fg((item.icon.color)(theme)),
-                item.title.fg(match item.item_type {
-                    ItemType::Trusted => theme.success,
-                    ItemType::Remake => theme.error,
-                    ItemType::None => theme.fg,
-                }),
-                item.size.fg(theme.fg),
-                item.date.fg(theme.fg),
-                item.seeders.to_string().fg(theme.success),
-                item.leechers.to_string().fg(theme.error),
-                shorten_number(item.downloads).fg(theme.fg),
-            ])
-            .aligned(align)
-            .fg(theme.fg)
-        })
-        .collect();
-
-    let mut headers = header.get_row(sel_sort.dir, sel_sort.sort as u32);
-    if let Some(columns) = columns {
-        let cols = columns.array();
-
-        headers.cells = cond_vec!(cols ; headers.cells);
-        rows = rows
-            .clone()
-            .into_iter()
-            .map(|mut r| {
-                r.cells = cond_vec!(cols ; r.cells.to_owned());
-                r
-            })
-            .collect::<Vec<ResultRow>>();
-        binding = cond_vec!(cols ; binding);
-    }
-    ResultTable {
-        headers,
-        rows,
-        binding,
-    }
-}
-
 impl Source for NyaaHtmlSource {
     async fn search(
         client: &reqwest::Client,
         search: &SearchQuery,
         config: &SourceConfig,
         date_format: Option<String>,
-    ) -> Result<SourceResponse, Box<dyn Error + Send + Sync>> {
+    ) -> Result<SourceResponse, NyaaError> {
         let nyaa = config.nyaa.to_owned().unwrap_or_default();
-        if nyaa.rss {
-            return nyaa_rss::search_rss::<Self>(
-                nyaa.base_url,
-                nyaa.timeout,
-                client,
-                search,
-                date_format,
-            )
-            .await;
-        }
-        let cat = search.category;
-        let filter = search.filter;
-        let page = search.page;
-        let user = search.user.to_owned().unwrap_or_default();
-        let sort = NyaaSort::from_repr(search.sort.sort)
-            .unwrap_or(NyaaSort::Date)
-            .to_url();
-
-        let base_url = add_protocol(nyaa.base_url, true);
-        // let base_url = add_protocol(ctx.config.base_url.clone(), true);
-        let (high, low) = (cat / 10, cat % 10);
-        let query = encode(&search.query);
-        let dir = search.sort.dir.to_url();
-        let url = Url::parse(&base_url)?;
-        let mut url_query = url.clone();
-        url_query.set_query(Some(&format!(
-            "q={}&c={}_{}&f={}&p={}&s={}&o={}&u={}",
-            query, high, low, filter, page, sort, dir, user
-        )));
-
-        let mut request = client.get(url_query.to_owned());
-        if let Some(timeout) = nyaa.timeout {
-            request = request.timeout(Duration::from_secs(timeout));
-        }
-        let response = request.send().await?;
-        if response.status() != StatusCode::OK {
-            // Throw error if response code is not OK
-            let code = response.status().as_u16();
-            return Err(format!("{}\nInvalid response code: {}", url_query, code).into());
-        }
-        let content = response.bytes().await?;
-        let doc = Html::parse_document(std::str::from_utf8(&content[..])?);
-
-        // let item_sel = &Selector::parse("table.torrent-list > tbody > tr")?;
-        let item_sel = &sel!("table.torrent-list > tbody > tr")?;
-        let icon_sel = &sel!("td:first-of-type > a")?;
-        let title_sel = &sel!("td:nth-of-type(2) > a:last-of-type")?;
-        let torrent_sel = &sel!("td:nth-of-type(3) > a:nth-of-type(1)")?;
-        let magnet_sel = &sel!("td:nth-of-type(3) > a:nth-of-type(2)")?;
-        let size_sel = &sel!("td:nth-of-type(4)")?;
-        let date_sel = &sel!("td:nth-of-type(5)").unwrap();
-        let seed_sel = &sel!("td:nth-of-type(6)")?;
-        let leech_sel = &sel!("td:nth-of-type(7)")?;
-        let dl_sel = &sel!("td:nth-of-type(8)")?;
-        let pagination_sel = &sel!(".pagination-page-info")?;
-
-        let mut last_page = 100;
-        let mut total_results = 7500;
-        // For searches, pagination has a description of total results found
-        if let Some(pagination) = doc.select(pagination_sel).next() {
-            // 6th word in pagination description contains total number of results
-            if let Some(num_results_str) = pagination.inner_html().split(' ').nth(5) {
-                if let Ok(num_results) = num_results_str.parse::<usize>() {
-                    last_page = (num_results + 74) / 75;
-                    total_results = num_results;
-                }
-            }
-        }
-
-        let items: Vec<Item> = doc
-            .select(item_sel)
-            .filter_map(|e| {
-                let cat_str = attr(e, icon_sel, "href");
-                let cat_str = cat_str.split('=').last().unwrap_or("");
-                let cat = Self::info().entry_from_str(cat_str);
-                let category = cat.id;
-                let icon = cat.icon.clone();
-
-                let torrent = attr(e, torrent_sel, "href");
-                let id = torrent
-                    .split('/')
-                    .last()?
-                    .split('.')
-                    .next()?
-                    .parse::<usize>()
-                    .ok()?;
-                let id = format!("nyaa-{}", id);
-                let file_name = format!("{}.torrent", id);
-
-                let size = inner(e, size_sel, "0 bytes")
-                    .replace('i', "")
-                    .replace("Bytes", "B");
-                let bytes = to_bytes(&size);
-
-                let mut date = inner(e, date_sel, "");
-                if let Some(date_format) = date_format.to_owned() {
-                    let naive =
-                        NaiveDateTime::parse_from_str(&date, "%Y-%m-%d %H:%M").unwrap_or_default();
-                    let date_time: DateTime<Local> = Local.from_utc_datetime(&naive);
-                    date = date_time.format(&date_format).to_string();
-                }
-
-                let seeders = as_type(inner(e, seed_sel, "0")).unwrap_or_default();
-                let leechers = as_type(inner(e, leech_sel, "0")).unwrap_or_default();
-                let downloads = as_type(inner(e, dl_sel, "0")).unwrap_or_default();
-                let torrent_link = url
-                    .join(&torrent)
-                    .map(Into::into)
-                    .unwrap_or("null".to_owned());
-                let post_link = url
-                    .join(&attr(e, title_sel, "href"))
-                    .map(Into::into)
-                    .unwrap_or("null".to_owned());
-
-                let trusted = e.value().classes().any(|e| e == "success");
-                let remake = e.value().classes().any(|e| e == "danger");
-                let item_type = match (trusted, remake) {
-                    (true, _) => ItemType::Trusted,
-                    (_, true) => ItemType::Remake,
-                    _ => ItemType::None,
-                };
-
-                Some(Item {
-                    id,
-                    date,
-                    seeders,
-                    leechers,
-                    downloads,
-                    size,
-                    bytes,
-                    title: attr(e, title_sel, "title"),
-                    torrent_link,
-                    magnet_link: attr(e, magnet_sel, "href"),
-                    post_link,
-                    file_name: file_name.to_owned(),
-                    category,
-                    icon,
-                    item_type,
-                    ..Default::default()
-                })
-            })
-            .collect();
-
-        Ok(SourceResponse::Results(ResultResponse {
-            items,
-            total_results,
-            last_page,
-        }))
+        search_nyaa::<Self>(client, search, nyaa, "nyaa", config.strict, date_format).await
     }
     async fn sort(
         client: &reqwest::Client,
         search: &SearchQuery,
         config: &SourceConfig,
         date_format: Option<String>,
-    ) -> Result<SourceResponse, Box<dyn Error + Send + Sync>> {
-        let nyaa = config.nyaa.to_owned().unwrap_or_default();
-        let sort = search.sort;
-        let mut res = NyaaHtmlSource::search(client, search, config, date_format).await;
-
-        if nyaa.rss {
-            if let Ok(SourceResponse::Results(res)) = &mut res {
-                nyaa_rss::sort_items(&mut res.items, sort);
-            }
-        }
-        res
+    ) -> Result<SourceResponse, NyaaError> {
+        // In RSS mode, the feed is sorted locally by `search_rss`
+        NyaaHtmlSource::search(client, search, config, date_format).await
     }
     async fn filter(
         client: &reqwest::Client,
         search: &SearchQuery,
         config: &SourceConfig,
         date_format: Option<String>,
-    ) -> Result<SourceResponse, Box<dyn Error + Send + Sync>> {
+    ) -> Result<SourceResponse, NyaaError> {
         NyaaHtmlSource::search(client, search, config, date_format).await
     }
     async fn categorize(
@@ -454,7 +230,7 @@ impl Source for NyaaHtmlSource {
         search: &SearchQuery,
         config: &SourceConfig,
         date_format: Option<String>,
-    ) -> Result<SourceResponse, Box<dyn Error + Send + Sync>> {
+    ) -> Result<SourceResponse, NyaaError> {
         NyaaHtmlSource::search(client, search, config, date_format).await
     }
     async fn solve(
@@ -463,7 +239,7 @@ impl Source for NyaaHtmlSource {
         search: &SearchQuery,
         config: &SourceConfig,
         date_format: Option<String>,
-    ) -> Result<SourceResponse, Box<dyn Error + Send + Sync>> {
+    ) -> Result<SourceResponse, NyaaError> {
         NyaaHtmlSource::search(client, search, config, date_format).await
     }
 
@@ -515,6 +291,7 @@ impl Source for NyaaHtmlSource {
                 .map(ToString::to_string)
                 .collect(),
             sorts: NyaaSort::VARIANTS.iter().map(ToString::to_string).collect(),
+            capabilities: SourceCapabilities::nyaa(),
         }
     }
 
@@ -1,4 +1,4 @@
-use std::{cmp::Ordering, collections::BTreeMap, error::Error, str::FromStr, time::Duration};
+use std::{cmp::Ordering, collections::BTreeMap, str::FromStr, time::Duration};
 
 use chrono::{DateTime, Local};
 use reqwest::{StatusCode, Url};
@@ -6,16 +6,22 @@ use rss::{extension::Extension, Channel};
 use urlencoding::encode;
 
 use crate::{
+    error::NyaaError,
     results::ResultResponse,
     sync::SearchQuery,
-    util::conv::to_bytes,
+    util::{conv::to_bytes, stats},
     widget::sort::{SelectedSort, SortDir},
 };
 
-use super::{add_protocol, nyaa_html::NyaaSort, Item, ItemType, Source, SourceResponse};
+use super::{
+    add_protocol, nyaa_html::NyaaSort, Item, ItemDate, ItemId, ItemType, Source, SourceResponse,
+};
 
 type ExtensionMap = BTreeMap<String, Vec<Extension>>;
 
+// The feed has no pages of its own, so it is split into pages of this size
+pub static RSS_PAGE_SIZE: usize = 25;
+
 pub fn get_ext_value<T: Default + FromStr>(ext_map: &ExtensionMap, key: &str) -> T {
     ext_map
         .get(key)
@@ -25,13 +31,15 @@ pub fn get_ext_value<T: Default + FromStr>(ext_map: &ExtensionMap, key: &str) ->
         .unwrap_or_default()
 }
 
+/// Sort items in descending order, or ascending if `sort.dir` is `Asc`
 pub fn sort_items(items: &mut [Item], sort: SelectedSort) {
     let f: fn(&Item, &Item) -> Ordering = match NyaaSort::from_repr(sort.sort) {
         Some(NyaaSort::Downloads) => |a, b| b.downloads.cmp(&a.downloads),
         Some(NyaaSort::Seeders) => |a, b| b.seeders.cmp(&a.seeders),
         Some(NyaaSort::Leechers) => |a, b| b.leechers.cmp(&a.leechers),
         Some(NyaaSort::Size) => |a, b| b.bytes.cmp(&a.bytes),
-        _ => |a, b| a.id.cmp(&b.id),
+        // Ids are given out in order, so break ties with the newer post
+        _ => |a, b| b.date.time.cmp(&a.date.time).then(b.id.num.cmp(&a.id.num)),
     };
     items.sort_by(f);
     if sort.dir == SortDir::Asc {
@@ -44,13 +52,13 @@ pub async fn search_rss<S: Source>(
     timeout: Option<u64>,
     client: &reqwest::Client,
     search: &SearchQuery,
+    id_prefix: &'static str,
     date_format: Option<String>,
-) -> Result<SourceResponse, Box<dyn Error + Send + Sync>> {
+) -> Result<SourceResponse, NyaaError> {
     let query = search.query.to_owned();
     let cat = search.category;
     let filter = search.filter;
     let user = search.user.to_owned().unwrap_or_default();
-    let last_page = 1;
     let (high, low) = (cat / 10, cat % 10);
     let query = encode(&query);
     let base_url = add_protocol(base_url, true);
@@ -67,11 +75,14 @@ pub async fn search_rss<S: Source>(
     if let Some(timeout) = timeout {
         request = request.timeout(Duration::from_secs(timeout));
     }
-    let response = request.send().await?;
+    let response = stats::send(request).await?;
     let code = response.status().as_u16();
     if code != StatusCode::OK {
         // Throw error if response code is not OK
-        return Err(format!("{}\nInvalid response code: {}", url, code).into());
+        return Err(NyaaError::Status {
+            url: url.to_string(),
+            code,
+        });
     }
 
     let bytes = response.bytes().await?;
@@ -113,13 +124,12 @@ pub async fn search_rss<S: Source>(
                 .unwrap_or("%Y-%m-%d %H:%M".to_owned());
 
             Some(Item {
-                id: format!("nyaa-{}", id_usize),
-                date: date.format(&date_format).to_string(),
+                id: ItemId::new(id_prefix, id_usize),
+                date: ItemDate::new(date, date_format),
                 seeders: get_ext_value(ext, "seeders"),
                 leechers: get_ext_value(ext, "leechers"),
                 downloads: get_ext_value(ext, "downloads"),
                 bytes: to_bytes(&size),
-                size,
                 title: item.title().unwrap_or("???").to_owned(),
                 torrent_link,
                 magnet_link: item.link().unwrap_or("???").to_owned(),
@@ -132,12 +142,26 @@ pub async fn search_rss<S: Source>(
             })
         })
         .collect();
-    let total_results = items.len();
+    let num_items = items.len();
     sort_items(&mut items, search.sort);
+
+    let last_page = num_items.div_ceil(RSS_PAGE_SIZE).max(1);
+    let page = search.page.clamp(1, last_page);
+    let items = items
+        .into_iter()
+        .skip((page - 1) * RSS_PAGE_SIZE)
+        .take(RSS_PAGE_SIZE)
+        .collect();
+    // Entries which couldn't be read are not the same as an empty feed
+    let total_results = match num_items == 0 && !channel.items.is_empty() {
+        true => None,
+        false => Some(num_items),
+    };
     Ok(SourceResponse::Results(ResultResponse {
         items,
         last_page,
         total_results,
+        ..Default::default()
     }))
     // Ok(items)
     // Ok(nyaa_table(
@@ -149,3 +173,26 @@ pub async fn search_rss<S: Source>(
     //     total_results,
     // ))
 }
+
+/// Retry a failed HTML search through the RSS feed. If the feed also fails,
+/// the original error is returned.
+pub async fn fallback_rss<S: Source>(
+    error: NyaaError,
+    base_url: String,
+    timeout: Option<u64>,
+    client: &reqwest::Client,
+    search: &SearchQuery,
+    id_prefix: &'static str,
+    date_format: Option<String>,
+) -> Result<SourceResponse, NyaaError> {
+    match search_rss::<S>(base_url, timeout, client, search, id_prefix, date_format).await {
+        Ok(SourceResponse::Results(mut res)) => {
+            res.warnings.push(format!(
+                "Using RSS since the search page failed to load:\n{}",
+                error
+            ));
+            Ok(SourceResponse::Results(res))
+        }
+        _ => Err(error),
+    }
+}
@@ -0,0 +1,144 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use reqwest::{StatusCode, Url};
+use scraper::{Html, Selector};
+use tokio::{sync::Semaphore, task::JoinSet};
+
+use crate::{client::progress::ResolveGuard, error::NyaaError, sel, util::stats};
+
+use super::{Item, ItemId, SourceConfig, Sources};
+
+// Most post pages fetched at once while resolving a batch
+static MAX_CONCURRENT_RESOLVES: usize = 4;
+
+/// Links found on post pages, so each post is only fetched once
+static CACHE: Mutex<Option<HashMap<ItemId, Links>>> = Mutex::new(None);
+
+#[derive(Clone, Default, Debug, PartialEq, Eq)]
+pub struct Links {
+    pub torrent: Option<String>,
+    pub magnet: Option<String>,
+}
+
+/// Whether the item has neither a torrent nor a magnet link, as with sources
+/// whose list pages leave them out
+pub fn needs_resolve(item: &Item) -> bool {
+    let missing = |link: &str| link.is_empty() || link == "null";
+    missing(&item.torrent_link) && missing(&item.magnet_link)
+}
+
+/// The first magnet link and torrent link on a post page. Relative links are
+/// made absolute with `post_url`.
+pub fn links_from_post(html: &str, post_url: &Url) -> Result<Links, NyaaError> {
+    let doc = Html::parse_document(html);
+    let magnet_sel = &sel!("a[href^=\"magnet:\"]")?;
+    let torrent_sel = &sel!("a[href$=\".torrent\"], a[href*=\"/download/\"]")?;
+    let href = |sel: &Selector| {
+        doc.select(sel)
+            .find_map(|e| e.value().attr("href"))
+            .and_then(|href| post_url.join(href).ok())
+            .map(String::from)
+    };
+    Ok(Links {
+        torrent: href(torrent_sel),
+        magnet: href(magnet_sel),
+    })
+}
+
+/// Links found for `id` before, counted as a cache hit if there are any
+fn cached(id: &ItemId) -> Option<Links> {
+    let links = CACHE.lock().ok()?.as_ref()?.get(id).cloned()?;
+    stats::record_cache_hit();
+    Some(links)
+}
+
+fn apply(mut item: Item, links: Links) -> Item {
+    if let Some(torrent) = links.torrent {
+        item.torrent_link = torrent;
+    }
+    if let Some(magnet) = links.magnet {
+        item.magnet_link = magnet;
+    }
+    item
+}
+
+/// Fill in the links of an item from its post page, if it has none. Used by
+/// sources which don't resolve items their own way.
+pub async fn from_post(client: &reqwest::Client, item: Item) -> Result<Item, NyaaError> {
+    if !needs_resolve(&item) {
+        return Ok(item);
+    }
+    if let Some(links) = cached(&item.id) {
+        return Ok(apply(item, links));
+    }
+    let post_url = Url::parse(&item.post_link)?;
+    let response = stats::send(client.get(post_url.clone())).await?;
+    if response.status() != StatusCode::OK {
+        return Err(NyaaError::Status {
+            url: item.post_link,
+            code: response.status().as_u16(),
+        });
+    }
+    let links = links_from_post(&response.text().await?, &post_url)?;
+    if links.torrent.is_none() && links.magnet.is_none() {
+        return Err(NyaaError::Parse(format!(
+            "No torrent or magnet link on the page of \"{}\"",
+            item.title
+        )));
+    }
+    if let Ok(mut cache) = CACHE.lock() {
+        cache
+            .get_or_insert_with(HashMap::new)
+            .insert(item.id.clone(), links.clone());
+    }
+    Ok(apply(item, links))
+}
+
+/// Resolve every item that needs it before downloading, a few at a time. The
+/// items which couldn't be resolved are returned with why, and left out of the
+/// download.
+pub async fn resolve_all(
+    src: Sources,
+    client: &reqwest::Client,
+    items: Vec<Item>,
+    config: &SourceConfig,
+) -> (Vec<Item>, Vec<(Item, NyaaError)>) {
+    let (pending, mut resolved): (Vec<Item>, Vec<Item>) = items
+        .into_iter()
+        .map(|i| match needs_resolve(&i) {
+            true => match cached(&i.id) {
+                Some(links) => apply(i, links),
+                None => i,
+            },
+            false => i,
+        })
+        .partition(needs_resolve);
+    if pending.is_empty() {
+        return (resolved, vec![]);
+    }
+
+    let guard = ResolveGuard::new(pending.len());
+    let permits = Arc::new(Semaphore::new(MAX_CONCURRENT_RESOLVES));
+    let mut set = JoinSet::new();
+    for item in pending {
+        let (client, config, permits) = (client.clone(), config.clone(), permits.clone());
+        set.spawn(async move {
+            let _permit = permits.acquire().await;
+            let res = src.resolve(&client, item.clone(), &config).await;
+            (item, res)
+        });
+    }
+    let mut failed = vec![];
+    while let Some(res) = set.join_next().await {
+        guard.finish();
+        match res {
+            Ok((_, Ok(item))) => resolved.push(item),
+            Ok((item, Err(e))) => failed.push((item, e)),
+            Err(_) => {}
+        }
+    }
+    (resolved, failed)
+}
@@ -1,30 +1,13 @@
-use std::{error::Error, time::Duration};
-
-use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
 use ratatui::style::Color;
-use reqwest::{StatusCode, Url};
-use scraper::{Html, Selector};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use strum::VariantArray as _;
-use urlencoding::encode;
 
-use crate::{
-    cats,
-    results::ResultResponse,
-    sel,
-    sync::SearchQuery,
-    theme::Theme,
-    util::{
-        conv::to_bytes,
-        html::{attr, inner},
-    },
-    widget::sort::{SelectedSort, SortDir},
-};
+use crate::{cats, error::NyaaError, sync::SearchQuery, theme::Theme, widget::sort::SelectedSort};
 
 use super::{
-    add_protocol,
-    nyaa_html::{nyaa_table, NyaaColumns, NyaaFilter, NyaaSort},
-    nyaa_rss, Item, ItemType, ResultTable, Source, SourceConfig, SourceInfo, SourceResponse,
+    nyaa_engine::{nyaa_table, search_nyaa},
+    nyaa_html::{NyaaConfig, NyaaFilter, NyaaSort},
+    Item, ResultTable, Source, SourceCapabilities, SourceConfig, SourceInfo, SourceResponse,
 };
 
 #[derive(Serialize, Deserialize, Clone, Copy, Default)]
@@ -68,33 +51,33 @@ impl Default for SukebeiCategoryTheme {
     }
 }
 
-#[derive(Serialize, Deserialize, Clone)]
-#[serde(default)]
-pub struct SukebeiNyaaConfig {
-    pub base_url: String,
-    pub default_sort: NyaaSort,
-    pub default_sort_dir: SortDir,
-    pub default_filter: NyaaFilter,
-    pub default_category: String,
-    pub default_search: String,
-    pub rss: bool,
-    pub timeout: Option<u64>,
-    pub columns: Option<NyaaColumns>,
-}
+/// Takes the same options as `[source.nyaa]`, only searching sukebei unless
+/// `base_url` is set
+#[derive(Serialize, Clone)]
+#[serde(transparent)]
+pub struct SukebeiNyaaConfig(pub NyaaConfig);
 
 impl Default for SukebeiNyaaConfig {
     fn default() -> Self {
-        Self {
+        Self(NyaaConfig {
             base_url: "https://sukebei.nyaa.si/".to_owned(),
-            default_sort: NyaaSort::Date,
-            default_sort_dir: SortDir::Desc,
-            default_filter: NyaaFilter::NoFilter,
-            default_category: "AllCategories".to_owned(),
-            default_search: Default::default(),
-            rss: false,
-            timeout: None,
-            columns: None,
+            ..Default::default()
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for SukebeiNyaaConfig {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // Missing options default to nyaa's, which only differ in the url
+        #[derive(Deserialize)]
+        struct Fields {
+            base_url: Option<String>,
+            #[serde(flatten)]
+            nyaa: NyaaConfig,
         }
+        let Fields { base_url, mut nyaa } = Fields::deserialize(deserializer)?;
+        nyaa.base_url = base_url.unwrap_or_else(|| Self::default().0.base_url);
+        Ok(Self(nyaa))
     }
 }
 
@@ -106,7 +89,7 @@ impl Source for SukebeiHtmlSource {
         search: &SearchQuery,
         config: &SourceConfig,
         date_format: Option<String>,
-    ) -> Result<SourceResponse, Box<dyn Error + Send + Sync>> {
+    ) -> Result<SourceResponse, NyaaError> {
         SukebeiHtmlSource::search(client, search, config, date_format).await
     }
     async fn categorize(
@@ -114,7 +97,7 @@ impl Source for SukebeiHtmlSource {
         search: &SearchQuery,
         config: &SourceConfig,
         date_format: Option<String>,
-    ) -> Result<SourceResponse, Box<dyn Error + Send + Sync>> {
+    ) -> Result<SourceResponse, NyaaError> {
         SukebeiHtmlSource::search(client, search, config, date_format).await
     }
     async fn sort(
@@ -122,17 +105,9 @@ impl Source for SukebeiHtmlSource {
         search: &SearchQuery,
         config: &SourceConfig,
         date_format: Option<String>,
-    ) -> Result<SourceResponse, Box<dyn Error + Send + Sync>> {
-        let sukebei = config.sukebei.to_owned().unwrap_or_default();
-        let sort = search.sort;
-        let mut res = SukebeiHtmlSource::search(client, search, config, date_format).await;
-
-        if sukebei.rss {
-            if let Ok(SourceResponse::Results(res)) = &mut res {
-                nyaa_rss::sort_items(&mut res.items, sort);
-            }
-        }
-        res
+    ) -> Result<SourceResponse, NyaaError> {
+        // In RSS mode, the feed is sorted locally by `search_rss`
+        SukebeiHtmlSource::search(client, search, config, date_format).await
     }
 
     async fn search(
@@ -140,155 +115,17 @@ impl Source for SukebeiHtmlSource {
         search: &SearchQuery,
         config: &SourceConfig,
         date_format: Option<String>,
-    ) -> Result<SourceResponse, Box<dyn Error + Send + Sync>> {
+    ) -> Result<SourceResponse, NyaaError> {
         let sukebei = config.sukebei.to_owned().unwrap_or_default();
-        if sukebei.rss {
-            return nyaa_rss::search_rss::<Self>(
-                sukebei.base_url,
-                sukebei.timeout,
-                client,
-                search,
-                date_format,
-            )
-            .await;
-        }
-        let cat = search.category;
-        let filter = search.filter;
-        let page = search.page;
-        let user = search.user.to_owned().unwrap_or_default();
-        let sort = NyaaSort::from_repr(search.sort.sort)
-            .unwrap_or(NyaaSort::Date)
-            .to_url();
-
-        let base_url = add_protocol(sukebei.base_url, true);
-        let (high, low) = (cat / 10, cat % 10);
-        let query = encode(&search.query);
-        let dir = search.sort.dir.to_url();
-        let url = Url::parse(&base_url)?;
-        let mut url_query = url.clone();
-        url_query.set_query(Some(&format!(
-            "q={}&c={}_{}&f={}&p={}&s={}&o={}&u={}",
-            query, high, low, filter, page, sort, dir, user
-        )));
-
-        let mut request = client.get(url_query.to_owned());
-        if let Some(timeout) = sukebei.timeout {
-            request = request.timeout(Duration::from_secs(timeout));
-        }
-        let response = request.send().await?;
-        if response.status() != StatusCode::OK {
-            // Throw error if response code is not OK
-            let code = response.status().as_u16();
-            return Err(format!("{}\nInvalid response code: {}", url_query, code).into());
-        }
-        let content = response.bytes().await?;
-        let doc = Html::parse_document(std::str::from_utf8(&content[..])?);
-
-        let item_sel = &sel!("table.torrent-list > tbody > tr")?;
-        let icon_sel = &sel!("td:first-of-type > a")?;
-        let title_sel = &sel!("td:nth-of-type(2) > a:last-of-type")?;
-        let torrent_sel = &sel!("td:nth-of-type(3) > a:nth-of-type(1)")?;
-        let magnet_sel = &sel!("td:nth-of-type(3) > a:nth-of-type(2)")?;
-        let size_sel = &sel!("td:nth-of-type(4)")?;
-        let date_sel = &sel!("td:nth-of-type(5)").unwrap();
-        let seed_sel = &sel!("td:nth-of-type(6)")?;
-        let leech_sel = &sel!("td:nth-of-type(7)")?;
-        let dl_sel = &sel!("td:nth-of-type(8)")?;
-        let pagination_sel = &sel!(".pagination-page-info")?;
-
-        let mut last_page = 100;
-        let mut total_results = 7500;
-        // For searches, pagination has a description of total results found
-        if let Some(pagination) = doc.select(pagination_sel).next() {
-            // 6th word in pagination description contains total number of results
-            if let Some(num_results_str) = pagination.inner_html().split(' ').nth(5) {
-                if let Ok(num_results) = num_results_str.parse::<usize>() {
-                    last_page = (num_results + 74) / 75;
-                    total_results = num_results;
-                }
-            }
-        }
-
-        let items: Vec<Item> = doc
-            .select(item_sel)
-            .filter_map(|e| {
-                let cat_str = attr(e, icon_sel, "href");
-                let cat_str = cat_str.split('=').last().unwrap_or("");
-                let cat = Self::info().entry_from_str(cat_str);
-                let category = cat.id;
-                let icon = cat.icon.clone();
-
-                let torrent = attr(e, torrent_sel, "href");
-                let post_link = url
-                    .join(&attr(e, title_sel, "href"))
-                    .map(Into::into)
-                    .unwrap_or("null".to_owned());
-                let id = post_link.split('/').last()?.parse::<usize>().ok()?;
-                let id = format!("sukebei-{}", id);
-                let file_name = format!("{}.torrent", id);
-
-                let size = inner(e, size_sel, "0 B")
-                    .replace('i', "")
-                    .replace("Bytes", "B");
-                let bytes = to_bytes(&size);
-
-                let mut date = inner(e, date_sel, "");
-                if let Some(date_format) = date_format.to_owned() {
-                    let naive =
-                        NaiveDateTime::parse_from_str(&date, "%Y-%m-%d %H:%M").unwrap_or_default();
-                    let date_time: DateTime<Local> = Local.from_utc_datetime(&naive);
-                    date = date_time.format(&date_format).to_string();
-                }
-
-                let seeders = inner(e, seed_sel, "0").parse().unwrap_or(0);
-                let leechers = inner(e, leech_sel, "0").parse().unwrap_or(0);
-                let downloads = inner(e, dl_sel, "0").parse().unwrap_or(0);
-                let torrent_link = url
-                    .join(&torrent)
-                    .map(Into::into)
-                    .unwrap_or("null".to_owned());
-
-                let trusted = e.value().classes().any(|e| e == "success");
-                let remake = e.value().classes().any(|e| e == "danger");
-                let item_type = match (trusted, remake) {
-                    (true, _) => ItemType::Trusted,
-                    (_, true) => ItemType::Remake,
-                    _ => ItemType::None,
-                };
-
-                Some(Item {
-                    id,
-                    date,
-                    seeders,
-                    leechers,
-                    downloads,
-                    size,
-                    bytes,
-                    title: attr(e, title_sel, "title"),
-                    torrent_link,
-                    magnet_link: attr(e, magnet_sel, "href"),
-                    post_link,
-                    file_name: file_name.to_owned(),
-                    category,
-                    icon,
-                    item_type,
-                    ..Default::default()
-                })
-            })
-            .collect();
-        Ok(SourceResponse::Results(ResultResponse {
-            items,
-            last_page,
-            total_results,
-        }))
-        // Ok(nyaa_table(
-        //     items,
-        //     &theme,
-        //     &search.sort,
-        //     sukebei.columns,
-        //     last_page,
-        //     total_results,
-        // ))
+        search_nyaa::<Self>(
+            client,
+            search,
+            sukebei.0,
+            "sukebei",
+            config.strict,
+            date_format,
+        )
+        .await
     }
 
     async fn solve(
@@ -297,7 +134,7 @@ impl Source for SukebeiHtmlSource {
         search: &SearchQuery,
         config: &SourceConfig,
         date_format: Option<String>,
-    ) -> Result<SourceResponse, Box<dyn Error + Send + Sync>> {
+    ) -> Result<SourceResponse, NyaaError> {
         SukebeiHtmlSource::search(client, search, config, date_format).await
     }
 
@@ -327,6 +164,7 @@ impl Source for SukebeiHtmlSource {
                 .map(ToString::to_string)
                 .collect(),
             sorts: NyaaSort::VARIANTS.iter().map(ToString::to_string).collect(),
+            capabilities: SourceCapabilities::nyaa(),
         }
     }
 
@@ -340,7 +178,7 @@ impl Source for SukebeiHtmlSource {
         let default = cfg
             .sukebei
             .as_ref()
-            .map(|c| c.default_category.to_owned())
+            .map(|c| c.0.default_category.to_owned())
             .unwrap_or_default();
         Self::info().entry_from_cfg(&default).id
     }
@@ -349,8 +187,8 @@ impl Source for SukebeiHtmlSource {
         cfg.sukebei
             .as_ref()
             .map(|c| SelectedSort {
-                sort: c.default_sort as usize,
-                dir: c.default_sort_dir,
+                sort: c.0.default_sort as usize,
+                dir: c.0.default_sort_dir,
             })
             .unwrap_or_default()
     }
@@ -358,14 +196,14 @@ impl Source for SukebeiHtmlSource {
     fn default_filter(cfg: &SourceConfig) -> usize {
         cfg.sukebei
             .as_ref()
-            .map(|c| c.default_filter as usize)
+            .map(|c| c.0.default_filter as usize)
             .unwrap_or_default()
     }
 
     fn default_search(cfg: &SourceConfig) -> String {
         cfg.sukebei
             .as_ref()
-            .map(|c| c.default_search.to_owned())
+            .map(|c| c.0.default_search.to_owned())
             .unwrap_or_default()
     }
 
@@ -376,6 +214,6 @@ impl Source for SukebeiHtmlSource {
         theme: &Theme,
     ) -> ResultTable {
         let sukebei = config.sukebei.to_owned().unwrap_or_default();
-        nyaa_table(items.into(), theme, &search.sort, &sukebei.columns)
+        nyaa_table(items.into(), theme, &search.sort, &sukebei.0.columns)
     }
 }
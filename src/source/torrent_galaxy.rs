@@ -1,7 +1,6 @@
 use std::{
     cmp::max,
     collections::HashMap,
-    error::Error,
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
@@ -17,18 +16,28 @@ use urlencoding::encode;
 
 use crate::{
     cats, collection, cond_vec,
+    error::NyaaError,
     results::{ResultColumn, ResultHeader, ResultResponse, ResultRow, ResultTable},
     sel,
     sync::SearchQuery,
     theme::Theme,
     util::{
         conv::{shorten_number, to_bytes},
-        html::{as_type, attr, inner},
+        html::{as_type, attr, check_selectors, inner},
+        stats,
+    },
+    widget::{
+        filter::filter_bit,
+        sort::{SelectedSort, SortDir},
     },
-    widget::sort::{SelectedSort, SortDir},
 };
 
-use super::{add_protocol, Item, ItemType, Source, SourceConfig, SourceInfo, SourceResponse};
+use super::{
+    add_protocol,
+    nyaa_engine::{parse_titles, parsed_widths, size_width},
+    ColumnConfig, Item, ItemDate, ItemId, ItemType, RequestHeaders, Source, SourceCapabilities,
+    SourceConfig, SourceInfo, SourceResponse,
+};
 
 #[derive(Serialize, Deserialize, Clone, Copy, Default)]
 #[serde(default)]
@@ -171,7 +180,11 @@ pub struct TgxConfig {
     pub default_category: String,
     pub default_search: String,
     pub timeout: Option<u64>,
+    /// Where to download torrents from, with `{id}` replaced by the post
+    /// number. Used instead of the links on the page.
+    pub download_url_template: Option<String>,
     pub columns: Option<TgxColumns>,
+    pub headers: Option<RequestHeaders>,
 }
 
 impl Default for TgxConfig {
@@ -184,7 +197,9 @@ impl Default for TgxConfig {
             default_category: "AllCategories".to_owned(),
             default_search: Default::default(),
             timeout: None,
+            download_url_template: None,
             columns: None,
+            headers: None,
         }
     }
 }
@@ -201,14 +216,71 @@ pub struct TgxColumns {
     seeders: Option<bool>,
     leechers: Option<bool>,
     views: Option<bool>,
+    /// Resolution read from the title, hidden unless enabled
+    resolution: Option<bool>,
+    /// Episode read from the title, hidden unless enabled
+    episode: Option<bool>,
+    /// Release group read from the title, hidden unless enabled
+    group: Option<bool>,
+    /// ✓ for trusted and ✗ for remakes, hidden unless enabled
+    status: Option<bool>,
+}
+
+impl ColumnConfig for TgxColumns {
+    const NAMES: &'static [&'static str] = &[
+        "Category",
+        "Status",
+        "Language",
+        "Title",
+        "Resolution",
+        "Episode",
+        "Group",
+        "IMDB",
+        "Uploader",
+        "Size",
+        "Date",
+        "Seeders",
+        "Leechers",
+        "Views",
+    ];
+
+    fn shown(self) -> Vec<bool> {
+        self.array().to_vec()
+    }
+
+    fn set(&mut self, idx: usize, shown: bool) {
+        let fields = [
+            &mut self.category,
+            &mut self.status,
+            &mut self.language,
+            &mut self.title,
+            &mut self.resolution,
+            &mut self.episode,
+            &mut self.group,
+            &mut self.imdb,
+            &mut self.uploader,
+            &mut self.size,
+            &mut self.date,
+            &mut self.seeders,
+            &mut self.leechers,
+            &mut self.views,
+        ];
+        if let Some(field) = fields.into_iter().nth(idx) {
+            *field = Some(shown);
+        }
+    }
 }
 
 impl TgxColumns {
-    fn array(self) -> [bool; 10] {
+    fn array(self) -> [bool; 14] {
         [
             self.category.unwrap_or(true),
+            self.status.unwrap_or(false),
             self.language.unwrap_or(true),
             self.title.unwrap_or(true),
+            self.resolution.unwrap_or(false),
+            self.episode.unwrap_or(false),
+            self.group.unwrap_or(false),
             self.imdb.unwrap_or(true),
             self.uploader.unwrap_or(true),
             self.size.unwrap_or(true),
@@ -249,10 +321,7 @@ pub enum TgxFilter {
 
 pub struct TorrentGalaxyHtmlSource;
 
-fn get_url(
-    base_url: String,
-    search: &SearchQuery,
-) -> Result<(Url, Url), Box<dyn Error + Send + Sync>> {
+fn get_url(base_url: String, search: &SearchQuery) -> Result<(Url, Url), NyaaError> {
     let base_url = Url::parse(&add_protocol(base_url, true))?.join("torrents.php")?;
 
     let query = encode(&search.query);
@@ -266,12 +335,15 @@ fn get_url(
         _ => "",
     };
     let ord = format!("&order={}", search.sort.dir.to_url());
-    let filter = match TgxFilter::from_repr(search.filter) {
-        Some(TgxFilter::OnlineStreams) => "&filterstream=1",
-        Some(TgxFilter::ExcludeXXX) => "&nox=1",
-        Some(TgxFilter::NoWildcard) => "&nowildcard=1",
-        _ => "",
-    };
+    let filter = [
+        (TgxFilter::OnlineStreams, "&filterstream=1"),
+        (TgxFilter::ExcludeXXX, "&nox=1"),
+        (TgxFilter::NoWildcard, "&nowildcard=1"),
+    ]
+    .into_iter()
+    .filter(|(f, _)| search.filter & filter_bit(*f as usize) != 0)
+    .map(|(_, param)| param)
+    .collect::<String>();
     let cat = match search.category {
         0 => "".to_owned(),
         x => format!("&c{}=1", x),
@@ -295,22 +367,23 @@ async fn try_get_content(
     client: &reqwest::Client,
     timeout: Option<u64>,
     url: &Url,
-) -> Result<String, Box<dyn Error + Send + Sync>> {
+) -> Result<String, NyaaError> {
     let mut request = client.get(url.to_owned());
     if let Some(timeout) = timeout {
         request = request.timeout(Duration::from_secs(timeout));
     }
-    let response = request
-        .header(
-            "User-Agent",
-            "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:126.0) Gecko/20100101 Firefox/126.0",
-        )
-        .send()
-        .await?;
+    let response = stats::send(request.header(
+        "User-Agent",
+        "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:126.0) Gecko/20100101 Firefox/126.0",
+    ))
+    .await?;
     if response.status() != StatusCode::OK {
         // Throw error if response code is not OK
         let code = response.status().as_u16();
-        return Err(format!("{}\nInvalid response code: {}", url, code).into());
+        return Err(NyaaError::Status {
+            url: url.to_string(),
+            code,
+        });
     }
     Ok(response.text().await?)
 }
@@ -367,7 +440,7 @@ impl Source for TorrentGalaxyHtmlSource {
         search: &SearchQuery,
         config: &SourceConfig,
         date_format: Option<String>,
-    ) -> Result<SourceResponse, Box<dyn Error + Send + Sync>> {
+    ) -> Result<SourceResponse, NyaaError> {
         TorrentGalaxyHtmlSource::search(client, search, config, date_format).await
     }
     async fn categorize(
@@ -375,7 +448,7 @@ impl Source for TorrentGalaxyHtmlSource {
         search: &SearchQuery,
         config: &SourceConfig,
         date_format: Option<String>,
-    ) -> Result<SourceResponse, Box<dyn Error + Send + Sync>> {
+    ) -> Result<SourceResponse, NyaaError> {
         TorrentGalaxyHtmlSource::search(client, search, config, date_format).await
     }
     async fn sort(
@@ -383,7 +456,7 @@ impl Source for TorrentGalaxyHtmlSource {
         search: &SearchQuery,
         config: &SourceConfig,
         date_format: Option<String>,
-    ) -> Result<SourceResponse, Box<dyn Error + Send + Sync>> {
+    ) -> Result<SourceResponse, NyaaError> {
         TorrentGalaxyHtmlSource::search(client, search, config, date_format).await
     }
 
@@ -392,7 +465,7 @@ impl Source for TorrentGalaxyHtmlSource {
         search: &SearchQuery,
         config: &SourceConfig,
         _date_format: Option<String>,
-    ) -> Result<SourceResponse, Box<dyn Error + Send + Sync>> {
+    ) -> Result<SourceResponse, NyaaError> {
         let tgx = config.tgx.to_owned().unwrap_or_default();
         let (base_url, url) = get_url(tgx.base_url.clone(), search)?;
 
@@ -406,15 +479,18 @@ impl Source for TorrentGalaxyHtmlSource {
             let hash = "4578678889c4b42ae37b543434c81d85";
             let mut hash_url = base_url.clone().join("hub.php")?;
             hash_url.set_query(Some(&format!("a=vlad&u={}", time)));
-            client
-                .post(hash_url.clone())
+            let mut request = client.post(hash_url.clone());
+            if let Some(timeout) = tgx.timeout {
+                request = request.timeout(Duration::from_secs(timeout));
+            }
+            stats::send(request
                 .body(format!("fash={}", hash))
                 .header("Content-Type", "application/x-www-form-urlencoded")
                 .header(
                     "User-Agent",
                     "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:126.0) Gecko/20100101 Firefox/126.0",
                 )
-                .send()
+                )
                 .await?;
         }
 
@@ -433,17 +509,21 @@ impl Source for TorrentGalaxyHtmlSource {
                 if let Some(timeout) = tgx.timeout {
                     request = request.timeout(Duration::from_secs(timeout));
                 }
-                let response = request
+                let response = stats::send(request
                     .header("User-Agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:126.0) Gecko/20100101 Firefox/126.0")
-                    .send()
+                    )
                     .await?;
                 let bytes = response.bytes().await?;
                 let mut picker = ratatui_image::picker::Picker::new((1, 2));
                 picker.protocol_type = ratatui_image::picker::ProtocolType::Halfblocks;
-                let dyn_image = image::load_from_memory(&bytes[..])?;
+                let dyn_image = image::load_from_memory(&bytes[..])
+                    .map_err(|e| NyaaError::Parse(format!("Failed to load captcha:\n{}", e)))?;
                 let image = picker.new_resize_protocol(dyn_image);
 
-                return Ok(SourceResponse::Captcha(image));
+                return Ok(SourceResponse::Captcha(super::Captcha {
+                    image,
+                    bytes: bytes.to_vec(),
+                }));
             }
         }
 
@@ -477,11 +557,12 @@ impl Source for TorrentGalaxyHtmlSource {
                     .and_then(|v| v.parse::<usize>().ok())
                     .unwrap_or_default();
                 let icon = Self::info().entry_from_id(cat_id).icon;
-                let date = e
+                let date: String = e
                     .select(date_sel)
                     .nth(0)
                     .map(|e| e.text().collect())
                     .unwrap_or_default();
+                let date = ItemDate::parse_utc(&date, "%d/%m/%y %H:%M", None);
                 let seeders = as_type(inner(e, seed_sel, "0")).unwrap_or_default();
                 let leechers = as_type(inner(e, leech_sel, "0")).unwrap_or_default();
                 let views = as_type(inner(e, views_sel, "0")).unwrap_or_default();
@@ -521,7 +602,7 @@ impl Source for TorrentGalaxyHtmlSource {
                 let post_link = attr(e, title_sel, "href");
 
                 let binding = post_link.split('/').collect::<Vec<&str>>();
-                let id = format!("tgx-{}", binding.get(2)?);
+                let id = ItemId::new("tgx", binding.get(2)?.parse().ok()?);
 
                 let post_link = base_url
                     .join(&post_link)
@@ -550,7 +631,6 @@ impl Source for TorrentGalaxyHtmlSource {
                     leechers,
                     downloads: views,
                     bytes: to_bytes(&size),
-                    size,
                     title: attr(e, title_sel, "title"),
                     torrent_link,
                     magnet_link,
@@ -565,7 +645,7 @@ impl Source for TorrentGalaxyHtmlSource {
             .collect::<Vec<Item>>();
 
         let mut last_page = 50;
-        let mut total_results = 2500;
+        let mut total_results = None;
         if let Some(pagination) = doc.select(pagination_sel).nth(0) {
             if let Ok(num_results) = pagination
                 .inner_html()
@@ -575,16 +655,33 @@ impl Source for TorrentGalaxyHtmlSource {
                 .parse::<usize>()
             {
                 if num_results != 0 || items.is_empty() {
-                    last_page = (num_results + 49) / 50;
-                    total_results = num_results;
+                    last_page = num_results.div_ceil(50);
+                    total_results = Some(num_results);
                 }
             }
         }
 
+        let mut warnings = vec![];
+        if config.strict {
+            // Other columns, like imdb and language, are often legitimately empty
+            let cols = [
+                cat_sel,
+                title_sel,
+                torrent_sel,
+                magnet_sel,
+                size_sel,
+                date_sel,
+                seed_sel,
+                leech_sel,
+            ];
+            warnings.extend(check_selectors(&doc, item_sel, &cols));
+        }
+
         Ok(SourceResponse::Results(ResultResponse {
             items,
             total_results,
             last_page,
+            warnings,
         }))
     }
 
@@ -594,7 +691,7 @@ impl Source for TorrentGalaxyHtmlSource {
         search: &SearchQuery,
         config: &SourceConfig,
         date_format: Option<String>,
-    ) -> Result<SourceResponse, Box<dyn Error + Send + Sync>> {
+    ) -> Result<SourceResponse, NyaaError> {
         let tgx = config.tgx.to_owned().unwrap_or_default();
         let time = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
 
@@ -602,15 +699,18 @@ impl Source for TorrentGalaxyHtmlSource {
         let base_url = Url::parse(&tgx.base_url)?;
         let mut hash_url = base_url.clone().join("hub.php")?;
         hash_url.set_query(Some(&format!("a=vlad&u={}", time)));
-        client
-            .post(hash_url.clone())
+        let mut request = client.post(hash_url.clone());
+        if let Some(timeout) = tgx.timeout {
+            request = request.timeout(Duration::from_secs(timeout));
+        }
+        stats::send(request
             .body(format!("fash={}", hash))
             .header("Content-Type", "application/x-www-form-urlencoded")
             .header(
                 "User-Agent",
                 "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:126.0) Gecko/20100101 Firefox/126.0",
             )
-            .send()
+            )
             .await?;
 
         let (_base_url, url) = get_url(tgx.base_url, search)?;
@@ -635,13 +735,12 @@ impl Source for TorrentGalaxyHtmlSource {
             .header("User-Agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:126.0) Gecko/20100101 Firefox/126.0")
             .header("Content-Type", "application/x-www-form-urlencoded");
 
-        let response = request.send().await?;
+        let response = stats::send(request).await?;
         if response.status() != StatusCode::OK {
-            return Err(format!(
-                "Captcha solution returned HTTP status {}",
-                response.status()
-            )
-            .into());
+            return Err(NyaaError::Status {
+                url: full_url.to_string(),
+                code: response.status().as_u16(),
+            });
         }
 
         TorrentGalaxyHtmlSource::search(client, search, config, date_format).await
@@ -694,6 +793,13 @@ impl Source for TorrentGalaxyHtmlSource {
                 .map(ToString::to_string)
                 .collect(),
             sorts: TgxSort::VARIANTS.iter().map(ToString::to_string).collect(),
+            capabilities: SourceCapabilities {
+                sort: true,
+                category: true,
+                comments: true,
+                multi_filter: true,
+                ..Default::default()
+            },
         }
     }
 
@@ -725,7 +831,7 @@ impl Source for TorrentGalaxyHtmlSource {
     fn default_filter(cfg: &SourceConfig) -> usize {
         cfg.tgx
             .as_ref()
-            .map(|c| c.default_filter as usize)
+            .map(|c| filter_bit(c.default_filter as usize))
             .unwrap_or_default()
     }
 
@@ -743,7 +849,11 @@ impl Source for TorrentGalaxyHtmlSource {
         theme: &Theme,
     ) -> ResultTable {
         let tgx = config.tgx.to_owned().unwrap_or_default();
-        let raw_date_width = items.iter().map(|i| i.date.len()).max().unwrap_or_default() as u16;
+        let raw_date_width = items
+            .iter()
+            .map(|i| i.date.to_string().len())
+            .max()
+            .unwrap_or_default() as u16;
         let date_width = max(raw_date_width, 6);
 
         let raw_uploader_width = items
@@ -758,14 +868,23 @@ impl Source for TorrentGalaxyHtmlSource {
             .max()
             .unwrap_or_default() as u16;
         let imdb_width = max(raw_imdb_width, 4);
+        let size_width = size_width(items);
+        let cols = tgx.columns.unwrap_or_default().array();
+        // Resolution, episode and group
+        let parsed = parse_titles(items, cols[4..=6].contains(&true));
+        let (ep_width, group_width) = parsed_widths(&parsed);
 
         let header = ResultHeader::new([
             ResultColumn::Normal("Cat".to_owned(), Constraint::Length(3)),
+            ResultColumn::Normal("".to_owned(), Constraint::Length(1)),
             ResultColumn::Normal("".to_owned(), Constraint::Length(2)),
             ResultColumn::Normal("Name".to_owned(), Constraint::Min(3)),
+            ResultColumn::Normal("Res".to_owned(), Constraint::Length(5)),
+            ResultColumn::Normal("Ep".to_owned(), Constraint::Length(ep_width)),
+            ResultColumn::Normal("Group".to_owned(), Constraint::Length(group_width)),
             ResultColumn::Normal("imdb".to_owned(), Constraint::Length(imdb_width)),
             ResultColumn::Normal("Uploader".to_owned(), Constraint::Length(uploader_width)),
-            ResultColumn::Sorted("Size".to_owned(), 9, TgxSort::Size as u32),
+            ResultColumn::Sorted("Size".to_owned(), size_width, TgxSort::Size as u32),
             ResultColumn::Sorted("Date".to_owned(), date_width, TgxSort::Date as u32),
             ResultColumn::Sorted("".to_owned(), 4, TgxSort::Seeders as u32),
             ResultColumn::Sorted("".to_owned(), 4, TgxSort::Leechers as u32),
@@ -773,6 +892,10 @@ impl Source for TorrentGalaxyHtmlSource {
         ]);
         let mut binding = header.get_binding();
         let align = [
+            Alignment::Left,
+            Alignment::Left,
+            Alignment::Left,
+            Alignment::Left,
             Alignment::Left,
             Alignment::Left,
             Alignment::Left,
@@ -786,19 +909,20 @@ impl Source for TorrentGalaxyHtmlSource {
         ];
         let mut rows: Vec<ResultRow> = items
             .iter()
-            .map(|item| {
+            .zip(parsed)
+            .map(|(item, parsed)| {
                 ResultRow::new([
                     item.icon.label.fg((item.icon.color)(theme)),
+                    theme.item_status(item),
                     item.extra
                         .get("lang")
                         .map(|l| get_lang(l.to_owned()))
                         .unwrap_or("??".to_owned())
                         .fg(theme.fg),
-                    item.title.to_owned().fg(match item.item_type {
-                        ItemType::Trusted => theme.success,
-                        ItemType::Remake => theme.error,
-                        ItemType::None => theme.fg,
-                    }),
+                    theme.item_title(item),
+                    parsed.resolution.unwrap_or_default().fg(theme.fg),
+                    parsed.episode.unwrap_or_default().fg(theme.fg),
+                    parsed.group.unwrap_or_default().fg(theme.fg),
                     item.extra
                         .get("imdb")
                         .cloned()
@@ -813,8 +937,8 @@ impl Source for TorrentGalaxyHtmlSource {
                             .get("uploader_status")
                             .and_then(|u| get_status_color(u.to_owned()))
                             .unwrap_or(theme.fg)),
-                    item.size.clone().fg(theme.fg),
-                    item.date.clone().fg(theme.fg),
+                    item.size().fg(theme.fg),
+                    item.date.to_string().fg(theme.fg),
                     item.seeders.to_string().fg(theme.success),
                     item.leechers.to_string().fg(theme.error),
                     shorten_number(item.downloads).fg(theme.fg),
@@ -823,26 +947,30 @@ impl Source for TorrentGalaxyHtmlSource {
                 .fg(theme.fg)
             })
             .collect();
+        // Keep the name and the columns it is sorted by on narrow terminals
+        let mut narrow = vec![
+            true, true, true, true, false, true, false, false, false, true, true, true, false,
+            false,
+        ];
         let mut headers = header.get_row(search.sort.dir, search.sort.sort as u32);
-        if let Some(columns) = tgx.columns {
-            let cols = columns.array();
-
-            headers.cells = cond_vec!(cols ; headers.cells);
-            rows = rows
-                .clone()
-                .into_iter()
-                .map(|mut r| {
-                    r.cells = cond_vec!(cols ; r.cells.to_owned());
-                    r
-                })
-                .collect::<Vec<ResultRow>>();
-            binding = cond_vec!(cols ; binding);
-        }
+
+        headers.cells = cond_vec!(cols ; headers.cells);
+        rows = rows
+            .clone()
+            .into_iter()
+            .map(|mut r| {
+                r.cells = cond_vec!(cols ; r.cells.to_owned());
+                r
+            })
+            .collect::<Vec<ResultRow>>();
+        binding = cond_vec!(cols ; binding);
+        narrow = cond_vec!(cols ; narrow);
 
         ResultTable {
             headers,
             rows,
             binding,
+            narrow,
         }
     }
 }
@@ -1,49 +1,206 @@
-use std::error::Error;
+use std::{
+    future::Future,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
-use crossterm::event::{self, Event};
-use tokio::sync::mpsc;
+use crossterm::{
+    event::{self, Event},
+    terminal,
+};
+use tokio::{sync::mpsc, task::JoinHandle};
+use tokio_util::sync::CancellationToken;
 
 use crate::{
     app::LoadType,
-    client::{Client, ClientConfig, DownloadResult},
-    results::Results,
-    source::{Item, SourceConfig, SourceResponse, SourceResults, Sources},
-    theme::Theme,
-    widget::sort::SelectedSort,
+    client::{progress::Batch, Client, ClientConfig, DownloadError, DownloadResult},
+    error::NyaaError,
+    results::{Results, ResultsView},
+    source::{
+        mock::MockSource, resolve, Item, ItemId, Source, SourceConfig, SourceResponse,
+        SourceResults, Sources,
+    },
+    util::{
+        links, term,
+        watch::{self, WatchItem},
+    },
+    widget::{sort::SelectedSort, user::UserCheck},
 };
 
-pub trait EventSync {
+use self::replay::Recorder;
+
+pub mod replay;
+pub mod tasks;
+
+// How long to wait for input before checking whether the terminal was suspended
+static EVENT_POLL_MILLIS: u64 = 50;
+
+/// Loads results from sources
+pub trait SearchService: Send + 'static {
     #[allow(clippy::too_many_arguments)]
     fn load_results(
         self,
-        tx_res: mpsc::Sender<Result<SourceResults, Box<dyn Error + Send + Sync>>>,
+        tx_res: mpsc::Sender<Result<SourceResults, NyaaError>>,
         load_type: LoadType,
         src: Sources,
         client: reqwest::Client,
         search: SearchQuery,
         config: SourceConfig,
-        theme: Theme,
+        view: ResultsView,
         date_format: Option<String>,
     ) -> impl std::future::Future<Output = ()> + std::marker::Send + 'static;
+    fn refresh_watch_later(
+        self,
+        tx_watch: mpsc::Sender<Result<Vec<WatchItem>, NyaaError>>,
+        src: Sources,
+        items: Vec<WatchItem>,
+        client: reqwest::Client,
+        config: SourceConfig,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send + 'static;
+    fn check_user(
+        self,
+        tx_user: mpsc::Sender<(Sources, String, UserCheck)>,
+        src: Sources,
+        client: reqwest::Client,
+        user: String,
+        config: SourceConfig,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send + 'static;
+}
+
+/// Sends torrents to clients, and checks their links and connections
+pub trait DownloadService: Send + 'static {
+    /// Send `items` as `batch`, or send the first item on its own if it's
+    /// `None`
     fn download(
         self,
         tx_dl: mpsc::Sender<DownloadResult>,
-        batch: bool,
+        batch: Option<Batch>,
         items: Vec<Item>,
         config: ClientConfig,
         rq_client: reqwest::Client,
         client: Client,
     ) -> impl std::future::Future<Output = ()> + std::marker::Send + 'static;
+    fn validate_links(
+        self,
+        tx_val: mpsc::Sender<Vec<ItemId>>,
+        items: Vec<Item>,
+        client: reqwest::Client,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send + 'static;
+    fn test_client(
+        self,
+        tx_test: mpsc::Sender<(Client, Result<(), String>)>,
+        client: Client,
+        config: ClientConfig,
+        rq_client: reqwest::Client,
+    ) -> impl std::future::Future<Output = ()> + std::marker::Send + 'static;
+}
+
+/// Reads events from the terminal, or from wherever else they come from
+pub trait EventService: Send + 'static {
     fn read_event_loop(
         self,
         tx_evt: mpsc::Sender<Event>,
     ) -> impl std::future::Future<Output = ()> + std::marker::Send + 'static;
 }
 
-#[derive(Clone)]
-pub struct AppSync;
+/// Every service the app needs, sent to the tasks which load results and
+/// download torrents
+pub trait EventSync: SearchService + DownloadService + EventService {}
+
+impl<T: SearchService + DownloadService + EventService> EventSync for T {}
+
+/// Run `fut` on its own task until it finishes or `cancel` is cancelled.
+/// Each operation is given a child of the app's token, so it can be stopped
+/// on its own, and everything still running stops when the app does.
+pub fn spawn_cancellable<F>(cancel: CancellationToken, fut: F) -> JoinHandle<()>
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    tokio::spawn(async move {
+        tokio::select! {
+            biased;
+            () = cancel.cancelled() => {}
+            () = fut => {}
+        }
+    })
+}
 
 #[derive(Clone, Default)]
+pub struct AppSync {
+    recorder: Option<Arc<Mutex<Recorder>>>,
+    offline: bool,
+}
+
+impl AppSync {
+    /// Record every event read from the terminal to a session file, which can
+    /// later be replayed with `--replay`
+    pub fn record(mut self, recorder: Recorder) -> Self {
+        self.recorder = Some(Arc::new(Mutex::new(recorder)));
+        self
+    }
+
+    /// Serve results from [`MockSource`] and skip real downloads, so nothing
+    /// is sent over the network
+    pub fn offline(mut self) -> Self {
+        self.offline = true;
+        self
+    }
+}
+
+/// Resolve the links of items which have none, then download them with
+/// `sync`. Items which couldn't be resolved are reported as errors of the same
+/// download, and stay in the batch. `batch` is the id of the batch being sent,
+/// if it is one. Once `stop` is cancelled, batches stop between torrents, and
+/// what wasn't sent is reported as skipped.
+#[allow(clippy::too_many_arguments)]
+pub async fn resolve_and_download<S: DownloadService>(
+    sync: S,
+    tx_dl: mpsc::Sender<DownloadResult>,
+    batch: Option<usize>,
+    items: Vec<Item>,
+    src: Sources,
+    sources: SourceConfig,
+    config: ClientConfig,
+    rq_client: reqwest::Client,
+    client: Client,
+    stop: CancellationToken,
+) {
+    let (items, failed) = tokio::select! {
+        res = resolve::resolve_all(src, &rq_client, items.clone(), &sources) => res,
+        () = stop.cancelled() => {
+            let mut res = DownloadResult::stopped(&items, batch.is_some());
+            res.batch_id = batch;
+            let _ = tx_dl.send(res).await;
+            return;
+        }
+    };
+    let errors: Vec<DownloadError> = failed
+        .into_iter()
+        .map(|(item, e)| {
+            DownloadError(format!(
+                "Failed to find the links of \"{}\":\n{}",
+                item.title, e
+            ))
+        })
+        .collect();
+    if items.is_empty() {
+        let mut res = DownloadResult::new(None, vec![], errors, batch.is_some());
+        res.batch_id = batch;
+        let _ = tx_dl.send(res).await;
+        return;
+    }
+    let (tx, mut rx) = mpsc::channel(1);
+    let send = batch.map(|id| Batch { id, stop });
+    sync.download(tx, send, items, config, rq_client, client)
+        .await;
+    if let Some(mut res) = rx.recv().await {
+        res.errors.extend(errors);
+        res.batch_id = batch;
+        let _ = tx_dl.send(res).await;
+    }
+}
+
+#[derive(Clone, Default, PartialEq)]
 pub struct SearchQuery {
     pub query: String,
     pub page: usize,
@@ -53,55 +210,200 @@ pub struct SearchQuery {
     pub user: Option<String>,
 }
 
-impl EventSync for AppSync {
+impl SearchQuery {
+    /// Whether `other` lists the same results, possibly in another order
+    pub fn same_results(&self, other: &SearchQuery) -> bool {
+        SearchQuery {
+            sort: other.sort,
+            ..self.clone()
+        } == *other
+    }
+
+    /// Whether `other` is the same search, possibly on another page or in
+    /// another order
+    pub fn same_view(&self, other: &SearchQuery) -> bool {
+        SearchQuery {
+            page: other.page,
+            sort: other.sort,
+            ..self.clone()
+        } == *other
+    }
+}
+
+impl SearchService for AppSync {
     async fn load_results(
         self,
-        tx_res: mpsc::Sender<Result<SourceResults, Box<dyn Error + Send + Sync>>>,
+        tx_res: mpsc::Sender<Result<SourceResults, NyaaError>>,
         load_type: LoadType,
         src: Sources,
         client: reqwest::Client,
         search: SearchQuery,
         config: SourceConfig,
-        theme: Theme,
+        view: ResultsView,
         date_format: Option<String>,
     ) {
-        let res = src
-            .load(load_type, &client, &search, &config, date_format)
+        let deep = matches!(load_type, LoadType::DeepSearching(_));
+        let res = match self.offline {
+            true => MockSource::search(&client, &search, &config, date_format).await,
+            false => {
+                src.load(load_type, &client, &search, &config, date_format)
+                    .await
+            }
+        };
+        let fmt =
+            match res {
+                Ok(SourceResponse::Results(res)) => {
+                    // Built on a thread of its own, as each row is formatted
+                    let offline = self.offline;
+                    tokio::task::spawn_blocking(move || {
+                        Results::prepare(search, res, view, deep, |items, search, theme| {
+                            match offline {
+                                true => MockSource::format_table(items, search, &config, theme),
+                                false => src.format_table(items, search, &config, theme),
+                            }
+                        })
+                    })
+                    .await
+                    .map(SourceResults::Results)
+                    .map_err(NyaaError::from)
+                }
+                #[cfg(feature = "captcha")]
+                Ok(SourceResponse::Captcha(c)) => Ok(SourceResults::Captcha(c)),
+                Err(e) => Err(e),
+            };
+        let _ = tx_res.send(fmt).await;
+    }
+
+    async fn refresh_watch_later(
+        self,
+        tx_watch: mpsc::Sender<Result<Vec<WatchItem>, NyaaError>>,
+        src: Sources,
+        items: Vec<WatchItem>,
+        client: reqwest::Client,
+        config: SourceConfig,
+    ) {
+        // Mock results don't have the same ids, so nothing would be found
+        if self.offline {
+            return;
+        }
+        let _ = tx_watch
+            .send(watch::refresh(src, &client, items, &config).await)
             .await;
-        let fmt = match res {
-            Ok(SourceResponse::Results(res)) => Ok(SourceResults::Results(Results::new(
-                search.clone(),
-                res.clone(),
-                src.format_table(&res.items, &search, &config, &theme),
-            ))),
+    }
+
+    async fn check_user(
+        self,
+        tx_user: mpsc::Sender<(Sources, String, UserCheck)>,
+        src: Sources,
+        client: reqwest::Client,
+        user: String,
+        config: SourceConfig,
+    ) {
+        let search = SearchQuery {
+            page: 1,
+            category: src.default_category(&config),
+            sort: src.default_sort(&config),
+            user: Some(user.clone()),
+            ..Default::default()
+        };
+        let res = match self.offline {
+            true => MockSource::search(&client, &search, &config, None).await,
+            false => {
+                src.load(LoadType::Searching, &client, &search, &config, None)
+                    .await
+            }
+        };
+        let check = match res {
+            Ok(SourceResponse::Results(res)) if res.items.is_empty() => UserCheck::Empty,
+            Ok(SourceResponse::Results(res)) => UserCheck::Found(res.total_results),
             #[cfg(feature = "captcha")]
-            Ok(SourceResponse::Captcha(c)) => Ok(SourceResults::Captcha(c)),
-            Err(e) => Err(e),
+            Ok(SourceResponse::Captcha(_)) => UserCheck::Failed("A captcha must be solved".into()),
+            Err(NyaaError::Status { code: 404, .. }) => UserCheck::Missing,
+            Err(e) => UserCheck::Failed(e.to_string()),
         };
-        let _ = tx_res.send(fmt).await;
+        let _ = tx_user.send((src, user, check)).await;
     }
+}
 
+impl DownloadService for AppSync {
     async fn download(
         self,
         tx_dl: mpsc::Sender<DownloadResult>,
-        batch: bool,
+        batch: Option<Batch>,
         items: Vec<Item>,
         config: ClientConfig,
         rq_client: reqwest::Client,
         client: Client,
     ) {
+        if self.offline {
+            let ids = items.into_iter().map(|i| i.id).collect::<Vec<ItemId>>();
+            let msg = format!("Pretended to download {} torrents (offline)", ids.len());
+            let _ = tx_dl
+                .send(DownloadResult::new(msg, ids, vec![], batch.is_some()))
+                .await;
+            return;
+        }
         let res = match batch {
-            true => client.batch_download(items, config, rq_client).await,
-            false => client.download(items[0].clone(), config, rq_client).await,
+            Some(batch) => client.batch_download(items, config, rq_client, batch).await,
+            // A single torrent is sent whole, even once stopped
+            None => client.download(items[0].clone(), config, rq_client).await,
         };
         let _ = tx_dl.send(res).await;
     }
 
+    async fn validate_links(
+        self,
+        tx_val: mpsc::Sender<Vec<ItemId>>,
+        items: Vec<Item>,
+        client: reqwest::Client,
+    ) {
+        // Mock links don't exist, but shouldn't be shown as dead
+        if self.offline {
+            return;
+        }
+        let _ = tx_val.send(links::dead_links(&client, items).await).await;
+    }
+
+    async fn test_client(
+        self,
+        tx_test: mpsc::Sender<(Client, Result<(), String>)>,
+        client: Client,
+        config: ClientConfig,
+        rq_client: reqwest::Client,
+    ) {
+        // Nothing is downloaded offline, so every client works
+        let res = match self.offline {
+            true => Ok(()),
+            false => client.test_connection(config, rq_client).await,
+        };
+        let _ = tx_test.send((client, res)).await;
+    }
+}
+
+impl EventService for AppSync {
     async fn read_event_loop(self, tx_evt: mpsc::Sender<Event>) {
+        if let (Some(rec), Ok((w, h))) = (&self.recorder, terminal::size()) {
+            // Store the initial size so the replay can use the same dimensions
+            if let Ok(mut rec) = rec.lock() {
+                let _ = rec.record(&Event::Resize(w, h));
+            }
+        }
         loop {
-            if let Ok(evt) = event::read() {
-                let _ = tx_evt.send(evt).await;
+            if term::is_suspended() {
+                tokio::time::sleep(Duration::from_millis(EVENT_POLL_MILLIS)).await;
+                continue;
+            }
+            if let Ok(true) = event::poll(Duration::from_millis(EVENT_POLL_MILLIS)) {
+                if let Ok(evt) = event::read() {
+                    if let Some(Ok(mut rec)) = self.recorder.as_ref().map(|r| r.lock()) {
+                        let _ = rec.record(&evt);
+                    }
+                    let _ = tx_evt.send(evt).await;
+                }
             }
+            // Polling blocks, so give the task a chance to be cancelled, or
+            // the runtime can't shut down once the app stops
+            tokio::task::yield_now().await;
         }
     }
 }
@@ -0,0 +1,212 @@
+use std::{
+    error::Error,
+    fs::{self, OpenOptions},
+    io::Write as _,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use crossterm::event::Event;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+
+use crate::{
+    app::LoadType,
+    client::{progress::Batch, Client, ClientConfig, DownloadResult},
+    error::NyaaError,
+    results::ResultsView,
+    source::{Item, ItemId, SourceConfig, SourceResults, Sources},
+    util::watch::WatchItem,
+    widget::user::UserCheck,
+};
+
+use super::{AppSync, DownloadService, EventService, SearchQuery, SearchService};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RecordedEvent {
+    /// Milliseconds since the previous event
+    #[serde(default)]
+    pub delay: u64,
+    pub event: Event,
+}
+
+/// A list of events read from the terminal, stored as TOML so sessions can be
+/// written (or tweaked) by hand for regression tests.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct Session {
+    #[serde(default)]
+    pub events: Vec<RecordedEvent>,
+}
+
+impl Session {
+    pub fn load(path: impl AsRef<Path>) -> Result<Session, Box<dyn Error>> {
+        let path = path.as_ref();
+        let s = fs::read_to_string(path)
+            .map_err(|e| format!("{path:?}\nUnable to read session:\n{e}"))?;
+        Ok(toml::from_str(&s)?)
+    }
+
+    /// The size of the terminal at the start of the session, if one was
+    /// recorded
+    pub fn size(&self) -> Option<(u16, u16)> {
+        self.events.iter().find_map(|e| match e.event {
+            Event::Resize(w, h) => Some((w, h)),
+            _ => None,
+        })
+    }
+}
+
+/// Appends every event to a session file as soon as it is read, so the
+/// recording survives even if the app crashes.
+pub struct Recorder {
+    path: PathBuf,
+    last: Instant,
+}
+
+impl Recorder {
+    pub fn new(path: impl AsRef<Path>) -> Result<Recorder, Box<dyn Error>> {
+        let path = path.as_ref().to_owned();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // Truncate any previous recording
+        fs::write(&path, "")?;
+        Ok(Recorder {
+            path,
+            last: Instant::now(),
+        })
+    }
+
+    pub fn record(&mut self, event: &Event) -> Result<(), Box<dyn Error>> {
+        let now = Instant::now();
+        let entry = Session {
+            events: vec![RecordedEvent {
+                delay: now.duration_since(self.last).as_millis() as u64,
+                event: event.clone(),
+            }],
+        };
+        self.last = now;
+
+        // Each entry is a `[[events]]` table, so appending keeps the file valid
+        let mut f = OpenOptions::new().append(true).open(&self.path)?;
+        f.write_all(toml::to_string(&entry)?.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Feeds the events of a recorded session back to the app instead of reading
+/// from the terminal. Results and downloads are still handled by [`AppSync`].
+#[derive(Clone)]
+pub struct ReplaySync {
+    session: Session,
+    sync: AppSync,
+}
+
+impl ReplaySync {
+    pub fn new(session: Session, sync: AppSync) -> Self {
+        ReplaySync { session, sync }
+    }
+}
+
+impl SearchService for ReplaySync {
+    async fn load_results(
+        self,
+        tx_res: mpsc::Sender<Result<SourceResults, NyaaError>>,
+        load_type: LoadType,
+        src: Sources,
+        client: reqwest::Client,
+        search: SearchQuery,
+        config: SourceConfig,
+        view: ResultsView,
+        date_format: Option<String>,
+    ) {
+        self.sync
+            .load_results(
+                tx_res,
+                load_type,
+                src,
+                client,
+                search,
+                config,
+                view,
+                date_format,
+            )
+            .await
+    }
+
+    async fn refresh_watch_later(
+        self,
+        tx_watch: mpsc::Sender<Result<Vec<WatchItem>, NyaaError>>,
+        src: Sources,
+        items: Vec<WatchItem>,
+        client: reqwest::Client,
+        config: SourceConfig,
+    ) {
+        self.sync
+            .refresh_watch_later(tx_watch, src, items, client, config)
+            .await
+    }
+
+    async fn check_user(
+        self,
+        tx_user: mpsc::Sender<(Sources, String, UserCheck)>,
+        src: Sources,
+        client: reqwest::Client,
+        user: String,
+        config: SourceConfig,
+    ) {
+        self.sync
+            .check_user(tx_user, src, client, user, config)
+            .await
+    }
+}
+
+impl DownloadService for ReplaySync {
+    async fn download(
+        self,
+        tx_dl: mpsc::Sender<DownloadResult>,
+        batch: Option<Batch>,
+        items: Vec<Item>,
+        config: ClientConfig,
+        rq_client: reqwest::Client,
+        client: Client,
+    ) {
+        self.sync
+            .download(tx_dl, batch, items, config, rq_client, client)
+            .await
+    }
+
+    async fn validate_links(
+        self,
+        tx_val: mpsc::Sender<Vec<ItemId>>,
+        items: Vec<Item>,
+        client: reqwest::Client,
+    ) {
+        self.sync.validate_links(tx_val, items, client).await
+    }
+
+    async fn test_client(
+        self,
+        tx_test: mpsc::Sender<(Client, Result<(), String>)>,
+        client: Client,
+        config: ClientConfig,
+        rq_client: reqwest::Client,
+    ) {
+        self.sync
+            .test_client(tx_test, client, config, rq_client)
+            .await
+    }
+}
+
+impl EventService for ReplaySync {
+    async fn read_event_loop(self, tx_evt: mpsc::Sender<Event>) {
+        for e in self.session.events.into_iter() {
+            if e.delay > 0 {
+                tokio::time::sleep(Duration::from_millis(e.delay)).await;
+            }
+            let _ = tx_evt.send(e.event).await;
+        }
+        // Stops the app once all events have been replayed
+        let _ = tx_evt.send(Event::FocusLost).await;
+    }
+}
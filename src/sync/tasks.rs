@@ -0,0 +1,246 @@
+use std::{fmt::Display, future::Future, time::Instant};
+
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+use super::spawn_cancellable;
+
+pub type TaskId = usize;
+
+/// What a task is doing, which decides how many can run at once
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TaskKind {
+    /// Reading events from the terminal
+    Events,
+    Search,
+    Download,
+    /// Checking the torrent links of the results shown
+    Validate,
+    WatchLater,
+    TestClient,
+    Webhook,
+    Update,
+    /// Checking whether an uploader has torrents
+    CheckUser,
+}
+
+impl TaskKind {
+    /// How many tasks of this kind can run at once
+    pub fn limit(self) -> usize {
+        match self {
+            TaskKind::Events
+            | TaskKind::Search
+            | TaskKind::Validate
+            | TaskKind::Update
+            | TaskKind::CheckUser => 1,
+            TaskKind::Download | TaskKind::WatchLater | TaskKind::TestClient => 8,
+            TaskKind::Webhook => 16,
+        }
+    }
+
+    /// Whether a new task stops the oldest one once at the limit, instead of
+    /// not being started. Only done for tasks whose work is made stale by the
+    /// next one, like a search being replaced by another.
+    pub fn replaces(self) -> bool {
+        matches!(
+            self,
+            TaskKind::Search | TaskKind::Validate | TaskKind::CheckUser
+        )
+    }
+}
+
+impl Display for TaskKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            TaskKind::Events => "Events",
+            TaskKind::Search => "Search",
+            TaskKind::Download => "Download",
+            TaskKind::Validate => "Validate",
+            TaskKind::WatchLater => "Watch later",
+            TaskKind::TestClient => "Test client",
+            TaskKind::Webhook => "Webhook",
+            TaskKind::Update => "Update",
+            TaskKind::CheckUser => "Check user",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// A task still running, as listed in the tasks popup
+#[derive(Clone, PartialEq, Debug)]
+pub struct TaskInfo {
+    pub id: TaskId,
+    pub kind: TaskKind,
+    pub name: String,
+    pub started: Instant,
+}
+
+struct Task {
+    info: TaskInfo,
+    cancel: CancellationToken,
+    handle: JoinHandle<()>,
+}
+
+/// Every task spawned by the app, so each can be listed and cancelled, and
+/// none is left running once the app stops
+pub struct TaskRegistry {
+    cancel: CancellationToken,
+    next_id: TaskId,
+    tasks: Vec<Task>,
+}
+
+impl Default for TaskRegistry {
+    fn default() -> Self {
+        TaskRegistry {
+            cancel: CancellationToken::new(),
+            next_id: 0,
+            tasks: vec![],
+        }
+    }
+}
+
+impl TaskRegistry {
+    /// Run `fut` on its own task, unless too many of `kind` are running
+    pub fn spawn<F>(
+        &mut self,
+        kind: TaskKind,
+        name: impl Into<String>,
+        fut: F,
+    ) -> Result<TaskId, String>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.start(kind, name, |cancel| spawn_cancellable(cancel, fut))
+    }
+
+    /// Run the future `f` makes on its own task, like [`TaskRegistry::spawn`],
+    /// but leave it to stop itself once the token it's given is cancelled, so
+    /// it can wrap up what it's doing first
+    pub fn spawn_stoppable<F>(
+        &mut self,
+        kind: TaskKind,
+        name: impl Into<String>,
+        f: impl FnOnce(CancellationToken) -> F,
+    ) -> Result<TaskId, String>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.start(kind, name, |cancel| tokio::spawn(f(cancel)))
+    }
+
+    fn start(
+        &mut self,
+        kind: TaskKind,
+        name: impl Into<String>,
+        run: impl FnOnce(CancellationToken) -> JoinHandle<()>,
+    ) -> Result<TaskId, String> {
+        self.reap();
+        if self.count(kind) >= kind.limit() {
+            match kind.replaces() {
+                true => {
+                    if let Some(oldest) = self.tasks.iter().find(|t| t.info.kind == kind) {
+                        let id = oldest.info.id;
+                        self.cancel(id);
+                    }
+                }
+                false => {
+                    return Err(format!(
+                        "Too many {} tasks running ({}), wait for one to finish",
+                        kind.to_string().to_lowercase(),
+                        kind.limit()
+                    ))
+                }
+            }
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        let cancel = self.cancel.child_token();
+        let handle = run(cancel.clone());
+        self.tasks.push(Task {
+            info: TaskInfo {
+                id,
+                kind,
+                name: name.into(),
+                started: Instant::now(),
+            },
+            cancel,
+            handle,
+        });
+        Ok(id)
+    }
+
+    /// Forget the tasks which have finished
+    pub fn reap(&mut self) {
+        self.tasks.retain(|t| !t.handle.is_finished());
+    }
+
+    /// The tasks still running, oldest first
+    pub fn running(&self) -> Vec<TaskInfo> {
+        self.tasks
+            .iter()
+            .filter(|t| !t.handle.is_finished())
+            .map(|t| t.info.clone())
+            .collect()
+    }
+
+    /// How many tasks of `kind` are still running
+    pub fn count(&self, kind: TaskKind) -> usize {
+        self.tasks
+            .iter()
+            .filter(|t| t.info.kind == kind && !t.handle.is_finished())
+            .count()
+    }
+
+    pub fn kind(&self, id: TaskId) -> Option<TaskKind> {
+        self.tasks
+            .iter()
+            .find(|t| t.info.id == id)
+            .map(|t| t.info.kind)
+    }
+
+    /// Stop the task `id` at its next await, or let it stop itself if it was
+    /// started with [`TaskRegistry::spawn_stoppable`]. Returns its kind if it
+    /// was still running.
+    pub fn cancel(&mut self, id: TaskId) -> Option<TaskKind> {
+        let i = self.tasks.iter().position(|t| t.info.id == id)?;
+        let task = self.tasks.remove(i);
+        task.cancel.cancel();
+        match task.handle.is_finished() {
+            true => None,
+            false => Some(task.info.kind),
+        }
+    }
+
+    /// Stop every task of `kind`, returning how many were still running
+    pub fn cancel_kind(&mut self, kind: TaskKind) -> usize {
+        let ids: Vec<TaskId> = self
+            .tasks
+            .iter()
+            .filter(|t| t.info.kind == kind)
+            .map(|t| t.info.id)
+            .collect();
+        ids.into_iter().filter_map(|id| self.cancel(id)).count()
+    }
+
+    /// Stop every task of `kind`, waiting for each to stop or wrap up
+    pub async fn join_kind(&mut self, kind: TaskKind) {
+        let (stopping, running) = std::mem::take(&mut self.tasks)
+            .into_iter()
+            .partition::<Vec<Task>, _>(|t| t.info.kind == kind);
+        self.tasks = running;
+        for task in stopping {
+            task.cancel.cancel();
+            let _ = task.handle.await;
+        }
+    }
+}
+
+impl Drop for TaskRegistry {
+    // Stop every task still running, however the app stops
+    fn drop(&mut self) {
+        self.cancel.cancel();
+        for task in self.tasks.iter() {
+            task.handle.abort();
+        }
+    }
+}
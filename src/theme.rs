@@ -5,10 +5,19 @@ use std::{
 };
 
 use indexmap::IndexMap;
-use ratatui::{style::Color, widgets::BorderType};
+use ratatui::{
+    buffer::Buffer,
+    style::{Color, Modifier, Stylize as _},
+    text::Span,
+    widgets::BorderType,
+};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-use crate::{app::Context, collection, config, source::SourceTheme};
+use crate::{
+    app::Context,
+    collection, config,
+    source::{Item, ItemType, SourceTheme},
+};
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Theme {
@@ -39,6 +48,80 @@ pub struct Theme {
 
     #[serde(default)]
     pub source: SourceTheme,
+
+    /// Drop every color, drawing highlights in reverse video instead
+    #[serde(default)]
+    pub monochrome: bool,
+    /// Mark trusted and remake titles with `[T]` and `[R]`
+    #[serde(default)]
+    pub markers: bool,
+    /// Colors some widgets use in place of the ones above
+    #[serde(default)]
+    pub widgets: WidgetThemes,
+}
+
+/// Border and title colors of one kind of widget, each falling back to the
+/// theme's own when not set
+#[derive(Clone, Copy, Default, Serialize, Deserialize, PartialEq, Debug)]
+pub struct WidgetColors {
+    #[serde(
+        default,
+        with = "color_to_tui::optional",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub border: Option<Color>,
+    #[serde(
+        default,
+        with = "color_to_tui::optional",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub border_focused: Option<Color>,
+    #[serde(
+        default,
+        with = "color_to_tui::optional",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub title: Option<Color>,
+}
+
+/// Which widget's colors to draw with
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WidgetKind {
+    Results,
+    Batch,
+    Details,
+    Search,
+    /// Every popup without colors of its own
+    Popup,
+    Notification,
+    /// The error popup and error notifications
+    Error,
+}
+
+#[derive(Clone, Copy, Default, Serialize, Deserialize, PartialEq, Debug)]
+#[serde(default)]
+pub struct WidgetThemes {
+    pub results: WidgetColors,
+    pub batch: WidgetColors,
+    pub details: WidgetColors,
+    pub search: WidgetColors,
+    pub popup: WidgetColors,
+    pub notification: WidgetColors,
+    pub error: WidgetColors,
+}
+
+impl WidgetThemes {
+    pub fn get(&self, kind: WidgetKind) -> &WidgetColors {
+        match kind {
+            WidgetKind::Results => &self.results,
+            WidgetKind::Batch => &self.batch,
+            WidgetKind::Details => &self.details,
+            WidgetKind::Search => &self.search,
+            WidgetKind::Popup => &self.popup,
+            WidgetKind::Notification => &self.notification,
+            WidgetKind::Error => &self.error,
+        }
+    }
 }
 
 pub fn load_user_themes(ctx: &mut Context, config_path: PathBuf) -> Result<(), String> {
@@ -135,6 +218,9 @@ impl Default for Theme {
             success: Color::Green,
             error: Color::Red,
             source: Default::default(),
+            monochrome: false,
+            markers: false,
+            widgets: Default::default(),
         }
     }
 }
@@ -143,6 +229,55 @@ impl Theme {
     fn from_path(path: impl AsRef<Path>) -> Result<Theme, Box<dyn Error>> {
         config::load_path(path)
     }
+
+    /// The title of `item`, colored by whether it is trusted or a remake
+    pub fn item_title(&self, item: &Item) -> Span<'static> {
+        let color = match item.item_type {
+            ItemType::Trusted => self.success,
+            ItemType::Remake => self.error,
+            ItemType::None => self.fg,
+        };
+        match item.item_type.marker().filter(|_| self.markers) {
+            Some(marker) => format!("{} {}", marker, item.title).fg(color),
+            None => item.title.to_owned().fg(color),
+        }
+    }
+
+    /// The border color of `kind` of widget, whether it's focused or not
+    pub fn border_of(&self, kind: WidgetKind, focused: bool) -> Color {
+        let colors = self.widgets.get(kind);
+        match (focused, kind) {
+            (true, WidgetKind::Error) => colors.border_focused.unwrap_or(self.error),
+            (false, WidgetKind::Error) => colors.border.unwrap_or(self.error),
+            (true, _) => colors.border_focused.unwrap_or(self.border_focused_color),
+            (false, _) => colors.border.unwrap_or(self.border_color),
+        }
+    }
+
+    /// Whether `item` is trusted or a remake, as a single character
+    pub fn item_status(&self, item: &Item) -> Span<'static> {
+        match item.item_type {
+            ItemType::Trusted => item.item_type.symbol().fg(self.success),
+            ItemType::Remake => item.item_type.symbol().fg(self.error),
+            ItemType::None => item.item_type.symbol().fg(self.fg),
+        }
+    }
+
+    /// Strip the colors from everything drawn in `buf`. Cells drawn on
+    /// another background, like the selected row, are reversed and focused
+    /// borders are bold, so they still stand out.
+    pub fn to_monochrome(&self, buf: &mut Buffer) {
+        for cell in buf.content.iter_mut() {
+            if cell.bg != self.bg && cell.bg != Color::Reset {
+                cell.modifier.insert(Modifier::REVERSED);
+            }
+            if cell.fg == self.border_focused_color {
+                cell.modifier.insert(Modifier::BOLD);
+            }
+            cell.fg = Color::Reset;
+            cell.bg = Color::Reset;
+        }
+    }
 }
 
 pub fn default_themes() -> IndexMap<String, Theme> {
@@ -161,6 +296,9 @@ pub fn default_themes() -> IndexMap<String, Theme> {
             success: Color::Rgb(80, 250, 123),
             error: Color::Rgb(255, 85, 85),
             source: Default::default(),
+            monochrome: false,
+            markers: false,
+            widgets: Default::default(),
         },
         "Gruvbox".to_owned() => Theme {
             name: "Gruvbox".to_owned(),
@@ -175,6 +313,9 @@ pub fn default_themes() -> IndexMap<String, Theme> {
             success: Color::Rgb(152, 151, 26),
             error: Color::Rgb(204, 36, 29),
             source: Default::default(),
+            monochrome: false,
+            markers: false,
+            widgets: Default::default(),
         },
         "Catppuccin Macchiato".to_owned() => Theme {
             name: "Catppuccin Macchiato".to_owned(),
@@ -189,6 +330,33 @@ pub fn default_themes() -> IndexMap<String, Theme> {
             success: Color::Rgb(166, 218, 149),
             error: Color::Rgb(237, 135, 150),
             source: Default::default(),
+            monochrome: false,
+            markers: false,
+            widgets: Default::default(),
+        },
+        "High Contrast".to_owned() => Theme {
+            name: "High Contrast".to_owned(),
+            bg: Color::Black,
+            fg: Color::White,
+            border: BorderType::Thick,
+            border_color: Color::White,
+            border_focused_color: Color::LightYellow,
+            hl_bg: Color::Blue,
+            solid_bg: Color::LightYellow,
+            solid_fg: Color::Black,
+            success: Color::LightGreen,
+            error: Color::LightRed,
+            source: Default::default(),
+            monochrome: false,
+            markers: false,
+            widgets: Default::default(),
+        },
+        "Monochrome".to_owned() => Theme {
+            name: "Monochrome".to_owned(),
+            border: BorderType::Double,
+            monochrome: true,
+            markers: true,
+            ..Theme::default()
         },
     ]
 }
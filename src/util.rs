@@ -1,4 +1,16 @@
+pub mod anime;
 pub mod cmd;
+pub mod complete;
 pub mod conv;
+pub mod crash;
+pub mod export;
 pub mod html;
+pub mod links;
+pub mod log;
+pub mod stats;
+pub mod template;
 pub mod term;
+pub mod torrent;
+pub mod update;
+pub mod watch;
+pub mod webhook;
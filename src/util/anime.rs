@@ -0,0 +1,139 @@
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+/// What could be read from a release title, like
+/// `[SubsPlease] Show Name - 05 (1080p) [ABCD1234].mkv`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ParsedTitle {
+    /// Name of the show, without the group, episode and tags
+    pub name: String,
+    /// Episode number, `S01E05` style if it had a season, or a range like
+    /// `01-12` for batches
+    pub episode: Option<String>,
+    /// Vertical resolution, e.g. `1080p`
+    pub resolution: Option<String>,
+    /// Release group, e.g. `SubsPlease`
+    pub group: Option<String>,
+    /// `HEVC`, `AVC` or `AV1`
+    pub codec: Option<String>,
+    /// Whether it is a batch of several episodes
+    pub batch: bool,
+}
+
+macro_rules! re {
+    ($re:literal) => {{
+        static RE: OnceLock<Regex> = OnceLock::new();
+        RE.get_or_init(|| Regex::new($re).unwrap())
+    }};
+}
+
+/// Read the show name, episode, resolution, group and codec from a release
+/// title. Anything which can't be found is left as `None`.
+pub fn parse(title: &str) -> ParsedTitle {
+    let title = title.trim();
+    let title = re!(r"(?i)\.(mkv|mp4|avi|torrent)$").replace(title, "");
+    // Scene style titles use dots or underscores instead of spaces
+    let title = match title.contains(' ') {
+        true => title.into_owned(),
+        false => title.replace(['.', '_'], " "),
+    };
+
+    let mut parsed = ParsedTitle::default();
+    let mut rest = title.as_str();
+    if let Some(c) = re!(r"^[\[(【]([^\])】]+)[\])】]\s*").captures(rest) {
+        parsed.group = Some(c[1].trim().to_owned());
+        rest = &rest[c[0].len()..];
+    } else if let Some(c) = re!(r"\S-([A-Za-z]\w*)(\s?[\[(][^\])]*[\])])*$").captures(rest) {
+        parsed.group = Some(c[1].to_owned());
+    }
+
+    parsed.resolution = re!(r"(?i)\b(\d{3,4})[pi]\b")
+        .captures(rest)
+        .map(|c| format!("{}p", &c[1]))
+        .or_else(|| {
+            re!(r"\b\d{3,4}x(\d{3,4})\b")
+                .captures(rest)
+                .map(|c| format!("{}p", &c[1]))
+        })
+        .or_else(|| {
+            re!(r"(?i)\b4k\b")
+                .is_match(rest)
+                .then(|| "2160p".to_owned())
+        });
+
+    parsed.codec = if re!(r"(?i)\b(hevc|[xh][.\s]?265)\b").is_match(rest) {
+        Some("HEVC".to_owned())
+    } else if re!(r"(?i)\b(avc|[xh][.\s]?264)\b").is_match(rest) {
+        Some("AVC".to_owned())
+    } else if re!(r"(?i)\bav1\b").is_match(rest) {
+        Some("AV1".to_owned())
+    } else {
+        None
+    };
+
+    // Where the name ends: at the episode, or the first tag
+    let mut end = rest.len();
+    if let Some(c) = re!(r"(?i)\bS(\d{1,2})E(\d{1,4})(?:\s?-\s?E?(\d{1,4}))?\b").captures(rest) {
+        let ep = match c.get(3) {
+            Some(last) => format!("S{:0>2}E{:0>2}-E{:0>2}", &c[1], &c[2], last.as_str()),
+            None => format!("S{:0>2}E{:0>2}", &c[1], &c[2]),
+        };
+        parsed.batch = c.get(3).is_some();
+        parsed.episode = Some(ep);
+        end = c.get(0).map(|m| m.start()).unwrap_or(end);
+    } else if let Some(c) =
+        re!(r"\s-\s(\d{1,4})(?:v\d)?(?:\s?[~-]\s?(\d{1,4})(?:v\d)?)?(?:\s|$|[\[(])").captures(rest)
+    {
+        parsed.episode = Some(match c.get(2) {
+            Some(last) => format!("{}-{}", &c[1], last.as_str()),
+            None => c[1].to_owned(),
+        });
+        parsed.batch = c.get(2).is_some();
+        end = c.get(0).map(|m| m.start()).unwrap_or(end);
+    } else if let Some(c) = re!(r"(?i)\b(?:ep?|episode)\s?(\d{1,4})\b").captures(rest) {
+        parsed.episode = Some(c[1].to_owned());
+        end = c.get(0).map(|m| m.start()).unwrap_or(end);
+    }
+    if let Some(m) =
+        re!(r"(?i)[\[(【]|\b\d{3,4}[pi]\b|\b(batch|complete|web|bd|bluray|hevc|[xh][.\s]?26[45])\b")
+            .find(rest)
+    {
+        end = end.min(m.start());
+    }
+    if re!(r"(?i)\b(batch|complete)\b").is_match(rest) {
+        parsed.batch = true;
+    }
+
+    parsed.name = rest[..end]
+        .trim_matches(|c: char| c.is_whitespace() || "-~|".contains(c))
+        .to_owned();
+    parsed
+}
+
+/// Which items to look for with a search made from a parsed title
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Related {
+    /// The same show, from any group or in any resolution
+    Show,
+    /// Other episodes of the show from the same group
+    Group,
+}
+
+impl Related {
+    pub fn label(self) -> &'static str {
+        match self {
+            Related::Show => "Same show",
+            Related::Group => "Same group",
+        }
+    }
+
+    /// The query finding items related to `parsed`, if enough of it was read
+    pub fn query(self, parsed: &ParsedTitle) -> Option<String> {
+        let name = Some(parsed.name.trim()).filter(|n| !n.is_empty())?;
+        match self {
+            Related::Show => Some(name.to_owned()),
+            Related::Group => Some(format!("{} {}", parsed.group.as_deref()?, name)),
+        }
+    }
+}
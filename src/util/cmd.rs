@@ -19,14 +19,27 @@ impl CommandBuilder {
     }
 
     pub fn run<S: Into<Option<String>>>(&self, shell: S) -> Result<(), Box<dyn Error>> {
-        let shell = Into::<Option<String>>::into(shell).unwrap_or(Self::default_shell());
+        self.exec(shell.into(), false).map(|_| ())
+    }
+
+    /// Run the command, returning what it printed
+    pub fn output<S: Into<Option<String>>>(&self, shell: S) -> Result<String, Box<dyn Error>> {
+        self.exec(shell.into(), true)
+            .map(|out| String::from_utf8_lossy(&out).into_owned())
+    }
+
+    fn exec(&self, shell: Option<String>, capture: bool) -> Result<Vec<u8>, Box<dyn Error>> {
+        let shell = shell.unwrap_or(Self::default_shell());
         let cmds = shell.split_whitespace().collect::<Vec<&str>>();
         if let [base_cmd, args @ ..] = cmds.as_slice() {
             let cmd = Command::new(base_cmd)
                 .args(args)
                 .arg(&self.cmd)
                 .stdin(Stdio::null())
-                .stdout(Stdio::null())
+                .stdout(match capture {
+                    true => Stdio::piped(),
+                    false => Stdio::null(),
+                })
                 .stderr(Stdio::piped())
                 .spawn();
 
@@ -49,7 +62,7 @@ impl CommandBuilder {
                 )
                 .into());
             }
-            Ok(())
+            Ok(output.stdout)
         } else {
             Err(format!("Shell command is not properly formatted:\n{}", shell).into())
         }
@@ -0,0 +1,72 @@
+use std::collections::BTreeMap;
+
+/// Most words kept, dropping the least seen once there are more
+static MAX_WORDS: usize = 5000;
+/// Shorter words aren't worth completing
+static MIN_LEN: usize = 3;
+
+/// Words from result titles and past searches, like release groups and show
+/// names, looked up by how they start to complete searches
+#[derive(Default, Clone)]
+pub struct PrefixIndex {
+    /// Each word in lowercase, with how it was last written and how many times
+    /// it has been seen
+    words: BTreeMap<String, (String, usize)>,
+}
+
+impl PrefixIndex {
+    /// Add every word in `text` worth completing
+    pub fn add(&mut self, text: &str) {
+        for word in words(text) {
+            let entry = self
+                .words
+                .entry(word.to_lowercase())
+                .or_insert_with(|| (word.to_owned(), 0));
+            word.clone_into(&mut entry.0);
+            entry.1 += 1;
+        }
+        while self.words.len() > MAX_WORDS {
+            let Some(least) = self
+                .words
+                .iter()
+                .min_by_key(|(_, (_, count))| *count)
+                .map(|(key, _)| key.to_owned())
+            else {
+                break;
+            };
+            self.words.remove(&least);
+        }
+    }
+
+    /// Words starting with `prefix` ignoring case, most seen first
+    pub fn complete(&self, prefix: &str) -> Vec<String> {
+        let prefix = prefix.to_lowercase();
+        if prefix.is_empty() {
+            return vec![];
+        }
+        let mut found: Vec<&(String, usize)> = self
+            .words
+            .range(prefix.clone()..)
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .filter(|(key, _)| **key != prefix)
+            .map(|(_, word)| word)
+            .collect();
+        // Stable, so words seen as often stay in alphabetical order
+        found.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        found.into_iter().map(|(word, _)| word.to_owned()).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+}
+
+fn words(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| c.is_whitespace() || "[](){}|,_/+~".contains(c))
+        .map(|w| w.trim_matches(|c: char| !c.is_alphanumeric()))
+        .filter(|w| w.chars().count() >= MIN_LEN && !w.chars().all(|c| c.is_ascii_digit()))
+}
@@ -1,5 +1,50 @@
+use std::sync::Mutex;
+
 use crossterm::event::{KeyCode, KeyModifiers, MediaKeyCode, ModifierKeyCode};
 use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// How sizes are shown, from `[size_format]`
+static SIZE_FORMAT: Mutex<SizeFormat> = Mutex::new(SizeFormat::DEFAULT);
+/// Sizes can't be shown more precisely than this
+pub static MAX_DECIMALS: usize = 3;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum SizeUnits {
+    /// Powers of 1024, e.g. `1.4 GiB`
+    Binary,
+    /// Powers of 1000, e.g. `1.5 GB`
+    Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(default)]
+pub struct SizeFormat {
+    pub units: SizeUnits,
+    /// Digits after the decimal point, up to `MAX_DECIMALS`
+    pub decimals: usize,
+}
+
+impl SizeFormat {
+    const DEFAULT: SizeFormat = SizeFormat {
+        units: SizeUnits::Binary,
+        decimals: 1,
+    };
+}
+
+impl Default for SizeFormat {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Show every size from now on with `fmt`
+pub fn set_size_format(fmt: SizeFormat) {
+    if let Ok(mut size_format) = SIZE_FORMAT.lock() {
+        *size_format = fmt;
+    }
+}
 
 pub fn add_protocol<S: Into<String>>(url: S, default_https: bool) -> String {
     let protocol = match default_https {
@@ -15,19 +60,70 @@ pub fn add_protocol<S: Into<String>>(url: S, default_https: bool) -> String {
     }
 }
 
-pub fn to_bytes(size: &str) -> usize {
-    let mut split = size.split_whitespace();
-    let b = split.next().unwrap_or("0");
-    let unit = split.last().unwrap_or("B");
+/// Bytes in a size shown by a source, like `1.4 GiB`, `700MB` or
+/// `1,024.5 KiB`. Units are read as powers of 1024, since sources showing `GB`
+/// mostly mean `GiB`.
+pub fn to_bytes(size: &str) -> u64 {
+    let size = size.trim().replace(',', "");
+    let split = size
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(size.len());
+    let (b, unit) = size.split_at(split);
     let f = b.parse::<f64>().unwrap_or(0.0);
-    let power = match unit.chars().next().unwrap_or('B') {
+    let power = match unit
+        .trim()
+        .chars()
+        .next()
+        .unwrap_or('B')
+        .to_ascii_uppercase()
+    {
         'T' => 4,
         'G' => 3,
         'M' => 2,
         'K' => 1,
         _ => 0,
     };
-    (1024_f64.powi(power) * f) as usize
+    (1024_f64.powi(power) * f) as u64
+}
+
+/// The hex info hash of a magnet link, in lowercase
+pub fn info_hash(magnet: &str) -> Option<String> {
+    let (_, rest) = magnet.split_once("xt=urn:btih:")?;
+    let hash = rest.split('&').next()?;
+    match hash.len() == 40 && hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        true => Some(hash.to_lowercase()),
+        false => None,
+    }
+}
+
+/// Format a number of bytes with the size format from the config, e.g.
+/// `1.4 MiB`
+pub fn to_size(bytes: u64) -> String {
+    let fmt = SIZE_FORMAT.lock().map(|f| *f).unwrap_or_default();
+    format_size(bytes, fmt)
+}
+
+/// Format a number of bytes with `fmt`, e.g. `1.4 MiB` or `1.47 MB`
+pub fn format_size(bytes: u64, fmt: SizeFormat) -> String {
+    let (base, units) = match fmt.units {
+        SizeUnits::Binary => (1024.0, ["B", "KiB", "MiB", "GiB", "TiB"]),
+        SizeUnits::Decimal => (1000.0, ["B", "kB", "MB", "GB", "TB"]),
+    };
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= base && unit < units.len() - 1 {
+        size /= base;
+        unit += 1;
+    }
+    match unit {
+        0 => format!("{} B", bytes),
+        _ => format!(
+            "{:.*} {}",
+            fmt.decimals.min(MAX_DECIMALS),
+            size,
+            units[unit]
+        ),
+    }
 }
 
 pub fn shorten_number(n: u32) -> String {
@@ -0,0 +1,219 @@
+use std::{
+    fs,
+    io::{self, BufRead as _, BufReader},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use chrono::Local;
+use directories::ProjectDirs;
+use regex::Regex;
+
+use crate::{
+    app::{Context, APP_NAME},
+    config::Config,
+    util::webhook::WebhookEvent,
+};
+
+/// How many lines from the end of the log to put in a crash report
+pub static LOG_LINES: usize = 100;
+static REDACTED: &str = "\"<redacted>\"";
+/// Config options holding credentials, matched anywhere in their name
+static SENSITIVE: &[&str] = &[
+    "password", "username", "token", "secret", "cookie", "api_key", "apikey", "auth",
+];
+
+/// What was known about the app when it crashed, kept up to date while it
+/// runs since nothing can be asked of it from the panic hook
+struct CrashState {
+    summary: String,
+    config: String,
+    /// Values from the config to leave out of the log
+    secrets: Vec<String>,
+    log: Option<PathBuf>,
+}
+
+static STATE: Mutex<CrashState> = Mutex::new(CrashState {
+    summary: String::new(),
+    config: String::new(),
+    secrets: Vec::new(),
+    log: None,
+});
+
+/// Remember the state of the app, to describe it if it crashes
+pub fn set_state(ctx: &Context) {
+    let summary = summary(ctx);
+    if let Ok(mut state) = STATE.lock() {
+        state.summary = summary;
+    }
+}
+
+/// Remember the config, with credentials left out
+pub fn set_config(config: &Config) {
+    let Ok(toml) = toml::to_string(config) else {
+        return;
+    };
+    let mut secrets = vec![];
+    if let Ok(value) = toml::Value::try_from(config) {
+        find_secrets(&value, "", &mut secrets);
+    }
+    for hook in config.webhooks.iter() {
+        secrets.extend(WebhookEvent::ALL.into_iter().map(|e| hook.url_for(e)));
+    }
+    // Longest first, so a secret containing another is left out whole
+    secrets.sort_by_key(|s| std::cmp::Reverse(s.len()));
+    let redacted = redact(&toml);
+    if let Ok(mut state) = STATE.lock() {
+        state.config = redacted;
+        state.secrets = secrets;
+    }
+}
+
+fn is_sensitive(key: &str) -> bool {
+    let name = key.to_lowercase();
+    SENSITIVE.iter().any(|s| name.contains(s))
+}
+
+/// Collect the values of options holding credentials in `value`
+fn find_secrets(value: &toml::Value, key: &str, found: &mut Vec<String>) {
+    match value {
+        toml::Value::String(s) if !s.is_empty() && is_sensitive(key) => found.push(s.to_owned()),
+        toml::Value::Table(t) => t.iter().for_each(|(k, v)| find_secrets(v, k, found)),
+        toml::Value::Array(a) => a.iter().for_each(|v| find_secrets(v, key, found)),
+        _ => {}
+    }
+}
+
+/// Leave `secrets` and the user and password of any URL out of `log`
+pub fn redact_log(log: &str, secrets: &[String]) -> String {
+    let userinfo = Regex::new(r"://[^/@\s]+@").unwrap();
+    let log = secrets.iter().fold(log.to_owned(), |log, s| {
+        log.replace(s.as_str(), "<redacted>")
+    });
+    userinfo.replace_all(&log, "://<redacted>@").into_owned()
+}
+
+/// Remember where the log is, to put its last lines in a crash report
+pub fn set_log_path(path: PathBuf) {
+    if let Ok(mut state) = STATE.lock() {
+        state.log = Some(path);
+    }
+}
+
+/// Where crash reports are written, the state dir if the OS has one
+pub fn dir() -> Option<PathBuf> {
+    let dirs = ProjectDirs::from("rs", "", APP_NAME)?;
+    Some(
+        dirs.state_dir()
+            .unwrap_or_else(|| dirs.data_local_dir())
+            .to_owned(),
+    )
+}
+
+/// One line for each thing which may help tell what led to a crash
+pub fn summary(ctx: &Context) -> String {
+    let search = &ctx.results.search;
+    [
+        format!("mode: {}", ctx.mode),
+        format!(
+            "load: {}",
+            ctx.load_type
+                .as_ref()
+                .map(|l| l.to_string())
+                .unwrap_or_default()
+        ),
+        format!("source: {}", ctx.src.name(&ctx.config.sources)),
+        format!("client: {}", ctx.client),
+        format!("query: {:?}", search.query),
+        format!("user: {}", ctx.user.as_deref().unwrap_or_default()),
+        format!("browsing: {}", ctx.browsing),
+        format!("page: {}/{}", ctx.page, ctx.results.response.last_page),
+        format!("results: {}", ctx.results.response.items.len()),
+        format!("batch: {}", ctx.batch.len()),
+        format!("downloads: {}", ctx.downloads),
+    ]
+    .join("\n")
+}
+
+/// Replace the values of options holding credentials, webhook URLs, and the
+/// user and password of any URL
+pub fn redact(config: &str) -> String {
+    let userinfo = Regex::new(r"://[^/@\s]+@").unwrap();
+    let mut table = "";
+    config
+        .lines()
+        .map(|line| {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with('[') {
+                table = trimmed;
+                return line.to_owned();
+            }
+            let Some((key, _)) = line.split_once(" = ") else {
+                return line.to_owned();
+            };
+            let name = key.trim().to_lowercase();
+            let secret = is_sensitive(&name) || (table.contains("webhooks") && name == "url");
+            match secret {
+                true => format!("{} = {}", key, REDACTED),
+                false => userinfo.replace_all(line, "://<redacted>@").into_owned(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// The last `n` lines of the file at `path`
+pub fn tail(path: impl AsRef<Path>, n: usize) -> io::Result<Vec<String>> {
+    let lines = BufReader::new(fs::File::open(path)?)
+        .lines()
+        .collect::<io::Result<Vec<String>>>()?;
+    Ok(lines[lines.len().saturating_sub(n)..].to_vec())
+}
+
+/// A crash report for `panic`, with everything remembered of the app
+pub fn report(panic: &str, backtrace: &str) -> String {
+    // The hook runs before unwinding, so the lock may be held by the thread
+    // which panicked
+    let state = STATE.try_lock();
+    let (summary, config, secrets, log) = match state.as_ref() {
+        Ok(s) => (
+            s.summary.as_str(),
+            s.config.as_str(),
+            s.secrets.as_slice(),
+            s.log.as_ref(),
+        ),
+        Err(_) => ("", "", [].as_slice(), None),
+    };
+    let log = match log.map(|p| tail(p, LOG_LINES)) {
+        Some(Ok(lines)) => redact_log(&lines.join("\n"), secrets),
+        Some(Err(e)) => format!("Failed to read the log:\n{}", e),
+        None => String::new(),
+    };
+    [
+        format!(
+            "nyaa v{} crashed on {} at {}",
+            env!("CARGO_PKG_VERSION"),
+            std::env::consts::OS,
+            Local::now().format("%Y-%m-%d %H:%M:%S")
+        ),
+        format!("## Panic\n{}", panic),
+        format!("## Backtrace\n{}", backtrace.trim_end()),
+        format!("## State\n{}", summary),
+        format!("## Log (last {} lines)\n{}", LOG_LINES, log),
+        format!("## Config\n{}", config),
+    ]
+    .join("\n\n")
+        + "\n"
+}
+
+/// Write `report` to a new file in `dir`, returning its path
+pub fn write(dir: impl AsRef<Path>, report: &str) -> io::Result<PathBuf> {
+    let dir = dir.as_ref();
+    fs::create_dir_all(dir)?;
+    let path = dir.join(format!(
+        "crash-{}.txt",
+        Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    fs::write(&path, report)?;
+    Ok(path)
+}
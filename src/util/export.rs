@@ -0,0 +1,176 @@
+use std::{fmt::Write as _, fs, path::PathBuf};
+
+use chrono::Local;
+
+use crate::source::Item;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ExportFormat {
+    Html,
+    Markdown,
+}
+
+impl ExportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Html => "html",
+            ExportFormat::Markdown => "md",
+        }
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Escape the characters markdown would read as formatting or table cells
+fn escape_markdown(text: &str) -> String {
+    text.chars().fold(String::new(), |mut s, c| {
+        if "\\`*_[]<>|".contains(c) {
+            s.push('\\');
+        }
+        s.push(c);
+        s
+    })
+}
+
+/// One line describing `item`, marked with `[T]` or `[R]` if it's trusted or
+/// a remake, for `--plain`
+pub fn plain_line(item: &Item) -> String {
+    let title = match item.item_type.marker() {
+        Some(marker) => format!("{} {}", marker, item.title),
+        None => item.title.to_owned(),
+    };
+    format!(
+        "{}, {}, {} seeders, {} leechers, {}, {}",
+        title,
+        item.size(),
+        item.seeders,
+        item.leechers,
+        item.date,
+        item.post_link
+    )
+}
+
+/// A page listing each item with its size, seeders, date and links, to share
+/// the batch with others
+pub fn batch_page(items: &[Item], format: ExportFormat) -> String {
+    let total = items.iter().fold(0, |acc, i| acc + i.bytes);
+    let summary = format!("{} torrents, {}", items.len(), super::conv::to_size(total));
+    match format {
+        ExportFormat::Html => html_page(items, &summary),
+        ExportFormat::Markdown => markdown_page(items, &summary),
+    }
+}
+
+fn html_page(items: &[Item], summary: &str) -> String {
+    let mut rows = String::new();
+    for item in items {
+        let mut links = vec![];
+        if !item.magnet_link.is_empty() {
+            links.push(format!(
+                "<a href=\"{}\">magnet</a>",
+                escape_html(&item.magnet_link)
+            ));
+        }
+        if !item.torrent_link.is_empty() {
+            links.push(format!(
+                "<a href=\"{}\">torrent</a>",
+                escape_html(&item.torrent_link)
+            ));
+        }
+        let title = match item.post_link.is_empty() {
+            true => escape_html(&item.title),
+            false => format!(
+                "<a href=\"{}\">{}</a>",
+                escape_html(&item.post_link),
+                escape_html(&item.title)
+            ),
+        };
+        let _ = writeln!(
+            rows,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            title,
+            escape_html(&item.size()),
+            item.seeders,
+            escape_html(&item.date.to_string()),
+            links.join(" ")
+        );
+    }
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>nyaa batch</title>
+<style>
+body {{ font-family: sans-serif; }}
+table {{ border-collapse: collapse; }}
+th, td {{ padding: 4px 8px; border-bottom: 1px solid #ccc; text-align: left; }}
+</style>
+</head>
+<body>
+<h1>nyaa batch</h1>
+<p>{}</p>
+<table>
+<tr><th>Name</th><th>Size</th><th>Seeders</th><th>Date</th><th>Links</th></tr>
+{}</table>
+</body>
+</html>
+"#,
+        escape_html(summary),
+        rows
+    )
+}
+
+fn markdown_page(items: &[Item], summary: &str) -> String {
+    let mut page = format!(
+        "# nyaa batch\n\n{}\n\n| Name | Size | Seeders | Date | Links |\n| --- | --- | --- | --- | --- |\n",
+        summary
+    );
+    for item in items {
+        let mut links = vec![];
+        if !item.magnet_link.is_empty() {
+            links.push(format!("[magnet](<{}>)", item.magnet_link));
+        }
+        if !item.torrent_link.is_empty() {
+            links.push(format!("[torrent](<{}>)", item.torrent_link));
+        }
+        let title = match item.post_link.is_empty() {
+            true => escape_markdown(&item.title),
+            false => format!("[{}](<{}>)", escape_markdown(&item.title), item.post_link),
+        };
+        let _ = writeln!(
+            page,
+            "| {} | {} | {} | {} | {} |",
+            title,
+            item.size(),
+            item.seeders,
+            item.date,
+            links.join(" ")
+        );
+    }
+    page
+}
+
+/// Write the page for `items` into `dir`, returning the path it was written to
+pub fn write_batch_page(
+    items: &[Item],
+    format: ExportFormat,
+    dir: &str,
+) -> Result<PathBuf, String> {
+    let dir = PathBuf::from(shellexpand::tilde(dir).to_string());
+    let name = format!(
+        "nyaa-batch-{}.{}",
+        Local::now().format("%Y%m%d-%H%M%S"),
+        format.extension()
+    );
+    let path = dir.join(name);
+    fs::create_dir_all(&dir)
+        .and_then(|_| fs::write(&path, batch_page(items, format)))
+        .map_err(|e| format!("Failed to export the batch to {}:\n{}", path.display(), e))?;
+    Ok(path)
+}
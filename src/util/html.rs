@@ -1,6 +1,6 @@
 use std::str::FromStr;
 
-use scraper::{ElementRef, Selector};
+use scraper::{selector::ToCss as _, ElementRef, Html, Selector};
 
 pub fn as_type<T: FromStr + Default>(s: String) -> Option<T> {
     s.chars()
@@ -24,3 +24,25 @@ pub fn attr(e: ElementRef, s: &Selector, attr: &str) -> String {
         .unwrap_or("")
         .to_owned()
 }
+
+/// Check the selectors used for scraping a page, returning a warning listing
+/// every selector that matched nothing. Column selectors only count as
+/// unmatched if they match nothing in every row.
+pub fn check_selectors(doc: &Html, rows: &Selector, cols: &[&Selector]) -> Option<String> {
+    let items: Vec<ElementRef> = doc.select(rows).collect();
+    let unmatched: Vec<String> = match items.is_empty() {
+        true => vec![rows.to_css_string()],
+        false => cols
+            .iter()
+            .filter(|s| items.iter().all(|i| i.select(s).next().is_none()))
+            .map(|s| s.to_css_string())
+            .collect(),
+    };
+    match unmatched.is_empty() {
+        true => None,
+        false => Some(format!(
+            "Selectors matched nothing, site layout may have changed:\n{}",
+            unmatched.join("\n")
+        )),
+    }
+}
@@ -0,0 +1,38 @@
+use std::sync::Arc;
+
+use reqwest::StatusCode;
+use tokio::{sync::Semaphore, task::JoinSet};
+
+use crate::{
+    source::{Item, ItemId},
+    util::stats,
+};
+
+// Most HEAD requests in flight at once
+static MAX_CONCURRENT_CHECKS: usize = 8;
+
+/// Send a HEAD request for each torrent link, returning the ids of those
+/// which are gone. Links which can't be reached at all are not counted, since
+/// the source may just be slow.
+pub async fn dead_links(client: &reqwest::Client, items: Vec<Item>) -> Vec<ItemId> {
+    let permits = Arc::new(Semaphore::new(MAX_CONCURRENT_CHECKS));
+    let mut set = JoinSet::new();
+    for item in items.into_iter().filter(|i| !i.torrent_link.is_empty()) {
+        let (client, permits) = (client.clone(), permits.clone());
+        set.spawn(async move {
+            let _permit = permits.acquire().await.ok()?;
+            let res = stats::send(client.head(&item.torrent_link)).await.ok()?;
+            match res.status() {
+                StatusCode::NOT_FOUND | StatusCode::GONE => Some(item.id),
+                _ => None,
+            }
+        });
+    }
+    let mut dead = vec![];
+    while let Some(res) = set.join_next().await {
+        if let Ok(Some(id)) = res {
+            dead.push(id);
+        }
+    }
+    dead
+}
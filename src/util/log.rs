@@ -0,0 +1,15 @@
+use std::{
+    fs::OpenOptions,
+    io::{self, Write as _},
+    path::Path,
+};
+
+use chrono::Local;
+
+pub static LOG_FILE: &str = "nyaa.log";
+
+/// Append a timestamped message to the log file
+pub fn append(path: impl AsRef<Path>, msg: &str) -> io::Result<()> {
+    let mut f = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(f, "[{}] {}", Local::now().format("%Y-%m-%d %H:%M:%S"), msg)
+}
@@ -0,0 +1,95 @@
+use std::{collections::BTreeMap, sync::Mutex, time::Duration};
+
+use serde::Serialize;
+
+/// How searches on one source have gone since nyaa opened
+#[derive(Serialize, Clone, Default, Debug, PartialEq)]
+pub struct SourceStats {
+    pub searches: u64,
+    pub failures: u64,
+    /// Time spent waiting for every search, in milliseconds
+    pub total_latency_ms: u64,
+}
+
+impl SourceStats {
+    pub fn average_latency_ms(&self) -> u64 {
+        self.total_latency_ms
+            .checked_div(self.searches)
+            .unwrap_or_default()
+    }
+}
+
+/// What nyaa has done since it opened, shown with `:` and written on exit with
+/// `--stats-json <file>`
+#[derive(Serialize, Clone, Default, Debug, PartialEq)]
+pub struct Stats {
+    pub searches: u64,
+    /// HTTP requests to sources, including logins and extra pages, and for
+    /// torrent files, link checks, webhooks and updates. Requests made to
+    /// download clients aren't counted.
+    pub requests: u64,
+    /// Requests answered from what was loaded before, without sending
+    /// anything
+    pub cache_hits: u64,
+    /// Bytes of torrent files downloaded
+    pub bytes_downloaded: u64,
+    /// Keyed by source name
+    pub sources: BTreeMap<String, SourceStats>,
+}
+
+static STATS: Mutex<Stats> = Mutex::new(Stats {
+    searches: 0,
+    requests: 0,
+    cache_hits: 0,
+    bytes_downloaded: 0,
+    sources: BTreeMap::new(),
+});
+
+fn update(f: impl FnOnce(&mut Stats)) {
+    if let Ok(mut stats) = STATS.lock() {
+        f(&mut stats);
+    }
+}
+
+/// Count a search on `source` which took `latency` to finish or fail
+pub fn record_search(source: &str, latency: Duration, ok: bool) {
+    update(|stats| {
+        stats.searches += 1;
+        let src = stats.sources.entry(source.to_owned()).or_default();
+        src.searches += 1;
+        src.total_latency_ms += latency.as_millis() as u64;
+        if !ok {
+            src.failures += 1;
+        }
+    });
+}
+
+/// Send `request`, counting it. Every request nyaa makes itself goes through
+/// here, so none are missed.
+pub async fn send(request: reqwest::RequestBuilder) -> reqwest::Result<reqwest::Response> {
+    update(|stats| stats.requests += 1);
+    request.send().await
+}
+
+/// Count a request which didn't have to be sent, since it was answered from
+/// what was loaded before
+pub fn record_cache_hit() {
+    update(|stats| stats.cache_hits += 1);
+}
+
+pub fn record_download(bytes: u64) {
+    update(|stats| stats.bytes_downloaded += bytes);
+}
+
+pub fn snapshot() -> Stats {
+    STATS.lock().map(|s| s.clone()).unwrap_or_default()
+}
+
+/// Forget everything recorded so far
+pub fn reset() {
+    update(|stats| *stats = Stats::default());
+}
+
+pub fn to_json(stats: &Stats) -> String {
+    serde_json::to_string_pretty(stats).unwrap_or_default()
+}
@@ -0,0 +1,49 @@
+use std::{collections::HashMap, sync::OnceLock};
+
+use regex::{Captures, Regex};
+
+/// `{name}` or `{name:02}` for a variable zero padded to 2 digits, and `{{`
+/// or `}}` for a literal brace
+fn pattern() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"\{\{|\}\}|\{([A-Za-z_]\w*)(?::0(\d+))?\}").unwrap())
+}
+
+/// Names of the variables in a query template like
+/// `{title} S{season:02} 1080p`, in the order they first appear
+pub fn variables(template: &str) -> Vec<String> {
+    let mut vars: Vec<String> = vec![];
+    for name in pattern()
+        .captures_iter(template)
+        .filter_map(|c| c.get(1).map(|m| m.as_str()))
+    {
+        if !vars.iter().any(|v| v == name) {
+            vars.push(name.to_owned());
+        }
+    }
+    vars
+}
+
+/// Replace each variable in `template` with its value. Numbers are padded
+/// with zeros up to the width given, and missing variables are left empty.
+/// Queries without any variables are searched as they are.
+pub fn expand(template: &str, values: &HashMap<String, String>) -> String {
+    if variables(template).is_empty() {
+        return template.to_owned();
+    }
+    pattern()
+        .replace_all(template, |c: &Captures| match (c.get(1), c.get(2)) {
+            (Some(name), width) => {
+                let value = values.get(name.as_str()).map(|v| v.trim()).unwrap_or("");
+                let width = width.and_then(|w| w.as_str().parse().ok()).unwrap_or(0);
+                match !value.is_empty() && value.chars().all(|c| c.is_ascii_digit()) {
+                    true => format!("{:0>width$}", value, width = width),
+                    false => value.to_owned(),
+                }
+            }
+            (None, _) => c[0][..1].to_owned(),
+        })
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
@@ -1,53 +1,224 @@
-use std::io::{self, stdout};
+use std::{
+    error::Error,
+    fmt,
+    io::{self, stdin, stdout, IsTerminal as _, Write as _},
+    sync::atomic::{AtomicBool, Ordering},
+};
 
 use crossterm::{
     cursor::SetCursorStyle,
-    event::{DisableBracketedPaste, EnableBracketedPaste},
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
-    ExecutableCommand as _,
+    event::{
+        self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+        Event, KeyCode, KeyEventKind, KeyModifiers,
+    },
+    terminal::{
+        disable_raw_mode, enable_raw_mode, is_raw_mode_enabled, EnterAlternateScreen,
+        LeaveAlternateScreen, SetTitle,
+    },
+    Command, ExecutableCommand as _,
 };
+use ratatui::{backend::Backend, Terminal};
 
+#[cfg(windows)]
+use crossterm::style::Print;
 #[cfg(unix)]
 use nix::{
     sys::signal::{self, Signal},
     unistd::Pid,
 };
-#[cfg(unix)]
-use ratatui::{backend::Backend, Terminal};
-#[cfg(unix)]
-use std::error::Error;
+
+// Set while the terminal has been handed back to the shell, so the event
+// reader does not steal input meant for it
+static SUSPENDED: AtomicBool = AtomicBool::new(false);
+
+pub fn is_suspended() -> bool {
+    SUSPENDED.load(Ordering::Relaxed)
+}
+
+// Whether clicks are reported, so it can be turned back on after suspending
+static MOUSE: AtomicBool = AtomicBool::new(false);
+
+/// Save the window title on the terminal's title stack, for [`PopTitle`] to
+/// restore. Terminals without a stack ignore it.
+struct PushTitle;
+
+impl Command for PushTitle {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        f.write_str("\x1b[22;0t")
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Restore the window title saved by [`PushTitle`]
+struct PopTitle;
+
+impl Command for PopTitle {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        f.write_str("\x1b[23;0t")
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> io::Result<()> {
+        Ok(())
+    }
+}
 
 pub fn setup_terminal() -> io::Result<()> {
     enable_raw_mode()?;
     stdout().execute(EnableBracketedPaste)?;
     stdout().execute(EnterAlternateScreen)?;
     stdout().execute(SetCursorStyle::SteadyBar)?;
+    stdout().execute(PushTitle)?;
+    if MOUSE.load(Ordering::Relaxed) {
+        stdout().execute(EnableMouseCapture)?;
+    }
     Ok(())
 }
 
-pub fn reset_terminal() -> io::Result<()> {
+/// Report mouse clicks as events, or stop reporting them. Most terminals
+/// still select text while Shift is held.
+pub fn set_mouse(on: bool) -> io::Result<()> {
+    MOUSE.store(on, Ordering::Relaxed);
+    match on {
+        true => stdout().execute(EnableMouseCapture).map(|_| ()),
+        false => stdout().execute(DisableMouseCapture).map(|_| ()),
+    }
+}
+
+pub fn set_title(title: &str) -> io::Result<()> {
+    stdout().execute(SetTitle(title)).map(|_| ())
+}
+
+/// Ring the terminal bell, which some terminals show by flashing the window
+pub fn bell() -> io::Result<()> {
+    let mut out = stdout();
+    out.write_all(b"\x07")?;
+    out.flush()
+}
+
+/// Read a line from the terminal without showing what's typed, for secrets.
+/// Input which isn't from a terminal, like a pipe, is read as it is.
+pub fn read_hidden() -> io::Result<String> {
+    if !stdin().is_terminal() {
+        let mut line = String::new();
+        stdin().read_line(&mut line)?;
+        return Ok(line.trim_end_matches(['\r', '\n']).to_owned());
+    }
+    enable_raw_mode()?;
+    let mut line = String::new();
+    let res = loop {
+        let key = match event::read() {
+            Ok(Event::Key(key)) if key.kind != KeyEventKind::Release => key,
+            Ok(_) => continue,
+            Err(e) => break Err(e),
+        };
+        match (key.code, key.modifiers) {
+            (KeyCode::Enter, _) => break Ok(line),
+            (KeyCode::Esc, _) | (KeyCode::Char('c'), KeyModifiers::CONTROL) => {
+                break Err(io::Error::new(io::ErrorKind::Interrupted, "Cancelled"))
+            }
+            (KeyCode::Backspace, _) => {
+                line.pop();
+            }
+            (KeyCode::Char(c), m) if !m.contains(KeyModifiers::CONTROL) => line.push(c),
+            _ => {}
+        }
+    };
     disable_raw_mode()?;
-    stdout().execute(SetCursorStyle::DefaultUserShape)?;
-    stdout().execute(LeaveAlternateScreen)?;
-    stdout().execute(DisableBracketedPaste)?;
-    Ok(())
+    eprintln!();
+    res
+}
+
+pub fn reset_terminal() -> io::Result<()> {
+    // Attempt every step, even if an earlier one fails, so the terminal is
+    // left as usable as possible. Only the first error is reported.
+    let results = [
+        match is_raw_mode_enabled() {
+            Ok(false) => Ok(()),
+            _ => disable_raw_mode(),
+        },
+        stdout()
+            .execute(SetCursorStyle::DefaultUserShape)
+            .map(|_| ()),
+        stdout().execute(LeaveAlternateScreen).map(|_| ()),
+        stdout().execute(DisableBracketedPaste).map(|_| ()),
+        stdout().execute(DisableMouseCapture).map(|_| ()),
+        stdout().execute(PopTitle).map(|_| ()),
+    ];
+    results.into_iter().collect()
+}
+
+/// Restore the terminal when the process is asked to terminate from outside
+/// (SIGTERM, SIGHUP, closing the console window), then exit.
+pub async fn reset_on_terminate() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        let (Ok(mut term), Ok(mut hup), Ok(mut int)) = (
+            signal(SignalKind::terminate()),
+            signal(SignalKind::hangup()),
+            signal(SignalKind::interrupt()),
+        ) else {
+            return;
+        };
+        tokio::select! {
+            _ = term.recv() => {},
+            _ = hup.recv() => {},
+            _ = int.recv() => {},
+        };
+    }
+    #[cfg(windows)]
+    {
+        use tokio::signal::windows::{ctrl_break, ctrl_close};
+        let (Ok(mut close), Ok(mut brk)) = (ctrl_close(), ctrl_break()) else {
+            return;
+        };
+        tokio::select! {
+            _ = close.recv() => {},
+            _ = brk.recv() => {},
+        };
+    }
+    let _ = reset_terminal();
+    std::process::exit(1);
 }
 
 #[cfg(unix)]
 pub fn suspend_self<B: Backend>(terminal: &mut Terminal<B>) -> Result<(), Box<dyn Error>> {
     // Make sure cursor is drawn
-
     terminal.draw(|f| f.set_cursor(0, 0))?;
 
+    SUSPENDED.store(true, Ordering::Relaxed);
     reset_terminal()?;
 
     signal::kill(Pid::from_raw(std::process::id() as i32), Signal::SIGTSTP)?;
     Ok(())
 }
 
-#[cfg(unix)]
+/// Windows has no job control, so instead start a nested shell (like vim does)
+/// and return to the app, with its state intact, once that shell exits.
+#[cfg(windows)]
+pub fn suspend_self<B: Backend>(terminal: &mut Terminal<B>) -> Result<(), Box<dyn Error>> {
+    // Make sure cursor is drawn
+    terminal.draw(|f| f.set_cursor(0, 0))?;
+
+    SUSPENDED.store(true, Ordering::Relaxed);
+    reset_terminal()?;
+
+    let shell = std::env::var("COMSPEC").unwrap_or("cmd.exe".to_owned());
+    // Through the same writer as the reset, so it lands on the main screen
+    // once raw mode is off
+    stdout().execute(Print("nyaa is suspended. Type \"exit\" to return.\n"))?;
+    std::process::Command::new(shell).status()?;
+    Ok(())
+}
+
 pub fn continue_self<B: Backend>(terminal: &mut Terminal<B>) -> Result<(), Box<dyn Error>> {
     setup_terminal()?;
+    SUSPENDED.store(false, Ordering::Relaxed);
 
     Terminal::clear(terminal)?;
     Ok(())
@@ -0,0 +1,74 @@
+use std::{
+    fmt::Write as _,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use sha1::{Digest, Sha1};
+
+// Deepest nesting of lists and dictionaries read, so a broken file can't
+// overflow the stack
+static MAX_DEPTH: usize = 64;
+
+/// Index just past the bencoded value starting at `pos`
+fn skip_value(data: &[u8], pos: usize, depth: usize) -> Option<usize> {
+    if depth > MAX_DEPTH {
+        return None;
+    }
+    match data.get(pos)? {
+        b'i' => Some(pos + data[pos..].iter().position(|b| *b == b'e')? + 1),
+        b'l' | b'd' => {
+            let mut pos = pos + 1;
+            while *data.get(pos)? != b'e' {
+                pos = skip_value(data, pos, depth + 1)?;
+            }
+            Some(pos + 1)
+        }
+        b'0'..=b'9' => {
+            let colon = pos + data[pos..].iter().position(|b| *b == b':')?;
+            let len: usize = std::str::from_utf8(&data[pos..colon]).ok()?.parse().ok()?;
+            let end = colon.checked_add(1)?.checked_add(len)?;
+            (end <= data.len()).then_some(end)
+        }
+        _ => None,
+    }
+}
+
+/// The info hash of a `.torrent` file, the SHA-1 of its `info` dictionary, as
+/// lowercase hex like in magnet links
+pub fn info_hash(data: &[u8]) -> Option<String> {
+    if data.first() != Some(&b'd') {
+        return None;
+    }
+    let mut pos = 1;
+    while *data.get(pos)? != b'e' {
+        let value = skip_value(data, pos, 1)?;
+        let end = skip_value(data, value, 1)?;
+        if &data[pos..value] == b"4:info" {
+            let hash = Sha1::digest(&data[value..end]);
+            return Some(hash.iter().fold(String::new(), |mut s, b| {
+                let _ = write!(s, "{:02x}", b);
+                s
+            }));
+        }
+        pos = end;
+    }
+    None
+}
+
+/// The first `.torrent` file in `dir` with the info hash `hash`
+pub fn find_duplicate(dir: &Path, hash: &str) -> Option<PathBuf> {
+    let mut paths: Vec<_> = fs::read_dir(dir)
+        .ok()?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.extension()
+                .is_some_and(|e| e.eq_ignore_ascii_case("torrent"))
+        })
+        .collect();
+    // Always report the same file if there are several
+    paths.sort();
+    paths
+        .into_iter()
+        .find(|p| fs::read(p).ok().and_then(|d| info_hash(&d)).as_deref() == Some(hash))
+}
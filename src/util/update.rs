@@ -0,0 +1,222 @@
+use std::{env, fmt::Write as _, fs, path::Path};
+
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use super::stats;
+
+/// The only target `make release` builds binaries for
+pub static RELEASE_TARGET: &str = "x86_64-unknown-linux-gnu";
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct Asset {
+    pub name: String,
+    pub browser_download_url: String,
+}
+
+/// A release on GitHub, with the binaries built for it
+#[derive(Deserialize, Clone, Debug)]
+pub struct Release {
+    pub tag_name: String,
+    #[serde(default)]
+    pub assets: Vec<Asset>,
+}
+
+impl Release {
+    pub fn version(&self) -> &str {
+        self.tag_name.trim_start_matches('v')
+    }
+
+    fn asset(&self, name: &str) -> Option<&Asset> {
+        self.assets.iter().find(|a| a.name == name)
+    }
+}
+
+/// `v1.10.0` as `[1, 10, 0]`, so versions compare in order
+pub fn parse_version(version: &str) -> Vec<u64> {
+    version
+        .trim_start_matches('v')
+        .split('.')
+        .map(|part| part.parse().unwrap_or_default())
+        .collect()
+}
+
+pub fn is_newer(version: &str, current: &str) -> bool {
+    parse_version(version) > parse_version(current)
+}
+
+/// The GitHub API url for the latest release of `repository`, like
+/// `https://github.com/Beastwick18/nyaa/`
+pub fn latest_release_url(repository: &str) -> Option<String> {
+    let (_, path) = repository
+        .trim_end_matches('/')
+        .trim_end_matches(".git")
+        .split_once("github.com/")?;
+    Some(format!(
+        "https://api.github.com/repos/{}/releases/latest",
+        path
+    ))
+}
+
+/// Whether releases have a binary for this platform
+pub fn has_release_binary() -> bool {
+    cfg!(all(
+        target_os = "linux",
+        target_arch = "x86_64",
+        target_env = "gnu"
+    ))
+}
+
+/// Name of the released binary, like `nyaa-0.9.0-x86_64-unknown-linux-gnu`
+pub fn asset_name(version: &str) -> String {
+    format!("nyaa-{}-{}", version, RELEASE_TARGET)
+}
+
+/// What installed `exe`, if it was a package manager which expects to update
+/// it too
+pub fn package_manager(exe: &Path) -> Option<&'static str> {
+    let exe = exe.canonicalize().unwrap_or_else(|_| exe.to_owned());
+    match exe {
+        e if e.starts_with("/nix/store") => Some("Nix"),
+        e if e.starts_with("/usr") && !e.starts_with("/usr/local") => {
+            Some("the system package manager")
+        }
+        _ => None,
+    }
+}
+
+/// Check `data` against a checksum file, which holds the SHA-256 of the binary
+/// in hex, optionally followed by its name like the output of `sha256sum`
+pub fn verify(data: &[u8], checksum: &str) -> Result<(), String> {
+    let expected = checksum
+        .split_whitespace()
+        .next()
+        .ok_or("The checksum file is empty")?
+        .to_lowercase();
+    let actual = Sha256::digest(data).iter().fold(String::new(), |mut s, b| {
+        let _ = write!(s, "{:02x}", b);
+        s
+    });
+    match actual == expected {
+        true => Ok(()),
+        false => Err(format!(
+            "Checksum mismatch, expected {} but got {}",
+            expected, actual
+        )),
+    }
+}
+
+pub async fn latest_release(client: &reqwest::Client) -> Result<Release, String> {
+    let url = latest_release_url(env!("CARGO_PKG_REPOSITORY"))
+        .ok_or("nyaa isn't released on GitHub, so there's nothing to check")?;
+    let request = client
+        .get(url)
+        .header("User-Agent", format!("nyaa/{}", env!("CARGO_PKG_VERSION")));
+    stats::send(request)
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Failed to get the latest release:\n{}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("Failed to read the latest release:\n{}", e))
+}
+
+/// The version of a newer release than this one, if there is one
+pub async fn check(client: &reqwest::Client) -> Result<Option<String>, String> {
+    let release = latest_release(client).await?;
+    Ok(
+        is_newer(release.version(), env!("CARGO_PKG_VERSION"))
+            .then(|| release.version().to_owned()),
+    )
+}
+
+async fn download(client: &reqwest::Client, asset: &Asset) -> Result<Vec<u8>, String> {
+    let bytes = stats::send(client.get(&asset.browser_download_url))
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Failed to download {}:\n{}", asset.name, e))?
+        .bytes()
+        .await
+        .map_err(|e| format!("Failed to download {}:\n{}", asset.name, e))?;
+    Ok(bytes.to_vec())
+}
+
+fn not_packaged(exe: &Path) -> Result<(), String> {
+    match package_manager(exe) {
+        Some(manager) => Err(format!(
+            "{}\nnyaa was installed by {}, so update it there instead",
+            exe.display(),
+            manager
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Put `data` in place of the binary at `exe`. The old binary is moved aside
+/// first, since Windows won't overwrite a running program. Binaries installed
+/// by a package manager are left alone.
+pub fn replace_exe(exe: &Path, data: &[u8]) -> Result<(), String> {
+    not_packaged(exe)?;
+    let new = exe.with_extension("new");
+    let old = exe.with_extension("old");
+    fs::write(&new, data)
+        .map_err(|e| format!("{}\nFailed to write the update:\n{}", new.display(), e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt as _;
+        fs::set_permissions(&new, fs::Permissions::from_mode(0o755))
+            .map_err(|e| format!("Failed to make the update executable:\n{}", e))?;
+    }
+    let _ = fs::remove_file(&old);
+    fs::rename(exe, &old)
+        .map_err(|e| format!("{}\nFailed to move the old binary:\n{}", exe.display(), e))?;
+    if let Err(e) = fs::rename(&new, exe) {
+        // Put the old binary back so nyaa still runs
+        let _ = fs::rename(&old, exe);
+        return Err(format!(
+            "{}\nFailed to replace the binary:\n{}",
+            exe.display(),
+            e
+        ));
+    }
+    let _ = fs::remove_file(&old);
+    Ok(())
+}
+
+/// Download the latest release for this platform and replace the running
+/// binary with it, once its checksum matches. Returns what happened.
+pub async fn self_update(client: &reqwest::Client) -> Result<String, String> {
+    if !has_release_binary() {
+        return Err(format!(
+            "Releases only have binaries for {}, so update nyaa the way it was installed",
+            RELEASE_TARGET
+        ));
+    }
+    let exe = env::current_exe().map_err(|e| format!("Failed to find the nyaa binary:\n{}", e))?;
+    not_packaged(&exe)?;
+    let release = latest_release(client).await?;
+    let current = env!("CARGO_PKG_VERSION");
+    if !is_newer(release.version(), current) {
+        return Ok(format!("nyaa v{} is already the latest version", current));
+    }
+    let name = asset_name(release.version());
+    let binary = release.asset(&name).ok_or(format!(
+        "Release {} has no binary for this platform ({})",
+        release.tag_name, name
+    ))?;
+    let checksum = release.asset(&format!("{}.sha256", name)).ok_or(format!(
+        "Release {} has no checksum for {}, so it can't be verified",
+        release.tag_name, name
+    ))?;
+
+    let data = download(client, binary).await?;
+    let checksum = String::from_utf8_lossy(&download(client, checksum).await?).into_owned();
+    verify(&data, &checksum)?;
+
+    replace_exe(&exe, &data)?;
+    Ok(format!(
+        "Updated nyaa from v{} to v{}",
+        current,
+        release.version()
+    ))
+}
@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app::LoadType,
+    error::NyaaError,
+    source::{Item, ItemId, SourceConfig, SourceResponse, Sources},
+    sync::SearchQuery,
+};
+
+/// An item kept to look at later, saved in the config as `[[watch_later]]`
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct WatchItem {
+    /// Shown as e.g. `nyaa-123`
+    pub id: String,
+    pub source: Sources,
+    pub title: String,
+    #[serde(default)]
+    pub post_link: String,
+    #[serde(default)]
+    pub torrent_link: String,
+    #[serde(default)]
+    pub magnet_link: String,
+    #[serde(default)]
+    pub file_name: String,
+    #[serde(default)]
+    pub bytes: u64,
+    #[serde(default)]
+    pub seeders: u32,
+    #[serde(default)]
+    pub leechers: u32,
+    #[serde(default)]
+    pub downloads: u32,
+}
+
+impl WatchItem {
+    pub fn new(item: &Item, source: Sources) -> Self {
+        WatchItem {
+            id: item.id.to_string(),
+            source,
+            title: item.title.to_owned(),
+            post_link: item.post_link.to_owned(),
+            torrent_link: item.torrent_link.to_owned(),
+            magnet_link: item.magnet_link.to_owned(),
+            file_name: item.file_name.to_owned(),
+            bytes: item.bytes,
+            seeders: item.seeders,
+            leechers: item.leechers,
+            downloads: item.downloads,
+        }
+    }
+
+    /// The item to put in the batch. Its date and category aren't kept, so
+    /// they're left as the defaults.
+    pub fn to_item(&self) -> Item {
+        Item {
+            id: ItemId::parse(&self.id).unwrap_or_default(),
+            title: self.title.to_owned(),
+            post_link: self.post_link.to_owned(),
+            torrent_link: self.torrent_link.to_owned(),
+            magnet_link: self.magnet_link.to_owned(),
+            file_name: self.file_name.to_owned(),
+            bytes: self.bytes,
+            seeders: self.seeders,
+            leechers: self.leechers,
+            downloads: self.downloads,
+            ..Default::default()
+        }
+    }
+}
+
+/// Search `src` for the title of each item, taking the seeders, leechers and
+/// downloads of the result with the same id. Items no longer found are left
+/// out.
+pub async fn refresh(
+    src: Sources,
+    client: &reqwest::Client,
+    items: Vec<WatchItem>,
+    config: &SourceConfig,
+) -> Result<Vec<WatchItem>, NyaaError> {
+    let mut refreshed = vec![];
+    for mut watch in items {
+        let search = SearchQuery {
+            query: watch.title.to_owned(),
+            page: 1,
+            category: src.default_category(config),
+            filter: src.default_filter(config),
+            sort: src.default_sort(config),
+            user: None,
+        };
+        // Only one variant without the captcha feature
+        #[allow(clippy::infallible_destructuring_match)]
+        let res = match src
+            .load(LoadType::Searching, client, &search, config, None)
+            .await?
+        {
+            SourceResponse::Results(res) => res,
+            #[cfg(feature = "captcha")]
+            SourceResponse::Captcha(_) => {
+                return Err(NyaaError::Other(format!(
+                    "{} asked for a captcha",
+                    src.name(config)
+                )))
+            }
+        };
+        if let Some(item) = res.items.iter().find(|i| i.id.to_string() == watch.id) {
+            watch.seeders = item.seeders;
+            watch.leechers = item.leechers;
+            watch.downloads = item.downloads;
+            refreshed.push(watch);
+        }
+    }
+    Ok(refreshed)
+}
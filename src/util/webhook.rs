@@ -0,0 +1,173 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::stats;
+
+/// Webhooks are given up on after this long, so a slow server can't pile up
+/// requests
+static WEBHOOK_TIMEOUT_SECS: u64 = 10;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookKind {
+    /// `{"content": "<message>"}`
+    Discord,
+    /// `{"text": "<message>"}`
+    Slack,
+    /// `{"event": "<event>", "message": "<message>", "titles": [...]}`
+    #[default]
+    Json,
+}
+
+/// A URL posted to when downloads finish, new results show up or errors
+/// happen, from `[[webhooks]]`
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct WebhookConfig {
+    /// Where to post, with `{event}` replaced by the name of the event
+    pub url: String,
+    pub kind: WebhookKind,
+    /// Message sent, with `{event}`, `{message}` and `{count}` replaced
+    pub template: Option<String>,
+    /// Post once torrents are sent to the download client
+    pub on_download: bool,
+    /// Post when reloading a search finds results which weren't there before
+    pub on_new_results: bool,
+    /// Post the message of each error shown. Errors from webhooks themselves
+    /// are only logged.
+    pub on_error: bool,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            kind: WebhookKind::Json,
+            template: None,
+            on_download: true,
+            on_new_results: true,
+            on_error: false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WebhookEvent {
+    Download,
+    NewResults,
+    Error,
+}
+
+impl WebhookEvent {
+    pub const ALL: [WebhookEvent; 3] = [
+        WebhookEvent::Download,
+        WebhookEvent::NewResults,
+        WebhookEvent::Error,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            WebhookEvent::Download => "download",
+            WebhookEvent::NewResults => "new_results",
+            WebhookEvent::Error => "error",
+        }
+    }
+}
+
+/// Something that happened, sent to each webhook listening for its event
+#[derive(Clone, Debug)]
+pub struct WebhookMessage {
+    pub event: WebhookEvent,
+    pub message: String,
+    /// Titles of the torrents it is about, if any
+    pub titles: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum Body<'a> {
+    Discord {
+        content: String,
+    },
+    Slack {
+        text: String,
+    },
+    Json {
+        event: &'static str,
+        message: String,
+        titles: &'a [String],
+    },
+}
+
+impl WebhookConfig {
+    pub fn wants(&self, event: WebhookEvent) -> bool {
+        match event {
+            WebhookEvent::Download => self.on_download,
+            WebhookEvent::NewResults => self.on_new_results,
+            WebhookEvent::Error => self.on_error,
+        }
+    }
+
+    pub fn url(&self, msg: &WebhookMessage) -> String {
+        self.url_for(msg.event)
+    }
+
+    pub fn url_for(&self, event: WebhookEvent) -> String {
+        self.url.replace("{event}", event.name())
+    }
+
+    /// Only the scheme and host of the URL, to name it in errors without the
+    /// token most services put in the path
+    pub fn host(&self) -> String {
+        match reqwest::Url::parse(&self.url) {
+            Ok(url) => format!("{}://{}", url.scheme(), url.host_str().unwrap_or_default()),
+            Err(_) => "<invalid url>".to_owned(),
+        }
+    }
+
+    /// The message to post, from `template` if set
+    pub fn text(&self, msg: &WebhookMessage) -> String {
+        match &self.template {
+            Some(template) => template
+                .replace("{event}", msg.event.name())
+                .replace("{message}", &msg.message)
+                .replace("{count}", &msg.titles.len().to_string()),
+            None => msg.message.to_owned(),
+        }
+    }
+}
+
+/// Post `msg` to `hook`
+pub async fn send(
+    client: &reqwest::Client,
+    hook: &WebhookConfig,
+    msg: &WebhookMessage,
+) -> Result<(), String> {
+    let url = hook.url(msg);
+    let text = hook.text(msg);
+    let body = match hook.kind {
+        WebhookKind::Discord => Body::Discord { content: text },
+        WebhookKind::Slack => Body::Slack { text },
+        WebhookKind::Json => Body::Json {
+            event: msg.event.name(),
+            message: text,
+            titles: &msg.titles,
+        },
+    };
+    let request = client
+        .post(&url)
+        .timeout(Duration::from_secs(WEBHOOK_TIMEOUT_SECS))
+        .json(&body);
+    let res = stats::send(request).await.map_err(|e| {
+        format!(
+            "Failed to post webhook to {}:\n{}",
+            hook.host(),
+            e.without_url()
+        )
+    })?;
+    match res.status() {
+        s if s.is_success() => Ok(()),
+        s => Err(format!("Webhook {} answered with {}", hook.host(), s)),
+    }
+}
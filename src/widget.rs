@@ -15,7 +15,11 @@ use ratatui::{
 use serde::{Deserialize, Serialize};
 use unicode_width::UnicodeWidthStr as _;
 
-use crate::{app::Context, style, theme::Theme};
+use crate::{
+    app::Context,
+    style,
+    theme::{Theme, WidgetKind},
+};
 
 #[cfg(feature = "captcha")]
 pub mod captcha;
@@ -23,18 +27,33 @@ pub mod captcha;
 pub mod batch;
 pub mod category;
 pub mod clients;
+pub mod columns;
+pub mod combo;
+pub mod confirm;
+pub mod dashboard;
+pub mod details;
+pub mod error;
 pub mod filter;
+pub mod find;
+pub mod focus;
 pub mod help;
+pub mod hints;
 pub mod input;
 pub mod notifications;
 pub mod notify_box;
 pub mod page;
+pub mod quit;
 pub mod results;
 pub mod search;
 pub mod sort;
 pub mod sources;
+pub mod stats;
+pub mod tasks;
+pub mod template;
 pub mod themes;
 pub mod user;
+pub mod watch_later;
+pub mod whats_new;
 
 pub trait Widget {
     fn draw(&mut self, buf: &mut Frame, ctx: &Context, area: Rect);
@@ -140,16 +159,42 @@ pub fn centered_rect(mut x_len: u16, mut y_len: u16, r: Rect) -> Rect {
     .split(popup_layout[1])[1]
 }
 
-pub fn border_block(theme: &Theme, focused: bool) -> Block {
-    Block::new()
-        .border_style(match focused {
-            true => style!(fg:theme.border_focused_color),
-            false => style!(fg:theme.border_color),
-        })
+pub fn border_block(theme: &Theme, focused: bool) -> Block<'_> {
+    widget_block(theme, focused, WidgetKind::Popup)
+}
+
+/// The block around `kind` of widget, with the colors the theme gives it
+pub fn widget_block(theme: &Theme, focused: bool, kind: WidgetKind) -> Block<'_> {
+    let block = Block::new()
+        .border_style(style!(fg:theme.border_of(kind, focused)))
         .bg(theme.bg)
         .fg(theme.fg)
         .borders(Borders::ALL)
-        .border_type(theme.border)
+        .border_type(theme.border);
+    match theme.widgets.get(kind).title {
+        Some(title) => block.title_style(style!(fg:title)),
+        None => block,
+    }
+}
+
+/// The block around a pane, which is only a title line in the minimal layout
+pub fn pane_block(ctx: &Context, focused: bool, kind: WidgetKind) -> Block<'_> {
+    match (ctx.config.layout.minimal, ctx.theme.widgets.get(kind).title) {
+        (true, Some(title)) => Block::new()
+            .bg(ctx.theme.bg)
+            .fg(ctx.theme.fg)
+            .title_style(style!(fg:title)),
+        (true, None) => Block::new().bg(ctx.theme.bg).fg(ctx.theme.fg),
+        (false, _) => widget_block(&ctx.theme, focused, kind),
+    }
+}
+
+/// Rows taken by a pane's borders and table header
+pub fn pane_header_height(ctx: &Context) -> usize {
+    match ctx.config.layout.minimal {
+        true => 2,
+        false => 3,
+    }
 }
 
 pub fn scrollbar(ctx: &Context, orientation: ScrollbarOrientation) -> Scrollbar<'_> {
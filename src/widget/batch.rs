@@ -1,22 +1,48 @@
 use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
-use human_bytes::human_bytes;
 use ratatui::{
     layout::{Constraint, Margin, Rect},
     style::{Style, Stylize},
-    widgets::{Clear, Row, ScrollbarOrientation, StatefulWidget, Table, Widget},
+    widgets::{Clear, LineGauge, Row, ScrollbarOrientation, StatefulWidget, Table, Widget},
     Frame,
 };
 
 use crate::{
     app::{Context, LoadType, Mode},
-    source::ItemType,
+    client::{download::DownloadConfig, progress},
+    style,
+    theme::WidgetKind,
     title,
+    util::{
+        conv::to_size,
+        export::{self, ExportFormat},
+    },
 };
 
-use super::{border_block, Corner, VirtualStatefulTable};
+use super::{focus::Pane, pane_block, Corner, VirtualStatefulTable};
+
+/// Write a page with the links of every item in the batch
+fn export(ctx: &mut Context, format: ExportFormat) {
+    if ctx.batch.is_empty() {
+        ctx.notify("The batch is empty");
+        return;
+    }
+    let dir = ctx
+        .config
+        .batch_export_dir
+        .clone()
+        .unwrap_or_else(|| DownloadConfig::default().save_dir);
+    match export::write_batch_page(&ctx.batch, format, &dir) {
+        Ok(path) => ctx.notify(format!(
+            "Exported {} torrents to {}",
+            ctx.batch.len(),
+            path.display()
+        )),
+        Err(e) => ctx.show_error(e),
+    }
+}
 
 pub struct BatchWidget {
-    table: VirtualStatefulTable,
+    pub table: VirtualStatefulTable,
 }
 
 impl Default for BatchWidget {
@@ -30,10 +56,12 @@ impl Default for BatchWidget {
 impl super::Widget for BatchWidget {
     fn draw(&mut self, f: &mut Frame, ctx: &Context, area: Rect) {
         let buf = f.buffer_mut();
-        let block = border_block(&ctx.theme, ctx.mode == Mode::Batch).title(title!("Batch"));
-        let focus_color = match ctx.mode {
-            Mode::Batch => ctx.theme.border_focused_color,
-            _ => ctx.theme.border_color,
+        let minimal = ctx.config.layout.minimal;
+        let block = pane_block(ctx, ctx.has_focus(Pane::Batch), WidgetKind::Batch)
+            .title(title!(ctx.tr("Batch")));
+        let focus_color = match ctx.has_focus(Pane::Batch) {
+            true => ctx.theme.border_focused_color,
+            false => ctx.theme.border_color,
         };
         let rows = ctx
             .batch
@@ -41,12 +69,8 @@ impl super::Widget for BatchWidget {
             .map(|i| {
                 Row::new([
                     i.icon.label.fg((i.icon.color)(&ctx.theme)),
-                    i.title.to_owned().fg(match i.item_type {
-                        ItemType::Trusted => ctx.theme.success,
-                        ItemType::Remake => ctx.theme.error,
-                        ItemType::None => ctx.theme.fg,
-                    }),
-                    format!("{:>9}", i.size).fg(ctx.theme.fg),
+                    ctx.theme.item_title(i),
+                    format!("{:>9}", i.size()).fg(ctx.theme.fg),
                 ])
             })
             .collect::<Vec<Row>>();
@@ -74,14 +98,14 @@ impl super::Widget for BatchWidget {
         super::scroll_padding(
             self.table.selected().unwrap_or(0),
             area.height as usize,
-            3,
+            super::pane_header_height(ctx),
             num_items,
             ctx.config.scroll_padding,
             self.table.state.offset_mut(),
         );
 
         StatefulWidget::render(table, area, buf, &mut self.table.state);
-        if ctx.batch.len() + 2 > area.height as usize {
+        if ctx.batch.len() + 2 > area.height as usize && !minimal {
             let sb = super::scrollbar(ctx, ScrollbarOrientation::VerticalRight);
             let sb_area = area.inner(&Margin {
                 vertical: 1,
@@ -95,7 +119,36 @@ impl super::Widget for BatchWidget {
             );
         }
 
-        let size = human_bytes(ctx.batch.iter().fold(0, |acc, i| acc + i.bytes) as f64);
+        // Drawn over the last row while a batch is downloading
+        if let Some(p) = progress::total_batch_progress().filter(|_| area.height > 3) {
+            let gauge_area = match minimal {
+                true => Rect::new(area.x, area.bottom() - 1, area.width, 1),
+                false => Rect::new(area.x + 1, area.bottom() - 2, area.width - 2, 1),
+            };
+            let label = match p.failed {
+                0 => format!("{}/{} {}", p.fetched, p.total, ctx.tr("fetched")),
+                n => format!(
+                    "{}/{} {}, {} {}",
+                    p.fetched,
+                    p.total,
+                    ctx.tr("fetched"),
+                    n,
+                    ctx.tr("failed")
+                ),
+            };
+            Clear.render(gauge_area, buf);
+            LineGauge::default()
+                .ratio(p.ratio())
+                .label(label)
+                .style(style!(fg:ctx.theme.fg, bg:ctx.theme.bg))
+                .gauge_style(style!(fg:match p.failed {
+                    0 => ctx.theme.success,
+                    _ => ctx.theme.error,
+                }))
+                .render(gauge_area, buf);
+        }
+
+        let size = to_size(ctx.batch.iter().fold(0, |acc, i| acc + i.bytes));
         let right_str = title!("Size({}): {}", ctx.batch.len(), size);
         if let Some((tr, area)) = Corner::TopRight.try_title(right_str, area, true) {
             tr.render(area, buf);
@@ -112,8 +165,12 @@ impl super::Widget for BatchWidget {
         {
             use KeyCode::*;
             match (code, modifiers) {
-                (Esc | Tab | BackTab, _) => {
-                    ctx.mode = Mode::Normal;
+                (Esc, _) if progress::total_batch_progress().is_some() => {
+                    progress::cancel_batches();
+                    ctx.notify("Stopping the batch download, press Ctrl-A to resume");
+                }
+                (Esc, _) => {
+                    ctx.focus_pane(Pane::Results);
                 }
                 (Char('q'), &KeyModifiers::NONE) => {
                     ctx.quit();
@@ -139,14 +196,28 @@ impl super::Widget for BatchWidget {
                 (Char(' '), &KeyModifiers::NONE) => {
                     if let Some(i) = self.table.selected() {
                         self.table.next(ctx.batch.len(), 0);
+                        if let Some(item) = ctx.batch.get(i) {
+                            let change = format!("removing \"{}\"", item.title);
+                            ctx.save_batch(change);
+                        }
                         ctx.batch.remove(i);
                         self.table.next(ctx.batch.len(), 0);
                     }
                 }
+                (Char(c @ '1'..='9'), &KeyModifiers::NONE) => {
+                    ctx.mode = Mode::KeyCombo(c.to_string());
+                }
+                (Char('u'), &KeyModifiers::NONE) => ctx.undo_batch(),
                 (Char('a'), &KeyModifiers::CONTROL) => {
-                    ctx.mode = Mode::Loading(LoadType::Batching);
+                    ctx.mode = match ctx.config.confirm_batch_download {
+                        true => Mode::Confirm(LoadType::Batching),
+                        false => Mode::Loading(LoadType::Batching),
+                    };
                 }
+                (Char('e'), &KeyModifiers::NONE) => export(ctx, ExportFormat::Html),
+                (Char('E'), &KeyModifiers::SHIFT) => export(ctx, ExportFormat::Markdown),
                 (Char('x'), &KeyModifiers::CONTROL) => {
+                    ctx.save_batch("clearing the batch");
                     ctx.batch.clear();
                 }
                 _ => {}
@@ -159,12 +230,17 @@ impl super::Widget for BatchWidget {
             ("Enter", "Download single torrent"),
             ("Ctrl-A", "Download all torrents"),
             ("Ctrl-X", "Clear batch"),
-            ("Esc/Tab/Shift-Tab", "Back to results"),
+            ("u", "Undo batch change"),
+            ("e/E", "Export batch as HTML/Markdown"),
+            ("Esc", "Stop batch download, while downloading"),
+            ("Tab/Shift-Tab", "Focus next/previous pane"),
+            ("Esc", "Back to results"),
             ("q", "Exit app"),
             ("g/G", "Goto Top/Bottom"),
             ("k, ↑", "Up"),
             ("j, ↓", "Down"),
             ("K, J", "Up/Down 4 items"),
+            ("{count}j, {count}k", "Repeat a motion, e.g. 5j"),
             ("Space", "Toggle item for batch download"),
         ])
     }
@@ -1,4 +1,6 @@
-use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use std::fs;
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Margin, Rect},
     widgets::StatefulWidget as _,
@@ -6,12 +8,17 @@ use ratatui::{
 };
 use ratatui_image::{protocol::StatefulProtocol, StatefulImage};
 
-use crate::app::{Context, LoadType, Mode};
+use crate::{
+    app::{Context, LoadType, Mode},
+    util::cmd::CommandBuilder,
+};
 
 use super::{input::InputWidget, Widget};
 
 pub struct CaptchaPopup {
     pub image: Option<Box<dyn StatefulProtocol>>,
+    /// The image as it was downloaded
+    pub bytes: Vec<u8>,
     pub input: InputWidget,
 }
 
@@ -19,15 +26,40 @@ impl Default for CaptchaPopup {
     fn default() -> Self {
         Self {
             image: None,
+            bytes: vec![],
             input: InputWidget::new(32, None),
         }
     }
 }
 
-impl InputWidget {
-    pub fn clear(&mut self) {
-        self.input.clear();
-        self.cursor = 0;
+impl CaptchaPopup {
+    /// Save the image and open it outside the terminal, where it isn't
+    /// shrunk into half blocks
+    fn open_image(&self, ctx: &mut Context) {
+        let ext = image::guess_format(&self.bytes)
+            .ok()
+            .and_then(|f| f.extensions_str().first().copied())
+            .unwrap_or("png");
+        let path = std::env::temp_dir().join(format!("nyaa-captcha.{}", ext));
+        if let Err(e) = fs::write(&path, &self.bytes) {
+            return ctx.show_error(format!(
+                "Failed to save the captcha to {}:\n{}",
+                path.display(),
+                e
+            ));
+        }
+        let file = path.to_string_lossy();
+        let res = match ctx.config.captcha_command.clone() {
+            Some(cmd) => CommandBuilder::new(cmd)
+                .sub("{file}", &file)
+                .run(None)
+                .map_err(|e| e.to_string()),
+            None => open::that_detached(&path).map_err(|e| e.to_string()),
+        };
+        match res {
+            Ok(()) => ctx.notify(format!("Opened the captcha saved to {}", file)),
+            Err(e) => ctx.show_error(format!("Failed to open the captcha:\n{}", e)),
+        }
     }
 }
 
@@ -74,16 +106,25 @@ impl Widget for CaptchaPopup {
         if let Event::Key(KeyEvent {
             code,
             kind: KeyEventKind::Press,
+            modifiers,
             ..
         }) = e
         {
-            match code {
-                KeyCode::Esc => {
+            match (code, modifiers) {
+                (KeyCode::Esc, _) => {
                     ctx.mode = Mode::Normal;
                 }
-                KeyCode::Enter => {
+                (KeyCode::Enter, _) => {
                     ctx.mode = Mode::Loading(LoadType::SolvingCaptcha(self.input.input.clone()));
                 }
+                // Searching again is answered with a new captcha
+                (KeyCode::Char('r'), &KeyModifiers::CONTROL) => {
+                    ctx.mode = Mode::Loading(LoadType::Searching);
+                    return;
+                }
+                (KeyCode::Char('o'), &KeyModifiers::CONTROL) => {
+                    return self.open_image(ctx);
+                }
                 _ => {}
             }
         }
@@ -93,11 +134,9 @@ impl Widget for CaptchaPopup {
     fn get_help() -> Option<Vec<(&'static str, &'static str)>> {
         Some(vec![
             ("Enter", "Confirm"),
-            ("Esc, f, q", "Close"),
-            ("g", "Top"),
-            ("G", "Bottom"),
-            ("j, ↓", "Down"),
-            ("k, ↑", "Up"),
+            ("Esc", "Close"),
+            ("Ctrl-r", "New captcha"),
+            ("Ctrl-o", "Open captcha outside the terminal"),
         ])
     }
 }
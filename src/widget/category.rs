@@ -9,12 +9,17 @@ use ratatui::{
 
 use crate::{
     app::{Context, LoadType, Mode},
+    source::Sources,
     style,
     theme::Theme,
     title,
 };
 
-use super::{border_block, VirtualStatefulTable, Widget};
+use super::{
+    border_block,
+    input::{self, InputWidget},
+    VirtualStatefulTable, Widget,
+};
 
 #[derive(Clone)]
 pub struct CatEntry {
@@ -62,15 +67,112 @@ pub struct CatStruct {
     pub entries: Vec<CatEntry>,
 }
 
-#[derive(Default)]
 pub struct CategoryPopup {
     pub selected: usize,
     pub major: usize,
     pub minor: usize,
     pub table: VirtualStatefulTable,
+    pub input: InputWidget,
+    /// Whether keys are typed into the filter instead of moving
+    filtering: bool,
+    /// Tab and entry last expanded on each source
+    expanded: Vec<(Sources, usize, usize)>,
+}
+
+impl Default for CategoryPopup {
+    fn default() -> Self {
+        CategoryPopup {
+            selected: 0,
+            major: 0,
+            minor: 0,
+            table: VirtualStatefulTable::new(),
+            input: InputWidget::new(30, None),
+            filtering: false,
+            expanded: vec![],
+        }
+    }
 }
 
 impl CategoryPopup {
+    /// The tab and entry last expanded on `src`, if the popup has been
+    /// used with it before
+    pub fn expanded(&self, src: Sources) -> Option<(usize, usize)> {
+        self.expanded
+            .iter()
+            .find(|(s, _, _)| *s == src)
+            .map(|(_, major, minor)| (*major, *minor))
+    }
+
+    fn close(&mut self, ctx: &mut Context) {
+        match self.expanded.iter_mut().find(|(s, _, _)| *s == ctx.src) {
+            Some(e) => *e = (ctx.src, self.major, self.minor),
+            None => self.expanded.push((ctx.src, self.major, self.minor)),
+        }
+        self.input.clear();
+        self.filtering = false;
+    }
+
+    /// Every entry matching the filter, as (major, minor). A tab matching
+    /// the filter matches all its entries.
+    fn matches(&self, ctx: &Context) -> Vec<(usize, usize)> {
+        let filter = self.input.input.trim().to_lowercase();
+        let filter = filter.as_str();
+        ctx.src_info
+            .cats
+            .iter()
+            .enumerate()
+            .flat_map(|(major, cat)| {
+                let all = cat.name.to_lowercase().contains(filter);
+                cat.entries
+                    .iter()
+                    .enumerate()
+                    .filter(move |(_, e)| all || e.name.to_lowercase().contains(filter))
+                    .map(move |(minor, _)| (major, minor))
+            })
+            .collect()
+    }
+
+    /// Rows shown as (major, None) for tabs and (major, Some(minor)) for
+    /// entries. While filtering, only tabs with matches are shown, expanded.
+    fn rows(&self, ctx: &Context) -> Vec<(usize, Option<usize>)> {
+        if self.input.input.trim().is_empty() {
+            let mut rows: Vec<(usize, Option<usize>)> =
+                (0..ctx.src_info.cats.len()).map(|i| (i, None)).collect();
+            if let Some(cat) = ctx.src_info.cats.get(self.major) {
+                rows.splice(
+                    self.major + 1..self.major + 1,
+                    (0..cat.entries.len()).map(|minor| (self.major, Some(minor))),
+                );
+            }
+            return rows;
+        }
+        let mut rows = vec![];
+        for (major, minor) in self.matches(ctx) {
+            if rows.last().map(|(m, _)| *m) != Some(major) {
+                rows.push((major, None));
+            }
+            rows.push((major, Some(minor)));
+        }
+        rows
+    }
+
+    /// Move to the next or previous entry matching the filter
+    fn next_match(&mut self, ctx: &Context, forward: bool) {
+        let matches = self.matches(ctx);
+        if matches.is_empty() {
+            return;
+        }
+        let pos = matches
+            .iter()
+            .position(|m| *m == (self.major, self.minor))
+            .unwrap_or(0);
+        let len = matches.len();
+        (self.major, self.minor) = match forward {
+            true => matches[(pos + 1) % len],
+            false => matches[(pos + len - 1) % len],
+        };
+    }
+
     fn next_tab(&mut self, max_cat: usize) {
         self.major = match self.major + 1 >= max_cat {
             true => 0,
@@ -96,35 +198,39 @@ impl CategoryPopup {
 
 impl Widget for CategoryPopup {
     fn draw(&mut self, f: &mut Frame, ctx: &Context, area: Rect) {
-        if let Some(cat) = ctx.src_info.cats.get(self.major) {
-            let mut tbl: Vec<Row> = ctx
-                .src_info
-                .cats
+        if ctx.src_info.cats.get(self.major).is_some() {
+            let filtered = !self.input.input.trim().is_empty();
+            let rows = self.rows(ctx);
+            let tbl: Vec<Row> = rows
                 .iter()
-                .enumerate()
-                .map(|(i, e)| match i == self.major {
-                    false => Row::new(Text::raw(format!(" ▶ {}", e.name))),
-                    true => Row::new(Text::raw(format!(" ▼ {}", e.name)))
-                        .style(style!(bg:ctx.theme.solid_bg, fg:ctx.theme.solid_fg)),
+                .map(|(major, minor)| {
+                    let cat = &ctx.src_info.cats[*major];
+                    match minor.and_then(|minor| cat.entries.get(minor)) {
+                        Some(e) => Row::new(vec![Line::from(vec![
+                            match e.id == self.selected {
+                                true => "  ",
+                                false => "   ",
+                            }
+                            .into(),
+                            e.icon.label.fg((e.icon.color)(&ctx.theme)),
+                            " ".into(),
+                            e.name.to_owned().into(),
+                        ])]),
+                        None if *major == self.major || filtered => {
+                            Row::new(Text::raw(format!(" ▼ {}", cat.name)))
+                                .style(style!(bg:ctx.theme.solid_bg, fg:ctx.theme.solid_fg))
+                        }
+                        None => Row::new(Text::raw(format!(" ▶ {}", cat.name))),
+                    }
                 })
                 .collect();
-
-            let cat_rows = cat.entries.iter().map(|e| {
-                Row::new(vec![Line::from(vec![
-                    match e.id == self.selected {
-                        true => "  ",
-                        false => "   ",
-                    }
-                    .into(),
-                    e.icon.label.fg((e.icon.color)(&ctx.theme)),
-                    " ".into(),
-                    e.name.to_owned().into(),
-                ])])
-            });
-            let num_items = cat.entries.len() + ctx.src_info.cats.len();
+            let num_items = rows.len();
             self.table.scrollbar_state = self.table.scrollbar_state.content_length(num_items);
-
-            tbl.splice(self.major + 1..self.major + 1, cat_rows);
+            self.table.select(
+                rows.iter()
+                    .position(|r| *r == (self.major, Some(self.minor)))
+                    .unwrap_or(0),
+            );
 
             let center = super::centered_rect(33, 14, area);
 
@@ -139,7 +245,7 @@ impl Widget for CategoryPopup {
 
             super::clear(center, f.buffer_mut(), ctx.theme.bg);
             let table = Table::new(tbl, [Constraint::Percentage(100)])
-                .block(border_block(&ctx.theme, true).title(title!("Category")))
+                .block(border_block(&ctx.theme, true).title(title!(ctx.tr("Category"))))
                 .highlight_style(Style::default().bg(ctx.theme.hl_bg));
             StatefulWidget::render(table, center, f.buffer_mut(), &mut self.table.state);
 
@@ -149,9 +255,28 @@ impl Widget for CategoryPopup {
                 horizontal: 0,
             });
             StatefulWidget::render(sb, sb_area, f.buffer_mut(), &mut self.table.scrollbar_state);
+
+            if self.filtering {
+                // Show the filter over the bottom border
+                let input_area = Rect::new(
+                    center.x + 2,
+                    center.bottom().saturating_sub(1),
+                    center.width.saturating_sub(4),
+                    1,
+                );
+                let prompt = Rect::new(center.x + 1, input_area.y, 1, 1);
+                f.render_widget(Text::raw("/"), prompt);
+                self.input.draw(f, ctx, input_area);
+                if self.filtering {
+                    self.input.show_cursor(f, input_area);
+                }
+            }
         }
     }
 
+    // Keys which can't do anything right now are still used up, rather than
+    // falling through to later arms
+    #[allow(clippy::collapsible_match)]
     fn handle_event(&mut self, ctx: &mut Context, e: &Event) {
         if let Event::Key(KeyEvent {
             code,
@@ -159,7 +284,36 @@ impl Widget for CategoryPopup {
             ..
         }) = e
         {
+            if self.filtering {
+                match code {
+                    KeyCode::Esc => {
+                        self.input.clear();
+                        self.filtering = false;
+                    }
+                    KeyCode::Enter if self.matches(ctx).is_empty() => {}
+                    KeyCode::Enter => {
+                        self.filtering = false;
+                    }
+                    KeyCode::Down | KeyCode::Tab => self.next_match(ctx, true),
+                    KeyCode::Up | KeyCode::BackTab => self.next_match(ctx, false),
+                    _ => {
+                        let prev = self.input.input.to_owned();
+                        self.input.handle_event(ctx, e);
+                        if self.input.input != prev {
+                            if let Some((major, minor)) = self.matches(ctx).first() {
+                                (self.major, self.minor) = (*major, *minor);
+                            }
+                        }
+                    }
+                }
+                if !matches!(code, KeyCode::Enter) || self.filtering {
+                    return;
+                }
+            }
             match code {
+                KeyCode::Char('/') => {
+                    self.filtering = true;
+                }
                 KeyCode::Enter => {
                     if let Some(cat) = ctx.src_info.cats.get(self.major) {
                         if let Some(item) = cat.entries.get(self.minor) {
@@ -167,9 +321,11 @@ impl Widget for CategoryPopup {
                             ctx.notify(format!("Category \"{}\"", item.name));
                         }
                     }
+                    self.close(ctx);
                     ctx.mode = Mode::Loading(LoadType::Categorizing);
                 }
                 KeyCode::Esc | KeyCode::Char('c') | KeyCode::Char('q') => {
+                    self.close(ctx);
                     ctx.mode = Mode::Normal;
                 }
                 KeyCode::Char('j') | KeyCode::Down => {
@@ -223,7 +379,7 @@ impl Widget for CategoryPopup {
     }
 
     fn get_help() -> Option<Vec<(&'static str, &'static str)>> {
-        Some(vec![
+        let mut help = vec![
             ("Enter", "Confirm"),
             ("Esc, c, q", "Close"),
             ("j, ↓", "Down"),
@@ -232,6 +388,11 @@ impl Widget for CategoryPopup {
             ("G", "Bottom"),
             ("Tab, J", "Next Tab"),
             ("S-Tab, K", "Prev Tab"),
-        ])
+            ("/", "Filter categories"),
+        ];
+        if let Some(input_help) = input::InputWidget::get_help() {
+            help.extend(input_help);
+        }
+        Some(help)
     }
 }
@@ -1,7 +1,7 @@
 use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
 use ratatui::{
     layout::{Constraint, Rect},
-    widgets::{Row, StatefulWidget as _, Table},
+    widgets::{Cell, Row, StatefulWidget as _, Table},
     Frame,
 };
 use strum::VariantArray;
@@ -14,14 +14,19 @@ use crate::{
 
 use super::{border_block, StatefulTable, Widget};
 
+static WIDTH: u16 = 70;
+
 pub struct ClientsPopup {
     pub table: StatefulTable<Client>,
+    /// Whether the connections have been tested since the app started
+    pub tested: bool,
 }
 
 impl Default for ClientsPopup {
     fn default() -> Self {
         ClientsPopup {
             table: StatefulTable::new(Client::VARIANTS),
+            tested: false,
         }
     }
 }
@@ -29,17 +34,45 @@ impl Default for ClientsPopup {
 impl Widget for ClientsPopup {
     fn draw(&mut self, f: &mut Frame, ctx: &Context, area: Rect) {
         let buf = f.buffer_mut();
-        let center = super::centered_rect(30, self.table.items.len() as u16 + 2, area);
+        let center = super::centered_rect(WIDTH, self.table.items.len() as u16 + 2, area);
         let items = self.table.items.iter().map(|item| {
-            Row::new(vec![match item == &ctx.client {
-                true => format!("  {}", item),
-                false => format!("   {}", item),
-            }])
+            let (status, error) = match ctx.client_status(*item) {
+                _ if !item.is_api() => (Cell::default(), Cell::default()),
+                Some(Ok(_)) => (
+                    Cell::from(ctx.tr("ok")).style(style!(fg:ctx.theme.success)),
+                    Cell::default(),
+                ),
+                Some(Err(e)) => (
+                    Cell::from(ctx.tr("failing")).style(style!(fg:ctx.theme.error)),
+                    // Only the first line fits
+                    Cell::from(e.lines().next().unwrap_or_default().to_owned())
+                        .style(style!(fg:ctx.theme.border_color)),
+                ),
+                None => (
+                    Cell::from(ctx.tr("testing")).style(style!(fg:ctx.theme.border_color)),
+                    Cell::default(),
+                ),
+            };
+            Row::new(vec![
+                Cell::from(match item == &ctx.client {
+                    true => format!("  {}", item),
+                    false => format!("   {}", item),
+                }),
+                status,
+                error,
+            ])
         });
         super::clear(center, buf, ctx.theme.bg);
-        let table = Table::new(items, [Constraint::Percentage(100)])
-            .block(border_block(&ctx.theme, true).title(title!("Download Client")))
-            .highlight_style(style!(bg:ctx.theme.hl_bg));
+        let table = Table::new(
+            items,
+            [
+                Constraint::Length(24),
+                Constraint::Length(8),
+                Constraint::Min(1),
+            ],
+        )
+        .block(border_block(&ctx.theme, true).title(title!(ctx.tr("Download Client"))))
+        .highlight_style(style!(bg:ctx.theme.hl_bg));
         table.render(center, buf, &mut self.table.state);
     }
 
@@ -66,9 +99,15 @@ impl Widget for ClientsPopup {
                 KeyCode::Char('g') => {
                     self.table.select(0);
                 }
+                KeyCode::Char('r') => {
+                    ctx.test_clients();
+                }
                 KeyCode::Enter => {
                     if let Some(c) = self.table.selected() {
                         ctx.client = *c;
+                        if let Some(Err(e)) = ctx.client_status(*c) {
+                            ctx.show_error(format!("{} may not work:\n{}", c, e));
+                        }
 
                         c.load_config(ctx);
                         match ctx.save_config() {
@@ -91,6 +130,7 @@ impl Widget for ClientsPopup {
             ("k, ↑", "Up"),
             ("g", "Top"),
             ("G", "Bottom"),
+            ("r", "Test connections again"),
         ])
     }
 }
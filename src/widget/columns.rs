@@ -0,0 +1,110 @@
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::{
+    layout::{Constraint, Rect},
+    widgets::{Row, StatefulWidget as _, Table},
+    Frame,
+};
+use unicode_width::UnicodeWidthStr as _;
+
+use crate::{
+    app::{Context, Mode},
+    style, title,
+};
+
+use super::{border_block, VirtualStatefulTable, Widget};
+
+pub struct ColumnsPopup {
+    pub table: VirtualStatefulTable,
+}
+
+impl Default for ColumnsPopup {
+    fn default() -> Self {
+        ColumnsPopup {
+            table: VirtualStatefulTable::new(),
+        }
+    }
+}
+
+fn num_rows(ctx: &Context) -> usize {
+    ctx.src
+        .columns(&ctx.config.sources)
+        .map(|c| c.len())
+        .unwrap_or(0)
+}
+
+impl Widget for ColumnsPopup {
+    fn draw(&mut self, f: &mut Frame, ctx: &Context, area: Rect) {
+        let columns = ctx.src.columns(&ctx.config.sources).unwrap_or_default();
+        let rows: Vec<String> = columns
+            .iter()
+            .map(|(name, shown)| {
+                let check = match shown {
+                    true => "[x]",
+                    false => "[ ]",
+                };
+                format!(" {} {}", check, ctx.tr(name))
+            })
+            .collect();
+        let title = ctx.tr("Columns");
+        let width = rows
+            .iter()
+            .map(|r| r.width())
+            .chain([title.width() + 2])
+            .max()
+            .unwrap_or(0) as u16
+            + 3;
+        let center = super::centered_rect(width.max(20), rows.len() as u16 + 2, area);
+        super::clear(center, f.buffer_mut(), ctx.theme.bg);
+        Table::new(
+            rows.into_iter().map(|r| Row::new([r])),
+            [Constraint::Percentage(100)],
+        )
+        .block(border_block(&ctx.theme, true).title(title!(title)))
+        .highlight_style(style!(bg:ctx.theme.hl_bg))
+        .render(center, f.buffer_mut(), &mut self.table.state);
+    }
+
+    fn handle_event(&mut self, ctx: &mut Context, e: &Event) {
+        if let Event::Key(KeyEvent {
+            code,
+            kind: KeyEventKind::Press,
+            ..
+        }) = e
+        {
+            match code {
+                KeyCode::Esc | KeyCode::Char('C') | KeyCode::Char('q') => {
+                    ctx.mode = Mode::Normal;
+                }
+                KeyCode::Char('j') | KeyCode::Down => {
+                    self.table.next_wrap(num_rows(ctx), 1);
+                }
+                KeyCode::Char('k') | KeyCode::Up => {
+                    self.table.next_wrap(num_rows(ctx), -1);
+                }
+                KeyCode::Char('G') => {
+                    self.table.select(num_rows(ctx).saturating_sub(1));
+                }
+                KeyCode::Char('g') => {
+                    self.table.select(0);
+                }
+                KeyCode::Enter | KeyCode::Char(' ') => {
+                    if let Some(i) = self.table.state.selected() {
+                        ctx.toggle_column(i);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn get_help() -> Option<Vec<(&'static str, &'static str)>> {
+        Some(vec![
+            ("Enter, Space", "Show/hide column"),
+            ("Esc, C, q", "Close"),
+            ("g", "Top"),
+            ("G", "Bottom"),
+            ("j, ↓", "Down"),
+            ("k, ↑", "Up"),
+        ])
+    }
+}
@@ -0,0 +1,254 @@
+use std::collections::BTreeMap;
+
+use crossterm::event::Event;
+use ratatui::{
+    layout::{Constraint, Rect},
+    style::Stylize as _,
+    text::Span,
+    widgets::{Row, Table, Widget as _},
+    Frame,
+};
+use serde::{Deserialize, Serialize};
+use unicode_width::UnicodeWidthStr as _;
+
+use crate::{
+    app::{Context, Mode},
+    style, title,
+};
+
+use super::{border_block, Widget};
+
+/// Written in place of the leader key in the keys of a chord
+pub static LEADER: &str = "<leader>";
+
+/// What a chord does once its last key is pressed
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ChordAction {
+    CopyTorrent,
+    CopyMagnet,
+    CopyPost,
+    CopyImdb,
+    CopyBatchMagnets,
+    CopyBatchTorrents,
+    AddPage,
+    AddAllPages,
+    InvertBatch,
+    ClearBatch,
+    UndoBatch,
+    SameShow,
+    SameGroup,
+    Reload,
+    DeepSearch,
+    Stats,
+    Tasks,
+}
+
+impl ChordAction {
+    pub fn describe(self) -> &'static str {
+        match self {
+            ChordAction::CopyTorrent => "Copy torrent link",
+            ChordAction::CopyMagnet => "Copy magnet link",
+            ChordAction::CopyPost => "Copy post link",
+            ChordAction::CopyImdb => "Copy imdb id",
+            ChordAction::CopyBatchMagnets => "Copy batch magnets",
+            ChordAction::CopyBatchTorrents => "Copy batch torrents",
+            ChordAction::AddPage => "Add page to batch",
+            ChordAction::AddAllPages => "Add all pages to batch",
+            ChordAction::InvertBatch => "Invert batch selection",
+            ChordAction::ClearBatch => "Clear batch selection",
+            ChordAction::UndoBatch => "Undo batch change",
+            ChordAction::SameShow => "Same show, other groups",
+            ChordAction::SameGroup => "Same group, other episodes",
+            ChordAction::Reload => "Reload",
+            ChordAction::DeepSearch => "Deep search",
+            ChordAction::Stats => "Stats",
+            ChordAction::Tasks => "Tasks",
+        }
+    }
+}
+
+/// The chords there are without any in the config
+static DEFAULT_CHORDS: &[(&str, ChordAction)] = &[
+    ("yt", ChordAction::CopyTorrent),
+    ("ym", ChordAction::CopyMagnet),
+    ("yp", ChordAction::CopyPost),
+    ("yi", ChordAction::CopyImdb),
+    ("yb", ChordAction::CopyBatchMagnets),
+    ("yB", ChordAction::CopyBatchTorrents),
+    ("ba", ChordAction::AddPage),
+    ("bA", ChordAction::AddAllPages),
+    ("bi", ChordAction::InvertBatch),
+    ("bc", ChordAction::ClearBatch),
+    ("Rs", ChordAction::SameShow),
+    ("Rg", ChordAction::SameGroup),
+];
+
+/// Sequences of keys pressed one after another in the results, each mapped
+/// to an action. The defaults, like `yt`, can be replaced by ones in the
+/// config, which may start with the leader key.
+#[derive(Clone, Debug)]
+pub struct Chords {
+    leader: Option<char>,
+    chords: Vec<(String, ChordAction)>,
+}
+
+impl Default for Chords {
+    fn default() -> Self {
+        Chords {
+            leader: None,
+            chords: DEFAULT_CHORDS
+                .iter()
+                .map(|(keys, action)| (keys.to_string(), *action))
+                .collect(),
+        }
+    }
+}
+
+impl Chords {
+    /// The default chords along with those in `user`, which replace any
+    /// default with the same keys. `<leader>` in their keys stands for
+    /// `leader`.
+    pub fn new(
+        leader: Option<char>,
+        user: &BTreeMap<String, ChordAction>,
+    ) -> Result<Chords, String> {
+        let mut chords = Chords {
+            leader,
+            ..Chords::default()
+        };
+        for (name, action) in user.iter() {
+            let keys = match (name.contains(LEADER), leader) {
+                (true, Some(leader)) => name.replace(LEADER, &leader.to_string()),
+                (true, None) => {
+                    return Err(format!(
+                        "Chord \"{}\" uses {}, but no leader is set",
+                        name, LEADER
+                    ))
+                }
+                (false, _) => name.to_owned(),
+            };
+            match keys.chars().next() {
+                _ if keys.chars().count() < 2 => {
+                    return Err(format!("Chord \"{}\" needs at least two keys", name))
+                }
+                Some(c) if c.is_ascii_digit() || c == 'Q' || c == '@' => {
+                    return Err(format!(
+                        "Chord \"{}\" can't start with {}, which starts a count or macro",
+                        name, c
+                    ))
+                }
+                _ => {}
+            }
+            chords.chords.retain(|(k, _)| *k != keys);
+            chords.chords.push((keys, *action));
+        }
+        // A chord would never be reached if another one ends where it goes on
+        for (keys, _) in chords.chords.iter() {
+            if let Some((shorter, _)) = chords
+                .chords
+                .iter()
+                .find(|(k, _)| k != keys && keys.starts_with(k.as_str()))
+            {
+                return Err(format!(
+                    "Chord \"{}\" can't be reached, since \"{}\" is finished first",
+                    chords.display(keys),
+                    chords.display(shorter)
+                ));
+            }
+        }
+        Ok(chords)
+    }
+
+    /// Whether pressing `key` starts a chord
+    pub fn starts(&self, key: char) -> bool {
+        self.chords.iter().any(|(k, _)| k.starts_with(key))
+    }
+
+    /// The action of the chord finished by `keys`
+    pub fn get(&self, keys: &str) -> Option<ChordAction> {
+        self.chords
+            .iter()
+            .find(|(k, _)| k == keys)
+            .map(|(_, action)| *action)
+    }
+
+    /// The keys which can follow `keys` to finish a chord or macro, with
+    /// what each does
+    pub fn continuations(&self, keys: &str) -> Option<Vec<(String, &'static str)>> {
+        let next: Vec<(String, &'static str)> = match keys {
+            "Q" => vec![("a-z".to_owned(), "Record macro")],
+            "@" => vec![
+                ("a-z".to_owned(), "Replay macro"),
+                ("@".to_owned(), "Replay last macro"),
+            ],
+            _ => self
+                .chords
+                .iter()
+                .filter_map(|(k, action)| {
+                    let rest = k.strip_prefix(keys).filter(|rest| !rest.is_empty())?;
+                    Some((self.display(rest), action.describe()))
+                })
+                .collect(),
+        };
+        Some(next).filter(|n| !n.is_empty())
+    }
+
+    /// `keys` as written in the config, with the leader key as `<leader>`
+    pub fn display(&self, keys: &str) -> String {
+        match self.leader {
+            Some(leader) => keys.replace(leader, LEADER),
+            None => keys.to_owned(),
+        }
+    }
+}
+
+/// Lists what can finish the pending key combo, once it has been waiting for
+/// a moment
+#[derive(Default)]
+pub struct ComboPopup;
+
+impl Widget for ComboPopup {
+    fn draw(&mut self, f: &mut Frame, ctx: &Context, area: Rect) {
+        let Mode::KeyCombo(keys) = &ctx.mode else {
+            return;
+        };
+        let Some(next) = ctx.chords.continuations(keys) else {
+            return;
+        };
+        let key_width = next.iter().map(|(k, _)| k.width()).max().unwrap_or(0) as u16;
+        let map_width = next
+            .iter()
+            .map(|(_, m)| ctx.tr(m).width())
+            .max()
+            .unwrap_or(0) as u16;
+        let width = (key_width + map_width + 3).min(area.width);
+        let height = (next.len() as u16 + 2).min(area.height);
+        // Kept out of the way in the bottom right corner
+        let popup = Rect::new(
+            area.right().saturating_sub(width + 1),
+            area.bottom().saturating_sub(height + 1),
+            width,
+            height,
+        );
+        let rows = next.into_iter().map(|(key, map)| {
+            Row::new([
+                Span::from(key).bold().fg(ctx.theme.border_focused_color),
+                ctx.tr(map).into(),
+            ])
+        });
+
+        let buf = f.buffer_mut();
+        super::clear(popup, buf, ctx.theme.bg);
+        Table::new(rows, [Constraint::Length(key_width), Constraint::Min(1)])
+            .block(border_block(&ctx.theme, true).title(title!(ctx.chords.display(keys))))
+            .style(style!(fg:ctx.theme.fg))
+            .render(popup, buf);
+    }
+
+    fn handle_event(&mut self, _ctx: &mut Context, _e: &Event) {}
+
+    fn get_help() -> Option<Vec<(&'static str, &'static str)>> {
+        None
+    }
+}
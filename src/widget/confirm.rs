@@ -0,0 +1,250 @@
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::{
+    layout::{Constraint, Margin, Rect},
+    widgets::{Row, StatefulWidget as _, Table, TableState},
+    Frame,
+};
+
+use crate::{
+    app::{Context, LoadType, Mode},
+    client::{Client, DownloadOptions},
+    source::Item,
+    style, title,
+    util::conv::to_size,
+};
+
+use super::{
+    border_block,
+    input::{self, InputWidget},
+    Widget,
+};
+
+// Rows before the editable fields
+static NUM_INFO: usize = 3;
+static LABEL_WIDTH: u16 = 10;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Field {
+    SavePath,
+    Label,
+    Paused,
+}
+
+/// Summary of a download, shown before it starts. The save path, label and
+/// paused flag can be changed for just this download.
+pub struct ConfirmPopup {
+    pub save_path: InputWidget,
+    pub label: InputWidget,
+    pub paused: bool,
+    items: Vec<Item>,
+    focus: usize,
+    /// Options last used with each client, used instead of its config
+    last: Vec<(Client, DownloadOptions)>,
+}
+
+impl Default for ConfirmPopup {
+    fn default() -> Self {
+        ConfirmPopup {
+            save_path: InputWidget::new(200, None),
+            label: InputWidget::new(50, None),
+            paused: false,
+            items: vec![],
+            focus: 0,
+            last: vec![],
+        }
+    }
+}
+
+impl ConfirmPopup {
+    /// Show `items` with the options last used with the client, or its
+    /// configured options if none have been used yet
+    pub fn open(&mut self, ctx: &Context, items: Vec<Item>) {
+        let opts = self
+            .last
+            .iter()
+            .find(|(c, _)| *c == ctx.client)
+            .map(|(_, o)| o.to_owned())
+            .unwrap_or_else(|| DownloadOptions::from_config(ctx.client, &ctx.config.client));
+        self.save_path.input = opts.save_path.unwrap_or_default();
+        self.save_path.cursor = self.save_path.input.len();
+        self.label.input = opts.label.unwrap_or_default();
+        self.label.cursor = self.label.input.len();
+        self.paused = opts.paused.unwrap_or(false);
+        self.items = items;
+        self.focus = 0;
+    }
+
+    /// The editable fields the current client supports
+    fn fields(ctx: &Context) -> Vec<Field> {
+        let mut fields = vec![];
+        if ctx.client.has_save_path() {
+            fields.push(Field::SavePath);
+        }
+        if ctx.client.has_label() {
+            fields.push(Field::Label);
+        }
+        if ctx.client.has_paused() {
+            fields.push(Field::Paused);
+        }
+        fields
+    }
+
+    pub fn options(&self, ctx: &Context) -> DownloadOptions {
+        let value = |input: &InputWidget| match input.input.trim().is_empty() {
+            true => None,
+            false => Some(input.input.trim().to_owned()),
+        };
+        DownloadOptions {
+            save_path: value(&self.save_path).filter(|_| ctx.client.has_save_path()),
+            label: value(&self.label).filter(|_| ctx.client.has_label()),
+            paused: Some(self.paused).filter(|_| ctx.client.has_paused()),
+        }
+    }
+
+    fn confirm(&mut self, ctx: &mut Context, load: LoadType) {
+        let opts = self.options(ctx);
+        match self.last.iter_mut().find(|(c, _)| *c == ctx.client) {
+            Some((_, last)) => *last = opts.clone(),
+            None => self.last.push((ctx.client, opts.clone())),
+        }
+        ctx.download_options = Some(opts);
+        ctx.mode = Mode::Loading(load);
+    }
+}
+
+impl Widget for ConfirmPopup {
+    fn draw(&mut self, f: &mut Frame, ctx: &Context, area: Rect) {
+        let Mode::Confirm(load) = &ctx.mode else {
+            return;
+        };
+        let fields = Self::fields(ctx);
+        let height = NUM_INFO + fields.len() + 2;
+        let center = super::centered_rect(50, height as u16, area);
+
+        let size = to_size(self.items.iter().fold(0, |acc, i| acc + i.bytes));
+        let (title, count) = match load {
+            LoadType::Batching => (
+                "Download Batch",
+                Row::new(["Torrents".to_owned(), self.items.len().to_string()]),
+            ),
+            _ => (
+                "Download",
+                Row::new([
+                    "Torrent".to_owned(),
+                    self.items
+                        .first()
+                        .map(|i| i.title.to_owned())
+                        .unwrap_or_default(),
+                ]),
+            ),
+        };
+        let mut rows = vec![
+            Row::new(["Client".to_owned(), ctx.client.to_string()]),
+            count,
+            Row::new(["Size".to_owned(), size]),
+        ];
+        for field in fields.iter() {
+            rows.push(match field {
+                Field::SavePath => Row::new(["Save to".to_owned(), ">".to_owned()]),
+                Field::Label => Row::new(["Label".to_owned(), ">".to_owned()]),
+                Field::Paused => Row::new([
+                    "Paused".to_owned(),
+                    match self.paused {
+                        true => "[x]".to_owned(),
+                        false => "[ ]".to_owned(),
+                    },
+                ]),
+            });
+        }
+
+        let selected = fields.get(self.focus).map(|_| NUM_INFO + self.focus);
+        let mut state = TableState::default().with_selected(selected);
+        let table = Table::new(rows, [Constraint::Length(LABEL_WIDTH), Constraint::Min(1)])
+            .block(border_block(&ctx.theme, true).title(title!(ctx.tr(title))))
+            .highlight_style(style!(bg:ctx.theme.hl_bg));
+
+        let buf = f.buffer_mut();
+        super::clear(center, buf, ctx.theme.bg);
+        table.render(center, buf, &mut state);
+
+        // Draw text inputs after the "> " in the value column
+        let inner = center.inner(&Margin {
+            vertical: 1,
+            horizontal: 1,
+        });
+        let input_x = inner.x + LABEL_WIDTH + 3;
+        let input_width = inner.right().saturating_sub(input_x);
+        for (i, field) in fields.iter().enumerate() {
+            let input = match field {
+                Field::SavePath => &mut self.save_path,
+                Field::Label => &mut self.label,
+                Field::Paused => continue,
+            };
+            let input_area = Rect::new(input_x, inner.y + (NUM_INFO + i) as u16, input_width, 1)
+                .intersection(inner);
+            input.draw(f, ctx, input_area);
+            if i == self.focus {
+                input.show_cursor(f, input_area);
+            }
+        }
+    }
+
+    fn handle_event(&mut self, ctx: &mut Context, e: &Event) {
+        let Mode::Confirm(load) = ctx.mode.to_owned() else {
+            return;
+        };
+        let fields = Self::fields(ctx);
+        let field = fields.get(self.focus).copied();
+        if let Event::Key(KeyEvent {
+            code,
+            kind: KeyEventKind::Press,
+            ..
+        }) = e
+        {
+            match code {
+                KeyCode::Esc => {
+                    ctx.mode = match load {
+                        LoadType::Batching => Mode::Batch,
+                        _ => Mode::Normal,
+                    };
+                    return;
+                }
+                KeyCode::Enter => return self.confirm(ctx, load),
+                KeyCode::Down | KeyCode::Tab if !fields.is_empty() => {
+                    self.focus = (self.focus + 1) % fields.len();
+                    return;
+                }
+                KeyCode::Up | KeyCode::BackTab if !fields.is_empty() => {
+                    self.focus = (self.focus + fields.len() - 1) % fields.len();
+                    return;
+                }
+                KeyCode::Char(' ') | KeyCode::Left | KeyCode::Right
+                    if field == Some(Field::Paused) =>
+                {
+                    self.paused = !self.paused;
+                    return;
+                }
+                _ => {}
+            }
+        }
+        match field {
+            Some(Field::SavePath) => self.save_path.handle_event(ctx, e),
+            Some(Field::Label) => self.label.handle_event(ctx, e),
+            _ => {}
+        }
+    }
+
+    fn get_help() -> Option<Vec<(&'static str, &'static str)>> {
+        let mut help = vec![
+            ("Enter", "Start download"),
+            ("Esc", "Cancel"),
+            ("Tab, ↓", "Next field"),
+            ("S-Tab, ↑", "Previous field"),
+            ("Space", "Toggle paused"),
+        ];
+        if let Some(input_help) = input::InputWidget::get_help() {
+            help.extend(input_help);
+        }
+        Some(help)
+    }
+}
@@ -0,0 +1,92 @@
+use crossterm::event::Event;
+use ratatui::{
+    layout::{Margin, Rect},
+    text::{Line, Span},
+    widgets::{Paragraph, Widget as _},
+    Frame,
+};
+
+use crate::{app::Context, style, title};
+
+use super::{border_block, Widget};
+
+// Keys worth knowing before the first search
+static KEYS: &[(&str, &str)] = &[
+    ("/, i", "Search"),
+    ("u", "Filter by User"),
+    ("c", "Categories"),
+    ("Ctrl-s", "Select source"),
+    ("d", "Select download client"),
+    ("F1, ?", "Help"),
+];
+
+/// Shown in place of the results until the first search, with recent
+/// searches, saved presets and a few keybinds to get started
+#[derive(Default)]
+pub struct DashboardWidget;
+
+impl Widget for DashboardWidget {
+    fn draw(&mut self, f: &mut Frame, ctx: &Context, area: Rect) {
+        let heading = |s: &str| {
+            Line::from(Span::styled(
+                ctx.tr(s).to_owned(),
+                style!(bold, fg:ctx.theme.border_focused_color),
+            ))
+        };
+        let entry = |s: String| Line::from(format!("  {}", s));
+        let none = || {
+            Line::from(Span::styled(
+                format!("  {}", ctx.tr("None yet")),
+                style!(fg:ctx.theme.border_color),
+            ))
+        };
+
+        let mut lines = vec![heading("Recent searches")];
+        match ctx.state.recent_searches.is_empty() {
+            true => lines.push(none()),
+            false => lines.extend(ctx.state.recent_searches.iter().cloned().map(entry)),
+        }
+        lines.push(Line::default());
+
+        lines.push(heading("Presets"));
+        match ctx.config.presets.is_empty() {
+            true => lines.push(none()),
+            false => lines.extend(ctx.config.presets.iter().map(|p| {
+                entry(format!(
+                    "{} ({})",
+                    p.name,
+                    p.source.name(&ctx.config.sources)
+                ))
+            })),
+        }
+        lines.push(Line::default());
+
+        lines.push(heading("Keys"));
+        let key_width = KEYS.iter().map(|(k, _)| k.len()).max().unwrap_or(0);
+        lines.extend(KEYS.iter().map(|(key, map)| {
+            Line::from(vec![
+                Span::styled(
+                    format!("  {:<width$}  ", key, width = key_width),
+                    style!(fg:ctx.theme.border_focused_color),
+                ),
+                Span::raw(ctx.tr(map)),
+            ])
+        }));
+
+        let block = border_block(&ctx.theme, true).title(title!(ctx.tr("Results")));
+        let inner = block.inner(area).inner(&Margin {
+            vertical: 0,
+            horizontal: 1,
+        });
+        let buf = f.buffer_mut();
+        super::clear(area, buf, ctx.theme.bg);
+        block.render(area, buf);
+        Paragraph::new(lines).render(inner, buf);
+    }
+
+    fn handle_event(&mut self, _ctx: &mut Context, _e: &Event) {}
+
+    fn get_help() -> Option<Vec<(&'static str, &'static str)>> {
+        None
+    }
+}
@@ -0,0 +1,161 @@
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::{
+    layout::{Margin, Rect},
+    text::{Line, Span},
+    widgets::{Paragraph, Widget as _, Wrap},
+    Frame,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app::Context,
+    source::{Item, ItemType},
+    style,
+    theme::WidgetKind,
+    title,
+};
+
+use super::{focus::Pane, pane_block, Widget};
+
+static LABEL_WIDTH: usize = 10;
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct LayoutConfig {
+    /// Show a details pane between the results and batch on wide terminals
+    pub three_pane: bool,
+    /// Narrowest terminal the three panes are shown on
+    pub min_width: u16,
+    /// Terminals narrower than this hide less useful columns, shrink the
+    /// search bar and show the batch over the results. 0 turns this off.
+    pub narrow_width: u16,
+    /// Drop the borders around panes and shrink the search bar to a single
+    /// line, for tmux panes and small terminals. Toggled with `m`.
+    pub minimal: bool,
+}
+
+impl Default for LayoutConfig {
+    fn default() -> Self {
+        LayoutConfig {
+            three_pane: false,
+            min_width: 160,
+            narrow_width: 100,
+            minimal: false,
+        }
+    }
+}
+
+/// Everything known about the selected result, shown in the middle pane of
+/// the three pane layout
+#[derive(Default)]
+pub struct DetailsWidget {
+    pub item: Option<Item>,
+    scroll: u16,
+}
+
+impl DetailsWidget {
+    /// Show `item`, scrolling back to the top if it changed
+    pub fn with_item(&mut self, item: Option<Item>) {
+        if self.item.as_ref().map(|i| &i.id) != item.as_ref().map(|i| &i.id) {
+            self.scroll = 0;
+        }
+        self.item = item;
+    }
+}
+
+impl Widget for DetailsWidget {
+    fn draw(&mut self, f: &mut Frame, ctx: &Context, area: Rect) {
+        let block = pane_block(ctx, ctx.has_focus(Pane::Details), WidgetKind::Details)
+            .title(title!(ctx.tr("Details")));
+        let inner = block.inner(area).inner(&Margin {
+            vertical: 0,
+            horizontal: 1,
+        });
+        let buf = f.buffer_mut();
+        super::clear(area, buf, ctx.theme.bg);
+        block.render(area, buf);
+
+        let Some(item) = &self.item else {
+            return;
+        };
+        let row = |label: &str, value: String| {
+            Line::from(vec![
+                Span::styled(
+                    format!("{:<width$}", ctx.tr(label), width = LABEL_WIDTH),
+                    style!(fg:ctx.theme.border_focused_color),
+                ),
+                Span::raw(value),
+            ])
+        };
+        let mut lines = vec![
+            Line::from(Span::styled(
+                item.title.to_owned(),
+                style!(bold, fg:match item.item_type {
+                    ItemType::Trusted => ctx.theme.success,
+                    ItemType::Remake => ctx.theme.error,
+                    ItemType::None => ctx.theme.fg,
+                }),
+            )),
+            Line::default(),
+            row(
+                "Category",
+                ctx.src_info.clone().entry_from_id(item.category).name,
+            ),
+            row("Size", item.size()),
+            row("Date", item.date.time.format(&item.date.format).to_string()),
+            row("Seeders", item.seeders.to_string()),
+            row("Leechers", item.leechers.to_string()),
+            row("Downloads", item.downloads.to_string()),
+        ];
+        let mut extra = item.extra.iter().collect::<Vec<(&String, &String)>>();
+        extra.sort();
+        lines.extend(extra.into_iter().map(|(k, v)| row(k, v.to_owned())));
+        lines.push(row("Post", item.post_link.to_owned()));
+
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .scroll((self.scroll, 0))
+            .render(inner, buf);
+    }
+
+    fn handle_event(&mut self, ctx: &mut Context, e: &Event) {
+        if let Event::Key(KeyEvent {
+            code,
+            kind: KeyEventKind::Press,
+            modifiers,
+            ..
+        }) = e
+        {
+            use KeyCode::*;
+            match (code, modifiers) {
+                (Esc, _) => {
+                    ctx.focus_pane(Pane::Results);
+                }
+                (Char('q'), &KeyModifiers::NONE) => {
+                    ctx.quit();
+                }
+                (Char('j') | Down, &KeyModifiers::NONE) => {
+                    self.scroll = self.scroll.saturating_add(1);
+                }
+                (Char('k') | Up, &KeyModifiers::NONE) => {
+                    self.scroll = self.scroll.saturating_sub(1);
+                }
+                (Char('g'), &KeyModifiers::NONE) => {
+                    self.scroll = 0;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn get_help() -> Option<Vec<(&'static str, &'static str)>> {
+        Some(vec![
+            ("Tab/Shift-Tab", "Focus next/previous pane"),
+            ("Esc", "Back to results"),
+            ("q", "Exit app"),
+            ("k, ↑", "Up"),
+            ("j, ↓", "Down"),
+            ("g", "Top"),
+        ])
+    }
+}
@@ -0,0 +1,196 @@
+use std::{cmp::min, error::Error};
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::{
+    layout::{Margin, Rect},
+    widgets::{Paragraph, ScrollbarOrientation, ScrollbarState, StatefulWidget as _, Widget as _},
+    Frame,
+};
+
+use crate::{app::Context, clip, style, theme::WidgetKind, title};
+
+use super::{Corner, Widget};
+
+/// An error message, along with the errors that caused it
+#[derive(Clone)]
+pub struct ErrorMsg {
+    pub msg: String,
+    pub chain: Vec<String>,
+}
+
+impl ErrorMsg {
+    pub fn new<S: ToString>(msg: S) -> Self {
+        ErrorMsg {
+            msg: msg.to_string(),
+            chain: vec![],
+        }
+    }
+
+    pub fn from_error(e: &(dyn Error + 'static)) -> Self {
+        let mut chain = vec![];
+        let mut source = e.source();
+        while let Some(s) = source {
+            chain.push(s.to_string());
+            source = s.source();
+        }
+        ErrorMsg {
+            msg: e.to_string(),
+            chain,
+        }
+    }
+
+    fn details(&self) -> String {
+        match self.chain.is_empty() {
+            true => "No further details".to_owned(),
+            false => self
+                .chain
+                .iter()
+                .enumerate()
+                .map(|(i, e)| format!("{}: {}", i + 1, e))
+                .collect::<Vec<String>>()
+                .join("\n"),
+        }
+    }
+
+    fn full(&self) -> String {
+        match self.chain.is_empty() {
+            true => self.msg.to_owned(),
+            false => format!("{}\n\nCaused by:\n{}", self.msg, self.details()),
+        }
+    }
+}
+
+/// Shows errors one at a time, on top of everything else. Takes all input
+/// until every error has been dismissed, one by one or all at once.
+#[derive(Default)]
+pub struct ErrorPopup {
+    errors: Vec<ErrorMsg>,
+    scroll: usize,
+    max_scroll: usize,
+    details: bool,
+}
+
+impl ErrorPopup {
+    pub fn add(&mut self, error: ErrorMsg) {
+        self.errors.push(error);
+    }
+
+    pub fn is_open(&self) -> bool {
+        !self.errors.is_empty()
+    }
+
+    fn dismiss(&mut self) {
+        if !self.errors.is_empty() {
+            self.errors.remove(0);
+        }
+        self.scroll = 0;
+        self.details = false;
+    }
+
+    fn dismiss_all(&mut self) {
+        self.errors.clear();
+        self.scroll = 0;
+        self.details = false;
+    }
+}
+
+impl Widget for ErrorPopup {
+    fn draw(&mut self, f: &mut Frame, ctx: &Context, area: Rect) {
+        let Some(error) = self.errors.first() else {
+            return;
+        };
+        let text = match self.details {
+            true => format!("{}\n\nCaused by:\n{}", error.msg, error.details()),
+            false => error.msg.to_owned(),
+        };
+
+        let width = min(area.width.saturating_sub(4), 80).max(20);
+        let lines = textwrap::wrap(&text, width.saturating_sub(2) as usize);
+        let height = min(lines.len() as u16 + 2, area.height.saturating_sub(2).max(3));
+        let center = super::centered_rect(width, height, area);
+
+        let visible = center.height.saturating_sub(2) as usize;
+        self.max_scroll = lines.len().saturating_sub(visible);
+        self.scroll = min(self.scroll, self.max_scroll);
+
+        let title = match self.errors.len() {
+            1 => title!(ctx.tr("Error")),
+            n => title!("{} (1/{})", ctx.tr("Error"), n),
+        };
+        let block = super::widget_block(&ctx.theme, true, WidgetKind::Error).title(title);
+        let content = lines.join("\n");
+
+        let buf = f.buffer_mut();
+        super::clear(center, buf, ctx.theme.bg);
+        Paragraph::new(content)
+            .block(block)
+            .style(style!(fg:ctx.theme.error, bg:ctx.theme.bg))
+            .scroll((self.scroll as u16, 0))
+            .render(center, buf);
+
+        // Closing every error is only mentioned if there's room
+        let hints = [
+            "y: copy, d: details, D: close all, Esc: close",
+            "y: copy, d: details, Esc: close",
+        ];
+        let hints = &hints[(self.errors.len() == 1) as usize..];
+        if let Some((hint, hint_area)) = hints
+            .iter()
+            .find_map(|h| Corner::BottomRight.try_title(*h, center, true))
+        {
+            f.render_widget(hint, hint_area);
+        }
+
+        // Only show scrollbar if content overflows
+        if self.max_scroll > 0 {
+            let mut sb_state = ScrollbarState::new(self.max_scroll).position(self.scroll);
+            let sb =
+                super::scrollbar(ctx, ScrollbarOrientation::VerticalRight).begin_symbol(Some(""));
+            let sb_area = center.inner(&Margin {
+                vertical: 1,
+                horizontal: 0,
+            });
+            sb.render(sb_area, f.buffer_mut(), &mut sb_state);
+        }
+    }
+
+    fn handle_event(&mut self, ctx: &mut Context, e: &Event) {
+        if let Event::Key(KeyEvent {
+            code,
+            kind: KeyEventKind::Press,
+            ..
+        }) = e
+        {
+            match code {
+                KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => self.dismiss(),
+                KeyCode::Char('D') => self.dismiss_all(),
+                KeyCode::Char('j') | KeyCode::Down => {
+                    self.scroll = min(self.scroll + 1, self.max_scroll);
+                }
+                KeyCode::Char('k') | KeyCode::Up => {
+                    self.scroll = self.scroll.saturating_sub(1);
+                }
+                KeyCode::Char('g') => self.scroll = 0,
+                // Clamped to the bottom when drawn
+                KeyCode::Char('G') => self.scroll = usize::MAX,
+                KeyCode::Char('d') => {
+                    self.details = !self.details;
+                    self.scroll = 0;
+                }
+                KeyCode::Char('y') => {
+                    if let Some(error) = self.errors.first() {
+                        match clip::copy_to_clipboard(error.full(), ctx.config.clipboard.clone()) {
+                            Ok(_) => ctx.notify("Copied error to clipboard"),
+                            Err(e) => self.add(ErrorMsg::new(format!("Failed to copy:\n{}", e))),
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn get_help() -> Option<Vec<(&'static str, &'static str)>> {
+        None
+    }
+}
@@ -7,6 +7,7 @@ use ratatui::{
 
 use crate::{
     app::{Context, LoadType, Mode},
+    results::QuickFilter,
     style, title,
 };
 
@@ -14,7 +15,12 @@ use super::{border_block, VirtualStatefulTable, Widget};
 
 pub struct FilterPopup {
     pub table: VirtualStatefulTable,
+    /// The filter searched with, or for a source taking several filters at
+    /// once, the bits of each one used
     pub selected: usize,
+    /// Filters checked since the popup was opened, for a source taking
+    /// several at once
+    pub checked: usize,
 }
 
 impl Default for FilterPopup {
@@ -22,26 +28,109 @@ impl Default for FilterPopup {
         FilterPopup {
             table: VirtualStatefulTable::new(),
             selected: 0,
+            checked: 0,
         }
     }
 }
 
+/// Bit for the filter at `index` when a source takes several at once, with
+/// the first filter, meaning no filter, having none
+pub fn filter_bit(index: usize) -> usize {
+    index.checked_sub(1).map_or(0, |i| 1 << i)
+}
+
+impl FilterPopup {
+    /// Start from the filters searched with each time the popup is opened
+    pub fn open(&mut self) {
+        self.checked = self.selected;
+    }
+
+    /// Search with `filter`, highlighting its row
+    pub fn select(&mut self, ctx: &Context, filter: usize) {
+        self.selected = filter;
+        self.checked = filter;
+        self.table.select(self.row(ctx));
+    }
+
+    /// Row of the filter searched with, or the first of them for a source
+    /// taking several at once
+    pub fn row(&self, ctx: &Context) -> usize {
+        match ctx.src_info.capabilities.multi_filter {
+            true if self.selected != 0 => self.selected.trailing_zeros() as usize + 1,
+            true => 0,
+            false => self.selected,
+        }
+    }
+
+    /// The filter to search with for only the filter at row `index`
+    pub fn from_row(ctx: &Context, index: usize) -> usize {
+        match ctx.src_info.capabilities.multi_filter {
+            true => filter_bit(index),
+            false => index,
+        }
+    }
+
+    /// Names of the filters checked, or of the first filter if none are
+    fn checked_names(&self, ctx: &Context) -> String {
+        let names = ctx
+            .src_info
+            .filters
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i > 0 && self.checked & filter_bit(*i) != 0)
+            .map(|(_, f)| format!("\"{}\"", f))
+            .collect::<Vec<_>>();
+        match names.is_empty() {
+            true => format!(
+                "\"{}\"",
+                ctx.src_info.filters.first().cloned().unwrap_or_default()
+            ),
+            false => names.join(", "),
+        }
+    }
+}
+
+/// Number of rows, the source's filters followed by the quick filters
+fn num_rows(ctx: &Context) -> usize {
+    ctx.src_info.filters.len() + QuickFilter::ALL.len()
+}
+
 impl Widget for FilterPopup {
     fn draw(&mut self, f: &mut Frame, ctx: &Context, area: Rect) {
-        let center = super::centered_rect(30, ctx.src_info.filters.len() as u16 + 2, area);
-        let items =
-            ctx.src_info
-                .filters
-                .iter()
-                .enumerate()
-                .map(|(i, item)| match i == self.selected {
-                    true => Row::new(vec![format!("  {}", item.to_owned())]),
-                    false => Row::new(vec![format!("   {}", item.to_owned())]),
-                });
+        let center = super::centered_rect(30, num_rows(ctx) as u16 + 2, area);
+        let quick = QuickFilter::ALL.iter().map(|q| {
+            let check = match ctx.quick_filters.contains(q) {
+                true => "[x]",
+                false => "[ ]",
+            };
+            Row::new(vec![format!(" {} {}", check, ctx.tr(q.label()))])
+        });
+        let multi = ctx.src_info.capabilities.multi_filter;
+        let items = ctx
+            .src_info
+            .filters
+            .iter()
+            .enumerate()
+            .map(|(i, item)| match (multi, i == self.selected) {
+                (true, _) => {
+                    let on = match i {
+                        0 => self.checked == 0,
+                        i => self.checked & filter_bit(i) != 0,
+                    };
+                    let check = match on {
+                        true => "[x]",
+                        false => "[ ]",
+                    };
+                    Row::new(vec![format!(" {} {}", check, item)])
+                }
+                (false, true) => Row::new(vec![format!("  {}", item.to_owned())]),
+                (false, false) => Row::new(vec![format!("   {}", item.to_owned())]),
+            })
+            .chain(quick);
         // super::dim_buffer(area, f.buffer_mut(), 0.5);
         super::clear(center, f.buffer_mut(), ctx.theme.bg);
         Table::new(items, [Constraint::Percentage(100)])
-            .block(border_block(&ctx.theme, true).title(title!("Filter")))
+            .block(border_block(&ctx.theme, true).title(title!(ctx.tr("Filter"))))
             .highlight_style(style!(bg:ctx.theme.hl_bg))
             .render(center, f.buffer_mut(), &mut self.table.state);
     }
@@ -58,19 +147,39 @@ impl Widget for FilterPopup {
                     ctx.mode = Mode::Normal;
                 }
                 KeyCode::Char('j') | KeyCode::Down => {
-                    self.table.next_wrap(ctx.src_info.filters.len(), 1);
+                    self.table.next_wrap(num_rows(ctx), 1);
                 }
                 KeyCode::Char('k') | KeyCode::Up => {
-                    self.table.next_wrap(ctx.src_info.filters.len(), -1);
+                    self.table.next_wrap(num_rows(ctx), -1);
                 }
                 KeyCode::Char('G') => {
-                    self.table.select(ctx.src_info.filters.len() - 1);
+                    self.table.select(num_rows(ctx) - 1);
                 }
                 KeyCode::Char('g') => {
                     self.table.select(0);
                 }
-                KeyCode::Enter => {
-                    if let Some(i) = self.table.state.selected() {
+                KeyCode::Enter | KeyCode::Char(' ') => {
+                    let Some(i) = self.table.state.selected() else {
+                        return;
+                    };
+                    // Quick filters are toggled without searching again
+                    if let Some(quick) = i
+                        .checked_sub(ctx.src_info.filters.len())
+                        .and_then(|q| QuickFilter::ALL.get(q))
+                    {
+                        ctx.toggle_quick_filter(*quick);
+                    } else if ctx.src_info.capabilities.multi_filter {
+                        // Filters are checked first, then searched with together
+                        match (*code, i) {
+                            (KeyCode::Enter, _) => {
+                                self.selected = self.checked;
+                                ctx.mode = Mode::Loading(LoadType::Filtering);
+                                ctx.notify(format!("Filter by {}", self.checked_names(ctx)));
+                            }
+                            (_, 0) => self.checked = 0,
+                            (_, i) => self.checked ^= filter_bit(i),
+                        }
+                    } else if *code == KeyCode::Enter {
                         self.selected = i;
                         ctx.mode = Mode::Loading(LoadType::Filtering);
                         if let Some(f) = ctx.src_info.filters.get(i) {
@@ -86,6 +195,8 @@ impl Widget for FilterPopup {
     fn get_help() -> Option<Vec<(&'static str, &'static str)>> {
         Some(vec![
             ("Enter", "Confirm"),
+            ("Enter, Space", "Toggle quick filter"),
+            ("Space", "Check filter, where several can be used"),
             ("Esc, f, q", "Close"),
             ("g", "Top"),
             ("G", "Bottom"),
@@ -0,0 +1,124 @@
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::{
+    layout::{Margin, Rect},
+    widgets::Widget as _,
+    Frame,
+};
+
+use crate::{
+    app::{Context, Mode},
+    title,
+};
+
+use super::{
+    border_block,
+    input::{self, InputWidget},
+    Corner, Widget,
+};
+
+static WIDTH: u16 = 40;
+
+/// Jumps to the results on the page with a title matching what is typed,
+/// without searching the source again
+pub struct FindPopup {
+    pub input: InputWidget,
+    /// Row selected when the popup was opened, restored if cancelled
+    start: usize,
+    /// Row to select once the event has been handled
+    select: Option<usize>,
+}
+
+impl Default for FindPopup {
+    fn default() -> Self {
+        FindPopup {
+            input: InputWidget::new(100, None),
+            start: 0,
+            select: None,
+        }
+    }
+}
+
+impl FindPopup {
+    pub fn open(&mut self, selected: usize) {
+        self.input.clear();
+        self.start = selected;
+        self.select = None;
+    }
+
+    /// The row to jump to, if it changed
+    pub fn take_selection(&mut self) -> Option<usize> {
+        self.select.take()
+    }
+}
+
+impl Widget for FindPopup {
+    fn draw(&mut self, f: &mut Frame, ctx: &Context, area: Rect) {
+        let width = WIDTH.min(area.width);
+        let height = 3.min(area.height);
+        let popup = Rect::new(area.x, area.bottom() - height, width, height);
+        let block = border_block(&ctx.theme, true).title(title!(ctx.tr("Find")));
+        let buf = f.buffer_mut();
+        super::clear(popup, buf, ctx.theme.bg);
+        block.render(popup, buf);
+
+        if !self.input.input.trim().is_empty() {
+            let matches = ctx.results.num_matches(&self.input.input);
+            let count = title!("{} {}", matches, ctx.tr("matches"));
+            if let Some((tr, area)) = Corner::TopRight.try_title(count, popup, true) {
+                f.render_widget(tr, area);
+            }
+        }
+
+        let input_area = popup.inner(&Margin {
+            vertical: 1,
+            horizontal: 1,
+        });
+        self.input.draw(f, ctx, input_area);
+        self.input.show_cursor(f, input_area);
+    }
+
+    fn handle_event(&mut self, ctx: &mut Context, e: &Event) {
+        if let Event::Key(KeyEvent {
+            code,
+            kind: KeyEventKind::Press,
+            ..
+        }) = e
+        {
+            match code {
+                KeyCode::Esc => {
+                    ctx.find = None;
+                    self.select = Some(self.start);
+                    ctx.mode = Mode::Normal;
+                    return;
+                }
+                KeyCode::Enter => {
+                    // Keep the pattern for jumping between matches with n/N
+                    ctx.find = match self.input.input.trim().is_empty() {
+                        true => None,
+                        false => Some(self.input.input.to_owned()),
+                    };
+                    ctx.mode = Mode::Normal;
+                    return;
+                }
+                _ => {}
+            }
+        }
+        let prev = self.input.input.to_owned();
+        self.input.handle_event(ctx, e);
+        if self.input.input != prev {
+            self.select = Some(
+                ctx.results
+                    .find(&self.input.input, self.start, true)
+                    .unwrap_or(self.start),
+            );
+        }
+    }
+
+    fn get_help() -> Option<Vec<(&'static str, &'static str)>> {
+        let mut help = vec![("Enter", "Confirm"), ("Esc", "Cancel")];
+        if let Some(input_help) = input::InputWidget::get_help() {
+            help.extend(input_help);
+        }
+        Some(help)
+    }
+}
@@ -0,0 +1,127 @@
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, MouseButton, MouseEventKind};
+use ratatui::layout::{Position, Rect};
+
+use crate::app::Mode;
+
+/// A pane of the main layout, as opposed to a popup drawn over it
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Pane {
+    #[default]
+    Results,
+    Details,
+    Batch,
+}
+
+impl Pane {
+    /// The mode whose keys the pane handles
+    pub fn mode(self) -> Mode {
+        match self {
+            Pane::Results => Mode::Normal,
+            Pane::Details => Mode::Details,
+            Pane::Batch => Mode::Batch,
+        }
+    }
+
+    pub fn from_mode(mode: &Mode) -> Option<Pane> {
+        match mode {
+            Mode::Normal => Some(Pane::Results),
+            Mode::Details => Some(Pane::Details),
+            Mode::Batch => Some(Pane::Batch),
+            _ => None,
+        }
+    }
+}
+
+/// Which pane has focus, kept apart from the mode so popups can open and
+/// close over it. Each pane laid out is noted, so Tab only cycles through
+/// the panes there are, and clicking one focuses it.
+#[derive(Clone, Default)]
+pub struct Focus {
+    pane: Pane,
+    /// Panes laid out in the last frame, from the bottom up
+    areas: Vec<(Pane, Rect)>,
+}
+
+impl Focus {
+    pub fn pane(&self) -> Pane {
+        self.pane
+    }
+
+    pub fn set(&mut self, pane: Pane) {
+        self.pane = pane;
+    }
+
+    /// Forget where panes were drawn, before drawing the next frame
+    pub fn clear(&mut self) {
+        self.areas.clear();
+    }
+
+    /// Note that `pane` was drawn over `area`
+    pub fn drawn(&mut self, pane: Pane, area: Rect) {
+        self.areas.push((pane, area));
+    }
+
+    /// Note that `pane` can be focused, but is only drawn once it is
+    pub fn hidden(&mut self, pane: Pane) {
+        self.areas.push((pane, Rect::default()));
+    }
+
+    /// Whether `pane` was laid out in the last frame, even if hidden
+    pub fn is_available(&self, pane: Pane) -> bool {
+        self.areas.iter().any(|(p, _)| *p == pane)
+    }
+
+    /// The pane drawn on top at `column`, `row`
+    pub fn pane_at(&self, column: u16, row: u16) -> Option<Pane> {
+        self.areas
+            .iter()
+            .rev()
+            .find(|(_, area)| area.contains(Position::new(column, row)))
+            .map(|(pane, _)| *pane)
+    }
+
+    /// The pane after the focused one, or before it if not `forward`, out of
+    /// those available, in the order they're laid out
+    pub fn cycle(&self, forward: bool) -> Pane {
+        let panes: Vec<Pane> = [Pane::Results, Pane::Details, Pane::Batch]
+            .into_iter()
+            .filter(|p| *p == self.pane || self.is_available(*p))
+            .collect();
+        let Some(i) = panes.iter().position(|p| *p == self.pane) else {
+            return self.pane;
+        };
+        let next = match forward {
+            true => i + 1,
+            false => i + panes.len() - 1,
+        };
+        panes[next % panes.len()]
+    }
+}
+
+/// Whether `evt` moves focus to the next pane (`Some(true)`) or the previous
+/// one (`Some(false)`)
+pub fn cycle_key(evt: &Event) -> Option<bool> {
+    match evt {
+        Event::Key(KeyEvent {
+            code: KeyCode::Tab,
+            kind: KeyEventKind::Press,
+            ..
+        }) => Some(true),
+        Event::Key(KeyEvent {
+            code: KeyCode::BackTab,
+            kind: KeyEventKind::Press,
+            ..
+        }) => Some(false),
+        _ => None,
+    }
+}
+
+/// Where the left mouse button was pressed, if `evt` is a click
+pub fn click(evt: &Event) -> Option<(u16, u16)> {
+    match evt {
+        Event::Mouse(m) if m.kind == MouseEventKind::Down(MouseButton::Left) => {
+            Some((m.column, m.row))
+        }
+        _ => None,
+    }
+}
@@ -8,16 +8,21 @@ use ratatui::{
     Frame,
 };
 
+use unicode_width::UnicodeWidthStr as _;
+
 use crate::{
     app::{Context, Mode},
     style, title,
 };
 
-use super::{border_block, StatefulTable, Widget};
+use super::{border_block, input::InputWidget, Corner, StatefulTable, Widget};
 
 pub struct HelpPopup {
     pub table: StatefulTable<(&'static str, &'static str)>,
     pub prev_mode: Mode,
+    pub filter: InputWidget,
+    filtering: bool,
+    all: Vec<(&'static str, &'static str)>,
 }
 
 impl Default for HelpPopup {
@@ -25,16 +30,152 @@ impl Default for HelpPopup {
         HelpPopup {
             table: StatefulTable::empty(),
             prev_mode: Mode::Normal,
+            filter: InputWidget::new(30, None),
+            filtering: false,
+            all: vec![],
         }
     }
 }
 
 impl HelpPopup {
     pub fn with_items(&mut self, items: Vec<(&'static str, &'static str)>, prev_mode: Mode) {
+        self.all = items;
+        self.prev_mode = prev_mode;
+        self.filter.clear();
+        self.filtering = false;
+        self.apply_filter();
+    }
+
+    /// Only show bindings whose key or action contains the filter
+    fn apply_filter(&mut self) {
+        let filter = self.filter.input.to_lowercase();
+        let items: Vec<(&'static str, &'static str)> = self
+            .all
+            .iter()
+            .filter(|(key, map)| {
+                key.to_lowercase().contains(&filter) || map.to_lowercase().contains(&filter)
+            })
+            .copied()
+            .collect();
         self.table.scrollbar_state = self.table.scrollbar_state.content_length(items.len());
         self.table.items = items;
-        self.prev_mode = prev_mode;
+        self.table.select(0);
+    }
+}
+
+/// Subcommands and their arguments, with what each does
+pub static COMMANDS: &[(&str, &str)] = &[
+    (
+        "secret <set|delete> <name>",
+        "Store or delete a download client secret in the OS keyring",
+    ),
+    (
+        "self-update",
+        "Replace nyaa with the latest x86_64 Linux release, once its checksum matches",
+    ),
+];
+
+/// Command line options and their arguments, with what each does
+pub static OPTIONS: &[(&str, &str)] = &[
+    ("-h, --help", "Print this help, with every mode's keybinds"),
+    ("-V, --version", "Print the version"),
+    (
+        "--offline",
+        "Search the built-in mock source instead of the real ones, and skip downloads",
+    ),
+    ("--record <file>", "Record the session's input to a file"),
+    (
+        "--replay <file>",
+        "Replay a recorded session without a terminal, then print the last frame",
+    ),
+    (
+        "--plain <query>",
+        "Search without a terminal, printing each result on a line",
+    ),
+    (
+        "--stats-json <file>",
+        "Write search stats to a file on exit",
+    ),
+    (
+        "--export-keybinds <file>",
+        "Write every mode's keybinds to a Markdown file",
+    ),
+    ("--export-man <file>", "Write the man page to a file"),
+];
+
+/// Format the commands, options and each mode's keybinds for `--help`
+pub fn to_usage(sections: &[(String, Vec<(&'static str, &'static str)>)]) -> String {
+    fn list(out: &mut String, entries: &[(&str, &str)]) {
+        let width = entries.iter().map(|(k, _)| k.width()).max().unwrap_or(0);
+        for (key, desc) in entries.iter() {
+            let pad = " ".repeat(width - key.width());
+            out.push_str(&format!("  {}{}  {}\n", key, pad, desc));
+        }
     }
+
+    let mut out = format!(
+        "nyaa v{}\n{}\n\nUsage: nyaa [OPTIONS]\n       nyaa <COMMAND>\n\nCommands:\n",
+        env!("CARGO_PKG_VERSION"),
+        env!("CARGO_PKG_DESCRIPTION")
+    );
+    list(&mut out, COMMANDS);
+    out.push_str("\nOptions:\n");
+    list(&mut out, OPTIONS);
+    for (mode, binds) in sections.iter() {
+        out.push_str(&format!("\n{} keybinds:\n", mode));
+        list(&mut out, binds);
+    }
+    out
+}
+
+/// Escape text for roff, so it isn't read as a request or escape sequence
+fn roff(text: &str) -> String {
+    let text = text.replace('\\', "\\e").replace('-', "\\-");
+    match text.starts_with(['.', '\'']) {
+        true => format!("\\&{}", text),
+        false => text,
+    }
+}
+
+/// Format the commands, options and each mode's keybinds as a man page
+pub fn to_man(sections: &[(String, Vec<(&'static str, &'static str)>)]) -> String {
+    fn list(out: &mut String, entries: &[(&str, &str)]) {
+        for (key, desc) in entries.iter() {
+            out.push_str(&format!(".TP\n.B {}\n{}\n", roff(key), roff(desc)));
+        }
+    }
+
+    let mut man = format!(
+        ".TH NYAA 1 \"\" \"nyaa {}\" \"User Commands\"\n.SH NAME\nnyaa \\- browse and download torrents in the terminal\n",
+        env!("CARGO_PKG_VERSION"),
+    );
+    man.push_str(".SH SYNOPSIS\n.B nyaa\n[\\fIOPTIONS\\fR]\n.br\n.B nyaa\n\\fICOMMAND\\fR\n");
+    man.push_str(".SH COMMANDS\n");
+    list(&mut man, COMMANDS);
+    man.push_str(".SH OPTIONS\n");
+    list(&mut man, OPTIONS);
+    man.push_str(".SH KEYBINDS\n");
+    for (mode, binds) in sections.iter() {
+        man.push_str(&format!(".SS {}\n", roff(mode)));
+        list(&mut man, binds);
+    }
+    man.push_str(&format!(
+        ".SH SEE ALSO\n{}\n",
+        roff(env!("CARGO_PKG_REPOSITORY"))
+    ));
+    man
+}
+
+/// Format each mode's keybinds as a Markdown table
+pub fn to_markdown(sections: &[(String, Vec<(&'static str, &'static str)>)]) -> String {
+    let mut md = "# Keybinds\n".to_owned();
+    for (mode, binds) in sections.iter() {
+        md.push_str(&format!("\n## {}\n| Key | Map |\n| --- | --- |\n", mode));
+        for (key, map) in binds.iter() {
+            md.push_str(&format!("| {} | {} |\n", key, map));
+        }
+    }
+    md
 }
 
 impl Widget for HelpPopup {
@@ -42,12 +183,15 @@ impl Widget for HelpPopup {
         let buf = f.buffer_mut();
         let max_size = 35;
 
-        // Get max len of Key and Action
-        let (key_max, map_max) = self.table.items.iter().fold((15, 15), |acc, e| {
-            (max(acc.0, e.0.len() as u16), max(acc.1, e.1.len() as u16))
+        // Size to every entry, so the popup stays put while filtering
+        let (key_max, map_max) = self.all.iter().fold((15, 15), |acc, e| {
+            (
+                max(acc.0, e.0.width() as u16),
+                max(acc.1, e.1.width() as u16),
+            )
         });
         // Cap height between the number of entries + 3 for padding, and 20
-        let height = min(max_size, self.table.items.len() + 3) as u16;
+        let height = min(max_size, self.all.len() + 3) as u16;
 
         let center = super::centered_rect(key_max + map_max + 6, height, area);
         let items = self.table.items.iter().map(|(key, map)| {
@@ -58,9 +202,9 @@ impl Widget for HelpPopup {
             ])
         });
         let header = Row::new([
-            Line::from("Key").alignment(Alignment::Center),
+            Line::from(ctx.tr("Key")).alignment(Alignment::Center),
             Line::from(""),
-            Line::from("Action").alignment(Alignment::Center),
+            Line::from(ctx.tr("Action")).alignment(Alignment::Center),
         ])
         .style(style!(bold, underlined, fg:ctx.theme.border_focused_color))
         .height(1)
@@ -77,10 +221,11 @@ impl Widget for HelpPopup {
         );
 
         let table = Table::new(items, [Constraint::Percentage(100)])
-            .block(
-                border_block(&ctx.theme, true)
-                    .title(title!("Help: {}", self.prev_mode.to_string())),
-            )
+            .block(border_block(&ctx.theme, true).title(title!(
+                "{}: {}",
+                ctx.tr("Help"),
+                ctx.tr(&self.prev_mode.to_string())
+            )))
             .header(header)
             .widths(Constraint::from_lengths([key_max, 1, map_max]))
             .highlight_style(style!(bg:ctx.theme.hl_bg));
@@ -98,6 +243,17 @@ impl Widget for HelpPopup {
             });
             sb.render(sb_area, buf, &mut self.table.scrollbar_state);
         }
+
+        if self.filtering || !self.filter.input.is_empty() {
+            let text = format!("/{}", self.filter.input);
+            if let Some((line, area)) = Corner::BottomLeft.try_title(text, center, true) {
+                f.render_widget(line, area);
+                if self.filtering {
+                    let input_area = Rect::new(area.x + 1, area.y, area.width, 1);
+                    self.filter.show_cursor(f, input_area);
+                }
+            }
+        }
     }
 
     fn handle_event(&mut self, ctx: &mut Context, e: &Event) {
@@ -107,7 +263,29 @@ impl Widget for HelpPopup {
             ..
         }) = e
         {
+            if self.filtering {
+                match code {
+                    KeyCode::Esc => {
+                        self.filter.clear();
+                        self.filtering = false;
+                        self.apply_filter();
+                    }
+                    KeyCode::Enter => self.filtering = false,
+                    KeyCode::Down => self.table.next_wrap(1),
+                    KeyCode::Up => self.table.next_wrap(-1),
+                    _ => {
+                        self.filter.handle_event(ctx, e);
+                        self.apply_filter();
+                    }
+                }
+                return;
+            }
             match code {
+                KeyCode::Char('/') => self.filtering = true,
+                KeyCode::Esc if !self.filter.input.is_empty() => {
+                    self.filter.clear();
+                    self.apply_filter();
+                }
                 KeyCode::Esc | KeyCode::Char('?') | KeyCode::F(1) | KeyCode::Char('q') => {
                     self.prev_mode.clone_into(&mut ctx.mode);
                 }
@@ -118,7 +296,7 @@ impl Widget for HelpPopup {
                     self.table.next_wrap(-1);
                 }
                 KeyCode::Char('G') => {
-                    self.table.select(self.table.items.len() - 1);
+                    self.table.select(self.table.items.len().saturating_sub(1));
                 }
                 KeyCode::Char('g') => {
                     self.table.select(0);
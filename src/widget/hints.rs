@@ -0,0 +1,74 @@
+use crossterm::event::Event;
+use ratatui::{
+    layout::Rect,
+    text::{Line, Span},
+    widgets::{Paragraph, Widget as _},
+    Frame,
+};
+use unicode_width::UnicodeWidthStr as _;
+
+use crate::{app::Context, style};
+
+use super::Widget;
+
+// Most modes list their important keys first, so only show the first few
+static MAX_HINTS: usize = 6;
+static SEPARATOR: &str = " │ ";
+
+/// A single line of keybinds for the current mode, shown at the bottom of the
+/// screen when `hint_bar` is enabled
+#[derive(Default)]
+pub struct HintBar {
+    pub hints: Vec<(&'static str, &'static str)>,
+}
+
+impl HintBar {
+    /// Show the first few of `hints`, ending with how to open help if `help`
+    pub fn with_items(&mut self, mut hints: Vec<(&'static str, &'static str)>, help: bool) {
+        match help {
+            true => {
+                hints.truncate(MAX_HINTS - 1);
+                hints.push(("F1", "Help"));
+            }
+            false => hints.truncate(MAX_HINTS),
+        }
+        self.hints = hints;
+    }
+}
+
+impl Widget for HintBar {
+    fn draw(&mut self, f: &mut Frame, ctx: &Context, area: Rect) {
+        let mut spans: Vec<Span> = vec![Span::raw(" ")];
+        let mut width = 1;
+        for (key, map) in self.hints.iter() {
+            let map = ctx.tr(map);
+            let sep = match spans.len() {
+                1 => "",
+                _ => SEPARATOR,
+            };
+            let hint_width = sep.width() + key.width() + 1 + map.width();
+            // Leave out hints which don't fit instead of cutting them off
+            if width + hint_width > area.width as usize {
+                break;
+            }
+            width += hint_width;
+            if !sep.is_empty() {
+                spans.push(Span::styled(sep, style!(fg:ctx.theme.border_color)));
+            }
+            spans.push(Span::styled(
+                *key,
+                style!(bold, fg:ctx.theme.border_focused_color),
+            ));
+            spans.push(Span::styled(format!(" {}", map), style!(fg:ctx.theme.fg)));
+        }
+        Paragraph::new(Line::from(spans))
+            .style(style!(bg:ctx.theme.bg))
+            .render(area, f.buffer_mut());
+    }
+
+    fn handle_event(&mut self, _ctx: &mut Context, _e: &Event) {}
+
+    fn get_help() -> Option<Vec<(&'static str, &'static str)>> {
+        None
+    }
+}
@@ -27,6 +27,11 @@ impl InputWidget {
         }
     }
 
+    pub fn clear(&mut self) {
+        self.input.clear();
+        self.cursor = 0;
+    }
+
     pub fn show_cursor(&self, f: &mut Frame, area: Rect) {
         f.set_cursor(
             min(area.x + self.cursor as u16, area.x + area.width.max(1) - 1),
@@ -53,6 +58,9 @@ impl super::Widget for InputWidget {
         p.render(area, f.buffer_mut());
     }
 
+    // Keys which can't do anything right now are still used up, rather than
+    // falling through to later arms
+    #[allow(clippy::collapsible_match)]
     fn handle_event(&mut self, _ctx: &mut Context, evt: &Event) {
         if let Event::Key(KeyEvent {
             code,
@@ -78,21 +86,21 @@ impl super::Widget for InputWidget {
                     let non_space = self.input[..min(self.cursor, self.input.len())]
                         .rfind(|item| item != ' ')
                         .unwrap_or(0);
-                    self.cursor = match self.input[..non_space].rfind(|item| item == ' ') {
+                    self.cursor = match self.input[..non_space].rfind(' ') {
                         Some(pos) => pos + 1,
                         None => 0,
                     };
                 }
                 (Char('w') | Right, &KeyModifiers::CONTROL) => {
                     let idx = min(self.cursor + 1, self.input.len());
-                    self.cursor = match self.input[idx..].find(|item| item == ' ') {
+                    self.cursor = match self.input[idx..].find(' ') {
                         Some(pos) => self.cursor + pos + 2,
                         None => self.input.len(),
                     };
                 }
                 (Delete, &KeyModifiers::CONTROL | &KeyModifiers::ALT) => {
                     let idx = min(self.cursor + 1, self.input.len());
-                    let new_cursor = match self.input[idx..].find(|item| item == ' ') {
+                    let new_cursor = match self.input[idx..].find(' ') {
                         Some(pos) => self.cursor + pos + 2,
                         None => self.input.len(),
                     };
@@ -101,7 +109,7 @@ impl super::Widget for InputWidget {
                 (Backspace, &KeyModifiers::CONTROL | &KeyModifiers::ALT) => {
                     let cursor = min(self.cursor, self.input.len());
                     let non_space = self.input[..cursor].rfind(|i| i != ' ').unwrap_or(0);
-                    self.cursor = match self.input[..non_space].rfind(|i| i == ' ') {
+                    self.cursor = match self.input[..non_space].rfind(' ') {
                         Some(pos) => pos + 1,
                         None => 0,
                     };
@@ -14,6 +14,12 @@ pub struct NotificationConfig {
     pub duration: Option<f64>,
     pub max_width: Option<u16>,
     pub animation_speed: Option<f64>,
+    /// Slide notifications in and out, instead of just showing them
+    pub animate: Option<bool>,
+    /// Most notifications on screen at once, the oldest are dropped first
+    pub max_notifications: Option<usize>,
+    /// Only write notifications to the log file, errors are still shown
+    pub quiet: Option<bool>,
 }
 
 pub struct NotificationWidget {
@@ -22,6 +28,9 @@ pub struct NotificationWidget {
     position: Corner,
     max_width: u16,
     animation_speed: f64,
+    animate: bool,
+    max_notifs: usize,
+    quiet: bool,
 }
 
 impl Default for NotificationWidget {
@@ -32,6 +41,9 @@ impl Default for NotificationWidget {
             position: Corner::TopRight,
             max_width: 75,
             animation_speed: 4.,
+            animate: true,
+            max_notifs: MAX_NOTIFS,
+            quiet: false,
         }
     }
 }
@@ -42,6 +54,16 @@ impl NotificationWidget {
         self.duration = conf.duration.unwrap_or(self.duration).max(0.01);
         self.max_width = conf.max_width.unwrap_or(self.max_width);
         self.animation_speed = conf.animation_speed.unwrap_or(self.animation_speed);
+        self.animate = conf.animate.unwrap_or(self.animate);
+        self.max_notifs = conf
+            .max_notifications
+            .unwrap_or(self.max_notifs)
+            .clamp(1, MAX_NOTIFS);
+        self.quiet = conf.quiet.unwrap_or(self.quiet);
+    }
+
+    pub fn is_quiet(&self) -> bool {
+        self.quiet
     }
 
     pub fn is_animating(&self) -> bool {
@@ -55,11 +77,42 @@ impl NotificationWidget {
             self.position,
             self.animation_speed,
             self.max_width,
+            self.animate,
             false,
         );
         self.add(new_notif);
     }
 
+    /// Show progress in a notification that stays open until `None` is given
+    pub fn set_progress(&mut self, progress: Option<String>) {
+        let current = self
+            .notifs
+            .iter_mut()
+            .find(|n| n.is_sticky() && !n.is_leaving());
+        match (current, progress) {
+            (Some(n), Some(p)) => {
+                if n.content() != p {
+                    n.set_content(p);
+                }
+            }
+            (Some(n), None) => n.dismiss(),
+            (None, Some(p)) => {
+                let new_notif = NotifyBox::new(
+                    p,
+                    self.duration,
+                    self.position,
+                    self.animation_speed,
+                    self.max_width,
+                    self.animate,
+                    false,
+                )
+                .sticky();
+                self.add(new_notif);
+            }
+            (None, None) => {}
+        }
+    }
+
     pub fn add_error(&mut self, error: String) {
         let new_notif = NotifyBox::new(
             error,
@@ -67,6 +120,7 @@ impl NotificationWidget {
             self.position,
             self.animation_speed,
             self.max_width,
+            self.animate,
             true,
         );
         self.add(new_notif);
@@ -87,9 +141,9 @@ impl NotificationWidget {
     }
 
     fn dismiss_oldest(&mut self) {
-        if self.notifs.len() >= MAX_NOTIFS {
+        if self.notifs.len() >= self.max_notifs {
             self.notifs
-                .drain(..=self.notifs.len().saturating_sub(MAX_NOTIFS));
+                .drain(..=self.notifs.len().saturating_sub(self.max_notifs));
         }
     }
 
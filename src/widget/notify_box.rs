@@ -5,7 +5,7 @@ use ratatui::{
     Frame,
 };
 
-use crate::{app::Context, style};
+use crate::{app::Context, style, theme::WidgetKind};
 
 use super::Corner;
 
@@ -112,6 +112,11 @@ impl AnimateState {
         self.done
     }
 
+    fn finish(&mut self) {
+        self.time = 1.0;
+        self.done = true;
+    }
+
     fn reset(&mut self) {
         self.time = 0.0;
         self.done = false;
@@ -123,6 +128,7 @@ pub struct NotifyBox {
     pub time: f64,
     pub duration: f64,
     animation_speed: f64,
+    animate: bool,
     max_width: u16,
     position: Corner,
     width: u16,
@@ -133,6 +139,7 @@ pub struct NotifyBox {
     leave_state: AnimateState,
     pub pos: Option<(i32, i32)>,
     error: bool,
+    sticky: bool,
 }
 
 impl NotifyBox {
@@ -142,6 +149,7 @@ impl NotifyBox {
         position: Corner,
         animation_speed: f64,
         max_width: u16,
+        animate: bool,
         error: bool,
     ) -> Self {
         let raw_content = content.clone();
@@ -154,6 +162,7 @@ impl NotifyBox {
             raw_content,
             position,
             animation_speed,
+            animate,
             max_width,
             start_offset: 0,
             stop_offset: 0,
@@ -163,9 +172,33 @@ impl NotifyBox {
             leave_state: AnimateState::new(),
             pos: None,
             error,
+            sticky: false,
         }
     }
 
+    /// Keep the notification open until it is dismissed
+    pub fn sticky(mut self) -> Self {
+        self.sticky = true;
+        self
+    }
+
+    pub fn is_sticky(&self) -> bool {
+        self.sticky
+    }
+
+    pub fn set_content(&mut self, content: String) {
+        self.raw_content = content;
+    }
+
+    pub fn content(&self) -> &str {
+        &self.raw_content
+    }
+
+    pub fn dismiss(&mut self) {
+        self.sticky = false;
+        self.time = 1.0;
+    }
+
     pub fn width(&self) -> u16 {
         self.width
     }
@@ -241,18 +274,21 @@ impl NotifyBox {
         let scroll_y = (pos.1 + 1).min(0).unsigned_abs() as u16;
         let block = match self.error {
             false => Block::new()
-                .border_style(style!(fg:ctx.theme.border_focused_color))
+                .border_style(style!(fg:ctx.theme.border_of(WidgetKind::Notification, true)))
                 .bg(ctx.theme.bg)
                 .fg(ctx.theme.fg)
                 .borders(border)
                 .border_type(ctx.theme.border),
             true => {
                 let mut block = Block::new()
-                    .border_style(style!(fg:ctx.theme.error))
+                    .border_style(style!(fg:ctx.theme.border_of(WidgetKind::Error, true)))
                     .bg(ctx.theme.bg)
                     .fg(ctx.theme.error)
                     .borders(border)
                     .border_type(ctx.theme.border);
+                if let Some(title) = ctx.theme.widgets.get(WidgetKind::Error).title {
+                    block = block.title_style(style!(fg:title));
+                }
                 if border.contains(Borders::TOP) {
                     let title = "Error: Press ESC to dismiss...";
                     if let Some(sub) = title.get((scroll_x as usize)..) {
@@ -278,6 +314,19 @@ impl NotifyBox {
             self.start_offset,
             self.stop_offset,
         );
+        if !self.animate {
+            // Jump straight to the end of each animation
+            return match self.time < 1.0 {
+                true => {
+                    self.enter_state.finish();
+                    stop_pos
+                }
+                false => {
+                    self.leave_state.finish();
+                    leave_pos
+                }
+            };
+        }
         if self.time < 1.0 {
             self.enter_state
                 .ease_out(start_pos, stop_pos, self.animation_speed, deltatime)
@@ -292,7 +341,7 @@ impl NotifyBox {
         self.pos = Some(self.next_pos(deltatime, area));
 
         // Dont automatically dismiss errors
-        if self.enter_state.is_done() && !self.error {
+        if self.enter_state.is_done() && !self.error && !self.sticky {
             self.time = 1.0_f64.min(self.time + deltatime / self.duration);
         }
         last_pos != self.pos
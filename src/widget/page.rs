@@ -1,7 +1,7 @@
 use std::cmp::{max, min};
 
 use crate::{
-    app::{Context, LoadType, Mode},
+    app::{Context, Mode},
     title,
 };
 use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
@@ -34,8 +34,8 @@ impl Widget for PagePopup {
         let buf = f.buffer_mut();
         let center = super::centered_rect(13, 3, area);
         let page_p = Paragraph::new(self.input.input.clone());
-        let indicator =
-            Paragraph::new(">").block(border_block(&ctx.theme, true).title(title!("Goto Page")));
+        let indicator = Paragraph::new(">")
+            .block(border_block(&ctx.theme, true).title(title!(ctx.tr("Goto Page"))));
         super::clear(center, buf, ctx.theme.bg);
         indicator.render(center, buf);
 
@@ -71,14 +71,14 @@ impl Widget for PagePopup {
                     self.input.cursor = 0;
                 }
                 KeyCode::Enter => {
-                    ctx.page = max(
+                    ctx.mode = Mode::Normal;
+                    ctx.goto_page(max(
                         min(
                             self.input.input.parse().unwrap_or(1),
                             ctx.results.response.last_page,
                         ),
                         1,
-                    );
-                    ctx.mode = Mode::Loading(LoadType::Searching);
+                    ));
 
                     // Clear input on Enter
                     self.input.input.clear();
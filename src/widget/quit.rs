@@ -0,0 +1,96 @@
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::{
+    layout::Rect,
+    style::Stylize as _,
+    text::Line,
+    widgets::{Paragraph, Widget as _},
+    Frame,
+};
+
+use crate::{
+    app::{Context, Mode},
+    client::progress,
+    title,
+};
+
+use super::{border_block, Widget};
+
+static WIDTH: u16 = 60;
+
+/// Shown when quitting while downloads are in flight, to wait for them to
+/// finish or cancel them before quitting
+#[derive(Default)]
+pub struct QuitPopup;
+
+impl Widget for QuitPopup {
+    fn draw(&mut self, f: &mut Frame, ctx: &Context, area: Rect) {
+        let waiting = ctx.is_waiting_to_quit();
+        let header = match waiting {
+            true => format!("Quitting once {} downloads finish", ctx.downloads),
+            false => format!("Quit with {} downloads still running?", ctx.downloads),
+        };
+        // Torrents sent to an API client have no progress until it replies
+        let pending =
+            progress::summary().unwrap_or_else(|| format!("Sending torrents to {}", ctx.client));
+        let batch = progress::total_batch_progress().map(|b| {
+            format!(
+                "Batch: {}/{} fetched, {} failed",
+                b.fetched, b.total, b.failed
+            )
+        });
+        let keys = match waiting {
+            true => ctx.tr("c: cancel them and quit, Esc: back"),
+            false => ctx.tr("Enter: wait for them, c: cancel them, Esc: back"),
+        };
+        let lines: Vec<Line> = [Line::from(header.bold()), Line::default()]
+            .into_iter()
+            .chain(
+                pending
+                    .lines()
+                    .chain(batch.as_deref())
+                    .map(|l| Line::from(format!(" {}", l))),
+            )
+            .chain([Line::default(), Line::from(keys.fg(ctx.theme.border_color))])
+            .collect();
+
+        let height = (lines.len() as u16 + 2).min(area.height);
+        let center = super::centered_rect(WIDTH, height, area);
+        let buf = f.buffer_mut();
+        super::clear(center, buf, ctx.theme.bg);
+        Paragraph::new(lines)
+            .block(border_block(&ctx.theme, true).title(title!(ctx.tr("Quit"))))
+            .fg(ctx.theme.fg)
+            .render(center, buf);
+    }
+
+    fn handle_event(&mut self, ctx: &mut Context, e: &Event) {
+        if let Event::Key(KeyEvent {
+            code,
+            kind: KeyEventKind::Press,
+            ..
+        }) = e
+        {
+            match code {
+                KeyCode::Enter | KeyCode::Char('w') => {
+                    ctx.quit_when_done(true);
+                }
+                KeyCode::Char('c') => {
+                    ctx.cancel_downloads_and_quit();
+                }
+                KeyCode::Esc | KeyCode::Char('q') => {
+                    ctx.quit_when_done(false);
+                    ctx.mode = Mode::Normal;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn get_help() -> Option<Vec<(&'static str, &'static str)>> {
+        Some(vec![
+            ("Enter, w", "Wait for downloads, then quit"),
+            ("c", "Cancel downloads and quit"),
+            ("Esc, q", "Back, without quitting"),
+        ])
+    }
+}
@@ -1,20 +1,109 @@
+use std::ops::Range;
+
 use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
 use ratatui::{
-    layout::{Margin, Rect},
+    layout::{Alignment, Margin, Rect},
     style::{Style, Stylize as _},
     symbols,
     text::Line,
-    widgets::{Clear, Paragraph, Row, ScrollbarOrientation, StatefulWidget, Table, Widget},
+    widgets::{
+        Clear, Paragraph, Row, ScrollbarOrientation, StatefulWidget, Table, TableState, Widget,
+    },
     Frame,
 };
 
 use crate::{
-    app::{Context, LoadType, Mode},
+    app::{Context, LoadType, Mode, Widgets},
+    results::{EmptyReason, ResultRow, Results},
+    source::{nyaa_html::NyaaTransport, Item, ItemId},
+    theme::WidgetKind,
     title,
     widget::sort::SortDir,
 };
 
-use super::{border_block, centered_rect, Corner, VirtualStatefulTable};
+use super::{centered_rect, focus::Pane, pane_block, Corner, VirtualStatefulTable};
+
+static SPINNER: &[&str] = &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+/// How often the loading spinner advances
+pub static SPINNER_MILLIS: u128 = 100;
+static SPINNER_DELAY_MILLIS: u128 = 1000;
+
+/// The rows a table `height` rows tall shows, scrolled as little as possible
+/// from `offset` to keep `selected` in view
+fn visible_rows(
+    selected: Option<usize>,
+    offset: usize,
+    height: usize,
+    num_items: usize,
+) -> Range<usize> {
+    let Some(last) = num_items.checked_sub(1) else {
+        return 0..0;
+    };
+    let height = height.max(1);
+    let selected = selected.unwrap_or(0).min(last);
+    let mut offset = offset.min(last);
+    if selected >= offset + height {
+        offset = selected + 1 - height;
+    }
+    offset = offset.min(selected);
+    offset..(offset + height).min(num_items)
+}
+
+/// Said when a key is pressed for something the source can't do
+fn unsupported(ctx: &mut Context, what: &str) {
+    let name = ctx.src.name(&ctx.config.sources);
+    ctx.notify(format!("{} doesn't support {}", name, what));
+}
+
+/// Put a star before the title of a starred item
+fn star_title(row: &mut ResultRow, item: &Item, ctx: &Context) {
+    if !ctx.results.is_starred(item) {
+        return;
+    }
+    if let Some(cell) = row
+        .cells
+        .iter_mut()
+        .find(|c| c.content.ends_with(&item.title))
+    {
+        cell.content = format!("★ {}", cell.content);
+    }
+}
+
+/// What to show in place of the results, with what might be done about it
+fn empty_message(ctx: &Context, reason: EmptyReason) -> Vec<Line<'static>> {
+    let search = &ctx.results.search;
+    let config = &ctx.config.sources;
+    let hint = |s: &str| Line::from(ctx.tr(s).to_owned()).fg(ctx.theme.border_color);
+    match reason {
+        EmptyReason::NoResults => {
+            let mut lines = vec![Line::from(ctx.tr("No results").to_owned())];
+            if search.category != ctx.src.default_category(config) || search.filter != 0 {
+                lines.push(hint("Try another category (c) or filter (f)"));
+            }
+            if search.user.is_some() {
+                lines.push(hint("Try searching all users (u)"));
+            }
+            lines
+        }
+        EmptyReason::Unreadable => {
+            let mut lines = vec![
+                Line::from(ctx.tr("Nothing could be read from the page").to_owned())
+                    .fg(ctx.theme.error),
+                hint("The mirror may be down, or its layout may have changed"),
+            ];
+            if ctx.src.transport(config) == NyaaTransport::Html && ctx.src_info.capabilities.rss {
+                lines.push(hint("Try setting rss = true for the source (s, then e)"));
+            }
+            lines.push(hint("Try another source (s) or base_url"));
+            lines
+        }
+        EmptyReason::Filtered => vec![
+            Line::from(ctx.tr("Every result is hidden by quick filters").to_owned()),
+            hint("Turn them off in the filter popup (f)"),
+        ],
+    }
+}
 
 pub struct ResultsWidget {
     pub table: VirtualStatefulTable,
@@ -29,12 +118,85 @@ impl ResultsWidget {
         *self.table.state.offset_mut() = 0;
     }
 
+    fn goto_page(&mut self, ctx: &mut Context, page: usize) {
+        ctx.goto_page(page);
+        // Pages of a deep search are shown right away
+        if ctx.results.deep.is_some() {
+            self.reset();
+        }
+    }
+
+    /// Select the item in `next` which was selected in `prev`, or the nearest
+    /// row if it is gone
+    pub fn reselect(&mut self, prev: &Results, next: &Results) {
+        let Some(sel) = self.table.selected() else {
+            return self.reset();
+        };
+        let idx = prev
+            .response
+            .items
+            .get(sel)
+            .and_then(|item| next.response.items.iter().position(|i| i.id == item.id))
+            .unwrap_or(sel.min(next.response.items.len().saturating_sub(1)));
+        self.table.select(idx);
+    }
+
+    /// Select the next or previous title matching the find pattern
+    fn find_next(&mut self, ctx: &mut Context, forward: bool) {
+        let Some(pattern) = ctx.find.as_ref() else {
+            return;
+        };
+        let len = ctx.results.response.items.len();
+        let sel = self.table.selected().unwrap_or(0);
+        let from = match forward {
+            true => sel + 1,
+            false => sel + len.saturating_sub(1),
+        };
+        match ctx.results.find(pattern, from, forward) {
+            Some(i) => self.table.select(i),
+            None => ctx.notify(format!("No titles match \"{}\"", pattern)),
+        }
+    }
+
+    /// Move the selection `amt` rows, adding or removing each row crossed
+    /// from the batch while in visual mode
+    pub fn move_by(&mut self, ctx: &mut Context, amt: isize) {
+        let len = ctx.results.response.items.len();
+        if !self.control_space {
+            self.table.next(len, amt);
+            return;
+        }
+        for _ in 0..amt.unsigned_abs() {
+            let prev = self.table.selected().unwrap_or(0);
+            let selected = self.table.next(len, amt.signum());
+            if prev == selected {
+                break;
+            }
+            // Rows are toggled on the way away from the anchor, and back again
+            // on the way towards it
+            let toggled = match amt > 0 {
+                true if selected <= self.visual_anchor => prev,
+                false if selected >= self.visual_anchor => prev,
+                _ => selected,
+            };
+            self.try_select_toggle(ctx, toggled);
+        }
+    }
+
     fn try_select_toggle(&self, ctx: &mut Context, sel: usize) {
         if let Some(item) = ctx.results.response.items.get(sel) {
+            let item = item.to_owned();
             if let Some(p) = ctx.batch.iter().position(|s| s.id == item.id) {
+                ctx.save_batch(format!("removing \"{}\"", item.title));
                 ctx.batch.remove(p);
+            } else if ctx.results.dead.contains(&item.id) {
+                ctx.notify(format!(
+                    "Skipped \"{}\", its torrent link is dead",
+                    item.title
+                ));
             } else {
-                ctx.batch.push(item.to_owned());
+                ctx.save_batch(format!("adding \"{}\"", item.title));
+                ctx.batch.push(item);
             }
         }
     }
@@ -54,28 +216,82 @@ impl Default for ResultsWidget {
 impl super::Widget for ResultsWidget {
     fn draw(&mut self, f: &mut Frame, ctx: &Context, area: Rect) {
         let buf = f.buffer_mut();
-        let focus_color = match ctx.mode {
-            Mode::Normal | Mode::KeyCombo(_) => ctx.theme.border_focused_color,
-            _ => ctx.theme.border_color,
+        let focus_color = match ctx.has_focus(Pane::Results) {
+            true => ctx.theme.border_focused_color,
+            false => ctx.theme.border_color,
+        };
+        // Quick filters may have hidden the selected row
+        let len = ctx.results.response.items.len();
+        if self.table.selected().is_some_and(|s| s >= len) {
+            self.table.select(len.saturating_sub(1));
+        }
+        let num_items = match ctx.load_type {
+            Some(_) => 0,
+            None => ctx.results.table.rows.len(),
         };
-        let header: Row = ctx.results.table.headers.clone().into();
+        let header_height = super::pane_header_height(ctx);
+        super::scroll_padding(
+            self.table.selected().unwrap_or(0),
+            area.height as usize,
+            header_height,
+            num_items,
+            ctx.config.scroll_padding,
+            self.table.state.offset_mut(),
+        );
+        // Only the rows in view are built, so a deep search with thousands of
+        // results draws as fast as a single page
+        let rows = visible_rows(
+            self.table.selected(),
+            self.table.state.offset(),
+            (area.height as usize).saturating_sub(header_height),
+            num_items,
+        );
+        *self.table.state.offset_mut() = rows.start;
+        let offset = rows.start;
+        let res_table = ctx.results.table.window(rows, ctx.narrow);
+        let header: Row = res_table.headers.clone().into();
         let header = header.fg(focus_color).underlined();
 
         Clear.render(area, buf);
+        // Messages in the middle would peek out from under popups
+        let under_popup = Widgets::is_popup(&ctx.mode);
         let items: Vec<Row> = match &ctx.load_type {
+            Some(_) if under_popup => vec![],
             Some(loadtype) => {
-                let message = format!("{}…", loadtype);
-                let load_area = centered_rect(message.len() as u16, 1, area);
+                let elapsed = ctx.load_start.map(|s| s.elapsed()).unwrap_or_default();
+                let host = ctx.src.host(&ctx.config.sources);
+                // Fast requests would only flicker, so wait before animating
+                let message = match elapsed.as_millis() < SPINNER_DELAY_MILLIS {
+                    true => format!("{} {}…", loadtype, host),
+                    false => format!(
+                        "{} {} {}… {:.1}s",
+                        SPINNER[(elapsed.as_millis() / SPINNER_MILLIS) as usize % SPINNER.len()],
+                        loadtype,
+                        host,
+                        elapsed.as_secs_f64()
+                    ),
+                };
+                let load_area = centered_rect(message.chars().count() as u16, 1, area);
                 Paragraph::new(message).render(load_area, buf);
                 vec![]
             }
-            _ => ctx
-                .results
-                .table
+            _ => res_table
                 .rows
-                .clone()
                 .into_iter()
-                .map(Into::into)
+                .enumerate()
+                .map(|(i, mut row)| {
+                    let i = i + offset;
+                    if let Some(item) = ctx.results.response.items.get(i) {
+                        star_title(&mut row, item, ctx);
+                    }
+                    let row: Row = row.into();
+                    // Highlight what's new since the last refresh
+                    match ctx.results.response.items.get(i) {
+                        Some(item) if ctx.results.dead.contains(&item.id) => row.dim(),
+                        Some(item) if ctx.results.new.contains(&item.id) => row.bold(),
+                        _ => row,
+                    }
+                })
                 .collect(),
         };
 
@@ -85,47 +301,66 @@ impl super::Widget for ResultsWidget {
             horizontal: 0,
         });
 
-        let num_items = items.len();
         let first_item = (ctx.page - 1) * 75;
-        let focused = matches!(ctx.mode, Mode::Normal | Mode::KeyCombo(_));
-        let table = Table::new(items, ctx.results.table.binding.to_owned())
+        let focused = ctx.has_focus(Pane::Results);
+        let minimal = ctx.config.layout.minimal;
+        let mut title = title!(
+            "{} {}-{} ({} {}): {} {}/{}",
+            match (ctx.history.label(), ctx.browsing) {
+                (Some(label), _) => ctx.tr(label),
+                (None, true) => ctx.tr("Front Page"),
+                (None, false) => ctx.tr("Results"),
+            },
+            first_item + 1,
+            num_items + first_item,
+            ctx.results
+                .response
+                .total_results
+                .map(|n| n.to_string())
+                .unwrap_or("?".to_owned()),
+            ctx.tr("total"),
+            ctx.tr("Page"),
+            ctx.page,
+            ctx.results.response.last_page,
+        );
+        if !ctx.results.new.is_empty() {
+            title.push_str(&format!(" +{} {}", ctx.results.new.len(), ctx.tr("new")));
+        }
+        let table = Table::new(items, res_table.binding)
             .header(header)
-            .block(border_block(&ctx.theme, focused).title(title!(
-                "Results {}-{} ({} total): Page {}/{}",
-                first_item + 1,
-                num_items + first_item,
-                ctx.results.response.total_results,
-                ctx.page,
-                ctx.results.response.last_page,
-            )))
+            .block(pane_block(ctx, focused, WidgetKind::Results).title(title))
             .highlight_style(Style::default().bg(ctx.theme.hl_bg));
 
-        super::scroll_padding(
-            self.table.selected().unwrap_or(0),
-            area.height as usize,
-            3,
-            num_items,
-            ctx.config.scroll_padding,
-            self.table.state.offset_mut(),
-        );
-
-        StatefulWidget::render(table, area, buf, &mut self.table.state);
-        StatefulWidget::render(
-            sb,
-            sb_area,
-            buf,
-            &mut self.table.scrollbar_state.content_length(num_items),
-        );
+        let mut state = TableState::default()
+            .with_selected(self.table.selected().map(|s| s.saturating_sub(offset)));
+        StatefulWidget::render(table, area, buf, &mut state);
+        if !minimal {
+            StatefulWidget::render(
+                sb,
+                sb_area,
+                buf,
+                &mut self.table.scrollbar_state.content_length(num_items),
+            );
+        }
 
-        if ctx.load_type.is_none() && num_items == 0 {
-            let center = centered_rect(10, 1, area);
-            Paragraph::new("No results").render(center, buf);
+        if let Some(reason) = ctx
+            .results
+            .empty_reason()
+            .filter(|_| ctx.load_type.is_none() && !under_popup)
+        {
+            let lines = empty_message(ctx, reason);
+            let width = lines.iter().map(|l| l.width()).max().unwrap_or(0) as u16;
+            let center = centered_rect(width, lines.len() as u16, area);
+            Paragraph::new(lines)
+                .alignment(Alignment::Center)
+                .render(center, buf);
         }
 
-        if area.height >= 3 {
+        // Batched items are marked on the left border
+        if area.height >= 3 && !minimal {
             if let Some(visible_items) = ctx.results.response.items.get(self.table.state.offset()..)
             {
-                let selected_ids: Vec<String> =
+                let selected_ids: Vec<ItemId> =
                     ctx.batch.clone().into_iter().map(|i| i.id).collect();
                 let vert_left = ctx.theme.border.to_border_set().vertical_left;
                 let lines = visible_items
@@ -149,19 +384,46 @@ impl super::Widget for ResultsWidget {
         let dl_src = title!(
             "dl: {}, src: {}",
             ctx.client.to_string(),
-            ctx.src.to_string()
+            ctx.src.name(&ctx.config.sources)
         );
         if let Some((tr, area)) = Corner::TopRight.try_title(dl_src, area, true) {
             f.render_widget(tr, area);
         }
 
-        if !ctx.last_key.is_empty() {
+        if !ctx.last_key.is_empty() && !minimal {
             let key_str = title!(ctx.last_key);
             if let Some((br, area)) = Corner::BottomRight.try_title(key_str, area, true) {
                 f.render_widget(br, area);
             }
         }
 
+        // Sticky filters stay on between searches, so show that some results
+        // may be hidden
+        let sticky: Vec<&str> = ctx
+            .quick_filters
+            .iter()
+            .filter(|f| f.sticky())
+            .map(|f| ctx.tr(f.label()))
+            .collect();
+        if !sticky.is_empty() && ctx.find.is_none() && ctx.recording.is_none() {
+            let filters = title!(sticky.join(", "));
+            if let Some((bl, area)) = Corner::BottomLeft.try_title(filters, area, true) {
+                f.render_widget(bl, area);
+            }
+        }
+        if let Some(pattern) = ctx.find.as_ref().filter(|_| ctx.recording.is_none()) {
+            let find = title!("find: {}", pattern);
+            if let Some((bl, area)) = Corner::BottomLeft.try_title(find, area, true) {
+                f.render_widget(bl, area);
+            }
+        }
+        if let Some(register) = ctx.recording {
+            let rec = title!("recording @{}", register);
+            if let Some((bl, area)) = Corner::BottomLeft.try_title(rec, area, true) {
+                f.render_widget(bl, area);
+            }
+        }
+
         // if let Some((bl, area)) =
         //     Corner::BottomLeft.try_title(format!("{} draws", self.draw_count), area, false)
         // {
@@ -170,6 +432,9 @@ impl super::Widget for ResultsWidget {
         // }
     }
 
+    // Keys which can't do anything right now are still used up, rather than
+    // falling through to later arms
+    #[allow(clippy::collapsible_match)]
     fn handle_event(&mut self, ctx: &mut Context, e: &Event) {
         if let Event::Key(KeyEvent {
             code,
@@ -180,21 +445,37 @@ impl super::Widget for ResultsWidget {
         {
             use KeyCode::*;
             match (code, modifiers) {
-                (Char('c'), &KeyModifiers::NONE) => {
-                    ctx.mode = Mode::Category;
-                }
-                (Char('s'), &KeyModifiers::NONE) => {
-                    ctx.mode = Mode::Sort(SortDir::Desc);
-                }
-                (Char('S'), &KeyModifiers::SHIFT) => {
-                    ctx.mode = Mode::Sort(SortDir::Asc);
+                // Chords from the config take the place of their first key
+                (Char(c), &KeyModifiers::NONE | &KeyModifiers::SHIFT) if ctx.chords.starts(*c) => {
+                    ctx.mode = Mode::KeyCombo(c.to_string());
                 }
+                (Char('c'), &KeyModifiers::NONE) => match ctx.src_info.capabilities.category {
+                    true => ctx.mode = Mode::Category,
+                    false => unsupported(ctx, "categories"),
+                },
+                (Char('s'), &KeyModifiers::NONE) => match ctx.src_info.capabilities.sort {
+                    true => ctx.mode = Mode::Sort(SortDir::Desc),
+                    false => unsupported(ctx, "sorting"),
+                },
+                (Char('S'), &KeyModifiers::SHIFT) => match ctx.src_info.capabilities.sort {
+                    true => ctx.mode = Mode::Sort(SortDir::Asc),
+                    false => unsupported(ctx, "sorting"),
+                },
                 (Char('f'), &KeyModifiers::NONE) => {
                     ctx.mode = Mode::Filter;
                 }
+                (Char('C'), &KeyModifiers::SHIFT) => {
+                    match ctx.src.columns(&ctx.config.sources).is_some() {
+                        true => ctx.mode = Mode::Columns,
+                        false => unsupported(ctx, "changing columns"),
+                    }
+                }
                 (Char('t'), &KeyModifiers::NONE) => {
                     ctx.mode = Mode::Theme;
                 }
+                (Char('T'), &KeyModifiers::SHIFT) => {
+                    ctx.toggle_browse();
+                }
                 (Char('/') | Char('i'), &KeyModifiers::NONE) => {
                     ctx.mode = Mode::Search;
                 }
@@ -203,14 +484,21 @@ impl super::Widget for ResultsWidget {
                 }
                 (Char('p') | Char('h') | Left, &KeyModifiers::NONE) => {
                     if ctx.page > 1 {
-                        ctx.page -= 1;
-                        ctx.mode = Mode::Loading(LoadType::Searching);
+                        self.goto_page(ctx, ctx.page - 1);
                     }
                 }
+                (Char('n'), &KeyModifiers::NONE) if ctx.find.is_some() => {
+                    self.find_next(ctx, true);
+                }
+                (Char('N'), &KeyModifiers::SHIFT) if ctx.find.is_some() => {
+                    self.find_next(ctx, false);
+                }
+                (Char('f'), &KeyModifiers::CONTROL) => {
+                    ctx.mode = Mode::Find;
+                }
                 (Char('n') | Char('l') | Right, &KeyModifiers::NONE) => {
                     if ctx.page < ctx.results.response.last_page {
-                        ctx.page += 1;
-                        ctx.mode = Mode::Loading(LoadType::Searching);
+                        self.goto_page(ctx, ctx.page + 1);
                     }
                 }
                 (Char('r'), &KeyModifiers::NONE) => {
@@ -220,30 +508,10 @@ impl super::Widget for ResultsWidget {
                     ctx.quit();
                 }
                 (Char('j') | KeyCode::Down, &KeyModifiers::NONE) => {
-                    let prev = self.table.selected().unwrap_or(0);
-                    let selected = self.table.next(ctx.results.response.items.len(), 1);
-                    if self.control_space && prev != selected {
-                        self.try_select_toggle(
-                            ctx,
-                            match selected <= self.visual_anchor {
-                                true => prev,
-                                false => selected,
-                            },
-                        );
-                    }
+                    self.move_by(ctx, 1);
                 }
                 (Char('k') | KeyCode::Up, &KeyModifiers::NONE) => {
-                    let prev = self.table.selected().unwrap_or(0);
-                    let selected = self.table.next(ctx.results.response.items.len(), -1);
-                    if self.control_space && prev != selected {
-                        self.try_select_toggle(
-                            ctx,
-                            match selected >= self.visual_anchor {
-                                true => prev,
-                                false => selected,
-                            },
-                        );
-                    }
+                    self.move_by(ctx, -1);
                 }
                 (Char('J'), &KeyModifiers::SHIFT) => {
                     self.table.next(ctx.results.response.items.len(), 4);
@@ -260,20 +528,21 @@ impl super::Widget for ResultsWidget {
                 }
                 (Char('H') | Char('P'), &KeyModifiers::SHIFT) => {
                     if ctx.page != 1 {
-                        ctx.page = 1;
-                        ctx.mode = Mode::Loading(LoadType::Searching);
+                        self.goto_page(ctx, 1);
                     }
                 }
                 (Char('L') | Char('N'), &KeyModifiers::SHIFT) => {
                     if ctx.page != ctx.results.response.last_page
                         && ctx.results.response.last_page > 0
                     {
-                        ctx.page = ctx.results.response.last_page;
-                        ctx.mode = Mode::Loading(LoadType::Searching);
+                        self.goto_page(ctx, ctx.results.response.last_page);
                     }
                 }
                 (Enter, &KeyModifiers::NONE) => {
-                    ctx.mode = Mode::Loading(LoadType::Downloading);
+                    ctx.mode = match ctx.config.prompt_download_options && ctx.client.is_api() {
+                        true => Mode::Confirm(LoadType::Downloading),
+                        false => Mode::Loading(LoadType::Downloading),
+                    };
                 }
                 (Char('s'), &KeyModifiers::CONTROL) => {
                     ctx.mode = Mode::Sources;
@@ -281,8 +550,18 @@ impl super::Widget for ResultsWidget {
                 (Char('d'), &KeyModifiers::NONE) => {
                     ctx.mode = Mode::Clients;
                 }
-                (Char('u'), &KeyModifiers::NONE) => {
-                    ctx.mode = Mode::User;
+                (Char('m'), &KeyModifiers::NONE) => {
+                    ctx.toggle_minimal();
+                }
+                (Char('D'), &KeyModifiers::SHIFT) => {
+                    ctx.deep_search();
+                }
+                (Char('u'), &KeyModifiers::NONE) => match ctx.src_info.capabilities.user {
+                    true => ctx.mode = Mode::User,
+                    false => unsupported(ctx, "searching by user"),
+                },
+                (Char(':'), _) => {
+                    ctx.mode = Mode::Stats;
                 }
                 (Char('o'), &KeyModifiers::NONE) => {
                     let link = ctx
@@ -299,7 +578,15 @@ impl super::Widget for ResultsWidget {
                         ctx.notify(format!("Opened {}", link));
                     }
                 }
-                (Char('y'), &KeyModifiers::NONE) => ctx.mode = Mode::KeyCombo("y".to_string()),
+                (Char('Q'), _) => match ctx.recording {
+                    Some(_) => ctx.stop_recording(),
+                    None => ctx.mode = Mode::KeyCombo("Q".to_string()),
+                },
+                (Char('@'), _) => ctx.mode = Mode::KeyCombo("@".to_string()),
+                (Char('U'), &KeyModifiers::SHIFT) => ctx.undo_batch(),
+                (Char(c @ '1'..='9'), &KeyModifiers::NONE) => {
+                    ctx.mode = Mode::KeyCombo(c.to_string());
+                }
                 (Char(' '), &KeyModifiers::CONTROL) => {
                     self.control_space = !self.control_space;
                     if self.control_space {
@@ -313,23 +600,39 @@ impl super::Widget for ResultsWidget {
                 }
                 (Char(' '), &KeyModifiers::NONE) => {
                     if let Some(sel) = self.table.state.selected() {
-                        if let Some(item) = &mut ctx.results.response.items.get_mut(sel) {
-                            if let Some(p) = ctx.batch.iter().position(|s| s.id == item.id) {
-                                ctx.batch.remove(p);
-                            } else {
-                                ctx.batch.push(item.to_owned());
-                            }
-                        }
+                        self.try_select_toggle(ctx, sel);
                     }
                 }
-                (Tab | BackTab, _) => {
-                    ctx.mode = Mode::Batch;
+                (Char('w'), &KeyModifiers::NONE) => {
+                    if let Some(item) = self
+                        .table
+                        .selected()
+                        .and_then(|s| ctx.results.response.items.get(s))
+                    {
+                        ctx.toggle_watch_later(&item.clone());
+                    }
+                }
+                (Char('W'), &KeyModifiers::SHIFT) => {
+                    ctx.mode = Mode::WatchLater;
+                }
+                (Char('*'), _) => {
+                    if let Some(item) = self
+                        .table
+                        .selected()
+                        .and_then(|s| ctx.results.response.items.get(s))
+                    {
+                        ctx.toggle_star(&item.clone());
+                    }
                 }
                 (Esc, &KeyModifiers::NONE) => {
                     if self.control_space {
                         ctx.notify("Exited VISUAL mode");
                         self.visual_anchor = 0;
                         self.control_space = false;
+                    } else if ctx.load_type.is_some() {
+                        ctx.cancel_load();
+                    } else if ctx.find.is_some() {
+                        ctx.find = None;
                     } else {
                         ctx.dismiss_notifications();
                     }
@@ -342,12 +645,13 @@ impl super::Widget for ResultsWidget {
     fn get_help() -> Option<Vec<(&'static str, &'static str)>> {
         Some(vec![
             ("Enter", "Confirm"),
-            ("Esc", "Dismiss notification"),
+            ("Esc", "Cancel search/Dismiss notification"),
             ("q", "Exit App"),
             ("g/G", "Goto Top/Bottom"),
             ("k, ↑", "Up"),
             ("j, ↓", "Down"),
             ("K, J", "Up/Down 4 items"),
+            ("{count}j, {count}n", "Repeat a motion, e.g. 5j"),
             ("n, l, →", "Next Page"),
             ("p, h, ←", "Prev Page"),
             ("N, L", "Last Page"),
@@ -355,19 +659,38 @@ impl super::Widget for ResultsWidget {
             ("r", "Reload"),
             ("o", "Open in browser"),
             ("yt, ym, yp, yi", "Copy torrent/magnet/post/imdb id"),
+            ("yb, yB", "Copy batch magnets/torrents"),
             ("Space", "Toggle item for batch download"),
             ("Ctrl-Space", "Multi-line select torrents"),
-            ("Tab/Shift-Tab", "Switch to Batches"),
+            ("*", "Star/unstar item"),
+            ("w", "Add/remove item from watch later"),
+            ("W", "Watch later"),
+            ("Rs, Rg", "Related: same show/same group"),
+            ("Ctrl-o, Backspace", "Back to the search before"),
+            ("Ctrl-i, Ctrl-n", "Forward to the next search"),
+            ("ba, bA", "Add page/all pages to batch"),
+            ("bi, bc", "Invert/Clear batch selection"),
+            ("U", "Undo batch change"),
+            ("Tab/Shift-Tab", "Focus next/previous pane"),
             ("/, i", "Search"),
+            ("Ctrl-f", "Find on page"),
+            ("n, N", "Next/Prev match, once found"),
             ("c", "Categories"),
             ("f", "Filters"),
+            ("C", "Columns"),
             ("s", "Sort"),
             ("S", "Sort reversed"),
             ("t", "Themes"),
+            ("T", "Toggle front page/search results"),
             ("u", "Filter by User"),
             ("d", "Select download client"),
+            ("D", "Deep search"),
+            ("m", "Toggle minimal layout"),
+            (":", "Stats"),
             ("Ctrl-p", "Goto page"),
             ("Ctrl-s", "Select source"),
+            ("Q{a-z}, Q", "Record macro/Stop recording"),
+            ("@{a-z}, @@", "Replay macro/Replay last macro"),
         ])
     }
 }
@@ -2,29 +2,143 @@ use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
 use ratatui::{
     layout::{Margin, Rect},
     style::Stylize,
+    text::Line,
     widgets::{Clear, Widget},
     Frame,
 };
 
+use unicode_width::UnicodeWidthStr as _;
+
 use crate::{
     app::{Context, LoadType, Mode},
+    theme::WidgetKind,
     title,
+    util::template,
 };
 
 use super::{
-    border_block,
     input::{self, InputWidget},
-    Corner,
+    widget_block, Corner, Widget as _,
 };
 
 pub struct SearchWidget {
     pub input: InputWidget,
+    /// Words the word being completed can be replaced with, while Tab is
+    /// pressed again and again
+    completion: Option<Completion>,
+}
+
+struct Completion {
+    /// Where the word being completed starts and ends in the input
+    start: usize,
+    end: usize,
+    candidates: Vec<String>,
+    idx: usize,
 }
 
 impl Default for SearchWidget {
     fn default() -> Self {
         SearchWidget {
             input: InputWidget::new(300, Some(|_| true)),
+            completion: None,
+        }
+    }
+}
+
+impl SearchWidget {
+    /// The start of the word before the cursor, and the word up to it
+    fn word_at_cursor(&self) -> (usize, &str) {
+        let cursor = self.input.cursor.min(self.input.input.len());
+        let before = self.input.input.get(..cursor).unwrap_or_default();
+        let start = before.rfind(' ').map(|i| i + 1).unwrap_or(0);
+        (start, &before[start..])
+    }
+
+    /// Replace the word before the cursor with the next (or previous) word
+    /// it could be completed to
+    fn complete(&mut self, ctx: &Context, back: bool) {
+        match self.completion.as_mut() {
+            Some(c) => {
+                let len = c.candidates.len();
+                c.idx = match back {
+                    true => (c.idx + len - 1) % len,
+                    false => (c.idx + 1) % len,
+                };
+            }
+            None => {
+                let (start, prefix) = self.word_at_cursor();
+                let candidates = ctx.completions.complete(prefix);
+                if candidates.is_empty() {
+                    return;
+                }
+                self.completion = Some(Completion {
+                    start,
+                    end: start + prefix.len(),
+                    idx: match back {
+                        true => candidates.len() - 1,
+                        false => 0,
+                    },
+                    candidates,
+                });
+            }
+        }
+        if let Some(c) = self.completion.as_mut() {
+            let word = &c.candidates[c.idx];
+            if self.input.input.len() - (c.end - c.start) + word.len() > self.input.max_len {
+                return;
+            }
+            self.input.input.replace_range(c.start..c.end, word);
+            c.end = c.start + word.len();
+            self.input.cursor = c.end;
+        }
+    }
+
+    /// The rest of the word Tab would complete to, shown after the cursor
+    fn hint(&self, ctx: &Context) -> Option<String> {
+        if self.completion.is_some() || self.input.cursor < self.input.input.len() {
+            return None;
+        }
+        let (_, prefix) = self.word_at_cursor();
+        let word = ctx.completions.complete(prefix).into_iter().next()?;
+        word.get(prefix.len()..).map(ToOwned::to_owned)
+    }
+
+    fn draw_hint(&self, f: &mut Frame, ctx: &Context, area: Rect) {
+        if ctx.mode != Mode::Search {
+            return;
+        }
+        let x = self.input.input.width() as u16;
+        if let Some(hint) = self.hint(ctx).filter(|_| x < area.width) {
+            let hint_area = Rect {
+                x: area.x + x,
+                width: area.width - x,
+                ..area
+            };
+            Line::from(hint.fg(ctx.theme.border_color)).render(hint_area, f.buffer_mut());
+        }
+    }
+
+    /// A single line without a border, for narrow terminals
+    fn draw_compact(&mut self, f: &mut Frame, ctx: &Context, area: Rect) {
+        let label = format!("{}: ", ctx.tr("Search"));
+        let color = match ctx.mode == Mode::Search {
+            true => ctx.theme.border_focused_color,
+            false => ctx.theme.border_color,
+        };
+        let buf = f.buffer_mut();
+        Clear.render(area, buf);
+        Line::from(label.as_str().fg(color).bold()).render(area, buf);
+
+        let label_width = label.width() as u16;
+        let input_area = Rect {
+            x: area.x + label_width.min(area.width),
+            width: area.width.saturating_sub(label_width),
+            ..area
+        };
+        self.input.draw(f, ctx, input_area);
+        self.draw_hint(f, ctx, input_area);
+        if ctx.mode == Mode::Search {
+            self.input.show_cursor(f, input_area);
         }
     }
 }
@@ -32,7 +146,11 @@ impl Default for SearchWidget {
 impl super::Widget for SearchWidget {
     fn draw(&mut self, f: &mut Frame, ctx: &Context, area: Rect) {
         let buf = f.buffer_mut();
-        let block = border_block(&ctx.theme, ctx.mode == Mode::Search).title(title!("Search"));
+        if area.height < 3 {
+            return self.draw_compact(f, ctx, area);
+        }
+        let block = widget_block(&ctx.theme, ctx.mode == Mode::Search, WidgetKind::Search)
+            .title(title!(ctx.tr("Search")));
         Clear.render(area, buf);
         block.render(area, buf);
         let input_area = area.inner(&Margin {
@@ -50,8 +168,18 @@ impl super::Widget for SearchWidget {
         if let Some((tr, area)) = Corner::TopRight.try_title(help_title, area, true) {
             tr.render(area, buf);
         }
+        // The uploader searched within, removed with Backspace at the start
+        if let Some(user) = ctx.user.as_ref() {
+            let chip = format!(" {} ✕ ", user)
+                .fg(ctx.theme.solid_fg)
+                .bg(ctx.theme.solid_bg);
+            if let Some((bl, area)) = Corner::BottomLeft.try_title(chip, area, true) {
+                bl.render(area, buf);
+            }
+        }
 
         self.input.draw(f, ctx, input_area);
+        self.draw_hint(f, ctx, input_area);
         if ctx.mode == Mode::Search {
             self.input.show_cursor(f, input_area);
         }
@@ -66,22 +194,56 @@ impl super::Widget for SearchWidget {
         }) = evt
         {
             use KeyCode::*;
+            if let Tab | BackTab = code {
+                let prev = self.input.input.clone();
+                self.complete(ctx, *code == BackTab);
+                if ctx.config.live_search && prev != self.input.input {
+                    ctx.live_search();
+                }
+                return;
+            }
+            self.completion = None;
             match (code, modifiers) {
                 (Esc, &KeyModifiers::NONE) => {
                     ctx.mode = Mode::Normal;
                 }
+                (Backspace, &KeyModifiers::NONE)
+                    if self.input.cursor == 0 && ctx.user.is_some() =>
+                {
+                    ctx.user = None;
+                    ctx.notify("Searching all users");
+                    if ctx.config.live_search {
+                        ctx.live_search();
+                    }
+                    return;
+                }
                 (Enter, &KeyModifiers::NONE) => {
+                    // Templates are searched once their variables are filled in
+                    if !template::variables(&self.input.input).is_empty() {
+                        ctx.mode = Mode::Template;
+                        return;
+                    }
+                    ctx.browsing = false;
                     ctx.mode = Mode::Loading(LoadType::Searching);
                     ctx.page = 1; // Go back to first page
                 }
                 _ => {}
             };
         }
+        let prev = self.input.input.clone();
         self.input.handle_event(ctx, evt);
+        if ctx.config.live_search && ctx.mode == Mode::Search && prev != self.input.input {
+            ctx.live_search();
+        }
     }
 
     fn get_help() -> Option<Vec<(&'static str, &'static str)>> {
-        let mut search_help = vec![("Enter", "Confirm"), ("Esc", "Stop")];
+        let mut search_help = vec![
+            ("Enter", "Confirm"),
+            ("Esc", "Stop"),
+            ("Tab/Shift-Tab", "Complete word"),
+            ("Backspace", "Remove user, at the start"),
+        ];
         if let Some(input_help) = input::InputWidget::get_help() {
             search_help.extend(input_help);
         }
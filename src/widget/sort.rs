@@ -15,12 +15,30 @@ use crate::{
 
 use super::{border_block, VirtualStatefulTable, Widget};
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub struct SelectedSort {
     pub sort: usize,
     pub dir: SortDir,
 }
 
+impl SelectedSort {
+    /// The sort after picking `sort` again, going from ascending to
+    /// descending and then back to the source's `default`
+    pub fn cycle(self, sort: usize, default: SelectedSort) -> SelectedSort {
+        match (self.sort == sort, self.dir) {
+            (true, SortDir::Asc) => SelectedSort {
+                sort,
+                dir: SortDir::Desc,
+            },
+            (true, SortDir::Desc) if self != default => default,
+            _ => SelectedSort {
+                sort,
+                dir: SortDir::Asc,
+            },
+        }
+    }
+}
+
 impl Default for SelectedSort {
     fn default() -> Self {
         Self {
@@ -30,7 +48,7 @@ impl Default for SelectedSort {
     }
 }
 
-#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize, Debug)]
 pub enum SortDir {
     #[serde(rename = "Desc")]
     Desc,
@@ -86,12 +104,12 @@ impl Widget for SortPopup {
             }])
         });
         let table = Table::new(items, [Constraint::Percentage(100)])
-            .block(border_block(&ctx.theme, true).title(title!(match ctx.mode
-                == Mode::Sort(SortDir::Asc)
-            {
-                true => "Sort Ascending",
-                false => "Sort Descending",
-            })))
+            .block(border_block(&ctx.theme, true).title(title!(ctx.tr(
+                match ctx.mode == Mode::Sort(SortDir::Asc) {
+                    true => "Sort Ascending",
+                    false => "Sort Descending",
+                }
+            ))))
             .highlight_style(style!(bg:ctx.theme.hl_bg));
         super::clear(center, buf, ctx.theme.bg);
         table.render(center, buf, &mut self.table.state);
@@ -120,6 +138,22 @@ impl Widget for SortPopup {
                 KeyCode::Char('g') => {
                     self.table.select(0);
                 }
+                KeyCode::Char(' ') => {
+                    if let Some(i) = self.table.state.selected() {
+                        let default = ctx.src.default_sort(&ctx.config.sources);
+                        self.selected = self.selected.cycle(i, default);
+                        self.table.select(self.selected.sort);
+                        ctx.mode = Mode::Loading(LoadType::Sorting);
+                        if let Some(s) = ctx.src_info.sorts.get(self.selected.sort) {
+                            ctx.notify(match self.selected == default {
+                                true => {
+                                    format!("Sort by \"{}\" {} (default)", s, self.selected.dir)
+                                }
+                                false => format!("Sort by \"{}\" {}", s, self.selected.dir),
+                            });
+                        }
+                    }
+                }
                 KeyCode::Enter => {
                     if let Some(i) = self.table.state.selected() {
                         self.selected.sort = i;
@@ -141,6 +175,7 @@ impl Widget for SortPopup {
     fn get_help() -> Option<Vec<(&'static str, &'static str)>> {
         Some(vec![
             ("Enter", "Confirm"),
+            ("Space", "Cycle ascending, descending, default"),
             ("Esc, s, q", "Close"),
             ("j, ↓", "Down"),
             ("k, ↑", "Up"),
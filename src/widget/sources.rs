@@ -1,16 +1,15 @@
+use crate::{
+    app::{Context, LoadType, Mode},
+    source::{SourceConfig, Sources},
+    style, title,
+};
 use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
 use ratatui::{
     layout::{Constraint, Rect},
-    widgets::{Row, StatefulWidget as _, Table},
+    widgets::{Cell, Row, StatefulWidget as _, Table},
     Frame,
 };
-use strum::VariantArray;
-
-use crate::{
-    app::{Context, LoadType, Mode},
-    source::Sources,
-    style, title,
-};
+use unicode_width::UnicodeWidthStr as _;
 
 use super::{border_block, StatefulTable, Widget};
 
@@ -21,24 +20,85 @@ pub struct SourcesPopup {
 impl Default for SourcesPopup {
     fn default() -> Self {
         SourcesPopup {
-            table: StatefulTable::new(Sources::VARIANTS),
+            table: StatefulTable::new(&Sources::all(&SourceConfig::default())),
         }
     }
 }
 
 impl Widget for SourcesPopup {
     fn draw(&mut self, f: &mut Frame, ctx: &Context, area: Rect) {
-        let buf = f.buffer_mut();
-        let center = super::centered_rect(30, self.table.items.len() as u16 + 2, area);
-        let items = self.table.items.iter().map(|item| {
-            Row::new(vec![match item == &ctx.src {
-                true => format!("  {}", item),
-                false => format!("   {}", item),
-            }])
+        // Custom sources can change whenever the config is reloaded
+        self.table.items = Sources::all(&ctx.config.sources);
+        let config = &ctx.config.sources;
+        // Only shown once a source has a `[source.auth]` entry
+        let show_auth = !config.auth.is_empty();
+        let rows: Vec<Vec<String>> = self
+            .table
+            .items
+            .iter()
+            .map(|src| {
+                let default = src.default_category(config);
+                let mut row = vec![
+                    match src == &ctx.src {
+                        true => format!("  {}", src.name(config)),
+                        false => format!("   {}", src.name(config)),
+                    },
+                    src.host(config),
+                    src.transport(config).to_string(),
+                    src.info(config).entry_from_id(default).name,
+                    match ctx.health(*src) {
+                        Some(true) => ctx.tr("ok").to_owned(),
+                        Some(false) => ctx.tr("failing").to_owned(),
+                        None => "-".to_owned(),
+                    },
+                ];
+                if show_auth {
+                    row.push(ctx.tr(src.auth_state(config).unwrap_or("-")).to_owned());
+                }
+                row
+            })
+            .collect();
+        let mut header: Vec<String> = ["", "Host", "Mode", "Category", "Status"]
+            .map(|h| ctx.tr(h).to_owned())
+            .into();
+        if show_auth {
+            header.push(ctx.tr("Auth").to_owned());
+        }
+        let widths: Vec<u16> = (0..header.len())
+            .map(|i| {
+                rows.iter()
+                    .map(|r| r[i].width())
+                    .chain([header[i].width()])
+                    .max()
+                    .unwrap_or(0) as u16
+            })
+            .collect();
+        // Borders and the spacing between columns
+        let width = widths.iter().sum::<u16>() + widths.len() as u16 + 1;
+        let center = super::centered_rect(width, rows.len() as u16 + 3, area);
+
+        let items = rows.into_iter().enumerate().map(|(i, r)| {
+            let status = match ctx.health(self.table.items[i]) {
+                Some(true) => style!(fg:ctx.theme.success),
+                Some(false) => style!(fg:ctx.theme.error),
+                None => style!(fg:ctx.theme.border_color),
+            };
+            let auth = match self.table.items[i].auth_state(&ctx.config.sources) {
+                Some("signed in") => style!(fg:ctx.theme.success),
+                Some("signed out") => style!(fg:ctx.theme.error),
+                _ => style!(fg:ctx.theme.fg),
+            };
+            Row::new(r.into_iter().enumerate().map(|(col, c)| match col {
+                4 => Cell::from(c).style(status),
+                5 => Cell::from(c).style(auth),
+                _ => Cell::from(c),
+            }))
         });
+        let buf = f.buffer_mut();
         super::clear(center, buf, ctx.theme.bg);
-        let table = Table::new(items, [Constraint::Percentage(100)])
-            .block(border_block(&ctx.theme, true).title(title!("Source")))
+        let table = Table::new(items, widths.into_iter().map(Constraint::Length))
+            .header(Row::new(header).style(style!(bold)))
+            .block(border_block(&ctx.theme, true).title(title!(ctx.tr("Source"))))
             .highlight_style(style!(bg:ctx.theme.hl_bg));
         table.render(center, buf, &mut self.table.state);
     }
@@ -66,6 +126,12 @@ impl Widget for SourcesPopup {
                 KeyCode::Char('g') => {
                     self.table.select(0);
                 }
+                KeyCode::Char('e') => {
+                    if let Some(src) = self.table.selected() {
+                        ctx.edit_source(*src);
+                        ctx.mode = Mode::Normal;
+                    }
+                }
                 KeyCode::Enter => {
                     if let Some(src) = self.table.selected() {
                         if !src.eq(&ctx.src) {
@@ -73,8 +139,19 @@ impl Widget for SourcesPopup {
                             ctx.config.source = *src;
                             ctx.mode = Mode::Loading(LoadType::Sourcing);
                             src.load_config(&mut ctx.config.sources);
+                            let config = &ctx.config.sources;
+                            if src.info(config).capabilities.auth && src.auth(config).is_none() {
+                                ctx.show_error(format!(
+                                    "{} needs an account, but has no [source.auth.\"{}\"]",
+                                    src.name(config),
+                                    src.auth_name(config)
+                                ));
+                            }
                             match ctx.save_config() {
-                                Ok(_) => ctx.notify(format!("Updated source to \"{}\"", src)),
+                                Ok(_) => ctx.notify(format!(
+                                    "Updated source to \"{}\"",
+                                    src.name(&ctx.config.sources)
+                                )),
                                 Err(e) => ctx.show_error(format!(
                                     "Failed to update default source in config file:\n{}",
                                     e
@@ -99,6 +176,7 @@ impl Widget for SourcesPopup {
             ("k, ↑", "Up"),
             ("g", "Top"),
             ("G", "Bottom"),
+            ("e", "Edit source settings"),
         ])
     }
 }
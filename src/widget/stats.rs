@@ -0,0 +1,95 @@
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::{
+    layout::{Constraint, Rect},
+    style::Stylize as _,
+    widgets::{Cell, Row, Table, Widget as _},
+    Frame,
+};
+
+use crate::{
+    app::{Context, Mode},
+    style, title,
+    util::{conv::to_size, stats},
+};
+
+use super::{border_block, Widget};
+
+static WIDTH: u16 = 50;
+
+#[derive(Default)]
+pub struct StatsPopup;
+
+impl Widget for StatsPopup {
+    fn draw(&mut self, f: &mut Frame, ctx: &Context, area: Rect) {
+        let stats = stats::snapshot();
+        let buf = f.buffer_mut();
+        let header = Row::new([
+            ctx.tr("Source"),
+            ctx.tr("Searches"),
+            ctx.tr("Failed"),
+            ctx.tr("Latency"),
+        ])
+        .bold()
+        .fg(ctx.theme.border_color);
+        let sources = stats.sources.iter().map(|(name, src)| {
+            Row::new([
+                Cell::from(name.to_owned()),
+                Cell::from(src.searches.to_string()),
+                match src.failures {
+                    0 => Cell::from("0"),
+                    n => Cell::from(n.to_string()).style(style!(fg:ctx.theme.error)),
+                },
+                Cell::from(format!("{} ms", src.average_latency_ms())),
+            ])
+        });
+        let totals = [
+            ("Searches", stats.searches.to_string()),
+            ("Requests", stats.requests.to_string()),
+            ("Cache hits", stats.cache_hits.to_string()),
+            ("Downloaded", to_size(stats.bytes_downloaded)),
+        ]
+        .map(|(label, value)| Row::new([Cell::from(ctx.tr(label)), Cell::from(value)]));
+        let rows: Vec<Row> = totals
+            .into_iter()
+            .chain([Row::default(), header])
+            .chain(sources)
+            .collect();
+
+        let center = super::centered_rect(WIDTH, rows.len() as u16 + 2, area);
+        super::clear(center, buf, ctx.theme.bg);
+        Table::new(
+            rows,
+            [
+                Constraint::Length(16),
+                Constraint::Length(10),
+                Constraint::Length(8),
+                Constraint::Min(1),
+            ],
+        )
+        .block(border_block(&ctx.theme, true).title(title!(ctx.tr("Stats"))))
+        .render(center, buf);
+    }
+
+    fn handle_event(&mut self, ctx: &mut Context, e: &Event) {
+        if let Event::Key(KeyEvent {
+            code,
+            kind: KeyEventKind::Press,
+            ..
+        }) = e
+        {
+            match code {
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char(':') => {
+                    ctx.mode = Mode::Normal;
+                }
+                KeyCode::Char('t') => {
+                    ctx.mode = Mode::Tasks;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn get_help() -> Option<Vec<(&'static str, &'static str)>> {
+        Some(vec![("t", "Tasks"), ("Esc, q, :", "Close")])
+    }
+}
@@ -0,0 +1,117 @@
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::{
+    layout::{Constraint, Rect},
+    style::Stylize as _,
+    widgets::{Cell, Row, StatefulWidget as _, Table},
+    Frame,
+};
+
+use crate::{
+    app::{Context, Mode},
+    style, title,
+};
+
+use super::{border_block, VirtualStatefulTable, Widget};
+
+static WIDTH: u16 = 70;
+static MAX_HEIGHT: u16 = 20;
+
+/// Lists every task running, to cancel any which are stuck
+pub struct TasksPopup {
+    pub table: VirtualStatefulTable,
+}
+
+impl Default for TasksPopup {
+    fn default() -> Self {
+        TasksPopup {
+            table: VirtualStatefulTable::new(),
+        }
+    }
+}
+
+impl Widget for TasksPopup {
+    fn draw(&mut self, f: &mut Frame, ctx: &Context, area: Rect) {
+        let rows: Vec<Row> = ctx
+            .tasks
+            .iter()
+            .map(|t| {
+                Row::new([
+                    Cell::from(format!(" {}", ctx.tr(&t.kind.to_string()))),
+                    Cell::from(t.name.clone()),
+                    Cell::from(format!("{:>5}s", t.started.elapsed().as_secs()))
+                        .style(style!(fg:ctx.theme.border_color)),
+                ])
+            })
+            .collect();
+        if let Some(last) = ctx.tasks.len().checked_sub(1) {
+            if self.table.selected().is_some_and(|s| s > last) {
+                self.table.select(last);
+            }
+        }
+        let height = (rows.len() as u16 + 3).min(MAX_HEIGHT);
+        let center = super::centered_rect(WIDTH, height, area);
+        super::clear(center, f.buffer_mut(), ctx.theme.bg);
+        let header = Row::new([ctx.tr(" Task"), ctx.tr("Doing"), ctx.tr("Running")])
+            .fg(ctx.theme.border_focused_color)
+            .underlined();
+        Table::new(
+            rows,
+            [
+                Constraint::Length(13),
+                Constraint::Min(1),
+                Constraint::Length(7),
+            ],
+        )
+        .header(header)
+        .block(border_block(&ctx.theme, true).title(title!(ctx.tr("Tasks"))))
+        .highlight_style(style!(bg:ctx.theme.hl_bg))
+        .render(center, f.buffer_mut(), &mut self.table.state);
+    }
+
+    fn handle_event(&mut self, ctx: &mut Context, e: &Event) {
+        if let Event::Key(KeyEvent {
+            code,
+            kind: KeyEventKind::Press,
+            ..
+        }) = e
+        {
+            let len = ctx.tasks.len();
+            match code {
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char(':') => {
+                    ctx.mode = Mode::Normal;
+                }
+                KeyCode::Char('j') | KeyCode::Down => {
+                    self.table.next_wrap(len, 1);
+                }
+                KeyCode::Char('k') | KeyCode::Up => {
+                    self.table.next_wrap(len, -1);
+                }
+                KeyCode::Char('G') => {
+                    self.table.select(len.saturating_sub(1));
+                }
+                KeyCode::Char('g') => {
+                    self.table.select(0);
+                }
+                KeyCode::Char('d') | KeyCode::Delete => {
+                    if let Some(task) = self.table.selected().and_then(|i| ctx.tasks.get(i)) {
+                        let (id, name) = (task.id, task.name.clone());
+                        ctx.cancel_task(id);
+                        ctx.notify(format!("Cancelled {}", name));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn get_help() -> Option<Vec<(&'static str, &'static str)>> {
+        Some(vec![
+            ("d, Del", "Cancel task"),
+            ("Esc, q, :", "Close"),
+            ("g", "Top"),
+            ("G", "Bottom"),
+            ("j, ↓", "Down"),
+            ("k, ↑", "Up"),
+        ])
+    }
+}
@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::{
+    layout::{Constraint, Margin, Rect},
+    style::Stylize as _,
+    text::Line,
+    widgets::{Row, Table, TableState, Widget as _},
+    Frame,
+};
+use unicode_width::UnicodeWidthStr as _;
+
+use crate::{
+    app::{Context, LoadType, Mode},
+    source::Item,
+    style, title,
+    util::{anime, template},
+};
+
+use super::{
+    border_block,
+    input::{self, InputWidget},
+    Widget,
+};
+
+static WIDTH: u16 = 50;
+
+/// Asks for the value of each variable in a query template before searching
+#[derive(Default)]
+pub struct TemplatePopup {
+    pub template: String,
+    pub vars: Vec<(String, InputWidget)>,
+    focus: usize,
+}
+
+impl TemplatePopup {
+    /// Ask for the variables in `template`, starting from the values they were
+    /// last given. Any without one are filled from the selected result's title.
+    pub fn open(
+        &mut self,
+        template: &str,
+        values: &HashMap<String, String>,
+        selected: Option<&Item>,
+    ) {
+        let parsed = selected.map(|i| anime::parse(&i.title)).unwrap_or_default();
+        self.template = template.to_owned();
+        self.focus = 0;
+        self.vars = template::variables(template)
+            .into_iter()
+            .map(|name| {
+                let value = values.get(&name).cloned().or_else(|| match name.as_str() {
+                    "title" | "name" => Some(parsed.name.clone()).filter(|n| !n.is_empty()),
+                    "episode" => parsed.episode.clone(),
+                    "resolution" => parsed.resolution.clone(),
+                    "group" => parsed.group.clone(),
+                    _ => None,
+                });
+                let mut input = InputWidget::new(100, None);
+                input.input = value.unwrap_or_default();
+                input.cursor = input.input.len();
+                (name, input)
+            })
+            .collect();
+    }
+
+    pub fn values(&self) -> HashMap<String, String> {
+        self.vars
+            .iter()
+            .map(|(name, input)| (name.to_owned(), input.input.to_owned()))
+            .collect()
+    }
+}
+
+impl Widget for TemplatePopup {
+    fn draw(&mut self, f: &mut Frame, ctx: &Context, area: Rect) {
+        let label_width = self.vars.iter().map(|(n, _)| n.width()).max().unwrap_or(0) as u16;
+        let height = self.vars.len() as u16 + 4;
+        let center = super::centered_rect(WIDTH, height, area);
+
+        let mut rows: Vec<Row> = self
+            .vars
+            .iter()
+            .map(|(name, _)| Row::new([name.to_owned(), ">".to_owned()]))
+            .collect();
+        rows.push(Row::new([""]));
+        let mut state = TableState::default().with_selected(Some(self.focus));
+        let table = Table::new(rows, [Constraint::Length(label_width), Constraint::Min(1)])
+            .block(border_block(&ctx.theme, true).title(title!(ctx.tr("Query Template"))))
+            .highlight_style(style!(bg:ctx.theme.hl_bg));
+
+        super::clear(center, f.buffer_mut(), ctx.theme.bg);
+        f.render_stateful_widget(table, center, &mut state);
+
+        let inner = center.inner(&Margin {
+            vertical: 1,
+            horizontal: 1,
+        });
+        // What will be searched, below the variables
+        let preview = template::expand(&self.template, &self.values());
+        Line::from(preview.fg(ctx.theme.border_color)).render(
+            Rect::new(inner.x, inner.bottom().saturating_sub(1), inner.width, 1),
+            f.buffer_mut(),
+        );
+
+        let input_x = inner.x + label_width + 3;
+        let input_width = inner.right().saturating_sub(input_x);
+        for (i, (_, input)) in self.vars.iter_mut().enumerate() {
+            let input_area = Rect::new(input_x, inner.y + i as u16, input_width, 1);
+            input.draw(f, ctx, input_area.intersection(inner));
+            if ctx.mode == Mode::Template && i == self.focus {
+                input.show_cursor(f, input_area);
+            }
+        }
+    }
+
+    fn handle_event(&mut self, ctx: &mut Context, e: &Event) {
+        if let Event::Key(KeyEvent {
+            code,
+            kind: KeyEventKind::Press,
+            ..
+        }) = e
+        {
+            let len = self.vars.len().max(1);
+            match code {
+                KeyCode::Esc => {
+                    ctx.mode = Mode::Search;
+                    return;
+                }
+                KeyCode::Enter => {
+                    ctx.template_values.extend(self.values());
+                    ctx.browsing = false;
+                    ctx.page = 1;
+                    ctx.mode = Mode::Loading(LoadType::Searching);
+                    return;
+                }
+                KeyCode::Down | KeyCode::Tab => {
+                    self.focus = (self.focus + 1) % len;
+                    return;
+                }
+                KeyCode::Up | KeyCode::BackTab => {
+                    self.focus = (self.focus + len - 1) % len;
+                    return;
+                }
+                _ => {}
+            }
+        }
+        if let Some((_, input)) = self.vars.get_mut(self.focus) {
+            input.handle_event(ctx, e);
+        }
+    }
+
+    fn get_help() -> Option<Vec<(&'static str, &'static str)>> {
+        let mut template_help = vec![
+            ("Enter", "Search"),
+            ("Esc", "Back to search"),
+            ("Tab, ↓", "Next field"),
+            ("S-Tab, ↑", "Previous field"),
+        ];
+        if let Some(input_help) = input::InputWidget::get_help() {
+            template_help.extend(input_help);
+        }
+        Some(template_help)
+    }
+}
@@ -9,7 +9,9 @@ use ratatui::{
 
 use crate::{
     app::{Context, Mode},
-    style, title,
+    style,
+    theme::Theme,
+    title,
 };
 
 use super::{border_block, VirtualStatefulTable, Widget};
@@ -28,6 +30,35 @@ impl Default for ThemePopup {
     }
 }
 
+impl ThemePopup {
+    /// Use the highlighted theme for the whole UI, without saving it
+    fn preview(&self, ctx: &mut Context) {
+        let idx = self.table.selected().unwrap_or(0);
+        if let Some((_, theme)) = ctx.themes.get_index(idx) {
+            set_theme(ctx, theme.clone());
+        }
+    }
+
+    /// Go back to the theme in use before the popup was opened
+    fn revert(&mut self, ctx: &mut Context) {
+        self.table.select(self.selected);
+        if let Some(theme) = ctx.themes.get(&ctx.config.theme) {
+            set_theme(ctx, theme.clone());
+        }
+    }
+}
+
+fn set_theme(ctx: &mut Context, theme: Theme) {
+    ctx.theme = theme;
+    ctx.theme.markers |= ctx.config.text_markers;
+    ctx.results.table = ctx.src.format_table(
+        &ctx.results.response.items,
+        &ctx.results.search,
+        &ctx.config.sources,
+        &ctx.theme,
+    );
+}
+
 impl Widget for ThemePopup {
     fn draw(&mut self, f: &mut Frame, ctx: &Context, area: Rect) {
         let buf = f.buffer_mut();
@@ -54,7 +85,7 @@ impl Widget for ThemePopup {
         );
 
         let table = Table::new(items, [Constraint::Percentage(100)])
-            .block(border_block(&ctx.theme, true).title(title!("Theme")))
+            .block(border_block(&ctx.theme, true).title(title!(ctx.tr("Theme"))))
             .highlight_style(style!(bg:ctx.theme.hl_bg));
         super::clear(center, buf, ctx.theme.bg);
         table.render(center, buf, &mut self.table.state);
@@ -83,33 +114,32 @@ impl Widget for ThemePopup {
         {
             match code {
                 KeyCode::Esc | KeyCode::Char('t') | KeyCode::Char('q') => {
+                    self.revert(ctx);
                     ctx.mode = Mode::Normal;
                 }
                 KeyCode::Char('j') | KeyCode::Down => {
                     self.table.next_wrap(ctx.themes.len(), 1);
+                    self.preview(ctx);
                 }
                 KeyCode::Char('k') | KeyCode::Up => {
                     self.table.next_wrap(ctx.themes.len(), -1);
+                    self.preview(ctx);
                 }
                 KeyCode::Char('G') => {
                     self.table.select(ctx.themes.len() - 1);
+                    self.preview(ctx);
                 }
                 KeyCode::Char('g') => {
                     self.table.select(0);
+                    self.preview(ctx);
                 }
                 KeyCode::Enter => {
                     let idx = self.table.selected().unwrap_or(0);
                     if let Some((_, theme)) = ctx.themes.get_index(idx) {
                         let theme_name = theme.name.clone();
                         self.selected = idx;
-                        ctx.theme = theme.clone();
                         ctx.config.theme.clone_from(&theme.name);
-                        ctx.results.table = ctx.src.format_table(
-                            &ctx.results.response.items,
-                            &ctx.results.search,
-                            &ctx.config.sources,
-                            &ctx.theme,
-                        );
+                        set_theme(ctx, theme.clone());
                         match ctx.save_config() {
                             Ok(_) => ctx.notify(format!("Updated theme to \"{}\"", theme_name)),
                             Err(e) => ctx.show_error(format!(
@@ -127,7 +157,7 @@ impl Widget for ThemePopup {
     fn get_help() -> Option<Vec<(&'static str, &'static str)>> {
         Some(vec![
             ("Enter", "Confirm"),
-            ("Esc, t, q", "Close"),
+            ("Esc, t, q", "Cancel"),
             ("j, ↓", "Down"),
             ("k, ↑", "Up"),
             ("g", "Top"),
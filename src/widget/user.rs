@@ -1,56 +1,402 @@
+use std::collections::HashMap;
+
 use crate::{
     app::{Context, LoadType, Mode},
-    title,
+    source::Sources,
+    style, title,
 };
-use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
 use ratatui::{
-    layout::{Margin, Rect},
-    widgets::{Paragraph, Widget as _},
+    layout::{Constraint, Margin, Rect},
+    style::Stylize as _,
+    text::Line,
+    widgets::{Row, StatefulWidget as _, Table, TableState},
     Frame,
 };
+use serde::{Deserialize, Serialize};
 
 use super::{
     border_block,
     input::{self, InputWidget},
-    Widget,
+    Corner, Widget,
 };
 
+/// A saved uploader, category, filter and query for one source
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SearchPreset {
+    pub name: String,
+    pub source: Sources,
+    /// Searched with the preset, which may be a query template like
+    /// `{title} S{season:02} 1080p`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    #[serde(default)]
+    pub user: Option<String>,
+    /// Same format as `default_category` for the source
+    #[serde(default)]
+    pub category: String,
+    #[serde(default)]
+    pub filter: usize,
+}
+
+/// What searching a source for an uploader's torrents found
+#[derive(Clone, PartialEq, Debug)]
+pub enum UserCheck {
+    Checking,
+    /// The uploader has torrents, this many if the source said
+    Found(Option<usize>),
+    /// The uploader exists, but has no torrents
+    Empty,
+    /// The source has no such uploader
+    Missing,
+    /// The source couldn't be searched
+    Failed(String),
+}
+
+impl UserCheck {
+    fn line(&self, ctx: &Context) -> Line<'static> {
+        let theme = &ctx.theme;
+        match self {
+            UserCheck::Checking => ctx.tr("Checking user…").fg(theme.border_color).into(),
+            UserCheck::Found(Some(n)) => {
+                Line::from(format!("{} ({})", ctx.tr("User found"), n).fg(theme.success))
+            }
+            UserCheck::Found(None) => ctx.tr("User found").fg(theme.success).into(),
+            UserCheck::Empty => ctx.tr("User has no results").fg(theme.error).into(),
+            UserCheck::Missing => ctx.tr("No such user").fg(theme.error).into(),
+            UserCheck::Failed(_) => ctx.tr("Couldn't check user").fg(theme.error).into(),
+        }
+    }
+}
+
+// Rows before the list of presets
+static NUM_FIELDS: usize = 4;
+static LABEL_WIDTH: u16 = 10;
+// Recent uploaders listed below the user field at once
+static MAX_SUGGESTIONS: usize = 5;
+
 pub struct UserPopup {
     pub input: InputWidget,
+    pub name: InputWidget,
+    pub category: usize,
+    pub filter: usize,
+    /// The search when the popup opened, replaced by a preset's query
+    pub query: String,
+    focus: usize,
+    confirmed: Option<(usize, usize, String)>,
+    /// Recent uploader highlighted below the user field, while listed
+    recent: Option<usize>,
+    checks: HashMap<(Sources, String), UserCheck>,
 }
 
 impl Default for UserPopup {
     fn default() -> Self {
         UserPopup {
             input: InputWidget::new(26, Some(|e| e.is_ascii())),
+            name: InputWidget::new(26, None),
+            category: 0,
+            filter: 0,
+            query: String::new(),
+            focus: 0,
+            confirmed: None,
+            recent: None,
+            checks: HashMap::new(),
+        }
+    }
+}
+
+impl UserPopup {
+    /// Start from the current category, filter and search each time the
+    /// popup opens
+    pub fn open(&mut self, category: usize, filter: usize, query: &str) {
+        self.category = category;
+        self.filter = filter;
+        self.query = query.to_owned();
+        self.focus = 0;
+        self.recent = None;
+    }
+
+    /// What was found for `user` on `src`, if it was checked
+    pub fn check(&self, src: Sources, user: &str) -> Option<&UserCheck> {
+        self.checks.get(&(src, user.to_owned()))
+    }
+
+    /// Keep what checking `user` on `src` found
+    pub fn set_check(&mut self, src: Sources, user: String, check: UserCheck) {
+        self.checks.insert((src, user), check);
+    }
+
+    /// Check the uploader typed in the background, unless it already was
+    fn check_input(&mut self, ctx: &mut Context) {
+        let user = self.input.input.trim().to_owned();
+        if user.is_empty() {
+            return;
+        }
+        match self.check(ctx.src, &user) {
+            None | Some(UserCheck::Failed(_)) => {
+                self.set_check(ctx.src, user.clone(), UserCheck::Checking);
+                ctx.check_user(user);
+            }
+            Some(_) => {}
+        }
+    }
+
+    /// Recent uploaders starting with what's typed, latest first
+    fn suggestions(&self, ctx: &Context) -> Vec<String> {
+        let typed = self.input.input.to_lowercase();
+        ctx.state
+            .recent_users
+            .iter()
+            .filter(|u| u.to_lowercase().starts_with(&typed) && **u != self.input.input)
+            .take(MAX_SUGGESTIONS)
+            .cloned()
+            .collect()
+    }
+
+    /// Move through the recent uploaders listed, returning whether the key
+    /// was used for it
+    fn on_recent(&mut self, ctx: &mut Context, code: &KeyCode) -> bool {
+        let Some(idx) = self.recent else {
+            return false;
+        };
+        let suggestions = self.suggestions(ctx);
+        match code {
+            KeyCode::Down => {
+                self.recent = Some((idx + 1).min(suggestions.len().saturating_sub(1)));
+            }
+            KeyCode::Up => self.recent = idx.checked_sub(1),
+            KeyCode::Esc => self.recent = None,
+            KeyCode::Enter => {
+                self.recent = None;
+                if let Some(user) = suggestions.get(idx) {
+                    self.input.input.clone_from(user);
+                    self.input.cursor = self.input.input.len();
+                    self.check_input(ctx);
+                }
+            }
+            _ => {
+                self.recent = None;
+                return false;
+            }
+        }
+        true
+    }
+
+    fn draw_recent(&self, f: &mut Frame, ctx: &Context, area: Rect, below: Rect) {
+        let (Some(idx), 0) = (self.recent, self.focus) else {
+            return;
+        };
+        let suggestions = self.suggestions(ctx);
+        if suggestions.is_empty() {
+            return;
+        }
+        let list = Rect {
+            x: below.x.saturating_sub(1),
+            y: below.y + 1,
+            width: below.width + 2,
+            height: suggestions.len() as u16 + 2,
+        }
+        .intersection(area);
+        let buf = f.buffer_mut();
+        super::clear(list, buf, ctx.theme.bg);
+        let mut state = TableState::default().with_selected(Some(idx));
+        Table::new(
+            suggestions.into_iter().map(|u| Row::new([u])),
+            [Constraint::Percentage(100)],
+        )
+        .block(border_block(&ctx.theme, true).title(title!(ctx.tr("Recent"))))
+        .highlight_style(style!(bg:ctx.theme.hl_bg))
+        .render(list, buf, &mut state);
+    }
+
+    /// The category, filter and query to search with, once confirmed
+    pub fn take_confirmed(&mut self) -> Option<(usize, usize, String)> {
+        self.confirmed.take()
+    }
+
+    fn presets(ctx: &Context) -> Vec<&SearchPreset> {
+        ctx.config
+            .presets
+            .iter()
+            .filter(|p| p.source == ctx.src)
+            .collect()
+    }
+
+    fn next_category(&mut self, ctx: &Context, amt: isize) {
+        let ids: Vec<usize> = ctx
+            .src_info
+            .cats
+            .iter()
+            .flat_map(|c| c.entries.iter().map(|e| e.id))
+            .collect();
+        if ids.is_empty() {
+            return;
+        }
+        let idx = ids.iter().position(|id| *id == self.category).unwrap_or(0) as isize;
+        self.category = ids[(idx + amt).rem_euclid(ids.len() as isize) as usize];
+    }
+
+    fn next_filter(&mut self, ctx: &Context, amt: isize) {
+        let len = ctx.src_info.filters.len() as isize;
+        if len > 0 {
+            self.filter = (self.filter as isize + amt).rem_euclid(len) as usize;
+        }
+    }
+
+    fn confirm(&mut self, ctx: &mut Context) {
+        ctx.user = match self.input.input.is_empty() {
+            true => None,
+            false => Some(self.input.input.to_owned()),
+        };
+        if let Some(user) = ctx.user.clone() {
+            ctx.remember_user(&user);
+        }
+        self.confirmed = Some((self.category, self.filter, self.query.to_owned()));
+        ctx.browsing = false;
+        ctx.page = 1;
+        ctx.mode = Mode::Loading(LoadType::Searching);
+    }
+
+    /// Search with a preset, as if it was picked from the list
+    pub fn search_preset(&mut self, ctx: &mut Context, preset: &SearchPreset) {
+        self.load_preset(ctx, preset);
+        self.confirm(ctx);
+    }
+
+    fn load_preset(&mut self, ctx: &Context, preset: &SearchPreset) {
+        self.input.input = preset.user.to_owned().unwrap_or_default();
+        self.input.cursor = self.input.input.len();
+        self.category = ctx.src_info.entry_from_cfg(&preset.category).id;
+        self.filter = preset.filter;
+        if let Some(query) = preset.query.as_ref() {
+            self.query.clone_from(query);
+        }
+        self.name.input.clone_from(&preset.name);
+        self.name.cursor = self.name.input.len();
+    }
+
+    fn save_preset(&mut self, ctx: &mut Context) {
+        let name = self.name.input.trim().to_owned();
+        if name.is_empty() {
+            return ctx.show_error(ctx.tr("Enter a name to save the preset as"));
+        }
+        let preset = SearchPreset {
+            name: name.clone(),
+            source: ctx.src,
+            query: match self.query.is_empty() {
+                true => None,
+                false => Some(self.query.to_owned()),
+            },
+            user: match self.input.input.is_empty() {
+                true => None,
+                false => Some(self.input.input.to_owned()),
+            },
+            category: ctx.src_info.clone().entry_from_id(self.category).cfg,
+            filter: self.filter,
+        };
+        let presets = &mut ctx.config.presets;
+        match presets
+            .iter_mut()
+            .find(|p| p.source == preset.source && p.name == preset.name)
+        {
+            Some(p) => *p = preset,
+            None => presets.push(preset),
+        }
+        match ctx.save_config() {
+            Ok(_) => ctx.notify(format!("Saved preset \"{}\"", name)),
+            Err(e) => ctx.show_error(format!("Failed to save preset:\n{}", e)),
+        }
+    }
+
+    fn delete_preset(&mut self, ctx: &mut Context, idx: usize) {
+        let Some(name) = Self::presets(ctx).get(idx).map(|p| p.name.to_owned()) else {
+            return;
+        };
+        let src = ctx.src;
+        ctx.config
+            .presets
+            .retain(|p| !(p.source == src && p.name == name));
+        self.focus = self.focus.min(NUM_FIELDS + Self::presets(ctx).len() - 1);
+        match ctx.save_config() {
+            Ok(_) => ctx.notify(format!("Deleted preset \"{}\"", name)),
+            Err(e) => ctx.show_error(format!("Failed to delete preset:\n{}", e)),
         }
     }
 }
 
 impl Widget for UserPopup {
     fn draw(&mut self, f: &mut Frame, ctx: &Context, area: Rect) {
+        let presets = Self::presets(ctx);
+        let height = NUM_FIELDS + 2 + presets.len() + (!presets.is_empty()) as usize;
+        let center = super::centered_rect(40, height as u16, area);
+
+        let category = ctx.src_info.clone().entry_from_id(self.category).name;
+        let filter = ctx
+            .src_info
+            .filters
+            .get(self.filter)
+            .cloned()
+            .unwrap_or_default();
+        let mut rows = vec![
+            Row::new(["User".to_owned(), ">".to_owned()]),
+            Row::new(["Category".to_owned(), format!("◀ {} ▶", category)]),
+            Row::new(["Filter".to_owned(), format!("◀ {} ▶", filter)]),
+            Row::new(["Save as".to_owned(), ">".to_owned()]),
+        ];
+        if !presets.is_empty() {
+            rows.push(
+                Row::new([Line::from("Presets")])
+                    .style(style!(bold, underlined, fg:ctx.theme.border_focused_color)),
+            );
+            rows.extend(
+                presets
+                    .iter()
+                    .map(|p| Row::new(["".to_owned(), p.name.to_owned()])),
+            );
+        }
+
+        // Skip over the presets header
+        let selected = match self.focus < NUM_FIELDS {
+            true => self.focus,
+            false => self.focus + 1,
+        };
+        let mut state = TableState::default().with_selected(Some(selected));
+        let table = Table::new(rows, [Constraint::Length(LABEL_WIDTH), Constraint::Min(1)])
+            .block(border_block(&ctx.theme, true).title(title!(ctx.tr("Search Constraints"))))
+            .highlight_style(style!(bg:ctx.theme.hl_bg));
+
         let buf = f.buffer_mut();
-        let center = super::centered_rect(30, 3, area);
-        let page_p = Paragraph::new(self.input.input.clone());
-        let indicator = Paragraph::new(">")
-            .block(border_block(&ctx.theme, true).title(title!("Posts by User")));
         super::clear(center, buf, ctx.theme.bg);
-        indicator.render(center, buf);
+        table.render(center, buf, &mut state);
 
-        let input_area = center.inner(&Margin {
+        // Draw text inputs after the "> " in the value column
+        let inner = center.inner(&Margin {
             vertical: 1,
             horizontal: 1,
         });
-        let input_area = Rect::new(
-            input_area.x + 2,
-            input_area.y,
-            input_area.width,
-            input_area.height,
-        );
-        page_p.render(input_area, buf);
+        let input_x = inner.x + LABEL_WIDTH + 3;
+        let input_width = inner.right().saturating_sub(input_x);
+        let user_area = Rect::new(input_x, inner.y, input_width, 1);
+        let name_area = Rect::new(input_x, inner.y + 3, input_width, 1);
+        self.input.draw(f, ctx, user_area.intersection(inner));
+        self.name.draw(f, ctx, name_area.intersection(inner));
+
+        let check = self
+            .check(ctx.src, self.input.input.trim())
+            .map(|c| c.line(ctx));
+        if let Some((line, line_area)) =
+            check.and_then(|c| Corner::BottomLeft.try_title(c, center, true))
+        {
+            f.render_widget(line, line_area);
+        }
+        self.draw_recent(f, ctx, area, user_area.intersection(inner));
 
         if ctx.mode == Mode::User {
-            self.input.show_cursor(f, input_area);
+            match self.focus {
+                0 => self.input.show_cursor(f, user_area),
+                3 => self.name.show_cursor(f, name_area),
+                _ => {}
+            }
         }
     }
 
@@ -58,26 +404,74 @@ impl Widget for UserPopup {
         if let Event::Key(KeyEvent {
             code,
             kind: KeyEventKind::Press,
+            modifiers,
             ..
         }) = e
         {
-            match code {
-                KeyCode::Esc => {
+            if self.on_recent(ctx, code) {
+                return;
+            }
+            let num_rows = NUM_FIELDS + Self::presets(ctx).len();
+            match (code, modifiers, self.focus) {
+                (KeyCode::Esc, _, _) => {
                     ctx.mode = Mode::Normal;
                 }
-                KeyCode::Enter => {
-                    ctx.user = Some(self.input.input.to_owned());
-                    ctx.mode = Mode::Loading(LoadType::Searching);
+                (KeyCode::Down, _, 0) if !self.suggestions(ctx).is_empty() => {
+                    self.recent = Some(0);
                 }
-                _ => {
-                    self.input.handle_event(ctx, e);
+                (KeyCode::Down | KeyCode::Tab, _, _) => {
+                    if self.focus == 0 {
+                        self.check_input(ctx);
+                    }
+                    self.focus = (self.focus + 1) % num_rows;
                 }
+                (KeyCode::Up | KeyCode::BackTab, _, _) => {
+                    if self.focus == 0 {
+                        self.check_input(ctx);
+                    }
+                    self.focus = (self.focus + num_rows - 1) % num_rows;
+                }
+                (KeyCode::Left, _, 1) => self.next_category(ctx, -1),
+                (KeyCode::Right, _, 1) => self.next_category(ctx, 1),
+                (KeyCode::Left, _, 2) => self.next_filter(ctx, -1),
+                (KeyCode::Right, _, 2) => self.next_filter(ctx, 1),
+                (KeyCode::Char('s'), &KeyModifiers::CONTROL, _) => self.save_preset(ctx),
+                (KeyCode::Delete, _, i) if i >= NUM_FIELDS => {
+                    self.delete_preset(ctx, i - NUM_FIELDS);
+                }
+                (KeyCode::Enter, _, i) => {
+                    if i >= NUM_FIELDS {
+                        let preset = Self::presets(ctx).get(i - NUM_FIELDS).map(|p| (*p).clone());
+                        if let Some(preset) = preset {
+                            self.load_preset(ctx, &preset);
+                        }
+                    }
+                    self.confirm(ctx);
+                }
+                (_, _, 0) => self.input.handle_event(ctx, e),
+                (_, _, 3) => self.name.handle_event(ctx, e),
+                _ => {}
+            }
+        } else {
+            match self.focus {
+                0 => self.input.handle_event(ctx, e),
+                3 => self.name.handle_event(ctx, e),
+                _ => {}
             }
         }
     }
 
     fn get_help() -> Option<Vec<(&'static str, &'static str)>> {
-        let mut search_help = vec![("Enter", "Confirm"), ("Esc", "Stop")];
+        let mut search_help = vec![
+            ("Enter", "Confirm/Use preset"),
+            ("Esc", "Stop"),
+            ("Tab, ↓", "Next field"),
+            ("S-Tab, ↑", "Previous field"),
+            ("↓", "Recent users, from the user field"),
+            ("←, →", "Change category/filter"),
+            ("C-s", "Save as preset"),
+            ("Del", "Delete preset"),
+        ];
         if let Some(input_help) = input::InputWidget::get_help() {
             search_help.extend(input_help);
         }
@@ -0,0 +1,137 @@
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::{
+    layout::{Constraint, Rect},
+    style::Stylize as _,
+    widgets::{Cell, Row, StatefulWidget as _, Table},
+    Frame,
+};
+
+use crate::{
+    app::{Context, Mode},
+    style, title,
+    util::conv::to_size,
+};
+
+use super::{border_block, VirtualStatefulTable, Widget};
+
+static WIDTH: u16 = 80;
+static MAX_HEIGHT: u16 = 20;
+
+pub struct WatchLaterPopup {
+    pub table: VirtualStatefulTable,
+}
+
+impl Default for WatchLaterPopup {
+    fn default() -> Self {
+        WatchLaterPopup {
+            table: VirtualStatefulTable::new(),
+        }
+    }
+}
+
+impl Widget for WatchLaterPopup {
+    fn draw(&mut self, f: &mut Frame, ctx: &Context, area: Rect) {
+        let list = &ctx.state.watch_later;
+        let rows: Vec<Row> = match list.is_empty() {
+            true => vec![Row::new([Cell::from(format!(
+                " {}",
+                ctx.tr("Add items from the results with w")
+            ))
+            .style(style!(fg:ctx.theme.border_color))])],
+            false => list
+                .iter()
+                .map(|w| {
+                    Row::new([
+                        Cell::from(format!(" {}", w.title)),
+                        Cell::from(format!("{:>9}", to_size(w.bytes))),
+                        Cell::from(format!("{:>4}", w.seeders)).style(style!(fg:ctx.theme.success)),
+                        Cell::from(format!("{:>4}", w.leechers)).style(style!(fg:ctx.theme.error)),
+                    ])
+                })
+                .collect(),
+        };
+        let height = (rows.len() as u16 + 3).min(MAX_HEIGHT);
+        let center = super::centered_rect(WIDTH, height, area);
+        super::clear(center, f.buffer_mut(), ctx.theme.bg);
+        let header = Row::new(["", "     Size", "   S", "   L"])
+            .fg(ctx.theme.border_focused_color)
+            .underlined();
+        Table::new(
+            rows,
+            [
+                Constraint::Min(1),
+                Constraint::Length(9),
+                Constraint::Length(4),
+                Constraint::Length(4),
+            ],
+        )
+        .header(header)
+        .block(border_block(&ctx.theme, true).title(title!(ctx.tr("Watch Later"))))
+        .highlight_style(style!(bg:ctx.theme.hl_bg))
+        .render(center, f.buffer_mut(), &mut self.table.state);
+    }
+
+    fn handle_event(&mut self, ctx: &mut Context, e: &Event) {
+        if let Event::Key(KeyEvent {
+            code,
+            kind: KeyEventKind::Press,
+            ..
+        }) = e
+        {
+            let len = ctx.state.watch_later.len();
+            match code {
+                KeyCode::Esc | KeyCode::Char('W') | KeyCode::Char('q') => {
+                    ctx.mode = Mode::Normal;
+                }
+                KeyCode::Char('j') | KeyCode::Down => {
+                    self.table.next_wrap(len, 1);
+                }
+                KeyCode::Char('k') | KeyCode::Up => {
+                    self.table.next_wrap(len, -1);
+                }
+                KeyCode::Char('G') => {
+                    self.table.select(len.saturating_sub(1));
+                }
+                KeyCode::Char('g') => {
+                    self.table.select(0);
+                }
+                KeyCode::Char(' ') | KeyCode::Enter => {
+                    if let Some(i) = self.table.state.selected().filter(|i| *i < len) {
+                        ctx.watch_later_to_batch(Some(i));
+                        self.table.select(i.min(len.saturating_sub(2)));
+                    }
+                }
+                KeyCode::Char('a') => {
+                    ctx.watch_later_to_batch(None);
+                    self.table.select(0);
+                }
+                KeyCode::Char('d') | KeyCode::Delete => {
+                    if let Some(i) = self.table.state.selected().filter(|i| *i < len) {
+                        let removed = ctx.state.watch_later.remove(i);
+                        ctx.notify(format!("Removed \"{}\" from watch later", removed.title));
+                        ctx.save_state();
+                        self.table.select(i.min(len.saturating_sub(2)));
+                    }
+                }
+                KeyCode::Char('r') => {
+                    ctx.refresh_watch_later();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn get_help() -> Option<Vec<(&'static str, &'static str)>> {
+        Some(vec![
+            ("Enter, Space", "Move item to batch"),
+            ("a", "Move every item to batch"),
+            ("d, Del", "Remove item"),
+            ("r", "Refresh seeders"),
+            ("Esc, W, q", "Close"),
+            ("g", "Top"),
+            ("G", "Bottom"),
+            ("j, ↓", "Down"),
+            ("k, ↑", "Up"),
+        ])
+    }
+}
@@ -0,0 +1,134 @@
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::{
+    layout::Rect,
+    style::Stylize as _,
+    text::Line,
+    widgets::{Paragraph, Widget as _},
+    Frame,
+};
+
+use crate::{
+    app::{Context, Mode},
+    title,
+    util::update::parse_version,
+};
+
+use super::{border_block, Widget};
+
+static WIDTH: u16 = 64;
+
+/// What each release added, newest first. Lines are kept short enough to
+/// fit the popup without wrapping.
+pub static RELEASES: &[(&str, &[&str])] = &[(
+    "0.9.0",
+    &[
+        "D searches several pages at once",
+        ": shows stats on searches, requests and downloads",
+        "m switches to a minimal layout without borders",
+        "Esc stops a batch download, Ctrl-A resumes it",
+        "Combos like y and b list the keys which finish them",
+        "High Contrast and Monochrome themes",
+        "text_markers marks trusted and remakes with [T] and [R]",
+        "columns.status adds a ✓/✗ column for trusted and remakes",
+        "nyaa --plain <query> prints results as plain lines",
+        "The terminal title shows the search and page",
+        "batch_bell rings the bell once a batch is downloaded",
+        "Ctrl-r reloads a captcha and Ctrl-o opens it",
+    ],
+)];
+
+/// Releases after `last`, up to and including `current`
+pub fn since(last: &str, current: &str) -> Vec<(&'static str, &'static [&'static str])> {
+    let (last, current) = (parse_version(last), parse_version(current));
+    RELEASES
+        .iter()
+        .filter(|(version, _)| {
+            let version = parse_version(version);
+            version > last && version <= current
+        })
+        .copied()
+        .collect()
+}
+
+/// Shown once after upgrading, listing what changed since the version which
+/// last ran
+#[derive(Default)]
+pub struct WhatsNewPopup {
+    releases: Vec<(&'static str, &'static [&'static str])>,
+    scroll: u16,
+    pending: bool,
+}
+
+impl WhatsNewPopup {
+    /// Show what changed since `last` once results are in focus
+    pub fn open(&mut self, last: &str, current: &str) {
+        self.releases = since(last, current);
+        self.scroll = 0;
+        self.pending = !self.releases.is_empty();
+    }
+
+    /// Whether the popup is waiting to be shown, which it only is once
+    pub fn take_pending(&mut self) -> bool {
+        std::mem::take(&mut self.pending)
+    }
+}
+
+impl Widget for WhatsNewPopup {
+    fn draw(&mut self, f: &mut Frame, ctx: &Context, area: Rect) {
+        let lines: Vec<Line> = self
+            .releases
+            .iter()
+            .flat_map(|(version, changes)| {
+                [Line::from(
+                    format!("v{}", version)
+                        .bold()
+                        .fg(ctx.theme.border_focused_color),
+                )]
+                .into_iter()
+                .chain(changes.iter().map(|c| Line::from(format!("- {}", c))))
+            })
+            .collect();
+        let height = (lines.len() as u16 + 2).min(area.height);
+        self.scroll = self
+            .scroll
+            .min((lines.len() as u16).saturating_sub(height.saturating_sub(2)));
+        let center = super::centered_rect(WIDTH, height, area);
+        let buf = f.buffer_mut();
+        super::clear(center, buf, ctx.theme.bg);
+        Paragraph::new(lines)
+            .block(border_block(&ctx.theme, true).title(title!(ctx.tr("What's New"))))
+            .fg(ctx.theme.fg)
+            .scroll((self.scroll, 0))
+            .render(center, buf);
+    }
+
+    fn handle_event(&mut self, ctx: &mut Context, e: &Event) {
+        if let Event::Key(KeyEvent {
+            code,
+            kind: KeyEventKind::Press,
+            ..
+        }) = e
+        {
+            match code {
+                KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => {
+                    ctx.mode = Mode::Normal;
+                }
+                KeyCode::Char('j') | KeyCode::Down => {
+                    self.scroll = self.scroll.saturating_add(1);
+                }
+                KeyCode::Char('k') | KeyCode::Up => {
+                    self.scroll = self.scroll.saturating_sub(1);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn get_help() -> Option<Vec<(&'static str, &'static str)>> {
+        Some(vec![
+            ("Esc, Enter, q", "Close"),
+            ("j, ↓", "Down"),
+            ("k, ↑", "Up"),
+        ])
+    }
+}
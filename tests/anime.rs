@@ -0,0 +1,301 @@
+use nyaa::{
+    app::Context,
+    results::{EmptyReason, QuickFilter, ResultResponse, Results, ResultsView},
+    source::{
+        nyaa_engine::{nyaa_table, parse_titles, NyaaColumns},
+        Item, ItemId, ItemType,
+    },
+    theme::Theme,
+    util::anime::{parse, ParsedTitle, Related},
+    widget::sort::SelectedSort,
+};
+
+#[test]
+fn test_parse_titles() {
+    assert_eq!(
+        parse("[SubsPlease] Sousou no Frieren - 05 (1080p) [ABCD1234].mkv"),
+        ParsedTitle {
+            name: "Sousou no Frieren".to_owned(),
+            episode: Some("05".to_owned()),
+            resolution: Some("1080p".to_owned()),
+            group: Some("SubsPlease".to_owned()),
+            codec: None,
+            batch: false,
+        }
+    );
+    assert_eq!(
+        parse("[Erai-raws] Dungeon Meshi - 01 ~ 12 [1080p][HEVC][Multiple Subtitle]"),
+        ParsedTitle {
+            name: "Dungeon Meshi".to_owned(),
+            episode: Some("01-12".to_owned()),
+            resolution: Some("1080p".to_owned()),
+            group: Some("Erai-raws".to_owned()),
+            codec: Some("HEVC".to_owned()),
+            batch: true,
+        }
+    );
+    assert_eq!(
+        parse("Show.Name.S01E05.720p.WEB.H.264-GROUP"),
+        ParsedTitle {
+            name: "Show Name".to_owned(),
+            episode: Some("S01E05".to_owned()),
+            resolution: Some("720p".to_owned()),
+            group: Some("GROUP".to_owned()),
+            codec: Some("AVC".to_owned()),
+            batch: false,
+        }
+    );
+
+    let batch = parse("[Judas] Vinland Saga (Season 2) [1080p][x265 10bit] (Batch)");
+    assert_eq!(batch.name, "Vinland Saga");
+    assert_eq!(batch.group.as_deref(), Some("Judas"));
+    assert_eq!(batch.episode, None);
+    assert!(batch.batch);
+
+    let plain = parse("Some Book Collection");
+    assert_eq!(plain.name, "Some Book Collection");
+    assert_eq!(plain.group, None);
+    assert_eq!(plain.resolution, None);
+}
+
+#[test]
+fn test_parsed_columns() {
+    let items = vec![Item {
+        title: "[SubsPlease] Sousou no Frieren - 05 (1080p) [ABCD1234].mkv".to_owned(),
+        ..Default::default()
+    }];
+    let headers = |columns: Option<NyaaColumns>| {
+        nyaa_table(
+            items.clone(),
+            &Theme::default(),
+            &SelectedSort::default(),
+            &columns,
+        )
+        .headers
+        .cells
+        .into_iter()
+        .map(|c| c.content.trim().to_owned())
+        .collect::<Vec<String>>()
+    };
+    // Hidden unless enabled
+    assert!(!headers(None).contains(&"Res".to_owned()));
+
+    let columns: NyaaColumns =
+        toml::from_str("resolution = true\nepisode = true\ngroup = true").unwrap();
+    assert_eq!(
+        &headers(Some(columns))[1..5],
+        ["Name", "Res", "Ep", "Group"]
+    );
+
+    let table = nyaa_table(
+        items.clone(),
+        &Theme::default(),
+        &SelectedSort::default(),
+        &Some(columns),
+    );
+    let cells: Vec<&str> = table.rows[0].cells[2..5]
+        .iter()
+        .map(|c| c.content.as_str())
+        .collect();
+    assert_eq!(cells, ["1080p", "05", "SubsPlease"]);
+
+    // Titles aren't parsed while every column made from them is hidden
+    assert_eq!(parse_titles(&items, false), [ParsedTitle::default()]);
+    assert_eq!(parse_titles(&items, true)[0].episode.as_deref(), Some("05"));
+}
+
+#[test]
+fn test_status_column() {
+    let items: Vec<Item> = [ItemType::Trusted, ItemType::Remake, ItemType::None]
+        .into_iter()
+        .map(|item_type| Item {
+            item_type,
+            ..Default::default()
+        })
+        .collect();
+    let table = |columns: Option<NyaaColumns>| {
+        nyaa_table(
+            items.clone(),
+            &Theme::default(),
+            &SelectedSort::default(),
+            &columns,
+        )
+    };
+    assert_eq!(table(None).headers.cells.len(), 7);
+
+    let table = table(Some(toml::from_str("status = true").unwrap()));
+    assert_eq!(table.headers.cells.len(), 8);
+    let status: Vec<&str> = table
+        .rows
+        .iter()
+        .map(|r| r.cells[1].content.as_str())
+        .collect();
+    assert_eq!(status, ["✓", "✗", ""]);
+}
+
+#[test]
+fn test_quick_filters() {
+    let titles = [
+        "[SubsPlease] Show - 01 (1080p) [ABCD1234].mkv",
+        "[SubsPlease] Show - 01 (720p) [ABCD1234].mkv",
+        "[Judas] Show - 01 [1080p][HEVC x265 10bit]",
+        "[Judas] Show (Season 1) [1080p][HEVC x265 10bit] (Batch)",
+    ];
+    let items = titles
+        .iter()
+        .map(|t| Item {
+            title: t.to_string(),
+            ..Default::default()
+        })
+        .collect();
+    let mut results = Results {
+        response: ResultResponse {
+            items,
+            total_results: Some(4),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let shown = |r: &Results| r.response.items.len();
+
+    results.quick_filter(&[QuickFilter::FullHd]);
+    assert_eq!(shown(&results), 3);
+    results.quick_filter(&[QuickFilter::FullHd, QuickFilter::Hevc]);
+    assert_eq!(shown(&results), 2);
+    results.quick_filter(&[QuickFilter::FullHd, QuickFilter::Hevc, QuickFilter::Batch]);
+    assert_eq!(shown(&results), 1);
+    assert_eq!(results.all_items().len(), 4);
+
+    // Nothing matching is told apart from an empty search
+    let mut none = results.clone();
+    none.response.items.retain(|_| false);
+    assert_eq!(none.empty_reason(), Some(EmptyReason::Filtered));
+
+    results.quick_filter(&[]);
+    assert_eq!(shown(&results), 4);
+    assert!(results.unfiltered.is_none());
+    assert_eq!(results.empty_reason(), None);
+}
+
+#[test]
+fn test_sticky_filters() {
+    let items: Vec<Item> = [ItemType::Trusted, ItemType::Remake, ItemType::None]
+        .into_iter()
+        .map(|item_type| Item {
+            item_type,
+            ..Default::default()
+        })
+        .collect();
+    let mut results = Results {
+        response: ResultResponse {
+            items,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    results.quick_filter(&[QuickFilter::NoRemakes]);
+    assert_eq!(results.response.items.len(), 2);
+    results.quick_filter(&[QuickFilter::TrustedOnly]);
+    assert_eq!(results.response.items.len(), 1);
+    assert!(QuickFilter::TrustedOnly.sticky() && !QuickFilter::Batch.sticky());
+
+    // Kept in the config, unlike the other quick filters
+    let mut ctx = Context::default();
+    ctx.toggle_quick_filter(QuickFilter::NoRemakes);
+    ctx.toggle_quick_filter(QuickFilter::Batch);
+    assert!(ctx.config.hide_remakes && !ctx.config.trusted_only);
+    ctx.toggle_quick_filter(QuickFilter::NoRemakes);
+    assert!(!ctx.config.hide_remakes);
+    assert_eq!(ctx.quick_filters, [QuickFilter::Batch]);
+}
+
+#[test]
+fn test_starred_filter() {
+    let items: Vec<Item> = (1..=3)
+        .map(|num| Item {
+            id: ItemId {
+                source: "nyaa",
+                num,
+            },
+            ..Default::default()
+        })
+        .collect();
+    let mut ctx = Context::default();
+    ctx.results.response.items = items.clone();
+    ctx.toggle_star(&items[0]);
+    ctx.toggle_star(&items[2]);
+    assert_eq!(ctx.state.starred, ["nyaa-1", "nyaa-3"]);
+    assert!(ctx.batch.is_empty());
+
+    ctx.toggle_quick_filter(QuickFilter::Starred);
+    assert_eq!(ctx.results.response.items.len(), 2);
+    assert!(!QuickFilter::Starred.sticky());
+
+    // Unstarring hides the item straight away
+    ctx.toggle_star(&items[0]);
+    assert_eq!(ctx.state.starred, ["nyaa-3"]);
+    assert_eq!(ctx.results.response.items.len(), 1);
+    assert!(ctx.results.is_starred(&items[2]));
+}
+
+#[test]
+fn test_prepare_results() {
+    let titles = [
+        "[SubsPlease] Show - 01 (1080p) [ABCD1234].mkv",
+        "[SubsPlease] Show - 01 (720p) [ABCD1234].mkv",
+        "[SubsPlease] Show - 02 (1080p) [ABCD1234].mkv",
+        "[SubsPlease] Show - 02 (720p) [ABCD1234].mkv",
+    ];
+    let items: Vec<Item> = titles
+        .iter()
+        .enumerate()
+        .map(|(num, t)| Item {
+            id: ItemId {
+                source: "nyaa",
+                num,
+            },
+            title: t.to_string(),
+            ..Default::default()
+        })
+        .collect();
+    let response = ResultResponse {
+        items,
+        last_page: 2,
+        ..Default::default()
+    };
+    let view = ResultsView {
+        quick_filters: vec![QuickFilter::FullHd],
+        starred: ["nyaa-2".to_owned()].into(),
+        ..Default::default()
+    };
+
+    // Only the first page of the deep search is formatted, once filtered
+    let results = Results::prepare(
+        Default::default(),
+        response,
+        view,
+        true,
+        |items, search, theme| nyaa_table(items.into(), theme, &search.sort, &None),
+    );
+    assert_eq!(results.all_items().len(), 4);
+    assert_eq!(results.response.items.len(), 1);
+    assert_eq!(results.response.items[0].title, titles[0]);
+    assert_eq!(results.table.rows.len(), 1);
+    assert!(results.starred.contains("nyaa-2"));
+}
+
+#[test]
+fn test_related_queries() {
+    let parsed = parse("[SubsPlease] Sousou no Frieren - 05 (1080p) [ABCD1234].mkv");
+    assert_eq!(
+        Related::Show.query(&parsed).as_deref(),
+        Some("Sousou no Frieren")
+    );
+    assert_eq!(
+        Related::Group.query(&parsed).as_deref(),
+        Some("SubsPlease Sousou no Frieren")
+    );
+    // Nothing to search for without a group
+    let parsed = parse("Shin Godzilla (2016) [BD 1080p x264 DTS-HD]");
+    assert_eq!(Related::Group.query(&parsed), None);
+}
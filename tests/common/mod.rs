@@ -1,13 +1,21 @@
 use std::{error::Error, path::PathBuf};
 
-use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
 use nyaa::{
     app::App,
     client::{Client, ClientConfig, DownloadResult},
     config::{Config, ConfigManager},
-    results::Results,
-    source::{Item, SourceResults},
-    sync::EventSync,
+    error::NyaaError,
+    results::{ResultResponse, Results, ResultsView},
+    source::{mock::MockSource, Item, Source as _, SourceResults},
+    sync::{
+        replay::{RecordedEvent, Session},
+        DownloadService, EventService, EventSync, SearchService,
+    },
+    util::watch::WatchItem,
+    widget::user::UserCheck,
 };
 use ratatui::{
     backend::{Backend as _, TestBackend},
@@ -18,9 +26,12 @@ use ratatui::{
 
 #[derive(Clone)]
 pub struct TestSync {
-    events: Vec<Event>,
+    events: Vec<RecordedEvent>,
+    mock: bool,
 }
 
+pub mod snapshot;
+
 pub struct TestConfig;
 
 pub type QueryFn = fn(
@@ -44,12 +55,24 @@ type ResultsFn = fn(
 ) -> Results;
 
 pub struct EventBuilder {
-    events: Vec<Event>,
+    events: Vec<RecordedEvent>,
+    delay: u64,
+    mock: bool,
 }
 
 impl EventBuilder {
     pub fn new() -> Self {
-        EventBuilder { events: Vec::new() }
+        EventBuilder {
+            events: Vec::new(),
+            delay: 0,
+            mock: false,
+        }
+    }
+
+    /// Serve results from `MockSource` instead of returning none
+    pub fn mock(&mut self) -> &mut Self {
+        self.mock = true;
+        self
     }
 
     pub fn string<S: Into<String>>(&mut self, string: S) -> &mut Self {
@@ -63,7 +86,15 @@ impl EventBuilder {
                 Event::Key(KeyEvent::new(KeyCode::Char(c), modif))
             })
             .collect::<Vec<Event>>();
-        self.events.extend(evts);
+        for evt in evts {
+            self.push(evt);
+        }
+        self
+    }
+
+    /// Wait before sending the next event, giving results time to load
+    pub fn wait(&mut self, millis: u64) -> &mut Self {
+        self.delay += millis;
         self
     }
 
@@ -87,8 +118,25 @@ impl EventBuilder {
         self.key_mod(KeyCode::BackTab, KeyModifiers::SHIFT)
     }
 
+    /// Append the events of a session recorded with `--record`
+    pub fn session<S: AsRef<str>>(&mut self, name: S) -> &mut Self {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/sessions")
+            .join(name.as_ref());
+        // Recorded delays are ignored to keep tests fast
+        let session = Session::load(path).unwrap();
+        for e in session.events {
+            self.push(e.event);
+        }
+        self
+    }
+
     pub fn push(&mut self, evt: Event) -> &mut Self {
-        self.events.push(evt);
+        self.events.push(RecordedEvent {
+            delay: self.delay,
+            event: evt,
+        });
+        self.delay = 0;
         self
     }
 
@@ -97,13 +145,22 @@ impl EventBuilder {
     }
 
     pub fn key_mod(&mut self, key: KeyCode, modifier: KeyModifiers) -> &mut Self {
-        self.events.push(Event::Key(KeyEvent::new(key, modifier)));
-        self
+        self.push(Event::Key(KeyEvent::new(key, modifier)))
+    }
+
+    pub fn click(&mut self, column: u16, row: u16) -> &mut Self {
+        self.push(Event::Mouse(MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        }))
     }
 
     pub fn build(&mut self) -> TestSync {
         TestSync {
             events: self.events.clone(),
+            mock: self.mock,
         }
     }
 }
@@ -112,6 +169,15 @@ pub async fn run_app<S: EventSync + Clone>(
     sync: S,
     w: u16,
     h: u16,
+) -> Result<Terminal<TestBackend>, Box<dyn Error>> {
+    run_app_with::<S, TestConfig>(sync, w, h).await
+}
+
+/// Run the app with the config loaded by `C`
+pub async fn run_app_with<S: EventSync + Clone, C: ConfigManager>(
+    sync: S,
+    w: u16,
+    h: u16,
 ) -> Result<Terminal<TestBackend>, Box<dyn Error>> {
     let mut backend = TestBackend::new(w, h);
     let _ = backend.clear();
@@ -120,8 +186,7 @@ pub async fn run_app<S: EventSync + Clone>(
 
     let mut app = App::default();
 
-    app.run_app::<_, S, TestConfig, true>(&mut terminal, sync)
-        .await?;
+    app.run_app::<_, S, C, true>(&mut terminal, sync).await?;
     Ok(terminal)
 }
 
@@ -147,47 +212,129 @@ pub fn print_buffer(buf: &Buffer) {
     }
 }
 
-impl EventSync for TestSync {
+impl SearchService for TestSync {
     async fn load_results(
         self,
-        tx_res: tokio::sync::mpsc::Sender<
-            Result<SourceResults, Box<dyn std::error::Error + Send + Sync>>,
-        >,
-        _loadtype: nyaa::app::LoadType,
+        tx_res: tokio::sync::mpsc::Sender<Result<SourceResults, NyaaError>>,
+        loadtype: nyaa::app::LoadType,
         _src: nyaa::source::Sources,
         _client: reqwest::Client,
-        _query: nyaa::sync::SearchQuery,
-        _config: nyaa::source::SourceConfig,
-        _theme: nyaa::theme::Theme,
+        query: nyaa::sync::SearchQuery,
+        config: nyaa::source::SourceConfig,
+        view: ResultsView,
         _date_format: Option<String>,
     ) {
-        let _ = tx_res
-            .send(Ok(SourceResults::Results(Results::default())))
-            .await;
+        let res = match self.mock {
+            true => {
+                let items = MockSource::items(&query);
+                let response = ResultResponse {
+                    total_results: Some(items.len()),
+                    last_page: 1,
+                    items,
+                    ..Default::default()
+                };
+                let deep = matches!(loadtype, nyaa::app::LoadType::DeepSearching(_));
+                Results::prepare(query, response, view, deep, |items, query, theme| {
+                    MockSource::format_table(items, query, &config, theme)
+                })
+            }
+            false => Results::default(),
+        };
+        let _ = tx_res.send(Ok(SourceResults::Results(res))).await;
     }
 
-    async fn read_event_loop(self, tx_evt: tokio::sync::mpsc::Sender<crossterm::event::Event>) {
-        for evt in self.events.into_iter() {
-            let _ = tx_evt.send(evt).await;
-        }
-        let _ = tx_evt.send(Event::FocusLost).await;
+    async fn refresh_watch_later(
+        self,
+        tx_watch: tokio::sync::mpsc::Sender<Result<Vec<WatchItem>, NyaaError>>,
+        _src: nyaa::source::Sources,
+        items: Vec<WatchItem>,
+        _client: reqwest::Client,
+        _config: nyaa::source::SourceConfig,
+    ) {
+        // Every item gains a seeder, to tell refreshed items apart
+        let items = items
+            .into_iter()
+            .map(|w| WatchItem {
+                seeders: w.seeders + 1,
+                ..w
+            })
+            .collect();
+        let _ = tx_watch.send(Ok(items)).await;
+    }
+
+    async fn check_user(
+        self,
+        tx_user: tokio::sync::mpsc::Sender<(nyaa::source::Sources, String, UserCheck)>,
+        src: nyaa::source::Sources,
+        _client: reqwest::Client,
+        user: String,
+        _config: nyaa::source::SourceConfig,
+    ) {
+        // Told apart by name, as nothing is searched while testing
+        let check = match user.as_str() {
+            "nobody" => UserCheck::Missing,
+            "quiet" => UserCheck::Empty,
+            _ => UserCheck::Found(Some(3)),
+        };
+        let _ = tx_user.send((src, user, check)).await;
     }
+}
 
+impl DownloadService for TestSync {
     async fn download(
         self,
         _tx_dl: tokio::sync::mpsc::Sender<DownloadResult>,
-        _batch: bool,
+        _batch: Option<nyaa::client::progress::Batch>,
         _items: Vec<Item>,
         _config: ClientConfig,
         _rq_client: reqwest::Client,
         _client: Client,
     ) {
     }
+
+    async fn validate_links(
+        self,
+        _tx_val: tokio::sync::mpsc::Sender<Vec<nyaa::source::ItemId>>,
+        _items: Vec<Item>,
+        _client: reqwest::Client,
+    ) {
+    }
+
+    async fn test_client(
+        self,
+        tx_test: tokio::sync::mpsc::Sender<(Client, Result<(), String>)>,
+        client: Client,
+        _config: ClientConfig,
+        _rq_client: reqwest::Client,
+    ) {
+        // No torrent client is running while testing, so nothing is connected
+        // to and every API client fails straight away
+        let res = match client.is_api() {
+            true => Err(format!("Failed to connect to {}", client)),
+            false => Ok(()),
+        };
+        let _ = tx_test.send((client, res)).await;
+    }
+}
+
+impl EventService for TestSync {
+    async fn read_event_loop(self, tx_evt: tokio::sync::mpsc::Sender<crossterm::event::Event>) {
+        for e in self.events.into_iter() {
+            if e.delay > 0 {
+                tokio::time::sleep(std::time::Duration::from_millis(e.delay)).await;
+            }
+            let _ = tx_evt.send(e.event).await;
+        }
+        let _ = tx_evt.send(Event::FocusLost).await;
+    }
 }
 
 impl ConfigManager for TestConfig {
     fn load() -> Result<nyaa::config::Config, Box<dyn Error>> {
-        Ok(Config::default())
+        let mut config = Config::default();
+        // Tests are drawn small, but should still use the full layout
+        config.layout.narrow_width = 0;
+        Ok(config)
     }
 
     fn store(_cfg: &nyaa::config::Config) -> Result<(), Box<dyn Error>> {
@@ -198,3 +345,44 @@ impl ConfigManager for TestConfig {
         Ok(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/config"))
     }
 }
+
+/// Serve HTTP on a free local port until the test ends, answering each request
+/// with what `respond` gives for its request line and body: the status, any
+/// more header lines, and the body. Returns the base URL.
+pub fn serve(respond: fn(&str) -> (u16, &'static str, String)) -> String {
+    use std::io::{BufRead as _, BufReader, Read as _, Write as _};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    std::thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            let mut reader = BufReader::new(&stream);
+            let mut request = String::new();
+            let _ = reader.read_line(&mut request);
+            let mut len = 0;
+            let mut header = String::new();
+            while reader.read_line(&mut header).is_ok_and(|n| n > 2) {
+                if let Some((name, value)) = header.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        len = value.trim().parse().unwrap_or_default();
+                    }
+                }
+                header.clear();
+            }
+            let mut body = vec![0; len];
+            let _ = reader.read_exact(&mut body);
+            request.push_str(&String::from_utf8_lossy(&body));
+
+            let (code, headers, body) = respond(&request);
+            let _ = write!(
+                stream,
+                "HTTP/1.1 {} Test\r\nContent-Length: {}\r\nConnection: close\r\n{}\r\n{}",
+                code,
+                body.len(),
+                headers,
+                body
+            );
+        }
+    });
+    format!("http://{}", addr)
+}
@@ -0,0 +1,74 @@
+use std::{fs, path::PathBuf};
+
+use nyaa::{app::Context, widget::Widget};
+use ratatui::{backend::TestBackend, buffer::Buffer, Terminal};
+
+use super::reset_buffer;
+
+/// Draw a single widget over the whole `w` x `h` area with the given context
+pub fn render<W: Widget>(widget: &mut W, ctx: &Context, w: u16, h: u16) -> Buffer {
+    let mut terminal = Terminal::new(TestBackend::new(w, h)).unwrap();
+    terminal
+        .draw(|f| {
+            let area = f.size();
+            widget.draw(f, ctx, area);
+        })
+        .unwrap();
+    reset_buffer(&terminal)
+}
+
+pub fn buffer_lines(buf: &Buffer) -> Vec<String> {
+    (0..buf.area.height)
+        .map(|y| {
+            (0..buf.area.width)
+                .map(|x| buf.get(x, y).symbol())
+                .collect::<String>()
+        })
+        .collect()
+}
+
+/// Compare a buffer against `tests/snapshots/<name>.snap`.
+///
+/// Run with `UPDATE_SNAPSHOTS=1` to write the snapshot instead. Missing
+/// snapshots are written, but still fail the test so they get reviewed.
+pub fn assert_snapshot<S: AsRef<str>>(name: S, buf: &Buffer) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(format!("{}.snap", name.as_ref()));
+    let actual = buffer_lines(buf);
+
+    let update = std::env::var("UPDATE_SNAPSHOTS").is_ok_and(|v| v == "1");
+    if update || !path.exists() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, actual.join("\n") + "\n").unwrap();
+        if !update {
+            panic!("new snapshot written to {:?}, review it and rerun", path);
+        }
+        return;
+    }
+
+    let stored = fs::read_to_string(&path).unwrap();
+    let expected: Vec<&str> = stored.lines().collect();
+    if expected == actual {
+        return;
+    }
+
+    let mut diff = String::new();
+    for i in 0..expected.len().max(actual.len()) {
+        match (expected.get(i), actual.get(i)) {
+            (Some(e), Some(a)) if e == a => diff += &format!("   {:>3} │{}│\n", i, a),
+            (e, a) => {
+                if let Some(e) = e {
+                    diff += &format!(" - {:>3} │{}│\n", i, e);
+                }
+                if let Some(a) = a {
+                    diff += &format!(" + {:>3} │{}│\n", i, a);
+                }
+            }
+        }
+    }
+    panic!(
+        "snapshot {:?} does not match (- expected, + actual):\n{}\nRun with UPDATE_SNAPSHOTS=1 to accept the changes",
+        path, diff
+    );
+}
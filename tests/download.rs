@@ -0,0 +1,226 @@
+use nyaa::{
+    client::{
+        download::{
+            sanitize_filename, save_torrent, torrent_path, DownloadConfig, DownloadFileClient,
+            Duplicates, FileNaming, Saved,
+        },
+        multidownload,
+        progress::Batch,
+        ClientConfig,
+    },
+    source::{Item, ItemId},
+    util::torrent::info_hash,
+};
+use tokio_util::sync::CancellationToken;
+
+static TORRENT: &[u8] = b"d8:announce14:http://tracker4:infod6:lengthi1e4:name1:ae7:comment2:hie";
+
+#[test]
+fn test_sanitize_filename() {
+    let title = r#"[Group] Show: "Part 2" / Ep 01? <1080p>*"#;
+    assert_eq!(
+        sanitize_filename(title, true),
+        "[Group] Show_ _Part 2_ _ Ep 01_ _1080p__"
+    );
+    assert_eq!(
+        sanitize_filename(title, false),
+        r#"[Group] Show: "Part 2" _ Ep 01? <1080p>*"#
+    );
+    assert_eq!(
+        sanitize_filename("Tabs\tand\nlines", false),
+        "Tabs_and_lines"
+    );
+
+    // Reserved device names and trailing dots only matter on Windows
+    assert_eq!(sanitize_filename("con", true), "_con");
+    assert_eq!(sanitize_filename("LPT1.batch", true), "_LPT1.batch");
+    assert_eq!(sanitize_filename("Console", true), "Console");
+    assert_eq!(sanitize_filename("Show... ", true), "Show");
+    assert_eq!(sanitize_filename("Show...", false), "Show...");
+    assert_eq!(sanitize_filename("..", false), "torrent");
+    assert_eq!(sanitize_filename("", true), "torrent");
+
+    // Cut on a character boundary
+    let long = "あ".repeat(100);
+    let short = sanitize_filename(&long, false);
+    assert!(short.len() <= 200);
+    assert!(long.starts_with(&short));
+}
+
+#[test]
+fn test_torrent_path() {
+    let dir = std::env::temp_dir().join("nyaa-test-torrent-path");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let item = Item {
+        id: ItemId::new("nyaa", 123),
+        title: "Show - 01 (1080p)".to_owned(),
+        file_name: "123.torrent".to_owned(),
+        ..Default::default()
+    };
+    let mut conf = DownloadConfig::default();
+    assert_eq!(
+        torrent_path(&item, &conf, &dir, TORRENT),
+        dir.join("123.torrent")
+    );
+
+    conf.naming = FileNaming::Title;
+    let path = torrent_path(&item, &conf, &dir, TORRENT);
+    assert_eq!(path, dir.join("Show - 01 (1080p).torrent"));
+
+    // Names taken by another torrent get the id added, the same each time
+    std::fs::write(&path, "").unwrap();
+    let taken = dir.join("Show - 01 (1080p) [nyaa-123].torrent");
+    assert_eq!(torrent_path(&item, &conf, &dir, TORRENT), taken);
+    assert_eq!(torrent_path(&item, &conf, &dir, TORRENT), taken);
+
+    // Names taken by the same torrent are kept
+    std::fs::write(&path, TORRENT).unwrap();
+    assert_eq!(torrent_path(&item, &conf, &dir, TORRENT), path);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_torrent_path_long_title() {
+    let dir = std::env::temp_dir().join("nyaa-test-torrent-path-long");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let item = Item {
+        id: ItemId::new("nyaa", 123),
+        title: "Long ".repeat(50),
+        ..Default::default()
+    };
+    let mut conf = DownloadConfig::default();
+    conf.naming = FileNaming::Title;
+    let path = torrent_path(&item, &conf, &dir, TORRENT);
+    std::fs::write(&path, "").unwrap();
+
+    // The title is cut to make room for the id, so it never overwrites
+    let taken = torrent_path(&item, &conf, &dir, TORRENT);
+    assert_ne!(taken, path);
+    let name = taken.file_stem().unwrap().to_str().unwrap();
+    assert!(name.ends_with(" [nyaa-123]"));
+    assert!(name.len() <= 200);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_info_hash() {
+    assert_eq!(
+        info_hash(TORRENT).as_deref(),
+        Some("8aa9d3c65b0164d222d9b2527a70f125668575ef")
+    );
+    // The same info with other keys around it
+    assert_eq!(
+        info_hash(b"d4:infod6:lengthi1e4:name1:aee"),
+        info_hash(TORRENT)
+    );
+    assert_eq!(info_hash(b"d8:announce3:urle"), None);
+    assert_eq!(info_hash(b"<html>Not found</html>"), None);
+    assert_eq!(info_hash(b"d4:infod6:lengthi1e4:name999:ae"), None);
+    // Nested too deep to read
+    let deep = [b"d4:info".as_slice(), &[b'l'; 1000], &[b'e'; 1001]].concat();
+    assert_eq!(info_hash(&deep), None);
+}
+
+#[test]
+fn test_duplicate_torrents() {
+    let dir = std::env::temp_dir().join("nyaa-test-duplicates");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let item = |num| Item {
+        id: ItemId::new("nyaa", num),
+        file_name: format!("{}.torrent", num),
+        ..Default::default()
+    };
+    let mut conf = DownloadConfig::default();
+    let first = dir.join("1.torrent");
+    assert_eq!(
+        save_torrent(&item(1), &conf, &dir, TORRENT).unwrap(),
+        Saved::New(first.clone())
+    );
+
+    // The same torrent from another post
+    assert_eq!(
+        save_torrent(&item(2), &conf, &dir, TORRENT).unwrap(),
+        Saved::Skipped(first.clone())
+    );
+    assert!(!dir.join("2.torrent").exists());
+
+    conf.duplicates = Duplicates::Warn;
+    assert_eq!(
+        save_torrent(&item(2), &conf, &dir, TORRENT).unwrap(),
+        Saved::Duplicate(dir.join("2.torrent"), first.clone())
+    );
+
+    conf.duplicates = Duplicates::Allow;
+    assert_eq!(
+        save_torrent(&item(3), &conf, &dir, TORRENT).unwrap(),
+        Saved::New(dir.join("3.torrent"))
+    );
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_download_twice() {
+    let dir = std::env::temp_dir().join("nyaa-test-download-twice");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let item = Item {
+        id: ItemId::new("nyaa", 123),
+        title: "Show - 01 (1080p)".to_owned(),
+        file_name: "123.torrent".to_owned(),
+        ..Default::default()
+    };
+    let mut conf = DownloadConfig::default();
+    conf.naming = FileNaming::Title;
+    conf.duplicates = Duplicates::Warn;
+    // Downloading the same item again writes over the same file
+    let path = dir.join("Show - 01 (1080p).torrent");
+    for _ in 0..3 {
+        assert_eq!(
+            save_torrent(&item, &conf, &dir, TORRENT).unwrap(),
+            Saved::New(path.clone())
+        );
+    }
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_stopped_batch() {
+    let items: Vec<Item> = (1..=3)
+        .map(|i| Item {
+            id: ItemId::new("nyaa", i),
+            ..Default::default()
+        })
+        .collect();
+    let batch = Batch {
+        id: 0,
+        stop: CancellationToken::new(),
+    };
+    batch.stop.cancel();
+
+    // Nothing is sent once stopped, and every item is reported as skipped
+    let res = multidownload::<DownloadFileClient, _>(
+        |n| format!("Saved {} torrents", n),
+        &items,
+        &ClientConfig::default(),
+        &reqwest::Client::new(),
+        batch,
+    )
+    .await;
+    assert!(res.success_ids.is_empty());
+    assert!(res.errors.is_empty());
+    let mut skipped = res.skipped;
+    skipped.sort_by_key(|id| id.num);
+    assert_eq!(skipped, items.into_iter().map(|i| i.id).collect::<Vec<_>>());
+}
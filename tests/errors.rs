@@ -0,0 +1,28 @@
+use nyaa::error::NyaaError;
+
+#[test]
+fn test_error_messages() {
+    let status = NyaaError::Status {
+        url: "https://nyaa.si/?q=test".to_owned(),
+        code: 503,
+    };
+    assert_eq!(
+        status.to_string(),
+        "https://nyaa.si/?q=test\nInvalid response code: 503"
+    );
+    let parse: NyaaError = "Page did not contain a results table".into();
+    assert_eq!(parse.to_string(), "Page did not contain a results table");
+}
+
+#[test]
+fn test_retryable() {
+    let status = |code| NyaaError::Status {
+        url: "https://nyaa.si".to_owned(),
+        code,
+    };
+    assert!(status(503).is_retryable());
+    assert!(status(429).is_retryable());
+    assert!(!status(404).is_retryable());
+    assert!(!NyaaError::Parse("bad html".to_owned()).is_retryable());
+    assert!(!NyaaError::Config("missing source".to_owned()).is_retryable());
+}
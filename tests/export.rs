@@ -0,0 +1,83 @@
+use crossterm::event::{Event, KeyCode, KeyEvent};
+use nyaa::{
+    app::{Context, Mode},
+    source::{Item, ItemDate, ItemType},
+    util::export::{batch_page, plain_line, write_batch_page, ExportFormat},
+    widget::{batch::BatchWidget, Widget as _},
+};
+
+fn items() -> Vec<Item> {
+    vec![
+        Item {
+            title: "[Group] Show <Uncut> & More - 01 (1080p)".to_owned(),
+            bytes: 1024 * 1024 * 512,
+            seeders: 42,
+            date: ItemDate::parse_utc("2024-05-01 12:00", "%Y-%m-%d %H:%M", None),
+            post_link: "https://nyaa.si/view/1".to_owned(),
+            torrent_link: "https://nyaa.si/download/1.torrent".to_owned(),
+            magnet_link: "magnet:?xt=urn:btih:abc&dn=Show".to_owned(),
+            ..Default::default()
+        },
+        Item {
+            title: "No links | at all".to_owned(),
+            ..Default::default()
+        },
+    ]
+}
+
+#[test]
+fn test_batch_html() {
+    let page = batch_page(&items(), ExportFormat::Html);
+    assert!(page.contains("<p>2 torrents, 512.0 MiB</p>"));
+    assert!(page.contains(
+        r#"<a href="https://nyaa.si/view/1">[Group] Show &lt;Uncut&gt; &amp; More - 01 (1080p)</a>"#
+    ));
+    assert!(page.contains(r#"<a href="magnet:?xt=urn:btih:abc&amp;dn=Show">magnet</a>"#));
+    assert!(page.contains(r#"<a href="https://nyaa.si/download/1.torrent">torrent</a>"#));
+    assert!(page.contains("<tr><td>No links | at all</td><td>0 B</td><td>0</td>"));
+}
+
+#[test]
+fn test_plain_line() {
+    let mut items = items();
+    items[0].item_type = ItemType::Remake;
+    assert_eq!(
+        plain_line(&items[0]),
+        "[R] [Group] Show <Uncut> & More - 01 (1080p), 512.0 MiB, 42 seeders, 0 leechers, 2024-05-01 12:00, https://nyaa.si/view/1"
+    );
+}
+
+#[test]
+fn test_batch_markdown() {
+    let page = batch_page(&items(), ExportFormat::Markdown);
+    let lines: Vec<&str> = page.lines().collect();
+    assert_eq!(lines[0], "# nyaa batch");
+    assert_eq!(lines[2], "2 torrents, 512.0 MiB");
+    assert_eq!(
+        lines[6],
+        r"| [\[Group\] Show \<Uncut\> & More - 01 (1080p)](<https://nyaa.si/view/1>) | 512.0 MiB | 42 | 2024-05-01 12:00 | [magnet](<magnet:?xt=urn:btih:abc&dn=Show>) [torrent](<https://nyaa.si/download/1.torrent>) |"
+    );
+    assert!(lines[7].starts_with(r"| No links \| at all | 0 B | 0 |"));
+}
+
+#[test]
+fn test_export_batch() {
+    let dir = std::env::temp_dir().join("nyaa-test-export");
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let path = write_batch_page(&items(), ExportFormat::Markdown, &dir.to_string_lossy()).unwrap();
+    assert_eq!(path.extension().unwrap(), "md");
+    assert!(std::fs::read_to_string(path)
+        .unwrap()
+        .starts_with("# nyaa batch"));
+
+    let mut ctx = Context::default();
+    ctx.mode = Mode::Batch;
+    ctx.config.batch_export_dir = Some(dir.join("batch").to_string_lossy().to_string());
+    ctx.batch = items();
+    BatchWidget::default().handle_event(&mut ctx, &Event::Key(KeyEvent::from(KeyCode::Char('e'))));
+    let exported: Vec<_> = std::fs::read_dir(dir.join("batch")).unwrap().collect();
+    assert_eq!(exported.len(), 1);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,24 @@
+use nyaa::util::html::check_selectors;
+use scraper::{Html, Selector};
+
+#[test]
+fn test_check_selectors() {
+    let doc = Html::parse_document(
+        r#"<table><tr><td><a href="/a">A</a></td></tr><tr><td>B</td></tr></table>"#,
+    );
+    let rows = Selector::parse("tr").unwrap();
+    let link = Selector::parse("td > a").unwrap();
+    let span = Selector::parse("td > span").unwrap();
+
+    assert_eq!(check_selectors(&doc, &rows, &[&link]), None);
+    assert_eq!(
+        check_selectors(&doc, &rows, &[&link, &span]),
+        Some("Selectors matched nothing, site layout may have changed:\ntd > span".to_owned())
+    );
+
+    let missing = Selector::parse("div.row").unwrap();
+    assert_eq!(
+        check_selectors(&doc, &missing, &[&link]),
+        Some("Selectors matched nothing, site layout may have changed:\ndiv.row".to_owned())
+    );
+}
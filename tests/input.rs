@@ -44,3 +44,67 @@ async fn test_search() {
         ])
     );
 }
+
+#[tokio::test]
+async fn test_cancel_search() {
+    // Searches never finish without mock results, so Esc cancels the first one,
+    // leaving the dashboard
+    let sync = EventBuilder::new().esc().quit().build();
+
+    let res = reset_buffer(&run_app(sync, 60, 8).await.unwrap());
+
+    assert_eq!(
+        res,
+        Buffer::with_lines([
+            r#"┌Search──────────────────────────────Press F1 or ? for help┐"#,
+            r#"│                                                          │"#,
+            r#"└──────────────────────────────────────────────────────────┘"#,
+            r#"┌Results───────────────────────────────────────────────────┐"#,
+            r#"│ Recent searches                                          │"#,
+            r#"│   None yet                                               │"#,
+            r#"│                                                          │"#,
+            r#"└──────────────────────────────────────────────────────────┘"#,
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_cancel_restores_search() {
+    // Each search is cancelled before its results are read, so the results of
+    // "frieren" stay, along with the sort and query they were searched with
+    let sync = EventBuilder::new()
+        .mock()
+        .string("/frieren")
+        .enter()
+        .wait(50)
+        .string("s")
+        .string("j")
+        .enter()
+        .esc()
+        .string("/one punch")
+        .enter()
+        .esc()
+        .string("s")
+        .quit()
+        .build();
+
+    assert_eq!(
+        reset_buffer(&run_app(sync, 60, 14).await.unwrap()),
+        Buffer::with_lines([
+            r#"┌Search──────────────────────────────Pres│Search cancelled│┐"#,
+            r#"│frieren                                 └────────────────┘│"#,
+            r#"└──────────────────────────┌──────────────────────────────┐┘"#,
+            r#"┌Results 1-3 (3┌Sort Descen│Sort by "Downloads" Descending│┐"#,
+            r#"│Cat Name      │  Date    └──────────────────────────────┘│"#,
+            r#"│Sub [SubsPleas│   Downloads                │311  104 15K  █"#,
+            r#"│Sub [SubsPleas│   Seeders                  │874   31 6120 █"#,
+            r#"│AMV [Fan-Sub] │   Leechers                 │  5    0 77   █"#,
+            r#"│              │   Size                     │              █"#,
+            r#"│              └────────────────────────────┘              █"#,
+            r#"│                                                          █"#,
+            r#"│                                                          █"#,
+            r#"│                                                          │"#,
+            r#"└─────────────────────────────────────────────────────────s┘"#,
+        ])
+    );
+}
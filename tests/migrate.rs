@@ -0,0 +1,150 @@
+use std::fs;
+
+use nyaa::config::{
+    migrate::{migrate, upgrade, version, CONFIG_VERSION},
+    state::{move_to_state, State},
+    validate::check,
+    Config,
+};
+use nyaa::source::Sources;
+use toml::Table;
+
+#[test]
+fn test_migrate() {
+    let mut table: Table = r#"
+        base_url = "nyaa.land"
+        default_theme = "Dracula"
+
+        [source.nyaa]
+        default_sort = "Seeders"
+        "#
+    .parse()
+    .unwrap();
+    let changes = migrate(&mut table);
+    assert_eq!(changes.len(), 2);
+    assert_eq!(version(&table), CONFIG_VERSION);
+
+    let text = toml::to_string(&table).unwrap();
+    assert!(check(&text).is_empty());
+    let config: Config = toml::from_str(&text).unwrap();
+    assert_eq!(config.theme, "Dracula");
+    let nyaa = config.sources.nyaa.unwrap();
+    assert_eq!(nyaa.base_url, "nyaa.land");
+    assert_eq!(nyaa.default_sort.to_string(), "Seeders");
+
+    // Nothing left to do once upgraded
+    assert!(migrate(&mut table).is_empty());
+}
+
+#[test]
+fn test_name_custom_sources() {
+    let mut table: Table = r#"
+        version = 2
+        default_source = { Custom = 1 }
+
+        [[presets]]
+        name = "Private"
+        source = { Custom = 1 }
+
+        [[presets]]
+        name = "Removed"
+        source = { Custom = 5 }
+
+        [[source.custom_nyaa]]
+        name = "My Nyaa"
+        base_url = "https://nyaa.example.com"
+
+        [[source.custom_nyaa]]
+        name = "My Sukebei"
+        base_url = "https://sukebei.example.com"
+        "#
+    .parse()
+    .unwrap();
+    assert_eq!(migrate(&mut table).len(), 1);
+
+    let config: Config = toml::from_str(&toml::to_string(&table).unwrap()).unwrap();
+    assert!(config.source == Sources::Custom("My Sukebei"));
+    assert!(config.presets[0].source == Sources::Custom("My Sukebei"));
+    // Instances which are gone are left to fall back to nyaa
+    assert!(config.presets[1].source == Sources::Custom("#5"));
+}
+
+#[test]
+fn test_migrate_keeps_newer_settings() {
+    let mut table: Table = r#"
+        base_url = "nyaa.land"
+        default_theme = "Dracula"
+        theme = "Gruvbox"
+
+        [source.nyaa]
+        base_url = "nyaa.si"
+        "#
+    .parse()
+    .unwrap();
+    assert!(migrate(&mut table).is_empty());
+    assert_eq!(table["base_url"].as_str(), Some("nyaa.land"));
+    assert_eq!(
+        table["source"]["nyaa"]["base_url"].as_str(),
+        Some("nyaa.si")
+    );
+    assert_eq!(table["theme"].as_str(), Some("Gruvbox"));
+    assert_eq!(version(&table), CONFIG_VERSION);
+}
+
+#[test]
+fn test_upgrade_file() {
+    let dir = std::env::temp_dir().join(format!("nyaa-migrate-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("config.toml");
+    let old = "default_theme = \"Dracula\"\n";
+    fs::write(&path, old).unwrap();
+
+    let changes = upgrade(&path).unwrap();
+    assert_eq!(changes.len(), 2);
+    assert_eq!(
+        fs::read_to_string(dir.join("config.v0.toml.bak")).unwrap(),
+        old
+    );
+    let text = fs::read_to_string(&path).unwrap();
+    let config: Config = toml::from_str(&text).unwrap();
+    assert_eq!(config.theme, "Dracula");
+    assert_eq!(config.version, CONFIG_VERSION);
+
+    // An up to date config is left alone
+    assert!(upgrade(&path).unwrap().is_empty());
+    assert_eq!(fs::read_to_string(&path).unwrap(), text);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_move_to_state() {
+    let dir = std::env::temp_dir().join(format!("nyaa-state-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("config.toml");
+    let state_path = dir.join("state.toml");
+    fs::write(
+        &path,
+        "version = 2\nlast_version = \"0.8.1\"\nstarred = [\"nyaa-1\"]\n",
+    )
+    .unwrap();
+
+    let changes = upgrade(&path).unwrap();
+    assert_eq!(changes.len(), 2);
+    let config: Table = fs::read_to_string(&path).unwrap().parse().unwrap();
+    assert!(!config.contains_key("last_version"));
+    assert!(!config.contains_key("starred"));
+    let state: State = toml::from_str(&fs::read_to_string(&state_path).unwrap()).unwrap();
+    assert_eq!(state.last_version.as_deref(), Some("0.8.1"));
+    assert_eq!(state.starred, ["nyaa-1"]);
+
+    // What's already in the state file wins over the old config
+    let mut table: Table = "last_version = \"0.7.0\"".parse().unwrap();
+    assert!(move_to_state(&mut table, &state_path).unwrap());
+    assert!(table.is_empty());
+    let state: State = toml::from_str(&fs::read_to_string(&state_path).unwrap()).unwrap();
+    assert_eq!(state.last_version.as_deref(), Some("0.8.1"));
+    assert!(!move_to_state(&mut table, &state_path).unwrap());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,841 @@
+use crossterm::event::{KeyCode, KeyModifiers};
+use std::{error::Error, path::PathBuf};
+
+use nyaa::{
+    app::{Context, LoadType, Mode},
+    config::{Config, ConfigManager},
+    results::{ResultResponse, ResultTable, Results},
+    source::{mock::MockSource, sukebei_nyaa::SukebeiNyaaConfig, Item},
+    sync::SearchQuery,
+    widget::{
+        results::ResultsWidget,
+        sort::{SelectedSort, SortDir},
+    },
+};
+use ratatui::buffer::Buffer;
+
+use crate::common::{reset_buffer, run_app, run_app_with, EventBuilder, TestConfig};
+
+#[allow(dead_code)]
+mod common;
+
+#[test]
+fn test_mock_items() {
+    let search = SearchQuery {
+        query: "frieren".to_owned(),
+        sort: SelectedSort {
+            sort: 2,
+            dir: SortDir::Desc,
+        },
+        ..Default::default()
+    };
+    let titles: Vec<String> = MockSource::items(&search)
+        .into_iter()
+        .map(|i| i.title)
+        .collect();
+    assert_eq!(
+        titles,
+        [
+            "[SubsPlease] Sousou no Frieren - 28 (1080p) [A1B2C3D4].mkv",
+            "[SubsPlease] Sousou no Frieren - 28 (720p) [E5F6A7B8].mkv",
+            "[Fan-Sub] Frieren AMV - Anytime Anywhere (1080p).mp4",
+        ]
+    );
+
+    // Trusted only
+    let search = SearchQuery {
+        filter: 2,
+        ..search
+    };
+    assert_eq!(MockSource::items(&search).len(), 2);
+}
+
+#[tokio::test]
+async fn test_mock_results() {
+    let sync = EventBuilder::new()
+        .mock()
+        .string("/one punch")
+        .enter()
+        .wait(50)
+        .quit()
+        .build();
+
+    assert_eq!(
+        reset_buffer(&run_app(sync, 60, 8).await.unwrap()),
+        Buffer::with_lines([
+            r#"┌Search──────────────────────────────Press F1 or ? for help┐"#,
+            r#"│one punch                                                 │"#,
+            r#"└──────────────────────────────────────────────────────────┘"#,
+            r#"┌Results 1-1 (1 total): Page 1/1─dl: Run Command, src: Nyaa┐"#,
+            r#"│Cat Name          Size          Date ▼                 │"#,
+            r#"│Sub [Erai-raws]  16.8 GiB 2024-06-12 09:41  412   56 3301 █"#,
+            r#"│                                                          █"#,
+            r#"└──────────────────────────────────────────────────────<CR>┘"#,
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_count_motion() {
+    let sync = EventBuilder::new()
+        .mock()
+        .string("/frieren")
+        .enter()
+        .wait(50)
+        .string("2j ")
+        .quit()
+        .build();
+
+    assert_eq!(
+        reset_buffer(&run_app(sync, 60, 8).await.unwrap()),
+        Buffer::with_lines([
+            r#"┌Search──────────────────────────────Press F1 or ? for help┐"#,
+            r#"│frieren                                                   │"#,
+            r#"└──────────────────────────────────────────────────────────┘"#,
+            r#"┌Results 1-3 (3 todl: Run Command, src: Nyaa┐┌Batch────────┐"#,
+            r#"│Cat Nam   Size          Dat             ││C N   Size   │"#,
+            r#"│Sub [Su 724.1 MiB 2024-06-1  874   31 6120 ││A [  98.4 MiB│"#,
+            r#"█AMV [Fa  98.4 MiB 2024-06-0    5    0 77   █│             │"#,
+            r#"└────────────────────────────────────<Space>┘└─────────────┘"#,
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_minimal_layout() {
+    let sync = EventBuilder::new()
+        .mock()
+        .string("/frieren")
+        .enter()
+        .wait(50)
+        .string(" m")
+        .esc()
+        .wait(600)
+        .quit()
+        .build();
+
+    assert_eq!(
+        reset_buffer(&run_app(sync, 60, 8).await.unwrap()),
+        Buffer::with_lines([
+            r#"Search: frieren                                             "#,
+            r#"Results 1-3 (3 totdl: Run Command, src: Nyaa Batch          "#,
+            r#"Cat Nam   Size          Date              Cat N   Size   "#,
+            r#"Sub [Su   1.4 GiB 2024-06-14  2311  104 15K  Sub [   1.4 GiB"#,
+            r#"Sub [Su 724.1 MiB 2024-06-14   874   31 6120                "#,
+            r#"AMV [Fa  98.4 MiB 2024-06-08     5    0 77                  "#,
+            r#"                                                            "#,
+            r#"                                                            "#,
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_combo_popup() {
+    let sync = EventBuilder::new()
+        .mock()
+        .string("/frieren")
+        .enter()
+        .wait(50)
+        .string("y")
+        .wait(600)
+        .quit()
+        .build();
+
+    assert_eq!(
+        reset_buffer(&run_app(sync, 60, 12).await.unwrap()),
+        Buffer::with_lines([
+            r#"┌Search──────────────────────────────Press F1 or ? for help┐"#,
+            r#"│frieren                                                   │"#,
+            r#"└──────────────────────────────────────────────────────────┘"#,
+            r#"┌Results 1-3 (3 total): Page 1/1─dl:┌y────────────────────┐┐"#,
+            r#"│Cat Name          Size          Dat│t Copy torrent link  ││"#,
+            r#"│Sub [SubsPlease   1.4 GiB 2024-06-1│m Copy magnet link   │█"#,
+            r#"│Sub [SubsPlease 724.1 MiB 2024-06-1│p Copy post link     │█"#,
+            r#"│AMV [Fan-Sub] F  98.4 MiB 2024-06-0│i Copy imdb id       │█"#,
+            r#"│                                   │b Copy batch magnets │█"#,
+            r#"│                                   │B Copy batch torrents│█"#,
+            r#"│                                   └─────────────────────┘│"#,
+            r#"└─────────────────────────────────────────────────────────y┘"#,
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_batch_invert() {
+    let sync = EventBuilder::new()
+        .mock()
+        .string("/frieren")
+        .enter()
+        .wait(50)
+        .string(" bi")
+        .quit()
+        .build();
+
+    assert_eq!(
+        reset_buffer(&run_app(sync, 60, 8).await.unwrap()),
+        Buffer::with_lines([
+            r#"┌Search──────────────────────────────Press F1 or ? for help┐"#,
+            r#"│frieren                                                   │"#,
+            r#"└──────────────────────────────────────────────────────────┘"#,
+            r#"┌Results 1-3 (3 todl: Run Command, src: Nyaa┐┌Batch────────┐"#,
+            r#"│Cat Nam   Size          Dat             ││C N   Size   │"#,
+            r#"│Sub [Su   1.4 GiB 2024-06-1 2311  104 15K  █│S [ 724.1 MiB│"#,
+            r#"█Sub [Su 724.1 MiB 2024-06-1  874   31 6120 █│A [  98.4 MiB│"#,
+            r#"└─────────────────────────────────────────bi┘└─────────────┘"#,
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_query_template() {
+    let sync = EventBuilder::new()
+        .mock()
+        .string("/{show} punch {res}")
+        .enter()
+        .string("one")
+        .tab()
+        .wait(50)
+        .quit()
+        .build();
+
+    assert_eq!(
+        reset_buffer(&run_app(sync, 60, 12).await.unwrap()),
+        Buffer::with_lines([
+            r#"┌Search──────────────────────────────Press F1 or ? for help┐"#,
+            r#"│{show} punch {res}                                        │"#,
+            r#"└──────────────────────────────────────────────────────────┘"#,
+            r#"┌Resu┌Query Template──────────────────────────────────┐Nyaa┐"#,
+            r#"│Cat │show > one                                      │   │"#,
+            r#"│Sub │res  >                                          │5K  █"#,
+            r#"│Sub │                                                │120 █"#,
+            r#"│Sub │one punch                                       │301 █"#,
+            r#"│Sub └────────────────────────────────────────────────┘450 │"#,
+            r#"│Sub [ASW] Kaiju 311.9 MiB 2024-06-11 15:30  356    9 4012 │"#,
+            r#"│Raw [Ohys-Raws] 512.3 MiB 2024-06-10 23:59   88    4 901  │"#,
+            r#"└─────────────────────────────────────────────────────<Tab>┘"#,
+        ])
+    );
+
+    let sync = EventBuilder::new()
+        .mock()
+        .string("/{show} punch {res}")
+        .enter()
+        .string("one")
+        .enter()
+        .wait(50)
+        .quit()
+        .build();
+
+    assert_eq!(
+        reset_buffer(&run_app(sync, 60, 8).await.unwrap()),
+        Buffer::with_lines([
+            r#"┌Search──────────────────────────────Press F1 or ? for help┐"#,
+            r#"│{show} punch {res}                                        │"#,
+            r#"└──────────────────────────────────────────────────────────┘"#,
+            r#"┌Results 1-1 (1 total): Page 1/1─dl: Run Command, src: Nyaa┐"#,
+            r#"│Cat Name          Size          Date ▼                 │"#,
+            r#"│Sub [Erai-raws]  16.8 GiB 2024-06-12 09:41  412   56 3301 █"#,
+            r#"│                                                          █"#,
+            r#"└──────────────────────────────────────────────────────<CR>┘"#,
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_browse() {
+    let sync = EventBuilder::new()
+        .mock()
+        .string("/frieren")
+        .enter()
+        .wait(50)
+        .string("T")
+        .wait(50)
+        .esc()
+        .wait(500)
+        .quit()
+        .build();
+
+    assert_eq!(
+        reset_buffer(&run_app(sync, 60, 6).await.unwrap()),
+        Buffer::with_lines([
+            r#"┌Search──────────────────────────────Press F1 or ? for help┐"#,
+            r#"│frieren                                                   │"#,
+            r#"└──────────────────────────────────────────────────────────┘"#,
+            r#"┌Front Page 1-12 (12 total): Pagedl: Run Command, src: Nyaa┐"#,
+            r#"│Cat Name          Size          Date ▼                 │"#,
+            r#"└─────────────────────────────────────────────────────<Esc>┘"#,
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_batch_all_pages() {
+    let sync = EventBuilder::new()
+        .mock()
+        .string("/frieren")
+        .enter()
+        .wait(50)
+        .string("bA")
+        .wait(50)
+        // Dismiss notifications so they don't cover the batch
+        .esc()
+        .wait(500)
+        .quit()
+        .build();
+
+    assert_eq!(
+        reset_buffer(&run_app(sync, 60, 8).await.unwrap()),
+        Buffer::with_lines([
+            r#"┌Search──────────────────────────────Press F1 or ? for help┐"#,
+            r#"│frieren                                                   │"#,
+            r#"└──────────────────────────────────────────────────────────┘"#,
+            r#"┌Results 1-3 (3 todl: Run Command, src: Nyaa┐┌Batch────────┐"#,
+            r#"│Cat Nam   Size          Dat             ││C N   Size   │"#,
+            r#"█Sub [Su   1.4 GiB 2024-06-1 2311  104 15K  █│S [   1.4 GiB│"#,
+            r#"█Sub [Su 724.1 MiB 2024-06-1  874   31 6120 █│S [ 724.1 MiB│"#,
+            r#"└──────────────────────────────────────<Esc>┘└─────────────┘"#,
+        ])
+    );
+}
+
+#[test]
+fn test_mark_new() {
+    let search = SearchQuery {
+        query: "frieren".to_owned(),
+        ..Default::default()
+    };
+    let results = |items: Vec<_>| {
+        let response = ResultResponse {
+            items,
+            ..Default::default()
+        };
+        Results::new(search.clone(), response, ResultTable::default())
+    };
+    let items = MockSource::items(&search);
+    let prev = results(items[1..].to_vec());
+    let mut next = results(items.clone());
+    next.mark_new(&prev);
+    assert_eq!(next.new.len(), 1);
+    assert!(next.new.contains(&items[0].id));
+
+    // Only compared against the same search
+    let mut other = results(items.clone());
+    other.search.page = 2;
+    other.mark_new(&prev);
+    assert!(other.new.is_empty());
+}
+
+#[test]
+fn test_deep_pages() {
+    let search = SearchQuery {
+        query: "frieren".to_owned(),
+        ..Default::default()
+    };
+    let items = MockSource::items(&search);
+    let titles = |items: &[Item]| items.iter().map(|i| i.title.clone()).collect::<Vec<_>>();
+    let response = ResultResponse {
+        items: items.clone(),
+        last_page: 2,
+        ..Default::default()
+    };
+    let mut results = Results::new(search, response, ResultTable::default());
+    assert!(!results.clone().show_page(2));
+
+    results.paginate();
+    assert_eq!(titles(&results.response.items), titles(&items[..2]));
+    assert_eq!(results.all_items().len(), 3);
+
+    let mut ctx = Context::default();
+    ctx.mode = Mode::Normal;
+    ctx.results = results;
+    ctx.goto_page(2);
+    assert!(ctx.mode == Mode::Normal);
+    assert_eq!(ctx.page, 2);
+    assert_eq!(titles(&ctx.results.response.items), titles(&items[2..]));
+    assert_eq!(ctx.results.table.rows.len(), 1);
+
+    // Other results load the page
+    ctx.results = Results::default();
+    ctx.goto_page(1);
+    assert!(ctx.mode == Mode::Loading(LoadType::Searching));
+}
+
+#[test]
+fn test_window_title() {
+    let mut ctx = Context::default();
+    assert_eq!(ctx.window_title(), "nyaa");
+
+    ctx.searched = true;
+    ctx.mode = Mode::Normal;
+    ctx.page = 2;
+    ctx.results.search.query = "frieren".to_owned();
+    assert_eq!(ctx.window_title(), "nyaa — 'frieren' p2");
+
+    ctx.mode = Mode::Batch;
+    assert_eq!(ctx.window_title(), "nyaa — 'frieren' p2 [Batch]");
+
+    ctx.mode = Mode::Normal;
+    ctx.browsing = true;
+    assert_eq!(ctx.window_title(), "nyaa — front page p2");
+}
+
+#[test]
+fn test_reselect() {
+    let search = SearchQuery {
+        query: "frieren".to_owned(),
+        ..Default::default()
+    };
+    let results = |search: &SearchQuery, items: Vec<_>| {
+        let response = ResultResponse {
+            items,
+            ..Default::default()
+        };
+        Results::new(search.clone(), response, ResultTable::default())
+    };
+    let items = MockSource::items(&search);
+    let prev = results(&search, items.clone());
+
+    // Resorting keeps the same item selected
+    let resorted = SearchQuery {
+        sort: SelectedSort {
+            sort: 2,
+            dir: SortDir::Asc,
+        },
+        ..search.clone()
+    };
+    assert!(resorted.same_results(&search));
+    let mut reversed = items.clone();
+    reversed.reverse();
+    let next = results(&resorted, reversed);
+    let mut widget = ResultsWidget::default();
+    widget.table.select(0);
+    widget.reselect(&prev, &next);
+    assert_eq!(widget.table.selected(), Some(2));
+
+    // Falls back to the nearest row once the item is gone
+    widget.table.select(2);
+    widget.reselect(&prev, &results(&search, items[..1].to_vec()));
+    assert_eq!(widget.table.selected(), Some(0));
+
+    let next_page = SearchQuery {
+        page: 2,
+        ..search.clone()
+    };
+    assert!(!next_page.same_results(&search));
+}
+
+#[test]
+fn test_mark_dead() {
+    let search = SearchQuery {
+        query: "frieren".to_owned(),
+        ..Default::default()
+    };
+    let items = MockSource::items(&search);
+    let response = ResultResponse {
+        items: items[1..].to_vec(),
+        ..Default::default()
+    };
+    let mut ctx = Context::default();
+    ctx.results = Results::new(search, response, ResultTable::default());
+
+    // Links from results no longer shown are ignored
+    ctx.results
+        .mark_dead(vec![items[0].id.clone(), items[1].id.clone()]);
+    assert_eq!(ctx.results.dead.len(), 1);
+    assert!(ctx.results.dead.contains(&items[1].id));
+
+    // Dead links are kept out of the batch
+    assert_eq!(ctx.add_to_batch(items.clone()), 2);
+    assert!(!ctx.batch.iter().any(|i| i.id == items[1].id));
+}
+
+#[tokio::test]
+async fn test_batch_undo() {
+    let sync = EventBuilder::new()
+        .mock()
+        .string("/frieren")
+        .enter()
+        .wait(50)
+        .string(" j bcU")
+        // Dismiss notifications so they don't cover the batch
+        .esc()
+        .wait(500)
+        .quit()
+        .build();
+
+    assert_eq!(
+        reset_buffer(&run_app(sync, 60, 8).await.unwrap()),
+        Buffer::with_lines([
+            r#"┌Search──────────────────────────────Press F1 or ? for help┐"#,
+            r#"│frieren                                                   │"#,
+            r#"└──────────────────────────────────────────────────────────┘"#,
+            r#"┌Results 1-3 (3 todl: Run Command, src: Nyaa┐┌Batch────────┐"#,
+            r#"│Cat Nam   Size          Dat             ││C N   Size   │"#,
+            r#"█Sub [Su   1.4 GiB 2024-06-1 2311  104 15K  ││S [   1.4 GiB│"#,
+            r#"█Sub [Su 724.1 MiB 2024-06-1  874   31 6120 █│S [ 724.1 MiB│"#,
+            r#"└──────────────────────────────────────<Esc>┘└─────────────┘"#,
+        ])
+    );
+}
+
+#[test]
+fn test_find() {
+    let search = SearchQuery::default();
+    let response = ResultResponse {
+        items: MockSource::items(&search),
+        ..Default::default()
+    };
+    let results = Results::new(search, response, ResultTable::default());
+
+    assert_eq!(results.find("one punch", 0, true), Some(2));
+    // Letters only have to appear in order
+    assert_eq!(results.find("dngnmeshi", 0, true), Some(3));
+    assert_eq!(results.find("FRN720", 0, true), Some(1));
+    // Searching wraps around, both forwards and backwards
+    assert_eq!(results.find("frieren", 2, true), Some(7));
+    assert_eq!(results.find("frieren", 8, true), Some(0));
+    assert_eq!(results.find("frieren", 6, false), Some(1));
+    assert_eq!(results.find("amv anytime", 6, false), Some(7));
+    assert_eq!(results.find("nothing like this", 0, true), None);
+    assert_eq!(results.find("  ", 0, true), None);
+}
+
+#[tokio::test]
+async fn test_find_next() {
+    let sync = EventBuilder::new()
+        .mock()
+        .string("/frieren")
+        .enter()
+        .wait(50)
+        .key_mod(KeyCode::Char('f'), KeyModifiers::CONTROL)
+        .string("720")
+        .enter()
+        .string("nn")
+        .quit()
+        .build();
+
+    assert_eq!(
+        reset_buffer(&run_app(sync, 60, 8).await.unwrap()),
+        Buffer::with_lines([
+            r#"┌Search──────────────────────────────Press F1 or ? for help┐"#,
+            r#"│frieren                                                   │"#,
+            r#"└──────────────────────────────────────────────────────────┘"#,
+            r#"┌Results 1-3 (3 total): Page 1/1─dl: Run Command, src: Nyaa┐"#,
+            r#"│Cat Name          Size          Date ▼                 │"#,
+            r#"│Sub [SubsPlease   1.4 GiB 2024-06-14 18:02 2311  104 15K  │"#,
+            r#"│Sub [SubsPlease 724.1 MiB 2024-06-14 18:01  874   31 6120 █"#,
+            r#"└find: 720────────────────────────────────────────────────n┘"#,
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_related_search() {
+    let related = || {
+        let mut events = EventBuilder::new();
+        events
+            .mock()
+            .string("/frieren")
+            .enter()
+            .wait(50)
+            .string("Rs")
+            .wait(50);
+        events
+    };
+
+    assert_eq!(
+        reset_buffer(&run_app(related().quit().build(), 60, 8).await.unwrap()),
+        Buffer::with_lines([
+            r#"┌Search──────────────────────────────Press F1 or ? for help┐"#,
+            r#"│Sousou no Frieren                                         │"#,
+            r#"└──────────────────────────────────────────────────────────┘"#,
+            r#"┌Same show 1-2 (2 total): Page 1/dl: Run Command, src: Nyaa┐"#,
+            r#"│Cat Name          Size          Date ▼                 │"#,
+            r#"│Sub [SubsPlease   1.4 GiB 2024-06-14 18:02 2311  104 15K  █"#,
+            r#"│Sub [SubsPlease 724.1 MiB 2024-06-14 18:01  874   31 6120 █"#,
+            r#"└────────────────────────────────────────────────────────Rs┘"#,
+        ])
+    );
+
+    // Back to the first search, without loading it again
+    let sync = related().key(KeyCode::Backspace).quit().build();
+    assert_eq!(
+        reset_buffer(&run_app(sync, 60, 8).await.unwrap()),
+        Buffer::with_lines([
+            r#"┌Search──────────────────────────────Press F1 or ? for help┐"#,
+            r#"│frieren                                                   │"#,
+            r#"└──────────────────────────────────────────────────────────┘"#,
+            r#"┌Results 1-3 (3 total): Page 1/1─dl: Run Command, src: Nyaa┐"#,
+            r#"│Cat Name          Size          Date ▼                 │"#,
+            r#"│Sub [SubsPlease   1.4 GiB 2024-06-14 18:02 2311  104 15K  █"#,
+            r#"│Sub [SubsPlease 724.1 MiB 2024-06-14 18:01  874   31 6120 █"#,
+            r#"└──────────────────────────────────────────────────────<BS>┘"#,
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_history() {
+    let searches = || {
+        let mut events = EventBuilder::new();
+        events
+            .mock()
+            .string("/frieren")
+            .enter()
+            .wait(50)
+            .string("j/");
+        for _ in 0.."frieren".len() {
+            events.key(KeyCode::Backspace);
+        }
+        events.string("one punch").enter().wait(50);
+        events
+    };
+
+    // Back to the first search, on the row it was left on
+    let sync = searches()
+        .key_mod(KeyCode::Char('o'), KeyModifiers::CONTROL)
+        .quit()
+        .build();
+    assert_eq!(
+        reset_buffer(&run_app(sync, 60, 8).await.unwrap()),
+        Buffer::with_lines([
+            r#"┌Search──────────────────────────────Press F1 or ? for help┐"#,
+            r#"│frieren                                                   │"#,
+            r#"└──────────────────────────────────────────────────────────┘"#,
+            r#"┌Results 1-3 (3 total): Page 1/1─dl: Run Command, src: Nyaa┐"#,
+            r#"│Cat Name          Size          Date ▼                 │"#,
+            r#"│Sub [SubsPlease   1.4 GiB 2024-06-14 18:02 2311  104 15K  │"#,
+            r#"│Sub [SubsPlease 724.1 MiB 2024-06-14 18:01  874   31 6120 █"#,
+            r#"└─────────────────────────────────────────────────────<C-o>┘"#,
+        ])
+    );
+
+    let sync = searches()
+        .key_mod(KeyCode::Char('o'), KeyModifiers::CONTROL)
+        .key_mod(KeyCode::Char('n'), KeyModifiers::CONTROL)
+        .quit()
+        .build();
+    assert_eq!(
+        reset_buffer(&run_app(sync, 60, 8).await.unwrap()),
+        Buffer::with_lines([
+            r#"┌Search──────────────────────────────Press F1 or ? for help┐"#,
+            r#"│one punch                                                 │"#,
+            r#"└──────────────────────────────────────────────────────────┘"#,
+            r#"┌Results 1-1 (1 total): Page 1/1─dl: Run Command, src: Nyaa┐"#,
+            r#"│Cat Name          Size          Date ▼                 │"#,
+            r#"│Sub [Erai-raws]  16.8 GiB 2024-06-12 09:41  412   56 3301 █"#,
+            r#"│                                                          █"#,
+            r#"└─────────────────────────────────────────────────────<C-n>┘"#,
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_quit_with_downloads() {
+    let sync = EventBuilder::new()
+        .mock()
+        .string("/one punch")
+        .enter()
+        .wait(50)
+        .enter()
+        .wait(50)
+        .string("q")
+        .esc()
+        .string("q")
+        .wait(50)
+        .string("c")
+        .wait(50)
+        // Quit already, so the help isn't opened
+        .string("?")
+        .quit()
+        .build();
+
+    assert_eq!(
+        reset_buffer(&run_app(sync, 70, 16).await.unwrap()),
+        Buffer::with_lines([
+            r#"┌Search────────────────────────┌────────────────────────────────────┐┐"#,
+            r#"│one punch                     │Downloading torrent with Run Command││"#,
+            r#"└──────────────────────────────└────────────────────────────────────┘┘"#,
+            r#"┌Results 1-1 (1 total): Page 1/1───────────dl: Run Command, src: Nyaa┐"#,
+            r#"│Cat ┌Quit──────────────────────────────────────────────────────┐   │"#,
+            r#"│Sub │Quit with 1 downloads still running?                      │301 █"#,
+            r#"│    │                                                          │    █"#,
+            r#"│    │ Sending torrents to Run Command                          │    █"#,
+            r#"│    │                                                          │    █"#,
+            r#"│    │Enter: wait for them, c: cancel them, Esc: back           │    █"#,
+            r#"│    └──────────────────────────────────────────────────────────┘    █"#,
+            r#"│                                                                    █"#,
+            r#"│                                                                    █"#,
+            r#"│                                                                    █"#,
+            r#"│                                                                    █"#,
+            r#"└───────────────────────────────────────────────────────────────────q┘"#,
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_focus_click() {
+    let sync = EventBuilder::new()
+        .mock()
+        .string("/frieren")
+        .enter()
+        .wait(50)
+        .string(" ")
+        // The batch widens once it's focused
+        .click(55, 5)
+        .string("j")
+        .quit()
+        .build();
+
+    assert_eq!(
+        reset_buffer(&run_app(sync, 60, 8).await.unwrap()),
+        Buffer::with_lines([
+            r#"┌Search──────────────────────────────Press F1 or ? for help┐"#,
+            r#"│frieren                                                   │"#,
+            r#"└──────────────────────────────────────────────────────────┘"#,
+            r#"┌Redl: Run Command, src: Nyaa┐┌Batch───────Size(1): 1.4 GiB┐"#,
+            r#"│Cat Nam   S              ││Cat Name             Size   │"#,
+            r#"█Sub [Su 1.4 202 2311 104 15K█│Sub [SubsPlease] S   1.4 GiB│"#,
+            r#"│Sub [Su 724 202  874  31 612█│                            │"#,
+            r#"└───────────────────────────j┘└────────────────────────────┘"#,
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_queued_keys() {
+    // Keys queued up are handled before drawing again, and a click still
+    // lands on the pane drawn after them
+    let sync = EventBuilder::new()
+        .mock()
+        .string("/frieren")
+        .enter()
+        .wait(50)
+        .string("jjk ")
+        .click(5, 5)
+        .string("G")
+        .quit()
+        .build();
+
+    assert_eq!(
+        reset_buffer(&run_app(sync, 60, 8).await.unwrap()),
+        Buffer::with_lines([
+            r#"┌Search──────────────────────────────Press F1 or ? for help┐"#,
+            r#"│frieren                                                   │"#,
+            r#"└──────────────────────────────────────────────────────────┘"#,
+            r#"┌Results 1-3 (3 todl: Run Command, src: Nyaa┐┌Batch────────┐"#,
+            r#"│Cat Nam   Size          Dat             ││C N   Size   │"#,
+            r#"█Sub [Su 724.1 MiB 2024-06-1  874   31 6120 ││S [ 724.1 MiB│"#,
+            r#"│AMV [Fa  98.4 MiB 2024-06-0    5    0 77   █│             │"#,
+            r#"└──────────────────────────────────────────G┘└─────────────┘"#,
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_held_motion() {
+    // A held key is moved through at once, still stopping at the last row and
+    // batching each row crossed in visual mode
+    let sync = EventBuilder::new()
+        .mock()
+        .string("/frieren")
+        .enter()
+        .wait(50)
+        .key_mod(KeyCode::Char(' '), KeyModifiers::CONTROL)
+        .string("jjjjjjk")
+        .quit()
+        .build();
+
+    assert_eq!(
+        reset_buffer(&run_app(sync, 60, 8).await.unwrap()),
+        Buffer::with_lines([
+            r#"┌Search──────────────────────────────P│Entered VISUAL mode│┐"#,
+            r#"│frieren                              └───────────────────┘│"#,
+            r#"└──────────────────────────────────────────────────────────┘"#,
+            r#"┌Results 1-3 (3 todl: Run Command, src: Nyaa┐┌Batch────────┐"#,
+            r#"│Cat Nam   Size          Dat             ││C N   Size   │"#,
+            r#"█Sub [Su   1.4 GiB 2024-06-1 2311  104 15K  ││S [   1.4 GiB│"#,
+            r#"█Sub [Su 724.1 MiB 2024-06-1  874   31 6120 █│S [ 724.1 MiB│"#,
+            r#"└──────────────────────────────────────────k┘└─────────────┘"#,
+        ])
+    );
+}
+
+/// Waits in the search box after changing sources, with Sukebei's default
+/// search typed in
+struct NoSourceSearchConfig;
+
+impl ConfigManager for NoSourceSearchConfig {
+    fn load() -> Result<Config, Box<dyn Error>> {
+        let mut config = TestConfig::load()?;
+        config.search_on_source_change = false;
+        let mut sukebei = SukebeiNyaaConfig::default();
+        sukebei.0.default_search = "frieren".to_owned();
+        config.sources.sukebei = Some(sukebei);
+        Ok(config)
+    }
+
+    fn store(_cfg: &Config) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    fn path() -> Result<PathBuf, Box<dyn Error>> {
+        TestConfig::path()
+    }
+}
+
+#[tokio::test]
+async fn test_source_default_search() {
+    let switch = || {
+        let mut events = EventBuilder::new();
+        events
+            .mock()
+            .string("/one punch")
+            .enter()
+            .wait(50)
+            .key_mod(KeyCode::Char('s'), KeyModifiers::CONTROL)
+            .string("j")
+            .enter()
+            .wait(50);
+        events
+    };
+
+    // Nothing is searched until Enter, and the results of Nyaa are gone
+    let sync = switch().quit().build();
+    assert_eq!(
+        reset_buffer(
+            &run_app_with::<_, NoSourceSearchConfig>(sync, 60, 8)
+                .await
+                .unwrap()
+        ),
+        Buffer::with_lines([
+            r#"┌Search───────────────────────┌───────────────────────────┐┐"#,
+            r#"│frieren                      │Updated source to "Sukebei"││"#,
+            r#"└─────────────────────────────└───────────────────────────┘┘"#,
+            r#"┌Results───────────────────────────────────────────────────┐"#,
+            r#"│ Recent searches                                          │"#,
+            r#"│   one punch                                              │"#,
+            r#"│                                                          │"#,
+            r#"└──────────────────────────────────────────────────────────┘"#,
+        ])
+    );
+
+    let sync = switch().enter().wait(50).quit().build();
+    assert_eq!(
+        reset_buffer(
+            &run_app_with::<_, NoSourceSearchConfig>(sync, 60, 8)
+                .await
+                .unwrap()
+        ),
+        Buffer::with_lines([
+            r#"┌Search──────────────────────────────Press F1 or ? for help┐"#,
+            r#"│frieren                                                   │"#,
+            r#"└─────────────────────────────┌───────────────────────────┐┘"#,
+            r#"┌Results 1-3 (3 total): Page 1│Updated source to "Sukebei"│┐"#,
+            r#"│Cat Name          Size       └───────────────────────────┘│"#,
+            r#"│Sub [SubsPlease   1.4 GiB 2024-06-14 18:02 2311  104 15K  █"#,
+            r#"│Sub [SubsPlease 724.1 MiB 2024-06-14 18:01  874   31 6120 █"#,
+            r#"└──────────────────────────────────────────────────────<CR>┘"#,
+        ])
+    );
+}
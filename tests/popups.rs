@@ -1,7 +1,10 @@
+use std::{error::Error, path::PathBuf, sync::Mutex};
+
 use crossterm::event::{KeyCode, KeyModifiers};
+use nyaa::config::{state::State, Config, ConfigManager};
 use ratatui::buffer::Buffer;
 
-use crate::common::{reset_buffer, run_app, EventBuilder};
+use crate::common::{reset_buffer, run_app, run_app_with, EventBuilder, TestConfig};
 
 #[allow(dead_code)]
 mod common;
@@ -67,22 +70,22 @@ async fn test_filters() {
             r#"└──────────────────────────────────────────────────────────┘"#,
             r#"┌Results 1-0 (0 total): Page 1/0─dl: Run Command, src: Nyaa┐"#,
             r#"│                                                          │"#,
-            r#"│                                                          │"#,
-            r#"│                                                          │"#,
-            r#"│                                                          │"#,
             r#"│              ┌Filter──────────────────────┐              │"#,
             r#"│              │   No Filter                │              │"#,
             r#"│              │   No Remakes               │              │"#,
             r#"│              │  Trusted Only             │              │"#,
             r#"│              │   Batches                  │              │"#,
+            r#"│              │ [ ] 1080p only             │              │"#,
+            r#"│              │ [ ] HEVC only              │              │"#,
+            r#"│              │ [ ] Batches only           │              │"#,
+            r#"│              │ [ ] Hide remakes           │              │"#,
+            r#"│              │ [ ] Hide untrusted         │              │"#,
+            r#"│              │ [ ] Starred only           │              │"#,
             r#"│              └────────────────────────────┘              │"#,
             r#"│                                                          │"#,
             r#"│                                                          │"#,
             r#"│                                                          │"#,
             r#"│                                                          │"#,
-            r#"│                                                          │"#,
-            r#"│                                                          │"#,
-            r#"│                                                          │"#,
             r#"└─────────────────────────────────────────────────────────f┘"#,
         ])
     );
@@ -184,12 +187,13 @@ async fn test_themes() {
             r#"╭Results 1-0 (0 total): Page 1/0─dl: Run Command, src: Nyaa╮"#,
             r#"│Cat Name                    Size     Date              │"#,
             r#"│                                                          │"#,
-            r#"│                                                          │"#,
             r#"│              ╭Theme───────────────────────╮              │"#,
             r#"│              │   Default                  │              │"#,
             r#"│              │   Dracula                  │              │"#,
             r#"│              │   Gruvbox                  │              │"#,
             r#"│              │  Catppuccin Macchiato     │              │"#,
+            r#"│              │   High Contrast            │              │"#,
+            r#"│              │   Monochrome               │              │"#,
             r#"│              │   My Custom Theme          │              │"#,
             r#"│              ╰────────────────────────────╯              │"#,
             r#"│                                                          │"#,
@@ -198,16 +202,19 @@ async fn test_themes() {
             r#"│                                                          │"#,
             r#"│                                                          │"#,
             r#"│                                                          │"#,
-            r#"│                                                          │"#,
             r#"╰──────────────────────────────────────────────────────<CR>╯"#,
         ])
     );
 }
 
+/// Connections are tested by `TestSync`, which fails every API client at
+/// once without connecting, so each shows as failing
 #[tokio::test]
 async fn test_download_client() {
     let sync = EventBuilder::new()
         .string('d')
+        // Let the tests report back before the next key
+        .wait(50)
         .string("jjj")
         .enter()
         .string('d')
@@ -224,14 +231,14 @@ async fn test_download_client() {
             r#"│                                                          │"#,
             r#"│                                                          │"#,
             r#"│                                                          │"#,
-            r#"│              ┌Download Client─────────────┐              │"#,
-            r#"│              │   qBittorrent              │              │"#,
-            r#"│              │   Transmission             │              │"#,
-            r#"│              │   rqbit                    │              │"#,
-            r#"│              │  Default App              │              │"#,
-            r#"│              │   Download Torrent File    │              │"#,
-            r#"│              │   Run Command              │              │"#,
-            r#"│              └────────────────────────────┘              │"#,
+            r#"┌Download Client───────────────────────────────────────────┐"#,
+            r#"│   qBittorrent           failing  Failed to connect to qBi│"#,
+            r#"│   Transmission          failing  Failed to connect to Tra│"#,
+            r#"│   rqbit                 failing  Failed to connect to rqb│"#,
+            r#"│  Default App                                            │"#,
+            r#"│   Download Torrent File                                  │"#,
+            r#"│   Run Command                                            │"#,
+            r#"└──────────────────────────────────────────────────────────┘"#,
             r#"│                                                          │"#,
             r#"│                                                          │"#,
             r#"│                                                          │"#,
@@ -262,54 +269,202 @@ async fn test_source() {
             r#"┌Results 1-0 (0 total): Page 1dl: Run Command, src: Sukebei┐"#,
             r#"│                                                          │"#,
             r#"│                                                          │"#,
+            r#"┌Source────────────────────────────────────────────────────┐"#,
+            r#"│                   Host             Mode Category   Status│"#,
+            r#"│   Nyaa            nyaa.si          HTML All Catego -     │"#,
+            r#"│  Sukebei         sukebei.nyaa.si  HTML All Catego -     │"#,
+            r#"│   TorrentGalaxy   torrentgalaxy.to HTML All Catego -     │"#,
+            r#"│   EZTV            eztvx.to         API  All TV     -     │"#,
+            r#"│   InternetArchive archive.org      API  All Catego -     │"#,
+            r#"│   Airing          nyaa.si          HTML English Tr -     │"#,
+            r#"└──────────────────────────────────────────────────────────┘"#,
             r#"│                                                          │"#,
             r#"│                                                          │"#,
-            r#"│              ┌Source──────────────────────┐              │"#,
-            r#"│              │   Nyaa                     │              │"#,
-            r#"│              │  Sukebei                  │              │"#,
-            r#"│              │   TorrentGalaxy            │              │"#,
-            r#"│              └────────────────────────────┘              │"#,
             r#"│                                                          │"#,
             r#"│                                                          │"#,
             r#"│                                                          │"#,
             r#"│                                                          │"#,
+            r#"└─────────────────────────────────────────────────────<C-s>┘"#,
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_user() {
+    let sync = EventBuilder::new()
+        .string('u')
+        .string("[subsplease] reallylongnamethatshouldcutoff")
+        .enter()
+        .string('u')
+        .quit()
+        .build();
+
+    assert_eq!(
+        reset_buffer(&run_app(sync, 60, 15).await.unwrap()),
+        Buffer::with_lines([
+            r#"┌Search──────────────────────────────Press F1 or ? for help┐"#,
+            r#"│                                                          │"#,
+            r#"└ [subsplease] reallylongnam ✕ ────────────────────────────┘"#,
+            r#"┌Results 1-0 (0 total): Page 1/0─dl: Run Command, src: Nyaa┐"#,
+            r#"│         ┌Search Constraints────────────────────┐         │"#,
+            r#"│         │User       > …bsplease] reallylongnam │         │"#,
+            r#"│         │Category   ◀ All Categories ▶         │         │"#,
+            r#"│         │Filter     ◀ No Filter ▶              │         │"#,
+            r#"│         │Save as    >                          │         │"#,
+            r#"│         └──────────────────────────────────────┘         │"#,
+            r#"│                                                          │"#,
+            r#"│                                                          │"#,
+            r#"│                                                          │"#,
             r#"│                                                          │"#,
+            r#"└─────────────────────────────────────────────────────────u┘"#,
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_user_preset() {
+    let sync = EventBuilder::new()
+        .string('u')
+        .string("subsplease")
+        .key(KeyCode::Down)
+        .wait(50)
+        .key(KeyCode::Right)
+        .key(KeyCode::Right)
+        .key(KeyCode::Down)
+        .key(KeyCode::Down)
+        .string("Subs")
+        .key_mod(KeyCode::Char('s'), KeyModifiers::CONTROL)
+        .esc()
+        // Reopen, then pick the preset at the bottom
+        .string('u')
+        .key(KeyCode::Up)
+        .enter()
+        .string('u')
+        .quit()
+        .build();
+
+    assert_eq!(
+        reset_buffer(&run_app(sync, 60, 15).await.unwrap()),
+        Buffer::with_lines([
+            r#"┌Search──────────────────────────────P│Saved preset "Subs"│┐"#,
+            r#"│                                     └───────────────────┘│"#,
+            r#"└ subsplease ✕ ────────────────────────────────────────────┘"#,
+            r#"┌Results 1┌Search Constraints────────────────────┐src: Nyaa┐"#,
+            r#"│         │User       > subsplease               │         │"#,
+            r#"│         │Category   ◀ English Translated ▶     │         │"#,
+            r#"│         │Filter     ◀ No Filter ▶              │         │"#,
+            r#"│         │Save as    > Subs                     │         │"#,
+            r#"│         │Presets                               │         │"#,
+            r#"│         │           Subs                       │         │"#,
+            r#"│         └User found (3)────────────────────────┘         │"#,
             r#"│                                                          │"#,
             r#"│                                                          │"#,
             r#"│                                                          │"#,
-            r#"└─────────────────────────────────────────────────────<C-s>┘"#,
+            r#"└─────────────────────────────────────────────────────────u┘"#,
         ])
     );
 }
 
 #[tokio::test]
-async fn test_user() {
+async fn test_user_recent() {
     let sync = EventBuilder::new()
         .string('u')
-        .string("[subsplease] reallylongnamethatshouldcutoff")
+        .string("nobody")
+        .enter()
+        .wait(50)
+        .string('u')
+        .key_mod(KeyCode::Char('u'), KeyModifiers::CONTROL)
+        .string("quiet")
         .enter()
+        .wait(50)
+        // Both are listed, latest first, and picking one checks it
         .string('u')
+        .key_mod(KeyCode::Char('u'), KeyModifiers::CONTROL)
+        .key(KeyCode::Down)
+        .key(KeyCode::Down)
         .quit()
         .build();
 
     assert_eq!(
         reset_buffer(&run_app(sync, 60, 15).await.unwrap()),
         Buffer::with_lines([
-            "┌Search──────────────────────────────Press F1 or ? for help┐",
-            "│                                                          │",
-            "└──────────────────────────────────────────────────────────┘",
-            "┌Results 1-0 (0 total): Page 1/0─dl: Run Command, src: Nyaa┐",
-            "│                                                          │",
-            "│                                                          │",
-            "│              ┌Posts by User───────────────┐              │",
-            "│              │> [subsplease] reallylongnam│              │",
-            "│              └────────────────────────────┘              │",
-            "│                                                          │",
-            "│                                                          │",
-            "│                                                          │",
-            "│                                                          │",
-            "│                                                          │",
-            "└─────────────────────────────────────────────────────────u┘",
+            r#"┌Search──────────────────────────────Press F1 or ? for help┐"#,
+            r#"│                                                          │"#,
+            r#"└ quiet ✕ ─────────────────────────────────────────────────┘"#,
+            r#"┌Results 1-0 (0 total): Page 1/0─dl: Run Command, src: Nyaa┐"#,
+            r#"│         ┌Search Constraints────────────────────┐         │"#,
+            r#"│         │User       >                          │         │"#,
+            r#"│         │Category   ◀┌Recent───────────────────┐         │"#,
+            r#"│         │Filter     ◀│quiet                    │         │"#,
+            r#"│         │Save as    >│nobody                   │         │"#,
+            r#"│         └────────────└─────────────────────────┘         │"#,
+            r#"│                                                          │"#,
+            r#"│                                                          │"#,
+            r#"│                                                          │"#,
+            r#"│                                                          │"#,
+            r#"└────────────────────────────────────────────────────<Down>┘"#,
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_user_check() {
+    let sync = EventBuilder::new()
+        .string('u')
+        .string("nobody")
+        .enter()
+        .wait(50)
+        .string('u')
+        .key_mod(KeyCode::Char('u'), KeyModifiers::CONTROL)
+        .key(KeyCode::Down)
+        .enter()
+        .wait(50)
+        .quit()
+        .build();
+
+    assert_eq!(
+        reset_buffer(&run_app(sync, 60, 15).await.unwrap()),
+        Buffer::with_lines([
+            r#"┌Search──────────────────────────────Press F1 or ? for help┐"#,
+            r#"│                                                          │"#,
+            r#"└ nobody ✕ ────────────────────────────────────────────────┘"#,
+            r#"┌Results 1-0 (0 total): Page 1/0─dl: Run Command, src: Nyaa┐"#,
+            r#"│         ┌Search Constraints────────────────────┐         │"#,
+            r#"│         │User       > nobody                   │         │"#,
+            r#"│         │Category   ◀ All Categories ▶         │         │"#,
+            r#"│         │Filter     ◀ No Filter ▶              │         │"#,
+            r#"│         │Save as    >                          │         │"#,
+            r#"│         └No such user──────────────────────────┘         │"#,
+            r#"│                                                          │"#,
+            r#"│                                                          │"#,
+            r#"│                                                          │"#,
+            r#"│                                                          │"#,
+            r#"└──────────────────────────────────────────────────────<CR>┘"#,
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_user_chip() {
+    let sync = EventBuilder::new()
+        .string('u')
+        .string("subsplease")
+        .enter()
+        .wait(50)
+        .string('/')
+        .key(KeyCode::Backspace)
+        .quit()
+        .build();
+
+    assert_eq!(
+        reset_buffer(&run_app(sync, 60, 6).await.unwrap()),
+        Buffer::with_lines([
+            r#"┌Search──────────────────────────────P│Searching all users│┐"#,
+            r#"│                                     └───────────────────┘│"#,
+            r#"└──────────────────────────────────────────────────────────┘"#,
+            r#"┌Results 1-0 (0 total): Page 1/0─dl: Run Command, src: Nyaa┐"#,
+            r#"│                        No results                        │"#,
+            r#"└──────────────────────────────────────────────────────<BS>┘"#,
         ])
     );
 }
@@ -372,3 +527,69 @@ async fn test_page() {
         ])
     );
 }
+
+static WHATS_NEW_STATE: Mutex<Option<State>> = Mutex::new(None);
+
+/// Never saves the config, but keeps the state between runs, starting from
+/// an older version
+struct NoSaveConfig;
+
+impl ConfigManager for NoSaveConfig {
+    fn load() -> Result<Config, Box<dyn Error>> {
+        let mut config = TestConfig::load()?;
+        config.save_config_on_change = false;
+        Ok(config)
+    }
+
+    fn store(_cfg: &Config) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    fn load_state() -> Result<State, Box<dyn Error>> {
+        Ok(WHATS_NEW_STATE
+            .lock()
+            .unwrap()
+            .clone()
+            .unwrap_or_else(|| State {
+                last_version: Some("0.8.1".to_owned()),
+                ..Default::default()
+            }))
+    }
+
+    fn store_state(state: &State) -> Result<(), Box<dyn Error>> {
+        *WHATS_NEW_STATE.lock().unwrap() = Some(state.clone());
+        Ok(())
+    }
+
+    fn path() -> Result<PathBuf, Box<dyn Error>> {
+        TestConfig::path()
+    }
+}
+
+#[tokio::test]
+async fn test_whats_new_once() {
+    let shows_whats_new = |buf: Buffer| {
+        buf.content()
+            .iter()
+            .map(|c| c.symbol())
+            .collect::<String>()
+            .contains("What's New")
+    };
+    let run = || async {
+        let sync = EventBuilder::new().mock().wait(50).quit().build();
+        reset_buffer(&run_app_with::<_, NoSaveConfig>(sync, 80, 24).await.unwrap())
+    };
+
+    assert!(shows_whats_new(run().await));
+    // The version was kept even though the config isn't saved on change
+    assert_eq!(
+        WHATS_NEW_STATE
+            .lock()
+            .unwrap()
+            .clone()
+            .unwrap()
+            .last_version,
+        Some(env!("CARGO_PKG_VERSION").to_owned())
+    );
+    assert!(!shows_whats_new(run().await));
+}
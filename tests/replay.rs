@@ -0,0 +1,104 @@
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use nyaa::sync::replay::{RecordedEvent, Recorder, Session};
+use ratatui::buffer::Buffer;
+
+use crate::common::{reset_buffer, run_app, EventBuilder};
+
+#[allow(dead_code)]
+mod common;
+
+#[test]
+fn test_record() {
+    let path = std::env::temp_dir().join("nyaa-test-record.toml");
+    let events = [
+        Event::Resize(80, 24),
+        Event::Key(KeyEvent::new(KeyCode::Char('/'), KeyModifiers::NONE)),
+        Event::Paste("one punch man".to_owned()),
+        Event::Key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)),
+        Event::FocusLost,
+    ];
+
+    let mut rec = Recorder::new(&path).unwrap();
+    for e in events.iter() {
+        rec.record(e).unwrap();
+    }
+
+    let session = Session::load(&path).unwrap();
+    assert_eq!(
+        session
+            .events
+            .into_iter()
+            .map(|RecordedEvent { event, .. }| event)
+            .collect::<Vec<_>>(),
+        events
+    );
+}
+
+#[tokio::test]
+async fn test_replay() {
+    let sync = EventBuilder::new().session("sort.toml").quit().build();
+
+    assert_eq!(
+        reset_buffer(&run_app(sync, 60, 22).await.unwrap()),
+        Buffer::with_lines([
+            r#"┌Search──────────────────────│Sort by "Seeders" Descending│┐"#,
+            r#"│                            └────────────────────────────┘│"#,
+            r#"└──────────────────────────────────────────────────────────┘"#,
+            r#"┌Results 1-0 (0 total): Page 1/0─dl: Run Command, src: Nyaa┐"#,
+            r#"│                                                          │"#,
+            r#"│                                                          │"#,
+            r#"│                                                          │"#,
+            r#"│              ┌Sort Descending─────────────┐              │"#,
+            r#"│              │   Date                     │              │"#,
+            r#"│              │   Downloads                │              │"#,
+            r#"│              │  Seeders                  │              │"#,
+            r#"│              │   Leechers                 │              │"#,
+            r#"│              │   Size                     │              │"#,
+            r#"│              └────────────────────────────┘              │"#,
+            r#"│                                                          │"#,
+            r#"│                                                          │"#,
+            r#"│                                                          │"#,
+            r#"│                                                          │"#,
+            r#"│                                                          │"#,
+            r#"│                                                          │"#,
+            r#"│                                                          │"#,
+            r#"└─────────────────────────────────────────────────────────s┘"#,
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_macro() {
+    let sync = EventBuilder::new()
+        .mock()
+        .string("/frieren")
+        .enter()
+        .wait(50)
+        .string("Qa jQ@a")
+        .wait(50)
+        .string("@@")
+        .wait(50)
+        // Dismiss notifications so they don't cover the batch
+        .esc()
+        .wait(500)
+        .quit()
+        .build();
+
+    assert_eq!(
+        reset_buffer(&run_app(sync, 60, 12).await.unwrap()),
+        Buffer::with_lines([
+            r#"┌Search──────────────────────────────Press F1 or ? for help┐"#,
+            r#"│frieren                                                   │"#,
+            r#"└──────────────────────────────────────────────────────────┘"#,
+            r#"┌Results 1-3 (3 todl: Run Command, src: Nyaa┐┌Batch────────┐"#,
+            r#"│Cat Nam   Size          Dat             ││C N   Size   │"#,
+            r#"█Sub [Su   1.4 GiB 2024-06-1 2311  104 15K  ││S [   1.4 GiB│"#,
+            r#"█Sub [Su 724.1 MiB 2024-06-1  874   31 6120 ││S [ 724.1 MiB│"#,
+            r#"█AMV [Fa  98.4 MiB 2024-06-0    5    0 77   █│A [  98.4 MiB│"#,
+            r#"│                                           █│             │"#,
+            r#"│                                           █│             │"#,
+            r#"│                                           █│             │"#,
+            r#"└──────────────────────────────────────<Esc>┘└─────────────┘"#,
+        ])
+    );
+}
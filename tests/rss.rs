@@ -0,0 +1,126 @@
+use nyaa::{
+    source::{nyaa_html::NyaaSort, nyaa_rss::sort_items, Item, ItemDate, ItemId},
+    util::conv::{format_size, to_bytes, SizeFormat, SizeUnits},
+    widget::sort::{SelectedSort, SortDir},
+};
+
+fn item(id: usize, seeders: u32) -> Item {
+    Item {
+        id: ItemId::new("nyaa", id),
+        seeders,
+        ..Default::default()
+    }
+}
+
+fn ids(items: &[Item]) -> Vec<String> {
+    items.iter().map(|i| i.id.to_string()).collect()
+}
+
+#[test]
+fn test_sort_items() {
+    let mut items = vec![item(9, 3), item(100, 1), item(42, 7)];
+
+    sort_items(
+        &mut items,
+        SelectedSort {
+            sort: 0,
+            dir: SortDir::Desc,
+        },
+    );
+    assert_eq!(ids(&items), ["nyaa-100", "nyaa-42", "nyaa-9"]);
+
+    sort_items(
+        &mut items,
+        SelectedSort {
+            sort: 0,
+            dir: SortDir::Asc,
+        },
+    );
+    assert_eq!(ids(&items), ["nyaa-9", "nyaa-42", "nyaa-100"]);
+
+    sort_items(
+        &mut items,
+        SelectedSort {
+            sort: 2,
+            dir: SortDir::Desc,
+        },
+    );
+    assert_eq!(ids(&items), ["nyaa-42", "nyaa-9", "nyaa-100"]);
+
+    sort_items(
+        &mut items,
+        SelectedSort {
+            sort: 2,
+            dir: SortDir::Asc,
+        },
+    );
+    assert_eq!(ids(&items), ["nyaa-100", "nyaa-9", "nyaa-42"]);
+}
+
+#[test]
+fn test_sort_items_by_date() {
+    let dated = |id, date| Item {
+        date: ItemDate::parse_utc(date, "%Y-%m-%d %H:%M", None),
+        ..item(id, 0)
+    };
+    let mut items = vec![
+        dated(1, "2024-06-10 12:00"),
+        dated(2, "2024-06-12 08:30"),
+        dated(3, "2024-06-10 12:00"),
+    ];
+    sort_items(
+        &mut items,
+        SelectedSort {
+            sort: 0,
+            dir: SortDir::Desc,
+        },
+    );
+    assert_eq!(ids(&items), ["nyaa-2", "nyaa-3", "nyaa-1"]);
+    assert_eq!(items[0].date.to_string(), "2024-06-12 08:30");
+}
+
+#[test]
+fn test_sort_items_by_size() {
+    // Sorting the shown sizes as text would put "900 MiB" above "1.2 GiB"
+    let sized = |id, size| Item {
+        id: ItemId::new("nyaa", id),
+        bytes: to_bytes(size),
+        ..Default::default()
+    };
+    let mut items = vec![
+        sized(1, "900 MiB"),
+        sized(2, "1.2 GiB"),
+        sized(3, "85.5 KiB"),
+        sized(4, "1,024.0 MiB"),
+    ];
+    sort_items(
+        &mut items,
+        SelectedSort {
+            sort: NyaaSort::Size as usize,
+            dir: SortDir::Desc,
+        },
+    );
+    assert_eq!(ids(&items), ["nyaa-2", "nyaa-4", "nyaa-1", "nyaa-3"]);
+}
+
+#[test]
+fn test_size_format() {
+    assert_eq!(to_bytes("1.5 GiB"), 1610612736);
+    assert_eq!(to_bytes("700MB"), 734003200);
+    assert_eq!(to_bytes("512 B"), 512);
+    assert_eq!(to_bytes("-"), 0);
+
+    let bytes = 1610612736;
+    assert_eq!(format_size(bytes, SizeFormat::default()), "1.5 GiB");
+    let decimal = SizeFormat {
+        units: SizeUnits::Decimal,
+        decimals: 2,
+    };
+    assert_eq!(format_size(bytes, decimal), "1.61 GB");
+    let whole = SizeFormat {
+        units: SizeUnits::Binary,
+        decimals: 0,
+    };
+    assert_eq!(format_size(bytes, whole), "2 GiB");
+    assert_eq!(format_size(512, decimal), "512 B");
+}
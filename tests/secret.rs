@@ -0,0 +1,73 @@
+use nyaa::{
+    config::{
+        secret::{check_name, keyring_delete, keyring_set, resolve, resolve_named, SecretRef},
+        validate::check,
+    },
+    source::auth::SourceAuth,
+};
+
+#[test]
+fn test_resolve() {
+    assert_eq!(resolve("adminadmin").unwrap(), "adminadmin");
+    assert_eq!(
+        SecretRef::parse("env: NYAA_TEST_PASS"),
+        SecretRef::Env("NYAA_TEST_PASS")
+    );
+
+    std::env::set_var("NYAA_TEST_PASS", "hunter2");
+    assert_eq!(resolve("env:NYAA_TEST_PASS").unwrap(), "hunter2");
+    assert!(resolve("env:NYAA_TEST_UNSET").is_err());
+
+    #[cfg(unix)]
+    {
+        assert_eq!(
+            resolve("cmd:printf 'hunter2\\nlogin: admin'").unwrap(),
+            "hunter2"
+        );
+        assert!(resolve("cmd:exit 1").is_err());
+    }
+}
+
+#[test]
+fn test_missing_env_secret() {
+    let report = check(
+        r#"
+        [client.transmission]
+        base_url = "http://localhost:9091/transmission/rpc"
+        username = "admin"
+        password = "env:NYAA_TEST_MISSING_PASS"
+        "#,
+    );
+    assert!(report.errors.is_empty());
+    assert_eq!(report.warnings.len(), 1);
+    assert_eq!(report.warnings[0].path, "client.transmission.password");
+}
+
+#[test]
+fn test_keyring_names() {
+    let err = keyring_set("qBittorrent.passwrod", "hunter2").unwrap_err();
+    assert!(err.starts_with("There is no secret named \"qBittorrent.passwrod\""));
+    assert!(keyring_delete("api_key").is_err());
+
+    // Any source can keep its login, cookie or token in the keyring
+    assert!(check_name("source.Nyaa.password").is_ok());
+    assert!(check_name("source.TorrentGalaxy.token").is_ok());
+    assert!(check_name("source..token").is_err());
+    assert!(check_name("source.Nyaa.api_key").is_err());
+    // So they can be left out of the config
+    let auth: SourceAuth = toml::from_str(
+        r#"
+        type = "login"
+        url = "https://nyaa.si/login"
+        "#,
+    )
+    .unwrap();
+    assert!(matches!(auth, SourceAuth::Login { password, .. } if password.is_empty()));
+
+    // Nothing is read from the keyring unless it's turned on
+    assert_eq!(
+        resolve_named("qBittorrent.password", Some("adminadmin")).unwrap(),
+        Some("adminadmin".to_owned())
+    );
+    assert_eq!(resolve_named("transmission.password", None).unwrap(), None);
+}
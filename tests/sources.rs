@@ -0,0 +1,902 @@
+use std::collections::HashMap;
+
+use crossterm::event::{Event, KeyCode, KeyEvent};
+use reqwest::{
+    cookie::{CookieStore as _, Jar},
+    header::{ACCEPT_LANGUAGE, AUTHORIZATION, USER_AGENT},
+    Url,
+};
+use tokio_util::sync::CancellationToken;
+
+use nyaa::{
+    app::{Context, Mode},
+    client::progress::{self, Batch, BatchGuard, ProgressGuard, ResolveGuard},
+    error::NyaaError,
+    source::{
+        airing::{airing_items, parse_schedule},
+        archive::{identifier_num, parse_archive, search_query},
+        auth::{is_expired, logged_in, SourceAuth},
+        category_map::UnifiedCategory,
+        custom_nyaa::{self, CustomCategories},
+        download_url,
+        eztv::{imdb_id, parse_eztv},
+        nyaa_api::parse_api,
+        nyaa_engine::{parse_html, search_nyaa},
+        nyaa_html::{NyaaConfig, NyaaHtmlSource, NyaaTransport},
+        resolve::{links_from_post, needs_resolve, resolve_all, Links},
+        Item, ItemDate, ItemId, ItemType, SourceCapabilities, SourceConfig, SourceResponse,
+        Sources,
+    },
+    sync::SearchQuery,
+    util::conv::info_hash,
+    widget::{batch::BatchWidget, results::ResultsWidget, Widget as _},
+};
+
+#[allow(dead_code)]
+mod common;
+
+#[test]
+fn test_custom_nyaa_sources() {
+    let config: SourceConfig = toml::from_str(
+        r#"
+        [[custom_nyaa]]
+        name = "My Nyaa"
+        base_url = "https://nyaa.example.com"
+        default_search = "frieren"
+
+        [[custom_nyaa]]
+        name = "My Sukebei"
+        categories = "Sukebei"
+        base_url = "https://sukebei.example.com"
+        timeout = 5
+        rss = true
+        "#,
+    )
+    .unwrap();
+
+    let all = Sources::all(&config);
+    assert_eq!(all.len(), 8);
+    assert!(all[6] == Sources::Custom("My Nyaa"));
+    assert!(all[7] == Sources::Custom("My Sukebei"));
+    let names: Vec<String> = all.iter().map(|s| s.name(&config)).collect();
+    assert_eq!(
+        names,
+        [
+            "Nyaa",
+            "Sukebei",
+            "TorrentGalaxy",
+            "EZTV",
+            "InternetArchive",
+            "Airing",
+            "My Nyaa",
+            "My Sukebei"
+        ]
+    );
+
+    let nyaa = &config.custom_nyaa[0];
+    assert_eq!(nyaa.nyaa.base_url, "https://nyaa.example.com");
+    assert!(nyaa.categories == CustomCategories::Nyaa);
+    assert_eq!(
+        Sources::Custom("My Nyaa").default_search(&config),
+        "frieren"
+    );
+    assert_eq!(Sources::Custom("My Sukebei").timeout(&config), Some(5));
+    assert!(config.custom_nyaa[1].categories == CustomCategories::Sukebei);
+
+    // Shown in the sources popup
+    assert_eq!(
+        Sources::Custom("My Sukebei").host(&config),
+        "sukebei.example.com"
+    );
+    assert_eq!(
+        Sources::Custom("My Sukebei").transport(&config),
+        NyaaTransport::Rss
+    );
+    assert_eq!(
+        Sources::Custom("My Nyaa").transport(&config),
+        NyaaTransport::Html
+    );
+    assert_eq!(
+        Sources::Custom("My Sukebei").config_table(&config),
+        "[[source.custom_nyaa]] named \"My Sukebei\""
+    );
+    assert_eq!(
+        Sources::TorrentGalaxy.config_table(&config),
+        "[source.torrentgalaxy]"
+    );
+
+    // Ids of each instance are kept apart from nyaa's and each other's
+    assert_eq!(custom_nyaa::id_prefix("My Sukebei"), "My Sukebei");
+    assert_ne!(
+        custom_nyaa::id_prefix("My Nyaa"),
+        custom_nyaa::id_prefix("My Sukebei")
+    );
+    assert_eq!(
+        ItemId::parse("My Sukebei-42"),
+        Some(ItemId::new(custom_nyaa::id_prefix("My Sukebei"), 42))
+    );
+
+    // Instances are saved by name, so reordering them keeps what was saved
+    let saved = HashMap::from([("source", Sources::Custom("My Sukebei"))]);
+    let saved = toml::to_string(&saved).unwrap();
+    let reordered: SourceConfig = toml::from_str(
+        r#"
+        [[custom_nyaa]]
+        name = "My Sukebei"
+        categories = "Sukebei"
+
+        [[custom_nyaa]]
+        name = "My Nyaa"
+        "#,
+    )
+    .unwrap();
+    let loaded: HashMap<String, Sources> = toml::from_str(&saved).unwrap();
+    assert_eq!(loaded["source"].name(&reordered), "My Sukebei");
+    assert!(Sources::all(&reordered)[6] == loaded["source"]);
+}
+
+#[test]
+fn test_sukebei_config() {
+    let config: SourceConfig = toml::from_str("[sukebei]\ntimeout = 5").unwrap();
+    assert_eq!(
+        Sources::SukebeiNyaa.base_url(&config),
+        "https://sukebei.nyaa.si/"
+    );
+    assert_eq!(Sources::SukebeiNyaa.timeout(&config), Some(5));
+
+    // Options are the same as nyaa's, and read back the same once written
+    let text = "[sukebei]\nbase_url = \"https://sukebei.example.com\"\nrss = true";
+    let config: SourceConfig = toml::from_str(text).unwrap();
+    let config: SourceConfig = toml::from_str(&toml::to_string(&config).unwrap()).unwrap();
+    assert_eq!(
+        Sources::SukebeiNyaa.base_url(&config),
+        "https://sukebei.example.com"
+    );
+    assert_eq!(Sources::SukebeiNyaa.transport(&config), NyaaTransport::Rss);
+}
+
+#[test]
+fn test_request_headers() {
+    let config: SourceConfig = toml::from_str(
+        r#"
+        [nyaa.headers]
+        user_agents = ["Mozilla/5.0 (X11; Linux x86_64)", "Mozilla/5.0 (Windows NT 10.0)"]
+        accept_language = "en-US,en;q=0.9"
+
+        [[custom_nyaa]]
+        name = "My Nyaa"
+        base_url = "https://nyaa.example.com"
+        headers = { accept_language = "ja" }
+        "#,
+    )
+    .unwrap();
+
+    let headers = Sources::Nyaa.headers(&config).unwrap();
+    let first = headers.to_header_map();
+    let second = headers.to_header_map();
+    assert_eq!(first[ACCEPT_LANGUAGE], "en-US,en;q=0.9");
+    // Each client takes the next User-Agent in turn
+    let mut agents = [&first[USER_AGENT], &second[USER_AGENT]];
+    agents.sort_by_key(|h| h.to_str().unwrap().to_owned());
+    assert_eq!(
+        agents,
+        [
+            "Mozilla/5.0 (Windows NT 10.0)",
+            "Mozilla/5.0 (X11; Linux x86_64)"
+        ]
+    );
+
+    let custom = Sources::Custom("My Nyaa")
+        .headers(&config)
+        .unwrap()
+        .to_header_map();
+    assert_eq!(custom[ACCEPT_LANGUAGE], "ja");
+    assert!(custom.get(USER_AGENT).is_none());
+    assert!(Sources::TorrentGalaxy.headers(&config).is_none());
+}
+
+#[test]
+fn test_download_url_template() {
+    let config: SourceConfig = toml::from_str(
+        r#"
+        [nyaa]
+        base_url = "nyaa.example.com"
+        download_url_template = "/dl/{id}.torrent"
+
+        [torrentgalaxy]
+        download_url_template = "https://files.example.com/torrent/{id}"
+        "#,
+    )
+    .unwrap();
+
+    let nyaa = Sources::Nyaa.download_url_template(&config).unwrap();
+    let base_url = Sources::Nyaa.base_url(&config);
+    assert_eq!(
+        download_url(&nyaa, &base_url, &ItemId::new("nyaa", 123)).as_deref(),
+        Some("https://nyaa.example.com/dl/123.torrent")
+    );
+    let tgx = Sources::TorrentGalaxy
+        .download_url_template(&config)
+        .unwrap();
+    assert_eq!(
+        download_url(&tgx, "", &ItemId::new("tgx", 45)).as_deref(),
+        Some("https://files.example.com/torrent/45")
+    );
+    assert!(Sources::SukebeiNyaa
+        .download_url_template(&config)
+        .is_none());
+}
+
+#[test]
+fn test_category_map() {
+    let config: SourceConfig = toml::from_str(
+        r#"
+        [[custom_nyaa]]
+        name = "My Sukebei"
+        categories = "Sukebei"
+
+        [category_map.TorrentGalaxy]
+        Movies = "4kMovies"
+        Tv = "NotACategory"
+        "#,
+    )
+    .unwrap();
+
+    let cat = UnifiedCategory::AnimeEnglish;
+    assert_eq!(cat.to_native(Sources::Nyaa, &config), Some(12));
+    assert_eq!(cat.to_native(Sources::SukebeiNyaa, &config), Some(11));
+    assert_eq!(cat.to_native(Sources::TorrentGalaxy, &config), Some(28));
+    assert_eq!(
+        cat.to_native(Sources::Custom("My Sukebei"), &config),
+        Some(11)
+    );
+
+    // Overrides replace the built in mapping, and unknown names match nothing
+    let movies = UnifiedCategory::Movies;
+    assert_eq!(movies.to_native(Sources::TorrentGalaxy, &config), Some(3));
+    assert_eq!(movies.to_native(Sources::Nyaa, &config), None);
+    assert_eq!(
+        UnifiedCategory::Tv.to_native(Sources::TorrentGalaxy, &config),
+        None
+    );
+
+    assert_eq!(
+        UnifiedCategory::from_native(Sources::Nyaa, 14, &config),
+        Some(UnifiedCategory::AnimeRaw)
+    );
+}
+
+#[test]
+fn test_client_progress() {
+    let magnet = "magnet:?xt=urn:btih:0123456789ABCDEF0123456789ABCDEF01234567&dn=Frieren";
+    let hash = info_hash(magnet).unwrap();
+    assert_eq!(hash, "0123456789abcdef0123456789abcdef01234567");
+    assert_eq!(info_hash("magnet:?xt=urn:btih:short&dn=x"), None);
+    assert_eq!(info_hash("https://nyaa.si/download/1.torrent"), None);
+
+    assert!(!progress::is_active());
+    let guard = ProgressGuard::new(format!("qbit-{}", hash), "Frieren".to_owned(), None);
+    assert!(progress::is_active());
+    guard.set(512 * 1024, 1024 * 1024, 2 * 1024 * 1024);
+    assert_eq!(
+        progress::summary(),
+        Some("50% @ 2.0 MiB/s Frieren".to_owned())
+    );
+    drop(guard);
+    assert!(!progress::is_active());
+
+    let guard = ResolveGuard::new(2);
+    guard.finish();
+    assert_eq!(progress::summary(), Some("Resolving links 1/2".to_owned()));
+    drop(guard);
+    assert!(!progress::is_active());
+}
+
+#[test]
+fn test_batch_progress() {
+    assert_eq!(progress::total_batch_progress(), None);
+    let batch = |id| Batch {
+        id,
+        stop: CancellationToken::new(),
+    };
+    let guard = BatchGuard::new(&batch(1), 4);
+    guard.finish(true);
+    guard.finish(false);
+    let p = progress::batch_progress(1).unwrap();
+    assert_eq!((p.total, p.fetched, p.failed), (4, 1, 1));
+    assert_eq!(p.ratio(), 0.5);
+
+    // A second batch is kept apart from the first
+    let second = BatchGuard::new(&batch(2), 2);
+    second.finish(true);
+    assert_eq!(progress::batch_progress(1).unwrap().fetched, 1);
+    assert_eq!(progress::batch_progress(2).unwrap().fetched, 1);
+    let total = progress::total_batch_progress().unwrap();
+    assert_eq!((total.total, total.fetched, total.failed), (6, 2, 1));
+    drop(second);
+    assert_eq!(progress::batch_progress(2), None);
+    assert!(progress::batch_progress(1).is_some());
+
+    // Esc stops the download instead of leaving the batch
+    let mut ctx = Context::default();
+    ctx.mode = Mode::Batch;
+    BatchWidget::default().handle_event(&mut ctx, &Event::Key(KeyEvent::from(KeyCode::Esc)));
+    assert!(ctx.mode == Mode::Batch);
+    assert!(guard.is_cancelled());
+
+    drop(guard);
+    assert_eq!(progress::total_batch_progress(), None);
+}
+
+#[test]
+fn test_api_transport() {
+    let config: SourceConfig = toml::from_str(
+        r#"
+        [nyaa]
+        transport = "api"
+
+        [sukebei]
+        transport = "api"
+        rss = true
+        "#,
+    )
+    .unwrap();
+    assert_eq!(Sources::Nyaa.transport(&config), NyaaTransport::Api);
+    // The older rss flag still wins
+    assert_eq!(Sources::SukebeiNyaa.transport(&config), NyaaTransport::Rss);
+    assert_eq!(
+        Sources::TorrentGalaxy.transport(&config),
+        NyaaTransport::Html
+    );
+}
+
+#[test]
+fn test_parse_api() {
+    let base_url = reqwest::Url::parse("https://nyaa.example.com/").unwrap();
+    let json = r#"{
+        "torrents": [
+            {
+                "id": 1834725,
+                "name": "[SubsPlease] Frieren - 01 (1080p)",
+                "magnet": "magnet:?xt=urn:btih:abc",
+                "main_category_id": 1,
+                "sub_category_id": 2,
+                "filesize": 1449551462,
+                "creation_date": "2024-06-12 09:41 UTC",
+                "stats": { "seeders": 120, "leechers": 4, "downloads": 9001 },
+                "is_trusted": true,
+                "is_remake": false
+            },
+            {
+                "id": 42,
+                "name": "Frieren 01 reencode",
+                "url": "https://nyaa.example.com/view/42?x",
+                "is_remake": true
+            }
+        ],
+        "total": 151
+    }"#;
+    let res = parse_api::<NyaaHtmlSource>(json, &base_url, 1, "nyaa", None).unwrap();
+    assert_eq!(res.total_results, Some(151));
+    assert_eq!(res.last_page, 3);
+    assert_eq!(res.items.len(), 2);
+
+    let first = &res.items[0];
+    assert_eq!(first.id, ItemId::new("nyaa", 1834725));
+    assert_eq!(first.title, "[SubsPlease] Frieren - 01 (1080p)");
+    assert_eq!(first.bytes, 1449551462);
+    assert_eq!(
+        (first.seeders, first.leechers, first.downloads),
+        (120, 4, 9001)
+    );
+    assert_eq!(first.category, 12);
+    assert!(matches!(first.item_type, ItemType::Trusted));
+    assert_eq!(
+        first.torrent_link,
+        "https://nyaa.example.com/download/1834725.torrent"
+    );
+    assert_eq!(first.post_link, "https://nyaa.example.com/view/1834725");
+
+    let second = &res.items[1];
+    assert!(matches!(second.item_type, ItemType::Remake));
+    assert_eq!(second.post_link, "https://nyaa.example.com/view/42?x");
+
+    // A bare list doesn't say how many pages there are
+    let res = parse_api::<NyaaHtmlSource>("[]", &base_url, 2, "nyaa", None).unwrap();
+    assert_eq!(res.last_page, 2);
+    assert!(parse_api::<NyaaHtmlSource>("<html>", &base_url, 1, "nyaa", None).is_err());
+}
+
+#[test]
+fn test_parse_html() {
+    let html = r#"<div class="pagination-page-info">Displaying results 1-75 out of 1000 results.</div>
+<table class="torrent-list"><tbody>
+<tr class="success">
+<td><a href="/?c=1_2"></a></td>
+<td><a href="/view/1810001" title="[SubsPlease] Show - 01 (1080p).mkv">[SubsPlease] Show - 01 (1080p).mkv</a></td>
+<td><a href="/download/1810001.torrent"></a><a href="magnet:?xt=urn:btih:abc"></a></td>
+<td>1.4 GiB</td><td>2024-06-14 18:01</td><td>120</td><td>4</td><td>5042</td>
+</tr>
+<tr class="danger">
+<td><a href="/?c=1_2"></a></td>
+<td><a href="/view/1810002" title="[Fan-Sub] Show - 01 (720p).mkv">[Fan-Sub] Show - 01 (720p).mkv</a></td>
+<td><a href="/download/1810002.torrent"></a><a href="magnet:?xt=urn:btih:def"></a></td>
+<td>700 MiB</td><td>2024-06-14 18:05</td><td>3</td><td>0</td><td>12</td>
+</tr>
+</tbody></table>"#;
+    let url = Url::parse("https://nyaa.si/?q=show").unwrap();
+    let res = parse_html::<NyaaHtmlSource>(html, &url, &NyaaConfig::default(), "nyaa", true, None);
+    let Ok(SourceResponse::Results(res)) = res else {
+        panic!("Failed to parse the page");
+    };
+    assert_eq!(res.total_results, Some(1000));
+    assert_eq!(res.last_page, 14);
+    assert!(res.warnings.is_empty());
+    assert_eq!(res.items.len(), 2);
+
+    let first = &res.items[0];
+    assert_eq!(first.id, ItemId::new("nyaa", 1810001));
+    assert_eq!(first.title, "[SubsPlease] Show - 01 (1080p).mkv");
+    assert_eq!(first.post_link, "https://nyaa.si/view/1810001");
+    assert_eq!(
+        first.torrent_link,
+        "https://nyaa.si/download/1810001.torrent"
+    );
+    assert_eq!(
+        (first.seeders, first.leechers, first.downloads),
+        (120, 4, 5042)
+    );
+    assert!(matches!(first.item_type, ItemType::Trusted));
+    assert!(matches!(res.items[1].item_type, ItemType::Remake));
+
+    // Pages without a results table are blocked or have changed
+    assert!(parse_html::<NyaaHtmlSource>(
+        "<html>",
+        &url,
+        &NyaaConfig::default(),
+        "nyaa",
+        false,
+        None
+    )
+    .is_err());
+}
+
+#[test]
+fn test_airing_items() {
+    let schedule = parse_schedule(
+        r#"{
+            "tz": "UTC",
+            "schedule": [
+                { "title": "Sousou no Frieren", "page": "sousou-no-frieren", "time": "16:30", "aired": true },
+                { "title": "Dr. Stone S4", "time": "23:00", "aired": false }
+            ]
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(schedule.len(), 2);
+    assert_eq!(schedule[0].time, "16:30");
+    assert!(parse_schedule("<html>").is_err());
+
+    let now = chrono::Utc::now();
+    let item = |title: &str, hours: i64| Item {
+        title: title.to_owned(),
+        date: ItemDate::new(now - chrono::TimeDelta::hours(hours), "%Y-%m-%d %H:%M"),
+        ..Default::default()
+    };
+    let items = vec![
+        item(
+            "[SubsPlease] Sousou no Frieren - 12 (1080p) [A1B2C3D4].mkv",
+            2,
+        ),
+        item(
+            "[SubsPlease] Sousou no Frieren - 11 (1080p) [E5F6A7B8].mkv",
+            170,
+        ),
+        item("[SubsPlease] Dr Stone S4 - 05 (1080p) [C9D0E1F2].mkv", 1),
+        item(
+            "[SubsPlease] Kusuriya no Hitorigoto - 08 (1080p) [0A1B2C3D].mkv",
+            3,
+        ),
+    ];
+    let airing = airing_items(items, &schedule, now);
+    let titles: Vec<&str> = airing.iter().map(|i| i.title.as_str()).collect();
+    assert_eq!(
+        titles,
+        [
+            "[SubsPlease] Sousou no Frieren - 12 (1080p) [A1B2C3D4].mkv",
+            "[SubsPlease] Dr Stone S4 - 05 (1080p) [C9D0E1F2].mkv",
+        ]
+    );
+    assert_eq!(airing[0].extra.get("airs"), Some(&"16:30".to_owned()));
+}
+
+#[test]
+fn test_parse_eztv() {
+    assert_eq!(imdb_id("tt0944947"), Some("944947".to_owned()));
+    assert_eq!(imdb_id(" tt6048596 "), Some("6048596".to_owned()));
+    assert_eq!(imdb_id("1923"), None);
+    assert_eq!(imdb_id("tt"), None);
+
+    let json = r#"{
+        "imdb_id": "",
+        "torrents_count": 250,
+        "limit": 100,
+        "page": 1,
+        "torrents": [
+            {
+                "id": 2100001,
+                "hash": "ab12cd",
+                "filename": "The.Bear.S03E05.1080p.WEB.H264-SuccessfulCrab[EZTVx.to].mkv",
+                "episode_url": "https://eztvx.to/ep/2100001/the-bear-s03e05/",
+                "torrent_url": "https://zoink.ch/torrent/The.Bear.S03E05.torrent",
+                "magnet_url": "magnet:?xt=urn:btih:ab12cd",
+                "title": "The Bear S03E05 1080p WEB H264-SuccessfulCrab EZTV",
+                "imdb_id": "14452776",
+                "season": "3",
+                "episode": "5",
+                "seeds": 310,
+                "peers": 12,
+                "date_released_unix": 1719446400,
+                "size_bytes": "2147483648"
+            },
+            {
+                "id": 2100002,
+                "title": "Shogun 2024 S01E10 720p HEVC x265-MeGusta EZTV",
+                "imdb_id": "0",
+                "season": "1",
+                "episode": "10",
+                "size_bytes": 524288000
+            }
+        ]
+    }"#;
+    let res = parse_eztv(json, "", None).unwrap();
+    assert_eq!(res.total_results, Some(250));
+    assert_eq!(res.last_page, 3);
+    assert_eq!(res.items.len(), 2);
+
+    let bear = &res.items[0];
+    assert_eq!(bear.id, ItemId::new("eztv", 2100001));
+    assert_eq!(bear.bytes, 2147483648);
+    assert_eq!((bear.seeders, bear.leechers), (310, 12));
+    assert_eq!(bear.date.to_string(), "2024-06-27 00:00");
+    assert_eq!(bear.extra["season"], "3");
+    assert_eq!(bear.extra["episode"], "5");
+    assert_eq!(bear.extra["imdb"], "tt14452776");
+    assert_eq!(bear.file_name, "ab12cd.torrent");
+    assert_eq!(res.items[1].bytes, 524288000);
+    assert!(!res.items[1].extra.contains_key("imdb"));
+
+    // Other searches only keep titles with every word
+    let res = parse_eztv(json, "shogun 720P", None).unwrap();
+    assert_eq!(res.items.len(), 1);
+    assert_eq!(res.items[0].id, ItemId::new("eztv", 2100002));
+    assert_eq!(res.total_results, None);
+    assert_eq!(parse_eztv(json, "tt14452776", None).unwrap().items.len(), 2);
+    assert!(parse_eztv("<html>", "", None).is_err());
+}
+
+#[test]
+fn test_parse_archive() {
+    assert_eq!(
+        search_query("night of the living dead", 1),
+        "(night of the living dead) AND mediatype:movies AND format:\"Archive BitTorrent\""
+    );
+    assert_eq!(search_query(" ", 0), "format:\"Archive BitTorrent\"");
+    assert_eq!(
+        identifier_num("gd1977-05-08"),
+        identifier_num("gd1977-05-08")
+    );
+    assert_ne!(
+        identifier_num("gd1977-05-08"),
+        identifier_num("gd1977-05-09")
+    );
+
+    let base_url = reqwest::Url::parse("https://archive.org/").unwrap();
+    let json = r#"{
+        "responseHeader": { "status": 0 },
+        "response": {
+            "numFound": 120,
+            "start": 0,
+            "docs": [
+                {
+                    "identifier": "night_of_the_living_dead",
+                    "title": "Night of the Living Dead",
+                    "item_size": 1431655765,
+                    "downloads": 250000,
+                    "publicdate": "2005-03-02T18:20:33Z",
+                    "mediatype": "movies"
+                },
+                {
+                    "identifier": "gd1977-05-08",
+                    "title": ["Grateful Dead Live at Barton Hall", "Cornell"],
+                    "mediatype": "etree"
+                }
+            ]
+        }
+    }"#;
+    let res = parse_archive(json, &base_url, None).unwrap();
+    assert_eq!(res.total_results, Some(120));
+    assert_eq!(res.last_page, 3);
+
+    let movie = &res.items[0];
+    assert_eq!(
+        movie.id,
+        ItemId::new("archive", identifier_num("night_of_the_living_dead"))
+    );
+    assert_eq!(movie.title, "Night of the Living Dead");
+    assert_eq!((movie.bytes, movie.downloads), (1431655765, 250000));
+    assert_eq!(movie.date.to_string(), "2005-03-02 18:20");
+    assert_eq!(movie.category, 1);
+    assert_eq!(
+        movie.torrent_link,
+        "https://archive.org/download/night_of_the_living_dead/night_of_the_living_dead_archive.torrent"
+    );
+    assert_eq!(
+        movie.post_link,
+        "https://archive.org/details/night_of_the_living_dead"
+    );
+
+    let show = &res.items[1];
+    assert_eq!(show.title, "Grateful Dead Live at Barton Hall");
+    assert_eq!(show.category, 6);
+    assert_eq!(show.extra["identifier"], "gd1977-05-08");
+    assert!(parse_archive("<html>", &base_url, None).is_err());
+}
+
+#[test]
+fn test_source_auth() {
+    let config: SourceConfig = toml::from_str(
+        r#"
+        [auth.Nyaa]
+        type = "login"
+        url = "https://nyaa.si/login"
+        username = "user"
+        password = "hunter2"
+
+        [auth.Sukebei]
+        type = "cookie"
+        cookie = "uid=1234; pass=abcd"
+
+        [auth.TorrentGalaxy]
+        type = "token"
+        token = "abc"
+        "#,
+    )
+    .unwrap();
+
+    let login = Sources::Nyaa.auth(&config).unwrap();
+    assert!(matches!(
+        &login,
+        SourceAuth::Login { username_field, password_field, .. }
+            if username_field == "username" && password_field == "password"
+    ));
+    // Airing searches nyaa, so it uses nyaa's login
+    assert_eq!(Sources::Airing.auth(&config), Some(login));
+    assert_eq!(Sources::Airing.auth_state(&config), Some("signed out"));
+    assert_eq!(Sources::SukebeiNyaa.auth_state(&config), Some("cookie"));
+    assert_eq!(Sources::Eztv.auth_state(&config), None);
+
+    let jar = Jar::default();
+    let url = Url::parse("https://sukebei.nyaa.si/").unwrap();
+    let cookie = Sources::SukebeiNyaa.auth(&config).unwrap();
+    cookie.add_cookies("Sukebei", &jar, &url).unwrap();
+    let cookies = jar.cookies(&url).unwrap();
+    let mut cookies: Vec<&str> = cookies.to_str().unwrap().split("; ").collect();
+    cookies.sort();
+    assert_eq!(cookies, ["pass=abcd", "uid=1234"]);
+    assert!(cookie.headers("Sukebei").unwrap().is_empty());
+
+    let token = Sources::TorrentGalaxy.auth(&config).unwrap();
+    assert_eq!(
+        token
+            .headers("TorrentGalaxy")
+            .unwrap()
+            .get(AUTHORIZATION)
+            .unwrap(),
+        "Bearer abc"
+    );
+
+    let status = |code| NyaaError::Status {
+        url: "https://nyaa.si/".to_owned(),
+        code,
+    };
+    assert!(is_expired(&status(403)));
+    assert!(is_expired(&NyaaError::Parse("login page".to_owned())));
+    assert!(!is_expired(&status(404)));
+}
+
+#[tokio::test]
+async fn test_resolve_links() {
+    let post = Url::parse("https://tracker.example.com/view/42").unwrap();
+    let html = r#"
+        <a href="/view/41">Previous</a>
+        <a href="/download/42.torrent">Download</a>
+        <a href="magnet:?xt=urn:btih:0123456789abcdef0123456789abcdef01234567">Magnet</a>
+    "#;
+    assert_eq!(
+        links_from_post(html, &post).unwrap(),
+        Links {
+            torrent: Some("https://tracker.example.com/download/42.torrent".to_owned()),
+            magnet: Some("magnet:?xt=urn:btih:0123456789abcdef0123456789abcdef01234567".to_owned()),
+        }
+    );
+    assert_eq!(
+        links_from_post("<p>Log in to download</p>", &post).unwrap(),
+        Links::default()
+    );
+
+    let listed = Item {
+        id: ItemId::new("nyaa", 1),
+        torrent_link: "https://nyaa.si/download/1.torrent".to_owned(),
+        ..Default::default()
+    };
+    let unlisted = Item {
+        id: ItemId::new("nyaa", 2),
+        torrent_link: "null".to_owned(),
+        ..Default::default()
+    };
+    assert!(!needs_resolve(&listed));
+    assert!(needs_resolve(&unlisted));
+
+    // Items which already have links are downloaded without fetching anything
+    let (items, failed) = resolve_all(
+        Sources::Nyaa,
+        &reqwest::Client::new(),
+        vec![listed.clone()],
+        &SourceConfig::default(),
+    )
+    .await;
+    assert_eq!(items.len(), 1);
+    assert!(failed.is_empty());
+}
+
+#[test]
+fn test_capabilities() {
+    let config: SourceConfig = toml::from_str(
+        r#"
+        [[custom_nyaa]]
+        name = "Private"
+        base_url = "https://nyaa.example.com"
+        needs_auth = true
+        "#,
+    )
+    .unwrap();
+    let caps = |src: Sources| src.info(&config).capabilities;
+    assert_eq!(caps(Sources::Nyaa), SourceCapabilities::nyaa());
+    assert!(!caps(Sources::TorrentGalaxy).user);
+    assert!(!caps(Sources::TorrentGalaxy).rss);
+    assert!(!caps(Sources::Eztv).category);
+    assert!(caps(Sources::Custom("Private")).auth);
+    assert!(!caps(Sources::Nyaa).auth);
+
+    // Keys for what the source can't do leave the mode as it was
+    let mut ctx = Context::default();
+    ctx.mode = Mode::Normal;
+    let mut widget = ResultsWidget::default();
+    let key = |c| Event::Key(KeyEvent::from(KeyCode::Char(c)));
+    ctx.src = Sources::TorrentGalaxy;
+    ctx.src_info = ctx.src.info(&config);
+    widget.handle_event(&mut ctx, &key('u'));
+    assert!(ctx.mode == Mode::Normal);
+    widget.handle_event(&mut ctx, &key('c'));
+    assert!(ctx.mode == Mode::Category);
+
+    ctx.mode = Mode::Normal;
+    ctx.src = Sources::Eztv;
+    ctx.src_info = ctx.src.info(&config);
+    widget.handle_event(&mut ctx, &key('c'));
+    assert!(ctx.mode == Mode::Normal);
+}
+
+#[test]
+fn test_toggle_columns() {
+    let mut config = SourceConfig::default();
+    let columns = Sources::TorrentGalaxy.columns(&config).unwrap();
+    assert_eq!(columns.len(), 14);
+    assert_eq!(columns[7], ("IMDB", true));
+    assert_eq!(Sources::InternetArchive.columns(&config), None);
+
+    Sources::TorrentGalaxy.set_column(&mut config, 7, false);
+    assert_eq!(
+        Sources::TorrentGalaxy.columns(&config).unwrap()[7],
+        ("IMDB", false)
+    );
+    // Only what was changed is written to the config
+    let toml = toml::to_string(&config).unwrap();
+    assert!(toml.contains("[torrentgalaxy.columns]\nimdb = false\n"));
+
+    // Airing shows nyaa's columns
+    Sources::Nyaa.set_column(&mut config, 3, true);
+    assert_eq!(
+        Sources::Airing.columns(&config).unwrap()[3],
+        ("Resolution", true)
+    );
+}
+
+#[tokio::test]
+async fn test_login() {
+    fn respond(request: &str) -> (u16, &'static str, String) {
+        match request {
+            r if r.starts_with("POST /login") && r.contains("password=hunter2") => (
+                302,
+                "Location: /\r\nSet-Cookie: session=abc\r\n",
+                String::new(),
+            ),
+            // Wrong passwords get the form again, still with 200
+            r if r.starts_with("POST /login") => (
+                200,
+                "",
+                r#"<form><input name="username"><input name="password"></form>"#.to_owned(),
+            ),
+            _ => (200, "", "<html>Signed in</html>".to_owned()),
+        }
+    }
+    let base_url = common::serve(respond);
+    let auth = |password: &str| -> SourceAuth {
+        toml::from_str(&format!(
+            "type = \"login\"\nurl = \"{}/login\"\nusername = \"user\"\npassword = \"{}\"",
+            base_url, password
+        ))
+        .unwrap()
+    };
+    let client = reqwest::Client::builder()
+        .cookie_store(true)
+        .build()
+        .unwrap();
+
+    let err = auth("wrong").login(&client, "LoginTest").await.unwrap_err();
+    assert!(matches!(err, NyaaError::Auth(_)));
+    assert!(!logged_in("LoginTest"));
+
+    auth("hunter2").login(&client, "LoginTest").await.unwrap();
+    assert!(logged_in("LoginTest"));
+}
+
+#[tokio::test]
+async fn test_rss_fallback() {
+    fn respond(request: &str) -> (u16, &'static str, String) {
+        if request.contains("page=rss") {
+            let feed = r#"<rss version="2.0"><channel><title>Nyaa</title><link>/</link><description /></channel></rss>"#;
+            return (200, "", feed.to_owned());
+        }
+        let code = match request {
+            r if r.contains("u=missing") => 404,
+            r if r.contains("u=private") => 403,
+            _ => 200,
+        };
+        (code, "", "<html>Not a results page</html>".to_owned())
+    }
+    let nyaa = NyaaConfig {
+        base_url: common::serve(respond),
+        rss_fallback: true,
+        ..Default::default()
+    };
+    let search = |user: &str| SearchQuery {
+        page: 1,
+        user: Some(user.to_owned()),
+        ..Default::default()
+    };
+    let client = reqwest::Client::new();
+    let load = |user: &'static str| {
+        let (client, nyaa) = (client.clone(), nyaa.clone());
+        async move {
+            search_nyaa::<NyaaHtmlSource>(&client, &search(user), nyaa, "nyaa", false, None).await
+        }
+    };
+
+    // Pages which can't be read are loaded from the feed instead
+    let Ok(SourceResponse::Results(res)) = load("someone").await else {
+        panic!("expected results from the feed");
+    };
+    assert!(res.warnings[0].starts_with("Using RSS"));
+
+    // The status is kept, to tell a missing user or an expired login
+    assert!(matches!(
+        load("missing").await,
+        Err(NyaaError::Status { code: 404, .. })
+    ));
+    assert!(matches!(
+        load("private").await,
+        Err(NyaaError::Status { code: 403, .. })
+    ));
+}
@@ -0,0 +1,86 @@
+use std::time::Duration;
+
+use crossterm::event::{Event, KeyCode, KeyEvent};
+use nyaa::{
+    app::{Context, Mode},
+    source::{
+        auth::SourceAuth,
+        nyaa_engine::search_nyaa,
+        nyaa_html::{NyaaConfig, NyaaHtmlSource},
+    },
+    sync::SearchQuery,
+    util::stats,
+    widget::{stats::StatsPopup, Widget as _},
+};
+
+#[allow(dead_code)]
+mod common;
+
+fn respond(request: &str) -> (u16, &'static str, String) {
+    if request.contains("page=rss") {
+        let feed = r#"<rss version="2.0"><channel><title>Nyaa</title><link>/</link><description /></channel></rss>"#;
+        return (200, "", feed.to_owned());
+    }
+    (200, "", "<html>Not a results page</html>".to_owned())
+}
+
+// The stats are global, so everything is checked in one test to keep other
+// tests from recording at the same time
+#[tokio::test]
+async fn test_stats() {
+    stats::reset();
+    stats::record_search("Nyaa", Duration::from_millis(100), true);
+    stats::record_search("Nyaa", Duration::from_millis(300), false);
+    stats::record_search("TorrentGalaxy", Duration::from_millis(50), true);
+    stats::record_download(1024);
+    stats::record_download(512);
+    stats::record_cache_hit();
+
+    // Every request is counted where it's sent, including the feed loaded
+    // when the page can't be read, and logins
+    let base_url = common::serve(respond);
+    let client = reqwest::Client::builder()
+        .cookie_store(true)
+        .build()
+        .unwrap();
+    let nyaa = NyaaConfig {
+        base_url: base_url.clone(),
+        rss_fallback: true,
+        ..Default::default()
+    };
+    let search = SearchQuery {
+        page: 1,
+        ..Default::default()
+    };
+    search_nyaa::<NyaaHtmlSource>(&client, &search, nyaa, "nyaa", false, None)
+        .await
+        .unwrap();
+    let login: SourceAuth = toml::from_str(&format!(
+        "type = \"login\"\nurl = \"{}/login\"\nusername = \"a\"\npassword = \"b\"",
+        base_url
+    ))
+    .unwrap();
+    let _ = login.login(&client, "Nyaa").await;
+
+    let snap = stats::snapshot();
+    assert_eq!(snap.searches, 3);
+    assert_eq!(snap.requests, 3);
+    assert_eq!(snap.cache_hits, 1);
+    assert_eq!(snap.bytes_downloaded, 1536);
+    let nyaa = &snap.sources["Nyaa"];
+    assert_eq!((nyaa.searches, nyaa.failures), (2, 1));
+    assert_eq!(nyaa.average_latency_ms(), 200);
+    assert_eq!(snap.sources["TorrentGalaxy"].average_latency_ms(), 50);
+
+    let json: serde_json::Value = serde_json::from_str(&stats::to_json(&snap)).unwrap();
+    assert_eq!(json["bytes_downloaded"], 1536);
+    assert_eq!(json["sources"]["Nyaa"]["failures"], 1);
+
+    let mut ctx = Context::default();
+    ctx.mode = Mode::Stats;
+    StatsPopup.handle_event(&mut ctx, &Event::Key(KeyEvent::from(KeyCode::Char(':'))));
+    assert!(ctx.mode == Mode::Normal);
+
+    stats::reset();
+    assert_eq!(stats::snapshot(), stats::Stats::default());
+}
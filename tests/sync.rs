@@ -0,0 +1,190 @@
+use std::{sync::Mutex, time::Duration};
+
+use nyaa::{
+    client::{
+        qbit::{QbitClient, QbitConfig},
+        ClientConfig, DownloadClient as _,
+    },
+    source::Item,
+    sync::{
+        spawn_cancellable,
+        tasks::{TaskKind, TaskRegistry},
+    },
+};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+#[allow(dead_code)]
+mod common;
+
+#[tokio::test]
+async fn test_cancel_operation() {
+    let root = CancellationToken::new();
+    let (tx, mut rx) = mpsc::channel::<usize>(1);
+
+    let (slow, fast) = (root.child_token(), root.child_token());
+    let slow_task = spawn_cancellable(slow.clone(), {
+        let tx = tx.clone();
+        async move {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            let _ = tx.send(1).await;
+        }
+    });
+    let fast_task = spawn_cancellable(fast, async move {
+        let _ = tx.send(2).await;
+    });
+
+    // Cancelling one operation leaves the others running
+    slow.cancel();
+    slow_task.await.unwrap();
+    fast_task.await.unwrap();
+    assert_eq!(rx.recv().await, Some(2));
+    assert_eq!(rx.recv().await, None);
+    assert!(!root.is_cancelled());
+}
+
+#[tokio::test]
+async fn test_cancel_all() {
+    let root = CancellationToken::new();
+    let tasks: Vec<_> = (0..3)
+        .map(|_| spawn_cancellable(root.child_token(), std::future::pending()))
+        .collect();
+
+    root.cancel();
+    for task in tasks {
+        tokio::time::timeout(Duration::from_secs(1), task)
+            .await
+            .expect("task should stop once cancelled")
+            .unwrap();
+    }
+}
+
+#[tokio::test]
+async fn test_task_limits() {
+    let mut tasks = TaskRegistry::default();
+    let search = tasks
+        .spawn(
+            TaskKind::Search,
+            "Searching \"one\"",
+            std::future::pending(),
+        )
+        .unwrap();
+    // A new search replaces the one before
+    let next = tasks
+        .spawn(
+            TaskKind::Search,
+            "Searching \"two\"",
+            std::future::pending(),
+        )
+        .unwrap();
+    assert_ne!(search, next);
+    let running = tasks.running();
+    assert_eq!(running.len(), 1);
+    assert_eq!(running[0].name, "Searching \"two\"");
+
+    // Too many downloads aren't started
+    for _ in 0..TaskKind::Download.limit() {
+        tasks
+            .spawn(TaskKind::Download, "Download", std::future::pending())
+            .unwrap();
+    }
+    assert!(tasks
+        .spawn(TaskKind::Download, "Download", std::future::pending())
+        .is_err());
+    assert_eq!(tasks.count(TaskKind::Download), TaskKind::Download.limit());
+
+    assert_eq!(tasks.cancel(next), Some(TaskKind::Search));
+    assert_eq!(tasks.kind(next), None);
+    assert_eq!(
+        tasks.cancel_kind(TaskKind::Download),
+        TaskKind::Download.limit()
+    );
+    assert!(tasks.running().is_empty());
+}
+
+#[tokio::test]
+async fn test_task_registry_drop() {
+    let (tx, mut rx) = mpsc::channel::<usize>(1);
+    let mut tasks = TaskRegistry::default();
+    tasks
+        .spawn(TaskKind::Download, "Download", async move {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            let _ = tx.send(1).await;
+        })
+        .unwrap();
+
+    // Every task stops once the registry is gone
+    drop(tasks);
+    let closed = tokio::time::timeout(Duration::from_secs(1), rx.recv()).await;
+    assert_eq!(closed, Ok(None));
+}
+
+#[tokio::test]
+async fn test_stoppable_task() {
+    let (tx, mut rx) = mpsc::channel::<Vec<usize>>(1);
+    let mut tasks = TaskRegistry::default();
+    tasks
+        .spawn_stoppable(TaskKind::Download, "Download batch", |stop| async move {
+            // Each item is sent whole, and the rest are skipped once stopped
+            let mut sent = vec![];
+            for i in 0..10 {
+                if stop.is_cancelled() {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                sent.push(i);
+            }
+            let _ = tx.send(sent).await;
+        })
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    // The task is waited for, and still reports what it did
+    tasks.join_kind(TaskKind::Download).await;
+    let sent = rx.try_recv().unwrap();
+    assert!(!sent.is_empty() && sent.len() < 10);
+}
+
+static QBIT_REQUESTS: Mutex<Vec<String>> = Mutex::new(vec![]);
+
+#[tokio::test]
+async fn test_qbit_watcher() {
+    fn respond(request: &str) -> (u16, &'static str, String) {
+        QBIT_REQUESTS.lock().unwrap().push(request.to_owned());
+        match request {
+            r if r.contains("/auth/login") => {
+                (200, "Set-Cookie: SID=abc; path=/\r\n", "Ok.".to_owned())
+            }
+            // Never finishes, so it's polled until stopped
+            r if r.contains("/torrents/info") => (200, "", "[]".to_owned()),
+            _ => (200, "", "Ok.".to_owned()),
+        }
+    }
+    let conf = ClientConfig {
+        qbit: Some(QbitConfig {
+            base_url: common::serve(respond),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let item = Item {
+        magnet_link: format!("magnet:?xt=urn:btih:{}", "a".repeat(40)),
+        ..Default::default()
+    };
+    let mut res = QbitClient::download(item, conf, reqwest::Client::new()).await;
+    assert!(res.errors.is_empty());
+
+    // Left for the app to start, so stopping downloads stops it too
+    let watcher = res.watchers.pop().unwrap();
+    let mut tasks = TaskRegistry::default();
+    tasks
+        .spawn_stoppable(TaskKind::Download, watcher.name.clone(), |stop| {
+            watcher.start(stop)
+        })
+        .unwrap();
+    tokio::time::timeout(Duration::from_secs(1), tasks.join_kind(TaskKind::Download))
+        .await
+        .expect("watcher should stop once cancelled");
+    let requests = QBIT_REQUESTS.lock().unwrap();
+    assert!(requests.last().unwrap().contains("/auth/logout"));
+}
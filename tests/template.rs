@@ -0,0 +1,22 @@
+use std::collections::HashMap;
+
+use nyaa::util::template::{expand, variables};
+
+#[test]
+fn test_template() {
+    let template = "{title} S{season:02} {resolution} {{batch}}";
+    assert_eq!(variables(template), ["title", "season", "resolution"]);
+    assert!(variables("one punch man").is_empty());
+    assert!(variables("{not a var} {1}").is_empty());
+
+    let values = HashMap::from([
+        ("title".to_owned(), "Frieren".to_owned()),
+        ("season".to_owned(), "1".to_owned()),
+    ]);
+    assert_eq!(expand(template, &values), "Frieren S01 {batch}");
+    assert_eq!(expand("{title} {title}", &values), "Frieren Frieren");
+    // Text is never padded, only numbers
+    assert_eq!(expand("{title:05}", &values), "Frieren");
+    // Plain queries are searched as they are
+    assert_eq!(expand(" {{one}}  punch ", &values), " {{one}}  punch ");
+}
@@ -0,0 +1,72 @@
+use std::path::Path;
+
+use nyaa::util::update::{
+    asset_name, is_newer, latest_release_url, package_manager, parse_version, replace_exe, verify,
+};
+
+#[test]
+fn test_versions() {
+    assert_eq!(parse_version("v1.10.0"), vec![1, 10, 0]);
+    assert!(is_newer("v0.10.0", "0.9.0"));
+    assert!(is_newer("1.0.0", "0.9.9"));
+    assert!(!is_newer("v0.9.0", "0.9.0"));
+    assert!(!is_newer("0.8.5", "0.9.0"));
+
+    assert_eq!(asset_name("0.9.0"), "nyaa-0.9.0-x86_64-unknown-linux-gnu");
+}
+
+#[test]
+fn test_release_url() {
+    assert_eq!(
+        latest_release_url("https://github.com/Beastwick18/nyaa/").as_deref(),
+        Some("https://api.github.com/repos/Beastwick18/nyaa/releases/latest")
+    );
+    assert_eq!(
+        latest_release_url("https://github.com/someone/nyaa.git").as_deref(),
+        Some("https://api.github.com/repos/someone/nyaa/releases/latest")
+    );
+    assert_eq!(latest_release_url("https://gitlab.com/someone/nyaa"), None);
+    assert!(latest_release_url(env!("CARGO_PKG_REPOSITORY")).is_some());
+}
+
+#[test]
+fn test_package_manager() {
+    let manager = |p| package_manager(Path::new(p));
+    assert_eq!(manager("/nix/store/abc-nyaa-0.9.0/bin/nyaa"), Some("Nix"));
+    assert_eq!(
+        manager("/usr/bin/nyaa-test-binary"),
+        Some("the system package manager")
+    );
+    assert_eq!(manager("/usr/local/bin/nyaa-test-binary"), None);
+    assert_eq!(manager("/home/someone/.cargo/bin/nyaa"), None);
+}
+
+#[test]
+fn test_verify() {
+    // sha256 of "hello"
+    let sum = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+    assert!(verify(b"hello", sum).is_ok());
+    assert!(verify(b"hello", &format!("{}  nyaa-0.9.0\n", sum.to_uppercase())).is_ok());
+    assert!(verify(b"hello!", sum).is_err());
+    assert!(verify(b"hello", "").is_err());
+}
+
+#[test]
+fn test_replace_exe() {
+    let dir = std::env::temp_dir().join("nyaa-test-update");
+    std::fs::create_dir_all(&dir).unwrap();
+    let exe = dir.join("nyaa");
+    std::fs::write(&exe, "old").unwrap();
+
+    replace_exe(&exe, b"new").unwrap();
+    assert_eq!(std::fs::read_to_string(&exe).unwrap(), "new");
+    assert!(!exe.with_extension("new").exists());
+    assert!(!exe.with_extension("old").exists());
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt as _;
+        let mode = std::fs::metadata(&exe).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o755);
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,182 @@
+use nyaa::config::validate::{check, Problem};
+
+fn paths(problems: &[Problem]) -> Vec<&str> {
+    problems.iter().map(|p| p.path.as_str()).collect()
+}
+
+#[test]
+fn test_invalid_values() {
+    let report = check(
+        r#"
+        default_source = "Piratebay"
+        timeout = "soon"
+
+        [source.nyaa]
+        default_sort = "Popularity"
+        "#,
+    );
+    assert_eq!(
+        paths(&report.errors),
+        ["default_source", "source.nyaa.default_sort", "timeout"]
+    );
+    assert!(report.errors[0].msg.contains("unknown variant `Piratebay`"));
+    assert!(report.warnings.is_empty());
+
+    let report = check("timeout = 30\n[layout\nthree_pane = true");
+    assert_eq!(paths(&report.errors), ["line 2"]);
+}
+
+#[test]
+fn test_warnings() {
+    let report = check(
+        r#"
+        default_theme = "Dracula"
+        colour = "red"
+        request_proxy = "socks5://proxy host:1080"
+
+        [layout]
+        three_pane = true
+        min_width = 80
+
+        [client.qBittorrent]
+        base_url = "http://localhost:8080"
+        passwrod = "hunter2"
+
+        [[source.custom_nyaa]]
+        name = "Nyaa"
+        base_url = "nyaa.example.com"
+        "#,
+    );
+    assert!(report.errors.is_empty());
+    assert_eq!(
+        paths(&report.warnings),
+        [
+            "client.qBittorrent.passwrod",
+            "colour",
+            "request_proxy",
+            "layout.min_width",
+            "source.custom_nyaa[0].name",
+        ]
+    );
+    assert_eq!(
+        report.to_string().lines().nth(2),
+        Some("- colour: Unknown key, which is ignored")
+    );
+    assert!(check("").is_empty());
+}
+
+#[test]
+fn test_bad_headers() {
+    let report = check(
+        r#"
+        [source.torrentgalaxy.headers]
+        user_agents = ["Mozilla/5.0", "Bad\nAgent"]
+        "#,
+    );
+    assert!(report.errors.is_empty());
+    assert_eq!(
+        paths(&report.warnings),
+        ["source.torrentgalaxy.headers.user_agents[1]"]
+    );
+}
+
+#[test]
+fn test_download_url_template() {
+    let report = check(
+        r#"
+        [source.nyaa]
+        download_url_template = "/download/{id}.torrent"
+
+        [source.sukebei]
+        download_url_template = "/download/latest.torrent"
+        "#,
+    );
+    assert_eq!(
+        paths(&report.warnings),
+        ["source.sukebei.download_url_template"]
+    );
+}
+
+#[test]
+fn test_size_format() {
+    let report = check(
+        r#"
+        [size_format]
+        units = "decimal"
+        decimals = 5
+        "#,
+    );
+    assert!(report.errors.is_empty());
+    assert_eq!(paths(&report.warnings), ["size_format.decimals"]);
+    assert!(!check("[size_format]\nunits = \"metric\"").errors.is_empty());
+}
+
+#[test]
+fn test_webhooks() {
+    let report = check(
+        r#"
+        [[webhooks]]
+        url = "https://discord.com/api/webhooks/1/abc"
+        kind = "discord"
+
+        [[webhooks]]
+        url = "hooks.example.com/{event}"
+        on_error = true
+        "#,
+    );
+    assert!(report.errors.is_empty());
+    assert_eq!(paths(&report.warnings), ["webhooks[1].url"]);
+    assert!(!check("[[webhooks]]\nkind = \"teams\"").errors.is_empty());
+}
+
+#[test]
+fn test_source_auth() {
+    let report = check(
+        r#"
+        [source.auth.Nyaa]
+        type = "login"
+        url = "https://nyaa.si/login"
+        username = "user"
+        password = "env:NYAA_TEST_UNSET_PASSWORD"
+
+        [source.auth.Piratebay]
+        type = "token"
+        token = "abc"
+        "#,
+    );
+    assert!(report.errors.is_empty());
+    assert_eq!(
+        paths(&report.warnings),
+        ["source.auth.Nyaa.password", "source.auth.Piratebay"]
+    );
+    assert!(!check("[source.auth.Nyaa]\ntype = \"oauth\"")
+        .errors
+        .is_empty());
+}
+
+#[test]
+fn test_chords() {
+    let report = check(
+        r#"
+        [chords]
+        "<leader>m" = "copy_magnet"
+        "#,
+    );
+    assert!(report.errors.is_empty());
+    assert_eq!(paths(&report.warnings), ["chords"]);
+    assert!(report.warnings[0].msg.contains("no leader is set"));
+
+    let report = check(
+        r#"
+        leader = " "
+
+        [chords]
+        "<leader>m" = "copy_magnet"
+        "<leader>d" = "deep_search"
+        "#,
+    );
+    assert!(report.warnings.is_empty());
+
+    let report = check("[chords]\ngg = \"top\"");
+    assert_eq!(paths(&report.errors), ["chords.gg"]);
+}
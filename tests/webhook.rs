@@ -0,0 +1,51 @@
+use nyaa::util::webhook::{WebhookConfig, WebhookEvent, WebhookKind, WebhookMessage};
+
+fn message(event: WebhookEvent) -> WebhookMessage {
+    WebhookMessage {
+        event,
+        message: "Sent 2 torrents to qBittorrent".to_owned(),
+        titles: vec!["One".to_owned(), "Two".to_owned()],
+    }
+}
+
+#[test]
+fn test_webhook_config() {
+    let hook: WebhookConfig = toml::from_str(r#"url = "https://example.com/hook""#).unwrap();
+    assert_eq!(hook.kind, WebhookKind::Json);
+    assert!(hook.wants(WebhookEvent::Download));
+    assert!(hook.wants(WebhookEvent::NewResults));
+    assert!(!hook.wants(WebhookEvent::Error));
+}
+
+#[test]
+fn test_webhook_templates() {
+    let hook = WebhookConfig {
+        url: "https://example.com/nyaa/{event}".to_owned(),
+        kind: WebhookKind::Discord,
+        template: Some("[{event}] {message} ({count})".to_owned()),
+        ..Default::default()
+    };
+    let msg = message(WebhookEvent::Download);
+    assert_eq!(hook.url(&msg), "https://example.com/nyaa/download");
+    assert_eq!(
+        hook.text(&msg),
+        "[download] Sent 2 torrents to qBittorrent (2)"
+    );
+
+    let plain = WebhookConfig {
+        url: "https://example.com/hook".to_owned(),
+        ..Default::default()
+    };
+    let msg = message(WebhookEvent::NewResults);
+    assert_eq!(plain.url(&msg), "https://example.com/hook");
+    assert_eq!(plain.text(&msg), "Sent 2 torrents to qBittorrent");
+}
+
+#[test]
+fn test_webhook_host() {
+    let hook = WebhookConfig {
+        url: "https://discord.com/api/webhooks/123/secret-token".to_owned(),
+        ..Default::default()
+    };
+    assert_eq!(hook.host(), "https://discord.com");
+}
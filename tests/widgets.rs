@@ -0,0 +1,937 @@
+use crossterm::event::{Event, KeyCode, KeyEvent};
+use nyaa::{
+    app::{App, Context, LoadType, Mode},
+    client::{qbit::QbitConfig, Client, DownloadOptions},
+    config::{state::State, Config, OnStartup},
+    locale::Locale,
+    results::{history::History, EmptyReason, ResultResponse, ResultRow, ResultTable, Results},
+    source::{mock::MockSource, Item, ItemId, ItemType, Source as _, Sources},
+    sync::SearchQuery,
+    theme::{default_themes, Theme, WidgetKind},
+    util::complete::PrefixIndex,
+    widget::{
+        self,
+        category::CategoryPopup,
+        columns::ColumnsPopup,
+        combo::{ChordAction, Chords, ComboPopup},
+        confirm::ConfirmPopup,
+        dashboard::DashboardWidget,
+        details::DetailsWidget,
+        error::{ErrorMsg, ErrorPopup},
+        filter::{filter_bit, FilterPopup},
+        focus::{Focus, Pane},
+        help::{self, HelpPopup},
+        hints::HintBar,
+        results::ResultsWidget,
+        search::SearchWidget,
+        sort::{SelectedSort, SortDir, SortPopup},
+        themes::ThemePopup,
+        user::SearchPreset,
+        watch_later::WatchLaterPopup,
+        whats_new::{self, WhatsNewPopup},
+        Widget as _,
+    },
+};
+use ratatui::{
+    backend::TestBackend,
+    buffer::Buffer,
+    layout::{Constraint, Rect},
+    style::{Color, Modifier},
+    widgets::Widget as _,
+    Terminal,
+};
+
+use crate::common::{
+    reset_buffer,
+    snapshot::{assert_snapshot, buffer_lines, render},
+    TestConfig,
+};
+
+#[allow(dead_code)]
+mod common;
+
+#[test]
+fn test_sort_popup() {
+    let mut ctx = Context::default();
+    ctx.mode = Mode::Sort(SortDir::Desc);
+    let buf = render(&mut SortPopup::default(), &ctx, 40, 12);
+    assert_snapshot("sort_popup", &buf);
+
+    ctx.mode = Mode::Sort(SortDir::Asc);
+    let buf = render(&mut SortPopup::default(), &ctx, 40, 12);
+    assert_snapshot("sort_popup_asc", &buf);
+}
+
+#[test]
+fn test_sort_cycle() {
+    let mut ctx = Context::default();
+    ctx.mode = Mode::Sort(SortDir::Desc);
+    let default = ctx.src.default_sort(&ctx.config.sources);
+    let mut popup = SortPopup {
+        selected: default,
+        ..Default::default()
+    };
+    let sort = |sort, dir| SelectedSort { sort, dir };
+
+    popup.handle_event(&mut ctx, &Event::Key(KeyEvent::from(KeyCode::Char('j'))));
+    let mut seen = vec![];
+    for _ in 0..4 {
+        popup.handle_event(&mut ctx, &Event::Key(KeyEvent::from(KeyCode::Char(' '))));
+        assert!(ctx.mode == Mode::Loading(LoadType::Sorting));
+        assert_eq!(popup.table.state.selected(), Some(popup.selected.sort));
+        seen.push(popup.selected);
+    }
+    assert_eq!(
+        seen,
+        vec![
+            sort(1, SortDir::Asc),
+            sort(1, SortDir::Desc),
+            default,
+            sort(0, SortDir::Asc),
+        ]
+    );
+    assert_eq!(default.cycle(0, default), sort(0, SortDir::Asc));
+    assert_eq!(sort(0, SortDir::Asc).cycle(0, default), default);
+}
+
+#[test]
+fn test_filter_popup() {
+    let mut ctx = Context::default();
+    ctx.mode = Mode::Filter;
+    let buf = render(&mut FilterPopup::default(), &ctx, 40, 12);
+    assert_snapshot("filter_popup", &buf);
+}
+
+#[test]
+fn test_multi_filter_popup() {
+    let mut ctx = Context::default();
+    ctx.mode = Mode::Filter;
+    ctx.src = Sources::TorrentGalaxy;
+    ctx.src_info = ctx.src.info(&ctx.config.sources);
+    let mut popup = FilterPopup::default();
+    popup.select(&ctx, 0);
+    let key = |c| Event::Key(KeyEvent::from(c));
+
+    // Checking filters doesn't search until confirmed
+    popup.handle_event(&mut ctx, &key(KeyCode::Char('j')));
+    popup.handle_event(&mut ctx, &key(KeyCode::Char(' ')));
+    popup.handle_event(&mut ctx, &key(KeyCode::Char('j')));
+    popup.handle_event(&mut ctx, &key(KeyCode::Char(' ')));
+    assert!(ctx.mode == Mode::Filter);
+    assert_eq!(popup.selected, 0);
+    let buf = render(&mut popup, &ctx, 40, 12);
+    assert_snapshot("filter_popup_multi", &buf);
+
+    popup.handle_event(&mut ctx, &key(KeyCode::Enter));
+    assert!(ctx.mode == Mode::Loading(LoadType::Filtering));
+    assert_eq!(popup.selected, filter_bit(1) | filter_bit(2));
+    assert_eq!(popup.row(&ctx), 1);
+
+    // Reopening starts from what was searched, and no filter clears the rest
+    ctx.mode = Mode::Filter;
+    popup.checked = 0;
+    popup.open();
+    assert_eq!(popup.checked, popup.selected);
+    popup.handle_event(&mut ctx, &key(KeyCode::Char('g')));
+    popup.handle_event(&mut ctx, &key(KeyCode::Char(' ')));
+    popup.handle_event(&mut ctx, &key(KeyCode::Enter));
+    assert_eq!(popup.selected, 0);
+
+    assert_eq!(FilterPopup::from_row(&ctx, 3), filter_bit(3));
+    ctx.src_info = Sources::Nyaa.info(&ctx.config.sources);
+    assert_eq!(FilterPopup::from_row(&ctx, 3), 3);
+}
+
+#[test]
+fn test_columns_popup() {
+    let mut ctx = Context::default();
+    ctx.mode = Mode::Columns;
+    let mut popup = ColumnsPopup::default();
+    popup.table.select(1);
+    popup.handle_event(&mut ctx, &Event::Key(KeyEvent::from(KeyCode::Enter)));
+    let buf = render(&mut popup, &ctx, 40, 15);
+    assert_snapshot("columns_popup", &buf);
+
+    // Shown again, and the table follows the config
+    popup.handle_event(&mut ctx, &Event::Key(KeyEvent::from(KeyCode::Char(' '))));
+    assert_eq!(
+        ctx.src.columns(&ctx.config.sources).unwrap()[1],
+        ("Status", false)
+    );
+}
+
+#[test]
+fn test_watch_later_popup() {
+    let items: Vec<Item> = (1..=3)
+        .map(|num| Item {
+            id: ItemId::new("nyaa", num),
+            title: format!("Show - 0{}", num),
+            bytes: 1024 * 1024 * num as u64,
+            seeders: num as u32,
+            ..Default::default()
+        })
+        .collect();
+    let mut ctx = Context::default();
+    for item in items.iter() {
+        ctx.toggle_watch_later(item);
+    }
+    // Added again to remove it
+    ctx.toggle_watch_later(&items[2]);
+    assert_eq!(ctx.state.watch_later.len(), 2);
+
+    ctx.mode = Mode::WatchLater;
+    let mut popup = WatchLaterPopup::default();
+    popup.table.select(0);
+    let buf = render(&mut popup, &ctx, 50, 8);
+    assert_snapshot("watch_later_popup", &buf);
+
+    // Kept in the state file, and read back into the batch with the same id
+    let text = toml::to_string(&ctx.state).unwrap();
+    let state: State = toml::from_str(&text).unwrap();
+    assert!(state.watch_later == ctx.state.watch_later);
+    popup.handle_event(&mut ctx, &Event::Key(KeyEvent::from(KeyCode::Enter)));
+    assert_eq!(ctx.batch.len(), 1);
+    assert_eq!(ctx.batch[0].id, items[0].id);
+    assert_eq!(ctx.state.watch_later.len(), 1);
+
+    let mut refreshed = ctx.state.watch_later.clone();
+    refreshed[0].seeders = 10;
+    ctx.update_watch_later(refreshed);
+    assert_eq!(ctx.state.watch_later[0].seeders, 10);
+
+    popup.handle_event(&mut ctx, &Event::Key(KeyEvent::from(KeyCode::Char('a'))));
+    assert_eq!(ctx.batch.len(), 2);
+    assert!(ctx.state.watch_later.is_empty());
+    assert_eq!(ItemId::parse("nyaa-2"), Some(items[1].id.clone()));
+}
+
+#[test]
+fn test_unfinished_batches() {
+    let items: Vec<Item> = (1..=4)
+        .map(|num| Item {
+            id: ItemId::new("nyaa", num),
+            title: format!("Show - 0{}", num),
+            ..Default::default()
+        })
+        .collect();
+    let mut ctx = Context::default();
+    let first = ctx.save_unfinished_batch(&items[..2]);
+    let second = ctx.save_unfinished_batch(&items[1..]);
+    assert_ne!(first, second);
+    // Each item is only kept with the batch sent last
+    assert_eq!(ctx.state.batches[0].items.len(), 1);
+    assert_eq!(ctx.state.batches[1].items.len(), 3);
+
+    ctx.forget_sent(second, &[items[1].id.clone(), items[2].id.clone()]);
+    ctx.forget_sent(first, &[items[0].id.clone()]);
+    assert_eq!(ctx.state.batches.len(), 1);
+    assert_eq!(ctx.state.batches[0].id, second);
+
+    // What's left is put back in the batch after a restart
+    let text = toml::to_string(&ctx.state).unwrap();
+    let mut ctx = Context::default();
+    ctx.state = toml::from_str(&text).unwrap();
+    ctx.restore_unfinished_batches();
+    assert_eq!(ctx.batch.len(), 1);
+    assert_eq!(ctx.batch[0].id, items[3].id);
+    // A new batch never reuses the id of one which is saved
+    assert!(ctx.save_unfinished_batch(&items[..1]) > second);
+
+    // Taken out of the batch, so not put back next time
+    ctx.batch.clear();
+    ctx.prune_unfinished_batches();
+    assert!(ctx.state.batches.is_empty());
+}
+
+#[test]
+fn test_theme_popup() {
+    let mut ctx = Context::default();
+    ctx.mode = Mode::Theme;
+    let buf = render(&mut ThemePopup::default(), &ctx, 40, 10);
+    assert_snapshot("theme_popup", &buf);
+}
+
+#[test]
+fn test_widget_colors() {
+    let theme: Theme = toml::from_str(
+        r##"
+        name = "Accents"
+        bg = "Reset"
+        fg = "White"
+        border = "Plain"
+        border_color = "White"
+        border_focused_color = "LightCyan"
+        hl_bg = "DarkGray"
+        solid_bg = "White"
+        solid_fg = "Black"
+        trusted = "Green"
+        remake = "Red"
+
+        [widgets.batch]
+        border_focused = "Magenta"
+        title = "#ff00ff"
+
+        [widgets.error]
+        border = "Yellow"
+        "##,
+    )
+    .unwrap();
+    let draw = |kind, focused| {
+        let mut buf = Buffer::empty(Rect::new(0, 0, 10, 3));
+        widget::widget_block(&theme, focused, kind)
+            .title("Title")
+            .render(buf.area, &mut buf);
+        (buf.get(0, 0).fg, buf.get(1, 0).fg)
+    };
+
+    assert_eq!(
+        draw(WidgetKind::Batch, true),
+        (Color::Magenta, Color::Rgb(255, 0, 255))
+    );
+    // Unset colors fall back to the theme's own
+    assert_eq!(draw(WidgetKind::Batch, false).0, Color::White);
+    assert_eq!(
+        draw(WidgetKind::Results, true),
+        (Color::LightCyan, Color::LightCyan)
+    );
+    assert_eq!(draw(WidgetKind::Error, true).0, Color::Red);
+    assert_eq!(draw(WidgetKind::Error, false).0, Color::Yellow);
+    assert_eq!(
+        theme.border_of(WidgetKind::Notification, true),
+        Color::LightCyan
+    );
+}
+
+#[test]
+fn test_whats_new() {
+    assert_eq!(whats_new::since("0.8.1", "0.9.0").len(), 1);
+    assert!(whats_new::since("0.9.0", "0.9.0").is_empty());
+    // Releases newer than the one running aren't listed
+    assert!(whats_new::since("0.8.1", "0.8.2").is_empty());
+
+    let mut popup = WhatsNewPopup::default();
+    popup.open("0.8.1", "0.9.0");
+    assert!(popup.take_pending());
+    assert!(!popup.take_pending());
+
+    let mut ctx = Context::default();
+    ctx.mode = Mode::WhatsNew;
+    let buf = render(&mut popup, &ctx, 70, 16);
+    assert_snapshot("whats_new_popup", &buf);
+}
+
+#[test]
+fn test_theme_preview() {
+    let mut ctx = Context::default();
+    ctx.mode = Mode::Theme;
+    let mut popup = ThemePopup::default();
+    let default = ctx.theme.name.clone();
+
+    // Moving previews the highlighted theme
+    popup.handle_event(&mut ctx, &Event::Key(KeyEvent::from(KeyCode::Char('j'))));
+    assert_eq!(ctx.theme.name, ctx.themes[1].name);
+    assert_eq!(ctx.config.theme, default);
+
+    // Cancelling goes back to the theme in use before
+    popup.handle_event(&mut ctx, &Event::Key(KeyEvent::from(KeyCode::Esc)));
+    assert_eq!(ctx.theme.name, default);
+    assert_eq!(popup.table.selected(), Some(0));
+}
+
+#[test]
+fn test_accessible_themes() {
+    let themes = default_themes();
+    let item = Item {
+        title: "Frieren - 01".to_owned(),
+        item_type: ItemType::Trusted,
+        ..Default::default()
+    };
+    assert_eq!(
+        themes["High Contrast"].item_title(&item).content,
+        "Frieren - 01"
+    );
+
+    let mono = &themes["Monochrome"];
+    assert_eq!(mono.item_title(&item).content, "[T] Frieren - 01");
+
+    // Highlights are reversed once the colors are gone
+    let mut buf = Buffer::empty(Rect::new(0, 0, 2, 1));
+    buf.get_mut(0, 0).set_fg(Color::Green);
+    buf.get_mut(1, 0).set_bg(mono.hl_bg);
+    mono.to_monochrome(&mut buf);
+    assert_eq!(buf.get(0, 0).fg, Color::Reset);
+    assert!(!buf.get(0, 0).modifier.contains(Modifier::REVERSED));
+    assert_eq!(buf.get(1, 0).bg, Color::Reset);
+    assert!(buf.get(1, 0).modifier.contains(Modifier::REVERSED));
+}
+
+#[test]
+fn test_error_popup() {
+    let mut ctx = Context::default();
+    let mut popup = ErrorPopup::default();
+    popup.add(ErrorMsg {
+        msg: "Failed to load results:\nhttps://nyaa.si/?q=frieren\nInvalid response code: 503 Service Unavailable, the server is currently unable to handle the request".to_owned(),
+        chain: vec![
+            "client error (Connect)".to_owned(),
+            "connection refused".to_owned(),
+        ],
+    });
+    popup.add(ErrorMsg::new("Second error"));
+    let buf = render(&mut popup, &ctx, 40, 8);
+    assert_snapshot("error_popup", &buf);
+
+    let key = |c| Event::Key(KeyEvent::from(KeyCode::Char(c)));
+    popup.handle_event(&mut ctx, &key('d'));
+    popup.handle_event(&mut ctx, &key('G'));
+    let buf = render(&mut popup, &ctx, 40, 8);
+    assert_snapshot("error_popup_details", &buf);
+
+    popup.handle_event(&mut ctx, &Event::Key(KeyEvent::from(KeyCode::Esc)));
+    let buf = render(&mut popup, &ctx, 40, 8);
+    assert_snapshot("error_popup_next", &buf);
+
+    popup.handle_event(&mut ctx, &Event::Key(KeyEvent::from(KeyCode::Esc)));
+    assert!(!popup.is_open());
+
+    // Every error can be dismissed at once
+    for i in 0..3 {
+        popup.add(ErrorMsg::new(format!("Error {}", i)));
+    }
+    popup.handle_event(&mut ctx, &key('D'));
+    assert!(!popup.is_open());
+}
+
+#[test]
+fn test_help_search() {
+    let mut ctx = Context::default();
+    ctx.mode = Mode::Help;
+    let mut popup = HelpPopup::default();
+    popup.with_items(
+        vec![
+            ("Enter", "Confirm"),
+            ("Esc, s, q", "Close"),
+            ("j, ↓", "Down"),
+            ("k, ↑", "Up"),
+        ],
+        Mode::Sort(SortDir::Desc),
+    );
+    let key = |c| Event::Key(KeyEvent::from(KeyCode::Char(c)));
+    for c in "/clo".chars() {
+        popup.handle_event(&mut ctx, &key(c));
+    }
+    assert_eq!(popup.table.items, [("Esc, s, q", "Close")]);
+    let buf = render(&mut popup, &ctx, 40, 8);
+    assert_snapshot("help_search", &buf);
+
+    // Keep the filter, then clear it
+    popup.handle_event(&mut ctx, &Event::Key(KeyEvent::from(KeyCode::Enter)));
+    assert_eq!(popup.table.items.len(), 1);
+    popup.handle_event(&mut ctx, &Event::Key(KeyEvent::from(KeyCode::Esc)));
+    assert_eq!(popup.table.items.len(), 4);
+    assert!(ctx.mode == Mode::Help);
+}
+
+#[test]
+fn test_export_keybinds() {
+    let md = help::to_markdown(&App::default().keybinds());
+    assert!(md.starts_with("# Keybinds\n\n## Normal\n| Key | Map |\n| --- | --- |\n"));
+    assert!(md.contains("\n## User\n"));
+    assert!(md.contains("| Esc, s, q | Close |"));
+}
+
+#[test]
+fn test_usage_and_man() {
+    let keybinds = App::default().keybinds();
+    let usage = help::to_usage(&keybinds);
+    assert!(usage.contains("\nOptions:\n  -h, --help  "));
+    assert!(usage.contains("\nUser keybinds:\n"));
+    for (key, map) in keybinds.iter().flat_map(|(_, b)| b) {
+        assert!(usage.contains(key) && usage.contains(map));
+    }
+
+    let man = help::to_man(&keybinds);
+    assert!(man.starts_with(".TH NYAA 1 "));
+    assert!(man.contains("\n.SS Normal\n.TP\n.B Enter\nConfirm\n"));
+    assert!(man.contains("\n.B \\-\\-export\\-man <file>\n"));
+    assert!(man.contains("\n.B self\\-update\n"));
+}
+
+#[test]
+fn test_hint_bar() {
+    let ctx = Context::default();
+    let mut hints = HintBar::default();
+    hints.with_items(
+        vec![
+            ("Enter", "Confirm"),
+            ("Esc, s, q", "Close"),
+            ("j, ↓", "Down"),
+            ("k, ↑", "Up"),
+            ("g", "Top"),
+            ("G", "Bottom"),
+        ],
+        true,
+    );
+    assert_eq!(hints.hints.len(), 6);
+    assert_eq!(hints.hints[5], ("F1", "Help"));
+    let buf = render(&mut hints, &ctx, 80, 1);
+    assert_snapshot("hint_bar", &buf);
+
+    // Hints that don't fit are left out
+    let buf = render(&mut hints, &ctx, 40, 1);
+    assert_snapshot("hint_bar_narrow", &buf);
+}
+
+#[test]
+fn test_locale() {
+    assert_eq!(Locale::Ja.tr("Close"), "閉じる");
+    assert_eq!(Locale::En.tr("Close"), "Close");
+    // Missing entries fall back to English
+    assert_eq!(Locale::Ja.tr("Not translated"), "Not translated");
+
+    let mut ctx = Context::default();
+    ctx.config.locale = Locale::Ja;
+    ctx.mode = Mode::Sort(SortDir::Desc);
+    let buf = render(&mut SortPopup::default(), &ctx, 40, 12);
+    assert_snapshot("sort_popup_ja", &buf);
+}
+
+#[test]
+fn test_confirm_popup() {
+    let mut ctx = Context::default();
+    ctx.mode = Mode::Confirm(LoadType::Batching);
+    ctx.client = Client::Qbit;
+    ctx.config.client.qbit = Some(QbitConfig {
+        savepath: Some("/data/anime".to_owned()),
+        category: Some("anime".to_owned()),
+        ..Default::default()
+    });
+    ctx.batch = MockSource::items(&SearchQuery {
+        query: "frieren".to_owned(),
+        ..Default::default()
+    });
+
+    let mut popup = ConfirmPopup::default();
+    popup.open(&ctx, ctx.batch.clone());
+    let buf = render(&mut popup, &ctx, 60, 10);
+    assert_snapshot("confirm_popup", &buf);
+
+    // Overrides only apply to the one download
+    popup.label.input = "airing".to_owned();
+    let opts = popup.options(&ctx);
+    let mut conf = ctx.config.client.clone();
+    opts.apply(ctx.client, &mut conf);
+    assert_eq!(
+        DownloadOptions::from_config(ctx.client, &conf),
+        DownloadOptions {
+            save_path: Some("/data/anime".to_owned()),
+            label: Some("airing".to_owned()),
+            paused: Some(false),
+        }
+    );
+    assert_eq!(
+        ctx.config
+            .client
+            .qbit
+            .as_ref()
+            .and_then(|q| q.category.to_owned()),
+        Some("anime".to_owned())
+    );
+
+    // A single download starts from the options last confirmed
+    popup.paused = true;
+    popup.handle_event(&mut ctx, &Event::Key(KeyEvent::from(KeyCode::Enter)));
+    assert!(ctx.mode == Mode::Loading(LoadType::Batching));
+    ctx.mode = Mode::Confirm(LoadType::Downloading);
+    popup.open(&ctx, ctx.batch[..1].to_vec());
+    let buf = render(&mut popup, &ctx, 60, 10);
+    assert_snapshot("confirm_popup_single", &buf);
+}
+
+#[test]
+fn test_on_startup() {
+    let config: Config = toml::from_str(
+        r#"
+        on_startup = { preset = "Subs" }
+
+        [[presets]]
+        name = "Subs"
+        source = "TorrentGalaxy"
+        user = "subsplease"
+        "#,
+    )
+    .unwrap();
+    assert_eq!(config.on_startup, OnStartup::Preset("Subs".to_owned()));
+
+    let mut app = App::default();
+    let mut ctx = Context::default();
+    config
+        .apply::<TestConfig>(&mut ctx, &mut app.widgets)
+        .unwrap();
+    assert!(ctx.src == Sources::TorrentGalaxy);
+    assert_eq!(ctx.user, Some("subsplease".to_owned()));
+    assert!(ctx.mode == Mode::Loading(LoadType::Searching));
+
+    let config: Config = toml::from_str(r#"on_startup = "idle""#).unwrap();
+    let mut ctx = Context::default();
+    config
+        .apply::<TestConfig>(&mut ctx, &mut app.widgets)
+        .unwrap();
+    assert!(ctx.mode == Mode::Normal);
+}
+
+#[test]
+fn test_dashboard() {
+    let mut ctx = Context::default();
+    ctx.remember_search("frieren");
+    ctx.remember_search("one punch man");
+    ctx.remember_search(" frieren ");
+    ctx.remember_search("");
+    assert_eq!(ctx.state.recent_searches, ["frieren", "one punch man"]);
+
+    ctx.config.presets = vec![SearchPreset {
+        name: "Subs".to_owned(),
+        source: Sources::Nyaa,
+        query: None,
+        user: Some("subsplease".to_owned()),
+        category: String::new(),
+        filter: 0,
+    }];
+    let buf = render(&mut DashboardWidget, &ctx, 40, 16);
+    assert_snapshot("dashboard", &buf);
+}
+
+#[test]
+fn test_details() {
+    let config: Config = toml::from_str(
+        r#"
+        [layout]
+        three_pane = true
+        "#,
+    )
+    .unwrap();
+    assert!(config.layout.three_pane);
+    assert_eq!(config.layout.min_width, 160);
+
+    let mut ctx = Context::default();
+    ctx.mode = Mode::Details;
+    let mut details = DetailsWidget::default();
+    details.with_item(
+        MockSource::items(&SearchQuery {
+            query: "frieren".to_owned(),
+            ..Default::default()
+        })
+        .first()
+        .cloned(),
+    );
+    let buf = render(&mut details, &ctx, 40, 14);
+    assert_snapshot("details", &buf);
+}
+
+#[test]
+fn test_narrow_layout() {
+    let search = SearchQuery {
+        query: "frieren".to_owned(),
+        page: 1,
+        ..Default::default()
+    };
+    let items = MockSource::items(&search);
+    let mut ctx = Context::default();
+    ctx.mode = Mode::Normal;
+    ctx.searched = true;
+    let table = MockSource::format_table(&items, &search, &ctx.config.sources, &ctx.theme);
+    let response = ResultResponse {
+        items: items.clone(),
+        total_results: Some(items.len()),
+        last_page: 1,
+        ..Default::default()
+    };
+    ctx.results = Results::new(search, response, table);
+    ctx.batch = items[..2].to_vec();
+
+    let mut app = App::default();
+    let mut draw = |ctx: &mut Context| {
+        let mut terminal = Terminal::new(TestBackend::new(60, 10)).unwrap();
+        terminal.draw(|f| app.draw(ctx, f)).unwrap();
+        reset_buffer(&terminal)
+    };
+    // Leechers and downloads are hidden, and the batch isn't shown until focused
+    assert_snapshot("narrow_layout", &draw(&mut ctx));
+    ctx.mode = Mode::Batch;
+    assert_snapshot("narrow_layout_batch", &draw(&mut ctx));
+}
+
+#[test]
+fn test_category_filter() {
+    let mut ctx = Context::default();
+    ctx.mode = Mode::Category;
+    ctx.src_info = Sources::Nyaa.info(&ctx.config.sources);
+    let mut popup = CategoryPopup::default();
+    let key = |popup: &mut CategoryPopup, ctx: &mut Context, code: KeyCode| {
+        popup.handle_event(ctx, &Event::Key(KeyEvent::from(code)));
+    };
+    key(&mut popup, &mut ctx, KeyCode::Char('/'));
+    for c in "non".chars() {
+        key(&mut popup, &mut ctx, KeyCode::Char(c));
+    }
+    key(&mut popup, &mut ctx, KeyCode::Down);
+    let buf = render(&mut popup, &ctx, 40, 16);
+    assert_snapshot("category_filter", &buf);
+
+    // The filtered entry is selected, and the tab it was on is remembered
+    key(&mut popup, &mut ctx, KeyCode::Enter);
+    assert!(ctx.mode == Mode::Loading(LoadType::Categorizing));
+    assert_eq!(popup.selected, 32);
+    assert_eq!(popup.expanded(Sources::Nyaa), Some((3, 2)));
+    assert_eq!(popup.expanded(Sources::SukebeiNyaa), None);
+}
+
+#[test]
+fn test_empty_results() {
+    let mut ctx = Context::default();
+    ctx.mode = Mode::Normal;
+    ctx.searched = true;
+    let search = SearchQuery {
+        query: "nothing".to_owned(),
+        page: 1,
+        filter: 2,
+        ..Default::default()
+    };
+    let empty = |total_results: Option<usize>| {
+        let response = ResultResponse {
+            total_results,
+            last_page: 1,
+            ..Default::default()
+        };
+        Results::new(search.clone(), response, ResultTable::default())
+    };
+
+    let mut app = App::default();
+    let mut draw = |ctx: &mut Context| {
+        let mut terminal = Terminal::new(TestBackend::new(70, 12)).unwrap();
+        terminal.draw(|f| app.draw(ctx, f)).unwrap();
+        reset_buffer(&terminal)
+    };
+    // The site said nothing matched, so suggest widening the search
+    ctx.results = empty(Some(0));
+    assert_eq!(
+        ctx.results.response.empty_reason(),
+        Some(EmptyReason::NoResults)
+    );
+    assert_snapshot("empty_results", &draw(&mut ctx));
+
+    // The page didn't say, so the mirror probably changed
+    ctx.results = empty(None);
+    assert_eq!(
+        ctx.results.response.empty_reason(),
+        Some(EmptyReason::Unreadable)
+    );
+    assert_snapshot("unreadable_results", &draw(&mut ctx));
+}
+
+#[test]
+fn test_prefix_index() {
+    let mut index = PrefixIndex::default();
+    index.add("[SubsPlease] Sousou no Frieren - 01 (1080p) [F5B2E8A2].mkv");
+    index.add("[Erai-raws] Sousou no Frieren - 02 [1080p][Multiple Subtitle]");
+    index.add("frieren ost");
+
+    // Most seen first, written the way it was seen last
+    assert_eq!(index.complete("fri"), ["frieren"]);
+    assert_eq!(index.complete("su"), ["SubsPlease", "Subtitle"]);
+    assert_eq!(index.complete("erai"), ["Erai-raws"]);
+    assert_eq!(index.complete("10"), ["1080p"]);
+    // Short words aren't worth completing
+    assert!(index.complete("0").is_empty());
+    assert!(index.complete("no").is_empty());
+    assert!(index.complete("").is_empty());
+}
+
+#[test]
+fn test_search_completion() {
+    let mut ctx = Context::default();
+    ctx.mode = Mode::Search;
+    ctx.completions
+        .add("[SubsPlease] Sousou no Frieren - 01 (1080p)");
+    ctx.completions.add("[SubsPlus] Frieren - 02");
+    ctx.completions.add("Frieren");
+    let mut search = SearchWidget::default();
+    let key = |search: &mut SearchWidget, ctx: &mut Context, code: KeyCode| {
+        search.handle_event(ctx, &Event::Key(KeyEvent::from(code)));
+    };
+    for c in "sousou no fr".chars() {
+        key(&mut search, &mut ctx, KeyCode::Char(c));
+    }
+    // The rest of the word is hinted, and completed with Tab
+    let buf = render(&mut search, &ctx, 30, 3);
+    assert_snapshot("search_completion", &buf);
+    key(&mut search, &mut ctx, KeyCode::Tab);
+    assert_eq!(search.input.input, "sousou no Frieren");
+    assert_eq!(search.input.cursor, search.input.input.len());
+
+    // Tab again cycles through every word it could be
+    for c in " subs".chars() {
+        key(&mut search, &mut ctx, KeyCode::Char(c));
+    }
+    key(&mut search, &mut ctx, KeyCode::Tab);
+    assert_eq!(search.input.input, "sousou no Frieren SubsPlease");
+    key(&mut search, &mut ctx, KeyCode::Tab);
+    assert_eq!(search.input.input, "sousou no Frieren SubsPlus");
+    key(&mut search, &mut ctx, KeyCode::BackTab);
+    assert_eq!(search.input.input, "sousou no Frieren SubsPlease");
+}
+
+#[test]
+fn test_focus() {
+    let mut focus = Focus::default();
+    focus.drawn(Pane::Results, Rect::new(0, 0, 40, 10));
+    focus.drawn(Pane::Batch, Rect::new(40, 0, 20, 10));
+    assert_eq!(focus.pane_at(45, 3), Some(Pane::Batch));
+    assert_eq!(focus.pane_at(39, 9), Some(Pane::Results));
+    assert_eq!(focus.pane_at(60, 3), None);
+
+    // Details isn't shown, so it's skipped
+    assert_eq!(focus.cycle(true), Pane::Batch);
+    assert_eq!(focus.cycle(false), Pane::Batch);
+    focus.set(Pane::Batch);
+    assert_eq!(focus.cycle(true), Pane::Results);
+
+    focus.clear();
+    focus.drawn(Pane::Results, Rect::new(0, 0, 30, 10));
+    focus.drawn(Pane::Details, Rect::new(30, 0, 15, 10));
+    focus.hidden(Pane::Batch);
+    assert_eq!(focus.cycle(true), Pane::Results);
+    assert_eq!(focus.cycle(false), Pane::Details);
+    focus.set(Pane::Results);
+    assert_eq!(focus.cycle(true), Pane::Details);
+    // Hidden panes can be cycled to, but not clicked
+    assert_eq!(focus.pane_at(0, 0), Some(Pane::Results));
+    assert!(focus.is_available(Pane::Batch));
+}
+
+fn numbered(search: &str, count: usize) -> Results {
+    let items: Vec<Item> = (0..count)
+        .map(|num| Item {
+            id: ItemId::new("nyaa", num),
+            title: format!("{} {}", search, num),
+            ..Default::default()
+        })
+        .collect();
+    let table = ResultTable {
+        rows: items
+            .iter()
+            .map(|i| ResultRow::new([i.title.clone(), i.id.num.to_string()]))
+            .collect(),
+        binding: vec![Constraint::Min(1), Constraint::Length(5)],
+        narrow: vec![true, false],
+        ..Default::default()
+    };
+    let search = SearchQuery {
+        query: search.to_owned(),
+        ..Default::default()
+    };
+    let response = ResultResponse {
+        items,
+        total_results: Some(count),
+        last_page: 1,
+        ..Default::default()
+    };
+    Results::new(search, response, table)
+}
+
+#[test]
+fn test_results_window() {
+    let results = numbered("show", 5000);
+    let window = results.table.window(4990..5000, true);
+    assert_eq!(window.rows.len(), 10);
+    assert_eq!(window.rows[0].cells.len(), 1);
+    assert_eq!(window.rows[0].cells[0].content, "show 4990");
+    assert_eq!(window.binding.len(), 1);
+    assert_eq!(results.table.window(4990..6000, false).rows.len(), 0);
+
+    // Only the rows in view are drawn, scrolled to keep the selected one shown
+    let mut ctx = Context::default();
+    ctx.mode = Mode::Normal;
+    ctx.searched = true;
+    ctx.results = results;
+    let mut widget = ResultsWidget::default();
+    widget.table.select(4000);
+    let buf = render(&mut widget, &ctx, 80, 8);
+    assert_eq!(widget.table.state.offset(), 3996);
+    let lines = buffer_lines(&buf);
+    assert!(lines[2].contains("show 3996"));
+    assert!(lines[6].contains("show 4000"));
+    assert!(lines[0].contains("Results 1-5000"));
+}
+
+#[test]
+fn test_history_eviction() {
+    let mut history = History::default();
+    for search in ["one", "two", "three"] {
+        history.visit(history.current(numbered(search, 10), 3));
+    }
+    assert_eq!(history.kept_items(), 30);
+    assert_eq!(history.evict(50, 10), 0);
+
+    // The views left longest ago are dropped first, keeping their search
+    assert_eq!(history.evict(25, 10), 2);
+    assert_eq!(history.kept_items(), 10);
+    let current = history.current(numbered("four", 10), 0);
+    let three = history.back(current).unwrap();
+    assert!(!three.evicted);
+    assert_eq!(three.kept_items(), 10);
+    let two = history.back(three).unwrap();
+    assert!(two.evicted);
+    assert_eq!(two.kept_items(), 0);
+    assert_eq!(two.results.search.query, "two");
+    assert_eq!(two.selected, 3);
+
+    // Four was left before three was, so it goes first
+    assert_eq!(history.evict(10, 0), 1);
+    let three = history.forward(two).unwrap();
+    assert!(!three.evicted);
+    let four = history.forward(three).unwrap();
+    assert!(four.evicted);
+    assert_eq!(four.results.search.query, "four");
+}
+
+#[test]
+fn test_chords() {
+    let user = [
+        ("<leader>m".to_owned(), ChordAction::CopyMagnet),
+        ("<leader>rs".to_owned(), ChordAction::SameShow),
+        ("yt".to_owned(), ChordAction::CopyPost),
+    ]
+    .into();
+    let chords = Chords::new(Some(' '), &user).unwrap();
+    assert!(chords.starts(' '));
+    assert!(chords.starts('y'));
+    assert!(!chords.starts('x'));
+    assert_eq!(chords.get(" m"), Some(ChordAction::CopyMagnet));
+    // Chords in the config replace the default with the same keys
+    assert_eq!(chords.get("yt"), Some(ChordAction::CopyPost));
+    assert_eq!(chords.get(" r"), None);
+    assert_eq!(
+        chords.continuations(" "),
+        Some(vec![
+            ("m".to_owned(), "Copy magnet link"),
+            ("rs".to_owned(), "Same show, other groups"),
+        ])
+    );
+    assert_eq!(chords.continuations(" m"), None);
+    assert_eq!(chords.display(" rs"), "<leader>rs");
+
+    let fails = |keys: &str| {
+        let user = [(keys.to_owned(), ChordAction::Reload)].into();
+        Chords::new(None, &user).unwrap_err()
+    };
+    assert!(fails("<leader>r").contains("no leader is set"));
+    assert!(fails("g").contains("at least two keys"));
+    assert!(fails("5r").contains("starts a count or macro"));
+    assert!(fails("ytt").contains("\"yt\" is finished first"));
+
+    // The keys after the leader are listed once it's pressed
+    let mut ctx = Context::default();
+    ctx.chords = chords;
+    ctx.mode = Mode::KeyCombo(" ".to_owned());
+    assert_snapshot("leader_chords", &render(&mut ComboPopup, &ctx, 40, 8));
+}